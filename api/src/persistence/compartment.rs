@@ -19,6 +19,11 @@ pub struct Compartment {
     /// All mappings in this compartment.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mappings: Option<Vec<Mapping>>,
+    /// All mapping templates in this compartment.
+    ///
+    /// Each template is materialized into a number of mappings when the compartment is loaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping_templates: Option<Vec<MappingTemplate>>,
     /// Lua code that will be compiled only once and can then be reused in various Lua scripts within mappings.
     ///
     /// This code should return a value. This value will then be made available to the scripts. How exactly, depends
@@ -36,6 +41,20 @@ pub struct Compartment {
     /// Can contain text notes, e.g. a helpful description of this compartment, instructions etc.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
+    /// Number of tracks, FXs or parameters covered by one bank.
+    ///
+    /// Bank-relative target descriptors add `current bank index * bank size` to their index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_size: Option<u32>,
+    /// The banks in this compartment, in order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banks: Option<Vec<Bank>>,
     #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub unknown_props: Option<BTreeMap<String, serde_json::Value>>,
 }
+
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Bank {
+    /// Display name of the bank, e.g. "Tracks 1-8".
+    pub name: String,
+}
@@ -89,6 +89,12 @@ pub struct MidiControllerConnection {
     /// Can be used by ReaLearn to verify whether the device connected to a port is the correct one.   
     #[serde(skip_serializing_if = "Option::is_none")]
     pub identity_response: Option<String>,
+    /// The structured identity reported by the device, either via MIDI-CI discovery or via the
+    /// classic MIDI device inquiry.
+    ///
+    /// Can be used by ReaLearn to find controller presets that match the device.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_identity: Option<MidiDeviceIdentity>,
     /// The MIDI input port to which this controller is usually connected on this machine.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub input_port: Option<MidiInputPort>,
@@ -97,6 +103,30 @@ pub struct MidiControllerConnection {
     pub output_port: Option<MidiOutputPort>,
 }
 
+/// Identity of a MIDI device.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct MidiDeviceIdentity {
+    /// SysEx manufacturer ID as hex.
+    ///
+    /// Example: "47" (Akai) or "00 20 29" (Novation)
+    pub manufacturer_id: String,
+    /// Device family code.
+    pub family: u16,
+    /// Device model number.
+    pub model: u16,
+    /// Software revision level as hex.
+    pub version: String,
+    /// IDs of the MIDI-CI profiles supported by the device (enabled or not), as hex.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<String>,
+    /// Manufacturer name as reported via MIDI-CI property exchange.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer_name: Option<String>,
+    /// Model name as reported via MIDI-CI property exchange.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+}
+
 /// A connection via OSC.
 #[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct OscControllerConnection {
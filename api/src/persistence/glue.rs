@@ -61,6 +61,8 @@ pub struct Glue {
 pub enum FeedbackValueTable {
     FromTextToDiscrete(DiscreteFeedbackValueTableContent),
     FromTextToContinuous(ContinuousFeedbackValueTableContent),
+    FromNumericRangeToContinuous(NumericRangeToContinuousFeedbackValueTableContent),
+    FromNumericRangeToColor(NumericRangeToColorFeedbackValueTableContent),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
@@ -73,6 +75,51 @@ pub struct ContinuousFeedbackValueTableContent {
     pub value: HashMap<String, f64>,
 }
 
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct NumericRangeToContinuousFeedbackValueTableContent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<FeedbackValueTableInput>,
+    /// Entries in ascending order. Values are between 0.0 and 1.0.
+    pub value: Vec<FeedbackValueTableRange<f64>>,
+    /// If `true`, the value fades from the value of the matching entry to the value of the next
+    /// entry as the input moves through the range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpolate: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct NumericRangeToColorFeedbackValueTableContent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<FeedbackValueTableInput>,
+    /// Entries in ascending order. The resulting color is used as feedback color.
+    pub value: Vec<FeedbackValueTableRange<VirtualColor>>,
+    /// If `true`, the color fades from the color of the matching entry to the color of the next
+    /// entry as the input moves through the range (RGB colors only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpolate: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FeedbackValueTableRange<T> {
+    /// Inclusive lower bound. If not given, the range is open towards negative infinity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<f64>,
+    /// Exclusive upper bound. If not given, the range is open towards positive infinity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<f64>,
+    pub value: T,
+}
+
+/// The number which is looked up in a numeric range table.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum FeedbackValueTableInput {
+    /// The normalized feedback value (0.0 to 1.0), after being processed by the glue.
+    #[default]
+    FeedbackValue,
+    /// The current value of the target in its native unit, e.g. dB for volume and peak targets.
+    TargetNumericValue,
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
 pub enum AbsoluteMode {
     #[default]
@@ -136,17 +183,17 @@ pub struct OnSinglePressFireMode {
     pub max_duration: Option<u32>,
 }
 
-#[derive(Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum VirtualColor {
     Rgb(RgbColor),
     Prop(PropColor),
 }
 
-#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct RgbColor(pub u8, pub u8, pub u8);
 
-#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct PropColor {
     pub prop: String,
 }
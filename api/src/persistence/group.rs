@@ -1,7 +1,7 @@
 use crate::persistence::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Default, PartialEq, Serialize, Deserialize)]
 pub struct Group {
     /// An optional ID that you can assign to this group in order to refer
     /// to it from somewhere else.
@@ -11,6 +11,12 @@ pub struct Group {
     pub id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// ID of the parent group.
+    ///
+    /// A group inherits tags, control/feedback enablement, the activation condition and the
+    /// defaults of its parent group. Tags are accumulated, all other settings can be overridden.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -19,4 +25,30 @@ pub struct Group {
     pub feedback_enabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub activation_condition: Option<ActivationCondition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<GroupDefaults>,
+}
+
+/// Settings which are applied to all mappings in a group (including descendant groups) that
+/// leave the corresponding setting at its default value.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroupDefaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub takeover_mode: Option<TakeoverMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_size_interval: Option<Interval<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_factor_interval: Option<Interval<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_color: Option<VirtualColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_background_color: Option<VirtualColor>,
+    /// MIDI channel of MIDI sources which are set to "any channel".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_channel: Option<u8>,
+    /// Track of targets which are set to track `<This>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_track: Option<TrackDescriptor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_policy: Option<FeedbackPolicy>,
 }
@@ -40,13 +40,32 @@ pub struct Mapping {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub success_audio_feedback: Option<SuccessAudioFeedback>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_policy: Option<FeedbackPolicy>,
+    /// Makes the mapping react to this control input of the unit only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_input: Option<MappingControlInput>,
+    /// Makes the mapping send feedback to this feedback output of the unit only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_output: Option<MappingFeedbackOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub unprocessed: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
-#[derive(Eq, PartialEq, Default, Serialize, Deserialize)]
+#[derive(PartialEq, Default, Serialize, Deserialize)]
 pub struct LifecycleHook {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub send_midi_feedback: Option<Vec<SendMidiFeedbackAction>>,
+    /// OSC messages sent to the feedback output (only if it's an OSC device).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_osc_feedback: Option<Vec<SendOscFeedbackAction>>,
+    /// Normalized value (0.0 to 1.0) which the mapping target should be set to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_target_value: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_parameters: Option<Vec<SetParameterAction>>,
+    /// Lua code returning a list of additional actions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lua: Option<String>,
 }
 
 #[derive(Eq, PartialEq, Serialize, Deserialize)]
@@ -55,6 +74,29 @@ pub enum SendMidiFeedbackAction {
     Raw { message: RawMidiMessage },
 }
 
+#[derive(PartialEq, Serialize, Deserialize)]
+pub struct SendOscFeedbackAction {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Vec<OscArgValue>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OscArgValue {
+    Bool(bool),
+    Int(i32),
+    Float(f64),
+    String(String),
+}
+
+#[derive(PartialEq, Serialize, Deserialize)]
+pub struct SetParameterAction {
+    pub parameter: ParamRef,
+    /// Normalized value (0.0 to 1.0).
+    pub value: f64,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RawMidiMessage {
@@ -68,6 +110,42 @@ pub enum SuccessAudioFeedback {
     Simple,
 }
 
+/// Controls when target value changes are sent as feedback.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct FeedbackPolicy {
+    /// Maximum number of feedback messages per second.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rate: Option<f64>,
+    /// Minimum difference (0.0 to 1.0) between the last sent and the new target value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_delta: Option<f64>,
+    /// Time in milliseconds the target value must stay unchanged before feedback is sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debounce: Option<u64>,
+    /// Time in milliseconds after the last control during which feedback is held back.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settle_delay: Option<u64>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum MappingControlInput {
+    FxInput,
+    MidiDevice { id: u8 },
+    OscDevice { id: String },
+    Keyboard,
+    WebSocket,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum MappingFeedbackOutput {
+    FxOutput,
+    MidiDevice { id: u8 },
+    OscDevice { id: String },
+    WebSocket,
+}
+
 #[derive(Eq, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "kind")]
 pub enum ActivationCondition {
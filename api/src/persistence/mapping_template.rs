@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// A mapping that is instantiated once for each value of a variable.
+///
+/// Instances are created whenever the compartment is loaded. They are not saved themselves, only
+/// the template is.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct MappingTemplate {
+    /// ID of this template.
+    ///
+    /// Must be unique within all templates in the compartment. Each instance gets the mapping ID
+    /// `<template ID>:<value>`, e.g. `fader:3`.
+    pub id: String,
+    /// Name of the variable without the leading `$`.
+    ///
+    /// Defaults to `i`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    /// First value of the variable.
+    ///
+    /// Defaults to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<i32>,
+    /// Last value of the variable (inclusive).
+    pub to: i32,
+    /// The mapping to be instantiated, in the same structure as a normal mapping.
+    ///
+    /// Each text in it can refer to the variable either directly (`$i`) or within an expression
+    /// (`${i + 8}`). The result is always a text. A text of the form `#{...}` (e.g. `#{i - 1}`) is
+    /// turned into a number, which makes it possible to parameterize numeric properties such as
+    /// channels, source numbers or track indexes.
+    pub mapping: serde_json::Value,
+}
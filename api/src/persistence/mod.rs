@@ -4,6 +4,7 @@ mod glue;
 mod group;
 mod instance;
 mod mapping;
+mod mapping_template;
 mod parameter;
 mod preset;
 mod root;
@@ -17,6 +18,7 @@ pub use glue::*;
 pub use group::*;
 pub use instance::*;
 pub use mapping::*;
+pub use mapping_template::*;
 pub use parameter::*;
 pub use preset::*;
 pub use root::*;
@@ -78,6 +78,17 @@ pub struct ControllerPresetMetaData {
         skip_serializing_if = "Option::is_none"
     )]
     pub midi_identity_pattern: Option<String>,
+    /// Structured MIDI device identity compatibility criteria.
+    ///
+    /// Matched against the identity that the device reports via MIDI-CI discovery or via the
+    /// classic MIDI device inquiry. Less brittle than [`Self::midi_identity_pattern`] and
+    /// preferred over it if the device identity is known.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub midi_device_identity: Option<MidiDeviceIdentityPattern>,
     /// Possible MIDI identity compatibility patterns.
     ///
     /// Will be used for auto-adding controllers and for finding the correct controller preset when calculating auto
@@ -102,6 +113,34 @@ pub struct ControllerPresetMetaData {
     pub provided_schemes: Vec<VirtualControlSchemeId>,
 }
 
+/// Criteria for matching a MIDI device identity.
+///
+/// Criteria which are not given match any value.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct MidiDeviceIdentityPattern {
+    /// SysEx manufacturer ID as hex.
+    ///
+    /// Example: "47" (Akai) or "00 20 29" (Novation)
+    pub manufacturer_id: String,
+    /// Device family code.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub family: Option<u16>,
+    /// Device model number.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub model: Option<u16>,
+    /// IDs of MIDI-CI profiles (as hex) which the device must support.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<String>,
+}
+
 /// Metadata that is specific to main presets.
 #[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct MainPresetMetaData {
@@ -2,6 +2,7 @@ use crate::persistence::{OscArgument, VirtualControlElementCharacter, VirtualCon
 use derive_more::Display;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use strum::EnumIter;
 
 #[derive(PartialEq, Default, Serialize, Deserialize)]
@@ -17,6 +18,8 @@ pub enum Source {
     Timer(TimerSource),
     RealearnParameter(RealearnParameterSource),
     Speech,
+    Lfo(LfoSource),
+    Sequencer(SequencerSource),
     // MIDI
     MidiNoteVelocity(MidiNoteVelocitySource),
     MidiNoteKeyNumber(MidiNoteKeyNumberSource),
@@ -37,12 +40,17 @@ pub enum Source {
     SlKeyboardDisplay(SlKeyboardDisplaySource),
     SiniConE24Display(SiniConE24DisplaySource),
     LaunchpadProScrollingTextDisplay,
+    SysExDisplay(SysExDisplaySource),
     // OSC
     Osc(OscSource),
     // Keyboard
     Key(KeySource),
     // Virtual
     Virtual(VirtualSource),
+    // Compound
+    Compound(CompoundSource),
+    // Audio
+    Audio(AudioSource),
 }
 
 // Only makes sense for sources that support both control *and* feedback.
@@ -278,6 +286,85 @@ pub struct SiniConE24DisplaySource {
     pub item_index: Option<u8>,
 }
 
+/// Generic text display which is driven by a user-defined SysEx template.
+#[derive(Default, PartialEq, Serialize, Deserialize)]
+pub struct SysExDisplaySource {
+    /// Space-separated hex bytes and placeholders, e.g. `F0 47 7F 43 {line} {text} F7`.
+    ///
+    /// Supported placeholders: `{line}`, `{text}`, `{len}` (number of text bytes),
+    /// `{checksum_start}` (marks the first byte covered by the checksum, defaults to the byte
+    /// after `F0`) and `{checksum}` (Roland-style checksum).
+    pub template: String,
+    /// Line or segment index, inserted at `{line}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u8>,
+    /// If set, the text is padded or truncated to exactly this number of characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<SysExDisplayAlignment>,
+    /// Character used for padding (default is space).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding: Option<char>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<SysExDisplayEncoding>,
+    /// Custom character-to-byte mappings which take precedence over the encoding.
+    ///
+    /// Bytes must be SysEx data bytes (0 - 127).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character_map: Option<BTreeMap<char, u8>>,
+}
+
+/// Alignment of text on a SysEx display
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum SysExDisplayAlignment {
+    #[default]
+    #[display(fmt = "Left")]
+    Left,
+    #[display(fmt = "Center")]
+    Center,
+    #[display(fmt = "Right")]
+    Right,
+}
+
+/// Character encoding of a SysEx display
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum SysExDisplayEncoding {
+    #[default]
+    #[display(fmt = "ASCII")]
+    Ascii,
+    #[display(fmt = "7-segment")]
+    SevenSegment,
+}
+
 #[derive(Default, PartialEq, Serialize, Deserialize)]
 pub struct OscSource {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -302,6 +389,110 @@ pub struct TimerSource {
     pub duration: u64,
 }
 
+#[derive(Default, PartialEq, Serialize, Deserialize)]
+pub struct LfoSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waveform: Option<LfoWaveform>,
+    /// Cycles per second. Ignored if `beats_per_cycle` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<f64>,
+    /// If set, the LFO is synchronized to the project timeline and completes one cycle every
+    /// this many beats.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beats_per_cycle: Option<f64>,
+    /// Phase offset in cycles (between 0.0 and 1.0).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase_offset: Option<f64>,
+    /// Compartment parameter which restarts the cycle whenever it's set to a non-zero value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrigger_parameter_index: Option<u32>,
+}
+
+/// Waveform of an LFO
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum LfoWaveform {
+    #[default]
+    #[display(fmt = "Sine")]
+    Sine,
+    #[display(fmt = "Triangle")]
+    Triangle,
+    #[display(fmt = "Saw")]
+    Saw,
+    #[display(fmt = "Square")]
+    Square,
+    #[display(fmt = "Random (sample & hold)")]
+    SampleAndHold,
+}
+
+#[derive(Default, PartialEq, Serialize, Deserialize)]
+pub struct SequencerSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<SequencerStep>>,
+    /// Length of one step in beats (e.g. 0.25 for sixteenth notes in 4/4).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beats_per_step: Option<f64>,
+    /// Amount by which every second step is delayed (between 0.0 and 1.0).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swing: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SequencerDirection>,
+    /// If set, the value of step n is taken from compartment parameter
+    /// `step_value_parameter_index + n` instead of from the step itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_value_parameter_index: Option<u32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SequencerStep {
+    /// Value between 0.0 and 1.0.
+    pub value: f64,
+    /// If `false`, the step is skipped and no value is emitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gate: Option<bool>,
+}
+
+/// Order in which sequencer steps are played
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum SequencerDirection {
+    #[default]
+    #[display(fmt = "Forward")]
+    Forward,
+    #[display(fmt = "Backward")]
+    Backward,
+    #[display(fmt = "Ping-pong")]
+    PingPong,
+    #[display(fmt = "Random")]
+    Random,
+}
+
 #[derive(Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct KeySource {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -320,3 +511,131 @@ pub struct VirtualSource {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub character: Option<VirtualControlElementCharacter>,
 }
+
+#[derive(Default, PartialEq, Serialize, Deserialize)]
+pub struct CompoundSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compound_kind: Option<CompoundSourceKind>,
+    /// Member sources. Must be either all MIDI sources or all virtual sources.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<Source>,
+    /// Combination: Maximum time in milliseconds between the first and the last member press.
+    ///
+    /// Sequence: Maximum time in milliseconds between two consecutive member presses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// How the members of a compound source need to be pressed
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum CompoundSourceKind {
+    /// All members held at the same time (also useful for note chords).
+    #[default]
+    #[display(fmt = "Combination")]
+    Combination,
+    /// Members pressed one after another.
+    #[display(fmt = "Sequence")]
+    Sequence,
+}
+
+/// Follows the audio input of the ReaLearn FX.
+#[derive(Default, PartialEq, Serialize, Deserialize)]
+pub struct AudioSource {
+    /// Index of the first analyzed input channel of the ReaLearn FX.
+    ///
+    /// Default: 0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u32>,
+    /// Number of analyzed input channels (starting at `channel`).
+    ///
+    /// Default: 2
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detector: Option<AudioLevelDetector>,
+    /// Envelope attack time in milliseconds.
+    ///
+    /// Default: 10
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attack: Option<u64>,
+    /// Envelope release time in milliseconds.
+    ///
+    /// Default: 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release: Option<u64>,
+    /// Level in dB.
+    ///
+    /// For level analysis, setting this turns the source into a gate which emits on/off values.
+    /// For onset and pitch analysis, this is the minimum level that needs to be reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analysis: Option<AudioAnalysis>,
+}
+
+/// How the level of the audio input is measured
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum AudioLevelDetector {
+    #[display(fmt = "Peak")]
+    Peak,
+    #[default]
+    #[display(fmt = "RMS")]
+    Rms,
+}
+
+/// What an audio source emits
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    TryFromPrimitive,
+    IntoPrimitive,
+    Display,
+)]
+#[repr(usize)]
+pub enum AudioAnalysis {
+    /// The level of the audio input (or on/off if a threshold is set).
+    #[default]
+    #[display(fmt = "Level")]
+    Level,
+    /// A short button press whenever a transient is detected.
+    #[display(fmt = "Onset")]
+    Onset,
+    /// The detected pitch as MIDI note number.
+    #[display(fmt = "Pitch")]
+    Pitch,
+}
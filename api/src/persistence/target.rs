@@ -166,6 +166,7 @@ pub enum Target {
     TakeMappingSnapshot(TakeMappingSnapshotTarget),
     #[serde(alias = "CycleThroughGroupMappings")]
     BrowseGroupMappings(BrowseGroupMappingsTarget),
+    SelectBank(SelectBankTarget),
     BrowsePotFilterItems(BrowsePotFilterItemsTarget),
     #[serde(alias = "NavigateWithinPotPresets")]
     BrowsePotPresets(BrowsePotPresetsTarget),
@@ -1132,6 +1133,12 @@ pub struct BrowseGroupMappingsTarget {
     pub group: Option<String>,
 }
 
+#[derive(Eq, PartialEq, Default, Serialize, Deserialize)]
+pub struct SelectBankTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+}
+
 #[derive(Eq, PartialEq, Default, Serialize, Deserialize)]
 pub struct BrowsePotFilterItemsTarget {
     #[serde(flatten)]
@@ -1631,6 +1638,9 @@ pub enum TrackDescriptor {
         index: u32,
         #[serde(skip_serializing_if = "Option::is_none")]
         scope: Option<TrackScope>,
+        /// If `true`, the offset of the current bank is added to the index.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bank_relative: Option<bool>,
     },
     ByName {
         #[serde(flatten)]
@@ -1813,6 +1823,9 @@ pub enum FxDescriptor {
         commons: FxDescriptorCommons,
         chain: FxChainDescriptor,
         index: u32,
+        /// If `true`, the offset of the current bank is added to the index.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bank_relative: Option<bool>,
     },
     ByName {
         #[serde(flatten)]
@@ -1909,6 +1922,9 @@ pub enum FxParameterDescriptor {
         #[serde(skip_serializing_if = "Option::is_none")]
         fx: Option<FxDescriptor>,
         index: u32,
+        /// If `true`, the offset of the current bank is added to the index.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bank_relative: Option<bool>,
     },
     ByName {
         #[serde(skip_serializing_if = "Option::is_none")]
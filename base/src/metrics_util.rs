@@ -1,11 +1,25 @@
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::sync::mpsc::{Receiver, SyncSender};
-use std::sync::OnceLock;
+use std::sync::{OnceLock, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
 /// This will contain the metrics sender for async metrics recording if metrics are enabled.
 static METRICS_SENDER: OnceLock<SyncSender<MetricsRecorderCommand>> = OnceLock::new();
 
+/// Maps unit IDs to the labels which are attached to per-unit metrics.
+static UNIT_LABELS: OnceLock<RwLock<HashMap<u32, UnitLabels>>> = OnceLock::new();
+
+thread_local! {
+    /// The unit which is currently being processed on this thread (if known).
+    ///
+    /// Used to attribute measurements deep down in the call stack (e.g. script execution) to a
+    /// unit without having to thread the unit ID through all the layers. Const-initialized, so
+    /// it's safe to access from real-time threads.
+    static CURRENT_UNIT: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
 #[derive(Debug)]
 pub struct MetricsHook {
     sender: SyncSender<MetricsRecorderCommand>,
@@ -114,9 +128,129 @@ pub fn metrics_are_enabled() -> bool {
     METRICS_SENDER.get().is_some()
 }
 
+/// Sets the instance ID and unit key which per-unit metrics of the given unit should carry as
+/// `instance` and `unit_key` label.
+pub fn set_unit_labels(unit_id: u32, instance_id: u32, unit_key: String) {
+    if !metrics_are_enabled() {
+        return;
+    }
+    let labels = UnitLabels {
+        instance_id,
+        unit_key,
+    };
+    unit_labels().write().unwrap().insert(unit_id, labels);
+}
+
+/// Forgets the labels of the given unit, e.g. when the unit is removed.
+pub fn remove_unit_labels(unit_id: u32) {
+    if !metrics_are_enabled() {
+        return;
+    }
+    unit_labels().write().unwrap().remove(&unit_id);
+}
+
+/// Executes the given function with the given unit marked as currently processed unit on this
+/// thread.
+pub fn within_unit<R>(unit_id: u32, f: impl FnOnce() -> R) -> R {
+    let previous_unit = CURRENT_UNIT.with(|u| u.replace(Some(unit_id)));
+    let result = f();
+    CURRENT_UNIT.with(|u| u.set(previous_unit));
+    result
+}
+
+/// Synchronously records the occurrence of the given event for the given unit.
+///
+/// Additional labels can be provided. Allocates, so don't call this from real-time threads.
+pub fn record_unit_occurrence(id: &'static str, unit_id: u32, labels: &[(&'static str, &str)]) {
+    if !metrics_are_enabled() {
+        return;
+    }
+    let mut all_labels = create_unit_labels(unit_id);
+    all_labels.extend(
+        labels
+            .iter()
+            .map(|(key, value)| metrics::Label::new(*key, value.to_string())),
+    );
+    metrics::counter!(id, all_labels).increment(1);
+}
+
+/// Asynchronously measures and records the time of the given operation for the given unit.
+///
+/// While the operation runs, the given unit is marked as currently processed unit on this thread.
+/// Doesn't allocate, so it's okay to call this from real-time threads.
+pub fn measure_unit_time<R>(id: &'static str, unit_id: u32, f: impl FnOnce() -> R) -> R {
+    if !metrics_are_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = within_unit(unit_id, f);
+    record_unit_duration(id, unit_id, start.elapsed());
+    result
+}
+
+/// Like [`measure_time`] but attributes the measurement to the unit which is currently processed
+/// on this thread (see [`within_unit`]).
+pub fn measure_time_in_current_unit<R>(id: &'static str, f: impl FnOnce() -> R) -> R {
+    if !metrics_are_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    let delta = start.elapsed();
+    match CURRENT_UNIT.with(|u| u.get()) {
+        None => record_duration(id, delta),
+        Some(unit_id) => record_unit_duration(id, unit_id, delta),
+    }
+    result
+}
+
+/// Records the given duration of the given unit into a histogram.
+pub fn record_unit_duration(id: &'static str, unit_id: u32, delta: Duration) {
+    if let Some(sender) = METRICS_SENDER.get() {
+        let task = MetricsRecorderCommand::UnitHistogram { id, unit_id, delta };
+        if sender.try_send(task).is_err() {
+            tracing::debug!("Helgobox metrics channel is full");
+        }
+    }
+}
+
+struct UnitLabels {
+    instance_id: u32,
+    unit_key: String,
+}
+
+fn unit_labels() -> &'static RwLock<HashMap<u32, UnitLabels>> {
+    UNIT_LABELS.get_or_init(Default::default)
+}
+
+/// The `unit` label always contains the unit ID. The `instance` and `unit_key` labels are only
+/// added if they have been set for the unit (yet).
+fn create_unit_labels(unit_id: u32) -> Vec<metrics::Label> {
+    let mut labels = vec![metrics::Label::new("unit", unit_id.to_string())];
+    if let Some(unit_labels) = unit_labels().read().unwrap().get(&unit_id) {
+        labels.push(metrics::Label::new(
+            "instance",
+            unit_labels.instance_id.to_string(),
+        ));
+        labels.push(metrics::Label::new(
+            "unit_key",
+            unit_labels.unit_key.clone(),
+        ));
+    }
+    labels
+}
+
 enum MetricsRecorderCommand {
     Finish,
-    Histogram { id: &'static str, delta: Duration },
+    Histogram {
+        id: &'static str,
+        delta: Duration,
+    },
+    UnitHistogram {
+        id: &'static str,
+        unit_id: u32,
+        delta: Duration,
+    },
 }
 
 fn keep_recording_metrics(receiver: Receiver<MetricsRecorderCommand>) {
@@ -126,6 +260,9 @@ fn keep_recording_metrics(receiver: Receiver<MetricsRecorderCommand>) {
             MetricsRecorderCommand::Histogram { id, delta } => {
                 metrics::histogram!(id).record(delta);
             }
+            MetricsRecorderCommand::UnitHistogram { id, unit_id, delta } => {
+                metrics::histogram!(id, create_unit_labels(unit_id)).record(delta);
+            }
         }
     }
 }
@@ -1,18 +1,22 @@
 use crate::application::{
     Affected, GroupModel, GroupProp, MappingCommand, MappingModel, MappingProp,
 };
-use crate::domain::{CompartmentParamIndex, GroupId, MappingId, ParamSetting};
+use crate::domain::{CompartmentBanks, CompartmentParamIndex, GroupId, MappingId, ParamSetting};
 use base::hash_util::NonCryptoHashMap;
+use helgobox_api::persistence::MappingTemplate;
 
 #[derive(Clone, Debug)]
 pub struct CompartmentModel {
     pub parameters: Vec<(CompartmentParamIndex, ParamSetting)>,
     pub default_group: GroupModel,
     pub groups: Vec<GroupModel>,
+    /// Contains the materialized instances of the mapping templates as well.
     pub mappings: Vec<MappingModel>,
+    pub mapping_templates: Vec<MappingTemplate>,
     pub common_lua: String,
     pub custom_data: NonCryptoHashMap<String, serde_json::Value>,
     pub notes: String,
+    pub banks: CompartmentBanks,
 }
 
 pub enum CompartmentCommand {
@@ -1,33 +1,40 @@
 use crate::application::{
     ActivationConditionCommand, ActivationConditionModel, ActivationConditionProp, Affected,
-    Change, GetProcessingRelevance, GroupData, ProcessingRelevance,
+    Change, GetProcessingRelevance, GroupData, ProcessingRelevance, TrackPropValues,
 };
-use crate::domain::{CompartmentKind, GroupId, GroupKey, Tag};
+use crate::domain::{ActivationCondition, CompartmentKind, FeedbackPolicy, GroupId, GroupKey, Tag};
 use core::fmt;
+use helgoboss_learn::{DiscreteIncrement, Interval, TakeoverMode, UnitValue, VirtualColor};
+use helgoboss_midi::Channel;
 use std::cell::RefCell;
+use std::ops::Deref;
 use std::rc::{Rc, Weak};
 
 pub enum GroupCommand {
     SetName(String),
+    SetParentId(Option<GroupId>),
     SetTags(Vec<Tag>),
     SetControlIsEnabled(bool),
     SetFeedbackIsEnabled(bool),
     ChangeActivationCondition(ActivationConditionCommand),
+    SetDefaults(GroupDefaults),
 }
 
 pub enum GroupProp {
     Name,
+    ParentId,
     Tags,
     ControlIsEnabled,
     FeedbackIsEnabled,
     InActivationCondition(Affected<ActivationConditionProp>),
+    Defaults,
 }
 
 impl GetProcessingRelevance for GroupProp {
     fn processing_relevance(&self) -> Option<ProcessingRelevance> {
         use GroupProp as P;
         match self {
-            P::Tags | P::ControlIsEnabled | P::FeedbackIsEnabled => {
+            P::ParentId | P::Tags | P::ControlIsEnabled | P::FeedbackIsEnabled | P::Defaults => {
                 Some(ProcessingRelevance::ProcessingRelevant)
             }
             P::InActivationCondition(p) => p.processing_relevance(),
@@ -43,10 +50,12 @@ pub struct GroupModel {
     id: GroupId,
     key: GroupKey,
     name: String,
+    parent_id: Option<GroupId>,
     tags: Vec<Tag>,
     control_is_enabled: bool,
     feedback_is_enabled: bool,
     pub activation_condition_model: ActivationConditionModel,
+    defaults: GroupDefaults,
 }
 
 impl<'a> Change<'a> for GroupModel {
@@ -62,6 +71,10 @@ impl<'a> Change<'a> for GroupModel {
                 self.name = v;
                 One(P::Name)
             }
+            C::SetParentId(v) => {
+                self.parent_id = v;
+                One(P::ParentId)
+            }
             C::SetTags(v) => {
                 self.tags = v;
                 One(P::Tags)
@@ -80,6 +93,10 @@ impl<'a> Change<'a> for GroupModel {
                     .change(cmd)
                     .map(|affected| One(P::InActivationCondition(affected)));
             }
+            C::SetDefaults(v) => {
+                self.defaults = v;
+                One(P::Defaults)
+            }
         };
         Some(affected)
     }
@@ -98,6 +115,10 @@ impl GroupModel {
         &self.name
     }
 
+    pub fn parent_id(&self) -> Option<GroupId> {
+        self.parent_id
+    }
+
     pub fn tags(&self) -> &[Tag] {
         &self.tags
     }
@@ -113,6 +134,10 @@ impl GroupModel {
     pub fn activation_condition_model(&self) -> &ActivationConditionModel {
         &self.activation_condition_model
     }
+
+    pub fn defaults(&self) -> &GroupDefaults {
+        &self.defaults
+    }
 }
 
 impl fmt::Display for GroupModel {
@@ -150,10 +175,12 @@ impl GroupModel {
             id: GroupId::default(),
             key: GroupKey::default(),
             name: Default::default(),
+            parent_id: None,
             tags: Default::default(),
             control_is_enabled: true,
             feedback_is_enabled: true,
             activation_condition_model: ActivationConditionModel::default(),
+            defaults: Default::default(),
         }
     }
 
@@ -195,6 +222,95 @@ impl GroupModel {
                 .activation_condition_model
                 .create_activation_condition(),
             tags: self.tags.clone(),
+            defaults: self.defaults.clone(),
+        }
+    }
+
+    /// Like [`Self::create_data`] but also takes the data inherited from the ancestor groups into
+    /// account.
+    ///
+    /// A parent which can't be found or which would lead to a cycle ends the inheritance chain.
+    pub fn create_effective_data<G: Deref<Target = GroupModel>>(
+        &self,
+        find_group: impl Fn(GroupId) -> Option<G>,
+    ) -> GroupData {
+        let mut data = self.create_data();
+        let mut visited_ids = vec![self.id];
+        let mut next_parent_id = self.parent_id;
+        while let Some(parent_id) = next_parent_id {
+            if visited_ids.contains(&parent_id) {
+                break;
+            }
+            let Some(parent) = find_group(parent_id) else {
+                break;
+            };
+            data = data.inherit(parent.create_data());
+            visited_ids.push(parent_id);
+            next_parent_id = parent.parent_id;
+        }
+        data
+    }
+}
+
+/// Defaults which a group passes on to its mappings.
+///
+/// A default only takes effect for mappings which leave the corresponding setting at its
+/// default value. `None` means that the group doesn't provide a default for that setting.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GroupDefaults {
+    pub takeover_mode: Option<TakeoverMode>,
+    pub step_size_interval: Option<Interval<UnitValue>>,
+    pub step_factor_interval: Option<Interval<DiscreteIncrement>>,
+    pub feedback_color: Option<VirtualColor>,
+    pub feedback_background_color: Option<VirtualColor>,
+    pub source_channel: Option<Channel>,
+    pub target_track: Option<TrackPropValues>,
+    pub feedback_policy: Option<FeedbackPolicy>,
+}
+
+impl GroupDefaults {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fills the defaults which are not provided by this group with the ones of the given parent.
+    pub fn inherit(self, parent: GroupDefaults) -> GroupDefaults {
+        GroupDefaults {
+            takeover_mode: self.takeover_mode.or(parent.takeover_mode),
+            step_size_interval: self.step_size_interval.or(parent.step_size_interval),
+            step_factor_interval: self.step_factor_interval.or(parent.step_factor_interval),
+            feedback_color: self.feedback_color.or(parent.feedback_color),
+            feedback_background_color: self
+                .feedback_background_color
+                .or(parent.feedback_background_color),
+            source_channel: self.source_channel.or(parent.source_channel),
+            target_track: self.target_track.or(parent.target_track),
+            feedback_policy: self.feedback_policy.or(parent.feedback_policy),
+        }
+    }
+}
+
+impl GroupData {
+    /// Combines this data with the one of the parent group.
+    ///
+    /// Tags are accumulated, control/feedback is only enabled if it's enabled in both groups and
+    /// the activation condition of the parent only applies if this group doesn't have one.
+    pub fn inherit(self, parent: GroupData) -> GroupData {
+        let mut tags = parent.tags;
+        tags.extend(self.tags);
+        GroupData {
+            control_is_enabled: parent.control_is_enabled && self.control_is_enabled,
+            feedback_is_enabled: parent.feedback_is_enabled && self.feedback_is_enabled,
+            activation_condition: if matches!(
+                self.activation_condition,
+                ActivationCondition::Always
+            ) {
+                parent.activation_condition
+            } else {
+                self.activation_condition
+            },
+            tags,
+            defaults: self.defaults.inherit(parent.defaults),
         }
     }
 }
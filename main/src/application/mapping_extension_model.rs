@@ -1,5 +1,6 @@
 use crate::domain::{
-    parse_hex_string, DisplayAsPrettyHex, LifecycleMidiData, LifecycleMidiMessage, MappingExtension,
+    parse_hex_string, CompartmentParamIndex, DisplayAsPrettyHex, LifecycleAction, LifecycleActions,
+    LifecycleMainData, LifecycleMidiData, LifecycleMidiMessage, LifecycleOscArg, MappingExtension,
 };
 
 use helgoboss_learn::RawMidiEvent;
@@ -19,6 +20,62 @@ pub struct MappingExtensionModel {
 #[serde(default)]
 pub struct LifecycleModel {
     pub send_midi_feedback: Vec<LifecycleMidiMessageModel>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub send_osc_feedback: Vec<LifecycleOscMessageModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_target_value: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub set_parameters: Vec<LifecycleParameterValueModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lua: Option<String>,
+}
+
+impl LifecycleModel {
+    /// Creates the actions which are executed in the main thread.
+    ///
+    /// Order: OSC messages, parameter values, target value, Lua script.
+    fn create_lifecycle_actions(&self) -> LifecycleActions {
+        let osc_actions = self
+            .send_osc_feedback
+            .iter()
+            .map(|m| LifecycleAction::SendOscFeedback {
+                address: m.address.clone(),
+                arguments: m.arguments.clone(),
+            });
+        let parameter_actions = self
+            .set_parameters
+            .iter()
+            .map(|p| LifecycleAction::SetParameter {
+                index: p.index,
+                value: p.value,
+            });
+        let target_action = self
+            .set_target_value
+            .map(|value| LifecycleAction::SetTargetValue { value });
+        LifecycleActions {
+            actions: osc_actions
+                .chain(parameter_actions)
+                .chain(target_action)
+                .collect(),
+            lua_script: self.lua.clone().filter(|code| !code.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleOscMessageModel {
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<LifecycleOscArg>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleParameterValueModel {
+    pub index: CompartmentParamIndex,
+    /// Key of the parameter if it was referred to by key, so that it can be exported that way again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub value: f64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -89,10 +146,18 @@ impl MappingExtensionModel {
                 .map(|m| m.create_lifecycle_midi_message())
                 .collect()
         }
-        let ext = MappingExtension::new(LifecycleMidiData {
-            activation_midi_messages: convert_messages(&self.on_activate.send_midi_feedback)?,
-            deactivation_midi_messages: convert_messages(&self.on_deactivate.send_midi_feedback)?,
-        });
+        let ext = MappingExtension::new(
+            LifecycleMidiData {
+                activation_midi_messages: convert_messages(&self.on_activate.send_midi_feedback)?,
+                deactivation_midi_messages: convert_messages(
+                    &self.on_deactivate.send_midi_feedback,
+                )?,
+            },
+            LifecycleMainData {
+                activation_actions: self.on_activate.create_lifecycle_actions(),
+                deactivation_actions: self.on_deactivate.create_lifecycle_actions(),
+            },
+        );
         Ok(ext)
     }
 }
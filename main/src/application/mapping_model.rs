@@ -1,16 +1,17 @@
 use crate::application::{
     merge_affected, ActivationConditionCommand, ActivationConditionModel, ActivationConditionProp,
-    Affected, Change, ChangeResult, GetProcessingRelevance, MappingExtensionModel, ModeCommand,
-    ModeModel, ModeProp, ProcessingRelevance, SourceCommand, SourceModel, SourceProp,
-    TargetCategory, TargetCommand, TargetModel, TargetModelFormatVeryShort, TargetModelWithContext,
-    TargetProp,
+    Affected, Change, ChangeResult, GetProcessingRelevance, GroupDefaults, MappingExtensionModel,
+    ModeCommand, ModeModel, ModeProp, ProcessingRelevance, SourceCategory, SourceCommand,
+    SourceModel, SourceProp, TargetCategory, TargetCommand, TargetModel,
+    TargetModelFormatVeryShort, TargetModelWithContext, TargetProp, VirtualTrackType,
 };
 use crate::domain::{
     ActivationCondition, CompartmentKind, CompoundMappingSource, CompoundMappingTarget,
-    EelTransformation, ExtendedProcessorContext, ExtendedSourceCharacter, FeedbackSendBehavior,
-    GroupId, MainMapping, MappingId, MappingKey, Mode, PersistentMappingProcessingState,
-    ProcessorMappingOptions, QualifiedMappingId, RealearnTarget, ReaperTarget, Script, Tag,
-    TargetCharacter, UnresolvedCompoundMappingTarget, VirtualFx, VirtualTrack,
+    EelTransformation, ExtendedProcessorContext, ExtendedSourceCharacter, FeedbackPolicy,
+    FeedbackSendBehavior, GroupId, MainMapping, MappingId, MappingIoSelection, MappingKey, Mode,
+    PersistentMappingProcessingState, ProcessorMappingOptions, QualifiedMappingId, RealearnTarget,
+    ReaperTarget, Script, Tag, TargetCharacter, UnresolvedCompoundMappingTarget, VirtualFx,
+    VirtualTrack,
 };
 use helgoboss_learn::{
     AbsoluteMode, ControlType, DetailedSourceCharacter, DiscreteIncrement, Interval,
@@ -18,6 +19,7 @@ use helgoboss_learn::{
 };
 
 use helgobox_api::persistence::TrackScope;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::error::Error;
 use std::rc::Rc;
@@ -30,6 +32,8 @@ pub enum MappingCommand {
     SetControlIsEnabled(bool),
     SetFeedbackIsEnabled(bool),
     SetFeedbackSendBehavior(FeedbackSendBehavior),
+    SetFeedbackPolicy(FeedbackPolicy),
+    SetIoSelection(MappingIoSelection),
     SetVisibleInProjection(bool),
     SetBeepOnSuccess(bool),
     ChangeActivationCondition(ActivationConditionCommand),
@@ -47,6 +51,8 @@ pub enum MappingProp {
     ControlIsEnabled,
     FeedbackIsEnabled,
     FeedbackSendBehavior,
+    FeedbackPolicy,
+    IoSelection,
     VisibleInProjection,
     BeepOnSuccess,
     AdvancedSettings,
@@ -65,6 +71,8 @@ impl GetProcessingRelevance for MappingProp {
             | P::ControlIsEnabled
             | P::FeedbackIsEnabled
             | P::FeedbackSendBehavior
+            | P::FeedbackPolicy
+            | P::IoSelection
             | P::VisibleInProjection
             | P::AdvancedSettings
             | P::BeepOnSuccess => Some(ProcessingRelevance::ProcessingRelevant),
@@ -94,6 +102,8 @@ pub struct MappingModel {
     control_is_enabled: bool,
     feedback_is_enabled: bool,
     feedback_send_behavior: FeedbackSendBehavior,
+    feedback_policy: FeedbackPolicy,
+    io_selection: MappingIoSelection,
     pub activation_condition_model: ActivationConditionModel,
     visible_in_projection: bool,
     beep_on_success: bool,
@@ -102,6 +112,10 @@ pub struct MappingModel {
     pub target_model: TargetModel,
     advanced_settings: Option<serde_yaml::mapping::Mapping>,
     extension_model: MappingExtensionModel,
+    /// ID of the mapping template from which this mapping has been materialized.
+    ///
+    /// Such mappings are not saved. They are materialized again when loading the compartment.
+    template_id: Option<String>,
 }
 
 pub type SharedMapping = Rc<RefCell<MappingModel>>;
@@ -162,6 +176,14 @@ impl<'a> Change<'a> for MappingModel {
                 self.feedback_send_behavior = v;
                 One(P::FeedbackSendBehavior)
             }
+            C::SetFeedbackPolicy(v) => {
+                self.feedback_policy = v;
+                One(P::FeedbackPolicy)
+            }
+            C::SetIoSelection(v) => {
+                self.io_selection = v;
+                One(P::IoSelection)
+            }
             C::SetVisibleInProjection(v) => {
                 self.visible_in_projection = v;
                 One(P::VisibleInProjection)
@@ -217,6 +239,8 @@ impl MappingModel {
             control_is_enabled: true,
             feedback_is_enabled: true,
             feedback_send_behavior: Default::default(),
+            feedback_policy: Default::default(),
+            io_selection: Default::default(),
             activation_condition_model: Default::default(),
             visible_in_projection: true,
             beep_on_success: false,
@@ -225,6 +249,7 @@ impl MappingModel {
             target_model: TargetModel::default_for_compartment(compartment),
             advanced_settings: None,
             extension_model: Default::default(),
+            template_id: None,
         }
     }
 
@@ -256,6 +281,14 @@ impl MappingModel {
         self.feedback_send_behavior
     }
 
+    pub fn feedback_policy(&self) -> FeedbackPolicy {
+        self.feedback_policy
+    }
+
+    pub fn io_selection(&self) -> MappingIoSelection {
+        self.io_selection
+    }
+
     pub fn visible_in_projection(&self) -> bool {
         self.visible_in_projection
     }
@@ -272,6 +305,18 @@ impl MappingModel {
         self.key = MappingKey::random();
     }
 
+    pub fn template_id(&self) -> Option<&str> {
+        self.template_id.as_deref()
+    }
+
+    pub fn is_template_instance(&self) -> bool {
+        self.template_id.is_some()
+    }
+
+    pub fn set_template_id(&mut self, template_id: Option<String>) {
+        self.template_id = template_id;
+    }
+
     pub fn qualified_id(&self) -> QualifiedMappingId {
         QualifiedMappingId::new(self.compartment, self.id)
     }
@@ -292,6 +337,21 @@ impl MappingModel {
         }
     }
 
+    /// Checks if this mapping has references to a project, e.g. refers to track or FX by ID.
+    pub fn has_project_references(&self) -> bool {
+        let target = &self.target_model;
+        match target.category() {
+            TargetCategory::Reaper => {
+                if target.target_type().supports_track() && target.track_type().refers_to_project()
+                {
+                    return true;
+                }
+                target.supports_fx() && target.fx_type().refers_to_project()
+            }
+            TargetCategory::Virtual => false,
+        }
+    }
+
     #[must_use]
     pub fn make_project_independent(
         &mut self,
@@ -333,6 +393,7 @@ impl MappingModel {
                         t.index().map(|index| VirtualTrack::ByIndex {
                             index,
                             scope: TrackScope::AllTracks,
+                            bank_relative: false,
                         })
                     } else {
                         None
@@ -389,6 +450,8 @@ impl MappingModel {
         MappingModel {
             id: MappingId::random(),
             key: MappingKey::random(),
+            // A duplicate is a normal mapping on its own
+            template_id: None,
             ..self.clone()
         }
     }
@@ -560,6 +623,67 @@ impl MappingModel {
     /// Creates an intermediate mapping for splintering into very dedicated mapping types that are
     /// then going to be distributed to real-time and main processor.
     pub fn create_main_mapping(&self, group_data: GroupData) -> MainMapping {
+        self.with_group_defaults(&group_data.defaults)
+            .create_main_mapping_internal(group_data)
+    }
+
+    /// Returns this mapping with the given group defaults applied to all settings that are left
+    /// at their default value.
+    fn with_group_defaults(&self, defaults: &GroupDefaults) -> Cow<MappingModel> {
+        if defaults.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut mapping = self.clone();
+        let default_mode = ModeModel::default();
+        let mode = &mut mapping.mode_model;
+        if let Some(v) = defaults.takeover_mode {
+            if mode.takeover_mode() == default_mode.takeover_mode() {
+                mode.change(ModeCommand::SetTakeoverMode(v));
+            }
+        }
+        if let Some(v) = defaults.step_size_interval {
+            if mode.step_size_interval() == default_mode.step_size_interval() {
+                mode.change(ModeCommand::SetStepSizeInterval(v));
+            }
+        }
+        if let Some(v) = defaults.step_factor_interval {
+            if mode.step_factor_interval() == default_mode.step_factor_interval() {
+                mode.change(ModeCommand::SetStepFactorInterval(v));
+            }
+        }
+        if let Some(v) = &defaults.feedback_color {
+            if mode.feedback_color().is_none() {
+                mode.change(ModeCommand::SetFeedbackColor(Some(v.clone())));
+            }
+        }
+        if let Some(v) = &defaults.feedback_background_color {
+            if mode.feedback_background_color().is_none() {
+                mode.change(ModeCommand::SetFeedbackBackgroundColor(Some(v.clone())));
+            }
+        }
+        if let Some(v) = defaults.source_channel {
+            let source = &mut mapping.source_model;
+            if source.category() == SourceCategory::Midi && source.channel().is_none() {
+                source.change(SourceCommand::SetChannel(Some(v)));
+            }
+        }
+        if let Some(v) = defaults.feedback_policy {
+            if mapping.feedback_policy.is_default() {
+                mapping.feedback_policy = v;
+            }
+        }
+        if let Some(v) = &defaults.target_track {
+            let target = &mut mapping.target_model;
+            if target.category() == TargetCategory::Reaper
+                && target.track_type() == VirtualTrackType::This
+            {
+                let _ = target.set_track_from_prop_values(v.clone(), false, None);
+            }
+        }
+        Cow::Owned(mapping)
+    }
+
+    fn create_main_mapping_internal(&self, group_data: GroupData) -> MainMapping {
         let id = self.id;
         let source = self.create_source();
         let mode = self.create_mode();
@@ -574,6 +698,8 @@ impl MappingModel {
             control_is_enabled: group_data.control_is_enabled && self.control_is_enabled(),
             feedback_is_enabled: group_data.feedback_is_enabled && self.feedback_is_enabled(),
             feedback_send_behavior: self.feedback_send_behavior(),
+            feedback_policy: self.feedback_policy(),
+            io_selection: self.io_selection(),
             beep_on_success: self.beep_on_success,
         };
         let mut merged_tags = group_data.tags;
@@ -595,6 +721,7 @@ impl MappingModel {
             self.extension_model
                 .create_mapping_extension()
                 .unwrap_or_default(),
+            self.mode_model.create_feedback_value_range_table(),
         )
     }
 }
@@ -604,6 +731,7 @@ pub struct GroupData {
     pub feedback_is_enabled: bool,
     pub activation_condition: ActivationCondition,
     pub tags: Vec<Tag>,
+    pub defaults: GroupDefaults,
 }
 
 impl Default for GroupData {
@@ -613,6 +741,7 @@ impl Default for GroupData {
             feedback_is_enabled: true,
             activation_condition: ActivationCondition::Always,
             tags: vec![],
+            defaults: Default::default(),
         }
     }
 }
@@ -1,17 +1,20 @@
-use crate::domain::{Backbone, EelTransformation, LuaFeedbackScript, Mode};
+use crate::domain::{
+    Backbone, EelTransformation, FeedbackValueRangeTable, LuaFeedbackScript, Mode, RangeTable,
+    RangeTableEntry,
+};
 
 use helgoboss_learn::{
     check_mode_applicability, create_unit_value_interval, full_discrete_interval,
     full_unit_interval, AbsoluteMode, ButtonUsage, DetailedSourceCharacter, DiscreteIncrement,
     EncoderUsage, FeedbackProcessor, FeedbackType, FireMode, GroupInteraction, Interval,
-    ModeApplicabilityCheckInput, ModeParameter, ModeSettings, OutOfRangeBehavior, TakeoverMode,
-    UnitValue, ValueSequence, VirtualColor,
+    ModeApplicabilityCheckInput, ModeParameter, ModeSettings, OutOfRangeBehavior, RgbColor,
+    TakeoverMode, UnitValue, ValueSequence, VirtualColor,
 };
 
 use crate::application::{Affected, Change, GetProcessingRelevance, ProcessingRelevance};
 use crate::base::CloneAsDefault;
 use base::hash_util::clone_to_other_hash_map;
-use helgobox_api::persistence::FeedbackValueTable;
+use helgobox_api::persistence::{FeedbackValueTable, FeedbackValueTableRange};
 use std::time::Duration;
 
 pub enum ModeCommand {
@@ -365,6 +368,39 @@ impl ModeModel {
         self.feedback_value_table.as_ref()
     }
 
+    /// Creates the numeric range table which is applied to the feedback value produced by the
+    /// mode.
+    pub fn create_feedback_value_range_table(&self) -> Option<FeedbackValueRangeTable> {
+        match self.feedback_value_table.as_ref()? {
+            FeedbackValueTable::FromTextToDiscrete(_)
+            | FeedbackValueTable::FromTextToContinuous(_) => None,
+            FeedbackValueTable::FromNumericRangeToContinuous(t) => {
+                let table = RangeTable {
+                    input: t.input.unwrap_or_default(),
+                    entries: convert_range_table_entries(&t.value, |v| UnitValue::new_clamped(*v)),
+                    interpolate: t.interpolate.unwrap_or(false),
+                };
+                Some(FeedbackValueRangeTable::ToContinuous(table))
+            }
+            FeedbackValueTable::FromNumericRangeToColor(t) => {
+                let table = RangeTable {
+                    input: t.input.unwrap_or_default(),
+                    entries: convert_range_table_entries(&t.value, |c| {
+                        use helgobox_api::persistence::VirtualColor as C;
+                        match c {
+                            C::Rgb(c) => VirtualColor::Rgb(RgbColor::new(c.0, c.1, c.2)),
+                            C::Prop(p) => VirtualColor::Prop {
+                                prop: p.prop.clone(),
+                            },
+                        }
+                    }),
+                    interpolate: t.interpolate.unwrap_or(false),
+                };
+                Some(FeedbackValueRangeTable::ToColor(table))
+            }
+        }
+    }
+
     pub fn absolute_mode(&self) -> AbsoluteMode {
         self.absolute_mode
     }
@@ -618,17 +654,20 @@ impl ModeModel {
             } else {
                 None
             },
-            feedback_value_table: self.feedback_value_table.as_ref().map(|t| match t {
+            feedback_value_table: self.feedback_value_table.as_ref().and_then(|t| match t {
                 FeedbackValueTable::FromTextToDiscrete(v) => {
-                    helgoboss_learn::FeedbackValueTable::FromTextToDiscrete(
+                    Some(helgoboss_learn::FeedbackValueTable::FromTextToDiscrete(
                         clone_to_other_hash_map(&v.value),
-                    )
+                    ))
                 }
                 FeedbackValueTable::FromTextToContinuous(v) => {
-                    helgoboss_learn::FeedbackValueTable::FromTextToContinuous(
+                    Some(helgoboss_learn::FeedbackValueTable::FromTextToContinuous(
                         clone_to_other_hash_map(&v.value),
-                    )
+                    ))
                 }
+                // Evaluated outside the mode, see `create_feedback_value_range_table()`
+                FeedbackValueTable::FromNumericRangeToContinuous(_)
+                | FeedbackValueTable::FromNumericRangeToColor(_) => None,
             }),
             make_absolute: if is_relevant(ModeParameter::MakeAbsolute) {
                 self.make_absolute
@@ -665,6 +704,26 @@ impl ModeModel {
     }
 }
 
+fn convert_range_table_entries<T, R>(
+    ranges: &[FeedbackValueTableRange<T>],
+    convert_value: impl Fn(&T) -> R,
+) -> Vec<RangeTableEntry<R>> {
+    let mut entries: Vec<_> = ranges
+        .iter()
+        .map(|r| RangeTableEntry {
+            from: r.from,
+            to: r.to,
+            value: convert_value(&r.value),
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        let a = a.from.unwrap_or(f64::NEG_INFINITY);
+        let b = b.from.unwrap_or(f64::NEG_INFINITY);
+        a.total_cmp(&b)
+    });
+    entries
+}
+
 fn default_jump_interval() -> Interval<UnitValue> {
     create_unit_value_interval(0.0, 0.03)
 }
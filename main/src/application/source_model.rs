@@ -2,11 +2,15 @@ use crate::application::{
     Affected, Change, GetProcessingRelevance, MappingProp, ProcessingRelevance,
 };
 use crate::base::CloneAsDefault;
+use crate::domain::ui_util::{format_as_percentage_without_unit, parse_unit_value_from_percentage};
 use crate::domain::{
-    Backbone, CompartmentKind, CompartmentParamIndex, CompoundMappingSource, EelMidiSourceScript,
-    ExtendedSourceCharacter, FlexibleMidiSourceScript, KeySource, Keystroke, LuaMidiSourceScript,
-    MidiSource, RealearnParameterSource, ReaperSource, SpeechSource, TimerSource,
-    VirtualControlElement, VirtualControlElementId, VirtualSource,
+    AudioSource, AudioSourceSettings, Backbone, CompartmentKind, CompartmentParamIndex,
+    CompoundMappingSource, CompoundSource, CompoundSourceMembers, CompoundSourceSettings,
+    EelMidiSourceScript, ExtendedSourceCharacter, FlexibleMidiSourceScript, KeySource, Keystroke,
+    LfoSettings, LfoSource, LuaMidiSourceScript, MidiSource, RealearnParameterSource, ReaperSource,
+    SequencerSettings, SequencerSource, SequencerStep, SpeechSource, SysExDisplayMidiSourceScript,
+    SysExDisplaySettings, TimerSource, VirtualControlElement, VirtualControlElementId,
+    VirtualSource,
 };
 use derive_more::Display;
 use helgoboss_learn::{
@@ -16,11 +20,16 @@ use helgoboss_learn::{
     DEFAULT_OSC_ARG_VALUE_RANGE,
 };
 use helgoboss_midi::{Channel, U14, U7};
-use helgobox_api::persistence::{MidiScriptKind, VirtualControlElementCharacter};
+use helgobox_api::persistence::{
+    AudioAnalysis, AudioLevelDetector, CompoundSourceKind, LfoWaveform, MidiScriptKind,
+    SequencerDirection, SysExDisplayAlignment, SysExDisplayEncoding,
+    VirtualControlElementCharacter,
+};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::Display;
@@ -44,6 +53,12 @@ pub enum SourceCommand {
     SetDisplayType(DisplayType),
     SetDisplayId(Option<u8>),
     SetLine(Option<u8>),
+    SetSysExDisplayTemplate(String),
+    SetSysExDisplayLength(Option<u32>),
+    SetSysExDisplayAlignment(SysExDisplayAlignment),
+    SetSysExDisplayPadding(char),
+    SetSysExDisplayEncoding(SysExDisplayEncoding),
+    SetSysExDisplayCharacterMap(BTreeMap<char, u8>),
     SetOscAddressPattern(String),
     SetOscArgIndex(Option<u32>),
     SetOscArgTypeTag(OscTypeTag),
@@ -53,9 +68,29 @@ pub enum SourceCommand {
     SetReaperSourceType(ReaperSourceType),
     SetTimerMillis(u64),
     SetParameterIndex(CompartmentParamIndex),
+    SetLfoWaveform(LfoWaveform),
+    SetLfoFrequency(f64),
+    SetLfoBeatsPerCycle(Option<f64>),
+    SetLfoPhaseOffset(f64),
+    SetLfoRetriggerParameterIndex(Option<CompartmentParamIndex>),
+    SetSequencerSteps(Vec<SequencerStep>),
+    SetSequencerBeatsPerStep(f64),
+    SetSequencerSwing(f64),
+    SetSequencerDirection(SequencerDirection),
+    SetSequencerStepValueParameterIndex(Option<CompartmentParamIndex>),
     SetKeystroke(Option<Keystroke>),
     SetControlElementCharacter(VirtualControlElementCharacter),
     SetControlElementId(VirtualControlElementId),
+    SetCompoundKind(CompoundSourceKind),
+    SetCompoundTimeoutMillis(Option<u64>),
+    SetCompoundMembers(Vec<SourceModel>),
+    SetAudioChannel(u32),
+    SetAudioChannelCount(u32),
+    SetAudioDetector(AudioLevelDetector),
+    SetAudioAttackMillis(u64),
+    SetAudioReleaseMillis(u64),
+    SetAudioThresholdDb(Option<f64>),
+    SetAudioAnalysis(AudioAnalysis),
 }
 
 #[derive(Eq, PartialEq)]
@@ -75,6 +110,12 @@ pub enum SourceProp {
     DisplayType,
     DisplayId,
     Line,
+    SysExDisplayTemplate,
+    SysExDisplayLength,
+    SysExDisplayAlignment,
+    SysExDisplayPadding,
+    SysExDisplayEncoding,
+    SysExDisplayCharacterMap,
     OscAddressPattern,
     OscArgIndex,
     OscArgTypeTag,
@@ -86,7 +127,27 @@ pub enum SourceProp {
     ControlElementId,
     TimerMillis,
     ParameterIndex,
+    LfoWaveform,
+    LfoFrequency,
+    LfoBeatsPerCycle,
+    LfoPhaseOffset,
+    LfoRetriggerParameterIndex,
+    SequencerSteps,
+    SequencerBeatsPerStep,
+    SequencerSwing,
+    SequencerDirection,
+    SequencerStepValueParameterIndex,
     Keystroke,
+    CompoundKind,
+    CompoundTimeoutMillis,
+    CompoundMembers,
+    AudioChannel,
+    AudioChannelCount,
+    AudioDetector,
+    AudioAttackMillis,
+    AudioReleaseMillis,
+    AudioThresholdDb,
+    AudioAnalysis,
 }
 
 impl GetProcessingRelevance for SourceProp {
@@ -165,6 +226,30 @@ impl<'a> Change<'a> for SourceModel {
                 self.line = v;
                 One(P::Line)
             }
+            C::SetSysExDisplayTemplate(v) => {
+                self.sysex_display_template = v;
+                One(P::SysExDisplayTemplate)
+            }
+            C::SetSysExDisplayLength(v) => {
+                self.sysex_display_length = v;
+                One(P::SysExDisplayLength)
+            }
+            C::SetSysExDisplayAlignment(v) => {
+                self.sysex_display_alignment = v;
+                One(P::SysExDisplayAlignment)
+            }
+            C::SetSysExDisplayPadding(v) => {
+                self.sysex_display_padding = v;
+                One(P::SysExDisplayPadding)
+            }
+            C::SetSysExDisplayEncoding(v) => {
+                self.sysex_display_encoding = v;
+                One(P::SysExDisplayEncoding)
+            }
+            C::SetSysExDisplayCharacterMap(v) => {
+                self.sysex_display_character_map = v;
+                One(P::SysExDisplayCharacterMap)
+            }
             C::SetOscAddressPattern(v) => {
                 self.osc_address_pattern = v;
                 One(P::OscAddressPattern)
@@ -209,10 +294,90 @@ impl<'a> Change<'a> for SourceModel {
                 self.parameter_index = v;
                 One(P::ParameterIndex)
             }
+            C::SetLfoWaveform(v) => {
+                self.lfo_waveform = v;
+                One(P::LfoWaveform)
+            }
+            C::SetLfoFrequency(v) => {
+                self.lfo_frequency = v;
+                One(P::LfoFrequency)
+            }
+            C::SetLfoBeatsPerCycle(v) => {
+                self.lfo_beats_per_cycle = v;
+                One(P::LfoBeatsPerCycle)
+            }
+            C::SetLfoPhaseOffset(v) => {
+                self.lfo_phase_offset = v;
+                One(P::LfoPhaseOffset)
+            }
+            C::SetLfoRetriggerParameterIndex(v) => {
+                self.lfo_retrigger_parameter_index = v;
+                One(P::LfoRetriggerParameterIndex)
+            }
+            C::SetSequencerSteps(v) => {
+                self.sequencer_steps = v;
+                One(P::SequencerSteps)
+            }
+            C::SetSequencerBeatsPerStep(v) => {
+                self.sequencer_beats_per_step = v;
+                One(P::SequencerBeatsPerStep)
+            }
+            C::SetSequencerSwing(v) => {
+                self.sequencer_swing = v;
+                One(P::SequencerSwing)
+            }
+            C::SetSequencerDirection(v) => {
+                self.sequencer_direction = v;
+                One(P::SequencerDirection)
+            }
+            C::SetSequencerStepValueParameterIndex(v) => {
+                self.sequencer_step_value_parameter_index = v;
+                One(P::SequencerStepValueParameterIndex)
+            }
             C::SetKeystroke(v) => {
                 self.keystroke = v;
                 One(P::Keystroke)
             }
+            C::SetCompoundKind(v) => {
+                self.compound_kind = v;
+                One(P::CompoundKind)
+            }
+            C::SetCompoundTimeoutMillis(v) => {
+                self.compound_timeout_millis = v;
+                One(P::CompoundTimeoutMillis)
+            }
+            C::SetCompoundMembers(v) => {
+                self.compound_members = v;
+                One(P::CompoundMembers)
+            }
+            C::SetAudioChannel(v) => {
+                self.audio_channel = v;
+                One(P::AudioChannel)
+            }
+            C::SetAudioChannelCount(v) => {
+                self.audio_channel_count = v;
+                One(P::AudioChannelCount)
+            }
+            C::SetAudioDetector(v) => {
+                self.audio_detector = v;
+                One(P::AudioDetector)
+            }
+            C::SetAudioAttackMillis(v) => {
+                self.audio_attack_millis = v;
+                One(P::AudioAttackMillis)
+            }
+            C::SetAudioReleaseMillis(v) => {
+                self.audio_release_millis = v;
+                One(P::AudioReleaseMillis)
+            }
+            C::SetAudioThresholdDb(v) => {
+                self.audio_threshold_db = v;
+                One(P::AudioThresholdDb)
+            }
+            C::SetAudioAnalysis(v) => {
+                self.audio_analysis = v;
+                One(P::AudioAnalysis)
+            }
         };
         Some(affected)
     }
@@ -237,6 +402,12 @@ pub struct SourceModel {
     display_type: DisplayType,
     display_id: Option<u8>,
     line: Option<u8>,
+    sysex_display_template: String,
+    sysex_display_length: Option<u32>,
+    sysex_display_alignment: SysExDisplayAlignment,
+    sysex_display_padding: char,
+    sysex_display_encoding: SysExDisplayEncoding,
+    sysex_display_character_map: BTreeMap<char, u8>,
     // OSC
     osc_address_pattern: String,
     osc_arg_index: Option<u32>,
@@ -248,11 +419,33 @@ pub struct SourceModel {
     reaper_source_type: ReaperSourceType,
     timer_millis: u64,
     parameter_index: CompartmentParamIndex,
+    lfo_waveform: LfoWaveform,
+    lfo_frequency: f64,
+    lfo_beats_per_cycle: Option<f64>,
+    lfo_phase_offset: f64,
+    lfo_retrigger_parameter_index: Option<CompartmentParamIndex>,
+    sequencer_steps: Vec<SequencerStep>,
+    sequencer_beats_per_step: f64,
+    sequencer_swing: f64,
+    sequencer_direction: SequencerDirection,
+    sequencer_step_value_parameter_index: Option<CompartmentParamIndex>,
     // Key
     keystroke: Option<Keystroke>,
     // Virtual
     control_element_character: VirtualControlElementCharacter,
     control_element_id: VirtualControlElementId,
+    // Compound
+    compound_kind: CompoundSourceKind,
+    compound_timeout_millis: Option<u64>,
+    compound_members: Vec<SourceModel>,
+    // Audio
+    audio_channel: u32,
+    audio_channel_count: u32,
+    audio_detector: AudioLevelDetector,
+    audio_attack_millis: u64,
+    audio_release_millis: u64,
+    audio_threshold_db: Option<f64>,
+    audio_analysis: AudioAnalysis,
 }
 
 impl Default for SourceModel {
@@ -281,6 +474,12 @@ impl SourceModel {
             display_type: Default::default(),
             display_id: Default::default(),
             line: None,
+            sysex_display_template: "".to_owned(),
+            sysex_display_length: None,
+            sysex_display_alignment: Default::default(),
+            sysex_display_padding: DEFAULT_SYSEX_DISPLAY_PADDING,
+            sysex_display_encoding: Default::default(),
+            sysex_display_character_map: Default::default(),
             osc_address_pattern: "".to_owned(),
             osc_arg_index: Some(0),
             osc_arg_type_tag: Default::default(),
@@ -290,7 +489,27 @@ impl SourceModel {
             reaper_source_type: Default::default(),
             timer_millis: Default::default(),
             parameter_index: Default::default(),
+            lfo_waveform: Default::default(),
+            lfo_frequency: DEFAULT_LFO_FREQUENCY,
+            lfo_beats_per_cycle: None,
+            lfo_phase_offset: 0.0,
+            lfo_retrigger_parameter_index: None,
+            sequencer_steps: default_sequencer_steps(),
+            sequencer_beats_per_step: DEFAULT_SEQUENCER_BEATS_PER_STEP,
+            sequencer_swing: 0.0,
+            sequencer_direction: Default::default(),
+            sequencer_step_value_parameter_index: None,
             keystroke: None,
+            compound_kind: Default::default(),
+            compound_timeout_millis: None,
+            compound_members: vec![],
+            audio_channel: 0,
+            audio_channel_count: DEFAULT_AUDIO_CHANNEL_COUNT,
+            audio_detector: Default::default(),
+            audio_attack_millis: DEFAULT_AUDIO_ATTACK_MILLIS,
+            audio_release_millis: DEFAULT_AUDIO_RELEASE_MILLIS,
+            audio_threshold_db: None,
+            audio_analysis: Default::default(),
         }
     }
 
@@ -354,6 +573,30 @@ impl SourceModel {
         self.line
     }
 
+    pub fn sysex_display_template(&self) -> &str {
+        &self.sysex_display_template
+    }
+
+    pub fn sysex_display_length(&self) -> Option<u32> {
+        self.sysex_display_length
+    }
+
+    pub fn sysex_display_alignment(&self) -> SysExDisplayAlignment {
+        self.sysex_display_alignment
+    }
+
+    pub fn sysex_display_padding(&self) -> char {
+        self.sysex_display_padding
+    }
+
+    pub fn sysex_display_encoding(&self) -> SysExDisplayEncoding {
+        self.sysex_display_encoding
+    }
+
+    pub fn sysex_display_character_map(&self) -> &BTreeMap<char, u8> {
+        &self.sysex_display_character_map
+    }
+
     pub fn osc_address_pattern(&self) -> &str {
         &self.osc_address_pattern
     }
@@ -394,6 +637,46 @@ impl SourceModel {
         self.timer_millis
     }
 
+    pub fn lfo_waveform(&self) -> LfoWaveform {
+        self.lfo_waveform
+    }
+
+    pub fn lfo_frequency(&self) -> f64 {
+        self.lfo_frequency
+    }
+
+    pub fn lfo_beats_per_cycle(&self) -> Option<f64> {
+        self.lfo_beats_per_cycle
+    }
+
+    pub fn lfo_phase_offset(&self) -> f64 {
+        self.lfo_phase_offset
+    }
+
+    pub fn lfo_retrigger_parameter_index(&self) -> Option<CompartmentParamIndex> {
+        self.lfo_retrigger_parameter_index
+    }
+
+    pub fn sequencer_steps(&self) -> &[SequencerStep] {
+        &self.sequencer_steps
+    }
+
+    pub fn sequencer_beats_per_step(&self) -> f64 {
+        self.sequencer_beats_per_step
+    }
+
+    pub fn sequencer_swing(&self) -> f64 {
+        self.sequencer_swing
+    }
+
+    pub fn sequencer_direction(&self) -> SequencerDirection {
+        self.sequencer_direction
+    }
+
+    pub fn sequencer_step_value_parameter_index(&self) -> Option<CompartmentParamIndex> {
+        self.sequencer_step_value_parameter_index
+    }
+
     pub fn control_element_character(&self) -> VirtualControlElementCharacter {
         self.control_element_character
     }
@@ -402,13 +685,53 @@ impl SourceModel {
         self.control_element_id
     }
 
+    pub fn compound_kind(&self) -> CompoundSourceKind {
+        self.compound_kind
+    }
+
+    pub fn compound_timeout_millis(&self) -> Option<u64> {
+        self.compound_timeout_millis
+    }
+
+    pub fn compound_members(&self) -> &[SourceModel] {
+        &self.compound_members
+    }
+
+    pub fn audio_channel(&self) -> u32 {
+        self.audio_channel
+    }
+
+    pub fn audio_channel_count(&self) -> u32 {
+        self.audio_channel_count
+    }
+
+    pub fn audio_detector(&self) -> AudioLevelDetector {
+        self.audio_detector
+    }
+
+    pub fn audio_attack_millis(&self) -> u64 {
+        self.audio_attack_millis
+    }
+
+    pub fn audio_release_millis(&self) -> u64 {
+        self.audio_release_millis
+    }
+
+    pub fn audio_threshold_db(&self) -> Option<f64> {
+        self.audio_threshold_db
+    }
+
+    pub fn audio_analysis(&self) -> AudioAnalysis {
+        self.audio_analysis
+    }
+
     pub fn supports_control(&self) -> bool {
         use SourceCategory::*;
         match self.category {
             Midi => self.midi_source_type.supports_control(),
             Osc => self.osc_arg_type_tag.supports_control(),
             Reaper => self.reaper_source_type.supports_control(),
-            Virtual | Keyboard => true,
+            Virtual | Keyboard | Compound | Audio => true,
             // Main use case: Group interaction (follow-only).
             Never => true,
         }
@@ -420,8 +743,8 @@ impl SourceModel {
             Midi => self.midi_source_type.supports_feedback(),
             Osc => self.osc_arg_type_tag.supports_feedback(),
             Reaper => self.reaper_source_type.supports_feedback(),
-            Virtual => true,
-            Keyboard | Never => false,
+            Virtual | Compound => true,
+            Keyboard | Audio | Never => false,
         }
     }
 
@@ -511,6 +834,23 @@ impl SourceModel {
                     RealearnParameter(p) => {
                         self.parameter_index = p.parameter_index;
                     }
+                    Lfo(l) => {
+                        let settings = l.settings();
+                        self.lfo_waveform = settings.waveform;
+                        self.lfo_frequency = settings.frequency;
+                        self.lfo_beats_per_cycle = settings.beats_per_cycle;
+                        self.lfo_phase_offset = settings.phase_offset;
+                        self.lfo_retrigger_parameter_index = settings.retrigger_parameter_index;
+                    }
+                    Sequencer(s) => {
+                        let settings = s.settings();
+                        self.sequencer_steps = settings.steps.clone();
+                        self.sequencer_beats_per_step = settings.beats_per_step;
+                        self.sequencer_swing = settings.swing;
+                        self.sequencer_direction = settings.direction;
+                        self.sequencer_step_value_parameter_index =
+                            settings.step_value_parameter_index;
+                    }
                     MidiDeviceChanges | RealearnInstanceStart | Timer(_) | Speech(_) => {}
                 }
             }
@@ -521,6 +861,41 @@ impl SourceModel {
                 self.category = SourceCategory::Keyboard;
                 self.keystroke = Some(s.stroke());
             }
+            Compound(s) => {
+                self.category = SourceCategory::Compound;
+                let settings = s.settings();
+                self.compound_kind = settings.kind;
+                self.compound_timeout_millis = settings.timeout.map(|t| t.as_millis() as u64);
+                let member_sources: Vec<_> = match s.members() {
+                    CompoundSourceMembers::Midi(members) => members
+                        .iter()
+                        .map(|m| CompoundMappingSource::Midi(m.clone()))
+                        .collect(),
+                    CompoundSourceMembers::Virtual(members) => members
+                        .iter()
+                        .map(|m| CompoundMappingSource::Virtual(*m))
+                        .collect(),
+                };
+                self.compound_members = member_sources
+                    .iter()
+                    .map(|member_source| {
+                        let mut model = SourceModel::new();
+                        let _ = model.apply_from_source(member_source);
+                        model
+                    })
+                    .collect();
+            }
+            Audio(s) => {
+                self.category = SourceCategory::Audio;
+                let settings = s.settings();
+                self.audio_channel = settings.channel;
+                self.audio_channel_count = settings.channel_count;
+                self.audio_detector = settings.detector;
+                self.audio_attack_millis = settings.attack.as_millis() as u64;
+                self.audio_release_millis = settings.release.as_millis() as u64;
+                self.audio_threshold_db = settings.threshold_db;
+                self.audio_analysis = settings.analysis;
+            }
         };
         Some(Affected::Multiple)
     }
@@ -563,7 +938,15 @@ impl SourceModel {
                 DetailedSourceCharacter::RangeControl,
                 DetailedSourceCharacter::Relative,
             ],
-            CompoundMappingSource::Key(_) => vec![DetailedSourceCharacter::MomentaryOnOffButton],
+            CompoundMappingSource::Key(_) | CompoundMappingSource::Compound(_) => {
+                vec![DetailedSourceCharacter::MomentaryOnOffButton]
+            }
+            CompoundMappingSource::Audio(s) => match s.character() {
+                SourceCharacter::MomentaryButton => {
+                    vec![DetailedSourceCharacter::MomentaryOnOffButton]
+                }
+                _ => vec![DetailedSourceCharacter::RangeControl],
+            },
         }
     }
 
@@ -652,6 +1035,14 @@ impl SourceModel {
                     Display => MidiSource::Display {
                         spec: self.display_spec(),
                     },
+                    SysExDisplay => MidiSource::Script {
+                        script: {
+                            let script = FlexibleMidiSourceScript::SysExDisplay(
+                                self.create_sysex_display_script()?,
+                            );
+                            CloneAsDefault::new(Some(script))
+                        },
+                    },
                 };
                 CompoundMappingSource::Midi(midi_source)
             }
@@ -680,23 +1071,152 @@ impl SourceModel {
                         ReaperSource::RealearnParameter(self.create_realearn_parameter_source())
                     }
                     Speech => ReaperSource::Speech(SpeechSource::new()),
+                    Lfo => ReaperSource::Lfo(self.create_lfo_source()),
+                    Sequencer => ReaperSource::Sequencer(self.create_sequencer_source()),
                 };
                 CompoundMappingSource::Reaper(reaper_source)
             }
             Never => CompoundMappingSource::Never,
             Keyboard => CompoundMappingSource::Key(self.create_key_source()?),
+            Compound => CompoundMappingSource::Compound(self.create_compound_source()?),
+            Audio => CompoundMappingSource::Audio(self.create_audio_source()),
         };
         Some(source)
     }
 
+    /// Returns `None` if there are no members or if MIDI and virtual members are mixed.
+    fn create_compound_source(&self) -> Option<CompoundSource> {
+        let member_sources: Vec<_> = self
+            .compound_members
+            .iter()
+            .map(|m| m.create_source_internal())
+            .collect::<Option<_>>()?;
+        let members = match member_sources.first()? {
+            CompoundMappingSource::Midi(_) => CompoundSourceMembers::Midi(
+                member_sources
+                    .into_iter()
+                    .map(|s| match s {
+                        CompoundMappingSource::Midi(s) => Some(s),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?,
+            ),
+            CompoundMappingSource::Virtual(_) => CompoundSourceMembers::Virtual(
+                member_sources
+                    .into_iter()
+                    .map(|s| match s {
+                        CompoundMappingSource::Virtual(s) => Some(s),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?,
+            ),
+            _ => return None,
+        };
+        let settings = CompoundSourceSettings {
+            kind: self.compound_kind,
+            timeout: self.compound_timeout_millis.map(Duration::from_millis),
+        };
+        Some(CompoundSource::new(settings, members))
+    }
+
+    /// Short summary of the member sources, one member per item.
+    pub fn format_compound_members(&self) -> String {
+        let separator = match self.compound_kind {
+            CompoundSourceKind::Combination => " + ",
+            CompoundSourceKind::Sequence => " > ",
+        };
+        let member_labels: Vec<_> = self
+            .compound_members
+            .iter()
+            .map(|m| m.to_string().replace('\n', " "))
+            .collect();
+        member_labels.join(separator)
+    }
+
+    fn create_audio_source(&self) -> AudioSource {
+        let settings = AudioSourceSettings {
+            channel: self.audio_channel,
+            channel_count: self.audio_channel_count,
+            detector: self.audio_detector,
+            attack: Duration::from_millis(self.audio_attack_millis),
+            release: Duration::from_millis(self.audio_release_millis),
+            threshold_db: self.audio_threshold_db,
+            analysis: self.audio_analysis,
+        };
+        AudioSource::new(settings)
+    }
+
+    /// Short summary of the audio analysis settings.
+    pub fn format_audio_settings(&self) -> String {
+        let first_channel = self.audio_channel.saturating_add(1);
+        let last_channel = self
+            .audio_channel
+            .saturating_add(self.audio_channel_count.max(1));
+        let channels = if first_channel == last_channel {
+            format!("Ch {first_channel}")
+        } else {
+            format!("Ch {first_channel}-{last_channel}")
+        };
+        format!(
+            "{channels}, {}, A {} ms, R {} ms",
+            self.audio_detector, self.audio_attack_millis, self.audio_release_millis
+        )
+    }
+
     pub fn create_key_source(&self) -> Option<KeySource> {
         Some(KeySource::new(self.keystroke?))
     }
 
+    fn create_sysex_display_script(&self) -> Option<SysExDisplayMidiSourceScript> {
+        let template = self.sysex_display_template.parse().ok()?;
+        let settings = SysExDisplaySettings {
+            line: self.line.unwrap_or_default(),
+            length: self.sysex_display_length,
+            alignment: self.sysex_display_alignment,
+            padding: self.sysex_display_padding,
+            encoding: self.sysex_display_encoding,
+            character_map: self.sysex_display_character_map.clone(),
+        };
+        SysExDisplayMidiSourceScript::new(template, settings).ok()
+    }
+
     fn create_timer_source(&self) -> TimerSource {
         TimerSource::new(Duration::from_millis(self.timer_millis))
     }
 
+    fn create_lfo_source(&self) -> LfoSource {
+        let settings = LfoSettings {
+            waveform: self.lfo_waveform,
+            frequency: self.lfo_frequency,
+            beats_per_cycle: self.lfo_beats_per_cycle,
+            phase_offset: self.lfo_phase_offset,
+            retrigger_parameter_index: self.lfo_retrigger_parameter_index,
+        };
+        LfoSource::new(settings)
+    }
+
+    fn create_sequencer_source(&self) -> SequencerSource {
+        let settings = SequencerSettings {
+            steps: self.sequencer_steps.clone(),
+            beats_per_step: self.sequencer_beats_per_step,
+            swing: self.sequencer_swing,
+            direction: self.sequencer_direction,
+            step_value_parameter_index: self.sequencer_step_value_parameter_index,
+        };
+        SequencerSource::new(settings)
+    }
+
+    pub fn format_sequencer_steps(&self) -> String {
+        format_sequencer_steps(&self.sequencer_steps)
+    }
+
+    pub fn format_lfo_rate(&self) -> String {
+        match self.lfo_beats_per_cycle {
+            None => format!("{} Hz", self.lfo_frequency),
+            Some(beats) => format!("{beats} beats"),
+        }
+    }
+
     fn create_realearn_parameter_source(&self) -> RealearnParameterSource {
         RealearnParameterSource {
             parameter_index: self.parameter_index,
@@ -791,7 +1311,7 @@ impl SourceModel {
 
     pub fn supports_type(&self) -> bool {
         use SourceCategory::*;
-        matches!(self.category, Midi | Virtual | Reaper)
+        matches!(self.category, Midi | Virtual | Reaper | Compound | Audio)
     }
 
     pub fn supports_channel(&self) -> bool {
@@ -919,6 +1439,10 @@ impl Display for SourceModel {
                     vec![t.to_string().into(), self.channel_label(), line_3, line_4]
                 }
                 t @ MidiSourceType::Display => vec![t.to_string().into()],
+                t @ MidiSourceType::SysExDisplay => vec![
+                    t.to_string().into(),
+                    format!("Line {}", self.line.unwrap_or_default()).into(),
+                ],
                 t => vec![t.to_string().into(), self.channel_label()],
             },
             Virtual => vec![
@@ -938,6 +1462,24 @@ impl Display for SourceModel {
                             format!("Parameter #{}", self.parameter_index.get() + 1).into(),
                         ]
                     }
+                    ReaperSourceType::Lfo => {
+                        vec![
+                            type_label,
+                            self.lfo_waveform.to_string().into(),
+                            self.format_lfo_rate().into(),
+                        ]
+                    }
+                    ReaperSourceType::Sequencer => {
+                        vec![
+                            type_label,
+                            format!(
+                                "{} steps, {} beats each",
+                                self.sequencer_steps.len(),
+                                self.sequencer_beats_per_step
+                            )
+                            .into(),
+                        ]
+                    }
                     _ => {
                         vec![type_label]
                     }
@@ -951,6 +1493,17 @@ impl Display for SourceModel {
                     .unwrap_or_else(|| Cow::Borrowed(KEY_UNDEFINED_LABEL));
                 vec![text]
             }
+            Compound => vec![
+                self.compound_kind.to_string().into(),
+                format!("{} members", self.compound_members.len()).into(),
+            ],
+            Audio => {
+                let line_2 = match self.audio_threshold_db {
+                    None => Cow::Borrowed(""),
+                    Some(db) => format!("Threshold {db} dB").into(),
+                };
+                vec![format!("Audio {}", self.audio_analysis).into(), line_2]
+            }
         };
         let non_empty_lines: Vec<_> = lines.into_iter().filter(|l| !l.is_empty()).collect();
         write!(f, "{}", non_empty_lines.join("\n"))
@@ -959,6 +1512,25 @@ impl Display for SourceModel {
 
 pub const KEY_UNDEFINED_LABEL: &str = "<Key undefined>";
 
+pub const DEFAULT_LFO_FREQUENCY: f64 = 1.0;
+
+pub const DEFAULT_AUDIO_CHANNEL_COUNT: u32 = 2;
+
+pub const DEFAULT_AUDIO_ATTACK_MILLIS: u64 = 10;
+
+pub const DEFAULT_AUDIO_RELEASE_MILLIS: u64 = 100;
+
+pub const DEFAULT_SYSEX_DISPLAY_PADDING: char = ' ';
+
+pub const DEFAULT_SEQUENCER_STEP_COUNT: usize = 8;
+
+/// Sixteenth notes in 4/4.
+pub const DEFAULT_SEQUENCER_BEATS_PER_STEP: f64 = 0.25;
+
+pub fn default_sequencer_steps() -> Vec<SequencerStep> {
+    vec![SequencerStep::default(); DEFAULT_SEQUENCER_STEP_COUNT]
+}
+
 #[derive(
     Clone,
     Copy,
@@ -991,6 +1563,12 @@ pub enum SourceCategory {
     #[serde(rename = "reaper")]
     #[display(fmt = "REAPER")]
     Reaper,
+    #[serde(rename = "compound")]
+    #[display(fmt = "Compound")]
+    Compound,
+    #[serde(rename = "audio")]
+    #[display(fmt = "Audio")]
+    Audio,
     #[serde(rename = "virtual")]
     #[display(fmt = "Virtual")]
     Virtual,
@@ -1014,6 +1592,8 @@ impl SourceCategory {
                 Osc => true,
                 Reaper => true,
                 Keyboard => true,
+                Compound => true,
+                Audio => true,
                 Virtual => false,
             },
             CompartmentKind::Main => true,
@@ -1067,6 +1647,8 @@ pub enum MidiSourceType {
     Display = 12,
     #[display(fmt = "Specific program change")]
     SpecificProgramChange = 13,
+    #[display(fmt = "SysEx display (feedback only)")]
+    SysExDisplay = 14,
 }
 
 impl MidiSourceType {
@@ -1150,7 +1732,7 @@ impl MidiSourceType {
 
     pub fn supports_control(self) -> bool {
         use MidiSourceType::*;
-        !matches!(self, Script | Display)
+        !matches!(self, Script | Display | SysExDisplay)
     }
 
     pub fn supports_feedback(self) -> bool {
@@ -1192,6 +1774,12 @@ pub enum ReaperSourceType {
     #[serde(rename = "speech")]
     #[display(fmt = "Speech (feedback only, no Linux)")]
     Speech,
+    #[serde(rename = "lfo")]
+    #[display(fmt = "LFO")]
+    Lfo,
+    #[serde(rename = "sequencer")]
+    #[display(fmt = "Step sequencer")]
+    Sequencer,
 }
 
 impl ReaperSourceType {
@@ -1203,13 +1791,17 @@ impl ReaperSourceType {
             Timer(_) => Self::Timer,
             RealearnParameter(_) => Self::RealearnParameter,
             Speech(_) => Self::Speech,
+            Lfo(_) => Self::Lfo,
+            Sequencer(_) => Self::Sequencer,
         }
     }
 
     pub fn supports_control(self) -> bool {
         use ReaperSourceType::*;
         match self {
-            MidiDeviceChanges | RealearnUnitStart | Timer | RealearnParameter => true,
+            MidiDeviceChanges | RealearnUnitStart | Timer | RealearnParameter | Lfo | Sequencer => {
+                true
+            }
             Speech => false,
         }
     }
@@ -1217,7 +1809,9 @@ impl ReaperSourceType {
     pub fn supports_feedback(self) -> bool {
         use ReaperSourceType::*;
         match self {
-            MidiDeviceChanges | RealearnUnitStart | Timer | RealearnParameter => false,
+            MidiDeviceChanges | RealearnUnitStart | Timer | RealearnParameter | Lfo | Sequencer => {
+                false
+            }
             Speech => true,
         }
     }
@@ -1231,6 +1825,57 @@ pub fn format_osc_feedback_args(args: &[String]) -> String {
     itertools::join(args.iter(), " ")
 }
 
+pub enum LfoRate {
+    Frequency(f64),
+    BeatsPerCycle(f64),
+}
+
+/// Parses texts such as "2 Hz" or "4 beats". A number without unit is interpreted as Hz.
+pub fn parse_lfo_rate(text: &str) -> Option<LfoRate> {
+    let text = text.trim().to_lowercase();
+    if let Some(beats) = text
+        .strip_suffix("beats")
+        .or_else(|| text.strip_suffix("beat"))
+    {
+        let beats: f64 = beats.trim().parse().ok()?;
+        return Some(LfoRate::BeatsPerCycle(beats));
+    }
+    let frequency: f64 = text.trim_end_matches("hz").trim().parse().ok()?;
+    Some(LfoRate::Frequency(frequency))
+}
+
+/// Formats step values as space-separated percentages. Rests are displayed as "-".
+pub fn format_sequencer_steps(steps: &[SequencerStep]) -> String {
+    let items = steps.iter().map(|s| {
+        if s.gate {
+            format_as_percentage_without_unit(s.value)
+        } else {
+            "-".to_string()
+        }
+    });
+    itertools::join(items, " ")
+}
+
+/// Parses texts such as "100 50 - 25" (percentages, "-" for a rest).
+pub fn parse_sequencer_steps(text: &str) -> Option<Vec<SequencerStep>> {
+    text.split_whitespace()
+        .map(|item| {
+            let step = if item == "-" {
+                SequencerStep {
+                    value: UnitValue::MIN,
+                    gate: false,
+                }
+            } else {
+                SequencerStep {
+                    value: parse_unit_value_from_percentage(item).ok()?,
+                    gate: true,
+                }
+            };
+            Some(step)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1244,4 +1889,28 @@ mod tests {
         // Then
         assert_eq!(s, CompoundMappingSource::Never);
     }
+
+    #[test]
+    fn parse_lfo_rates() {
+        assert!(matches!(parse_lfo_rate("2 Hz"), Some(LfoRate::Frequency(f)) if f == 2.0));
+        assert!(matches!(parse_lfo_rate("0.5"), Some(LfoRate::Frequency(f)) if f == 0.5));
+        assert!(matches!(
+            parse_lfo_rate("4 beats"),
+            Some(LfoRate::BeatsPerCycle(b)) if b == 4.0
+        ));
+        assert!(parse_lfo_rate("fast").is_none());
+    }
+
+    #[test]
+    fn parse_and_format_sequencer_steps() {
+        let steps = parse_sequencer_steps("100 50 - 0").unwrap();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[1].value, UnitValue::new(0.5));
+        assert!(!steps[2].gate);
+        assert_eq!(
+            parse_sequencer_steps(&format_sequencer_steps(&steps)).unwrap(),
+            steps
+        );
+        assert!(parse_sequencer_steps("100 loud").is_none());
+    }
 }
@@ -40,14 +40,15 @@ use crate::domain::{
     UnresolvedPlayrateTarget, UnresolvedPreviewPotPresetTarget, UnresolvedReaperTarget,
     UnresolvedRouteAutomationModeTarget, UnresolvedRouteMonoTarget, UnresolvedRouteMuteTarget,
     UnresolvedRoutePanTarget, UnresolvedRoutePhaseTarget, UnresolvedRouteTouchStateTarget,
-    UnresolvedRouteVolumeTarget, UnresolvedSeekTarget, UnresolvedTakeMappingSnapshotTarget,
-    UnresolvedTempoTarget, UnresolvedTrackArmTarget, UnresolvedTrackAutomationModeTarget,
-    UnresolvedTrackMonitoringModeTarget, UnresolvedTrackMuteTarget, UnresolvedTrackPanTarget,
-    UnresolvedTrackParentSendTarget, UnresolvedTrackPeakTarget, UnresolvedTrackPhaseTarget,
-    UnresolvedTrackSelectionTarget, UnresolvedTrackShowTarget, UnresolvedTrackSoloTarget,
-    UnresolvedTrackToolTarget, UnresolvedTrackTouchStateTarget, UnresolvedTrackVolumeTarget,
-    UnresolvedTrackWidthTarget, UnresolvedTransportTarget, VirtualChainFx, VirtualControlElement,
-    VirtualControlElementId, VirtualFx, VirtualFxParameter, VirtualMappingSnapshotIdForLoad,
+    UnresolvedRouteVolumeTarget, UnresolvedSeekTarget, UnresolvedSelectBankTarget,
+    UnresolvedTakeMappingSnapshotTarget, UnresolvedTempoTarget, UnresolvedTrackArmTarget,
+    UnresolvedTrackAutomationModeTarget, UnresolvedTrackMonitoringModeTarget,
+    UnresolvedTrackMuteTarget, UnresolvedTrackPanTarget, UnresolvedTrackParentSendTarget,
+    UnresolvedTrackPeakTarget, UnresolvedTrackPhaseTarget, UnresolvedTrackSelectionTarget,
+    UnresolvedTrackShowTarget, UnresolvedTrackSoloTarget, UnresolvedTrackToolTarget,
+    UnresolvedTrackTouchStateTarget, UnresolvedTrackVolumeTarget, UnresolvedTrackWidthTarget,
+    UnresolvedTransportTarget, VirtualChainFx, VirtualControlElement, VirtualControlElementId,
+    VirtualFx, VirtualFxParameter, VirtualMappingSnapshotIdForLoad,
     VirtualMappingSnapshotIdForTake, VirtualTarget, VirtualTrack, VirtualTrackRoute,
 };
 
@@ -1472,7 +1473,8 @@ impl TargetModel {
             ByName | AllByName => {
                 self.track_name = track.name;
             }
-            ByIndex | ByIndexTcp | ByIndexMcp => {
+            ByIndex | ByIndexTcp | ByIndexMcp | ByIndexInBank | ByIndexInBankTcp
+            | ByIndexInBankMcp => {
                 self.track_index = track.index;
             }
             ByIdOrName => {
@@ -1559,7 +1561,7 @@ impl TargetModel {
             ByName | AllByName => {
                 self.fx_name = fx.name;
             }
-            ByIndex => {
+            ByIndex | ByIndexInBank => {
                 self.fx_index = fx.index;
             }
             ByIdOrIndex => {
@@ -1817,9 +1819,11 @@ impl TargetModel {
                 wild_match: WildMatch::new(&self.track_name),
                 allow_multiple: true,
             },
-            ByIndex | ByIndexTcp | ByIndexMcp => VirtualTrack::ByIndex {
+            ByIndex | ByIndexTcp | ByIndexMcp | ByIndexInBank | ByIndexInBankTcp
+            | ByIndexInBankMcp => VirtualTrack::ByIndex {
                 index: self.track_index,
                 scope: self.track_type.virtual_track_scope().unwrap_or_default(),
+                bank_relative: self.track_type.is_bank_relative(),
             },
             ByIdOrName => {
                 VirtualTrack::ByIdOrName(self.track_id?, WildMatch::new(&self.track_name))
@@ -1967,7 +1971,10 @@ impl TargetModel {
                 wild_match: WildMatch::new(&self.fx_name),
                 allow_multiple: true,
             },
-            ByIndex => VirtualChainFx::ByIndex(self.fx_index),
+            ByIndex | ByIndexInBank => VirtualChainFx::ByIndex {
+                index: self.fx_index,
+                bank_relative: self.fx_type == ByIndexInBank,
+            },
             ByIdOrIndex => VirtualChainFx::ByIdOrIndex(self.fx_id, self.fx_index),
             Dynamic => {
                 let evaluator = ExpressionEvaluator::compile(&self.fx_expression).ok()?;
@@ -2049,10 +2056,12 @@ impl TargetModel {
                 name: self.track_name.clone(),
                 allow_multiple: Some(true),
             },
-            ByIndex | ByIndexTcp | ByIndexMcp => TrackDescriptor::ByIndex {
+            ByIndex | ByIndexTcp | ByIndexMcp | ByIndexInBank | ByIndexInBankTcp
+            | ByIndexInBankMcp => TrackDescriptor::ByIndex {
                 commons,
                 index: self.track_index,
                 scope: self.track_type.virtual_track_scope(),
+                bank_relative: self.track_type.is_bank_relative().then_some(true),
             },
             Dynamic | DynamicTcp | DynamicMcp => TrackDescriptor::Dynamic {
                 commons,
@@ -2107,10 +2116,11 @@ impl TargetModel {
                 name: self.fx_name.clone(),
                 allow_multiple: Some(true),
             },
-            ByIndex | ByIdOrIndex => FxDescriptor::ByIndex {
+            ByIndex | ByIdOrIndex | ByIndexInBank => FxDescriptor::ByIndex {
                 commons,
                 chain,
                 index: self.fx_index,
+                bank_relative: (self.fx_type == ByIndexInBank).then_some(true),
             },
         }
     }
@@ -2193,7 +2203,10 @@ impl TargetModel {
         let param = match self.param_type {
             ByName => VirtualFxParameter::ByName(WildMatch::new(&self.param_name)),
             ById => VirtualFxParameter::ById(self.param_index),
-            ByIndex => VirtualFxParameter::ByIndex(self.param_index),
+            ByIndex | ByIndexInBank => VirtualFxParameter::ByIndex {
+                index: self.param_index,
+                bank_relative: self.param_type == ByIndexInBank,
+            },
             Dynamic => {
                 let evaluator = ExpressionEvaluator::compile(&self.param_expression).ok()?;
                 VirtualFxParameter::Dynamic(Box::new(evaluator))
@@ -2596,6 +2609,9 @@ impl TargetModel {
                             exclusivity: self.exclusivity.into(),
                         })
                     }
+                    SelectBank => UnresolvedReaperTarget::SelectBank(UnresolvedSelectBankTarget {
+                        compartment,
+                    }),
                     AnyOn => UnresolvedReaperTarget::AnyOn(UnresolvedAnyOnTarget {
                         parameter: self.any_on_parameter,
                     }),
@@ -3659,6 +3675,7 @@ fn virtualize_track(
         VirtualTrack::ByIndex {
             index: track.index().expect("impossible"),
             scope: TrackScope::AllTracks,
+            bank_relative: false,
         }
     } else {
         VirtualTrack::ById(*track.guid())
@@ -3677,7 +3694,10 @@ fn virtualize_fx(
             is_input_fx: fx.is_input_fx(),
             chain_fx: if special_monitoring_fx_handling && context.is_on_monitoring_fx_chain() {
                 // Doesn't make sense to refer to FX via UUID if we are on monitoring FX chain.
-                VirtualChainFx::ByIndex(fx.index())
+                VirtualChainFx::ByIndex {
+                    index: fx.index(),
+                    bank_relative: false,
+                }
             } else if let Ok(guid) = fx.get_or_query_guid() {
                 VirtualChainFx::ById(guid, Some(fx.index()))
             } else {
@@ -3754,6 +3774,12 @@ pub enum VirtualTrackType {
     ByIdOrName,
     #[display(fmt = "From clip column")]
     FromClipColumn,
+    #[display(fmt = "At position in bank")]
+    ByIndexInBank,
+    #[display(fmt = "At TCP position in bank")]
+    ByIndexInBankTcp,
+    #[display(fmt = "At MCP position in bank")]
+    ByIndexInBankMcp,
 }
 
 impl Default for VirtualTrackType {
@@ -3873,11 +3899,24 @@ impl VirtualTrackType {
                     Self::ByName
                 }
             }
-            ByIndex { scope, .. } => match scope {
+            ByIndex {
+                scope,
+                bank_relative: false,
+                ..
+            } => match scope {
                 TrackScope::AllTracks => Self::ByIndex,
                 TrackScope::TracksVisibleInTcp => Self::ByIndexTcp,
                 TrackScope::TracksVisibleInMcp => Self::ByIndexMcp,
             },
+            ByIndex {
+                scope,
+                bank_relative: true,
+                ..
+            } => match scope {
+                TrackScope::AllTracks => Self::ByIndexInBank,
+                TrackScope::TracksVisibleInTcp => Self::ByIndexInBankTcp,
+                TrackScope::TracksVisibleInMcp => Self::ByIndexInBankMcp,
+            },
             FromClipColumn { .. } => Self::FromClipColumn,
         }
     }
@@ -3887,15 +3926,30 @@ impl VirtualTrackType {
     }
 
     pub fn is_by_index(&self) -> bool {
-        matches!(self, Self::ByIndex | Self::ByIndexTcp | Self::ByIndexMcp)
+        matches!(
+            self,
+            Self::ByIndex
+                | Self::ByIndexTcp
+                | Self::ByIndexMcp
+                | Self::ByIndexInBank
+                | Self::ByIndexInBankTcp
+                | Self::ByIndexInBankMcp
+        )
+    }
+
+    pub fn is_bank_relative(&self) -> bool {
+        matches!(
+            self,
+            Self::ByIndexInBank | Self::ByIndexInBankTcp | Self::ByIndexInBankMcp
+        )
     }
 
     pub fn virtual_track_scope(&self) -> Option<TrackScope> {
         use VirtualTrackType::*;
         match self {
-            ByIndex | Dynamic => Some(TrackScope::AllTracks),
-            ByIndexTcp | DynamicTcp => Some(TrackScope::TracksVisibleInTcp),
-            ByIndexMcp | DynamicMcp => Some(TrackScope::TracksVisibleInMcp),
+            ByIndex | ByIndexInBank | Dynamic => Some(TrackScope::AllTracks),
+            ByIndexTcp | ByIndexInBankTcp | DynamicTcp => Some(TrackScope::TracksVisibleInTcp),
+            ByIndexMcp | ByIndexInBankMcp | DynamicMcp => Some(TrackScope::TracksVisibleInMcp),
             _ => None,
         }
     }
@@ -3957,6 +4011,9 @@ pub enum VirtualFxType {
     #[display(fmt = "By ID or pos (legacy)")]
     #[serde(rename = "id-or-index")]
     ByIdOrIndex,
+    #[display(fmt = "At position in bank")]
+    #[serde(rename = "index-in-bank")]
+    ByIndexInBank,
 }
 
 impl Default for VirtualFxType {
@@ -3984,7 +4041,14 @@ impl VirtualFxType {
                             Self::ByName
                         }
                     }
-                    ByIndex(_) => Self::ByIndex,
+                    ByIndex {
+                        bank_relative: false,
+                        ..
+                    } => Self::ByIndex,
+                    ByIndex {
+                        bank_relative: true,
+                        ..
+                    } => Self::ByIndexInBank,
                     ByIdOrIndex(_, _) => Self::ByIdOrIndex,
                 }
             }
@@ -4003,6 +4067,7 @@ impl VirtualFxType {
             AllByName => true,
             ByIndex => true,
             ByIdOrIndex => true,
+            ByIndexInBank => true,
         }
     }
 
@@ -4044,6 +4109,9 @@ pub enum VirtualFxParameterType {
     #[display(fmt = "At position")]
     #[serde(rename = "index-manual")]
     ByIndex,
+    #[display(fmt = "At position in bank")]
+    #[serde(rename = "index-in-bank")]
+    ByIndexInBank,
 }
 
 impl Default for VirtualFxParameterType {
@@ -4058,7 +4126,14 @@ impl VirtualFxParameterType {
         match param {
             Dynamic(_) => Self::Dynamic,
             ByName(_) => Self::ByName,
-            ByIndex(_) => Self::ByIndex,
+            ByIndex {
+                bank_relative: false,
+                ..
+            } => Self::ByIndex,
+            ByIndex {
+                bank_relative: true,
+                ..
+            } => Self::ByIndexInBank,
             ById(_) => Self::ById,
         }
     }
@@ -4164,7 +4239,7 @@ impl Display for FxSnapshot {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Debug, PartialEq, Default)]
 pub struct TrackPropValues {
     pub r#type: VirtualTrackType,
     pub id: Option<Guid>,
@@ -9,20 +9,23 @@ use crate::application::{
 use crate::base::{notification, prop, when, AsyncNotifier, Prop};
 use crate::domain::{
     convert_plugin_param_index_range_to_iter, create_lua_midi_script_source_runtime,
-    lua_module_path_without_ext, Backbone, BasicSettings, CompartmentKind, CompartmentParamIndex,
-    CompartmentSettings, CompoundMappingSource, ControlContext, ControlInput, DomainEvent,
-    DomainEventHandler, ExtendedProcessorContext, FeedbackAudioHookTask, FeedbackOutput,
-    FeedbackRealTimeTask, FinalSourceFeedbackValue, GroupId, GroupKey, IncomingCompoundSourceValue,
-    InputDescriptor, InstanceId, InternalInfoEvent, LastTouchedTargetFilter, MainMapping,
-    MappingId, MappingKey, MappingMatchedEvent, MessageCaptureEvent, MidiControlInput,
-    NormalMainTask, OscFeedbackTask, ParamSetting, PluginParams, ProcessorContext,
-    ProjectionFeedbackValue, QualifiedMappingId, RealearnControlSurfaceMainTask, RealearnTarget,
-    ReaperTarget, ReaperTargetType, SharedInstance, SharedUnit, SourceFeedbackEvent,
-    StayActiveWhenProjectInBackground, Tag, TargetControlEvent, TargetTouchEvent,
-    TargetValueChangedEvent, Unit, UnitContainer, UnitId, VirtualControlElementId, VirtualFx,
-    VirtualSource, VirtualSourceValue, LUA_MIDI_SCRIPT_SOURCE_RUNTIME_NAME,
+    lua_module_path_without_ext, AdditionalIo, Backbone, BasicSettings, CompartmentKind,
+    CompartmentParamIndex, CompartmentSettings, CompoundMappingSource, ControlContext,
+    ControlInput, ControlInputRecording, DomainEvent, DomainEventHandler, ExtendedProcessorContext,
+    FeedbackAudioHookTask, FeedbackOutput, FeedbackRealTimeTask, FinalSourceFeedbackValue, GroupId,
+    GroupKey, IncomingCompoundSourceValue, InputDescriptor, InstanceId, InternalInfoEvent,
+    LastTouchedTargetFilter, MainMapping, MappingId, MappingKey, MappingMatchedEvent,
+    MessageCaptureEvent, MidiControlInput, MidiRoutingTable, NormalMainTask, OscFeedbackTask,
+    ParamSetting, PluginParams, ProcessorContext, ProjectionFeedbackValue, QualifiedMappingId,
+    RealearnControlSurfaceMainTask, RealearnTarget, ReaperTarget, ReaperTargetType, SharedInstance,
+    SharedUnit, SourceFeedbackEvent, StayActiveWhenProjectInBackground, Tag, TargetControlEvent,
+    TargetTouchEvent, TargetValueChangedEvent, Unit, UnitContainer, UnitId,
+    VirtualControlElementId, VirtualFx, VirtualSource, VirtualSourceValue, WebSocketFeedbackValue,
+    LUA_MIDI_SCRIPT_SOURCE_RUNTIME_NAME,
+};
+use base::{
+    metrics_util, Global, NamedChannelSender, SenderToNormalThread, SenderToRealTimeThread,
 };
-use base::{Global, NamedChannelSender, SenderToNormalThread, SenderToRealTimeThread};
 use derivative::Derivative;
 use enum_map::EnumMap;
 
@@ -37,7 +40,7 @@ use base::hash_util::{NonCryptoHashMap, NonCryptoHashSet};
 use core::iter;
 use helgoboss_learn::{AbsoluteMode, ControlResult, ControlValue, UnitValue};
 use helgobox_api::persistence::{
-    FxDescriptor, MappingModification, TargetTouchCause, TrackDescriptor,
+    FxDescriptor, MappingModification, MappingTemplate, TargetTouchCause, TrackDescriptor,
     VirtualControlElementCharacter,
 };
 use helgobox_api::runtime::InstanceInfoEvent;
@@ -57,6 +60,7 @@ pub trait SessionUi {
     fn celebrate_success(&self);
     fn conditions_changed(&self);
     fn send_projection_feedback(&self, session: &UnitModel, value: ProjectionFeedbackValue);
+    fn send_web_socket_feedback(&self, session: &UnitModel, value: WebSocketFeedbackValue);
     fn mapping_matched(&self, event: MappingMatchedEvent);
     fn handle_target_control(&self, event: TargetControlEvent);
     fn handle_source_feedback(&self, event: SourceFeedbackEvent);
@@ -65,6 +69,7 @@ pub trait SessionUi {
     fn handle_everything_changed(&self, unit_model: &UnitModel);
     fn handle_unit_name_changed(&self);
     fn handle_global_control_and_feedback_state_changed(&self);
+    fn control_input_recorded(&self, session: &UnitModel, recording: ControlInputRecording);
     fn handle_affected(
         &self,
         session: &UnitModel,
@@ -91,6 +96,8 @@ pub struct UnitModel {
     pub name: Option<String>,
     pub let_matched_events_through: Prop<bool>,
     pub let_unmatched_events_through: Prop<bool>,
+    /// Applied to all MIDI events that are let through to the FX output.
+    pub midi_routing: Prop<MidiRoutingTable>,
     pub stay_active_when_project_in_background: Prop<StayActiveWhenProjectInBackground>,
     pub auto_correct_settings: Prop<bool>,
     pub real_input_logging_enabled: Prop<bool>,
@@ -102,6 +109,8 @@ pub struct UnitModel {
     pub reset_feedback_when_releasing_source: Prop<bool>,
     pub control_input: Prop<ControlInput>,
     pub feedback_output: Prop<Option<FeedbackOutput>>,
+    /// Control inputs and feedback outputs used in addition to the main ones.
+    pub additional_io: Prop<AdditionalIo>,
     pub auto_load_mode: Prop<AutoLoadMode>,
     pub auto_load_fallback_compartment: Option<CompartmentModel>,
     pub auto_load_fallback_preset_id: Option<String>,
@@ -116,6 +125,8 @@ pub struct UnitModel {
     active_main_preset_id: Option<String>,
     processor_context: ProcessorContext,
     mappings: EnumMap<CompartmentKind, Vec<SharedMapping>>,
+    /// The instances of these templates are part of `mappings`.
+    mapping_templates: EnumMap<CompartmentKind, Vec<MappingTemplate>>,
     compartment_common_lua: EnumMap<CompartmentKind, String>,
     compartment_notes: EnumMap<CompartmentKind, String>,
     default_main_group: SharedGroup,
@@ -143,6 +154,7 @@ pub struct UnitModel {
     global_preset_link_manager: Box<dyn PresetLinkManager>,
     unit_preset_link_config: FxPresetLinkConfig,
     use_unit_preset_links_only: bool,
+    control_input_recording_is_active: bool,
     // It's okay not to use Weak here because the instance lives longer than the unit.
     instance: SharedInstance,
     unit: SharedUnit,
@@ -260,6 +272,7 @@ impl UnitModel {
             unit_id,
             let_matched_events_through: prop(session_defaults::LET_MATCHED_EVENTS_THROUGH),
             let_unmatched_events_through: prop(session_defaults::LET_UNMATCHED_EVENTS_THROUGH),
+            midi_routing: Default::default(),
             stay_active_when_project_in_background: prop(
                 session_defaults::STAY_ACTIVE_WHEN_PROJECT_IN_BACKGROUND,
             ),
@@ -275,6 +288,7 @@ impl UnitModel {
             ),
             control_input: prop(initial_input),
             feedback_output: prop(initial_output),
+            additional_io: Default::default(),
             auto_load_mode: prop(session_defaults::MAIN_PRESET_AUTO_LOAD_MODE),
             auto_load_fallback_compartment: None,
             auto_load_fallback_preset_id: None,
@@ -286,6 +300,7 @@ impl UnitModel {
             active_main_preset_id: None,
             processor_context: context,
             mappings: Default::default(),
+            mapping_templates: Default::default(),
             compartment_common_lua: Default::default(),
             compartment_notes: Default::default(),
             default_main_group: Rc::new(RefCell::new(GroupModel::default_for_compartment(
@@ -311,6 +326,7 @@ impl UnitModel {
             global_preset_link_manager: Box::new(preset_link_manager),
             unit_preset_link_config: Default::default(),
             use_unit_preset_links_only: false,
+            control_input_recording_is_active: false,
             instance,
             unit,
             global_feedback_audio_hook_task_sender,
@@ -484,6 +500,13 @@ impl UnitModel {
             let mapping_source = m.source_model.create_source();
             if let (Virtual(virtual_source), Some(v)) = (&mapping_source, &virtual_source_value) {
                 virtual_source.control(v).is_some()
+            } else if let (Compound(compound_source), Some(v)) =
+                (&mapping_source, &virtual_source_value)
+            {
+                compound_source.member_reacts_to_virtual_value(v).is_some()
+                    || mapping_source
+                        .reacts_to_source_value_with(source_value)
+                        .is_some()
             } else {
                 mapping_source
                     .reacts_to_source_value_with(source_value)
@@ -536,13 +559,24 @@ impl UnitModel {
         // It's important to sync feedback device first, otherwise the initial feedback messages
         // won't arrive!
         self.sync_settings();
+        self.sync_midi_routing();
+        self.sync_additional_io();
         self.sync_upper_floor_membership();
+        self.sync_metrics_labels();
         // Now sync mappings - which includes initial feedback.
         for compartment in CompartmentKind::enum_iter() {
             self.sync_all_mappings_full(compartment);
         }
     }
 
+    fn sync_metrics_labels(&self) {
+        metrics_util::set_unit_labels(
+            self.unit_id.into(),
+            self.instance_id.into(),
+            self.unit_key().to_string(),
+        );
+    }
+
     /// Makes all autostart mappings hit the target.
     pub fn notify_realearn_unit_started(&self) {
         debug!("Sending NotifyRealearnUnitStarted");
@@ -598,12 +632,28 @@ impl UnitModel {
             .do_sync(move |s, _| {
                 s.borrow().sync_upper_floor_membership();
             });
+        // Keep labeling per-unit metrics with the current unit key.
+        when(self.unit_key.changed())
+            .with(weak_session.clone())
+            .do_sync(move |s, _| {
+                s.borrow().sync_metrics_labels();
+            });
         // Keep syncing some general settings to real-time processor.
         when(self.settings_changed())
             .with(weak_session.clone())
             .do_async(move |s, _| {
                 s.borrow().sync_settings();
             });
+        when(self.midi_routing.changed())
+            .with(weak_session.clone())
+            .do_async(move |s, _| {
+                s.borrow().sync_midi_routing();
+            });
+        when(self.additional_io.changed())
+            .with(weak_session.clone())
+            .do_async(move |s, _| {
+                s.borrow().sync_additional_io();
+            });
         // When FX is reordered, invalidate FX indexes. This is primarily for the GUI.
         // Existing GUID-tracked `Fx` instances will detect wrong index automatically.
         when(
@@ -676,9 +726,11 @@ impl UnitModel {
         self.let_matched_events_through
             .changed()
             .merge(self.let_unmatched_events_through.changed())
+            .merge(self.midi_routing.changed())
             .merge(self.stay_active_when_project_in_background.changed())
             .merge(self.control_input.changed())
             .merge(self.feedback_output.changed())
+            .merge(self.additional_io.changed())
             .merge(self.auto_correct_settings.changed())
             .merge(self.send_feedback_only_if_armed.changed())
             .merge(self.reset_feedback_when_releasing_source.changed())
@@ -1439,6 +1491,60 @@ impl UnitModel {
         self.notify_mapping_list_changed(compartment, None);
     }
 
+    /// Replaces the mapping template which has the same ID as the given one (or adds it if there's
+    /// no such template yet) and replaces its instances with the given ones.
+    ///
+    /// The new instances take the place of the first old instance or are added at the end.
+    pub fn upsert_mapping_template(
+        &mut self,
+        compartment: CompartmentKind,
+        template: MappingTemplate,
+        instances: impl Iterator<Item = MappingModel>,
+    ) {
+        self.stop_mapping_actions();
+        let mappings = &mut self.mappings[compartment];
+        let is_old_instance = |m: &SharedMapping| m.borrow().template_id() == Some(&template.id);
+        let mut index = mappings
+            .iter()
+            .position(is_old_instance)
+            .unwrap_or(mappings.len());
+        mappings.retain(|m| !is_old_instance(m));
+        for mut m in instances {
+            m.set_template_id(Some(template.id.clone()));
+            mappings.insert(index, share_mapping(m));
+            index += 1;
+        }
+        let templates = &mut self.mapping_templates[compartment];
+        if let Some(existing_template) = templates.iter_mut().find(|t| t.id == template.id) {
+            *existing_template = template;
+        } else {
+            templates.push(template);
+        }
+        self.notify_mapping_list_changed(compartment, None);
+    }
+
+    /// Replaces the mapping which has the same key as the given one or adds it at the end if there's
+    /// no such mapping yet.
+    pub fn upsert_mapping(&mut self, compartment: CompartmentKind, mapping: MappingModel) {
+        if let Some(existing_mapping) = self.find_mapping_by_key(compartment, mapping.key()) {
+            *existing_mapping.borrow_mut() = mapping;
+            self.notify_mapping_list_changed(compartment, None);
+        } else {
+            self.add_mapping(compartment, mapping);
+        }
+    }
+
+    /// Replaces the group which has the same key as the given one or adds it if there's no such
+    /// group yet.
+    pub fn upsert_group(&mut self, compartment: CompartmentKind, group: GroupModel) {
+        if let Some(existing_group) = self.find_group_by_key(compartment, group.key()).cloned() {
+            *existing_group.borrow_mut() = group;
+            self.notify_group_list_changed(compartment);
+        } else {
+            self.add_group(compartment, group);
+        }
+    }
+
     fn mapping_key_set(&self, compartment: CompartmentKind) -> NonCryptoHashSet<MappingKey> {
         self.mappings[compartment]
             .iter()
@@ -2206,6 +2312,7 @@ impl UnitModel {
                 .mappings(compartment)
                 .map(|ptr| ptr.borrow().clone())
                 .collect(),
+            mapping_templates: self.mapping_templates[compartment].clone(),
             custom_data: self
                 .unit()
                 .borrow()
@@ -2213,6 +2320,7 @@ impl UnitModel {
                 .clone(),
             common_lua: self.compartment_common_lua[compartment].clone(),
             notes: self.compartment_notes[compartment].clone(),
+            banks: self.unit().borrow().banks(compartment).clone(),
         }
     }
 
@@ -2241,6 +2349,7 @@ impl UnitModel {
             default_group.replace(model.default_group);
             self.set_groups_without_notification(compartment, model.groups);
             self.set_mappings_without_notification(compartment, model.mappings);
+            self.set_mapping_templates_without_notification(compartment, model.mapping_templates);
             let compartment_params = self.params.compartment_params_mut(compartment);
             compartment_params.reset_all();
             compartment_params.apply_given_settings(model.parameters);
@@ -2251,6 +2360,7 @@ impl UnitModel {
             self.unit
                 .borrow_mut()
                 .set_custom_compartment_data(compartment, model.custom_data);
+            self.unit.borrow_mut().set_banks(compartment, model.banks);
             self.compartment_common_lua[compartment] = model.common_lua;
             self.compartment_notes[compartment] = model.notes;
         } else {
@@ -2276,6 +2386,7 @@ impl UnitModel {
             .replace(GroupModel::default_for_compartment(compartment));
         self.set_groups_without_notification(compartment, std::iter::empty());
         self.set_mappings_without_notification(compartment, std::iter::empty());
+        self.set_mapping_templates_without_notification(compartment, vec![]);
         self.params.compartment_params_mut(compartment).reset_all();
         self.unit
             .borrow()
@@ -2284,6 +2395,9 @@ impl UnitModel {
         self.unit
             .borrow_mut()
             .set_custom_compartment_data(compartment, Default::default());
+        self.unit
+            .borrow_mut()
+            .set_banks(compartment, Default::default());
         self.compartment_common_lua[compartment] = Default::default();
         self.compartment_notes[compartment] = Default::default();
     }
@@ -2344,6 +2458,29 @@ impl UnitModel {
         self.mappings[compartment] = mappings.into_iter().map(share_mapping).collect();
     }
 
+    pub fn mapping_templates(&self, compartment: CompartmentKind) -> &[MappingTemplate] {
+        &self.mapping_templates[compartment]
+    }
+
+    pub fn find_mapping_template(
+        &self,
+        compartment: CompartmentKind,
+        template_id: &str,
+    ) -> Option<&MappingTemplate> {
+        self.mapping_templates[compartment]
+            .iter()
+            .find(|t| t.id == template_id)
+    }
+
+    /// Doesn't touch the template instances.
+    pub fn set_mapping_templates_without_notification(
+        &mut self,
+        compartment: CompartmentKind,
+        templates: Vec<MappingTemplate>,
+    ) {
+        self.mapping_templates[compartment] = templates;
+    }
+
     pub fn set_groups_without_notification(
         &mut self,
         compartment: CompartmentKind,
@@ -2369,6 +2506,33 @@ impl UnitModel {
             .send_complaining(NormalMainTask::SendAllFeedback);
     }
 
+    pub fn control_input_recording_is_active(&self) -> bool {
+        self.control_input_recording_is_active
+    }
+
+    pub fn start_recording_control_input(&mut self) {
+        self.control_input_recording_is_active = true;
+        self.normal_main_task_sender
+            .send_complaining(NormalMainTask::StartRecordingControlInput);
+    }
+
+    /// The recording arrives asynchronously via [`SessionUi::control_input_recorded`].
+    pub fn stop_recording_control_input(&mut self) {
+        self.control_input_recording_is_active = false;
+        self.normal_main_task_sender
+            .send_complaining(NormalMainTask::StopRecordingControlInput);
+    }
+
+    pub fn replay_control_input(&self, recording: ControlInputRecording, speed: f64) {
+        self.normal_main_task_sender
+            .send_complaining(NormalMainTask::StartReplayingControlInput { recording, speed });
+    }
+
+    pub fn stop_replaying_control_input(&self) {
+        self.normal_main_task_sender
+            .send_complaining(NormalMainTask::StopReplayingControlInput);
+    }
+
     pub fn log_debug_info(&self) {
         self.log_debug_info_internal();
         self.normal_main_task_sender
@@ -2601,6 +2765,20 @@ impl UnitModel {
             .send_complaining(NormalMainTask::UpdateSettings(settings));
     }
 
+    fn sync_midi_routing(&self) {
+        self.normal_main_task_sender
+            .send_complaining(NormalMainTask::UpdateMidiRouting(
+                self.midi_routing.get_ref().clone(),
+            ));
+    }
+
+    fn sync_additional_io(&self) {
+        self.normal_main_task_sender
+            .send_complaining(NormalMainTask::UpdateAdditionalIo(
+                self.additional_io.get_ref().clone(),
+            ));
+    }
+
     fn sync_persistent_mapping_processing_state(&self, mapping: &MappingModel) {
         self.normal_main_task_sender.send_complaining(
             NormalMainTask::UpdatePersistentMappingProcessingState {
@@ -2616,7 +2794,12 @@ impl UnitModel {
             .notify_mappings_in_unit_changed(self.unit_id);
         let group_data = self
             .find_group_of_mapping(m)
-            .map(|g| g.borrow().create_data())
+            .map(|g| {
+                g.borrow().create_effective_data(|id| {
+                    self.find_group_by_id(m.compartment(), id)
+                        .map(|g| g.borrow())
+                })
+            })
             .unwrap_or_default();
         let main_mapping = m.create_main_mapping(group_data);
         self.normal_main_task_sender
@@ -2674,7 +2857,7 @@ impl UnitModel {
                 let mapping = mapping.borrow();
                 let group_data = group_map
                     .get(&mapping.group_id())
-                    .map(|g| g.create_data())
+                    .map(|g| g.create_effective_data(|id| group_map.get(&id).map(|g| &**g)))
                     .unwrap_or_default();
                 mapping.create_main_mapping(group_data)
             })
@@ -2719,6 +2902,7 @@ impl UnitModel {
 impl Drop for UnitModel {
     fn drop(&mut self) {
         debug!("Dropping UnitModel...");
+        metrics_util::remove_unit_labels(self.unit_id.into());
         self.party_is_over_subject.next(());
     }
 }
@@ -2761,6 +2945,10 @@ impl DomainEventHandler for WeakUnitModel {
                 let s = session.try_borrow()?;
                 s.ui().conditions_changed()
             }
+            ControlInputRecorded(recording) => {
+                let s = session.try_borrow()?;
+                s.ui().control_input_recorded(&s, recording);
+            }
             TimeForCelebratingSuccess => {
                 let s = session.try_borrow()?;
                 s.ui().celebrate_success()
@@ -2820,6 +3008,10 @@ impl DomainEventHandler for WeakUnitModel {
                 let s = session.try_borrow()?;
                 s.ui().send_projection_feedback(&s, value);
             }
+            WebSocketFeedback(value) => {
+                let s = session.try_borrow()?;
+                s.ui().send_web_socket_feedback(&s, value);
+            }
             MappingMatched(event) => {
                 let s = session.try_borrow()?;
                 s.ui().mapping_matched(event);
@@ -2974,22 +3166,7 @@ pub type WeakUnitModel = Weak<RefCell<UnitModel>>;
 fn mappings_have_project_references<'a>(
     mut mappings: impl Iterator<Item = &'a SharedMapping>,
 ) -> bool {
-    mappings.any(mapping_has_project_references)
-}
-
-/// Checks if the given mapping has references to a project, e.g. refers to track or FX by ID.
-fn mapping_has_project_references(mapping: &SharedMapping) -> bool {
-    let mapping = mapping.borrow();
-    let target = &mapping.target_model;
-    match target.category() {
-        TargetCategory::Reaper => {
-            if target.target_type().supports_track() && target.track_type().refers_to_project() {
-                return true;
-            }
-            target.supports_fx() && target.fx_type().refers_to_project()
-        }
-        TargetCategory::Virtual => false,
-    }
+    mappings.any(|m| m.borrow().has_project_references())
 }
 
 pub fn reaper_supports_global_midi_filter() -> bool {
@@ -1,11 +1,12 @@
 use crate::domain::{
-    classify_midi_message, AudioBlockProps, ControlEvent, ControlEventTimestamp,
-    DisplayAsPrettyHex, IncomingMidiMessage, InstanceId, MidiControlInput, MidiEvent,
-    MidiMessageClassification, MidiScanResult, MidiScanner, RealTimeProcessor,
+    classify_midi_message, is_midi_ci_message_addressed_to, AudioBlockProps, AudioBlockStart,
+    ControlEvent, ControlEventTimestamp, DisplayAsPrettyHex, IncomingMidiMessage, InstanceId,
+    MidiCiMessageType, MidiCiMuid, MidiControlInput, MidiEvent, MidiMessageClassification,
+    MidiScanResult, MidiScanner, OwnedIncomingMidiMessage, RealTimeProcessor,
     SharedRealTimeInstance, UnitId,
 };
 use base::byte_pattern::{BytePattern, PatternByte};
-use base::metrics_util::{measure_time, record_duration};
+use base::metrics_util::{measure_time, measure_unit_time, record_duration};
 use base::non_blocking_lock;
 use helgoboss_learn::{AbstractTimestamp, MidiSourceValue, RawMidiEvent, RawMidiEvents};
 use helgoboss_midi::{DataEntryByteOrder, RawShortMessage, ShortMessage, ShortMessageType};
@@ -54,6 +55,33 @@ pub struct MidiDeviceInquiryReply {
     pub message: ArrayVec<[u8; RawMidiEvent::MAX_LENGTH]>,
 }
 
+#[derive(Debug)]
+pub struct MidiCiTransactionCommand {
+    pub output_device_id: MidiOutputDeviceId,
+    pub input_device_id: Option<MidiInputDeviceId>,
+    /// The MIDI-CI message to be sent.
+    pub request: RawMidiEvent,
+    /// Our own MUID. Only replies addressed to it are considered.
+    pub muid: MidiCiMuid,
+    pub reply_type: MidiCiMessageType,
+    pub sender: async_channel::Sender<MidiCiTransactionReply>,
+}
+
+#[derive(Debug)]
+struct MidiCiTransactionTask {
+    input_device_id: Option<MidiInputDeviceId>,
+    muid: MidiCiMuid,
+    reply_type: MidiCiMessageType,
+    sender: async_channel::Sender<MidiCiTransactionReply>,
+    request_sent_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct MidiCiTransactionReply {
+    pub input_device_id: MidiInputDeviceId,
+    pub message: ArrayVec<[u8; RawMidiEvent::MAX_LENGTH]>,
+}
+
 impl Display for MidiDeviceInquiryReply {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         DisplayAsPrettyHex(self.message.as_slice()).fmt(f)
@@ -83,6 +111,17 @@ pub enum NormalAudioHookTask {
     ///
     /// Gives up immediately if the output device or optional input device is not open.
     RequestMidiDeviceIdentity(RequestMidiDeviceIdentityCommand),
+    /// Instructs the audio hook to send a MIDI-CI message to the given output device and to
+    /// forward all MIDI-CI replies of the given type which are addressed to the given MUID.
+    ///
+    /// Gives up after about one second (by dropping the sender) or as soon as the receiver is
+    /// dropped. Replaces any ongoing MIDI-CI transaction.
+    ///
+    /// Gives up immediately if the output device is not open.
+    MidiCiTransaction(MidiCiTransactionCommand),
+    /// Feeds a recorded MIDI message into the real-time processor of the given unit, as if it had
+    /// arrived from the given MIDI input device (`None` means FX input).
+    ReplayMidi(UnitId, Option<MidiInputDeviceId>, OwnedIncomingMidiMessage),
     #[cfg(feature = "playtime")]
     PlaytimeClipEngineCommand(playtime_clip_engine::rt::audio_hook::PlaytimeAudioHookCommand),
 }
@@ -128,6 +167,7 @@ pub fn send_midi_device_feedback(
 pub struct RealearnAudioHook {
     state: AudioHookState,
     midi_device_inquiry_task: Option<MidiDeviceInquiryTask>,
+    midi_ci_transaction_task: Option<MidiCiTransactionTask>,
     real_time_instances: SmallVec<[(InstanceId, SharedRealTimeInstance); 256]>,
     real_time_processors: SmallVec<[(UnitId, SharedRealTimeProcessor); 256]>,
     normal_task_receiver: crossbeam_channel::Receiver<NormalAudioHookTask>,
@@ -159,6 +199,7 @@ impl RealearnAudioHook {
         Self {
             state: AudioHookState::Normal,
             midi_device_inquiry_task: None,
+            midi_ci_transaction_task: None,
             real_time_instances: Default::default(),
             real_time_processors: Default::default(),
             normal_task_receiver,
@@ -256,6 +297,7 @@ impl RealearnAudioHook {
         self.call_real_time_instances(block_props);
         // Process some tasks
         self.check_for_midi_device_inquiry_response();
+        self.check_for_midi_ci_replies();
     }
 
     fn on_post(&mut self, args: OnAudioBufferArgs) {
@@ -309,11 +351,11 @@ impl RealearnAudioHook {
     fn call_real_time_processors(&mut self, block_props: AudioBlockProps, might_be_rebirth: bool) {
         match &mut self.state {
             AudioHookState::Normal => {
-                let timestamp = ControlEventTimestamp::now();
+                let block_start = AudioBlockStart::now();
                 self.call_real_time_processors_in_normal_state(
                     block_props,
                     might_be_rebirth,
-                    timestamp,
+                    block_start,
                 );
             }
             AudioHookState::LearningSource {
@@ -347,7 +389,7 @@ impl RealearnAudioHook {
         &mut self,
         block_props: AudioBlockProps,
         might_be_rebirth: bool,
-        timestamp: ControlEventTimestamp,
+        block_start: AudioBlockStart,
     ) {
         // 1a. Drive real-time processors and determine used MIDI devices "on the go".
         //
@@ -360,7 +402,7 @@ impl RealearnAudioHook {
         //
         let mut midi_dev_id_is_used = [false; MidiInputDeviceId::MAX_DEVICE_COUNT as usize];
         let mut midi_devs_used_at_all = false;
-        for (_, p) in self.real_time_processors.iter() {
+        for (unit_id, p) in self.real_time_processors.iter() {
             // Since 1.12.0, we "drive" each plug-in instance's real-time processor
             // primarily by the global audio hook. See https://github.com/helgoboss/helgobox/issues/84 why this is
             // better. We also call it by the plug-in `process()` method though in order
@@ -368,18 +410,32 @@ impl RealearnAudioHook {
             // stop doing so synchronously if the plug-in is
             // gone.
             let mut guard = p.lock_recover();
-            guard.run_from_audio_hook_all(block_props, might_be_rebirth, timestamp);
+            measure_unit_time("helgobox.rt.unit.run", (*unit_id).into(), || {
+                guard.run_from_audio_hook_all(
+                    block_props,
+                    might_be_rebirth,
+                    block_start.timestamp(),
+                );
+            });
             if guard.control_is_globally_enabled() {
                 if let MidiControlInput::Device(dev_id) = guard.midi_control_input() {
                     midi_dev_id_is_used[dev_id.get() as usize] = true;
                     midi_devs_used_at_all = true;
                 }
+                for dev_id in guard.additional_midi_input_devices() {
+                    midi_dev_id_is_used[dev_id.get() as usize] = true;
+                    midi_devs_used_at_all = true;
+                }
             }
         }
         // 1b. Forward MIDI events from MIDI devices to ReaLearn instances and filter
         //     them globally if desired by the instance.
         if midi_devs_used_at_all {
-            self.distribute_midi_events_to_processors(block_props, &midi_dev_id_is_used, timestamp);
+            self.distribute_midi_events_to_processors(
+                block_props,
+                &midi_dev_id_is_used,
+                block_start,
+            );
         }
     }
 
@@ -387,7 +443,7 @@ impl RealearnAudioHook {
         &mut self,
         block_props: AudioBlockProps,
         midi_dev_id_is_used: &[bool; MidiInputDeviceId::MAX_DEVICE_COUNT as usize],
-        timestamp: ControlEventTimestamp,
+        block_start: AudioBlockStart,
     ) {
         for dev_id in 0..MidiInputDeviceId::MAX_DEVICE_COUNT {
             if !midi_dev_id_is_used[dev_id as usize] {
@@ -406,13 +462,23 @@ impl RealearnAudioHook {
                                 Err(_) => continue,
                                 Ok(e) => e,
                             };
+                        let timestamp =
+                            block_start.event_timestamp(our_event.offset(), block_props.frame_rate);
                         let our_event = ControlEvent::new(our_event, timestamp);
                         let mut filter_out_event = false;
-                        for (_, p) in self.real_time_processors.iter() {
+                        for (unit_id, p) in self.real_time_processors.iter() {
                             let mut guard = p.lock_recover();
                             if guard.control_is_globally_enabled()
-                                && guard.midi_control_input() == MidiControlInput::Device(dev_id)
-                                && guard.process_incoming_midi_from_audio_hook(our_event)
+                                && guard.listens_to_midi_device(dev_id)
+                                && measure_unit_time(
+                                    "helgobox.rt.unit.midi",
+                                    (*unit_id).into(),
+                                    || {
+                                        guard.process_incoming_midi_from_audio_hook(
+                                            our_event, dev_id,
+                                        )
+                                    },
+                                )
                             {
                                 filter_out_event = true;
                             }
@@ -428,6 +494,37 @@ impl RealearnAudioHook {
         }
     }
 
+    fn replay_midi(
+        &self,
+        unit_id: UnitId,
+        device: Option<MidiInputDeviceId>,
+        msg: &OwnedIncomingMidiMessage,
+    ) {
+        let Some((_, p)) = self
+            .real_time_processors
+            .iter()
+            .find(|(id, _)| *id == unit_id)
+        else {
+            return;
+        };
+        let mut guard = p.lock_recover();
+        if !guard.control_is_globally_enabled() {
+            return;
+        }
+        let listens = match device {
+            None => guard.midi_control_input() == MidiControlInput::FxInput,
+            Some(dev_id) => guard.listens_to_midi_device(dev_id),
+        };
+        if !listens {
+            return;
+        }
+        let event = ControlEvent::new(
+            MidiEvent::without_offset(msg.as_incoming()),
+            ControlEventTimestamp::now(),
+        );
+        guard.process_replayed_midi_from_audio_hook(event, device);
+    }
+
     fn process_midi_device_inquiry_command(
         &mut self,
         command: RequestMidiDeviceIdentityCommand,
@@ -460,6 +557,51 @@ impl RealearnAudioHook {
         }
     }
 
+    fn process_midi_ci_transaction_command(
+        &mut self,
+        command: MidiCiTransactionCommand,
+    ) -> Result<(), &'static str> {
+        let MidiCiTransactionCommand {
+            output_device_id,
+            input_device_id,
+            request,
+            muid,
+            reply_type,
+            sender,
+        } = command;
+        let output_dev = Reaper::get().midi_output_device_by_id(output_device_id);
+        output_dev.with_midi_output(|output| -> Result<(), &'static str> {
+            let output = output.ok_or("MIDI output device not open")?;
+            tracing::debug!(
+                msg = "Sending MIDI-CI message...",
+                ?output_device_id,
+                ?reply_type
+            );
+            output.send_msg(request, SendMidiTime::Instantly);
+            Ok(())
+        })?;
+        let task = MidiCiTransactionTask {
+            input_device_id,
+            muid,
+            reply_type,
+            sender,
+            request_sent_at: Instant::now(),
+        };
+        self.midi_ci_transaction_task = Some(task);
+        Ok(())
+    }
+
+    fn check_for_midi_ci_replies(&mut self) {
+        let Some(task) = self.midi_ci_transaction_task.as_ref() else {
+            // No task
+            return;
+        };
+        if !task.check_for_replies() {
+            // Task done
+            self.midi_ci_transaction_task = None;
+        }
+    }
+
     fn process_normal_commands(&mut self, block_props: AudioBlockProps) {
         use NormalAudioHookTask::*;
         let mut count = 0;
@@ -494,6 +636,12 @@ impl RealearnAudioHook {
                 RequestMidiDeviceIdentity(command) => {
                     let _ = self.process_midi_device_inquiry_command(command);
                 }
+                MidiCiTransaction(command) => {
+                    let _ = self.process_midi_ci_transaction_command(command);
+                }
+                ReplayMidi(unit_id, device, msg) => {
+                    self.replay_midi(unit_id, device, &msg);
+                }
                 #[cfg(feature = "playtime")]
                 PlaytimeClipEngineCommand(command) => {
                     let _ = self
@@ -628,6 +776,60 @@ impl MidiDeviceInquiryTask {
     }
 }
 
+impl MidiCiTransactionTask {
+    /// Returns `false` if task not necessary anymore.
+    pub fn check_for_replies(&self) -> bool {
+        if self.sender.is_closed() {
+            // Receiver got what it wanted
+            return false;
+        }
+        // Give up if waited too long for replies.
+        if self.request_sent_at.elapsed() > Duration::from_secs(1) {
+            tracing::debug!(msg = "Stopped waiting for MIDI-CI replies after timeout");
+            return false;
+        }
+        if let Some(id) = self.input_device_id {
+            let dev = Reaper::get().midi_input_device_by_id(id);
+            self.process_input_dev(dev)
+        } else {
+            Reaper::get()
+                .midi_input_devices()
+                .all(|dev| self.process_input_dev(dev))
+        }
+    }
+
+    /// Returns `false` if task not necessary anymore.
+    fn process_input_dev(&self, dev: MidiInputDevice) -> bool {
+        dev.with_midi_input(|mi| {
+            let Some(mi) = mi else {
+                return true;
+            };
+            for evt in mi.get_read_buf() {
+                let msg = evt.message();
+                if msg.r#type() != ShortMessageType::SystemExclusiveStart {
+                    continue;
+                }
+                let bytes = msg.as_slice();
+                if !is_midi_ci_message_addressed_to(bytes, self.reply_type, self.muid) {
+                    continue;
+                }
+                let Ok(message) = ArrayVec::try_from(bytes) else {
+                    continue;
+                };
+                let reply = MidiCiTransactionReply {
+                    input_device_id: dev.id(),
+                    message,
+                };
+                tracing::debug!(msg = "Received MIDI-CI reply", ?reply);
+                if let Err(async_channel::TrySendError::Closed(_)) = self.sender.try_send(reply) {
+                    return false;
+                }
+            }
+            true
+        })
+    }
+}
+
 const MIDI_DEVICE_INQUIRY_REQUEST: &[u8] = &[0xF0, 0x7E, 0x7F, 0x06, 0x01, 0xF7];
 
 static MIDI_DEVICE_INQUIRY_REPLY_PATTERN: OnceLock<BytePattern> = OnceLock::new();
@@ -0,0 +1,440 @@
+use helgoboss_learn::{ControlValue, Fraction, SourceCharacter, UnitValue};
+use helgobox_api::persistence::{AudioAnalysis, AudioLevelDetector};
+use reaper_medium::Hz;
+use std::time::Duration;
+
+/// Levels below this are treated as silence.
+pub const AUDIO_SOURCE_MIN_DB: f64 = -60.0;
+
+/// Threshold used for onset and pitch analysis if none is set.
+const DEFAULT_DETECTION_THRESHOLD_DB: f64 = -40.0;
+
+/// Level changes smaller than this are not reported (in order to not flood the glue section).
+const LEVEL_EPSILON: f64 = 0.001;
+
+/// An onset is detected if the fast envelope exceeds the slow reference envelope by this factor.
+const ONSET_RATIO: f64 = 2.0;
+
+/// Time constant of the slow reference envelope used for onset detection.
+const ONSET_REFERENCE_TIME: Duration = Duration::from_millis(250);
+
+/// Minimum time between two onsets.
+const ONSET_MIN_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Length of the window over which zero crossings are counted for pitch detection.
+const PITCH_WINDOW: Duration = Duration::from_millis(50);
+
+/// Signal amplitude which needs to be exceeded in order to count as zero crossing.
+///
+/// Prevents that low-level noise is counted as crossing.
+const ZERO_CROSSING_HYSTERESIS: f64 = 0.001;
+
+const HIGHEST_NOTE_NUMBER: u32 = 127;
+
+/// Gives access to the audio samples of one block, channel by channel.
+pub trait AudioInput {
+    fn channel_count(&self) -> usize;
+
+    fn channel_data(&self, channel_index: usize) -> &[f64];
+}
+
+impl AudioInput for vst::buffer::Inputs<'_, f64> {
+    fn channel_count(&self) -> usize {
+        self.len()
+    }
+
+    fn channel_data(&self, channel_index: usize) -> &[f64] {
+        self.get(channel_index)
+    }
+}
+
+impl AudioInput for [&[f64]] {
+    fn channel_count(&self) -> usize {
+        self.len()
+    }
+
+    fn channel_data(&self, channel_index: usize) -> &[f64] {
+        self[channel_index]
+    }
+}
+
+/// A source which analyzes the audio input of the ReaLearn FX.
+///
+/// Depending on the analysis, it emits the level of the input (continuous, or on/off if a
+/// threshold is set), a short button press on each detected onset or the detected pitch as
+/// MIDI note number. It's processed in the real-time thread, once per audio block.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AudioSource {
+    settings: AudioSourceSettings,
+    state: AudioSourceState,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AudioSourceSettings {
+    /// Index of the first analyzed input channel.
+    pub channel: u32,
+    pub channel_count: u32,
+    pub detector: AudioLevelDetector,
+    pub attack: Duration,
+    pub release: Duration,
+    /// Level: Turns the source into a gate.
+    ///
+    /// Onset and pitch: Minimum level.
+    pub threshold_db: Option<f64>,
+    pub analysis: AudioAnalysis,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+struct AudioSourceState {
+    /// Follows the peak amplitude (peak detector) or the mean power (RMS detector).
+    envelope: f64,
+    /// Follows the same input as `envelope` but slowly. Reference for onset detection.
+    reference_envelope: f64,
+    gate_is_open: bool,
+    onset_is_on: bool,
+    samples_since_onset: u64,
+    signal_is_positive: bool,
+    zero_crossing_count: u64,
+    pitch_window_sample_count: u64,
+    last_emitted_value: Option<ControlValue>,
+}
+
+impl AudioSource {
+    pub fn new(settings: AudioSourceSettings) -> Self {
+        Self {
+            settings,
+            state: Default::default(),
+        }
+    }
+
+    pub fn settings(&self) -> &AudioSourceSettings {
+        &self.settings
+    }
+
+    pub fn on_deactivate(&mut self) {
+        self.state = Default::default();
+    }
+
+    pub fn character(&self) -> SourceCharacter {
+        match self.settings.analysis {
+            AudioAnalysis::Level if self.settings.threshold_db.is_none() => {
+                SourceCharacter::RangeElement
+            }
+            AudioAnalysis::Level | AudioAnalysis::Onset => SourceCharacter::MomentaryButton,
+            AudioAnalysis::Pitch => SourceCharacter::RangeElement,
+        }
+    }
+
+    pub fn max_discrete_value(&self) -> Option<u32> {
+        match self.settings.analysis {
+            AudioAnalysis::Pitch => Some(HIGHEST_NOTE_NUMBER),
+            AudioAnalysis::Level | AudioAnalysis::Onset => None,
+        }
+    }
+
+    /// Analyzes the given block of audio and returns a control value if there's something new.
+    ///
+    /// Emits at most one value per block. Must not allocate because it's called in the real-time
+    /// thread.
+    pub fn process<I: AudioInput + ?Sized>(
+        &mut self,
+        input: &I,
+        sample_rate: Hz,
+    ) -> Option<ControlValue> {
+        let first_channel = self.settings.channel as usize;
+        let end_channel =
+            (first_channel + self.settings.channel_count as usize).min(input.channel_count());
+        if first_channel >= end_channel {
+            return None;
+        }
+        let sample_rate = sample_rate.get();
+        let attack_coefficient = smoothing_coefficient(self.settings.attack, sample_rate);
+        let release_coefficient = smoothing_coefficient(self.settings.release, sample_rate);
+        let reference_coefficient = smoothing_coefficient(ONSET_REFERENCE_TIME, sample_rate);
+        let channel_count = (end_channel - first_channel) as f64;
+        let frame_count = (first_channel..end_channel)
+            .map(|i| input.channel_data(i).len())
+            .min()
+            .unwrap_or(0);
+        let state = &mut self.state;
+        for frame in 0..frame_count {
+            let mut peak = 0.0f64;
+            let mut power_sum = 0.0;
+            let mut mono_sum = 0.0;
+            for channel in first_channel..end_channel {
+                let sample = input.channel_data(channel)[frame];
+                peak = peak.max(sample.abs());
+                power_sum += sample * sample;
+                mono_sum += sample;
+            }
+            let detector_input = match self.settings.detector {
+                AudioLevelDetector::Peak => peak,
+                AudioLevelDetector::Rms => power_sum / channel_count,
+            };
+            let coefficient = if detector_input > state.envelope {
+                attack_coefficient
+            } else {
+                release_coefficient
+            };
+            state.envelope = smooth(state.envelope, detector_input, coefficient);
+            state.reference_envelope = smooth(
+                state.reference_envelope,
+                detector_input,
+                reference_coefficient,
+            );
+            let mono = mono_sum / channel_count;
+            if state.signal_is_positive && mono < -ZERO_CROSSING_HYSTERESIS {
+                state.signal_is_positive = false;
+                state.zero_crossing_count += 1;
+            } else if !state.signal_is_positive && mono > ZERO_CROSSING_HYSTERESIS {
+                state.signal_is_positive = true;
+                state.zero_crossing_count += 1;
+            }
+        }
+        state.samples_since_onset += frame_count as u64;
+        state.pitch_window_sample_count += frame_count as u64;
+        let level_db = amplitude_to_db(self.envelope_amplitude(self.state.envelope));
+        match self.settings.analysis {
+            AudioAnalysis::Level => match self.settings.threshold_db {
+                None => {
+                    let value = ControlValue::AbsoluteContinuous(db_to_unit_value(level_db));
+                    self.emit_if_changed(value)
+                }
+                Some(threshold_db) => {
+                    let gate_is_open = level_db >= threshold_db;
+                    if gate_is_open == self.state.gate_is_open {
+                        return None;
+                    }
+                    self.state.gate_is_open = gate_is_open;
+                    Some(on_off_value(gate_is_open))
+                }
+            },
+            AudioAnalysis::Onset => {
+                if self.state.onset_is_on {
+                    // An onset is reported as a short button press, released with the next block
+                    self.state.onset_is_on = false;
+                    return Some(on_off_value(false));
+                }
+                let reference_db =
+                    amplitude_to_db(self.envelope_amplitude(self.state.reference_envelope));
+                let min_interval_samples = (ONSET_MIN_INTERVAL.as_secs_f64() * sample_rate) as u64;
+                let is_onset = level_db >= self.detection_threshold_db()
+                    && level_db - reference_db >= amplitude_to_db(ONSET_RATIO)
+                    && self.state.samples_since_onset >= min_interval_samples;
+                if !is_onset {
+                    return None;
+                }
+                self.state.onset_is_on = true;
+                self.state.samples_since_onset = 0;
+                Some(on_off_value(true))
+            }
+            AudioAnalysis::Pitch => {
+                let window_sample_count = (PITCH_WINDOW.as_secs_f64() * sample_rate) as u64;
+                if self.state.pitch_window_sample_count < window_sample_count.max(1) {
+                    return None;
+                }
+                let zero_crossing_count = self.state.zero_crossing_count;
+                let sample_count = self.state.pitch_window_sample_count;
+                self.state.zero_crossing_count = 0;
+                self.state.pitch_window_sample_count = 0;
+                if level_db < self.detection_threshold_db() || zero_crossing_count == 0 {
+                    return None;
+                }
+                // Each period has two zero crossings
+                let frequency =
+                    zero_crossing_count as f64 / 2.0 * sample_rate / sample_count as f64;
+                let note_number = (69.0 + 12.0 * (frequency / 440.0).log2())
+                    .round()
+                    .clamp(0.0, HIGHEST_NOTE_NUMBER as f64)
+                    as u32;
+                let value =
+                    ControlValue::AbsoluteDiscrete(Fraction::new(note_number, HIGHEST_NOTE_NUMBER));
+                self.emit_if_changed(value)
+            }
+        }
+    }
+
+    fn detection_threshold_db(&self) -> f64 {
+        self.settings
+            .threshold_db
+            .unwrap_or(DEFAULT_DETECTION_THRESHOLD_DB)
+    }
+
+    fn envelope_amplitude(&self, envelope: f64) -> f64 {
+        match self.settings.detector {
+            AudioLevelDetector::Peak => envelope,
+            AudioLevelDetector::Rms => envelope.sqrt(),
+        }
+    }
+
+    fn emit_if_changed(&mut self, value: ControlValue) -> Option<ControlValue> {
+        if let Some(last_value) = self.state.last_emitted_value {
+            let is_same = match (last_value, value) {
+                (ControlValue::AbsoluteContinuous(v1), ControlValue::AbsoluteContinuous(v2)) => {
+                    (v1.get() - v2.get()).abs() < LEVEL_EPSILON
+                }
+                (v1, v2) => v1 == v2,
+            };
+            if is_same {
+                return None;
+            }
+        }
+        self.state.last_emitted_value = Some(value);
+        Some(value)
+    }
+}
+
+/// Coefficient of a one-pole smoothing filter which reaches ~63% of a step within the given time.
+fn smoothing_coefficient(time: Duration, sample_rate: f64) -> f64 {
+    let samples = time.as_secs_f64() * sample_rate;
+    if samples <= 0.0 {
+        return 0.0;
+    }
+    (-1.0 / samples).exp()
+}
+
+fn smooth(current: f64, target: f64, coefficient: f64) -> f64 {
+    coefficient * current + (1.0 - coefficient) * target
+}
+
+fn amplitude_to_db(amplitude: f64) -> f64 {
+    if amplitude <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    20.0 * amplitude.log10()
+}
+
+/// Maps the range from [`AUDIO_SOURCE_MIN_DB`] to 0 dB linearly to the unit interval.
+fn db_to_unit_value(db: f64) -> UnitValue {
+    UnitValue::new_clamped((db - AUDIO_SOURCE_MIN_DB) / -AUDIO_SOURCE_MIN_DB)
+}
+
+fn on_off_value(on: bool) -> ControlValue {
+    let value = if on { UnitValue::MAX } else { UnitValue::MIN };
+    ControlValue::AbsoluteContinuous(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::TAU;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+    const BLOCK_SIZE: usize = 512;
+
+    #[test]
+    fn level() {
+        // Given
+        let mut source = AudioSource::new(settings(AudioAnalysis::Level, None));
+        // When
+        let values = process_blocks(&mut source, 50, |_| sine(440.0, 0.5));
+        // Then
+        let last_value = values.iter().flatten().last().unwrap();
+        // 0.5 is around -6 dB
+        let unit_value = last_value.to_unit_value().unwrap().get();
+        assert!((unit_value - 0.9).abs() < 0.02, "{unit_value}");
+    }
+
+    #[test]
+    fn level_rms() {
+        // Given
+        let mut source = AudioSource::new(AudioSourceSettings {
+            detector: AudioLevelDetector::Rms,
+            attack: Duration::from_millis(100),
+            ..settings(AudioAnalysis::Level, None)
+        });
+        // When
+        let values = process_blocks(&mut source, 50, |_| sine(440.0, 0.5));
+        // Then
+        let last_value = values.iter().flatten().last().unwrap();
+        // RMS of a sine with amplitude 0.5 is around -9 dB
+        let unit_value = last_value.to_unit_value().unwrap().get();
+        assert!((unit_value - 0.85).abs() < 0.02, "{unit_value}");
+    }
+
+    #[test]
+    fn gate() {
+        // Given
+        let mut source = AudioSource::new(settings(AudioAnalysis::Level, Some(-20.0)));
+        // When
+        let silent_values = process_blocks(&mut source, 10, |_| silence());
+        let loud_values = process_blocks(&mut source, 10, |_| sine(440.0, 0.5));
+        let release_values = process_blocks(&mut source, 50, |_| silence());
+        // Then
+        assert!(silent_values.iter().all(Option::is_none));
+        assert_eq!(
+            loud_values.into_iter().flatten().collect::<Vec<_>>(),
+            vec![on_off_value(true)]
+        );
+        assert_eq!(
+            release_values.into_iter().flatten().collect::<Vec<_>>(),
+            vec![on_off_value(false)]
+        );
+    }
+
+    #[test]
+    fn onset() {
+        // Given
+        let mut source = AudioSource::new(settings(AudioAnalysis::Onset, None));
+        // When
+        process_blocks(&mut source, 20, |_| silence());
+        let values = process_blocks(&mut source, 3, |_| sine(440.0, 0.5));
+        // Then
+        assert_eq!(
+            values,
+            vec![Some(on_off_value(true)), Some(on_off_value(false)), None]
+        );
+    }
+
+    #[test]
+    fn pitch() {
+        // Given
+        let mut source = AudioSource::new(settings(AudioAnalysis::Pitch, None));
+        // When
+        let values = process_blocks(&mut source, 20, |_| sine(440.0, 0.5));
+        // Then
+        let last_value = values.iter().flatten().last().unwrap();
+        assert_eq!(
+            *last_value,
+            ControlValue::AbsoluteDiscrete(Fraction::new(69, 127))
+        );
+    }
+
+    fn settings(analysis: AudioAnalysis, threshold_db: Option<f64>) -> AudioSourceSettings {
+        AudioSourceSettings {
+            channel: 0,
+            channel_count: 2,
+            detector: AudioLevelDetector::Peak,
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(100),
+            threshold_db,
+            analysis,
+        }
+    }
+
+    /// Returns a function which generates the sample at the given absolute frame index.
+    fn sine(frequency: f64, amplitude: f64) -> impl Fn(usize) -> f64 {
+        move |frame| amplitude * (TAU * frequency * frame as f64 / SAMPLE_RATE).sin()
+    }
+
+    fn silence() -> impl Fn(usize) -> f64 {
+        |_| 0.0
+    }
+
+    fn process_blocks<F: Fn(usize) -> f64>(
+        source: &mut AudioSource,
+        block_count: usize,
+        create_signal: impl Fn(usize) -> F,
+    ) -> Vec<Option<ControlValue>> {
+        (0..block_count)
+            .map(|block_index| {
+                let signal = create_signal(block_index);
+                let block: Vec<f64> = (0..BLOCK_SIZE)
+                    .map(|i| signal(block_index * BLOCK_SIZE + i))
+                    .collect();
+                let input: [&[f64]; 2] = [&block, &block];
+                source.process(&input[..], Hz::new_panic(SAMPLE_RATE))
+            })
+            .collect()
+    }
+}
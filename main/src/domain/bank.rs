@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+/// A named bank within a compartment, e.g. "Tracks 1-8".
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct BankDefinition {
+    pub name: String,
+}
+
+/// The banks of one compartment.
+///
+/// Target descriptors which are marked as bank-relative add the offset of the current bank to
+/// their index. That way one mapping can serve all banks.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct CompartmentBanks {
+    /// Number of tracks, FXs or parameters covered by one bank.
+    pub size: u32,
+    /// The banks in order. The position of a bank in this list is its bank index.
+    pub banks: Vec<BankDefinition>,
+}
+
+impl CompartmentBanks {
+    pub fn count(&self) -> u32 {
+        self.banks.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.banks.is_empty()
+    }
+
+    /// Makes sure the given bank index refers to an existing bank (or is 0 if there are no banks).
+    pub fn clamp_index(&self, index: u32) -> u32 {
+        index.min(self.count().saturating_sub(1))
+    }
+
+    /// Returns the index offset which bank-relative descriptors should apply for the given bank.
+    pub fn offset(&self, index: u32) -> u32 {
+        self.clamp_index(index).saturating_mul(self.size)
+    }
+
+    pub fn name(&self, index: u32) -> Option<&str> {
+        let bank = self.banks.get(index as usize)?;
+        Some(&bank.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn banks(size: u32, count: usize) -> CompartmentBanks {
+        CompartmentBanks {
+            size,
+            banks: (0..count)
+                .map(|i| BankDefinition {
+                    name: format!("Bank {}", i + 1),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn offset() {
+        // Given
+        let banks = banks(8, 16);
+        // When
+        // Then
+        assert_eq!(banks.offset(0), 0);
+        assert_eq!(banks.offset(1), 8);
+        assert_eq!(banks.offset(15), 120);
+        assert_eq!(banks.offset(16), 120);
+        assert_eq!(banks.name(15), Some("Bank 16"));
+        assert_eq!(banks.name(16), None);
+    }
+
+    #[test]
+    fn no_banks() {
+        // Given
+        let banks = CompartmentBanks::default();
+        // When
+        // Then
+        assert!(banks.is_empty());
+        assert_eq!(banks.clamp_index(3), 0);
+        assert_eq!(banks.offset(3), 0);
+    }
+}
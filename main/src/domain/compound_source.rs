@@ -0,0 +1,515 @@
+use crate::domain::{
+    ControlEventTimestamp, RealearnSourceContext, VirtualFeedbackValue, VirtualSource,
+    VirtualSourceValue,
+};
+use helgoboss_learn::{
+    ControlValue, FeedbackValue, MidiSource, MidiSourceValue, RawMidiEvent, RawMidiEvents,
+    UnitValue,
+};
+use helgoboss_midi::{DataEntryByteOrder, RawShortMessage, ShortMessage};
+use helgobox_api::persistence::CompoundSourceKind;
+use smallvec::SmallVec;
+use std::borrow::Cow;
+use std::time::Duration;
+
+/// A source which combines several member sources into one button-like source.
+///
+/// Depending on the kind, it fires when all members are held at the same time (combinations and
+/// note chords) or when the members are pressed one after another (key sequences). It always
+/// emits an "on" value when it fires and an "off" value as soon as the combination is released.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CompoundSource {
+    settings: CompoundSourceSettings,
+    members: CompoundSourceMembers,
+    state: CompoundSourceState,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CompoundSourceSettings {
+    pub kind: CompoundSourceKind,
+    /// Combination: Maximum time between the first and the last member press.
+    ///
+    /// Sequence: Maximum time between two consecutive member presses.
+    pub timeout: Option<Duration>,
+}
+
+/// The members of a compound source.
+///
+/// Members of one compound source must all be of the same type because MIDI sources are
+/// processed in the real-time thread and virtual sources in the main thread.
+#[derive(Clone, PartialEq, Debug)]
+pub enum CompoundSourceMembers {
+    Midi(Vec<MidiSource>),
+    Virtual(Vec<VirtualSource>),
+}
+
+impl CompoundSourceMembers {
+    pub fn len(&self) -> usize {
+        match self {
+            CompoundSourceMembers::Midi(members) => members.len(),
+            CompoundSourceMembers::Virtual(members) => members.len(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+struct CompoundSourceState {
+    /// Combination: Which members are currently held.
+    pressed: Vec<bool>,
+    /// Combination: Time of the press which started the current combination attempt.
+    ///
+    /// Sequence: Time of the last press which advanced the sequence.
+    reference_press: Option<ControlEventTimestamp>,
+    /// Sequence: Index of the member which is expected to be pressed next.
+    sequence_position: usize,
+    /// Whether the compound source has fired and not been released yet.
+    is_on: bool,
+}
+
+impl CompoundSource {
+    pub fn new(settings: CompoundSourceSettings, members: CompoundSourceMembers) -> Self {
+        let state = CompoundSourceState {
+            pressed: vec![false; members.len()],
+            ..Default::default()
+        };
+        Self {
+            settings,
+            members,
+            state,
+        }
+    }
+
+    pub fn settings(&self) -> &CompoundSourceSettings {
+        &self.settings
+    }
+
+    pub fn members(&self) -> &CompoundSourceMembers {
+        &self.members
+    }
+
+    /// Resets the state without allocating (might be called in the real-time thread).
+    pub fn on_deactivate(&mut self) {
+        self.state.reset();
+    }
+
+    /// Returns the control value of the first MIDI member which reacts to the given value.
+    ///
+    /// In contrast to [`Self::control_midi`], this doesn't change the state.
+    pub fn member_reacts_to_midi_value(
+        &self,
+        value: &MidiSourceValue<RawShortMessage>,
+    ) -> Option<ControlValue> {
+        let CompoundSourceMembers::Midi(members) = &self.members else {
+            return None;
+        };
+        members.iter().find_map(|m| m.control(value))
+    }
+
+    /// Returns the control value of the first virtual member which reacts to the given value.
+    ///
+    /// In contrast to [`Self::control_virtual`], this doesn't change the state.
+    pub fn member_reacts_to_virtual_value(
+        &self,
+        value: &VirtualSourceValue,
+    ) -> Option<ControlValue> {
+        let CompoundSourceMembers::Virtual(members) = &self.members else {
+            return None;
+        };
+        members.iter().find_map(|m| m.control(value))
+    }
+
+    /// Creates one combined MIDI feedback value which sends the feedback to all MIDI members.
+    pub fn midi_feedback(
+        &self,
+        feedback_value: Cow<FeedbackValue>,
+        source_context: RealearnSourceContext,
+    ) -> Option<MidiSourceValue<'static, RawShortMessage>> {
+        let CompoundSourceMembers::Midi(members) = &self.members else {
+            return None;
+        };
+        let events: RawMidiEvents = members
+            .iter()
+            .filter_map(|m| {
+                m.feedback_flexible(feedback_value.clone().into_owned(), source_context)
+            })
+            .flat_map(|v| midi_source_value_to_raw_events(&v.final_value))
+            .collect();
+        if events.is_empty() {
+            return None;
+        }
+        let value = MidiSourceValue::Raw {
+            feedback_address_info: None,
+            events,
+        };
+        Some(value)
+    }
+
+    /// Creates one feedback value for each virtual member.
+    pub fn virtual_feedback(
+        &self,
+        feedback_value: Cow<FeedbackValue>,
+    ) -> SmallVec<[VirtualFeedbackValue; 1]> {
+        let CompoundSourceMembers::Virtual(members) = &self.members else {
+            return SmallVec::new();
+        };
+        members
+            .iter()
+            .map(|m| m.feedback(feedback_value.clone().into_owned()))
+            .collect()
+    }
+
+    pub fn control_midi(
+        &mut self,
+        value: &MidiSourceValue<RawShortMessage>,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<ControlValue> {
+        let CompoundSourceMembers::Midi(members) = &self.members else {
+            return None;
+        };
+        self.state
+            .process(&self.settings, members.len(), timestamp, |i| {
+                members[i].control(value)
+            })
+    }
+
+    pub fn control_virtual(
+        &mut self,
+        value: &VirtualSourceValue,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<ControlValue> {
+        let CompoundSourceMembers::Virtual(members) = &self.members else {
+            return None;
+        };
+        self.state
+            .process(&self.settings, members.len(), timestamp, |i| {
+                members[i].control(value)
+            })
+    }
+}
+
+impl CompoundSourceState {
+    fn reset(&mut self) {
+        self.pressed.fill(false);
+        self.reference_press = None;
+        self.sequence_position = 0;
+        self.is_on = false;
+    }
+
+    /// Processes an incoming value. `control` returns the control value if the member with the
+    /// given index matches the incoming value.
+    fn process(
+        &mut self,
+        settings: &CompoundSourceSettings,
+        member_count: usize,
+        timestamp: ControlEventTimestamp,
+        control: impl Fn(usize) -> Option<ControlValue>,
+    ) -> Option<ControlValue> {
+        let fire = match settings.kind {
+            CompoundSourceKind::Combination => {
+                self.process_combination(settings, member_count, timestamp, control)
+            }
+            CompoundSourceKind::Sequence => {
+                self.process_sequence(settings, member_count, timestamp, control)
+            }
+        }?;
+        self.is_on = fire;
+        let value = if fire { UnitValue::MAX } else { UnitValue::MIN };
+        Some(ControlValue::AbsoluteContinuous(value))
+    }
+
+    /// Returns `Some(true)` when the combination fires and `Some(false)` when it's released.
+    fn process_combination(
+        &mut self,
+        settings: &CompoundSourceSettings,
+        member_count: usize,
+        timestamp: ControlEventTimestamp,
+        control: impl Fn(usize) -> Option<ControlValue>,
+    ) -> Option<bool> {
+        let (index, control_value) = (0..member_count).find_map(|i| Some((i, control(i)?)))?;
+        let is_press = control_value.is_on();
+        let was_pressed = std::mem::replace(self.pressed.get_mut(index)?, is_press);
+        if !is_press {
+            if !self.pressed.contains(&true) {
+                self.reference_press = None;
+            }
+            // Releasing any member releases the whole combination.
+            return if self.is_on { Some(false) } else { None };
+        }
+        if was_pressed || self.is_on {
+            return None;
+        }
+        let attempt_is_running = self
+            .reference_press
+            .is_some_and(|t| !timed_out(settings, t, timestamp));
+        if !attempt_is_running {
+            // Start a new attempt. Members which are still held from a timed-out attempt don't
+            // count anymore.
+            self.pressed.iter_mut().for_each(|p| *p = false);
+            self.pressed[index] = true;
+            self.reference_press = Some(timestamp);
+        }
+        if self.pressed.iter().all(|p| *p) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `Some(true)` when the sequence completes and `Some(false)` when its last member is
+    /// released.
+    fn process_sequence(
+        &mut self,
+        settings: &CompoundSourceSettings,
+        member_count: usize,
+        timestamp: ControlEventTimestamp,
+        control: impl Fn(usize) -> Option<ControlValue>,
+    ) -> Option<bool> {
+        let last_index = member_count.checked_sub(1)?;
+        if self.is_on {
+            if let Some(control_value) = control(last_index) {
+                // Releasing the last member releases the sequence
+                return if control_value.is_on() {
+                    None
+                } else {
+                    Some(false)
+                };
+            }
+        }
+        if self.sequence_position > 0
+            && !self
+                .reference_press
+                .is_some_and(|t| !timed_out(settings, t, timestamp))
+        {
+            self.sequence_position = 0;
+        }
+        // The same source might appear multiple times in a sequence, so the expected member
+        // takes precedence.
+        let index = if let Some(v) = control(self.sequence_position) {
+            if !v.is_on() {
+                return None;
+            }
+            self.sequence_position
+        } else {
+            let control_value = (0..member_count).find_map(&control)?;
+            if !control_value.is_on() {
+                return None;
+            }
+            // Wrong member. The sequence starts over, maybe with this very press.
+            if control(0).is_some() {
+                self.sequence_position = 1;
+                self.reference_press = Some(timestamp);
+            } else {
+                self.sequence_position = 0;
+                self.reference_press = None;
+            }
+            return None;
+        };
+        self.reference_press = Some(timestamp);
+        if index == last_index {
+            self.sequence_position = 0;
+            Some(true)
+        } else {
+            self.sequence_position += 1;
+            None
+        }
+    }
+}
+
+fn midi_source_value_to_raw_events(value: &MidiSourceValue<RawShortMessage>) -> Vec<RawMidiEvent> {
+    if let Some(events) = value.to_raw() {
+        return events.into_iter().collect();
+    }
+    value
+        .to_short_messages::<RawShortMessage>(DataEntryByteOrder::MsbFirst)
+        .into_iter()
+        .flatten()
+        .filter_map(|msg| {
+            let (status_byte, data_byte_1, data_byte_2) = msg.to_bytes();
+            RawMidiEvent::try_from_slice(0, &[status_byte, data_byte_1.get(), data_byte_2.get()])
+                .ok()
+        })
+        .collect()
+}
+
+fn timed_out(
+    settings: &CompoundSourceSettings,
+    reference: ControlEventTimestamp,
+    now: ControlEventTimestamp,
+) -> bool {
+    match settings.timeout {
+        None => false,
+        Some(timeout) => now - reference > timeout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        AudioBlockStart, SampleOffset, VirtualControlElement, VirtualControlElementCharacter,
+    };
+    use helgoboss_learn::AbstractTimestamp;
+    use helgoboss_midi::test_util::{channel, key_number, note_on};
+    use reaper_medium::Hz;
+
+    fn button(index: u32) -> VirtualControlElement {
+        VirtualControlElement::Indexed {
+            id: index,
+            character: VirtualControlElementCharacter::Button,
+        }
+    }
+
+    fn compound(kind: CompoundSourceKind, count: u32) -> CompoundSource {
+        CompoundSource::new(
+            CompoundSourceSettings {
+                kind,
+                timeout: None,
+            },
+            CompoundSourceMembers::Virtual(
+                (0..count).map(|i| VirtualSource::new(button(i))).collect(),
+            ),
+        )
+    }
+
+    fn send(source: &mut CompoundSource, index: u32, on: bool) -> Option<ControlValue> {
+        send_at(source, index, on, ControlEventTimestamp::now())
+    }
+
+    fn send_at(
+        source: &mut CompoundSource,
+        index: u32,
+        on: bool,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<ControlValue> {
+        let value = if on { UnitValue::MAX } else { UnitValue::MIN };
+        let value = VirtualSourceValue::new(button(index), ControlValue::AbsoluteContinuous(value));
+        source.control_virtual(&value, timestamp)
+    }
+
+    /// Returns a timestamp the given number of milliseconds after the given block start.
+    fn later(block_start: AudioBlockStart, millis: u64) -> ControlEventTimestamp {
+        block_start.event_timestamp(SampleOffset::new(millis), Hz::new_panic(1000.0))
+    }
+
+    fn with_timeout(mut source: CompoundSource, millis: u64) -> CompoundSource {
+        source.settings.timeout = Some(Duration::from_millis(millis));
+        source
+    }
+
+    fn on() -> Option<ControlValue> {
+        Some(ControlValue::AbsoluteContinuous(UnitValue::MAX))
+    }
+
+    fn off() -> Option<ControlValue> {
+        Some(ControlValue::AbsoluteContinuous(UnitValue::MIN))
+    }
+
+    #[test]
+    fn combination() {
+        let mut source = compound(CompoundSourceKind::Combination, 2);
+        assert_eq!(send(&mut source, 0, true), None);
+        assert_eq!(send(&mut source, 1, true), on());
+        assert_eq!(send(&mut source, 0, false), off());
+        assert_eq!(send(&mut source, 1, false), None);
+        // Pressing one member alone doesn't fire
+        assert_eq!(send(&mut source, 1, true), None);
+        assert_eq!(send(&mut source, 1, false), None);
+        // Order doesn't matter
+        assert_eq!(send(&mut source, 1, true), None);
+        assert_eq!(send(&mut source, 0, true), on());
+        assert_eq!(send(&mut source, 1, false), off());
+        // Re-pressing the released member fires again
+        assert_eq!(send(&mut source, 1, true), on());
+    }
+
+    #[test]
+    fn sequence() {
+        let mut source = compound(CompoundSourceKind::Sequence, 3);
+        assert_eq!(send(&mut source, 0, true), None);
+        assert_eq!(send(&mut source, 0, false), None);
+        assert_eq!(send(&mut source, 1, true), None);
+        assert_eq!(send(&mut source, 1, false), None);
+        assert_eq!(send(&mut source, 2, true), on());
+        assert_eq!(send(&mut source, 2, false), off());
+        // Wrong order resets the sequence
+        assert_eq!(send(&mut source, 1, true), None);
+        assert_eq!(send(&mut source, 2, true), None);
+        // Pressing the first member in the middle of the sequence restarts it
+        assert_eq!(send(&mut source, 0, true), None);
+        assert_eq!(send(&mut source, 0, true), None);
+        assert_eq!(send(&mut source, 1, true), None);
+        assert_eq!(send(&mut source, 2, true), on());
+    }
+
+    #[test]
+    fn combination_timeout() {
+        // Given
+        let mut source = with_timeout(compound(CompoundSourceKind::Combination, 2), 100);
+        let start = AudioBlockStart::now();
+        // When
+        // Then
+        assert_eq!(send_at(&mut source, 0, true, start.timestamp()), None);
+        // Too late, this press starts a new attempt
+        assert_eq!(send_at(&mut source, 1, true, later(start, 200)), None);
+        // Releasing and pressing the first member again is in time for the new attempt
+        assert_eq!(send_at(&mut source, 0, false, later(start, 210)), None);
+        assert_eq!(send_at(&mut source, 0, true, later(start, 250)), on());
+    }
+
+    #[test]
+    fn sequence_timeout() {
+        // Given
+        let mut source = with_timeout(compound(CompoundSourceKind::Sequence, 2), 100);
+        let start = AudioBlockStart::now();
+        // When
+        // Then
+        assert_eq!(send_at(&mut source, 0, true, start.timestamp()), None);
+        // Too late, the sequence starts over
+        assert_eq!(send_at(&mut source, 1, true, later(start, 150)), None);
+        assert_eq!(send_at(&mut source, 0, true, later(start, 200)), None);
+        assert_eq!(send_at(&mut source, 1, true, later(start, 250)), on());
+    }
+
+    #[test]
+    fn midi_members() {
+        // Given
+        let mut source = CompoundSource::new(
+            CompoundSourceSettings {
+                kind: CompoundSourceKind::Combination,
+                timeout: None,
+            },
+            CompoundSourceMembers::Midi(
+                [60, 64]
+                    .into_iter()
+                    .map(|key| MidiSource::NoteVelocity {
+                        channel: Some(channel(0)),
+                        key_number: Some(key_number(key)),
+                    })
+                    .collect(),
+            ),
+        );
+        let mut send = |key: u8, velocity: u8| {
+            let value = MidiSourceValue::Plain(note_on(0, key, velocity));
+            source.control_midi(&value, ControlEventTimestamp::now())
+        };
+        // When
+        // Then
+        assert_eq!(send(60, 100), None);
+        // Not a member
+        assert_eq!(send(62, 100), None);
+        assert_eq!(send(64, 100), on());
+        assert_eq!(send(64, 0), off());
+    }
+
+    #[test]
+    fn deactivate_resets_state() {
+        // Given
+        let mut source = compound(CompoundSourceKind::Combination, 2);
+        send(&mut source, 0, true);
+        // When
+        source.on_deactivate();
+        // Then
+        assert_eq!(send(&mut source, 1, true), None);
+        assert_eq!(source.state.pressed.len(), 2);
+    }
+}
@@ -309,7 +309,9 @@ impl EelCondition {
     }
 }
 
-fn extract_used_param_indexes(eel_script: &str) -> NonCryptoHashSet<u32> {
+/// Returns the zero-based indexes of all parameters referred to as `p1`, `p2`, ... in the given EEL
+/// script.
+pub fn extract_used_param_indexes(eel_script: &str) -> NonCryptoHashSet<u32> {
     let param_regex = regex!(r"\bp([0-9]+)\b");
     param_regex
         .captures_iter(eel_script)
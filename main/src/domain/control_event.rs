@@ -1,4 +1,6 @@
+use crate::domain::SampleOffset;
 use helgoboss_learn::AbstractTimestamp;
+use reaper_medium::Hz;
 use std::fmt::{Display, Formatter};
 use std::ops::Sub;
 use std::time::{Duration, Instant};
@@ -6,28 +8,165 @@ use std::time::{Duration, Instant};
 pub type ControlEvent<P> = helgoboss_learn::ControlEvent<P, ControlEventTimestamp>;
 
 /// Timestamp of a control event.
+///
+/// If the event arrived within an audio block (e.g. MIDI), the timestamp can additionally carry
+/// the position of the event within that block. This makes time differences between events
+/// sample-accurate and lets real-time targets react at the correct position in the block.
 //
-// Don't expose the inner field, it should stay private. We might swap the time unit in future to
-// improve performance and accuracy.
+// Don't expose the inner fields, they should stay private. We might swap the time unit in future
+// to improve performance and accuracy.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub struct ControlEventTimestamp(Instant);
+pub struct ControlEventTimestamp {
+    /// Start of the audio block in which the event arrived (or the moment of arrival if the event
+    /// is not tied to an audio block).
+    block_start: AudioBlockStart,
+    /// Position within the audio block in samples.
+    sample_offset: SampleOffset,
+    /// Position within the audio block as duration.
+    block_offset: Duration,
+}
+
+impl ControlEventTimestamp {
+    /// Position of the event within the audio block in which it arrived.
+    pub fn sample_offset(&self) -> SampleOffset {
+        self.sample_offset
+    }
+
+    /// Position of the event within the audio block in which it arrived, as duration.
+    pub fn block_offset(&self) -> Duration {
+        self.block_offset
+    }
+
+    fn instant(&self) -> Instant {
+        self.block_start.0 + self.block_offset
+    }
+}
 
 impl AbstractTimestamp for ControlEventTimestamp {
     fn now() -> Self {
+        Self {
+            block_start: AudioBlockStart::now(),
+            sample_offset: SampleOffset::ZERO,
+            block_offset: Duration::ZERO,
+        }
+    }
+}
+
+/// Start of an audio block.
+///
+/// Must be captured exactly once at the beginning of each audio block. The timestamps of all
+/// events arriving within that block are derived from it, so their differences only depend on
+/// their sample offsets and not on when they happen to be processed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AudioBlockStart(Instant);
+
+impl AudioBlockStart {
+    pub fn now() -> Self {
         Self(Instant::now())
     }
+
+    /// Returns the timestamp of an event at the beginning of this block.
+    pub fn timestamp(self) -> ControlEventTimestamp {
+        ControlEventTimestamp {
+            block_start: self,
+            sample_offset: SampleOffset::ZERO,
+            block_offset: Duration::ZERO,
+        }
+    }
+
+    /// Returns the timestamp of an event at the given position within this block.
+    pub fn event_timestamp(
+        self,
+        sample_offset: SampleOffset,
+        sample_rate: Hz,
+    ) -> ControlEventTimestamp {
+        ControlEventTimestamp {
+            block_start: self,
+            sample_offset,
+            block_offset: sample_offset.to_duration(sample_rate),
+        }
+    }
 }
 
 impl Sub for ControlEventTimestamp {
     type Output = Duration;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        self.0 - rhs.0
+        // Saturating because a sample-accurate timestamp can lie slightly in the future.
+        self.instant().saturating_duration_since(rhs.instant())
     }
 }
 
 impl Display for ControlEventTimestamp {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.0)
+        write!(f, "{:?}", self.instant())?;
+        if self.sample_offset != SampleOffset::ZERO {
+            write!(f, " (sample offset {})", self.sample_offset.get())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_rate() -> Hz {
+        Hz::new_panic(48000.0)
+    }
+
+    #[test]
+    fn event_timestamp_carries_offset() {
+        // Given
+        let block_start = AudioBlockStart::now();
+        // When
+        let timestamp = block_start.event_timestamp(SampleOffset::new(12000), sample_rate());
+        // Then
+        assert_eq!(timestamp.sample_offset(), SampleOffset::new(12000));
+        assert_eq!(timestamp.block_offset(), Duration::from_millis(250));
+        assert_eq!(
+            timestamp - block_start.timestamp(),
+            Duration::from_millis(250)
+        );
+    }
+
+    #[test]
+    fn events_in_same_block_differ_by_offset_only() {
+        // Given
+        let block_start = AudioBlockStart::now();
+        let first = block_start.event_timestamp(SampleOffset::new(2400), sample_rate());
+        // When
+        std::thread::sleep(Duration::from_millis(5));
+        let second = block_start.event_timestamp(SampleOffset::new(7200), sample_rate());
+        // Then
+        assert_eq!(second - first, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn subtraction_saturates() {
+        // Given
+        let block_start = AudioBlockStart::now();
+        let early = block_start.timestamp();
+        let late = block_start.event_timestamp(SampleOffset::new(480), sample_rate());
+        // When
+        let diff = early - late;
+        // Then
+        assert_eq!(diff, Duration::ZERO);
+    }
+
+    #[test]
+    fn offset_is_relative_to_block_start() {
+        // Given
+        let first_block = AudioBlockStart::now();
+        std::thread::sleep(Duration::from_millis(5));
+        let second_block = AudioBlockStart::now();
+        // When
+        let first = first_block.event_timestamp(SampleOffset::new(24000), sample_rate());
+        let second = second_block.event_timestamp(SampleOffset::new(24000), sample_rate());
+        // Then
+        assert_eq!(
+            second - first,
+            second_block.timestamp() - first_block.timestamp()
+        );
     }
 }
@@ -0,0 +1,352 @@
+use crate::domain::{
+    ControlEventTimestamp, KeyMessage, Keystroke, OscDeviceId, OwnedIncomingMidiMessage,
+};
+use anyhow::{anyhow, bail, Context};
+use helgoboss_midi::{RawShortMessage, ShortMessage, ShortMessageFactory, U7};
+use reaper_medium::{AccelMsgKind, MidiInputDeviceId};
+use rosc::OscPacket;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Timestamped control input of one unit, as captured by the [`ControlInputRecorder`].
+///
+/// Can be saved to a file and replayed later via [`ControlInputReplay`] in order to reproduce
+/// controller-related issues without having the hardware present.
+///
+/// Only covers MIDI, OSC and keyboard input. Control messages sent by WebSocket clients are not
+/// recorded.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ControlInputRecording {
+    pub events: Vec<RecordedControlEvent>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedControlEvent {
+    /// Time elapsed since the start of the recording in microseconds.
+    pub time: u64,
+    #[serde(flatten)]
+    pub input: RecordedControlInput,
+}
+
+impl RecordedControlEvent {
+    pub fn time(&self) -> Duration {
+        Duration::from_micros(self.time)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum RecordedControlInput {
+    /// MIDI message as raw bytes.
+    ///
+    /// A device of `None` means that the message arrived at the FX input.
+    Midi {
+        device: Option<u8>,
+        bytes: Vec<u8>,
+    },
+    /// OSC packet encoded in the OSC wire format.
+    Osc {
+        device: OscDeviceId,
+        bytes: Vec<u8>,
+    },
+    Key {
+        stroke: Keystroke,
+        press: bool,
+    },
+}
+
+impl RecordedControlInput {
+    pub fn from_midi(device: Option<MidiInputDeviceId>, msg: &OwnedIncomingMidiMessage) -> Self {
+        let bytes = match msg {
+            OwnedIncomingMidiMessage::Short(msg) => {
+                let (status_byte, data_byte_1, data_byte_2) = msg.to_bytes();
+                vec![status_byte, data_byte_1.get(), data_byte_2.get()]
+            }
+            OwnedIncomingMidiMessage::SysEx(bytes) => bytes.clone(),
+        };
+        Self::Midi {
+            device: device.map(|d| d.get()),
+            bytes,
+        }
+    }
+
+    pub fn from_osc(device: OscDeviceId, packet: &OscPacket) -> anyhow::Result<Self> {
+        let bytes = rosc::encoder::encode(packet).context("couldn't encode OSC packet")?;
+        Ok(Self::Osc { device, bytes })
+    }
+
+    /// Returns `None` if the key message is neither a press nor a release.
+    pub fn from_key(msg: KeyMessage) -> Option<Self> {
+        let interaction_kind = msg.interaction_kind();
+        if !interaction_kind.is_press_or_release() {
+            return None;
+        }
+        let input = Self::Key {
+            stroke: msg.stroke(),
+            press: interaction_kind.is_press(),
+        };
+        Some(input)
+    }
+
+    pub fn decode(&self) -> anyhow::Result<DecodedControlInput> {
+        let decoded = match self {
+            RecordedControlInput::Midi { device, bytes } => DecodedControlInput::Midi {
+                device: device.map(MidiInputDeviceId::new),
+                msg: decode_midi_message(bytes)?,
+            },
+            RecordedControlInput::Osc { device, bytes } => {
+                let (_, packet) =
+                    rosc::decoder::decode_udp(bytes).context("couldn't decode OSC packet")?;
+                DecodedControlInput::Osc {
+                    device: *device,
+                    packet,
+                }
+            }
+            RecordedControlInput::Key { stroke, press } => {
+                let kind = if *press {
+                    AccelMsgKind::KeyDown
+                } else {
+                    AccelMsgKind::KeyUp
+                };
+                DecodedControlInput::Key(KeyMessage::new(kind, *stroke))
+            }
+        };
+        Ok(decoded)
+    }
+}
+
+/// Recorded control input in a form that can be fed into the processors again.
+#[derive(Clone, Debug)]
+pub enum DecodedControlInput {
+    Midi {
+        /// `None` means FX input.
+        device: Option<MidiInputDeviceId>,
+        msg: OwnedIncomingMidiMessage,
+    },
+    Osc {
+        device: OscDeviceId,
+        packet: OscPacket,
+    },
+    Key(KeyMessage),
+}
+
+fn decode_midi_message(bytes: &[u8]) -> anyhow::Result<OwnedIncomingMidiMessage> {
+    match bytes {
+        [0xF0, ..] => Ok(OwnedIncomingMidiMessage::SysEx(bytes.to_vec())),
+        [status_byte, data_byte_1, data_byte_2] => {
+            let msg = RawShortMessage::from_bytes((
+                *status_byte,
+                U7::try_from(*data_byte_1).map_err(|_| anyhow!("invalid first data byte"))?,
+                U7::try_from(*data_byte_2).map_err(|_| anyhow!("invalid second data byte"))?,
+            ))
+            .map_err(|_| anyhow!("invalid status byte"))?;
+            Ok(OwnedIncomingMidiMessage::Short(msg))
+        }
+        _ => bail!("recorded MIDI message is neither sys-ex nor a 3-byte short message"),
+    }
+}
+
+/// Collects control input of one unit while recording is active.
+#[derive(Debug)]
+pub struct ControlInputRecorder {
+    start: ControlEventTimestamp,
+    events: Vec<RecordedControlEvent>,
+}
+
+impl ControlInputRecorder {
+    pub fn new(start: ControlEventTimestamp) -> Self {
+        Self {
+            start,
+            events: vec![],
+        }
+    }
+
+    pub fn record(&mut self, timestamp: ControlEventTimestamp, input: RecordedControlInput) {
+        let event = RecordedControlEvent {
+            time: (timestamp - self.start).as_micros() as u64,
+            input,
+        };
+        self.events.push(event);
+    }
+
+    pub fn finish(mut self) -> ControlInputRecording {
+        // Events recorded in the real-time thread arrive with a slight delay, so they might be
+        // slightly out of order.
+        self.events.sort_by_key(|e| e.time);
+        ControlInputRecording {
+            events: self.events,
+        }
+    }
+}
+
+/// Feeds a recording back into the processors at original or scaled speed.
+#[derive(Debug)]
+pub struct ControlInputReplay {
+    recording: ControlInputRecording,
+    start: ControlEventTimestamp,
+    speed: f64,
+    next_index: usize,
+}
+
+impl ControlInputReplay {
+    /// A speed of 1.0 replays the recording at its original speed, 2.0 twice as fast.
+    pub fn new(recording: ControlInputRecording, start: ControlEventTimestamp, speed: f64) -> Self {
+        Self {
+            recording,
+            start,
+            speed,
+            next_index: 0,
+        }
+    }
+
+    /// Returns all events which are due at the given time and haven't been returned yet.
+    pub fn take_due_events(&mut self, now: ControlEventTimestamp) -> &[RecordedControlEvent] {
+        let elapsed = (now - self.start).mul_f64(self.speed);
+        let first_index = self.next_index;
+        let due_count = self.recording.events[first_index..]
+            .iter()
+            .take_while(|e| e.time() <= elapsed)
+            .count();
+        self.next_index += due_count;
+        &self.recording.events[first_index..self.next_index]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_index >= self.recording.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AudioBlockStart, SampleOffset};
+    use helgoboss_midi::test_util::note_on;
+    use reaper_medium::Hz;
+
+    #[test]
+    fn decode_short_midi_message() {
+        // Given
+        let msg = OwnedIncomingMidiMessage::Short(note_on(2, 64, 100));
+        let input = RecordedControlInput::from_midi(Some(MidiInputDeviceId::new(4)), &msg);
+        // When
+        let decoded = input.decode().unwrap();
+        // Then
+        let DecodedControlInput::Midi {
+            device,
+            msg: OwnedIncomingMidiMessage::Short(decoded_msg),
+        } = decoded
+        else {
+            panic!("expected short MIDI message");
+        };
+        assert_eq!(device, Some(MidiInputDeviceId::new(4)));
+        assert_eq!(decoded_msg, note_on(2, 64, 100));
+    }
+
+    #[test]
+    fn decode_sys_ex_midi_message() {
+        // Given
+        let bytes = vec![0xF0, 0x7E, 0x7F, 0x06, 0x01, 0xF7];
+        let msg = OwnedIncomingMidiMessage::SysEx(bytes.clone());
+        let input = RecordedControlInput::from_midi(None, &msg);
+        // When
+        let decoded = input.decode().unwrap();
+        // Then
+        let DecodedControlInput::Midi {
+            device,
+            msg: OwnedIncomingMidiMessage::SysEx(decoded_bytes),
+        } = decoded
+        else {
+            panic!("expected sys-ex MIDI message");
+        };
+        assert_eq!(device, None);
+        assert_eq!(decoded_bytes, bytes);
+    }
+
+    #[test]
+    fn reject_invalid_midi_bytes() {
+        // Given
+        let invalid_inputs = [
+            vec![],
+            vec![0x90, 64],
+            vec![0x90, 64, 100, 0],
+            vec![0x90, 0x80, 100],
+            vec![0x40, 64, 100],
+        ];
+        // When
+        // Then
+        for bytes in invalid_inputs {
+            assert!(decode_midi_message(&bytes).is_err(), "{bytes:?}");
+        }
+    }
+
+    #[test]
+    fn finish_orders_events_by_time() {
+        // Given
+        let block_start = AudioBlockStart::now();
+        let mut recorder = ControlInputRecorder::new(block_start.timestamp());
+        // When
+        recorder.record(later(block_start, 20), note(2));
+        recorder.record(later(block_start, 10), note(1));
+        recorder.record(later(block_start, 30), note(3));
+        let recording = recorder.finish();
+        // Then
+        let times: Vec<_> = recording.events.iter().map(|e| e.time).collect();
+        assert_eq!(times, vec![10_000, 20_000, 30_000]);
+        let key_numbers: Vec<_> = recording
+            .events
+            .iter()
+            .map(|e| match &e.input {
+                RecordedControlInput::Midi { bytes, .. } => bytes[1],
+                _ => panic!("expected MIDI input"),
+            })
+            .collect();
+        assert_eq!(key_numbers, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn replay_returns_each_due_event_once() {
+        // Given
+        let block_start = AudioBlockStart::now();
+        let mut replay = ControlInputReplay::new(recording(), block_start.timestamp(), 1.0);
+        // When
+        // Then
+        assert_eq!(replay.take_due_events(later(block_start, 5)).len(), 0);
+        assert_eq!(replay.take_due_events(later(block_start, 10)).len(), 1);
+        assert_eq!(replay.take_due_events(later(block_start, 10)).len(), 0);
+        assert!(!replay.is_finished());
+        assert_eq!(replay.take_due_events(later(block_start, 100)).len(), 2);
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn replay_scales_time_by_speed() {
+        // Given
+        let block_start = AudioBlockStart::now();
+        let mut replay = ControlInputReplay::new(recording(), block_start.timestamp(), 2.0);
+        // When
+        let due_count = replay.take_due_events(later(block_start, 10)).len();
+        // Then
+        assert_eq!(due_count, 2);
+        assert!(!replay.is_finished());
+    }
+
+    /// Recording with events at 10, 20 and 30 milliseconds.
+    fn recording() -> ControlInputRecording {
+        let block_start = AudioBlockStart::now();
+        let mut recorder = ControlInputRecorder::new(block_start.timestamp());
+        for (i, millis) in [10, 20, 30].into_iter().enumerate() {
+            recorder.record(later(block_start, millis), note(i as u8));
+        }
+        recorder.finish()
+    }
+
+    /// Returns a timestamp the given number of milliseconds after the given block start.
+    fn later(block_start: AudioBlockStart, millis: u64) -> ControlEventTimestamp {
+        block_start.event_timestamp(SampleOffset::new(millis), Hz::new_panic(1000.0))
+    }
+
+    fn note(key_number: u8) -> RecordedControlInput {
+        let msg = OwnedIncomingMidiMessage::Short(note_on(0, key_number, 100));
+        RecordedControlInput::from_midi(None, &msg)
+    }
+}
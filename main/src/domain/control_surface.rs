@@ -5,7 +5,7 @@ use crate::domain::{
     MonitoringFxChainChangeDetector, OscDeviceId, OscInputDevice, OscScanResult,
     QualifiedInstanceEvent, ReaperConfigChangeDetector, ReaperMessage, ReaperTarget,
     SharedInstance, SharedMainProcessors, TargetTouchEvent, TouchedTrackParameterType, UnitEvent,
-    UnitId, WeakInstance,
+    UnitId, WeakInstance, WebSocketControlMessage,
 };
 use base::{metrics_util, Global, NamedChannelSender, SenderToNormalThread};
 use crossbeam_channel::Receiver;
@@ -103,6 +103,8 @@ pub enum RealearnControlSurfaceMainTask<EH: DomainEventHandler> {
     StartCapturingOsc(OscCaptureSender),
     StopCapturingOsc,
     SendAllFeedback,
+    /// Control message addressed to the unit with the given ID.
+    ProcessWebSocketControlMessage(UnitId, WebSocketControlMessage),
 }
 
 /// Not all events in REAPER are communicated via a control surface, e.g. action invocations.
@@ -460,6 +462,18 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
                         m.send_all_feedback();
                     }
                 }
+                ProcessWebSocketControlMessage(unit_id, msg) => {
+                    let evt = ControlEvent::new(&msg, ControlEventTimestamp::now());
+                    let mut main_processors = self.main_processors.borrow_mut();
+                    let proc = main_processors.iter_mut().find(|p| p.unit_id() == unit_id);
+                    if let Some(proc) = proc {
+                        if proc.wants_web_socket_messages() {
+                            metrics_util::within_unit(unit_id.into(), || {
+                                proc.process_incoming_web_socket_msg(evt);
+                            });
+                        }
+                    }
+                }
             }
         }
     }
@@ -486,8 +500,10 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
 
     fn run_main_processors(&mut self, timestamp: ControlEventTimestamp) {
         for p in &mut *self.main_processors.borrow_mut() {
-            p.run_essential(timestamp);
-            p.run_control(timestamp);
+            metrics_util::measure_unit_time("helgobox.main.unit.run", p.unit_id().into(), || {
+                p.run_essential(timestamp);
+                p.run_control(timestamp);
+            });
         }
     }
 
@@ -719,10 +735,12 @@ impl<EH: DomainEventHandler> RealearnControlSurfaceMiddleware<EH> {
                 .extend(dev.poll_multiple(OSC_INCOMING_BULK_SIZE));
             for proc in &mut *self.main_processors.borrow_mut() {
                 if proc.wants_osc_from(dev.id()) {
-                    for packet in &self.osc_buffer {
-                        let evt = ControlEvent::new(packet, timestamp);
-                        proc.process_incoming_osc_packet(evt);
-                    }
+                    metrics_util::within_unit(proc.unit_id().into(), || {
+                        for packet in &self.osc_buffer {
+                            let evt = ControlEvent::new(packet, timestamp);
+                            proc.process_incoming_osc_packet(*dev.id(), evt);
+                        }
+                    });
                 }
             }
             if let Some(sender) = &self.osc_capture_sender {
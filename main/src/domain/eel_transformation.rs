@@ -1,4 +1,5 @@
 use crate::base::eel;
+use base::metrics_util::measure_time_in_current_unit;
 use helgoboss_learn::{Transformation, TransformationInput, TransformationOutput, UnitValue};
 use std::os::raw::c_void;
 
@@ -153,7 +154,9 @@ impl Transformation for EelTransformation {
             if let Some(rel_time_var) = eel_unit.rel_time {
                 rel_time_var.set(input.meta_data.rel_time.as_millis() as _);
             }
-            eel_unit.program.execute();
+            measure_time_in_current_unit("helgobox.script.eel.transformation", || {
+                eel_unit.program.execute();
+            });
             output_var.get()
         };
         let output = if v == STOP {
@@ -1,7 +1,8 @@
 use crate::domain::{
-    CompartmentKind, CompoundMappingTarget, ControlLogContext, ControlLogEntry, FeedbackLogEntry,
-    InternalInfoEvent, MappingId, MessageCaptureResult, PluginParamIndex, PluginParams,
-    ProjectionFeedbackValue, QualifiedMappingId, RawParamValue,
+    CompartmentKind, CompoundMappingTarget, ControlInputRecording, ControlLogContext,
+    ControlLogEntry, FeedbackLogEntry, InternalInfoEvent, MappingId, MessageCaptureResult,
+    PluginParamIndex, PluginParams, ProjectionFeedbackValue, QualifiedMappingId, RawParamValue,
+    WebSocketFeedbackValue,
 };
 use base::hash_util::NonCryptoHashSet;
 use helgoboss_learn::{AbsoluteValue, ControlValue};
@@ -24,6 +25,7 @@ pub enum DomainEvent<'a> {
     TargetValueChanged(TargetValueChangedEvent<'a>),
     Info(&'a InternalInfoEvent),
     ProjectionFeedback(ProjectionFeedbackValue),
+    WebSocketFeedback(WebSocketFeedbackValue),
     MappingMatched(MappingMatchedEvent),
     HandleTargetControl(TargetControlEvent),
     HandleSourceFeedback(SourceFeedbackEvent<'a>),
@@ -33,6 +35,7 @@ pub enum DomainEvent<'a> {
     MappingModificationRequested(MappingModificationRequestedEvent),
     TimeForCelebratingSuccess,
     ConditionsChanged,
+    ControlInputRecorded(ControlInputRecording),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
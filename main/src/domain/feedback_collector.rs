@@ -3,50 +3,50 @@ use crate::domain::{
     PreliminaryRealFeedbackValue, PreliminarySourceFeedbackValue, RealearnSourceState,
 };
 use base::hash_util::NonCryptoHashSet;
-use helgoboss_learn::devices::x_touch::XTouchMackieLcdState;
 use helgoboss_learn::{
     DisplaySpecAddress, MackieLcdScope, MidiSourceValue, RawFeedbackAddressInfo, RawMidiEvent,
     XTouchMackieLcdColorRequest,
 };
+use reaper_medium::MidiOutputDeviceId;
 
 /// Responsible for collecting non-final feedback values and aggregating them into final ones.
 pub struct FeedbackCollector<'a> {
-    x_touch_mackie_lcd_feedback_collector: Option<XTouchMackieLcdFeedbackCollector<'a>>,
-}
-
-struct XTouchMackieLcdFeedbackCollector<'a> {
-    state: &'a mut XTouchMackieLcdState,
-    changed_x_touch_mackie_lcd_extenders: NonCryptoHashSet<u8>,
+    global_source_state: &'a mut RealearnSourceState,
+    /// MIDI output devices for which X-Touch Mackie LCD colors are collected.
+    x_touch_mackie_lcd_devices: Vec<MidiOutputDeviceId>,
+    changed_x_touch_mackie_lcd_extenders: NonCryptoHashSet<(MidiOutputDeviceId, u8)>,
 }
 
 impl<'a> FeedbackCollector<'a> {
+    /// Collects for all given feedback outputs, usually the main and all additional feedback
+    /// outputs of the unit.
     pub fn new(
         global_source_state: &'a mut RealearnSourceState,
-        feedback_output: Option<FeedbackOutput>,
+        feedback_outputs: impl IntoIterator<Item = FeedbackOutput>,
     ) -> Self {
-        let x_touch_mackie_lcd_state = match feedback_output {
-            Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id))) => {
-                Some(global_source_state.get_x_touch_mackie_lcd_state_mut(dev_id))
-            }
-            // No or no direct MIDI device output. Then we can ignore this because
-            // the X-Touch!
-            _ => None,
-        };
+        let x_touch_mackie_lcd_devices = feedback_outputs
+            .into_iter()
+            .filter_map(|output| match output {
+                FeedbackOutput::Midi(MidiDestination::Device(dev_id)) => Some(dev_id),
+                // No direct MIDI device output. Then we can ignore this because the X-Touch!
+                _ => None,
+            })
+            .collect();
         Self {
-            x_touch_mackie_lcd_feedback_collector: x_touch_mackie_lcd_state.map(|state| {
-                XTouchMackieLcdFeedbackCollector {
-                    state,
-                    changed_x_touch_mackie_lcd_extenders: Default::default(),
-                }
-            }),
+            global_source_state,
+            x_touch_mackie_lcd_devices,
+            changed_x_touch_mackie_lcd_extenders: Default::default(),
         }
     }
 
     /// Spits the given feedback value immediately out again if it's already final or only has a
     /// projection part, but collects it if it's non-final.
+    ///
+    /// The given feedback output is the one which the feedback value is restricted to, if any.
     pub fn process(
         &mut self,
         preliminary_feedback_value: PreliminaryRealFeedbackValue,
+        feedback_output: Option<FeedbackOutput>,
     ) -> Option<FinalRealFeedbackValue> {
         match preliminary_feedback_value.source {
             None => {
@@ -56,13 +56,18 @@ impl<'a> FeedbackCollector<'a> {
             Some(preliminary_source_feedback_value) => match preliminary_source_feedback_value {
                 PreliminarySourceFeedbackValue::Midi(v) => {
                     if let Some(req) = v.x_touch_mackie_lcd_color_request {
-                        self.process_x_touch_mackie_lcd_color_request(req);
+                        self.process_x_touch_mackie_lcd_color_request(req, feedback_output);
                     }
                     FinalRealFeedbackValue::new(
                         preliminary_feedback_value.projection,
                         Some(FinalSourceFeedbackValue::Midi(v.final_value)),
                     )
                 }
+                // Is final MIDI value already.
+                PreliminarySourceFeedbackValue::CompoundMidi(v) => FinalRealFeedbackValue::new(
+                    preliminary_feedback_value.projection,
+                    Some(FinalSourceFeedbackValue::Midi(v)),
+                ),
                 // Is final OSC value already.
                 PreliminarySourceFeedbackValue::Osc(v) => FinalRealFeedbackValue::new(
                     preliminary_feedback_value.projection,
@@ -77,53 +82,110 @@ impl<'a> FeedbackCollector<'a> {
         }
     }
 
-    /// Takes the collected and aggregated material and produces the final feedback values.
+    /// Takes the collected and aggregated material and produces the final feedback values, each one
+    /// together with the output it's destined for.
     pub fn generate_final_feedback_values(
         self,
-    ) -> impl Iterator<Item = FinalRealFeedbackValue> + 'a {
-        self.x_touch_mackie_lcd_feedback_collector
+    ) -> impl Iterator<Item = (FeedbackOutput, FinalRealFeedbackValue)> + 'a {
+        let global_source_state = self.global_source_state;
+        self.changed_x_touch_mackie_lcd_extenders
             .into_iter()
-            .flat_map(|x_touch_collector| {
-                x_touch_collector
-                    .changed_x_touch_mackie_lcd_extenders
-                    .into_iter()
-                    .filter_map(|extender_index| {
-                        let sysex = x_touch_collector.state.sysex(extender_index);
-                        let midi_event = RawMidiEvent::try_from_iter(0, sysex).ok()?;
-                        let feedback_address = RawFeedbackAddressInfo::Display {
-                            spec: DisplaySpecAddress::XTouchMackieLcdColors { extender_index },
-                        };
-                        let source_feedback_value = FinalSourceFeedbackValue::Midi(
-                            MidiSourceValue::single_raw(Some(feedback_address), midi_event),
-                        );
-                        FinalRealFeedbackValue::new(None, Some(source_feedback_value))
-                    })
+            .filter_map(move |(dev_id, extender_index)| {
+                let state = global_source_state.get_x_touch_mackie_lcd_state_mut(dev_id);
+                let midi_event =
+                    RawMidiEvent::try_from_iter(0, state.sysex(extender_index)).ok()?;
+                let feedback_address = RawFeedbackAddressInfo::Display {
+                    spec: DisplaySpecAddress::XTouchMackieLcdColors { extender_index },
+                };
+                let source_feedback_value = FinalSourceFeedbackValue::Midi(
+                    MidiSourceValue::single_raw(Some(feedback_address), midi_event),
+                );
+                let feedback_value =
+                    FinalRealFeedbackValue::new(None, Some(source_feedback_value))?;
+                let feedback_output = FeedbackOutput::Midi(MidiDestination::Device(dev_id));
+                Some((feedback_output, feedback_value))
             })
     }
 
-    fn process_x_touch_mackie_lcd_color_request(&mut self, req: XTouchMackieLcdColorRequest) {
-        let collector = match &mut self.x_touch_mackie_lcd_feedback_collector {
-            None => return,
-            Some(c) => c,
-        };
-        let channels = match req.channel {
-            None => 0..MackieLcdScope::CHANNEL_COUNT,
-            Some(ch) => ch..ch + 1,
-        };
-        let mut at_least_one_color_change = false;
-        for ch in channels {
-            let changed =
-                collector
-                    .state
-                    .notify_color_requested(req.extender_index, ch, req.color_index);
-            if changed {
-                at_least_one_color_change = true;
+    fn process_x_touch_mackie_lcd_color_request(
+        &mut self,
+        req: XTouchMackieLcdColorRequest,
+        feedback_output: Option<FeedbackOutput>,
+    ) {
+        for &dev_id in &self.x_touch_mackie_lcd_devices {
+            if !is_destined_for_device(feedback_output, dev_id) {
+                continue;
+            }
+            let state = self
+                .global_source_state
+                .get_x_touch_mackie_lcd_state_mut(dev_id);
+            let channels = match req.channel {
+                None => 0..MackieLcdScope::CHANNEL_COUNT,
+                Some(ch) => ch..ch + 1,
+            };
+            let mut at_least_one_color_change = false;
+            for ch in channels {
+                let changed = state.notify_color_requested(req.extender_index, ch, req.color_index);
+                if changed {
+                    at_least_one_color_change = true;
+                }
+            }
+            if at_least_one_color_change {
+                self.changed_x_touch_mackie_lcd_extenders
+                    .insert((dev_id, req.extender_index));
             }
         }
-        if at_least_one_color_change {
-            collector
-                .changed_x_touch_mackie_lcd_extenders
-                .insert(req.extender_index);
-        }
+    }
+}
+
+/// Returns whether feedback restricted to the given output (`None` means all outputs) reaches the
+/// given MIDI output device.
+fn is_destined_for_device(
+    feedback_output: Option<FeedbackOutput>,
+    dev_id: MidiOutputDeviceId,
+) -> bool {
+    match feedback_output {
+        None => true,
+        Some(output) => output == FeedbackOutput::Midi(MidiDestination::Device(dev_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn midi_device_output(dev_id: u8) -> FeedbackOutput {
+        FeedbackOutput::Midi(MidiDestination::Device(MidiOutputDeviceId::new(dev_id)))
+    }
+
+    #[test]
+    fn collect_for_all_midi_device_outputs() {
+        // Given
+        let mut source_state = RealearnSourceState::default();
+        let outputs = [
+            midi_device_output(1),
+            FeedbackOutput::Midi(MidiDestination::FxOutput),
+            midi_device_output(2),
+        ];
+        // When
+        let collector = FeedbackCollector::new(&mut source_state, outputs);
+        // Then
+        assert_eq!(
+            collector.x_touch_mackie_lcd_devices,
+            vec![MidiOutputDeviceId::new(1), MidiOutputDeviceId::new(2)]
+        );
+    }
+
+    #[test]
+    fn restricted_feedback_reaches_selected_device_only() {
+        // Given
+        let dev_1 = MidiOutputDeviceId::new(1);
+        let dev_2 = MidiOutputDeviceId::new(2);
+        // When
+        // Then
+        assert!(is_destined_for_device(None, dev_1));
+        assert!(is_destined_for_device(None, dev_2));
+        assert!(!is_destined_for_device(Some(midi_device_output(2)), dev_1));
+        assert!(is_destined_for_device(Some(midi_device_output(2)), dev_2));
     }
 }
@@ -0,0 +1,324 @@
+use crate::domain::{CompartmentKind, QualifiedMappingId};
+use base::hash_util::NonCryptoHashMap;
+use helgoboss_learn::UnitValue;
+use std::time::{Duration, Instant};
+
+/// Decides whether target value changes of a mapping are sent as feedback immediately, later or
+/// not at all.
+///
+/// Useful for taming motor faders which fight the user and for not overwhelming slow devices with
+/// meter-like feedback.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct FeedbackPolicy {
+    /// Maximum number of feedback messages per second.
+    pub max_rate: Option<f64>,
+    /// Minimum difference (normalized) between the last sent and the new target value.
+    pub min_delta: Option<f64>,
+    /// Sends feedback only when the target value hasn't changed for this duration.
+    pub debounce: Option<Duration>,
+    /// Holds back feedback until this duration has passed since the mapping was last controlled.
+    pub settle_delay: Option<Duration>,
+}
+
+impl FeedbackPolicy {
+    /// Lowest supported maximum rate (one message every 1000 seconds).
+    pub const MIN_MAX_RATE: f64 = 0.001;
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if let Some(rate) = self.max_rate {
+            if !rate.is_finite() || rate < Self::MIN_MAX_RATE {
+                return Err("max rate must be a number of at least 0.001 messages per second");
+            }
+        }
+        if let Some(delta) = self.min_delta {
+            if !(0.0..=1.0).contains(&delta) {
+                return Err("min delta must be a number between 0.0 and 1.0");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `None` for invalid rates, which is the same as having no maximum rate.
+    fn min_interval(&self) -> Option<Duration> {
+        let rate = self.max_rate?;
+        if !rate.is_finite() || rate <= 0.0 {
+            return None;
+        }
+        Duration::try_from_secs_f64(1.0 / rate).ok()
+    }
+}
+
+/// Attached to a feedback value in order to subject it to the feedback policy of the mapping
+/// which produced it.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FeedbackPolicyContext {
+    pub mapping_id: QualifiedMappingId,
+    pub policy: FeedbackPolicy,
+    /// The target value reflected by the feedback value.
+    pub target_value: Option<UnitValue>,
+}
+
+/// Keeps track of feedback sent for mappings that have a non-default feedback policy.
+#[derive(Debug, Default)]
+pub struct FeedbackPolicyStates {
+    states: NonCryptoHashMap<QualifiedMappingId, FeedbackPolicyState>,
+}
+
+#[derive(Debug, Default)]
+struct FeedbackPolicyState {
+    policy: FeedbackPolicy,
+    last_sent_at: Option<Instant>,
+    last_sent_value: Option<UnitValue>,
+    last_change_at: Option<Instant>,
+    last_control_at: Option<Instant>,
+    /// Whether feedback has been held back and should be sent as soon as possible.
+    pending: bool,
+}
+
+impl FeedbackPolicyStates {
+    /// Should be called whenever the mapping is controlled.
+    pub fn notify_controlled(
+        &mut self,
+        id: QualifiedMappingId,
+        policy: FeedbackPolicy,
+        now: Instant,
+    ) {
+        if policy.settle_delay.is_none() {
+            return;
+        }
+        let state = self.states.entry(id).or_default();
+        state.policy = policy;
+        state.last_control_at = Some(now);
+    }
+
+    /// Should be called whenever feedback subject to a feedback policy is about to be sent.
+    ///
+    /// Returns `true` if the feedback should be sent right now. If it returns `false`, feedback
+    /// might have been postponed. Postponed feedback can be obtained via [`Self::take_due`].
+    pub fn process_feedback(&mut self, context: FeedbackPolicyContext, now: Instant) -> bool {
+        let state = self.states.entry(context.mapping_id).or_default();
+        state.policy = context.policy;
+        state.last_change_at = Some(now);
+        if state.is_below_min_delta(context.target_value) {
+            state.pending = false;
+            return false;
+        }
+        if state.is_held_back(now) {
+            state.pending = true;
+            return false;
+        }
+        state.mark_sent(context.target_value, now);
+        true
+    }
+
+    /// Writes the mappings whose postponed feedback should be sent now into the given buffer,
+    /// replacing its previous content.
+    ///
+    /// The caller should send feedback for these mappings and call [`Self::notify_sent`].
+    pub fn take_due(&self, now: Instant, due_ids: &mut Vec<QualifiedMappingId>) {
+        due_ids.clear();
+        due_ids.extend(
+            self.states
+                .iter()
+                .filter(|(_, s)| s.pending && !s.is_held_back(now))
+                .map(|(id, _)| *id),
+        );
+    }
+
+    pub fn notify_sent(&mut self, id: QualifiedMappingId, value: Option<UnitValue>, now: Instant) {
+        if let Some(state) = self.states.get_mut(&id) {
+            state.mark_sent(value, now);
+        }
+    }
+
+    /// Forgets about everything that happened in the given compartment, e.g. after its mappings
+    /// have been replaced.
+    pub fn clear(&mut self, compartment: CompartmentKind) {
+        self.states.retain(|id, _| id.compartment != compartment);
+    }
+}
+
+impl FeedbackPolicyState {
+    fn is_below_min_delta(&self, new_value: Option<UnitValue>) -> bool {
+        match (self.policy.min_delta, self.last_sent_value, new_value) {
+            (Some(min_delta), Some(last), Some(new)) => (new.get() - last.get()).abs() < min_delta,
+            _ => false,
+        }
+    }
+
+    fn is_held_back(&self, now: Instant) -> bool {
+        let still_within =
+            |since: Option<Instant>, duration: Option<Duration>| match (since, duration) {
+                (Some(since), Some(duration)) => now < since + duration,
+                _ => false,
+            };
+        still_within(self.last_control_at, self.policy.settle_delay)
+            || still_within(self.last_change_at, self.policy.debounce)
+            || still_within(self.last_sent_at, self.policy.min_interval())
+    }
+
+    fn mark_sent(&mut self, value: Option<UnitValue>, now: Instant) {
+        self.pending = false;
+        self.last_sent_at = Some(now);
+        if value.is_some() {
+            self.last_sent_value = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::MappingId;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn val(v: f64) -> Option<UnitValue> {
+        Some(UnitValue::new(v))
+    }
+
+    fn due(states: &FeedbackPolicyStates, now: Instant) -> Vec<QualifiedMappingId> {
+        let mut due_ids = vec![];
+        states.take_due(now, &mut due_ids);
+        due_ids
+    }
+
+    fn ctx(
+        mapping_id: QualifiedMappingId,
+        policy: FeedbackPolicy,
+        target_value: Option<UnitValue>,
+    ) -> FeedbackPolicyContext {
+        FeedbackPolicyContext {
+            mapping_id,
+            policy,
+            target_value,
+        }
+    }
+
+    #[test]
+    fn max_rate() {
+        // Given
+        let mut states = FeedbackPolicyStates::default();
+        let id = QualifiedMappingId::new(CompartmentKind::Main, MappingId::random());
+        let policy = FeedbackPolicy {
+            max_rate: Some(10.0),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        // When
+        // Then
+        assert!(states.process_feedback(ctx(id, policy, val(0.1)), start));
+        assert!(!states.process_feedback(ctx(id, policy, val(0.2)), start + ms(50)));
+        assert!(due(&states, start + ms(90)).is_empty());
+        assert_eq!(due(&states, start + ms(100)), vec![id]);
+        states.notify_sent(id, val(0.2), start + ms(100));
+        assert!(due(&states, start + ms(300)).is_empty());
+    }
+
+    #[test]
+    fn min_delta() {
+        // Given
+        let mut states = FeedbackPolicyStates::default();
+        let id = QualifiedMappingId::new(CompartmentKind::Main, MappingId::random());
+        let policy = FeedbackPolicy {
+            min_delta: Some(0.05),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        // When
+        // Then
+        assert!(states.process_feedback(ctx(id, policy, val(0.5)), start));
+        assert!(!states.process_feedback(ctx(id, policy, val(0.52)), start + ms(1)));
+        assert!(states.process_feedback(ctx(id, policy, val(0.56)), start + ms(2)));
+        assert!(due(&states, start + ms(1000)).is_empty());
+    }
+
+    #[test]
+    fn debounce() {
+        // Given
+        let mut states = FeedbackPolicyStates::default();
+        let id = QualifiedMappingId::new(CompartmentKind::Main, MappingId::random());
+        let policy = FeedbackPolicy {
+            debounce: Some(ms(100)),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        // When
+        // Then
+        assert!(!states.process_feedback(ctx(id, policy, val(0.1)), start));
+        assert!(!states.process_feedback(ctx(id, policy, val(0.2)), start + ms(80)));
+        assert!(due(&states, start + ms(150)).is_empty());
+        assert_eq!(due(&states, start + ms(180)), vec![id]);
+    }
+
+    #[test]
+    fn settle_delay() {
+        // Given
+        let mut states = FeedbackPolicyStates::default();
+        let id = QualifiedMappingId::new(CompartmentKind::Main, MappingId::random());
+        let policy = FeedbackPolicy {
+            settle_delay: Some(ms(500)),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        // When
+        states.notify_controlled(id, policy, start);
+        // Then
+        assert!(!states.process_feedback(ctx(id, policy, val(0.3)), start + ms(10)));
+        states.notify_controlled(id, policy, start + ms(300));
+        assert!(due(&states, start + ms(600)).is_empty());
+        assert_eq!(due(&states, start + ms(800)), vec![id]);
+        states.notify_sent(id, val(0.3), start + ms(800));
+        assert!(states.process_feedback(ctx(id, policy, val(0.4)), start + ms(900)));
+    }
+
+    #[test]
+    fn invalid_max_rate() {
+        // Given
+        let mut states = FeedbackPolicyStates::default();
+        let id = QualifiedMappingId::new(CompartmentKind::Main, MappingId::random());
+        let start = Instant::now();
+        // When
+        // Then
+        for max_rate in [1e-30, f64::NAN, f64::INFINITY, -1.0] {
+            let policy = FeedbackPolicy {
+                max_rate: Some(max_rate),
+                ..Default::default()
+            };
+            assert!(policy.validate().is_err());
+            assert!(states.process_feedback(ctx(id, policy, val(0.1)), start));
+            assert!(states.process_feedback(ctx(id, policy, val(0.2)), start + ms(1)));
+        }
+        let valid_policy = FeedbackPolicy {
+            max_rate: Some(FeedbackPolicy::MIN_MAX_RATE),
+            ..Default::default()
+        };
+        assert!(valid_policy.validate().is_ok());
+    }
+
+    #[test]
+    fn clear_only_affects_given_compartment() {
+        // Given
+        let mut states = FeedbackPolicyStates::default();
+        let main_id = QualifiedMappingId::new(CompartmentKind::Main, MappingId::random());
+        let controller_id =
+            QualifiedMappingId::new(CompartmentKind::Controller, MappingId::random());
+        let policy = FeedbackPolicy {
+            debounce: Some(ms(100)),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        states.process_feedback(ctx(main_id, policy, val(0.1)), start);
+        states.process_feedback(ctx(controller_id, policy, val(0.1)), start);
+        // When
+        states.clear(CompartmentKind::Main);
+        // Then
+        assert_eq!(due(&states, start + ms(200)), vec![controller_id]);
+    }
+}
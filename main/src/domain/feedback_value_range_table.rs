@@ -0,0 +1,253 @@
+use helgoboss_learn::{
+    AbsoluteValue, FeedbackStyle, FeedbackValue, NumericFeedbackValue, NumericValue, PropProvider,
+    PropValue, RgbColor, UnitValue, VirtualColor,
+};
+use helgobox_api::persistence::FeedbackValueTableInput;
+use std::borrow::Cow;
+
+/// Looks up feedback values or colors by numeric ranges.
+///
+/// In contrast to the text-based feedback value tables, which are evaluated by the mode, this
+/// is evaluated after the mode has produced its feedback value.
+#[derive(Clone, Debug)]
+pub enum FeedbackValueRangeTable {
+    ToContinuous(RangeTable<UnitValue>),
+    ToColor(RangeTable<VirtualColor>),
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeTable<T> {
+    pub input: FeedbackValueTableInput,
+    /// Sorted by lower bound.
+    pub entries: Vec<RangeTableEntry<T>>,
+    pub interpolate: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeTableEntry<T> {
+    /// Inclusive lower bound, `None` means negative infinity.
+    pub from: Option<f64>,
+    /// Exclusive upper bound, `None` means positive infinity.
+    pub to: Option<f64>,
+    pub value: T,
+}
+
+impl<T> RangeTableEntry<T> {
+    fn contains(&self, x: f64) -> bool {
+        self.from.map_or(true, |from| x >= from) && self.to.map_or(true, |to| x < to)
+    }
+}
+
+impl FeedbackValueRangeTable {
+    /// Returns the feedback value unchanged if it's not numeric or if no entry matches.
+    ///
+    /// The prop provider is necessary for looking up the target's numeric value and for resolving
+    /// property-based colors.
+    pub fn apply<'a, 'b>(
+        &self,
+        feedback_value: Cow<'a, FeedbackValue<'b>>,
+        prop_provider: Option<&dyn PropProvider>,
+    ) -> Cow<'a, FeedbackValue<'b>> {
+        let FeedbackValue::Numeric(v) = feedback_value.as_ref() else {
+            return feedback_value;
+        };
+        let new_value = match self {
+            FeedbackValueRangeTable::ToContinuous(t) => {
+                let value = input_value(t.input, v, prop_provider)
+                    .and_then(|x| t.lookup(x, |v| Some(*v), interpolate_unit_value));
+                value.map(|value| {
+                    let style = FeedbackStyle {
+                        color: v.style.color,
+                        background_color: v.style.background_color,
+                    };
+                    NumericFeedbackValue::new(style, AbsoluteValue::Continuous(value))
+                })
+            }
+            FeedbackValueRangeTable::ToColor(t) => {
+                let color = input_value(t.input, v, prop_provider).and_then(|x| {
+                    t.lookup(x, |c| resolve_color(c, prop_provider), interpolate_color)
+                });
+                color.map(|color| {
+                    let style = FeedbackStyle {
+                        color: Some(color),
+                        background_color: v.style.background_color,
+                    };
+                    NumericFeedbackValue::new(style, v.value)
+                })
+            }
+        };
+        match new_value {
+            None => feedback_value,
+            Some(v) => Cow::Owned(FeedbackValue::Numeric(v)),
+        }
+    }
+}
+
+impl<T> RangeTable<T> {
+    fn lookup<V>(
+        &self,
+        x: f64,
+        resolve: impl Fn(&T) -> Option<V>,
+        interpolate: impl Fn(V, V, f64) -> V,
+    ) -> Option<V> {
+        let index = self.entries.iter().position(|e| e.contains(x))?;
+        let entry = &self.entries[index];
+        let value = resolve(&entry.value)?;
+        if !self.interpolate {
+            return Some(value);
+        }
+        let (Some(from), Some(to), Some(next_entry)) =
+            (entry.from, entry.to, self.entries.get(index + 1))
+        else {
+            // Open ranges and the last entry don't have anything to interpolate towards
+            return Some(value);
+        };
+        let Some(next_value) = resolve(&next_entry.value) else {
+            return Some(value);
+        };
+        let ratio = (x - from) / (to - from);
+        Some(interpolate(value, next_value, ratio))
+    }
+}
+
+fn input_value(
+    input: FeedbackValueTableInput,
+    v: &NumericFeedbackValue,
+    prop_provider: Option<&dyn PropProvider>,
+) -> Option<f64> {
+    match input {
+        FeedbackValueTableInput::FeedbackValue => Some(v.value.to_unit_value().get()),
+        FeedbackValueTableInput::TargetNumericValue => {
+            match prop_provider?.get_prop_value("target.numeric_value")? {
+                PropValue::Numeric(NumericValue::Decimal(v)) => Some(v),
+                PropValue::Numeric(NumericValue::Discrete(v)) => Some(v as f64),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn resolve_color(
+    color: &VirtualColor,
+    prop_provider: Option<&dyn PropProvider>,
+) -> Option<RgbColor> {
+    match color {
+        VirtualColor::Rgb(c) => Some(*c),
+        VirtualColor::Prop { prop } => match prop_provider?.get_prop_value(prop)? {
+            PropValue::Color(c) => Some(c),
+            _ => None,
+        },
+    }
+}
+
+fn interpolate_unit_value(a: UnitValue, b: UnitValue, ratio: f64) -> UnitValue {
+    UnitValue::new_clamped(a.get() + (b.get() - a.get()) * ratio)
+}
+
+fn interpolate_color(a: RgbColor, b: RgbColor, ratio: f64) -> RgbColor {
+    let interpolate_channel =
+        |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * ratio).round() as u8;
+    RgbColor::new(
+        interpolate_channel(a.r(), b.r()),
+        interpolate_channel(a.g(), b.g()),
+        interpolate_channel(a.b(), b.b()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn green() -> RgbColor {
+        RgbColor::new(0, 255, 0)
+    }
+
+    fn yellow() -> RgbColor {
+        RgbColor::new(255, 255, 0)
+    }
+
+    fn red() -> RgbColor {
+        RgbColor::new(255, 0, 0)
+    }
+
+    fn entry<T>(from: Option<f64>, to: Option<f64>, value: T) -> RangeTableEntry<T> {
+        RangeTableEntry { from, to, value }
+    }
+
+    fn meter_table(interpolate: bool) -> FeedbackValueRangeTable {
+        FeedbackValueRangeTable::ToColor(RangeTable {
+            input: FeedbackValueTableInput::FeedbackValue,
+            entries: vec![
+                entry(None, Some(0.5), VirtualColor::Rgb(green())),
+                entry(Some(0.5), Some(0.8), VirtualColor::Rgb(yellow())),
+                entry(Some(0.8), None, VirtualColor::Rgb(red())),
+            ],
+            interpolate,
+        })
+    }
+
+    fn numeric(value: f64) -> FeedbackValue<'static> {
+        FeedbackValue::Numeric(NumericFeedbackValue::new(
+            FeedbackStyle::default(),
+            AbsoluteValue::Continuous(UnitValue::new(value)),
+        ))
+    }
+
+    fn color_of(value: &FeedbackValue) -> Option<RgbColor> {
+        match value {
+            FeedbackValue::Numeric(v) => v.style.color,
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn color_thresholds() {
+        // Given
+        let table = meter_table(false);
+        // When
+        let low = table.apply(Cow::Owned(numeric(0.2)), None);
+        let middle = table.apply(Cow::Owned(numeric(0.6)), None);
+        let high = table.apply(Cow::Owned(numeric(0.8)), None);
+        // Then
+        assert_eq!(color_of(&low), Some(green()));
+        assert_eq!(color_of(&middle), Some(yellow()));
+        assert_eq!(color_of(&high), Some(red()));
+    }
+
+    #[test]
+    fn color_interpolation() {
+        // Given
+        let table = meter_table(true);
+        // When
+        let value = table.apply(Cow::Owned(numeric(0.65)), None);
+        // Then
+        assert_eq!(color_of(&value), Some(RgbColor::new(255, 128, 0)));
+    }
+
+    #[test]
+    fn continuous_interpolation() {
+        // Given
+        let table = FeedbackValueRangeTable::ToContinuous(RangeTable {
+            input: FeedbackValueTableInput::FeedbackValue,
+            entries: vec![
+                entry(Some(0.0), Some(0.5), UnitValue::MIN),
+                entry(Some(0.5), None, UnitValue::MAX),
+            ],
+            interpolate: true,
+        });
+        // When
+        let value = table.apply(Cow::Owned(numeric(0.25)), None);
+        // Then
+        assert_eq!(value.into_owned(), numeric(0.5));
+    }
+
+    #[test]
+    fn non_numeric_values_are_left_alone() {
+        // Given
+        let table = meter_table(false);
+        // When
+        let value = table.apply(Cow::Owned(FeedbackValue::Off), None);
+        // Then
+        assert_eq!(value.into_owned(), FeedbackValue::Off);
+    }
+}
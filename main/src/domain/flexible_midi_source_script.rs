@@ -1,4 +1,8 @@
-use crate::domain::{AdditionalLuaMidiSourceScriptInput, EelMidiSourceScript, LuaMidiSourceScript};
+use crate::domain::{
+    AdditionalLuaMidiSourceScriptInput, EelMidiSourceScript, LuaMidiSourceScript,
+    SysExDisplayMidiSourceScript,
+};
+use base::metrics_util::measure_time_in_current_unit;
 use helgoboss_learn::{FeedbackValue, MidiSourceScript, MidiSourceScriptOutcome};
 use std::borrow::Cow;
 
@@ -6,6 +10,7 @@ use std::borrow::Cow;
 pub enum FlexibleMidiSourceScript<'lua> {
     Eel(EelMidiSourceScript),
     Lua(LuaMidiSourceScript<'lua>),
+    SysExDisplay(SysExDisplayMidiSourceScript),
 }
 
 impl<'a, 'lua: 'a> MidiSourceScript<'a> for FlexibleMidiSourceScript<'lua> {
@@ -17,8 +22,17 @@ impl<'a, 'lua: 'a> MidiSourceScript<'a> for FlexibleMidiSourceScript<'lua> {
         additional_input: Self::AdditionalInput,
     ) -> Result<MidiSourceScriptOutcome, Cow<'static, str>> {
         match self {
-            FlexibleMidiSourceScript::Eel(s) => s.execute(input_value, ()),
-            FlexibleMidiSourceScript::Lua(s) => s.execute(input_value, additional_input),
+            FlexibleMidiSourceScript::Eel(s) => {
+                measure_time_in_current_unit("helgobox.script.eel.midi_source", || {
+                    s.execute(input_value, ())
+                })
+            }
+            FlexibleMidiSourceScript::Lua(s) => {
+                measure_time_in_current_unit("helgobox.script.lua.midi_source", || {
+                    s.execute(input_value, additional_input)
+                })
+            }
+            FlexibleMidiSourceScript::SysExDisplay(s) => s.execute(input_value, ()),
         }
     }
 }
@@ -0,0 +1,489 @@
+use crate::domain::{
+    convert_count_to_step_size, CompartmentKind, CompartmentParamIndex, ControlEvent,
+    ControlEventTimestamp, FeedbackCollector, FeedbackDestinations, FinalSourceFeedbackValue,
+    MainMapping, MappingKey, NoopLogger, PluginParams, RawParamValue, RealearnSourceContext,
+    RealearnSourceState, SpecificCompoundFeedbackValue, VirtualSourceValue,
+};
+use anyhow::Context;
+use enum_map::EnumMap;
+use helgoboss_learn::{
+    AbsoluteValue, AbstractTimestamp, ControlType, ControlValue, Fraction, MidiSourceValue,
+    ModeControlResult, PropProvider, PropValue, Target, UnitValue,
+};
+use helgoboss_midi::{
+    ControlChange14BitMessageScanner, PollingParameterNumberMessageScanner, RawShortMessage,
+};
+use std::borrow::Cow;
+use std::time::Duration;
+
+/// Step size used when a relative control value hits a continuous mock target.
+const MOCK_TARGET_RELATIVE_STEP_SIZE: f64 = 0.01;
+
+/// Simulates the mappings of one unit without REAPER.
+///
+/// This is a simulation, not the real thing: It doesn't drive the real-time processor or main
+/// processor (both need REAPER) but dispatches control and feedback on its own, in a simplified
+/// way. Only the per-mapping building blocks are shared with the real processors, that is source
+/// matching, glue (including EEL and Lua scripts), feedback value calculation and parameter-based
+/// activation conditions. Routing via virtual control elements is re-implemented here. REAPER
+/// targets are substituted with [`MockTarget`]s, which simply remember the value they were set to.
+///
+/// Not simulated: the split between real-time and main-thread processing, group interaction,
+/// "Send feedback after control", feedback policies, source takeover between units, target-based
+/// activation conditions, target activation conditions (e.g. "track must be selected"), projection
+/// feedback and glue features that depend on time passing by (e.g. "Fire after timeout").
+pub struct HeadlessUnit {
+    mappings: EnumMap<CompartmentKind, Vec<HeadlessMapping>>,
+    params: PluginParams,
+    nrpn_scanner: PollingParameterNumberMessageScanner,
+    cc_14_bit_scanner: ControlChange14BitMessageScanner,
+    source_state: RealearnSourceState,
+}
+
+struct HeadlessMapping {
+    key: MappingKey,
+    mapping: MainMapping,
+    /// Is `Some` if the mapping has a REAPER target.
+    target: Option<MockTarget>,
+}
+
+impl HeadlessUnit {
+    pub fn new(
+        mappings: impl IntoIterator<Item = (MappingKey, MainMapping)>,
+        params: PluginParams,
+    ) -> Self {
+        let mut headless_mappings: EnumMap<CompartmentKind, Vec<HeadlessMapping>> =
+            Default::default();
+        for (key, mut mapping) in mappings {
+            mapping.init_target_and_activation_headless(&params);
+            let target = if mapping.has_reaper_target() {
+                Some(MockTarget::default())
+            } else {
+                None
+            };
+            headless_mappings[mapping.compartment()].push(HeadlessMapping {
+                key,
+                mapping,
+                target,
+            });
+        }
+        Self {
+            mappings: headless_mappings,
+            params,
+            nrpn_scanner: PollingParameterNumberMessageScanner::new(Duration::from_millis(1)),
+            cc_14_bit_scanner: Default::default(),
+            source_state: Default::default(),
+        }
+    }
+
+    /// Replaces the mock target of the given mapping, e.g. in order to simulate a discrete target.
+    pub fn set_target(
+        &mut self,
+        compartment: CompartmentKind,
+        key: &MappingKey,
+        target: MockTarget,
+    ) -> anyhow::Result<()> {
+        let m = self.find_mapping_mut(compartment, key)?;
+        let mock_target = m
+            .target
+            .as_mut()
+            .context("mapping doesn't have a REAPER target")?;
+        *mock_target = target;
+        Ok(())
+    }
+
+    pub fn target(&self, compartment: CompartmentKind, key: &MappingKey) -> Option<&MockTarget> {
+        self.mappings[compartment]
+            .iter()
+            .find(|m| &m.key == key)?
+            .target
+            .as_ref()
+    }
+
+    /// Returns the feedback which is sent when the unit is loaded.
+    pub fn feedback_all(&mut self) -> Vec<FinalSourceFeedbackValue> {
+        let this = &*self;
+        let feedback_values: Vec<_> = CompartmentKind::enum_iter()
+            .flat_map(|compartment| {
+                (0..this.mappings[compartment].len())
+                    .filter_map(move |i| this.feedback_of_mapping(compartment, i))
+            })
+            .collect();
+        self.finalize_feedback(feedback_values)
+    }
+
+    /// Processes an incoming short MIDI message and returns the resulting feedback.
+    pub fn send_midi(&mut self, msg: RawShortMessage) -> Vec<FinalSourceFeedbackValue> {
+        let mut changed_mappings = vec![];
+        self.control_midi(&MidiSourceValue::Plain(msg), &mut changed_mappings);
+        for nrpn_msg in self.nrpn_scanner.feed(&msg).into_iter().flatten() {
+            self.control_midi(
+                &MidiSourceValue::ParameterNumber(nrpn_msg),
+                &mut changed_mappings,
+            );
+        }
+        if let Some(cc14_msg) = self.cc_14_bit_scanner.feed(&msg) {
+            self.control_midi(
+                &MidiSourceValue::ControlChange14Bit(cc14_msg),
+                &mut changed_mappings,
+            );
+        }
+        self.feedback_of_changed_mappings(changed_mappings)
+    }
+
+    /// Processes an incoming MIDI system-exclusive message and returns the resulting feedback.
+    pub fn send_sys_ex(&mut self, bytes: &[u8]) -> Vec<FinalSourceFeedbackValue> {
+        let mut changed_mappings = vec![];
+        self.control_midi(
+            &MidiSourceValue::BorrowedSysEx(bytes),
+            &mut changed_mappings,
+        );
+        self.feedback_of_changed_mappings(changed_mappings)
+    }
+
+    /// Simulates a target value change that didn't originate from the unit itself (e.g. the user
+    /// moving a fader in REAPER) and returns the resulting feedback.
+    pub fn change_target_value(
+        &mut self,
+        compartment: CompartmentKind,
+        key: &MappingKey,
+        value: AbsoluteValue,
+    ) -> anyhow::Result<Vec<FinalSourceFeedbackValue>> {
+        let index = self.mappings[compartment]
+            .iter()
+            .position(|m| &m.key == key)
+            .with_context(|| format!("mapping {key} not found in {compartment}"))?;
+        let target = self.mappings[compartment][index]
+            .target
+            .as_mut()
+            .context("mapping doesn't have a REAPER target")?;
+        target.value = value;
+        Ok(self.feedback_of_changed_mappings(vec![(compartment, index)]))
+    }
+
+    /// Changes a compartment parameter and returns the feedback caused by mappings getting
+    /// activated or deactivated.
+    pub fn set_parameter(
+        &mut self,
+        compartment: CompartmentKind,
+        index: CompartmentParamIndex,
+        value: RawParamValue,
+    ) -> Vec<FinalSourceFeedbackValue> {
+        self.params
+            .compartment_params_mut(compartment)
+            .at_mut(index)
+            .set_raw_value(value);
+        let mut feedback_values = vec![];
+        for i in 0..self.mappings[compartment].len() {
+            let m = &mut self.mappings[compartment][i];
+            let was_active_before = m.mapping.is_active_in_terms_of_activation_state();
+            m.mapping.update_activation_from_params(&self.params);
+            let is_active_now = m.mapping.is_active_in_terms_of_activation_state();
+            if is_active_now == was_active_before {
+                continue;
+            }
+            let feedback_value = if is_active_now {
+                self.feedback_of_mapping(compartment, i)
+            } else {
+                m.mapping
+                    .off_feedback(source_context(), NoopLogger)
+                    .map(|v| v.value)
+            };
+            feedback_values.extend(feedback_value);
+        }
+        self.finalize_feedback(feedback_values)
+    }
+
+    fn find_mapping_mut(
+        &mut self,
+        compartment: CompartmentKind,
+        key: &MappingKey,
+    ) -> anyhow::Result<&mut HeadlessMapping> {
+        self.mappings[compartment]
+            .iter_mut()
+            .find(|m| &m.key == key)
+            .with_context(|| format!("mapping {key} not found in {compartment}"))
+    }
+
+    fn control_midi(
+        &mut self,
+        value: &MidiSourceValue<RawShortMessage>,
+        changed_mappings: &mut Vec<(CompartmentKind, usize)>,
+    ) {
+        let timestamp = ControlEventTimestamp::now();
+        for compartment in CompartmentKind::enum_iter() {
+            for i in 0..self.mappings[compartment].len() {
+                let mapping = &mut self.mappings[compartment][i].mapping;
+                if !mapping.control_is_effectively_on() {
+                    continue;
+                }
+                let Some(control_value) =
+                    mapping.control_midi_source(ControlEvent::new(value, timestamp))
+                else {
+                    continue;
+                };
+                let evt = ControlEvent::new(control_value, timestamp);
+                self.control_mapping(compartment, i, evt, changed_mappings);
+            }
+        }
+    }
+
+    fn control_virtual(
+        &mut self,
+        evt: ControlEvent<VirtualSourceValue>,
+        changed_mappings: &mut Vec<(CompartmentKind, usize)>,
+    ) {
+        let compartment = CompartmentKind::Main;
+        for i in 0..self.mappings[compartment].len() {
+            let mapping = &mut self.mappings[compartment][i].mapping;
+            if !mapping.control_is_effectively_on() {
+                continue;
+            }
+            let virtual_source_value = evt.payload();
+            let Some(control_value) =
+                mapping.control_virtual_source(evt.with_payload(&virtual_source_value))
+            else {
+                continue;
+            };
+            self.control_mapping(
+                compartment,
+                i,
+                evt.with_payload(control_value),
+                changed_mappings,
+            );
+        }
+    }
+
+    fn control_mapping(
+        &mut self,
+        compartment: CompartmentKind,
+        index: usize,
+        evt: ControlEvent<ControlValue>,
+        changed_mappings: &mut Vec<(CompartmentKind, usize)>,
+    ) {
+        let m = &mut self.mappings[compartment][index];
+        if let Some(t) = m.mapping.virtual_target().copied() {
+            // Controller mapping with virtual target
+            let Some(ModeControlResult::HitTarget { value }) =
+                m.mapping.control_given_target(evt, &t)
+            else {
+                return;
+            };
+            let virtual_source_value = VirtualSourceValue::new(t.control_element(), value);
+            self.control_virtual(evt.with_payload(virtual_source_value), changed_mappings);
+        } else if let Some(target) = m.target.as_mut() {
+            // Mapping with REAPER target
+            let Some(ModeControlResult::HitTarget { value }) =
+                m.mapping.control_given_target(evt, &*target)
+            else {
+                return;
+            };
+            if target.hit(value) {
+                changed_mappings.push((compartment, index));
+            }
+        }
+    }
+
+    fn feedback_of_changed_mappings(
+        &mut self,
+        changed_mappings: Vec<(CompartmentKind, usize)>,
+    ) -> Vec<FinalSourceFeedbackValue> {
+        let feedback_values: Vec<_> = changed_mappings
+            .into_iter()
+            .filter_map(|(compartment, i)| self.feedback_of_mapping(compartment, i))
+            .collect();
+        self.finalize_feedback(feedback_values)
+    }
+
+    fn feedback_of_mapping(
+        &self,
+        compartment: CompartmentKind,
+        index: usize,
+    ) -> Option<SpecificCompoundFeedbackValue> {
+        let m = &self.mappings[compartment][index];
+        if !m.mapping.feedback_is_effectively_on() {
+            return None;
+        }
+        let target = m.target.as_ref()?;
+        m.mapping.feedback_given_prop_provider(
+            false,
+            true,
+            Some(target.value),
+            source_context(),
+            target,
+            NoopLogger,
+        )
+    }
+
+    /// Translates virtual feedback into real feedback via controller mappings and produces the
+    /// final source feedback values.
+    fn finalize_feedback(
+        &mut self,
+        feedback_values: Vec<SpecificCompoundFeedbackValue>,
+    ) -> Vec<FinalSourceFeedbackValue> {
+        let mut real_feedback_values = vec![];
+        for feedback_value in feedback_values {
+            match feedback_value {
+                SpecificCompoundFeedbackValue::Virtual {
+                    values,
+                    destinations,
+                } => {
+                    for value in values {
+                        let controller_mappings = self.mappings[CompartmentKind::Controller]
+                            .iter()
+                            .filter(|m| m.mapping.feedback_is_effectively_on());
+                        for m in controller_mappings {
+                            let Some(t) = m.mapping.virtual_target() else {
+                                continue;
+                            };
+                            if t.control_element() != value.control_element() {
+                                continue;
+                            }
+                            let compound_feedback_value = m.mapping.feedback_given_target_value(
+                                Cow::Borrowed(value.feedback_value()),
+                                FeedbackDestinations {
+                                    with_source_feedback: destinations.with_source_feedback
+                                        && m.mapping.feedback_is_enabled(),
+                                    ..destinations
+                                },
+                                source_context(),
+                                None,
+                                NoopLogger,
+                            );
+                            if let Some(SpecificCompoundFeedbackValue::Real(v)) =
+                                compound_feedback_value
+                            {
+                                real_feedback_values.push(v);
+                            }
+                        }
+                    }
+                }
+                SpecificCompoundFeedbackValue::Real(v) => {
+                    real_feedback_values.push(v);
+                }
+            }
+        }
+        let mut collector = FeedbackCollector::new(&mut self.source_state, None);
+        real_feedback_values
+            .into_iter()
+            .filter_map(|v| collector.process(v, None)?.source)
+            .collect()
+    }
+}
+
+/// Stand-in for a REAPER target which remembers the value it was set to.
+#[derive(Clone, Debug)]
+pub struct MockTarget {
+    control_type: ControlType,
+    value: AbsoluteValue,
+    hit_count: u32,
+}
+
+impl Default for MockTarget {
+    fn default() -> Self {
+        Self::continuous(UnitValue::MIN)
+    }
+}
+
+impl MockTarget {
+    pub fn continuous(value: UnitValue) -> Self {
+        Self {
+            control_type: ControlType::AbsoluteContinuous,
+            value: AbsoluteValue::Continuous(value),
+            hit_count: 0,
+        }
+    }
+
+    /// Like a continuous target but doesn't ignore repeated values, e.g. an action trigger.
+    pub fn retriggerable(value: UnitValue) -> Self {
+        Self {
+            control_type: ControlType::AbsoluteContinuousRetriggerable,
+            ..Self::continuous(value)
+        }
+    }
+
+    /// A target with the given number of discrete values, e.g. a preset selector.
+    pub fn discrete(count: u32, index: u32) -> Self {
+        Self {
+            control_type: ControlType::AbsoluteDiscrete {
+                atomic_step_size: convert_count_to_step_size(count),
+                is_retriggerable: false,
+            },
+            value: AbsoluteValue::Discrete(Fraction::new(index, count.saturating_sub(1))),
+            hit_count: 0,
+        }
+    }
+
+    /// A target which only understands increments and decrements.
+    pub fn relative(value: UnitValue) -> Self {
+        Self {
+            control_type: ControlType::Relative,
+            ..Self::continuous(value)
+        }
+    }
+
+    pub fn value(&self) -> AbsoluteValue {
+        self.value
+    }
+
+    /// Returns how often the target has been hit, including hits which didn't change its value.
+    pub fn hit_count(&self) -> u32 {
+        self.hit_count
+    }
+
+    /// Returns whether the value changed.
+    fn hit(&mut self, value: ControlValue) -> bool {
+        self.hit_count += 1;
+        let new_value = match value {
+            ControlValue::AbsoluteContinuous(v) => AbsoluteValue::Continuous(v),
+            ControlValue::AbsoluteDiscrete(f) => AbsoluteValue::Discrete(f),
+            ControlValue::RelativeDiscrete(i) => self.incremented_value(i.get()),
+            ControlValue::RelativeContinuous(i) => {
+                self.incremented_value(i.to_discrete_increment().get())
+            }
+        };
+        let changed = new_value.to_unit_value() != self.value.to_unit_value();
+        self.value = new_value;
+        changed
+    }
+
+    fn incremented_value(&self, increment: i32) -> AbsoluteValue {
+        match self.value {
+            AbsoluteValue::Discrete(f) => {
+                let actual = (f.actual() as i64 + increment as i64).clamp(0, f.max_val() as i64);
+                AbsoluteValue::Discrete(Fraction::new(actual as u32, f.max_val()))
+            }
+            AbsoluteValue::Continuous(v) => {
+                let new_value = v.get() + increment as f64 * MOCK_TARGET_RELATIVE_STEP_SIZE;
+                AbsoluteValue::Continuous(UnitValue::new_clamped(new_value))
+            }
+        }
+    }
+}
+
+impl<'a> Target<'a> for MockTarget {
+    type Context = ();
+
+    fn current_value(&self, _: ()) -> Option<AbsoluteValue> {
+        Some(self.value)
+    }
+
+    fn control_type(&self, _: ()) -> ControlType {
+        self.control_type
+    }
+}
+
+impl PropProvider for MockTarget {
+    fn get_prop_value(&self, key: &str) -> Option<PropValue> {
+        match key {
+            "y" | "target.value" => Some(PropValue::Normalized(self.value.to_unit_value())),
+            _ => None,
+        }
+    }
+}
+
+fn source_context() -> RealearnSourceContext<'static> {
+    RealearnSourceContext {
+        additional_script_input: Default::default(),
+    }
+}
@@ -1,11 +1,14 @@
 use crate::domain::{MidiControlInput, MidiDestination, OscDeviceId};
 use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
+use std::iter;
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum ControlInput {
     Midi(MidiControlInput),
     Osc(OscDeviceId),
     Keyboard,
+    /// Control messages sent by WebSocket clients acting as virtual controller device.
+    WebSocket,
 }
 
 impl ControlInput {
@@ -58,6 +61,8 @@ pub enum DeviceControlInput {
 pub enum FeedbackOutput {
     Midi(MidiDestination),
     Osc(OscDeviceId),
+    /// Feedback sent to WebSocket clients acting as virtual controller device.
+    WebSocket,
 }
 
 impl FeedbackOutput {
@@ -76,6 +81,17 @@ impl FeedbackOutput {
         }
     }
 
+    /// Short identifier of this output, used as label value when recording metrics.
+    pub fn metric_label(&self) -> String {
+        use FeedbackOutput::*;
+        match self {
+            Midi(MidiDestination::FxOutput) => "fx-output".to_string(),
+            Midi(MidiDestination::Device(id)) => format!("midi-{}", id.get()),
+            Osc(id) => format!("osc-{id}"),
+            WebSocket => "web-socket".to_string(),
+        }
+    }
+
     pub fn device_output(self) -> Option<DeviceFeedbackOutput> {
         use FeedbackOutput::*;
         match self {
@@ -91,3 +107,221 @@ pub enum DeviceFeedbackOutput {
     Midi(MidiOutputDeviceId),
     Osc(OscDeviceId),
 }
+
+/// Control inputs and feedback outputs which a unit uses in addition to its main control input
+/// and feedback output.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct AdditionalIo {
+    pub control_inputs: Vec<DeviceControlInput>,
+    pub feedback_outputs: Vec<DeviceFeedbackOutput>,
+}
+
+impl AdditionalIo {
+    pub fn is_empty(&self) -> bool {
+        self.control_inputs.is_empty() && self.feedback_outputs.is_empty()
+    }
+
+    pub fn contains_control_input(&self, input: ControlInput) -> bool {
+        match input.device_input() {
+            None => false,
+            Some(i) => self.control_inputs.contains(&i),
+        }
+    }
+
+    pub fn toggle_control_input(&mut self, input: DeviceControlInput) {
+        if let Some(i) = self.control_inputs.iter().position(|i| *i == input) {
+            self.control_inputs.remove(i);
+        } else {
+            self.control_inputs.push(input);
+        }
+    }
+
+    /// Returns the given main control input followed by all additional control inputs.
+    pub fn control_inputs(
+        &self,
+        main_input: ControlInput,
+    ) -> impl Iterator<Item = ControlInput> + '_ {
+        let additional_inputs = self
+            .control_inputs
+            .iter()
+            .map(|i| ControlInput::from_device_input(*i))
+            .filter(move |i| *i != main_input);
+        iter::once(main_input).chain(additional_inputs)
+    }
+
+    /// Returns the given main feedback output followed by all additional feedback outputs.
+    pub fn feedback_outputs(
+        &self,
+        main_output: Option<FeedbackOutput>,
+    ) -> impl Iterator<Item = FeedbackOutput> + '_ {
+        let additional_outputs = self
+            .feedback_outputs
+            .iter()
+            .map(|o| FeedbackOutput::from_device_output(*o))
+            .filter(move |o| Some(*o) != main_output);
+        main_output.into_iter().chain(additional_outputs)
+    }
+
+    /// Returns the MIDI destinations among [`Self::feedback_outputs`].
+    pub fn midi_destinations(
+        &self,
+        main_output: Option<FeedbackOutput>,
+    ) -> impl Iterator<Item = MidiDestination> + '_ {
+        self.feedback_outputs(main_output)
+            .filter_map(|o| o.midi_destination())
+    }
+
+    pub fn toggle_feedback_output(&mut self, output: DeviceFeedbackOutput) {
+        if let Some(i) = self.feedback_outputs.iter().position(|o| *o == output) {
+            self.feedback_outputs.remove(i);
+        } else {
+            self.feedback_outputs.push(output);
+        }
+    }
+}
+
+/// Restricts a mapping to particular control inputs and feedback outputs of its unit.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct MappingIoSelection {
+    /// `None` means the mapping reacts to all control inputs of the unit.
+    pub control_input: Option<ControlInput>,
+    /// `None` means the mapping sends feedback to all feedback outputs of the unit.
+    pub feedback_output: Option<FeedbackOutput>,
+}
+
+impl MappingIoSelection {
+    /// Returns whether the mapping should react to an event coming from the given input.
+    ///
+    /// Events whose origin is unknown or not tied to a particular input are always accepted.
+    pub fn accepts_control_input(&self, origin: Option<ControlInput>) -> bool {
+        match (self.control_input, origin) {
+            (Some(selected), Some(origin)) => selected == origin,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feedback_outputs_include_main_and_additional_outputs() {
+        // Given
+        let osc_dev_id = OscDeviceId::random();
+        let io = AdditionalIo {
+            control_inputs: vec![],
+            feedback_outputs: vec![
+                DeviceFeedbackOutput::Midi(MidiOutputDeviceId::new(2)),
+                DeviceFeedbackOutput::Osc(osc_dev_id),
+                DeviceFeedbackOutput::Midi(MidiOutputDeviceId::new(1)),
+            ],
+        };
+        let main_output = Some(FeedbackOutput::Midi(MidiDestination::Device(
+            MidiOutputDeviceId::new(1),
+        )));
+        // When
+        let outputs: Vec<_> = io.feedback_outputs(main_output).collect();
+        let midi_destinations: Vec<_> = io.midi_destinations(main_output).collect();
+        // Then
+        assert_eq!(
+            outputs,
+            vec![
+                FeedbackOutput::Midi(MidiDestination::Device(MidiOutputDeviceId::new(1))),
+                FeedbackOutput::Midi(MidiDestination::Device(MidiOutputDeviceId::new(2))),
+                FeedbackOutput::Osc(osc_dev_id),
+            ]
+        );
+        assert_eq!(
+            midi_destinations,
+            vec![
+                MidiDestination::Device(MidiOutputDeviceId::new(1)),
+                MidiDestination::Device(MidiOutputDeviceId::new(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn feedback_outputs_without_main_output() {
+        // Given
+        let io = AdditionalIo {
+            control_inputs: vec![],
+            feedback_outputs: vec![DeviceFeedbackOutput::Midi(MidiOutputDeviceId::new(3))],
+        };
+        // When
+        let midi_destinations: Vec<_> = io.midi_destinations(None).collect();
+        // Then
+        assert_eq!(
+            midi_destinations,
+            vec![MidiDestination::Device(MidiOutputDeviceId::new(3))]
+        );
+    }
+
+    #[test]
+    fn control_inputs_include_main_and_additional_inputs() {
+        // Given
+        let osc_dev_id = OscDeviceId::random();
+        let io = AdditionalIo {
+            control_inputs: vec![
+                DeviceControlInput::Osc(osc_dev_id),
+                DeviceControlInput::Midi(MidiInputDeviceId::new(4)),
+            ],
+            feedback_outputs: vec![],
+        };
+        // When
+        let inputs: Vec<_> = io.control_inputs(ControlInput::Osc(osc_dev_id)).collect();
+        // Then
+        assert_eq!(
+            inputs,
+            vec![
+                ControlInput::Osc(osc_dev_id),
+                ControlInput::Midi(MidiControlInput::Device(MidiInputDeviceId::new(4))),
+            ]
+        );
+    }
+
+    #[test]
+    fn io_selection_without_control_input_accepts_everything() {
+        // Given
+        let selection = MappingIoSelection::default();
+        // When
+        // Then
+        assert!(selection.accepts_control_input(None));
+        assert!(selection.accepts_control_input(Some(ControlInput::Keyboard)));
+        assert!(selection.accepts_control_input(Some(ControlInput::Midi(
+            MidiControlInput::Device(MidiInputDeviceId::new(5))
+        ))));
+    }
+
+    #[test]
+    fn io_selection_with_control_input_accepts_only_that_input() {
+        // Given
+        let selected = ControlInput::Midi(MidiControlInput::Device(MidiInputDeviceId::new(5)));
+        let selection = MappingIoSelection {
+            control_input: Some(selected),
+            feedback_output: None,
+        };
+        // When
+        // Then
+        assert!(selection.accepts_control_input(Some(selected)));
+        assert!(!selection.accepts_control_input(Some(ControlInput::Midi(
+            MidiControlInput::Device(MidiInputDeviceId::new(6))
+        ))));
+        assert!(
+            !selection.accepts_control_input(Some(ControlInput::Midi(MidiControlInput::FxInput)))
+        );
+        assert!(!selection.accepts_control_input(Some(ControlInput::Osc(OscDeviceId::random()))));
+    }
+
+    #[test]
+    fn io_selection_accepts_events_of_unknown_origin() {
+        // Given
+        let selection = MappingIoSelection {
+            control_input: Some(ControlInput::Osc(OscDeviceId::random())),
+            feedback_output: None,
+        };
+        // When
+        // Then
+        assert!(selection.accepts_control_input(None));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::domain::{CompartmentParamIndex, RawParamValue};
+use helgoboss_learn::{ControlValue, UnitValue};
+use helgobox_api::persistence::LfoWaveform;
+use reaper_high::{Project, Reaper};
+use std::f64::consts::TAU;
+use std::time::Instant;
+
+/// A source which continuously generates values following a periodic waveform.
+///
+/// The phase is either derived from wall-clock time (free-running) or from the beat position
+/// of the current project (tempo-synced).
+#[derive(Clone, PartialEq, Debug)]
+pub struct LfoSource {
+    settings: LfoSettings,
+    state: LfoState,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LfoSettings {
+    pub waveform: LfoWaveform,
+    /// Cycles per second. Only used if not tempo-synced.
+    pub frequency: f64,
+    /// If set, the LFO is synced to the project and completes one cycle every this many beats.
+    pub beats_per_cycle: Option<f64>,
+    /// Phase offset in cycles (0.0 to 1.0).
+    pub phase_offset: f64,
+    /// If set, the LFO restarts its cycle whenever this compartment parameter is set to a
+    /// non-zero value.
+    pub retrigger_parameter_index: Option<CompartmentParamIndex>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct LfoState {
+    /// Point in time at which the free-running LFO started its current run.
+    start: Option<Instant>,
+    /// Beat position at which the tempo-synced LFO was retriggered the last time.
+    beat_origin: f64,
+    last_value: Option<UnitValue>,
+    sample_and_hold: SampleAndHoldState,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct SampleAndHoldState {
+    cycle: Option<i64>,
+    value: f64,
+    rng_state: u32,
+}
+
+impl LfoSource {
+    pub fn new(settings: LfoSettings) -> Self {
+        Self {
+            settings,
+            state: LfoState {
+                start: None,
+                beat_origin: 0.0,
+                last_value: None,
+                sample_and_hold: SampleAndHoldState {
+                    cycle: None,
+                    value: 0.0,
+                    // Any non-zero seed works for xorshift.
+                    rng_state: 0x9E37_79B9,
+                },
+            },
+        }
+    }
+
+    pub fn settings(&self) -> &LfoSettings {
+        &self.settings
+    }
+
+    pub fn on_deactivate(&mut self) {
+        self.state.start = None;
+        self.state.last_value = None;
+    }
+
+    /// Restarts the cycle if the given parameter is the retrigger parameter and has been set to
+    /// a non-zero value.
+    pub fn process_parameter_change(&mut self, index: CompartmentParamIndex, value: RawParamValue) {
+        if self.settings.retrigger_parameter_index != Some(index) || value <= 0.0 {
+            return;
+        }
+        self.retrigger();
+    }
+
+    pub fn retrigger(&mut self) {
+        self.state.start = Some(Instant::now());
+        self.state.beat_origin = current_beat_position(Reaper::get().current_project());
+        self.state.sample_and_hold.cycle = None;
+    }
+
+    pub fn poll(&mut self) -> Option<ControlValue> {
+        let raw_phase = self.current_raw_phase();
+        let value = self.value_at(raw_phase);
+        if self.state.last_value == Some(value) {
+            return None;
+        }
+        self.state.last_value = Some(value);
+        Some(ControlValue::AbsoluteContinuous(value))
+    }
+
+    /// Returns the number of cycles elapsed since start, including the phase offset.
+    fn current_raw_phase(&mut self) -> f64 {
+        let cycles = match self.settings.beats_per_cycle {
+            Some(beats_per_cycle) if beats_per_cycle > 0.0 => {
+                let beats = current_beat_position(Reaper::get().current_project());
+                (beats - self.state.beat_origin) / beats_per_cycle
+            }
+            _ => {
+                let start = *self.state.start.get_or_insert_with(Instant::now);
+                start.elapsed().as_secs_f64() * self.settings.frequency.max(0.0)
+            }
+        };
+        cycles + self.settings.phase_offset
+    }
+
+    fn value_at(&mut self, raw_phase: f64) -> UnitValue {
+        let phase = raw_phase.rem_euclid(1.0);
+        use LfoWaveform::*;
+        let value = match self.settings.waveform {
+            Sine => 0.5 + 0.5 * (TAU * phase).sin(),
+            Triangle => {
+                if phase < 0.5 {
+                    2.0 * phase
+                } else {
+                    2.0 - 2.0 * phase
+                }
+            }
+            Saw => phase,
+            Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            SampleAndHold => self
+                .state
+                .sample_and_hold
+                .value_for_cycle(raw_phase.floor() as i64),
+        };
+        UnitValue::new_clamped(value)
+    }
+}
+
+impl SampleAndHoldState {
+    fn value_for_cycle(&mut self, cycle: i64) -> f64 {
+        if self.cycle != Some(cycle) {
+            self.cycle = Some(cycle);
+            self.value = self.next_random();
+        }
+        self.value
+    }
+
+    /// Simple xorshift PRNG, good enough for modulation purposes.
+    fn next_random(&mut self) -> f64 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        x as f64 / u32::MAX as f64
+    }
+}
+
+pub(crate) fn current_beat_position(project: Project) -> f64 {
+    let reference_pos = if project.is_playing() {
+        project.play_position_latency_compensated()
+    } else {
+        project.edit_cursor_position()
+    };
+    project.beat_info_at(reference_pos).full_beats.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_lfo(waveform: LfoWaveform) -> LfoSource {
+        LfoSource::new(LfoSettings {
+            waveform,
+            frequency: 1.0,
+            beats_per_cycle: None,
+            phase_offset: 0.0,
+            retrigger_parameter_index: None,
+        })
+    }
+
+    fn values_at(lfo: &mut LfoSource, phases: &[f64]) -> Vec<f64> {
+        phases
+            .iter()
+            .map(|phase| {
+                let value = lfo.value_at(*phase).get();
+                // Round to get rid of floating point noise
+                (value * 1_000_000.0).round() / 1_000_000.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn waveforms_at_known_phases() {
+        // Given
+        let phases = [0.0, 0.25, 0.5, 0.75];
+        // When
+        // Then
+        assert_eq!(
+            values_at(&mut create_lfo(LfoWaveform::Sine), &phases),
+            vec![0.5, 1.0, 0.5, 0.0]
+        );
+        assert_eq!(
+            values_at(&mut create_lfo(LfoWaveform::Triangle), &phases),
+            vec![0.0, 0.5, 1.0, 0.5]
+        );
+        assert_eq!(
+            values_at(&mut create_lfo(LfoWaveform::Saw), &phases),
+            vec![0.0, 0.25, 0.5, 0.75]
+        );
+        assert_eq!(
+            values_at(&mut create_lfo(LfoWaveform::Square), &phases),
+            vec![1.0, 1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn phase_wraps_around() {
+        // Given
+        let mut lfo = create_lfo(LfoWaveform::Saw);
+        // When
+        let values = values_at(&mut lfo, &[1.25, 3.5, -0.25]);
+        // Then
+        assert_eq!(values, vec![0.25, 0.5, 0.75]);
+    }
+
+    #[test]
+    fn sample_and_hold() {
+        // Given
+        let mut lfo = create_lfo(LfoWaveform::SampleAndHold);
+        // When
+        let values = values_at(&mut lfo, &[0.1, 0.5, 0.9, 1.1, 1.9, 2.1]);
+        // Then
+        // Value is held within each cycle ...
+        assert_eq!(values[0], values[1]);
+        assert_eq!(values[1], values[2]);
+        assert_eq!(values[3], values[4]);
+        // ... and changes with each new cycle
+        assert_ne!(values[2], values[3]);
+        assert_ne!(values[4], values[5]);
+        assert!(values.iter().all(|v| (0.0..=1.0).contains(v)));
+    }
+}
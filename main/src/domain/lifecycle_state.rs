@@ -0,0 +1,229 @@
+use crate::domain::{LifecycleActions, LifecycleMainData, LifecyclePhase, QualifiedMappingId};
+use base::hash_util::NonCryptoIndexMap;
+
+/// Keeps track of the mappings whose main-thread activation actions have been executed.
+///
+/// That's what we use to execute the deactivation actions later, also if the mapping has been
+/// removed in the meantime. The only deactivation action which has no effect for removed mappings
+/// is setting the target value (because there's no target anymore).
+#[derive(Debug, Default)]
+pub struct LifecycleState {
+    /// Ordered by activation.
+    active: NonCryptoIndexMap<QualifiedMappingId, LifecycleMainData>,
+}
+
+/// A lifecycle phase which needs to be executed for a particular mapping.
+#[derive(Debug)]
+pub struct LifecycleStep {
+    pub mapping_id: QualifiedMappingId,
+    pub phase: LifecyclePhase,
+    pub data: LifecycleMainData,
+}
+
+impl LifecycleStep {
+    pub fn actions(&self) -> &LifecycleActions {
+        self.data.actions(self.phase)
+    }
+}
+
+impl LifecycleState {
+    pub fn is_active(&self, mapping_id: QualifiedMappingId) -> bool {
+        self.active.contains_key(&mapping_id)
+    }
+
+    /// Returns the IDs of all mappings whose activation actions have been executed, in the order
+    /// of activation.
+    pub fn active_mapping_ids(&self) -> impl Iterator<Item = QualifiedMappingId> + '_ {
+        self.active.keys().copied()
+    }
+
+    /// Compares the active lifecycle data of the given mappings with the desired one, updates the
+    /// state and returns the lifecycle phases which need to be executed.
+    ///
+    /// `desired_data` should return `None` if the mapping is off, doesn't have main-thread
+    /// lifecycle actions or doesn't exist anymore. Mappings whose lifecycle data didn't change are
+    /// skipped. All deactivations come before all activations. Within each phase, the order of
+    /// `mapping_ids` is preserved.
+    pub fn sync<'a>(
+        &mut self,
+        mapping_ids: &[QualifiedMappingId],
+        desired_data: impl Fn(QualifiedMappingId) -> Option<&'a LifecycleMainData>,
+    ) -> Vec<LifecycleStep> {
+        let mut steps = vec![];
+        for &mapping_id in mapping_ids {
+            let Some(active_data) = self.active.get(&mapping_id) else {
+                continue;
+            };
+            if desired_data(mapping_id) == Some(active_data) {
+                continue;
+            }
+            if let Some(data) = self.active.shift_remove(&mapping_id) {
+                steps.push(LifecycleStep {
+                    mapping_id,
+                    phase: LifecyclePhase::Deactivation,
+                    data,
+                });
+            }
+        }
+        for &mapping_id in mapping_ids {
+            if self.active.contains_key(&mapping_id) {
+                continue;
+            }
+            let Some(data) = desired_data(mapping_id) else {
+                continue;
+            };
+            self.active.insert(mapping_id, data.clone());
+            steps.push(LifecycleStep {
+                mapping_id,
+                phase: LifecyclePhase::Activation,
+                data: data.clone(),
+            });
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{CompartmentKind, MappingId};
+    use base::hash_util::NonCryptoHashMap;
+
+    #[test]
+    fn activation_in_mapping_order() {
+        // Given
+        let mut state = LifecycleState::default();
+        let ids = [id(), id(), id()];
+        let desired = desired(&[(ids[0], "a"), (ids[1], "b"), (ids[2], "c")]);
+        // When
+        let steps = state.sync(&[ids[2], ids[0], ids[1]], |id| desired.get(&id));
+        // Then
+        assert_eq!(
+            summarize(&steps),
+            vec![
+                (ids[2], LifecyclePhase::Activation),
+                (ids[0], LifecyclePhase::Activation),
+                (ids[1], LifecyclePhase::Activation),
+            ]
+        );
+        assert!(ids.iter().all(|id| state.is_active(*id)));
+    }
+
+    #[test]
+    fn deactivation_before_activation() {
+        // Given
+        let mut state = LifecycleState::default();
+        let ids = [id(), id()];
+        let old_desired = desired(&[(ids[1], "b")]);
+        state.sync(&ids[1..], |id| old_desired.get(&id));
+        // When
+        let new_desired = desired(&[(ids[0], "a")]);
+        let steps = state.sync(&ids, |id| new_desired.get(&id));
+        // Then
+        assert_eq!(
+            summarize(&steps),
+            vec![
+                (ids[1], LifecyclePhase::Deactivation),
+                (ids[0], LifecyclePhase::Activation),
+            ]
+        );
+        assert_eq!(state.active_mapping_ids().collect::<Vec<_>>(), vec![ids[0]]);
+    }
+
+    #[test]
+    fn unchanged_mappings_are_skipped() {
+        // Given
+        let mut state = LifecycleState::default();
+        let ids = [id()];
+        let desired = desired(&[(ids[0], "a")]);
+        state.sync(&ids, |id| desired.get(&id));
+        // When
+        let steps = state.sync(&ids, |id| desired.get(&id));
+        // Then
+        assert!(steps.is_empty());
+        assert!(state.is_active(ids[0]));
+    }
+
+    #[test]
+    fn changed_data_causes_deactivation_and_activation() {
+        // Given
+        let mut state = LifecycleState::default();
+        let ids = [id()];
+        let old_desired = desired(&[(ids[0], "old")]);
+        state.sync(&ids, |id| old_desired.get(&id));
+        // When
+        let new_desired = desired(&[(ids[0], "new")]);
+        let steps = state.sync(&ids, |id| new_desired.get(&id));
+        // Then
+        assert_eq!(
+            summarize(&steps),
+            vec![
+                (ids[0], LifecyclePhase::Deactivation),
+                (ids[0], LifecyclePhase::Activation),
+            ]
+        );
+        assert_eq!(&steps[0].data, &old_desired[&ids[0]]);
+        assert_eq!(&steps[1].data, &new_desired[&ids[0]]);
+    }
+
+    #[test]
+    fn removed_mapping_is_deactivated_with_previous_data() {
+        // Given
+        let mut state = LifecycleState::default();
+        let ids = [id()];
+        let desired = desired(&[(ids[0], "a")]);
+        state.sync(&ids, |id| desired.get(&id));
+        // When
+        let steps = state.sync(&ids, |_| None);
+        // Then
+        assert_eq!(
+            summarize(&steps),
+            vec![(ids[0], LifecyclePhase::Deactivation)]
+        );
+        assert_eq!(&steps[0].data, &desired[&ids[0]]);
+        assert!(!state.is_active(ids[0]));
+    }
+
+    #[test]
+    fn mappings_not_given_are_left_alone() {
+        // Given
+        let mut state = LifecycleState::default();
+        let ids = [id(), id()];
+        let desired = desired(&[(ids[0], "a"), (ids[1], "b")]);
+        state.sync(&ids, |id| desired.get(&id));
+        // When
+        let steps = state.sync(&ids[1..], |_| None);
+        // Then
+        assert_eq!(
+            summarize(&steps),
+            vec![(ids[1], LifecyclePhase::Deactivation)]
+        );
+        assert!(state.is_active(ids[0]));
+    }
+
+    fn id() -> QualifiedMappingId {
+        QualifiedMappingId::new(CompartmentKind::Main, MappingId::random())
+    }
+
+    fn desired(
+        entries: &[(QualifiedMappingId, &str)],
+    ) -> NonCryptoHashMap<QualifiedMappingId, LifecycleMainData> {
+        entries
+            .iter()
+            .map(|(id, script)| {
+                let data = LifecycleMainData {
+                    activation_actions: LifecycleActions {
+                        actions: vec![],
+                        lua_script: Some(script.to_string()),
+                    },
+                    deactivation_actions: Default::default(),
+                };
+                (*id, data)
+            })
+            .collect()
+    }
+
+    fn summarize(steps: &[LifecycleStep]) -> Vec<(QualifiedMappingId, LifecyclePhase)> {
+        steps.iter().map(|s| (s.mapping_id, s.phase)).collect()
+    }
+}
@@ -1,6 +1,7 @@
 use crate::domain::{SafeLua, ScriptColor, ScriptFeedbackEvent};
 use anyhow::ensure;
 use base::hash_util::NonCryptoHashSet;
+use base::metrics_util::measure_time_in_current_unit;
 use helgoboss_learn::{
     FeedbackScript, FeedbackScriptInput, FeedbackScriptOutput, FeedbackValue, NumericValue,
     PropProvider, PropValue,
@@ -92,8 +93,10 @@ impl<'a> FeedbackScript for LuaFeedbackScript<'a> {
         &self,
         input: FeedbackScriptInput,
     ) -> Result<FeedbackScriptOutput, Cow<'static, str>> {
-        self.feedback_internal(input)
-            .map_err(|e| e.to_string().into())
+        measure_time_in_current_unit("helgobox.script.lua.feedback", || {
+            self.feedback_internal(input)
+        })
+        .map_err(|e| e.to_string().into())
     }
 
     fn used_props(&self) -> Result<NonCryptoHashSet<String>, Box<dyn Error>> {
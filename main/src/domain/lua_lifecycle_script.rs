@@ -0,0 +1,97 @@
+use crate::domain::{LifecycleAction, LifecyclePhase, SafeLua};
+use anyhow::{ensure, Context};
+use mlua::{Function, IntoLua, LuaSerdeExt, Table, Value};
+
+/// A Lua script which is executed when a mapping is activated or deactivated.
+///
+/// It has access to the variable `context` (containing the lifecycle phase) and returns a list
+/// of lifecycle actions (or nothing).
+#[derive(Debug)]
+pub struct LuaLifecycleScript<'lua> {
+    lua: &'lua SafeLua,
+    function: Function<'lua>,
+    env: Table<'lua>,
+    context_key: Value<'lua>,
+}
+
+impl<'lua> LuaLifecycleScript<'lua> {
+    pub fn compile(lua: &'lua SafeLua, lua_script: &str) -> anyhow::Result<Self> {
+        ensure!(!lua_script.trim().is_empty(), "script empty");
+        let env = lua.create_fresh_environment(false)?;
+        let function = lua.compile_as_function("Lifecycle script", lua_script, env.clone())?;
+        let script = Self {
+            lua,
+            function,
+            env,
+            context_key: "context".into_lua(lua.as_ref())?,
+        };
+        Ok(script)
+    }
+
+    pub fn execute(&self, phase: LifecyclePhase) -> anyhow::Result<Vec<LifecycleAction>> {
+        let context = ScriptContext {
+            phase: match phase {
+                LifecyclePhase::Activation => "Activation",
+                LifecyclePhase::Deactivation => "Deactivation",
+            },
+        };
+        let context_lua_value = self.lua.as_ref().to_value(&context)?;
+        self.env
+            .raw_set(self.context_key.clone(), context_lua_value)?;
+        let value: Value = self.function.call(())?;
+        let actions: Option<Vec<LifecycleAction>> = self
+            .lua
+            .as_ref()
+            .from_value(value)
+            .context("Lua script result has wrong type")?;
+        Ok(actions.unwrap_or_default())
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ScriptContext {
+    phase: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::LifecycleOscArg;
+
+    #[test]
+    fn basics() {
+        // Given
+        let text = r#"
+            if context.phase ~= "Activation" then
+                return nil
+            end
+            return {
+                { kind = "SendOscFeedback", address = "/layout", arguments = { "mixer", 2 } },
+                { kind = "SetParameter", index = 3, value = 0.5 },
+            }
+        "#;
+        let lua = SafeLua::new().unwrap();
+        let script = LuaLifecycleScript::compile(&lua, text).unwrap();
+        // When
+        let activation_actions = script.execute(LifecyclePhase::Activation).unwrap();
+        let deactivation_actions = script.execute(LifecyclePhase::Deactivation).unwrap();
+        // Then
+        assert_eq!(
+            activation_actions,
+            vec![
+                LifecycleAction::SendOscFeedback {
+                    address: "/layout".to_string(),
+                    arguments: vec![
+                        LifecycleOscArg::String("mixer".to_string()),
+                        LifecycleOscArg::Int(2)
+                    ],
+                },
+                LifecycleAction::SetParameter {
+                    index: 3.try_into().unwrap(),
+                    value: 0.5,
+                },
+            ]
+        );
+        assert!(deactivation_actions.is_empty());
+    }
+}
@@ -1,32 +1,36 @@
 use crate::domain::{
     aggregate_target_values, format_as_pretty_hex, get_project_options, say,
-    AdditionalFeedbackEvent, AdditionalLuaMidiSourceScriptInput, Backbone, CompartmentKind,
-    CompoundChangeEvent, CompoundFeedbackValue, CompoundMappingSource,
-    CompoundMappingSourceAddress, CompoundMappingTarget, ControlContext, ControlEvent,
-    ControlEventTimestamp, ControlInput, ControlLogContext, ControlLogEntry, ControlLogEntryKind,
-    ControlMode, ControlOutcome, DeviceFeedbackOutput, DomainEvent, DomainEventHandler,
-    ExtendedProcessorContext, FeedbackAudioHookTask, FeedbackCollector, FeedbackDestinations,
-    FeedbackLogEntry, FeedbackOutput, FeedbackRealTimeTask, FeedbackResolution,
-    FeedbackSendBehavior, FinalRealFeedbackValue, FinalSourceFeedbackValue,
-    GlobalControlAndFeedbackState, GroupId, HitInstructionContext, HitInstructionResponse,
-    InstanceId, InternalInfoEvent, IoUpdatedEvent, KeyMessage, MainMapping, MainSourceMessage,
-    MappingActivationEffect, MappingControlResult, MappingId, MappingInfo, MessageCaptureEvent,
-    MessageCaptureResult, MidiControlInput, MidiDestination, MidiScanResult, NoopLogger,
-    NormalRealTimeTask, OrderedMappingIdSet, OrderedMappingMap, OscDeviceId, OscFeedbackTask,
-    PluginParamIndex, PluginParams, ProcessorContext, ProjectOptions, ProjectionFeedbackValue,
-    QualifiedInstanceEvent, QualifiedMappingId, RawParamValue, RealTimeMappingUpdate,
-    RealTimeTargetUpdate, RealearnMonitoringFxParameterValueChangedEvent,
+    set_compartment_parameter_value, AdditionalFeedbackEvent, AdditionalIo,
+    AdditionalLuaMidiSourceScriptInput, Backbone, CompartmentKind, CompoundChangeEvent,
+    CompoundFeedbackValue, CompoundMappingSourceAddress, CompoundMappingTarget, ControlContext,
+    ControlEvent, ControlEventTimestamp, ControlInput, ControlInputRecorder, ControlInputRecording,
+    ControlInputReplay, ControlLogContext, ControlLogEntry, ControlLogEntryKind, ControlMode,
+    ControlOutcome, DecodedControlInput, DeviceControlInput, DeviceFeedbackOutput, DomainEvent,
+    DomainEventHandler, ExtendedProcessorContext, FeedbackAudioHookTask, FeedbackCollector,
+    FeedbackDestinations, FeedbackLogEntry, FeedbackOutput, FeedbackPolicyStates,
+    FeedbackRealTimeTask, FeedbackResolution, FeedbackSendBehavior, FinalRealFeedbackValue,
+    FinalSourceFeedbackValue, GlobalControlAndFeedbackState, GroupId, HitInstructionContext,
+    HitInstructionResponse, IncomingMidiMessage, InstanceId, InternalInfoEvent, IoUpdatedEvent,
+    KeyMessage, LifecycleAction, LifecycleActions, LifecycleMainData, LifecyclePhase,
+    LifecycleState, LuaLifecycleScript, MainMapping, MainSourceMessage, MappingActivationEffect,
+    MappingControlResult, MappingId, MappingInfo, MappingPropProvider, MessageCaptureEvent,
+    MessageCaptureResult, MidiControlInput, MidiDestination, MidiRoutingTable, MidiScanResult,
+    NoopLogger, NormalAudioHookTask, NormalRealTimeTask, OrderedMappingIdSet, OrderedMappingMap,
+    OscDeviceId, OscFeedbackTask, PluginParamIndex, PluginParams, ProcessorContext, ProjectOptions,
+    ProjectionFeedbackValue, QualifiedInstanceEvent, QualifiedMappingId, RawParamValue,
+    RealTimeMappingUpdate, RealTimeTargetUpdate, RealearnMonitoringFxParameterValueChangedEvent,
     RealearnParameterChangePayload, RealearnSourceContext, ReaperConfigChange, ReaperMessage,
-    ReaperSourceFeedbackValue, ReaperTarget, SharedInstance, SharedUnit, SourceFeedbackEvent,
-    SourceFeedbackLogger, SourceReleasedEvent, SpecificCompoundFeedbackValue, TargetControlEvent,
-    TargetValueChangedEvent, UnitContainer, UnitEvent, UnitOrchestrationEvent,
-    UpdatedSingleMappingOnStateEvent, VirtualControlElement, VirtualSourceValue,
+    ReaperSourceFeedbackValue, ReaperTarget, RecordedControlInput, SharedInstance, SharedUnit,
+    SourceFeedbackEvent, SourceFeedbackLogger, SourceReleasedEvent, SpecificCompoundFeedbackValue,
+    TargetControlEvent, TargetValueChangedEvent, UnitContainer, UnitEvent, UnitOrchestrationEvent,
+    UpdatedSingleMappingOnStateEvent, VirtualControlElement, VirtualFeedbackValue,
+    VirtualSourceValue, WebSocketControlMessage, WebSocketFeedbackValue,
 };
 use derive_more::Display;
 use enum_map::EnumMap;
 use helgoboss_learn::{
     AbsoluteValue, AbstractTimestamp, ControlValue, GroupInteraction, MidiSourceValue,
-    MinIsMaxBehavior, ModeControlOptions, RawMidiEvent, Target, BASE_EPSILON,
+    MinIsMaxBehavior, ModeControlOptions, RawMidiEvent, Target, UnitValue, BASE_EPSILON,
 };
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -38,10 +42,12 @@ use crate::domain::ui_util::{
     log_virtual_control_input, log_virtual_feedback_output,
 };
 use base::hash_util::{NonCryptoHashMap, NonCryptoHashSet, NonCryptoIndexSet};
-use base::{hash_util, NamedChannelSender, SenderToNormalThread, SenderToRealTimeThread};
+use base::{
+    hash_util, metrics_util, NamedChannelSender, SenderToNormalThread, SenderToRealTimeThread,
+};
 use helgoboss_midi::{ControlChange14BitMessage, ParameterNumberMessage, RawShortMessage};
 use reaper_high::{ChangeEvent, Reaper};
-use reaper_medium::ReaperNormalizedFxParamValue;
+use reaper_medium::{MidiInputDeviceId, ReaperNormalizedFxParamValue};
 use rosc::{OscMessage, OscPacket, OscType};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
@@ -50,8 +56,9 @@ use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Instant;
 use strum::EnumIter;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 /// This can be come pretty big when multiple track volumes are adjusted at once.
 const FEEDBACK_TASK_QUEUE_SIZE: usize = 20_000;
@@ -79,6 +86,10 @@ pub struct MainProcessor<EH: DomainEventHandler> {
     collections: Collections,
     /// Contains IDs of those mappings who need to be polled as frequently as possible.
     poll_control_mappings: EnumMap<CompartmentKind, OrderedMappingIdSet>,
+    control_input_recorder: Option<ControlInputRecorder>,
+    control_input_replay: Option<ControlInputReplay>,
+    /// Reused in each main loop cycle for collecting mappings with postponed feedback that is due.
+    due_feedback_mapping_ids: Vec<QualifiedMappingId>,
 }
 
 #[derive(Debug)]
@@ -88,6 +99,11 @@ struct Basics<EH: DomainEventHandler> {
     common_lua: EnumMap<CompartmentKind, Option<mlua::Value<'static>>>,
     unit_container: &'static dyn UnitContainer,
     settings: BasicSettings,
+    additional_io: AdditionalIo,
+    /// The input from which the control event that's currently being processed originates.
+    ///
+    /// `None` if unknown (e.g. REAPER sources) or if nothing is being processed at the moment.
+    current_control_input: Option<ControlInput>,
     control_is_globally_enabled: bool,
     // TODO-medium Now that we communicate the feedback output separately, we could limit the scope
     //  of its meaning to "instance enabled etc."
@@ -108,7 +124,9 @@ struct Basics<EH: DomainEventHandler> {
     // TODO-low This reason is now outdated. We detected a general issue with reentrancy.
     //  https://github.com/helgoboss/reaper-rs/issues/54
     last_feedback_checksum_by_address:
-        RefCell<NonCryptoHashMap<CompoundMappingSourceAddress, FeedbackChecksum>>,
+        RefCell<NonCryptoHashMap<(FeedbackOutput, CompoundMappingSourceAddress), FeedbackChecksum>>,
+    /// Same RefCell reasoning as above.
+    feedback_policy_states: RefCell<FeedbackPolicyStates>,
     target_based_conditional_activation_processors:
         EnumMap<CompartmentKind, TargetBasedConditionalActivationProcessor>,
 }
@@ -237,6 +255,7 @@ struct Collections {
     milli_dependent_feedback_mappings: EnumMap<CompartmentKind, OrderedMappingIdSet>,
     parameters: PluginParams,
     previous_target_values: EnumMap<CompartmentKind, NonCryptoHashMap<MappingId, AbsoluteValue>>,
+    lifecycle_state: LifecycleState,
 }
 
 #[derive(Debug)]
@@ -253,6 +272,7 @@ struct Channels {
     normal_real_time_task_sender: SenderToRealTimeThread<NormalRealTimeTask>,
     feedback_real_time_task_sender: SenderToRealTimeThread<FeedbackRealTimeTask>,
     feedback_audio_hook_task_sender: SenderToRealTimeThread<FeedbackAudioHookTask>,
+    normal_audio_hook_task_sender: SenderToRealTimeThread<NormalAudioHookTask>,
     osc_feedback_task_sender: SenderToNormalThread<OscFeedbackTask>,
     additional_feedback_event_sender: SenderToNormalThread<AdditionalFeedbackEvent>,
     unit_orchestration_event_sender: SenderToNormalThread<UnitOrchestrationEvent>,
@@ -275,6 +295,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         normal_real_time_task_sender: SenderToRealTimeThread<NormalRealTimeTask>,
         feedback_real_time_task_sender: SenderToRealTimeThread<FeedbackRealTimeTask>,
         feedback_audio_hook_task_sender: SenderToRealTimeThread<FeedbackAudioHookTask>,
+        normal_audio_hook_task_sender: SenderToRealTimeThread<NormalAudioHookTask>,
         additional_feedback_event_sender: SenderToNormalThread<AdditionalFeedbackEvent>,
         instance_orchestration_event_sender: SenderToNormalThread<UnitOrchestrationEvent>,
         osc_feedback_task_sender: SenderToNormalThread<OscFeedbackTask>,
@@ -295,6 +316,8 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 unit_id,
                 common_lua: Default::default(),
                 settings: Default::default(),
+                additional_io: Default::default(),
+                current_control_input: None,
                 control_is_globally_enabled: false,
                 feedback_is_globally_enabled: false,
                 event_handler,
@@ -315,12 +338,14 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                     normal_real_time_task_sender,
                     feedback_real_time_task_sender,
                     feedback_audio_hook_task_sender,
+                    normal_audio_hook_task_sender,
                     osc_feedback_task_sender,
                     additional_feedback_event_sender,
                     unit_orchestration_event_sender: instance_orchestration_event_sender,
                     integration_test_feedback_sender: None,
                 },
                 last_feedback_checksum_by_address: Default::default(),
+                feedback_policy_states: Default::default(),
                 target_based_conditional_activation_processors: Default::default(),
             },
             collections: Collections {
@@ -331,8 +356,12 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 milli_dependent_feedback_mappings: Default::default(),
                 parameters: Default::default(),
                 previous_target_values: Default::default(),
+                lifecycle_state: Default::default(),
             },
             poll_control_mappings: Default::default(),
+            control_input_recorder: None,
+            control_input_replay: None,
+            due_feedback_mapping_ids: vec![],
         }
     }
 
@@ -419,9 +448,84 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 break;
             }
         }
+        if control_is_effectively_enabled {
+            self.process_control_input_replay(timestamp);
+        }
         self.poll_control(timestamp);
     }
 
+    /// Feeds all recorded control events which are due into the same input paths which are used
+    /// for live control input.
+    ///
+    /// Each event is routed by the device on which it was recorded. Events from devices which
+    /// this unit doesn't listen to (anymore) are skipped, just like live input would be.
+    fn process_control_input_replay(&mut self, timestamp: ControlEventTimestamp) {
+        let Some(replay) = self.control_input_replay.as_mut() else {
+            return;
+        };
+        let inputs: Vec<_> = replay
+            .take_due_events(timestamp)
+            .iter()
+            .map(|e| e.input.decode())
+            .collect();
+        if replay.is_finished() {
+            debug!("Finished replaying control input");
+            self.control_input_replay = None;
+        }
+        for input in inputs {
+            let input = match input {
+                Ok(i) => i,
+                Err(e) => {
+                    warn!("Skipping recorded control input: {e:#}");
+                    continue;
+                }
+            };
+            match input {
+                DecodedControlInput::Midi { device, msg } => {
+                    self.basics
+                        .channels
+                        .normal_audio_hook_task_sender
+                        .send_complaining(NormalAudioHookTask::ReplayMidi(
+                            self.basics.unit_id,
+                            device,
+                            msg,
+                        ));
+                }
+                DecodedControlInput::Osc { device, packet } => {
+                    if !self.wants_osc_from(&device) {
+                        continue;
+                    }
+                    self.basics.current_control_input = Some(ControlInput::Osc(device));
+                    self.process_incoming_osc_packet_internal(ControlEvent::new(
+                        &packet, timestamp,
+                    ));
+                    self.basics.current_control_input = None;
+                }
+                DecodedControlInput::Key(msg) => {
+                    if !self.wants_keys() {
+                        continue;
+                    }
+                    self.basics.current_control_input = Some(ControlInput::Keyboard);
+                    self.process_incoming_key_msg_internal(ControlEvent::new(msg, timestamp));
+                    self.basics.current_control_input = None;
+                }
+            }
+        }
+    }
+
+    fn record_control_input(
+        &mut self,
+        timestamp: ControlEventTimestamp,
+        create_input: impl FnOnce() -> Option<RecordedControlInput>,
+    ) {
+        let Some(recorder) = self.control_input_recorder.as_mut() else {
+            return;
+        };
+        if let Some(input) = create_input() {
+            recorder.record(timestamp, input);
+        }
+    }
+
     fn process_control_task(&mut self, task: ControlMainTask) {
         use ControlMainTask::*;
         match task {
@@ -520,7 +624,8 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                         (false, mode_poll_result)
                     } else if m.source().wants_to_be_polled() && m.control_is_effectively_on() {
                         // Mode was either not polled at all or without result, poll source.
-                        let res = if let Some(source_control_value) = m.poll_source() {
+                        let params = self.collections.parameters.compartment_params(compartment);
+                        let res = if let Some(source_control_value) = m.poll_source(params) {
                             let control_event = ControlEvent::new(source_control_value, timestamp);
                             control_mapping_stage_one(
                                 &self.basics,
@@ -640,6 +745,42 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.process_feedback_tasks();
         self.process_unit_events();
         self.poll_for_feedback();
+        self.send_postponed_feedback();
+    }
+
+    /// Sends feedback which has been held back by a mapping's feedback policy and is due now.
+    fn send_postponed_feedback(&mut self) {
+        if !self.basics.instance_feedback_is_effectively_enabled() {
+            return;
+        }
+        let now = Instant::now();
+        self.basics
+            .feedback_policy_states
+            .borrow()
+            .take_due(now, &mut self.due_feedback_mapping_ids);
+        if self.due_feedback_mapping_ids.is_empty() {
+            return;
+        }
+        let feedback_values: Vec<_> = self
+            .due_feedback_mapping_ids
+            .drain(..)
+            .filter_map(|id| {
+                let m = self.collections.mappings[id.compartment].get(&id.id)?;
+                if !m.feedback_is_effectively_on() {
+                    return None;
+                }
+                let control_context = self.basics.control_context(id.compartment);
+                let current_value = m
+                    .current_aggregated_target_value(control_context)
+                    .map(|v| v.to_unit_value());
+                self.basics
+                    .feedback_policy_states
+                    .borrow_mut()
+                    .notify_sent(id, current_value, now);
+                m.feedback(true, control_context)
+            })
+            .collect();
+        self.send_feedback(FeedbackReason::Normal, feedback_values);
     }
 
     /// This goes through all mappings that returned "high" feedback resolution - which they do if
@@ -1216,6 +1357,21 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 UpdateSettings(settings) => {
                     self.update_settings(settings);
                 }
+                UpdateMidiRouting(midi_routing) => {
+                    self.basics
+                        .channels
+                        .normal_real_time_task_sender
+                        .send_complaining(NormalRealTimeTask::UpdateMidiRouting(midi_routing));
+                }
+                UpdateAdditionalIo(additional_io) => {
+                    self.basics
+                        .channels
+                        .normal_real_time_task_sender
+                        .send_complaining(NormalRealTimeTask::UpdateAdditionalIo(
+                            additional_io.clone(),
+                        ));
+                    self.basics.additional_io = additional_io;
+                }
                 UpdateCompartmentSettings(compartment, settings) => {
                     self.update_compartment_settings(compartment, settings);
                 }
@@ -1283,6 +1439,39 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 UseIntegrationTestFeedbackSender(sender) => {
                     self.basics.channels.integration_test_feedback_sender = Some(sender);
                 }
+                StartRecordingControlInput => {
+                    debug!("Start recording control input");
+                    self.control_input_recorder = Some(ControlInputRecorder::new(timestamp));
+                    self.basics
+                        .channels
+                        .normal_real_time_task_sender
+                        .send_complaining(NormalRealTimeTask::UpdateControlInputRecordingEnabled(
+                            true,
+                        ));
+                }
+                StopRecordingControlInput => {
+                    debug!("Stop recording control input");
+                    self.basics
+                        .channels
+                        .normal_real_time_task_sender
+                        .send_complaining(NormalRealTimeTask::UpdateControlInputRecordingEnabled(
+                            false,
+                        ));
+                    if let Some(recorder) = self.control_input_recorder.take() {
+                        self.basics.event_handler.handle_event_ignoring_error(
+                            DomainEvent::ControlInputRecorded(recorder.finish()),
+                        );
+                    }
+                }
+                StartReplayingControlInput { recording, speed } => {
+                    debug!("Start replaying control input");
+                    self.control_input_replay =
+                        Some(ControlInputReplay::new(recording, timestamp, speed));
+                }
+                StopReplayingControlInput => {
+                    debug!("Stop replaying control input");
+                    self.control_input_replay = None;
+                }
                 PotentiallyEnableOrDisableControlOrFeedback => {
                     self.potentially_enable_or_disable_control_or_feedback(
                         self.any_main_mapping_is_effectively_on(),
@@ -1342,6 +1531,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             project_options,
         );
         if let Some(new_feedback_is_enabled) = new_feedback_is_enabled {
+            self.sync_all_lifecycle_actions();
             if new_feedback_is_enabled {
                 for compartment in CompartmentKind::enum_iter() {
                     self.handle_feedback_after_having_updated_all_mappings(
@@ -1408,6 +1598,11 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 }
             }
             self.process_target_updates(compartment, target_updates);
+            let changed_mapping_ids: Vec<_> = changed_mappings
+                .iter()
+                .map(|id| QualifiedMappingId::new(compartment, *id))
+                .collect();
+            self.sync_lifecycle_actions(&changed_mapping_ids);
             // Important to send IO event first ...
             self.notify_feedback_dev_usage_might_have_changed(compartment);
             self.handle_feedback_after_having_updated_particular_mappings(
@@ -1437,6 +1632,13 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             .channels
             .normal_real_time_task_sender
             .send_complaining(NormalRealTimeTask::UpdateSettings(settings));
+        if settings.feedback_output != self.basics.settings.feedback_output {
+            // The snapshot would be outdated as soon as the output is switched back
+            self.basics
+                .unit
+                .borrow_mut()
+                .clear_web_socket_feedback_snapshot();
+        }
         let any_main_mapping_is_effectively_on = self.any_main_mapping_is_effectively_on();
         self.basics
             .update_settings_internal(settings, any_main_mapping_is_effectively_on);
@@ -1450,6 +1652,10 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
     ) {
         tracing::debug!("Updating {} mappings in {}...", mappings.len(), compartment,);
         self.basics.clear_last_feedback();
+        self.basics
+            .feedback_policy_states
+            .borrow_mut()
+            .clear(compartment);
         let mut mappings_by_group: NonCryptoHashMap<GroupId, Vec<MappingId>> = HashMap::default();
         let mut mapping_infos: NonCryptoHashMap<QualifiedMappingId, MappingInfo> =
             HashMap::default();
@@ -1531,6 +1737,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 compartment,
                 real_time_mappings,
             ));
+        self.sync_all_lifecycle_actions();
         // Important to send IO event first ...
         self.notify_feedback_dev_usage_might_have_changed(compartment);
         // ... and then mapping update. Otherwise, if this is an upper-floor instance
@@ -1584,6 +1791,13 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 LogToConsole(msg) => {
                     Reaper::get().show_console_msg(msg);
                 }
+                RecordMidi { device, event } => {
+                    if let Some(recorder) = self.control_input_recorder.as_mut() {
+                        let timestamp = event.timestamp();
+                        let input = RecordedControlInput::from_midi(device, &event.into_payload());
+                        recorder.record(timestamp, input);
+                    }
+                }
             }
         }
     }
@@ -1831,7 +2045,17 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
 
     pub fn wants_osc_from(&self, device_id: &OscDeviceId) -> bool {
         self.wants_messages_in_general()
-            && self.basics.settings.control_input == ControlInput::Osc(*device_id)
+            && (self.basics.settings.control_input == ControlInput::Osc(*device_id)
+                || self
+                    .basics
+                    .additional_io
+                    .control_inputs
+                    .contains(&DeviceControlInput::Osc(*device_id)))
+    }
+
+    pub fn wants_web_socket_messages(&self) -> bool {
+        self.wants_messages_in_general()
+            && self.basics.settings.control_input == ControlInput::WebSocket
     }
 
     pub fn process_info_event(&mut self, evt: &InternalInfoEvent) {
@@ -1844,15 +2068,25 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         // First process internally.
         // Convenience: Send all feedback whenever a MIDI device is connected.
         if let ReaperMessage::MidiDevicesConnected(payload) = evt.payload() {
-            if let Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id))) =
+            let is_main_output = |dev_id| {
                 self.basics.settings.feedback_output
+                    == Some(FeedbackOutput::Midi(MidiDestination::Device(dev_id)))
+            };
+            let is_additional_output = |dev_id| {
+                self.basics
+                    .additional_io
+                    .feedback_outputs
+                    .contains(&DeviceFeedbackOutput::Midi(dev_id))
+            };
+            if payload
+                .output_devices
+                .iter()
+                .any(|dev_id| is_main_output(*dev_id) || is_additional_output(*dev_id))
             {
-                if payload.output_devices.contains(&dev_id) {
-                    self.basics
-                        .channels
-                        .self_normal_sender
-                        .send_if_space(NormalMainTask::SendAllFeedback);
-                }
+                self.basics
+                    .channels
+                    .self_normal_sender
+                    .send_if_space(NormalMainTask::SendAllFeedback);
             }
         }
         // Inform UI of MIDI device changes
@@ -1911,6 +2145,19 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
     pub fn process_incoming_key_msg(
         &mut self,
         evt: ControlEvent<KeyMessage>,
+    ) -> KeyProcessingResult {
+        self.record_control_input(evt.timestamp(), || {
+            RecordedControlInput::from_key(evt.payload())
+        });
+        self.basics.current_control_input = Some(ControlInput::Keyboard);
+        let result = self.process_incoming_key_msg_internal(evt);
+        self.basics.current_control_input = None;
+        result
+    }
+
+    fn process_incoming_key_msg_internal(
+        &mut self,
+        evt: ControlEvent<KeyMessage>,
     ) -> KeyProcessingResult {
         if self.basics.settings.real_input_logging_enabled {
             self.log_incoming_message(evt);
@@ -1953,7 +2200,20 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
     }
 
     /// This doesn't check if control enabled! You need to check before.
-    pub fn process_incoming_osc_packet(&mut self, evt: ControlEvent<&OscPacket>) {
+    pub fn process_incoming_osc_packet(
+        &mut self,
+        dev_id: OscDeviceId,
+        evt: ControlEvent<&OscPacket>,
+    ) {
+        self.record_control_input(evt.timestamp(), || {
+            RecordedControlInput::from_osc(dev_id, evt.payload()).ok()
+        });
+        self.basics.current_control_input = Some(ControlInput::Osc(dev_id));
+        self.process_incoming_osc_packet_internal(evt);
+        self.basics.current_control_input = None;
+    }
+
+    fn process_incoming_osc_packet_internal(&mut self, evt: ControlEvent<&OscPacket>) {
         if self.basics.settings.real_input_logging_enabled {
             let timestamp = evt.timestamp();
             self.log_incoming_message(ControlEvent::new(
@@ -1968,12 +2228,80 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             }
             OscPacket::Bundle(bundle) => {
                 for p in bundle.content.iter() {
-                    self.process_incoming_osc_packet(evt.with_payload(p));
+                    self.process_incoming_osc_packet_internal(evt.with_payload(p));
                 }
             }
         }
     }
 
+    /// This doesn't check if control enabled! You need to check before.
+    ///
+    /// WebSocket control messages are not captured by the control input recorder.
+    pub fn process_incoming_web_socket_msg(&mut self, evt: ControlEvent<&WebSocketControlMessage>) {
+        if self.basics.settings.real_input_logging_enabled {
+            self.log_incoming_message(evt.payload());
+        }
+        self.basics.current_control_input = Some(ControlInput::WebSocket);
+        self.process_incoming_web_socket_msg_internal(evt);
+        self.basics.current_control_input = None;
+    }
+
+    fn process_incoming_web_socket_msg_internal(
+        &mut self,
+        evt: ControlEvent<&WebSocketControlMessage>,
+    ) {
+        match evt.payload() {
+            WebSocketControlMessage::Raw(msg) => {
+                let msg = MainSourceMessage::Osc(msg);
+                self.process_incoming_message_internal(evt.with_payload(msg));
+            }
+            WebSocketControlMessage::Virtual(value) => {
+                // Learning virtual sources from WebSocket messages is not supported yet.
+                if self.basics.control_mode != ControlMode::Controlling {
+                    return;
+                }
+                self.process_incoming_virtual_source_value(evt.with_payload(*value));
+            }
+        }
+    }
+
+    /// Controls main mappings with virtual sources directly, bypassing the controller compartment.
+    fn process_incoming_virtual_source_value(&mut self, evt: ControlEvent<VirtualSourceValue>) {
+        let mut results = self.basics.process_main_mappings_with_virtual_sources(
+            &mut self.collections.mappings[CompartmentKind::Main],
+            evt,
+            ControlOptions::default(),
+            &self.collections.parameters,
+        );
+        if self.basics.settings.virtual_input_logging_enabled {
+            let match_outcome = if results.is_empty() {
+                MatchOutcome::Unmatched
+            } else {
+                MatchOutcome::Matched
+            };
+            log_virtual_control_input(
+                self.basics.unit_id,
+                format_control_input_with_match_result(evt.payload(), match_outcome),
+            );
+        }
+        self.basics.send_feedback(
+            &self.collections.mappings_with_virtual_targets,
+            FeedbackReason::Normal,
+            results
+                .iter_mut()
+                .filter_map(|r| r.control_result.feedback_value.take()),
+        );
+        for r in results {
+            control_mapping_stage_three(
+                &self.basics,
+                &mut self.collections,
+                r.compartment,
+                r.control_result,
+                GroupInteractionProcessing::On(r.group_interaction_input),
+            )
+        }
+    }
+
     fn process_incoming_message_internal(
         &mut self,
         evt: ControlEvent<MainSourceMessage>,
@@ -2033,9 +2361,15 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             let mut enforce_target_refresh = false;
             // Search for 958 to know why we use a for loop here instead of collect().
             let mut results = vec![];
+            let control_input = self.basics.current_control_input;
             for m in self.collections.mappings[compartment]
                 .values_mut()
-                .filter(|m| m.control_is_effectively_on())
+                .filter(|m| {
+                    m.control_is_effectively_on()
+                        && m.options()
+                            .io_selection
+                            .accepts_control_input(control_input)
+                })
             {
                 let control_outcome = m.control_source(evt.payload());
                 match_outcome.upgrade_from(control_outcome.into());
@@ -2093,11 +2427,15 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         unused_sources: UnusedSources,
         changed_mappings: impl Iterator<Item = MappingId>,
     ) {
+        let changed_mapping_ids: Vec<_> = changed_mappings
+            .map(|id| QualifiedMappingId::new(compartment, id))
+            .collect();
+        self.sync_lifecycle_actions(&changed_mapping_ids);
         // Send feedback
         self.handle_feedback_after_having_updated_particular_mappings(
             compartment,
             unused_sources,
-            changed_mappings,
+            changed_mapping_ids.into_iter().map(|id| id.id),
         );
         // Updating the "on" mappings is not exactly cheap as it turned out in
         // https://github.com/helgoboss/helgobox/issues/913. And it also turns out we can skip it in many cases
@@ -2166,6 +2504,128 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             ));
     }
 
+    /// Executes the main-thread lifecycle actions of all mappings whose "feedback on" state has
+    /// changed since the last call, including mappings which are gone.
+    fn sync_all_lifecycle_actions(&mut self) {
+        let state = &self.collections.lifecycle_state;
+        let gone_mapping_ids = state.active_mapping_ids().filter(|id| {
+            self.get_normal_or_virtual_target_mapping(id.compartment, id.id)
+                .is_none()
+        });
+        let existing_mapping_ids = self
+            .all_mappings()
+            .filter(|m| !m.lifecycle_main_data().is_empty() || state.is_active(m.qualified_id()))
+            .map(MainMapping::qualified_id);
+        let mapping_ids: Vec<_> = gone_mapping_ids.chain(existing_mapping_ids).collect();
+        self.sync_lifecycle_actions(&mapping_ids);
+    }
+
+    /// Executes the main-thread lifecycle actions of the given mappings if their "feedback on"
+    /// state or lifecycle configuration has changed since the last call.
+    ///
+    /// Should be called after the mappings have been updated but before their feedback is sent.
+    /// This guarantees that lifecycle actions (e.g. switching the layout of an OSC device) are
+    /// processed before normal feedback. All deactivation actions are executed before any
+    /// activation actions, each in the order of the given mapping IDs.
+    fn sync_lifecycle_actions(&mut self, mapping_ids: &[QualifiedMappingId]) {
+        if mapping_ids.is_empty() {
+            return;
+        }
+        let mut state = std::mem::take(&mut self.collections.lifecycle_state);
+        let steps = state.sync(mapping_ids, |id| self.desired_lifecycle_data(id));
+        self.collections.lifecycle_state = state;
+        for step in steps {
+            self.run_lifecycle_actions(step.mapping_id, step.actions(), step.phase);
+        }
+    }
+
+    /// Returns the main-thread lifecycle data which should currently be active for the given
+    /// mapping.
+    fn desired_lifecycle_data(&self, id: QualifiedMappingId) -> Option<&LifecycleMainData> {
+        if !self.basics.feedback_is_globally_enabled {
+            return None;
+        }
+        let mapping = self.get_normal_or_virtual_target_mapping(id.compartment, id.id)?;
+        if !mapping.feedback_is_effectively_on() {
+            return None;
+        }
+        let data = mapping.lifecycle_main_data();
+        if data.is_empty() {
+            return None;
+        }
+        Some(data)
+    }
+
+    fn run_lifecycle_actions(
+        &mut self,
+        id: QualifiedMappingId,
+        actions: &LifecycleActions,
+        phase: LifecyclePhase,
+    ) {
+        for action in &actions.actions {
+            self.run_lifecycle_action(id, action);
+        }
+        let Some(lua_script) = &actions.lua_script else {
+            return;
+        };
+        // Lifecycle scripts run rarely, so we compile them on demand.
+        let lua = unsafe { Backbone::main_thread_lua() };
+        let script_actions =
+            LuaLifecycleScript::compile(lua, lua_script).and_then(|script| script.execute(phase));
+        match script_actions {
+            Ok(script_actions) => {
+                for action in &script_actions {
+                    self.run_lifecycle_action(id, action);
+                }
+            }
+            Err(e) => {
+                warn!("Couldn't execute lifecycle script of mapping {id:?}: {e:#}");
+            }
+        }
+    }
+
+    fn run_lifecycle_action(&mut self, id: QualifiedMappingId, action: &LifecycleAction) {
+        match action {
+            LifecycleAction::SendOscFeedback { address, arguments } => {
+                let msg = OscMessage {
+                    addr: address.clone(),
+                    args: arguments.iter().map(|arg| arg.to_osc_type()).collect(),
+                };
+                if let Some(test_sender) = &self.basics.channels.integration_test_feedback_sender {
+                    // Integration test
+                    test_sender.send_if_space(FinalSourceFeedbackValue::Osc(msg));
+                    return;
+                }
+                let Some(FeedbackOutput::Osc(dev_id)) = self.basics.settings.feedback_output else {
+                    return;
+                };
+                if self.basics.settings.real_output_logging_enabled {
+                    log_lifecycle_output(self.basics.unit_id, format_osc_message(&msg));
+                }
+                // Normal OSC feedback goes through the same channel, so the order is preserved.
+                self.basics
+                    .channels
+                    .osc_feedback_task_sender
+                    .send_complaining(OscFeedbackTask::new(dev_id, msg));
+            }
+            LifecycleAction::SetTargetValue { value } => {
+                let value = ControlValue::AbsoluteContinuous(UnitValue::new_clamped(*value));
+                self.hit_target(id, value);
+            }
+            LifecycleAction::SetParameter { index, value } => {
+                let plugin_param_index = id.compartment.to_plugin_param_index(*index);
+                let result = set_compartment_parameter_value(
+                    self.basics.control_context(id.compartment),
+                    plugin_param_index,
+                    UnitValue::new_clamped(*value),
+                );
+                if let Err(e) = result {
+                    warn!("Couldn't set compartment parameter in lifecycle action: {e}");
+                }
+            }
+        }
+    }
+
     fn update_single_mapping_on_state(&self, id: QualifiedMappingId) {
         let is_on =
             if let Some(m) = self.get_normal_or_virtual_target_mapping(id.compartment, id.id) {
@@ -2559,6 +3019,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         );
         let target_is_active = mapping.target_is_active();
         self.update_map_entries(compartment, *mapping);
+        self.sync_lifecycle_actions(&[id]);
         self.send_diff_feedback(diff_feedback);
         self.update_single_mapping_on_state(id);
         // This could be a lead mapping in terms of target-based conditional activation. If so,
@@ -2624,6 +3085,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             } else {
                 (false, false)
             };
+        if was_on_before != is_on_now {
+            self.sync_lifecycle_actions(&[id]);
+        }
         // Send feedback if necessary (right now we assume that changed processing state doesn't
         // change anything about the source or target, so we use a much more simple mechanism to
         // determine necessary diff feedback than when updating the complete mapping).
@@ -2847,6 +3311,8 @@ pub enum NormalMainTask {
     /// auto-load is enabled).
     NotifyConditionsChanged,
     UpdateSettings(BasicSettings),
+    UpdateMidiRouting(MidiRoutingTable),
+    UpdateAdditionalIo(AdditionalIo),
     UpdateCompartmentSettings(CompartmentKind, CompartmentSettings),
     PotentiallyEnableOrDisableControlOrFeedback,
     SendAllFeedback,
@@ -2859,6 +3325,16 @@ pub enum NormalMainTask {
     DisableControl,
     ReturnToControlMode,
     UseIntegrationTestFeedbackSender(SenderToNormalThread<FinalSourceFeedbackValue>),
+    /// Starts capturing incoming MIDI, OSC and keyboard control input.
+    StartRecordingControlInput,
+    /// Stops capturing control input and emits the recording as domain event.
+    StopRecordingControlInput,
+    /// Feeds the given recording back into the processors, speed being a factor (1.0 = original).
+    StartReplayingControlInput {
+        recording: ControlInputRecording,
+        speed: f64,
+    },
+    StopReplayingControlInput,
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -2973,6 +3449,12 @@ pub enum NormalRealTimeToMainThreadTask {
         value: MidiSourceValue<'static, RawShortMessage>,
     },
     LogToConsole(String),
+    /// Incoming MIDI which should be recorded (only sent while recording control input).
+    RecordMidi {
+        /// `None` means FX input.
+        device: Option<MidiInputDeviceId>,
+        event: ControlEvent<OwnedIncomingMidiMessage>,
+    },
 }
 
 /// A parameter-related task (which is potentially sent very frequently, just think of automation).
@@ -3027,11 +3509,21 @@ pub enum ControlMainTask {
     },
 }
 
+#[derive(Clone, Debug)]
 pub enum OwnedIncomingMidiMessage {
     Short(RawShortMessage),
     SysEx(Vec<u8>),
 }
 
+impl OwnedIncomingMidiMessage {
+    pub fn as_incoming(&self) -> IncomingMidiMessage {
+        match self {
+            OwnedIncomingMidiMessage::Short(msg) => IncomingMidiMessage::Short(*msg),
+            OwnedIncomingMidiMessage::SysEx(bytes) => IncomingMidiMessage::SysEx(bytes),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
 pub struct ControlOptions {
     pub enforce_send_feedback_after_control: bool,
@@ -3158,6 +3650,11 @@ impl<EH: DomainEventHandler> Basics<EH> {
         move |entry| {
             // Handle logging to mapping panel
             if context != ControlLogContext::Polling {
+                metrics_util::record_unit_occurrence(
+                    "helgobox.control.events",
+                    self.unit_id.into(),
+                    &[("kind", entry.kind.metric_label())],
+                );
                 let event = TargetControlEvent::new(mapping_id, context, entry);
                 self.event_handler
                     .handle_event_ignoring_error(DomainEvent::HandleTargetControl(event));
@@ -3561,7 +4058,10 @@ impl<EH: DomainEventHandler> Basics<EH> {
                 self.control_context(m.compartment()),
                 logger,
             )
-            .map(CompoundFeedbackValue::normal);
+            .map(|v| {
+                CompoundFeedbackValue::normal(v, m.options().io_selection.feedback_output)
+                    .with_feedback_policy(m.feedback_policy_context(new_target_value))
+            });
         self.send_feedback(
             mappings_with_virtual_targets,
             FeedbackReason::Normal,
@@ -3622,7 +4122,12 @@ impl<EH: DomainEventHandler> Basics<EH> {
         let mut match_outcome = MatchOutcome::Unmatched;
         let mut extended_control_results: Vec<_> = mappings_with_virtual_targets
             .values_mut()
-            .filter(|m| m.control_is_effectively_on())
+            .filter(|m| {
+                m.control_is_effectively_on()
+                    && m.options()
+                        .io_selection
+                        .accepts_control_input(self.current_control_input)
+            })
             .flat_map(|m| {
                 let virtual_source_value = match m.control_virtualizing(evt) {
                     Some(ControlOutcome::Matched(v)) => v,
@@ -3686,7 +4191,20 @@ impl<EH: DomainEventHandler> Basics<EH> {
         (extended_control_results, match_outcome)
     }
 
+    /// Returns `false` if the feedback policy of the mapping which produced the given feedback value
+    /// suppresses or postpones it.
+    fn feedback_policy_allows(&self, feedback_value: &CompoundFeedbackValue) -> bool {
+        let Some(context) = feedback_value.feedback_policy else {
+            return true;
+        };
+        self.feedback_policy_states
+            .borrow_mut()
+            .process_feedback(context, Instant::now())
+    }
+
     /// Sends both direct and virtual-source feedback.
+    ///
+    /// This is the single point where feedback policies are applied.
     pub fn send_feedback(
         &self,
         mappings_with_virtual_targets: &OrderedMappingMap<MainMapping>,
@@ -3694,57 +4212,81 @@ impl<EH: DomainEventHandler> Basics<EH> {
         feedback_values: impl IntoIterator<Item = CompoundFeedbackValue>,
     ) {
         let mut global_source_state = Backbone::source_state().borrow_mut();
-        let mut feedback_collector =
-            FeedbackCollector::new(&mut global_source_state, self.settings.feedback_output);
+        let mut feedback_collector = FeedbackCollector::new(
+            &mut global_source_state,
+            self.additional_io
+                .feedback_outputs(self.settings.feedback_output),
+        );
         for feedback_value in feedback_values.into_iter() {
+            if !self.feedback_policy_allows(&feedback_value) {
+                continue;
+            }
             match feedback_value.value {
                 SpecificCompoundFeedbackValue::Virtual {
                     destinations,
-                    value,
+                    values,
                 } => {
-                    // At this point we still include controller mappings for which feedback
-                    // is explicitly not enabled (not supported by controller) in order to
-                    // support at least projection feedback (#414)!
-                    if self.settings.virtual_output_logging_enabled {
-                        log_virtual_feedback_output(self.unit_id, &value);
-                    }
-                    // Iterate over (controller) mappings with virtual targets.
-                    for m in mappings_with_virtual_targets
-                        .values()
-                        .filter(|m| m.feedback_is_effectively_on())
-                    {
-                        // Should always be true.
-                        if let Some(t) = m.virtual_target() {
-                            if t.control_element() == value.control_element() {
-                                // Virtual source matched virtual target. The following method
-                                // will always produce real target values (because controller
-                                // mappings can't have virtual sources).
-                                let compound_feedback_value = m.feedback_given_target_value(
-                                    // This clone is unavoidable because we are producing
-                                    // real feedback values and these will be sent to another
-                                    //  thread, so they must be self-contained.
-                                    Cow::Borrowed(value.feedback_value()),
-                                    FeedbackDestinations {
-                                        with_source_feedback: destinations.with_source_feedback
-                                            && m.feedback_is_enabled(),
-                                        ..destinations
-                                    },
-                                    self.source_context(m.compartment()),
-                                    self.source_feedback_logger(m.qualified_id()),
-                                );
-                                if let Some(SpecificCompoundFeedbackValue::Real(
-                                    preliminary_feedback_value,
-                                )) = compound_feedback_value
-                                {
-                                    // Successful virtual-to-real feedback
-                                    if let Some(final_feedback_value) =
-                                        feedback_collector.process(preliminary_feedback_value)
+                    for value in values {
+                        // At this point we still include controller mappings for which feedback
+                        // is explicitly not enabled (not supported by controller) in order to
+                        // support at least projection feedback (#414)!
+                        if self.settings.virtual_output_logging_enabled {
+                            log_virtual_feedback_output(self.unit_id, &value);
+                        }
+                        if destinations.with_source_feedback {
+                            self.send_virtual_web_socket_feedback(feedback_reason, &value);
+                        }
+                        // Iterate over (controller) mappings with virtual targets.
+                        for m in mappings_with_virtual_targets
+                            .values()
+                            .filter(|m| m.feedback_is_effectively_on())
+                        {
+                            // Should always be true.
+                            if let Some(t) = m.virtual_target() {
+                                if t.control_element() == value.control_element() {
+                                    // Virtual source matched virtual target. The following method
+                                    // will always produce real target values (because controller
+                                    // mappings can't have virtual sources).
+                                    let prop_provider = MappingPropProvider::new(
+                                        m,
+                                        self.control_context(m.compartment()),
+                                    );
+                                    let compound_feedback_value = m.feedback_given_target_value(
+                                        // This clone is unavoidable because we are producing
+                                        // real feedback values and these will be sent to another
+                                        //  thread, so they must be self-contained.
+                                        Cow::Borrowed(value.feedback_value()),
+                                        FeedbackDestinations {
+                                            with_source_feedback: destinations.with_source_feedback
+                                                && m.feedback_is_enabled(),
+                                            ..destinations
+                                        },
+                                        self.source_context(m.compartment()),
+                                        Some(&prop_provider),
+                                        self.source_feedback_logger(m.qualified_id()),
+                                    );
+                                    if let Some(SpecificCompoundFeedbackValue::Real(
+                                        preliminary_feedback_value,
+                                    )) = compound_feedback_value
                                     {
-                                        self.send_direct_feedback(
-                                            feedback_reason,
-                                            final_feedback_value,
-                                            feedback_value.is_feedback_after_control,
-                                        );
+                                        // Successful virtual-to-real feedback
+                                        // The controller mapping knows best which device
+                                        // the control element belongs to.
+                                        let feedback_output = m
+                                            .options()
+                                            .io_selection
+                                            .feedback_output
+                                            .or(feedback_value.feedback_output);
+                                        if let Some(final_feedback_value) = feedback_collector
+                                            .process(preliminary_feedback_value, feedback_output)
+                                        {
+                                            self.send_direct_feedback(
+                                                feedback_reason,
+                                                final_feedback_value,
+                                                feedback_value.is_feedback_after_control,
+                                                feedback_output,
+                                            );
+                                        }
                                     }
                                 }
                             }
@@ -3752,21 +4294,29 @@ impl<EH: DomainEventHandler> Basics<EH> {
                     }
                 }
                 SpecificCompoundFeedbackValue::Real(preliminary_feedback_value) => {
-                    if let Some(final_feedback_value) =
-                        feedback_collector.process(preliminary_feedback_value)
+                    if let Some(final_feedback_value) = feedback_collector
+                        .process(preliminary_feedback_value, feedback_value.feedback_output)
                     {
                         self.send_direct_feedback(
                             feedback_reason,
                             final_feedback_value,
                             feedback_value.is_feedback_after_control,
+                            feedback_value.feedback_output,
                         );
                     }
                 }
             }
         }
         // Send special collected feedback
-        for final_feedback_value in feedback_collector.generate_final_feedback_values() {
-            self.send_direct_feedback(feedback_reason, final_feedback_value, false);
+        for (feedback_output, final_feedback_value) in
+            feedback_collector.generate_final_feedback_values()
+        {
+            self.send_direct_feedback(
+                feedback_reason,
+                final_feedback_value,
+                false,
+                Some(feedback_output),
+            );
         }
     }
 
@@ -3790,7 +4340,7 @@ impl<EH: DomainEventHandler> Basics<EH> {
             let previous_checksum = self
                 .last_feedback_checksum_by_address
                 .borrow_mut()
-                .insert(address, checksum);
+                .insert((feedback_output, address), checksum);
             // Block duplicates in certain cases. If it's feedback-after-control, we always need to
             // send because that's sort of the point of this feature. If it's a source-takeover, we
             // also need to send because we don't know what the other instance sent before that
@@ -3829,6 +4379,7 @@ impl<EH: DomainEventHandler> Basics<EH> {
                                     format_midi_source_value(&v),
                                 );
                             }
+                            self.record_feedback_sent(feedback_output);
                             self.channels
                                 .feedback_real_time_task_sender
                                 .send_complaining(FeedbackRealTimeTask::FxOutputFeedback(v));
@@ -3851,6 +4402,7 @@ impl<EH: DomainEventHandler> Basics<EH> {
                                     format_midi_source_value(&v),
                                 );
                             }
+                            self.record_feedback_sent(feedback_output);
                             self.channels
                                 .feedback_audio_hook_task_sender
                                 .send_complaining(FeedbackAudioHookTask::MidiDeviceFeedback(
@@ -3867,10 +4419,22 @@ impl<EH: DomainEventHandler> Basics<EH> {
                             format_osc_message(&msg),
                         );
                     }
+                    self.record_feedback_sent(feedback_output);
                     self.channels
                         .osc_feedback_task_sender
                         .send_complaining(OscFeedbackTask::new(dev_id, msg));
                 }
+                (FinalSourceFeedbackValue::Osc(msg), FeedbackOutput::WebSocket) => {
+                    if self.settings.real_output_logging_enabled {
+                        log_real_feedback_output(
+                            self.unit_id,
+                            feedback_reason,
+                            format_osc_message(&msg),
+                        );
+                    }
+                    self.record_feedback_sent(feedback_output);
+                    self.send_web_socket_feedback(WebSocketFeedbackValue::Raw(msg));
+                }
                 (FinalSourceFeedbackValue::Reaper(ReaperSourceFeedbackValue::Speech(v)), _) => {
                     let _ = say(v);
                 }
@@ -3879,16 +4443,53 @@ impl<EH: DomainEventHandler> Basics<EH> {
         }
     }
 
+    fn record_feedback_sent(&self, feedback_output: FeedbackOutput) {
+        if !metrics_util::metrics_are_enabled() {
+            return;
+        }
+        metrics_util::record_unit_occurrence(
+            "helgobox.feedback.sent",
+            self.unit_id.into(),
+            &[("output", &feedback_output.metric_label())],
+        );
+    }
+
+    /// WebSocket clients can act as virtual controller device, so they receive virtual feedback
+    /// directly (without the need for a controller mapping with a matching virtual target).
+    fn send_virtual_web_socket_feedback(
+        &self,
+        feedback_reason: FeedbackReason,
+        value: &VirtualFeedbackValue,
+    ) {
+        if self.settings.feedback_output != Some(FeedbackOutput::WebSocket) {
+            return;
+        }
+        if !feedback_reason.is_always_allowed() && !self.instance_feedback_is_effectively_enabled()
+        {
+            return;
+        }
+        self.record_feedback_sent(FeedbackOutput::WebSocket);
+        self.send_web_socket_feedback(WebSocketFeedbackValue::Virtual(value.clone()));
+    }
+
+    fn send_web_socket_feedback(&self, value: WebSocketFeedbackValue) {
+        self.unit.borrow_mut().memorize_web_socket_feedback(&value);
+        self.event_handler
+            .handle_event_ignoring_error(DomainEvent::WebSocketFeedback(value));
+    }
+
     fn send_direct_feedback(
         &self,
         feedback_reason: FeedbackReason,
         feedback_value: FinalRealFeedbackValue,
         is_feedback_after_control: bool,
+        feedback_output: Option<FeedbackOutput>,
     ) {
         self.send_direct_device_feedback(
             feedback_reason,
             feedback_value.source,
             is_feedback_after_control,
+            feedback_output,
         );
         self.send_direct_projection_feedback(feedback_value.projection);
     }
@@ -3902,40 +4503,51 @@ impl<EH: DomainEventHandler> Basics<EH> {
         }
     }
 
+    /// Sends the given source feedback to the main feedback output and all additional feedback
+    /// outputs, restricted to the given output if one is given.
     fn send_direct_device_feedback(
         &self,
         feedback_reason: FeedbackReason,
         feedback_value: Option<FinalSourceFeedbackValue>,
         is_feedback_after_control: bool,
+        selected_feedback_output: Option<FeedbackOutput>,
     ) {
         if !feedback_reason.is_always_allowed() && !self.instance_feedback_is_effectively_enabled()
         {
             return;
         }
-        if let Some(feedback_output) = self.settings.feedback_output {
-            if let Some(source_feedback_value) = feedback_value {
-                // At this point we can be sure that this mapping can't have a
-                // virtual source.
-                if feedback_reason.is_source_release() {
-                    // Possible interference with other instances. Don't switch off yet!
-                    // Give other instances the chance to take over.
-                    let event = UnitOrchestrationEvent::SourceReleased(SourceReleasedEvent {
-                        unit_id: self.unit_id.to_owned(),
-                        feedback_output,
-                        feedback_value: source_feedback_value,
-                    });
-                    self.channels
-                        .unit_orchestration_event_sender
-                        .send_complaining(event);
-                } else {
-                    // Send feedback right now.
-                    self.send_direct_source_feedback(
-                        feedback_output,
-                        feedback_reason,
-                        source_feedback_value,
-                        is_feedback_after_control,
-                    );
-                }
+        let Some(source_feedback_value) = feedback_value else {
+            return;
+        };
+        let feedback_outputs = self
+            .additional_io
+            .feedback_outputs(self.settings.feedback_output)
+            .filter(|o| match selected_feedback_output {
+                None => true,
+                Some(selected) => selected == *o,
+            });
+        for feedback_output in feedback_outputs {
+            // At this point we can be sure that this mapping can't have a
+            // virtual source.
+            if feedback_reason.is_source_release() {
+                // Possible interference with other instances. Don't switch off yet!
+                // Give other instances the chance to take over.
+                let event = UnitOrchestrationEvent::SourceReleased(SourceReleasedEvent {
+                    unit_id: self.unit_id.to_owned(),
+                    feedback_output,
+                    feedback_value: source_feedback_value.clone(),
+                });
+                self.channels
+                    .unit_orchestration_event_sender
+                    .send_complaining(event);
+            } else {
+                // Send feedback right now.
+                self.send_direct_source_feedback(
+                    feedback_output,
+                    feedback_reason,
+                    source_feedback_value.clone(),
+                    is_feedback_after_control,
+                );
             }
         }
     }
@@ -3972,8 +4584,10 @@ impl<EH: DomainEventHandler> Basics<EH> {
             .values_mut()
             .filter(|m| m.control_is_effectively_on())
             .filter_map(|m| {
-                if let CompoundMappingSource::Virtual(s) = &m.source() {
-                    let control_value = s.control(&evt.payload())?;
+                let virtual_source_value = evt.payload();
+                if let Some(control_value) =
+                    m.control_virtual_source(evt.with_payload(&virtual_source_value))
+                {
                     let control_event = evt.with_payload(control_value);
                     let options = ControlOptions {
                         enforce_target_refresh,
@@ -4141,6 +4755,14 @@ fn control_mapping_stage_one<EH: DomainEventHandler>(
     basics
         .event_handler
         .notify_mapping_matched(m.compartment(), m.id());
+    let control_event_timestamp = control_event.timestamp();
+    let feedback_policy = m.options().feedback_policy;
+    if !feedback_policy.is_default() {
+        basics
+            .feedback_policy_states
+            .borrow_mut()
+            .notify_controlled(m.qualified_id(), feedback_policy, Instant::now());
+    }
     let result = m.control_from_mode(
         control_event,
         options,
@@ -4153,6 +4775,15 @@ fn control_mapping_stage_one<EH: DomainEventHandler>(
         m.last_non_performance_target_value(),
         basics.target_control_logger(ControlLogContext::Normal, m.qualified_id()),
     );
+    // Source-to-target latency: From the moment the control event arrived until the target
+    // was hit.
+    if metrics_util::metrics_are_enabled() {
+        metrics_util::record_unit_duration(
+            "helgobox.control.latency",
+            basics.unit_id.into(),
+            ControlEventTimestamp::now() - control_event_timestamp,
+        );
+    }
     if result.at_least_one_target_caused_effect && result.celebrate_success {
         basics.celebrate_success();
     }
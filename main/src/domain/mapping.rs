@@ -1,16 +1,18 @@
 use crate::domain::{
     prop_feedback_resolution, prop_is_affected_by, ActivationChange, ActivationCondition,
-    BoxedHitInstruction, CompartmentParamIndex, CompoundChangeEvent, ControlContext, ControlEvent,
-    ControlEventTimestamp, ControlOptions, ExtendedProcessorContext, FeedbackResolution, GroupId,
+    AudioInput, AudioSource, BoxedHitInstruction, CompartmentParamIndex, CompartmentParams,
+    CompoundChangeEvent, CompoundSource, CompoundSourceMembers, ControlContext, ControlEvent,
+    ControlEventTimestamp, ControlOptions, ExtendedProcessorContext, FeedbackOutput,
+    FeedbackPolicy, FeedbackPolicyContext, FeedbackResolution, FeedbackValueRangeTable, GroupId,
     HitResponse, KeyMessage, KeySource, MappingActivationEffect, MappingControlContext,
-    MappingData, MappingInfo, MappingPropProvider, MessageCaptureEvent, MidiScanResult, MidiSource,
-    Mode, OscDeviceId, OscScanResult, PersistentMappingProcessingState, PluginParamIndex,
-    PluginParams, RealTimeMappingUpdate, RealTimeReaperTarget, RealTimeTargetUpdate,
-    RealearnParameterChangePayload, RealearnParameterSource, RealearnSourceContext, RealearnTarget,
-    ReaperMessage, ReaperSource, ReaperSourceFeedbackValue, ReaperTarget, ReaperTargetType, Tag,
-    TargetCharacter, TrackExclusivity, UnresolvedReaperTarget, VirtualControlElement,
-    VirtualFeedbackValue, VirtualSource, VirtualSourceAddress, VirtualSourceValue, VirtualTarget,
-    COMPARTMENT_PARAMETER_COUNT,
+    MappingData, MappingInfo, MappingIoSelection, MappingPropProvider, MessageCaptureEvent,
+    MidiScanResult, MidiSource, Mode, OscDeviceId, OscScanResult, PersistentMappingProcessingState,
+    PluginParamIndex, PluginParams, RealTimeMappingUpdate, RealTimeReaperTarget,
+    RealTimeTargetUpdate, RealearnParameterChangePayload, RealearnParameterSource,
+    RealearnSourceContext, RealearnTarget, ReaperMessage, ReaperSource, ReaperSourceFeedbackValue,
+    ReaperTarget, ReaperTargetType, Tag, TargetCharacter, TrackExclusivity, UnresolvedReaperTarget,
+    VirtualControlElement, VirtualFeedbackValue, VirtualSource, VirtualSourceAddress,
+    VirtualSourceValue, VirtualTarget, COMPARTMENT_PARAMETER_COUNT,
 };
 use derive_more::Display;
 use enum_map::Enum;
@@ -18,8 +20,8 @@ use helgoboss_learn::{
     format_percentage_without_unit, parse_percentage_without_unit, AbsoluteValue, ControlResult,
     ControlType, ControlValue, FeedbackValue, GroupInteraction, MidiSourceAddress, MidiSourceValue,
     ModeControlOptions, ModeControlResult, ModeFeedbackOptions, NumericFeedbackValue, NumericValue,
-    OscSource, OscSourceAddress, PreliminaryMidiSourceFeedbackValue, PropValue, RawMidiEvent,
-    SourceCharacter, Target, UnitValue, ValueFormatter, ValueParser,
+    OscSource, OscSourceAddress, PreliminaryMidiSourceFeedbackValue, PropProvider, PropValue,
+    RawMidiEvent, SourceCharacter, Target, UnitValue, ValueFormatter, ValueParser,
 };
 use helgoboss_midi::{Channel, RawShortMessage, ShortMessage};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
@@ -30,9 +32,10 @@ use crate::domain::unresolved_reaper_target::UnresolvedReaperTargetDef;
 use base::hash_util::{NonCryptoHashSet, NonCryptoIndexMap, NonCryptoIndexSet};
 use playtime_api::persistence::{ColumnAddress, RowAddress, SlotAddress};
 use reaper_high::{Fx, Project, Track, TrackRoute};
-use reaper_medium::MidiInputDeviceId;
-use rosc::OscMessage;
+use reaper_medium::{Hz, MidiInputDeviceId};
+use rosc::{OscMessage, OscType};
 use serde::{Deserialize, Serialize};
+use smallvec::{smallvec, SmallVec};
 use std::convert::TryInto;
 use std::fmt;
 use std::fmt::{Display, Formatter, Write};
@@ -53,6 +56,8 @@ pub struct ProcessorMappingOptions {
     pub control_is_enabled: bool,
     pub feedback_is_enabled: bool,
     pub feedback_send_behavior: FeedbackSendBehavior,
+    pub feedback_policy: FeedbackPolicy,
+    pub io_selection: MappingIoSelection,
     pub beep_on_success: bool,
 }
 
@@ -162,16 +167,93 @@ pub struct LifecycleMidiData {
     pub deactivation_midi_messages: Vec<LifecycleMidiMessage>,
 }
 
+/// Lifecycle actions which are executed in the main thread.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LifecycleMainData {
+    pub activation_actions: LifecycleActions,
+    pub deactivation_actions: LifecycleActions,
+}
+
+impl LifecycleMainData {
+    pub fn is_empty(&self) -> bool {
+        self.activation_actions.is_empty() && self.deactivation_actions.is_empty()
+    }
+
+    pub fn actions(&self, phase: LifecyclePhase) -> &LifecycleActions {
+        use LifecyclePhase::*;
+        match phase {
+            Activation => &self.activation_actions,
+            Deactivation => &self.deactivation_actions,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LifecycleActions {
+    pub actions: Vec<LifecycleAction>,
+    /// Lua code which returns additional actions to be executed after the static ones.
+    pub lua_script: Option<String>,
+}
+
+impl LifecycleActions {
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty() && self.lua_script.is_none()
+    }
+}
+
+/// A lifecycle action. Deserializable because Lua lifecycle scripts return them.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "kind")]
+pub enum LifecycleAction {
+    SendOscFeedback {
+        address: String,
+        #[serde(default)]
+        arguments: Vec<LifecycleOscArg>,
+    },
+    SetTargetValue {
+        value: f64,
+    },
+    SetParameter {
+        index: CompartmentParamIndex,
+        value: f64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LifecycleOscArg {
+    Bool(bool),
+    Int(i32),
+    Float(f64),
+    String(String),
+}
+
+impl LifecycleOscArg {
+    pub fn to_osc_type(&self) -> OscType {
+        match self {
+            LifecycleOscArg::Bool(v) => OscType::Bool(*v),
+            LifecycleOscArg::Int(v) => OscType::Int(*v),
+            LifecycleOscArg::Float(v) => OscType::Float(*v as f32),
+            LifecycleOscArg::String(v) => OscType::String(v.clone()),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct MappingExtension {
     /// If it's None, it means it's splintered already.
     lifecycle_midi_data: Option<LifecycleMidiData>,
+    lifecycle_main_data: LifecycleMainData,
 }
 
 impl MappingExtension {
-    pub fn new(lifecycle_midi_data: LifecycleMidiData) -> Self {
+    pub fn new(
+        lifecycle_midi_data: LifecycleMidiData,
+        lifecycle_main_data: LifecycleMainData,
+    ) -> Self {
         Self {
             lifecycle_midi_data: Some(lifecycle_midi_data),
+            lifecycle_main_data,
         }
     }
 }
@@ -196,6 +278,8 @@ pub struct MainMapping {
     activation_condition_2: ActivationCondition,
     activation_state: ActivationState,
     extension: MappingExtension,
+    /// Numeric range table which is applied to the feedback value produced by the mode.
+    feedback_value_range_table: Option<FeedbackValueRangeTable>,
     initial_target_value: Option<AbsoluteValue>,
     /// Called "y_last" in the control transformation formula.
     last_non_performance_target_value: Cell<Option<AbsoluteValue>>,
@@ -230,6 +314,7 @@ impl MainMapping {
         activation_condition_2: ActivationCondition,
         options: ProcessorMappingOptions,
         extension: MappingExtension,
+        feedback_value_range_table: Option<FeedbackValueRangeTable>,
     ) -> MainMapping {
         MainMapping {
             core: MappingCore {
@@ -255,6 +340,7 @@ impl MainMapping {
             activation_condition_2,
             activation_state: Default::default(),
             extension,
+            feedback_value_range_table,
             initial_target_value: None,
             last_non_performance_target_value: Cell::new(None),
         }
@@ -548,6 +634,24 @@ impl MainMapping {
         self.last_non_performance_target_value = Cell::new(target_value);
     }
 
+    /// Initializes target and activation without consulting REAPER.
+    ///
+    /// Virtual targets are resolved as usual. REAPER targets stay unresolved but are considered
+    /// active, so the caller can substitute them with mock targets (see [`HeadlessUnit`]).
+    ///
+    /// [`HeadlessUnit`]: crate::domain::HeadlessUnit
+    #[cfg(test)]
+    pub fn init_target_and_activation_headless(&mut self, params: &PluginParams) {
+        self.targets = match self.unresolved_target.as_ref() {
+            Some(UnresolvedCompoundMappingTarget::Virtual(t)) => {
+                vec![CompoundMappingTarget::Virtual(*t)]
+            }
+            _ => vec![],
+        };
+        self.core.options.target_is_active = self.unresolved_target.is_some();
+        self.update_activation_from_params(params);
+    }
+
     fn resolve_target(
         &mut self,
         context: ExtendedProcessorContext,
@@ -729,10 +833,34 @@ impl MainMapping {
         )
     }
 
+    pub fn lifecycle_main_data(&self) -> &LifecycleMainData {
+        &self.extension.lifecycle_main_data
+    }
+
     pub fn source(&self) -> &CompoundMappingSource {
         &self.core.source
     }
 
+    /// Lets the source process the given virtual source value (stateful for compound sources).
+    pub fn control_virtual_source(
+        &mut self,
+        evt: ControlEvent<&VirtualSourceValue>,
+    ) -> Option<ControlValue> {
+        self.core
+            .source
+            .control_virtual(evt.payload(), evt.timestamp())
+    }
+
+    /// Lets the source process the given MIDI source value (stateful for compound sources).
+    pub fn control_midi_source(
+        &mut self,
+        evt: ControlEvent<&MidiSourceValue<RawShortMessage>>,
+    ) -> Option<ControlValue> {
+        self.core
+            .source
+            .control_midi(evt.payload(), evt.timestamp())
+    }
+
     pub fn targets(&self) -> &[CompoundMappingTarget] {
         &self.targets
     }
@@ -803,6 +931,32 @@ impl MainMapping {
         result
     }
 
+    /// Lets the mode process the given control event against the given target without hitting it.
+    ///
+    /// Unlike [`Self::control_from_mode`], this doesn't need REAPER. It's up to the caller to
+    /// apply the result to the target.
+    #[cfg(test)]
+    pub fn control_given_target<'a>(
+        &mut self,
+        source_control_event: ControlEvent<ControlValue>,
+        target: &impl Target<'a, Context = ()>,
+    ) -> Option<ModeControlResult<ControlValue>> {
+        let options = self.mode_control_options();
+        let last_non_performance_target_value = self.last_non_performance_target_value();
+        self.core.increase_invocation_count();
+        let res = self.core.mode.control_with_options(
+            source_control_event,
+            target,
+            (),
+            options,
+            last_non_performance_target_value,
+        )?;
+        if let ModeControlResult::HitTarget { .. } = res {
+            self.core.time_of_last_control = Some(Instant::now());
+        }
+        Some(res)
+    }
+
     /// Controls target directly without using mode.
     ///
     /// Don't execute in real-time processor because this executes REAPER main-thread-only
@@ -1079,7 +1233,28 @@ impl MainMapping {
         control_context: ControlContext,
     ) -> Option<CompoundFeedbackValue> {
         self.feedback_entry_point(true, true, new_target_value, control_context, NoopLogger)
-            .map(CompoundFeedbackValue::normal)
+            .map(|v| {
+                CompoundFeedbackValue::normal(v, self.core.options.io_selection.feedback_output)
+                    .with_feedback_policy(self.feedback_policy_context(new_target_value))
+            })
+    }
+
+    /// Returns what's necessary to subject feedback reflecting the given target value to the
+    /// feedback policy of this mapping. Returns `None` if this mapping uses the default policy.
+    pub fn feedback_policy_context(
+        &self,
+        target_value: Option<AbsoluteValue>,
+    ) -> Option<FeedbackPolicyContext> {
+        let policy = self.core.options.feedback_policy;
+        if policy.is_default() {
+            return None;
+        }
+        let context = FeedbackPolicyContext {
+            mapping_id: self.qualified_id(),
+            policy,
+            target_value: target_value.map(|v| v.to_unit_value()),
+        };
+        Some(context)
     }
 
     /// Returns `None` when used on mappings with virtual targets.
@@ -1095,7 +1270,7 @@ impl MainMapping {
             context,
             NoopLogger,
         )
-        .map(CompoundFeedbackValue::normal)
+        .map(|v| CompoundFeedbackValue::normal(v, self.core.options.io_selection.feedback_output))
     }
 
     /// This is the primary entry point to feedback!
@@ -1118,10 +1293,33 @@ impl MainMapping {
         // - This leaves us with asking the mode. That means the user needs to explicitly choose
         //   whether it wants numerical or textual feedback.
         let prop_provider = MappingPropProvider::new(self, control_context);
+        self.feedback_given_prop_provider(
+            with_projection_feedback,
+            with_source_feedback,
+            combined_target_value,
+            control_context.source_context,
+            &prop_provider,
+            logger,
+        )
+    }
+
+    /// Produces feedback with target properties taken from the given prop provider.
+    ///
+    /// This is what [`Self::feedback_entry_point`] does after looking up the current target. It
+    /// doesn't need REAPER as long as the given prop provider doesn't.
+    pub fn feedback_given_prop_provider(
+        &self,
+        with_projection_feedback: bool,
+        with_source_feedback: bool,
+        combined_target_value: Option<AbsoluteValue>,
+        source_context: RealearnSourceContext,
+        prop_provider: &impl PropProvider,
+        logger: impl SourceFeedbackLogger,
+    ) -> Option<SpecificCompoundFeedbackValue> {
         let feedback_value = if self.core.mode.wants_advanced_feedback() {
-            self.core.mode.build_feedback(&prop_provider)
+            self.core.mode.build_feedback(prop_provider)
         } else {
-            let style = self.core.mode.feedback_style(&prop_provider);
+            let style = self.core.mode.feedback_style(prop_provider);
             FeedbackValue::Numeric(NumericFeedbackValue::new(style, combined_target_value?))
         };
         let source_feedback_is_okay = if self.core.options.feedback_send_behavior
@@ -1137,7 +1335,8 @@ impl MainMapping {
                 with_projection_feedback,
                 with_source_feedback: with_source_feedback && source_feedback_is_okay,
             },
-            control_context.source_context,
+            source_context,
+            Some(prop_provider),
             logger,
         )
     }
@@ -1163,11 +1362,14 @@ impl MainMapping {
     /// can give us ownership of the feedback value. It's also better than taking an owned value
     /// because it's possible that we don't produce a feedback value at all! In which a consumer
     /// that can't give up ownership would need to make a clone in advance - for nothing!
+    ///
+    /// The prop provider is only necessary for numeric range tables.
     pub fn feedback_given_target_value(
         &self,
         feedback_value: Cow<FeedbackValue>,
         destinations: FeedbackDestinations,
         source_context: RealearnSourceContext,
+        prop_provider: Option<&dyn PropProvider>,
         logger: impl SourceFeedbackLogger,
     ) -> Option<SpecificCompoundFeedbackValue> {
         let options = ModeFeedbackOptions {
@@ -1179,6 +1381,10 @@ impl MainMapping {
             options,
             Default::default(),
         )?;
+        let mode_value = match &self.feedback_value_range_table {
+            None => mode_value,
+            Some(table) => table.apply(mode_value, prop_provider),
+        };
         logger.log(FeedbackLogEntry {
             feedback_value: mode_value.as_ref(),
         });
@@ -1223,7 +1429,7 @@ impl MainMapping {
             },
             source_context,
         )
-        .map(CompoundFeedbackValue::normal)
+        .map(|v| CompoundFeedbackValue::normal(v, self.core.options.io_selection.feedback_output))
     }
 
     fn manual_feedback_after_control_if_enabled(
@@ -1237,14 +1443,15 @@ impl MainMapping {
         {
             if self.feedback_is_effectively_on() {
                 // No projection feedback in this case! Just the source controller needs this hack.
-                self.feedback_entry_point(
-                    false,
-                    true,
-                    self.current_aggregated_target_value(context),
-                    context,
-                    NoopLogger,
-                )
-                .map(CompoundFeedbackValue::feedback_after_control)
+                let target_value = self.current_aggregated_target_value(context);
+                self.feedback_entry_point(false, true, target_value, context, NoopLogger)
+                    .map(|v| {
+                        CompoundFeedbackValue::feedback_after_control(
+                            v,
+                            self.core.options.io_selection.feedback_output,
+                        )
+                        .with_feedback_policy(self.feedback_policy_context(target_value))
+                    })
             } else {
                 None
             }
@@ -1278,9 +1485,9 @@ impl MainMapping {
     }
 
     /// Polls the source.
-    pub fn poll_source(&mut self) -> Option<ControlValue> {
+    pub fn poll_source(&mut self, params: &CompartmentParams) -> Option<ControlValue> {
         match &mut self.core.source {
-            CompoundMappingSource::Reaper(s) => s.poll(),
+            CompoundMappingSource::Reaper(s) => s.poll(params),
             _ => None,
         }
     }
@@ -1361,7 +1568,7 @@ pub enum UnresolvedTargetCategory {
     Virtual,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum LifecyclePhase {
     Activation,
     Deactivation,
@@ -1456,6 +1663,38 @@ impl RealTimeMapping {
         &self.core.source
     }
 
+    /// Lets the source process the given virtual source value (stateful for compound sources).
+    pub fn control_virtual_source(
+        &mut self,
+        evt: ControlEvent<&VirtualSourceValue>,
+    ) -> Option<ControlValue> {
+        self.core
+            .source
+            .control_virtual(evt.payload(), evt.timestamp())
+    }
+
+    /// Lets the source process the given MIDI source value (stateful for compound sources).
+    pub fn control_midi_source(
+        &mut self,
+        evt: ControlEvent<&MidiSourceValue<RawShortMessage>>,
+    ) -> Option<ControlValue> {
+        self.core
+            .source
+            .control_midi(evt.payload(), evt.timestamp())
+    }
+
+    /// Lets an audio source analyze the given block of audio input.
+    pub fn control_audio_source<I: AudioInput + ?Sized>(
+        &mut self,
+        input: &I,
+        sample_rate: Hz,
+    ) -> Option<ControlValue> {
+        let CompoundMappingSource::Audio(s) = &mut self.core.source else {
+            return None;
+        };
+        s.process(input, sample_rate)
+    }
+
     pub fn has_reaper_target(&self) -> bool {
         matches!(self.target_category, Some(UnresolvedTargetCategory::Reaper))
     }
@@ -1481,16 +1720,28 @@ impl RealTimeMapping {
         if !self.target_is_resolved {
             return None;
         }
-        let control_value = if let CompoundMappingSource::Midi(s) = &self.core.source {
-            s.control(evt.payload())?
-        } else {
+        let control_value = self.control_midi_source(evt)?;
+        self.virtualize(evt.with_payload(control_value))
+    }
+
+    pub fn control_audio_virtualizing<I: AudioInput + ?Sized>(
+        &mut self,
+        input: &I,
+        sample_rate: Hz,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<PartialControlMatch> {
+        if !self.target_is_resolved {
             return None;
-        };
+        }
+        let control_value = self.control_audio_source(input, sample_rate)?;
+        self.virtualize(ControlEvent::new(control_value, timestamp))
+    }
+
+    fn virtualize(&mut self, evt: ControlEvent<ControlValue>) -> Option<PartialControlMatch> {
         if let Some(RealTimeCompoundMappingTarget::Virtual(t)) = self.resolved_target.as_ref() {
-            match_partially(&mut self.core, t, evt.with_payload(control_value))
-                .map(PartialControlMatch::ProcessVirtual)
+            match_partially(&mut self.core, t, evt).map(PartialControlMatch::ProcessVirtual)
         } else {
-            Some(PartialControlMatch::ProcessDirect(control_value))
+            Some(PartialControlMatch::ProcessDirect(evt.payload()))
         }
     }
 }
@@ -1566,6 +1817,8 @@ pub enum CompoundMappingSource {
     Virtual(VirtualSource),
     Reaper(ReaperSource),
     Key(KeySource),
+    Compound(CompoundSource),
+    Audio(AudioSource),
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -1594,10 +1847,46 @@ impl CompoundMappingSource {
         use CompoundMappingSource::*;
         match self {
             Reaper(s) => s.on_deactivate(),
+            Compound(s) => s.on_deactivate(),
+            Audio(s) => s.on_deactivate(),
             _ => {}
         }
     }
 
+    /// Processes the given MIDI source value.
+    ///
+    /// In contrast to [`Self::reacts_to_source_value_with`], this can change the state of the
+    /// source.
+    pub fn control_midi(
+        &mut self,
+        value: &MidiSourceValue<RawShortMessage>,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<ControlValue> {
+        use CompoundMappingSource::*;
+        match self {
+            Midi(s) => s.control(value),
+            Compound(s) => s.control_midi(value, timestamp),
+            _ => None,
+        }
+    }
+
+    /// Processes the given virtual source value.
+    ///
+    /// In contrast to [`Self::reacts_to_source_value_with`], this can change the state of the
+    /// source.
+    pub fn control_virtual(
+        &mut self,
+        value: &VirtualSourceValue,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<ControlValue> {
+        use CompoundMappingSource::*;
+        match self {
+            Virtual(s) => s.control(value),
+            Compound(s) => s.control_virtual(value, timestamp),
+            _ => None,
+        }
+    }
+
     /// If this returns `true`, the `poll` method should be called, on a regular basis.
     pub fn wants_to_be_polled(&self) -> bool {
         use CompoundMappingSource::*;
@@ -1698,6 +1987,12 @@ impl CompoundMappingSource {
             (Key(s), IncomingCompoundSourceValue::Key(m)) => {
                 s.reacts_to_message_with(m).map(ControlResult::Processed)
             }
+            (Compound(s), IncomingCompoundSourceValue::Midi(v)) => s
+                .member_reacts_to_midi_value(v)
+                .map(ControlResult::Processed),
+            (Compound(s), IncomingCompoundSourceValue::Virtual(m)) => s
+                .member_reacts_to_virtual_value(m)
+                .map(ControlResult::Processed),
             _ => None,
         }
     }
@@ -1736,7 +2031,9 @@ impl CompoundMappingSource {
             Virtual(s) => s.format_control_value(value),
             Osc(s) => s.format_control_value(value),
             Reaper(s) => s.format_control_value(value),
-            Never | Key(_) => Ok(format_percentage_without_unit(value.to_unit_value()?.get())),
+            Never | Key(_) | Compound(_) | Audio(_) => {
+                Ok(format_percentage_without_unit(value.to_unit_value()?.get()))
+            }
         }
     }
 
@@ -1747,7 +2044,9 @@ impl CompoundMappingSource {
             Virtual(s) => s.parse_control_value(text),
             Osc(s) => s.parse_control_value(text),
             Reaper(s) => s.parse_control_value(text),
-            Never | Key(_) => parse_percentage_without_unit(text)?.try_into(),
+            Never | Key(_) | Compound(_) | Audio(_) => {
+                parse_percentage_without_unit(text)?.try_into()
+            }
         }
     }
 
@@ -1758,8 +2057,11 @@ impl CompoundMappingSource {
             Virtual(s) => s.character(),
             Osc(s) => ExtendedSourceCharacter::Normal(s.character()),
             Reaper(s) => ExtendedSourceCharacter::Normal(s.character()),
+            Audio(s) => ExtendedSourceCharacter::Normal(s.character()),
             Never => ExtendedSourceCharacter::VirtualContinuous,
-            Key(_) => ExtendedSourceCharacter::Normal(SourceCharacter::MomentaryButton),
+            Key(_) | Compound(_) => {
+                ExtendedSourceCharacter::Normal(SourceCharacter::MomentaryButton)
+            }
         }
     }
 
@@ -1779,10 +2081,13 @@ impl CompoundMappingSource {
             Reaper(s) => s
                 .feedback(&feedback_value)
                 .map(PreliminarySourceFeedbackValue::Reaper),
+            Compound(s) => s
+                .midi_feedback(feedback_value, source_context)
+                .map(PreliminarySourceFeedbackValue::CompoundMidi),
             // This is handled in a special way by consumers.
             Virtual(_) => None,
             // No feedback for other sources.
-            Key(_) | Never => None,
+            Key(_) | Audio(_) | Never => None,
         }
     }
 
@@ -1790,21 +2095,30 @@ impl CompoundMappingSource {
         use CompoundMappingSource::*;
         match self {
             Midi(s) => s.consumes(msg),
-            Reaper(_) | Virtual(_) | Osc(_) | Never | Key(_) => false,
+            // Compound sources don't consume because each member message is also meaningful on
+            // its own.
+            Reaper(_) | Virtual(_) | Osc(_) | Never | Key(_) | Compound(_) | Audio(_) => false,
         }
     }
 
     pub fn is_virtual(&self) -> bool {
-        matches!(self, CompoundMappingSource::Virtual(_))
+        match self {
+            CompoundMappingSource::Virtual(_) => true,
+            CompoundMappingSource::Compound(s) => {
+                matches!(s.members(), CompoundSourceMembers::Virtual(_))
+            }
+            _ => false,
+        }
     }
 
     pub fn max_discrete_value(&self) -> Option<u32> {
         use CompoundMappingSource::*;
         match self {
             Midi(s) => s.max_discrete_value(),
+            Audio(s) => s.max_discrete_value(),
             // TODO-medium OSC will also support discrete values as soon as we allow integers and
             //  configuring max values
-            Reaper(_) | Virtual(_) | Osc(_) | Never | Key(_) => None,
+            Reaper(_) | Virtual(_) | Osc(_) | Never | Key(_) | Compound(_) => None,
         }
     }
 }
@@ -1813,28 +2127,49 @@ impl CompoundMappingSource {
 pub struct CompoundFeedbackValue {
     pub value: SpecificCompoundFeedbackValue,
     pub is_feedback_after_control: bool,
+    /// Restricts source feedback to this output. `None` means all feedback outputs of the unit.
+    pub feedback_output: Option<FeedbackOutput>,
+    /// If set, the feedback is sent only if the feedback policy of the producing mapping allows
+    /// it.
+    pub feedback_policy: Option<FeedbackPolicyContext>,
 }
 
 impl CompoundFeedbackValue {
-    pub fn normal(value: SpecificCompoundFeedbackValue) -> Self {
+    pub fn normal(
+        value: SpecificCompoundFeedbackValue,
+        feedback_output: Option<FeedbackOutput>,
+    ) -> Self {
         Self {
             value,
             is_feedback_after_control: false,
+            feedback_output,
+            feedback_policy: None,
         }
     }
 
-    pub fn feedback_after_control(value: SpecificCompoundFeedbackValue) -> Self {
+    pub fn feedback_after_control(
+        value: SpecificCompoundFeedbackValue,
+        feedback_output: Option<FeedbackOutput>,
+    ) -> Self {
         Self {
             value,
             is_feedback_after_control: true,
+            feedback_output,
+            feedback_policy: None,
         }
     }
+
+    pub fn with_feedback_policy(mut self, feedback_policy: Option<FeedbackPolicyContext>) -> Self {
+        self.feedback_policy = feedback_policy;
+        self
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum SpecificCompoundFeedbackValue {
     Virtual {
-        value: VirtualFeedbackValue,
+        /// Usually one value, more if the source is a compound source with virtual members.
+        values: SmallVec<[VirtualFeedbackValue; 1]>,
         destinations: FeedbackDestinations,
     },
     Real(PreliminaryRealFeedbackValue),
@@ -1870,7 +2205,13 @@ impl SpecificCompoundFeedbackValue {
             // Virtual source
             SpecificCompoundFeedbackValue::Virtual {
                 destinations,
-                value: vs.feedback(mode_value.into_owned()),
+                values: smallvec![vs.feedback(mode_value.into_owned())],
+            }
+        } else if let (CompoundMappingSource::Compound(cs), true) = (&source, source.is_virtual()) {
+            // Compound source with virtual members
+            SpecificCompoundFeedbackValue::Virtual {
+                destinations,
+                values: cs.virtual_feedback(mode_value),
             }
         } else {
             // Real source
@@ -1944,6 +2285,8 @@ impl ProjectionFeedbackValue {
 #[derive(Clone, PartialEq, Debug)]
 pub enum PreliminarySourceFeedbackValue {
     Midi(PreliminaryMidiSourceFeedbackValue<'static, RawShortMessage>),
+    /// Combined feedback for all MIDI members of a compound source. Is final already.
+    CompoundMidi(MidiSourceValue<'static, RawShortMessage>),
     Osc(OscMessage),
     Reaper(ReaperSourceFeedbackValue),
 }
@@ -2806,3 +3149,21 @@ pub enum ControlLogEntryKind {
     #[display(fmt = "Failed to execute hit instruction")]
     FailedExecutingHitInstruction,
 }
+
+impl ControlLogEntryKind {
+    /// Short and stable identifier, used as label value when recording metrics.
+    pub fn metric_label(&self) -> &'static str {
+        use ControlLogEntryKind::*;
+        match self {
+            IgnoredByGlue => "ignored-by-glue",
+            LeftTargetUntouched => "left-target-untouched",
+            IgnoredByTarget => "ignored-by-target",
+            HitSuccessfully => "hit-successfully",
+            HitFailed => "hit-failed",
+            CreatedHitInstruction => "created-hit-instruction",
+            DiscardedHitInstruction => "discarded-hit-instruction",
+            ExecutedHitInstructionSuccessfully => "executed-hit-instruction-successfully",
+            FailedExecutingHitInstruction => "failed-executing-hit-instruction",
+        }
+    }
+}
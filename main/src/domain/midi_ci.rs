@@ -0,0 +1,580 @@
+use crate::domain::{
+    format_as_pretty_hex, parse_hex_string, DisplayAsPrettyHex, MidiDeviceInquiryReply,
+};
+use helgoboss_learn::RawMidiEvent;
+use helgobox_api::persistence::MidiDeviceIdentity as PersistentMidiDeviceIdentity;
+use reaper_medium::MidiInputDeviceId;
+use serde::Deserialize;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Display, Formatter};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::time::Instant;
+
+/// Universal SysEx sub-ID #1 of MIDI-CI messages.
+const MIDI_CI_SUB_ID: u8 = 0x0D;
+
+/// We speak MIDI-CI version 1.2.
+const MIDI_CI_VERSION: u8 = 0x02;
+
+/// Device ID which addresses the whole function block instead of a particular MIDI channel.
+const FUNCTION_BLOCK: u8 = 0x7F;
+
+/// SysEx ID reserved for non-commercial use. That's what we identify ourselves with.
+const NON_COMMERCIAL_MANUFACTURER_ID: [u8; 3] = [0x7D, 0x00, 0x00];
+
+const CATEGORY_PROFILE_CONFIGURATION: u8 = 0x04;
+const CATEGORY_PROPERTY_EXCHANGE: u8 = 0x08;
+
+/// The MIDI-CI message types (sub-ID #2) that we send or expect as reply.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[repr(u8)]
+pub enum MidiCiMessageType {
+    ProfileInquiry = 0x20,
+    ProfileInquiryReply = 0x21,
+    PropertyExchangeCapabilitiesInquiry = 0x30,
+    PropertyExchangeCapabilitiesReply = 0x31,
+    PropertyGetInquiry = 0x34,
+    PropertyGetReply = 0x35,
+    Discovery = 0x70,
+    DiscoveryReply = 0x71,
+}
+
+/// 28-bit MIDI-CI unique identifier.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MidiCiMuid(u32);
+
+impl MidiCiMuid {
+    pub const BROADCAST: Self = Self(0x0FFF_FFFF);
+
+    /// Generates a random MUID outside of the reserved range.
+    pub fn random() -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        Instant::now().hash(&mut hasher);
+        Self(hasher.finish() as u32 % 0x0FFF_FF00)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self(decode_u28(bytes)?))
+    }
+
+    pub fn to_bytes(self) -> [u8; 4] {
+        encode_u28(self.0)
+    }
+}
+
+impl Display for MidiCiMuid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:07X}", self.0)
+    }
+}
+
+/// Manufacturer, family, model and version of a MIDI device.
+///
+/// Can be obtained via MIDI-CI discovery or via the classic device inquiry.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct MidiDeviceIdentity {
+    /// SysEx manufacturer ID. One-byte IDs are stored as `[id, 0, 0]`, as in MIDI-CI.
+    pub manufacturer_id: [u8; 3],
+    pub family: u16,
+    pub model: u16,
+    pub version: [u8; 4],
+}
+
+impl MidiDeviceIdentity {
+    /// Parses the reply to a classic Universal SysEx device inquiry.
+    pub fn from_device_inquiry_reply(message: &[u8]) -> Option<Self> {
+        let [0xF0, 0x7E, _, 0x06, 0x02, payload @ .., 0xF7] = message else {
+            return None;
+        };
+        let mut reader = PayloadReader::new(payload);
+        let manufacturer_id = match reader.byte()? {
+            0x00 => {
+                let extended = reader.take(2)?;
+                [0x00, extended[0], extended[1]]
+            }
+            id => [id, 0x00, 0x00],
+        };
+        let identity = Self {
+            manufacturer_id,
+            family: reader.u14()?,
+            model: reader.u14()?,
+            version: reader.take(4)?.try_into().ok()?,
+        };
+        Some(identity)
+    }
+}
+
+/// Formats the given manufacturer ID as hex, using one byte for one-byte IDs.
+pub fn format_midi_manufacturer_id(id: [u8; 3]) -> String {
+    if id[0] == 0x00 {
+        format_as_pretty_hex(&id)
+    } else {
+        format_as_pretty_hex(&id[..1])
+    }
+}
+
+/// Parses a manufacturer ID such as "47" or "00 20 29".
+pub fn parse_midi_manufacturer_id(text: &str) -> Option<[u8; 3]> {
+    match parse_hex_string(text).ok()?.as_slice() {
+        [id] => Some([*id, 0x00, 0x00]),
+        [a, b, c] => Some([*a, *b, *c]),
+        _ => None,
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct MidiCiDiscoveryReply {
+    pub muid: MidiCiMuid,
+    pub identity: MidiDeviceIdentity,
+    pub categories: u8,
+    pub max_sysex_size: u32,
+}
+
+impl MidiCiDiscoveryReply {
+    pub fn parse(message: &[u8]) -> Option<Self> {
+        let msg = parse_midi_ci_message(message, MidiCiMessageType::DiscoveryReply)?;
+        let mut reader = PayloadReader::new(msg.payload);
+        let reply = Self {
+            muid: msg.source,
+            identity: MidiDeviceIdentity {
+                manufacturer_id: reader.take(3)?.try_into().ok()?,
+                family: reader.u14()?,
+                model: reader.u14()?,
+                version: reader.take(4)?.try_into().ok()?,
+            },
+            categories: reader.byte()?,
+            max_sysex_size: reader.u28()?,
+        };
+        Some(reply)
+    }
+
+    pub fn supports_profile_configuration(&self) -> bool {
+        self.categories & CATEGORY_PROFILE_CONFIGURATION != 0
+    }
+
+    pub fn supports_property_exchange(&self) -> bool {
+        self.categories & CATEGORY_PROPERTY_EXCHANGE != 0
+    }
+}
+
+/// 5-byte MIDI-CI profile ID.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MidiCiProfileId(pub [u8; 5]);
+
+impl Display for MidiCiProfileId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        DisplayAsPrettyHex(&self.0).fmt(f)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct MidiCiProfileInquiryReply {
+    pub enabled: Vec<MidiCiProfileId>,
+    pub disabled: Vec<MidiCiProfileId>,
+}
+
+impl MidiCiProfileInquiryReply {
+    pub fn parse(message: &[u8]) -> Option<Self> {
+        let msg = parse_midi_ci_message(message, MidiCiMessageType::ProfileInquiryReply)?;
+        let mut reader = PayloadReader::new(msg.payload);
+        let reply = Self {
+            enabled: reader.profile_ids()?,
+            disabled: reader.profile_ids()?,
+        };
+        Some(reply)
+    }
+}
+
+/// One chunk of a property exchange reply.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MidiCiPropertyChunk {
+    pub request_id: u8,
+    pub header: Vec<u8>,
+    pub chunk_count: u16,
+    /// 1-based.
+    pub chunk_index: u16,
+    pub data: Vec<u8>,
+}
+
+impl MidiCiPropertyChunk {
+    pub fn parse(message: &[u8]) -> Option<Self> {
+        let msg = parse_midi_ci_message(message, MidiCiMessageType::PropertyGetReply)?;
+        let mut reader = PayloadReader::new(msg.payload);
+        let request_id = reader.byte()?;
+        let header_len = reader.u14()?;
+        let header = reader.take(header_len as usize)?.to_vec();
+        let chunk_count = reader.u14()?;
+        let chunk_index = reader.u14()?;
+        let data_len = reader.u14()?;
+        let chunk = Self {
+            request_id,
+            header,
+            chunk_count,
+            chunk_index,
+            data: reader.take(data_len as usize)?.to_vec(),
+        };
+        Some(chunk)
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.chunk_index >= self.chunk_count
+    }
+}
+
+/// The part of the "DeviceInfo" property exchange resource which helps identifying a device.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MidiCiDeviceInfo {
+    #[serde(default)]
+    pub manufacturer: Option<String>,
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub serial_number: Option<String>,
+}
+
+impl MidiCiDeviceInfo {
+    /// Assembles the device info from the chunks of a successful property exchange reply.
+    pub fn from_chunks(chunks: &[MidiCiPropertyChunk]) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct ReplyHeader {
+            status: Option<u32>,
+        }
+        let first_chunk = chunks.first()?;
+        let header: ReplyHeader = serde_json::from_slice(&first_chunk.header).ok()?;
+        if header.status != Some(200) {
+            return None;
+        }
+        let data: Vec<u8> = chunks.iter().flat_map(|c| c.data.iter().copied()).collect();
+        serde_json::from_slice(&data).ok()
+    }
+}
+
+/// Everything we found out about a device via MIDI-CI.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MidiCiDevice {
+    pub muid: MidiCiMuid,
+    pub identity: MidiDeviceIdentity,
+    /// Enabled and disabled profiles.
+    pub profiles: Vec<MidiCiProfileId>,
+    pub device_info: Option<MidiCiDeviceInfo>,
+}
+
+/// Result of identifying a MIDI device via classic device inquiry and/or MIDI-CI discovery.
+#[derive(Debug)]
+pub struct MidiDeviceIdentification {
+    /// The input device on which the device replied.
+    pub input_device_id: MidiInputDeviceId,
+    /// Reply to the classic device inquiry, if the device answered it.
+    pub device_inquiry_reply: Option<MidiDeviceInquiryReply>,
+    /// Result of MIDI-CI discovery, if the device supports MIDI-CI.
+    pub midi_ci_device: Option<MidiCiDevice>,
+}
+
+impl MidiDeviceIdentification {
+    /// Returns the structured identity, preferring the one reported via MIDI-CI.
+    pub fn identity(&self) -> Option<MidiDeviceIdentity> {
+        if let Some(d) = &self.midi_ci_device {
+            return Some(d.identity);
+        }
+        let reply = self.device_inquiry_reply.as_ref()?;
+        MidiDeviceIdentity::from_device_inquiry_reply(&reply.message)
+    }
+
+    pub fn device_inquiry_reply_bytes(&self) -> Option<&[u8]> {
+        Some(self.device_inquiry_reply.as_ref()?.message.as_slice())
+    }
+
+    pub fn to_persistent_identity(&self) -> Option<PersistentMidiDeviceIdentity> {
+        let identity = self.identity()?;
+        let midi_ci_device = self.midi_ci_device.as_ref();
+        let device_info = midi_ci_device.and_then(|d| d.device_info.as_ref());
+        let persistent_identity = PersistentMidiDeviceIdentity {
+            manufacturer_id: format_midi_manufacturer_id(identity.manufacturer_id),
+            family: identity.family,
+            model: identity.model,
+            version: format_as_pretty_hex(&identity.version),
+            profiles: midi_ci_device
+                .map(|d| d.profiles.iter().map(|p| p.to_string()).collect())
+                .unwrap_or_default(),
+            manufacturer_name: device_info.and_then(|i| i.manufacturer.clone()),
+            model_name: device_info.and_then(|i| i.model.clone()),
+        };
+        Some(persistent_identity)
+    }
+}
+
+/// Builds a discovery message which asks all MIDI-CI devices to reply.
+pub fn build_midi_ci_discovery(source: MidiCiMuid) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(17);
+    payload.extend(NON_COMMERCIAL_MANUFACTURER_ID);
+    // Family, model and version
+    payload.extend([0x00; 8]);
+    payload.push(CATEGORY_PROFILE_CONFIGURATION | CATEGORY_PROPERTY_EXCHANGE);
+    payload.extend(encode_u28(RawMidiEvent::MAX_LENGTH as u32));
+    // Output path ID
+    payload.push(0x00);
+    build_midi_ci_message(
+        MidiCiMessageType::Discovery,
+        source,
+        MidiCiMuid::BROADCAST,
+        &payload,
+    )
+}
+
+pub fn build_midi_ci_profile_inquiry(source: MidiCiMuid, destination: MidiCiMuid) -> Vec<u8> {
+    build_midi_ci_message(MidiCiMessageType::ProfileInquiry, source, destination, &[])
+}
+
+pub fn build_midi_ci_property_exchange_capabilities_inquiry(
+    source: MidiCiMuid,
+    destination: MidiCiMuid,
+) -> Vec<u8> {
+    // 1 simultaneous request, property exchange version 0.0
+    build_midi_ci_message(
+        MidiCiMessageType::PropertyExchangeCapabilitiesInquiry,
+        source,
+        destination,
+        &[0x01, 0x00, 0x00],
+    )
+}
+
+/// Builds a single-chunk property exchange inquiry which gets the given resource.
+pub fn build_midi_ci_property_get_inquiry(
+    source: MidiCiMuid,
+    destination: MidiCiMuid,
+    request_id: u8,
+    resource: &str,
+) -> Vec<u8> {
+    let header = serde_json::json!({ "resource": resource }).to_string();
+    let mut payload = Vec::with_capacity(header.len() + 9);
+    payload.push(request_id & 0x7F);
+    payload.extend(encode_u14(header.len() as u16));
+    payload.extend(header.bytes());
+    // Number of chunks, this chunk, no data
+    payload.extend(encode_u14(1));
+    payload.extend(encode_u14(1));
+    payload.extend(encode_u14(0));
+    build_midi_ci_message(
+        MidiCiMessageType::PropertyGetInquiry,
+        source,
+        destination,
+        &payload,
+    )
+}
+
+/// Checks whether the given message is a MIDI-CI message of the given type addressed to the given
+/// MUID.
+///
+/// Doesn't allocate, so it can be used in real-time threads.
+pub fn is_midi_ci_message_addressed_to(
+    message: &[u8],
+    message_type: MidiCiMessageType,
+    destination: MidiCiMuid,
+) -> bool {
+    message.len() > 14
+        && message[0] == 0xF0
+        && message[1] == 0x7E
+        && message[3] == MIDI_CI_SUB_ID
+        && message[4] == message_type as u8
+        && message[10..14] == destination.to_bytes()
+}
+
+fn build_midi_ci_message(
+    message_type: MidiCiMessageType,
+    source: MidiCiMuid,
+    destination: MidiCiMuid,
+    payload: &[u8],
+) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(15 + payload.len());
+    bytes.extend([
+        0xF0,
+        0x7E,
+        FUNCTION_BLOCK,
+        MIDI_CI_SUB_ID,
+        message_type as u8,
+        MIDI_CI_VERSION,
+    ]);
+    bytes.extend(source.to_bytes());
+    bytes.extend(destination.to_bytes());
+    bytes.extend_from_slice(payload);
+    bytes.push(0xF7);
+    bytes
+}
+
+struct MidiCiMessage<'a> {
+    source: MidiCiMuid,
+    payload: &'a [u8],
+}
+
+fn parse_midi_ci_message(message: &[u8], message_type: MidiCiMessageType) -> Option<MidiCiMessage> {
+    let [0xF0, 0x7E, _, MIDI_CI_SUB_ID, actual_type, _version, rest @ .., 0xF7] = message else {
+        return None;
+    };
+    if *actual_type != message_type as u8 || rest.len() < 8 {
+        return None;
+    }
+    let msg = MidiCiMessage {
+        source: MidiCiMuid::from_bytes(&rest[0..4])?,
+        payload: &rest[8..],
+    };
+    Some(msg)
+}
+
+struct PayloadReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PayloadReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        if count > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u14(&mut self) -> Option<u16> {
+        let bytes = self.take(2)?;
+        Some((bytes[0] & 0x7F) as u16 | ((bytes[1] & 0x7F) as u16) << 7)
+    }
+
+    fn u28(&mut self) -> Option<u32> {
+        decode_u28(self.take(4)?)
+    }
+
+    fn profile_ids(&mut self) -> Option<Vec<MidiCiProfileId>> {
+        let count = self.u14()?;
+        (0..count)
+            .map(|_| Some(MidiCiProfileId(self.take(5)?.try_into().ok()?)))
+            .collect()
+    }
+}
+
+fn encode_u14(value: u16) -> [u8; 2] {
+    [(value & 0x7F) as u8, ((value >> 7) & 0x7F) as u8]
+}
+
+fn encode_u28(value: u32) -> [u8; 4] {
+    [
+        (value & 0x7F) as u8,
+        ((value >> 7) & 0x7F) as u8,
+        ((value >> 14) & 0x7F) as u8,
+        ((value >> 21) & 0x7F) as u8,
+    ]
+}
+
+fn decode_u28(bytes: &[u8]) -> Option<u32> {
+    let bytes: [u8; 4] = bytes.try_into().ok()?;
+    let value = bytes
+        .iter()
+        .rev()
+        .fold(0u32, |value, b| (value << 7) | (*b & 0x7F) as u32);
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_identity_reply() {
+        // Given
+        let reply = [
+            0xF0, 0x7E, 0x00, 0x06, 0x02, 0x47, 0x4E, 0x00, 0x19, 0x00, 0x01, 0x02, 0x03, 0x04,
+            0xF7,
+        ];
+        // When
+        let identity = MidiDeviceIdentity::from_device_inquiry_reply(&reply).unwrap();
+        // Then
+        assert_eq!(identity.manufacturer_id, [0x47, 0x00, 0x00]);
+        assert_eq!(identity.family, 0x4E);
+        assert_eq!(identity.model, 0x19);
+        assert_eq!(identity.version, [0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn discovery_reply() {
+        // Given
+        let device_muid = MidiCiMuid(0x0123_4567);
+        let our_muid = MidiCiMuid(0x0765_4321);
+        // Manufacturer, family, model, version, categories, max SysEx size
+        let payload = [
+            &[0x00, 0x20, 0x29][..],
+            &[0x03, 0x01],
+            &[0x10, 0x00],
+            &[0x01, 0x00, 0x00, 0x00],
+            &[0x0C],
+            &[0x00, 0x02, 0x00, 0x00],
+        ]
+        .concat();
+        let message = build_midi_ci_message(
+            MidiCiMessageType::DiscoveryReply,
+            device_muid,
+            our_muid,
+            &payload,
+        );
+        // When
+        let reply = MidiCiDiscoveryReply::parse(&message).unwrap();
+        // Then
+        assert!(is_midi_ci_message_addressed_to(
+            &message,
+            MidiCiMessageType::DiscoveryReply,
+            our_muid
+        ));
+        assert_eq!(reply.muid, device_muid);
+        assert_eq!(reply.identity.manufacturer_id, [0x00, 0x20, 0x29]);
+        assert_eq!(reply.identity.family, 0x83);
+        assert_eq!(reply.identity.model, 0x10);
+        assert!(reply.supports_profile_configuration());
+        assert!(reply.supports_property_exchange());
+        assert_eq!(reply.max_sysex_size, 256);
+    }
+
+    #[test]
+    fn profile_inquiry_reply() {
+        // Given
+        // One enabled profile, no disabled profiles
+        let payload = [0x01, 0x00, 0x7E, 0x00, 0x01, 0x02, 0x03, 0x00, 0x00];
+        let message = build_midi_ci_message(
+            MidiCiMessageType::ProfileInquiryReply,
+            MidiCiMuid(1),
+            MidiCiMuid(2),
+            &payload,
+        );
+        // When
+        let reply = MidiCiProfileInquiryReply::parse(&message).unwrap();
+        // Then
+        assert_eq!(
+            reply.enabled,
+            vec![MidiCiProfileId([0x7E, 0x00, 0x01, 0x02, 0x03])]
+        );
+        assert!(reply.disabled.is_empty());
+        assert_eq!(reply.enabled[0].to_string(), "7E 00 01 02 03");
+    }
+
+    #[test]
+    fn manufacturer_ids() {
+        assert_eq!(parse_midi_manufacturer_id("47"), Some([0x47, 0x00, 0x00]));
+        assert_eq!(
+            parse_midi_manufacturer_id("00 20 29"),
+            Some([0x00, 0x20, 0x29])
+        );
+        assert_eq!(parse_midi_manufacturer_id("00 20"), None);
+        assert_eq!(format_midi_manufacturer_id([0x47, 0x00, 0x00]), "47");
+        assert_eq!(format_midi_manufacturer_id([0x00, 0x20, 0x29]), "00 20 29");
+    }
+}
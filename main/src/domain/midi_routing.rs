@@ -0,0 +1,425 @@
+use anyhow::{ensure, Context};
+use helgoboss_midi::{RawShortMessage, ShortMessage, ShortMessageType, U7};
+use serde::{Deserialize, Serialize};
+
+/// Per-unit routing table which is applied to MIDI events that are let through to the FX output.
+///
+/// Rules are checked in order, the first matching rule wins. Events not matched by any rule pass
+/// unchanged. Applying the table doesn't allocate, so it's safe to use in the real-time thread.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct MidiRoutingTable {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<MidiRoutingRule>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MidiRoutingRule {
+    // Conditions
+    /// Message types this rule applies to. Empty means all types.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub message_types: Vec<MidiRoutingMessageType>,
+    /// Zero-based channel. `None` means any channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+    /// Lowest note, controller or program number (inclusive).
+    ///
+    /// Messages without such a number (e.g. pitch bend) never match a rule with a number range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_number: Option<u8>,
+    /// Highest note, controller or program number (inclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_number: Option<u8>,
+    // Actions
+    /// Swallows matched events.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub drop: bool,
+    /// Zero-based channel to which matched channel messages are sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_channel: Option<u8>,
+    /// Replaces the note, controller or program number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_number: Option<u8>,
+    /// Added to the note, controller or program number (after `target_number` has been applied).
+    ///
+    /// Events whose resulting number falls outside of 0 - 127 are dropped.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub transpose: i8,
+    /// Velocity curve for note-on events, expressed as exponent (1.0 = linear, > 1.0 = softer,
+    /// < 1.0 = harder).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity_curve: Option<f64>,
+    /// Lowest velocity of note-on events after the curve has been applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_velocity: Option<u8>,
+    /// Highest velocity of note-on events after the curve has been applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_velocity: Option<u8>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MidiRoutingMessageType {
+    NoteOn,
+    NoteOff,
+    PolyphonicKeyPressure,
+    ControlChange,
+    ProgramChange,
+    ChannelPressure,
+    PitchBend,
+    /// System common and system real-time messages.
+    System,
+    SysEx,
+}
+
+impl MidiRoutingTable {
+    /// Checks that all channels and numbers are within the MIDI value ranges.
+    ///
+    /// Rules with values outside of these ranges would otherwise wrap around or drop events
+    /// silently.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            rule.validate()
+                .with_context(|| format!("Rule {} is invalid", i + 1))?;
+        }
+        Ok(())
+    }
+
+    /// Routes the given short message.
+    ///
+    /// Returns `None` if the message should be swallowed.
+    pub fn route(&self, msg: RawShortMessage) -> Option<RawShortMessage> {
+        let Some(msg_type) = MidiRoutingMessageType::from_short_message(&msg) else {
+            return Some(msg);
+        };
+        match self.rules.iter().find(|r| r.matches_short(msg_type, &msg)) {
+            None => Some(msg),
+            Some(rule) => rule.apply(msg_type, msg),
+        }
+    }
+
+    /// Returns whether a system-exclusive message should be passed through.
+    pub fn lets_sys_ex_through(&self) -> bool {
+        match self.rules.iter().find(|r| r.matches_sys_ex()) {
+            None => true,
+            Some(rule) => !rule.drop,
+        }
+    }
+}
+
+impl MidiRoutingRule {
+    fn validate(&self) -> anyhow::Result<()> {
+        let channels = [
+            ("channel", self.channel),
+            ("targetChannel", self.target_channel),
+        ];
+        for (name, value) in channels {
+            if let Some(v) = value {
+                ensure!(v <= 15, "{name} must be between 0 and 15 but is {v}");
+            }
+        }
+        let numbers = [
+            ("minNumber", self.min_number),
+            ("maxNumber", self.max_number),
+            ("targetNumber", self.target_number),
+            ("minVelocity", self.min_velocity),
+            ("maxVelocity", self.max_velocity),
+        ];
+        for (name, value) in numbers {
+            if let Some(v) = value {
+                ensure!(v <= 127, "{name} must be between 0 and 127 but is {v}");
+            }
+        }
+        Ok(())
+    }
+
+    fn matches_type(&self, msg_type: MidiRoutingMessageType) -> bool {
+        self.message_types.is_empty() || self.message_types.contains(&msg_type)
+    }
+
+    fn has_number_range(&self) -> bool {
+        self.min_number.is_some() || self.max_number.is_some()
+    }
+
+    fn matches_short(&self, msg_type: MidiRoutingMessageType, msg: &RawShortMessage) -> bool {
+        if !self.matches_type(msg_type) {
+            return false;
+        }
+        if let Some(channel) = self.channel {
+            match msg.channel() {
+                Some(ch) if ch.get() == channel => {}
+                _ => return false,
+            }
+        }
+        if self.has_number_range() {
+            let Some(number) = msg_type.number(msg) else {
+                return false;
+            };
+            let number = number.get();
+            if self.min_number.is_some_and(|min| number < min)
+                || self.max_number.is_some_and(|max| number > max)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn matches_sys_ex(&self) -> bool {
+        self.matches_type(MidiRoutingMessageType::SysEx)
+            && self.channel.is_none()
+            && !self.has_number_range()
+    }
+
+    fn apply(
+        &self,
+        msg_type: MidiRoutingMessageType,
+        msg: RawShortMessage,
+    ) -> Option<RawShortMessage> {
+        if self.drop {
+            return None;
+        }
+        let (status_byte, mut data_byte_1, mut data_byte_2) = msg.to_bytes();
+        let status_byte = match self.target_channel {
+            Some(ch) if msg_type.is_channel_message() => (status_byte & 0xf0) | (ch & 0x0f),
+            _ => status_byte,
+        };
+        if msg_type.number(&msg).is_some() {
+            let number = self.target_number.unwrap_or(data_byte_1.get()) as i16;
+            let number = number + self.transpose as i16;
+            data_byte_1 = U7::try_from(u8::try_from(number).ok()?).ok()?;
+        }
+        if msg_type == MidiRoutingMessageType::NoteOn && data_byte_2.get() > 0 {
+            data_byte_2 = self.map_velocity(data_byte_2);
+        }
+        RawShortMessage::from_bytes((status_byte, data_byte_1, data_byte_2)).ok()
+    }
+
+    fn map_velocity(&self, velocity: U7) -> U7 {
+        if self.velocity_curve.is_none()
+            && self.min_velocity.is_none()
+            && self.max_velocity.is_none()
+        {
+            return velocity;
+        }
+        let min = self.min_velocity.unwrap_or(1).clamp(1, 127) as f64;
+        let max = self.max_velocity.unwrap_or(127).clamp(1, 127) as f64;
+        let normalized = (velocity.get() - 1) as f64 / 126.0;
+        let curved = match self.velocity_curve {
+            Some(exponent) if exponent > 0.0 => normalized.powf(exponent),
+            _ => normalized,
+        };
+        let mapped = (min + curved * (max - min)).round();
+        // Velocity 0 would turn a note-on into a note-off.
+        U7::new(mapped.clamp(1.0, 127.0) as u8)
+    }
+}
+
+impl MidiRoutingMessageType {
+    fn from_short_message(msg: &RawShortMessage) -> Option<Self> {
+        use ShortMessageType::*;
+        let t = match msg.r#type() {
+            NoteOn => Self::NoteOn,
+            NoteOff => Self::NoteOff,
+            PolyphonicKeyPressure => Self::PolyphonicKeyPressure,
+            ControlChange => Self::ControlChange,
+            ProgramChange => Self::ProgramChange,
+            ChannelPressure => Self::ChannelPressure,
+            PitchBendChange => Self::PitchBend,
+            SystemExclusiveStart => return None,
+            _ => Self::System,
+        };
+        Some(t)
+    }
+
+    fn is_channel_message(self) -> bool {
+        !matches!(self, Self::System | Self::SysEx)
+    }
+
+    /// Returns the note, controller or program number of the given message, if it has one.
+    fn number(self, msg: &RawShortMessage) -> Option<U7> {
+        use MidiRoutingMessageType::*;
+        match self {
+            NoteOn | NoteOff | PolyphonicKeyPressure | ControlChange | ProgramChange => {
+                Some(msg.data_byte_1())
+            }
+            ChannelPressure | PitchBend | System | SysEx => None,
+        }
+    }
+}
+
+fn is_false(v: &bool) -> bool {
+    !*v
+}
+
+fn is_zero(v: &i8) -> bool {
+    *v == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_midi::test_util::*;
+
+    fn rule() -> MidiRoutingRule {
+        MidiRoutingRule {
+            message_types: vec![],
+            channel: None,
+            min_number: None,
+            max_number: None,
+            drop: false,
+            target_channel: None,
+            target_number: None,
+            transpose: 0,
+            velocity_curve: None,
+            min_velocity: None,
+            max_velocity: None,
+        }
+    }
+
+    #[test]
+    fn empty_table_passes_everything() {
+        // Given
+        let table = MidiRoutingTable::default();
+        // When
+        // Then
+        assert_eq!(table.route(note_on(0, 60, 100)), Some(note_on(0, 60, 100)));
+        assert!(table.lets_sys_ex_through());
+    }
+
+    #[test]
+    fn split_zones() {
+        // Given
+        let table = MidiRoutingTable {
+            rules: vec![
+                MidiRoutingRule {
+                    message_types: vec![
+                        MidiRoutingMessageType::NoteOn,
+                        MidiRoutingMessageType::NoteOff,
+                    ],
+                    max_number: Some(59),
+                    target_channel: Some(1),
+                    ..rule()
+                },
+                MidiRoutingRule {
+                    message_types: vec![
+                        MidiRoutingMessageType::NoteOn,
+                        MidiRoutingMessageType::NoteOff,
+                    ],
+                    min_number: Some(60),
+                    target_channel: Some(2),
+                    transpose: 12,
+                    ..rule()
+                },
+            ],
+        };
+        // When
+        // Then
+        assert_eq!(table.route(note_on(0, 40, 100)), Some(note_on(1, 40, 100)));
+        assert_eq!(table.route(note_off(0, 40, 0)), Some(note_off(1, 40, 0)));
+        assert_eq!(table.route(note_on(0, 60, 100)), Some(note_on(2, 72, 100)));
+        assert_eq!(table.route(note_on(0, 120, 100)), None);
+        assert_eq!(
+            table.route(control_change(0, 7, 100)),
+            Some(control_change(0, 7, 100))
+        );
+    }
+
+    #[test]
+    fn remap_cc_and_filter() {
+        // Given
+        let table = MidiRoutingTable {
+            rules: vec![
+                MidiRoutingRule {
+                    message_types: vec![MidiRoutingMessageType::ControlChange],
+                    channel: Some(0),
+                    min_number: Some(1),
+                    max_number: Some(1),
+                    target_number: Some(11),
+                    ..rule()
+                },
+                MidiRoutingRule {
+                    message_types: vec![
+                        MidiRoutingMessageType::PitchBend,
+                        MidiRoutingMessageType::SysEx,
+                    ],
+                    drop: true,
+                    ..rule()
+                },
+            ],
+        };
+        // When
+        // Then
+        assert_eq!(
+            table.route(control_change(0, 1, 64)),
+            Some(control_change(0, 11, 64))
+        );
+        assert_eq!(
+            table.route(control_change(1, 1, 64)),
+            Some(control_change(1, 1, 64))
+        );
+        assert_eq!(table.route(pitch_bend_change(0, 8192)), None);
+        assert!(!table.lets_sys_ex_through());
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_values() {
+        // Given
+        let valid = MidiRoutingTable {
+            rules: vec![MidiRoutingRule {
+                channel: Some(15),
+                target_channel: Some(0),
+                min_number: Some(0),
+                max_number: Some(127),
+                target_number: Some(127),
+                ..rule()
+            }],
+        };
+        let invalid_channel = MidiRoutingTable {
+            rules: vec![MidiRoutingRule {
+                target_channel: Some(16),
+                ..rule()
+            }],
+        };
+        let invalid_number = MidiRoutingTable {
+            rules: vec![
+                rule(),
+                MidiRoutingRule {
+                    target_number: Some(128),
+                    ..rule()
+                },
+            ],
+        };
+        // When
+        // Then
+        assert!(valid.validate().is_ok());
+        assert!(invalid_channel.validate().is_err());
+        let error = invalid_number.validate().unwrap_err();
+        assert_eq!(error.to_string(), "Rule 2 is invalid");
+        assert_eq!(
+            error.root_cause().to_string(),
+            "targetNumber must be between 0 and 127 but is 128"
+        );
+    }
+
+    #[test]
+    fn velocity_curve() {
+        // Given
+        let table = MidiRoutingTable {
+            rules: vec![MidiRoutingRule {
+                velocity_curve: Some(2.0),
+                min_velocity: Some(20),
+                max_velocity: Some(100),
+                ..rule()
+            }],
+        };
+        // When
+        // Then
+        assert_eq!(table.route(note_on(0, 60, 1)), Some(note_on(0, 60, 20)));
+        assert_eq!(table.route(note_on(0, 60, 64)), Some(note_on(0, 60, 40)));
+        assert_eq!(table.route(note_on(0, 60, 127)), Some(note_on(0, 60, 100)));
+        // Note-on with velocity 0 is a note-off and must stay one
+        assert_eq!(table.route(note_on(0, 60, 0)), Some(note_on(0, 60, 0)));
+        assert_eq!(table.route(note_off(0, 60, 64)), Some(note_off(0, 60, 64)));
+    }
+}
@@ -1,4 +1,5 @@
-use reaper_medium::{Hz, MIDI_INPUT_FRAME_RATE};
+use reaper_medium::{Hz, MidiFrameOffset, MIDI_INPUT_FRAME_RATE};
+use std::time::Duration;
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct MidiEvent<T> {
@@ -43,4 +44,19 @@ impl SampleOffset {
     pub fn get(self) -> u64 {
         self.0
     }
+
+    pub fn to_duration(self, sample_rate: Hz) -> Duration {
+        if self.0 == 0 {
+            return Duration::ZERO;
+        }
+        // Sample rate might not be known yet
+        Duration::try_from_secs_f64(self.0 as f64 / sample_rate.get()).unwrap_or_default()
+    }
+}
+
+/// Converts a position within the current audio block to the frame offset unit used when sending
+/// MIDI to output devices (1/1024000 of a second, *not* sample frames).
+pub fn convert_block_offset_to_midi_frame_offset(block_offset: Duration) -> MidiFrameOffset {
+    let frame_offset = (block_offset.as_secs_f64() * MIDI_INPUT_FRAME_RATE.get()).round();
+    MidiFrameOffset::new(frame_offset as u32)
 }
@@ -40,9 +40,15 @@ pub use lua_midi_source_script::*;
 mod lua_feedback_script;
 pub use lua_feedback_script::*;
 
+mod lua_lifecycle_script;
+pub use lua_lifecycle_script::*;
+
 mod flexible_midi_source_script;
 pub use flexible_midi_source_script::*;
 
+mod sysex_display_midi_source_script;
+pub use sysex_display_midi_source_script::*;
+
 mod realearn_target;
 pub use realearn_target::*;
 
@@ -61,6 +67,9 @@ pub use r#virtual::*;
 mod midi_util;
 pub use midi_util::*;
 
+mod midi_ci;
+pub use midi_ci::*;
+
 mod midi_source_scanner;
 pub use midi_source_scanner::*;
 
@@ -102,6 +111,9 @@ pub use targets::*;
 mod group;
 pub use group::*;
 
+mod bank;
+pub use bank::*;
+
 mod midi_types;
 pub use midi_types::*;
 
@@ -111,6 +123,30 @@ pub use reaper_source::*;
 mod key_source;
 pub use key_source::*;
 
+mod lfo_source;
+pub use lfo_source::*;
+
+mod sequencer_source;
+pub use sequencer_source::*;
+
+mod compound_source;
+pub use compound_source::*;
+
+mod audio_source;
+pub use audio_source::*;
+
+mod midi_routing;
+pub use midi_routing::*;
+
+mod feedback_policy;
+pub use feedback_policy::*;
+
+mod lifecycle_state;
+pub use lifecycle_state::*;
+
+mod feedback_value_range_table;
+pub use feedback_value_range_table::*;
+
 mod device_change_detector;
 pub use device_change_detector::*;
 
@@ -167,3 +203,14 @@ mod playtime_util;
 
 mod hex;
 pub use hex::*;
+
+mod web_socket_device;
+pub use web_socket_device::*;
+
+mod control_input_recording;
+pub use control_input_recording::*;
+
+#[cfg(test)]
+mod headless_unit;
+#[cfg(test)]
+pub use headless_unit::*;
@@ -1,7 +1,7 @@
 use crate::domain::{
     convert_reaper_color_to_helgoboss_learn, get_fx_name, get_track_name, Backbone,
     CompoundChangeEvent, CompoundMappingTarget, ControlContext, FeedbackResolution, MainMapping,
-    RealearnTarget, ReaperTarget, UnresolvedCompoundMappingTarget,
+    RealearnTarget, ReaperTarget, UnitEvent, UnresolvedCompoundMappingTarget,
 };
 use enum_dispatch::enum_dispatch;
 use helgoboss_learn::{AbsoluteValue, NumericValue, PropProvider, PropValue, Target};
@@ -211,6 +211,10 @@ enum GlobalProps {
 enum MappingProps {
     #[strum(serialize = "mapping.name")]
     Name(MappingNameProp),
+    #[strum(serialize = "mapping.bank.position")]
+    BankPosition(MappingBankPositionProp),
+    #[strum(serialize = "mapping.bank.name")]
+    BankName(MappingBankNameProp),
 }
 
 #[enum_dispatch]
@@ -375,6 +379,52 @@ impl MappingProp for MappingNameProp {
     }
 }
 
+/// Whether the current bank of the compartment of the given mapping has changed.
+fn current_bank_changed(args: &PropIsAffectedByArgs<&MainMapping>) -> bool {
+    matches!(
+        args.event,
+        CompoundChangeEvent::Unit(UnitEvent::CurrentBankChanged { compartment })
+            if *compartment == args.object.compartment()
+    )
+}
+
+#[derive(Default)]
+struct MappingBankPositionProp;
+
+impl MappingProp for MappingBankPositionProp {
+    fn is_affected_by(&self, args: PropIsAffectedByArgs<&MainMapping>) -> bool {
+        current_bank_changed(&args)
+    }
+
+    fn get_value(&self, input: PropGetValueArgs<&MainMapping>) -> Option<PropValue> {
+        let unit = input.control_context.unit.borrow();
+        let compartment = input.object.compartment();
+        if unit.banks(compartment).is_empty() {
+            return None;
+        }
+        let position = unit.current_bank_index(compartment) + 1;
+        Some(PropValue::Numeric(NumericValue::Discrete(position as i32)))
+    }
+}
+
+#[derive(Default)]
+struct MappingBankNameProp;
+
+impl MappingProp for MappingBankNameProp {
+    fn is_affected_by(&self, args: PropIsAffectedByArgs<&MainMapping>) -> bool {
+        current_bank_changed(&args)
+    }
+
+    fn get_value(&self, input: PropGetValueArgs<&MainMapping>) -> Option<PropValue> {
+        let unit = input.control_context.unit.borrow();
+        let compartment = input.object.compartment();
+        let name = unit
+            .banks(compartment)
+            .name(unit.current_bank_index(compartment))?;
+        Some(PropValue::Text(name.to_owned().into()))
+    }
+}
+
 #[derive(Default)]
 struct TargetTextValueProp;
 
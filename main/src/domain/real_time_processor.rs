@@ -1,13 +1,14 @@
 use crate::domain::{
-    classify_midi_message, BasicSettings, CompartmentKind, CompoundMappingSource, ControlEvent,
-    ControlEventTimestamp, ControlLogEntry, ControlLogEntryKind, ControlMainTask, ControlMode,
-    ControlOptions, FeedbackSendBehavior, LifecycleMidiMessage, LifecyclePhase, MappingCore,
-    MappingId, MatchOutcome, MidiClockCalculator, MidiEvent, MidiMessageClassification,
-    MidiScanResult, MidiScanner, MidiSendTarget, NormalRealTimeToMainThreadTask, OrderedMappingMap,
-    OwnedIncomingMidiMessage, PartialControlMatch, PersistentMappingProcessingState,
-    QualifiedMappingId, RealTimeCompoundMappingTarget, RealTimeControlContext, RealTimeMapping,
-    RealTimeReaperTarget, SampleOffset, SendMidiDestination, UnitId, VirtualSourceValue,
-    WeakRealTimeInstance,
+    classify_midi_message, convert_block_offset_to_midi_frame_offset, AdditionalIo, AudioInput,
+    BasicSettings, CompartmentKind, ControlEvent, ControlEventTimestamp, ControlInput,
+    ControlLogEntry, ControlLogEntryKind, ControlMainTask, ControlMode, ControlOptions,
+    DeviceControlInput, FeedbackOutput, FeedbackSendBehavior, LifecycleMidiMessage, LifecyclePhase,
+    MappingCore, MappingId, MatchOutcome, MidiClockCalculator, MidiEvent,
+    MidiMessageClassification, MidiRoutingTable, MidiScanResult, MidiScanner, MidiSendTarget,
+    NormalRealTimeToMainThreadTask, OrderedMappingMap, OwnedIncomingMidiMessage,
+    PartialControlMatch, PersistentMappingProcessingState, QualifiedMappingId,
+    RealTimeCompoundMappingTarget, RealTimeControlContext, RealTimeMapping, RealTimeReaperTarget,
+    SampleOffset, SendMidiDestination, UnitId, VirtualSourceValue, WeakRealTimeInstance,
 };
 use helgoboss_learn::{ControlValue, MidiSourceValue, ModeControlResult, RawMidiEvent};
 use helgoboss_midi::{
@@ -39,11 +40,16 @@ pub struct RealTimeProcessor {
     unit_id: UnitId,
     // Synced processing settings
     settings: BasicSettings,
+    midi_routing: MidiRoutingTable,
+    additional_io: AdditionalIo,
     control_mode: ControlMode,
     mappings: EnumMap<CompartmentKind, OrderedMappingMap<RealTimeMapping>>,
     // State
     control_is_globally_enabled: bool,
     feedback_is_globally_enabled: bool,
+    control_input_recording_enabled: bool,
+    /// Input from which the currently processed MIDI event originates (if known).
+    current_control_input: Option<ControlInput>,
     // Inter-thread communication
     normal_task_receiver: crossbeam_channel::Receiver<NormalRealTimeTask>,
     feedback_task_receiver: crossbeam_channel::Receiver<FeedbackRealTimeTask>,
@@ -52,6 +58,9 @@ pub struct RealTimeProcessor {
     control_main_task_sender: SenderToNormalThread<ControlMainTask>,
     // Scanners for more complex MIDI message types
     nrpn_scanner: PollingParameterNumberMessageScanner,
+    /// Offset and timestamp of the latest CC message per channel which was fed into the (N)RPN
+    /// scanner. Used for (N)RPN messages which are detected by polling.
+    latest_nrpn_part_events: [Option<ControlEvent<SampleOffset>>; 16],
     cc_14_bit_scanner: ControlChange14BitMessageScanner,
     // For MIDI capturing
     midi_scanner: MidiScanner,
@@ -77,6 +86,8 @@ impl RealTimeProcessor {
             unit_id,
             instance,
             settings: Default::default(),
+            midi_routing: Default::default(),
+            additional_io: Default::default(),
             control_mode: ControlMode::Controlling,
             normal_task_receiver,
             feedback_task_receiver,
@@ -88,11 +99,14 @@ impl RealTimeProcessor {
                 Main => ordered_map_with_capacity(5000),
             },
             nrpn_scanner: PollingParameterNumberMessageScanner::new(Duration::from_millis(1)),
+            latest_nrpn_part_events: [None; 16],
             cc_14_bit_scanner: Default::default(),
             midi_scanner: Default::default(),
             midi_clock_calculator: Default::default(),
             control_is_globally_enabled: false,
             feedback_is_globally_enabled: false,
+            control_input_recording_enabled: false,
+            current_control_input: None,
             sample_rate: Hz::new_panic(1.0),
         }
     }
@@ -118,11 +132,14 @@ impl RealTimeProcessor {
                 self.process_unmatched(event.payload(), Caller::Vst(host));
                 return;
             }
+            self.record_incoming_midi(None, event);
+            self.current_control_input = Some(ControlInput::Midi(MidiControlInput::FxInput));
             self.process_incoming_midi(event, Caller::Vst(host));
+            self.current_control_input = None;
         } else {
             // #33, #290 If MIDI input device is not set to <FX input>, we want to pass through all
             // messages that arrive on FX input.
-            self.send_incoming_midi_to_fx_output(event.payload(), Caller::Vst(host))
+            self.pass_midi_through_to_fx_output(event.payload(), Caller::Vst(host))
         }
     }
 
@@ -145,6 +162,26 @@ impl RealTimeProcessor {
         self.settings.midi_control_input()
     }
 
+    /// MIDI input devices which this unit listens to in addition to its main control input.
+    pub fn additional_midi_input_devices(&self) -> impl Iterator<Item = MidiInputDeviceId> + '_ {
+        self.additional_io
+            .control_inputs
+            .iter()
+            .filter_map(|input| match input {
+                DeviceControlInput::Midi(dev_id) => Some(*dev_id),
+                DeviceControlInput::Osc(_) => None,
+            })
+    }
+
+    /// Returns whether this unit wants to process MIDI events from the given input device.
+    pub fn listens_to_midi_device(&self, dev_id: MidiInputDeviceId) -> bool {
+        self.settings.midi_control_input() == MidiControlInput::Device(dev_id)
+            || self
+                .additional_io
+                .control_inputs
+                .contains(&DeviceControlInput::Midi(dev_id))
+    }
+
     pub fn control_is_globally_enabled(&self) -> bool {
         self.control_is_globally_enabled
     }
@@ -156,14 +193,55 @@ impl RealTimeProcessor {
     pub fn process_incoming_midi_from_audio_hook(
         &mut self,
         event: ControlEvent<MidiEvent<IncomingMidiMessage>>,
+        dev_id: MidiInputDeviceId,
     ) -> bool {
+        self.record_incoming_midi(Some(dev_id), event);
+        self.current_control_input = Some(ControlInput::Midi(MidiControlInput::Device(dev_id)));
         let match_outcome = self.process_incoming_midi(event, Caller::AudioHook);
+        self.current_control_input = None;
         let let_through = (match_outcome.matched_or_consumed()
             && self.settings.let_matched_events_through)
             || (!match_outcome.matched_or_consumed() && self.settings.let_unmatched_events_through);
         !let_through
     }
 
+    /// This should be called by audio hook when replaying recorded control input.
+    ///
+    /// In contrast to [`Self::process_incoming_midi_from_audio_hook`], this doesn't record the
+    /// message again.
+    ///
+    /// A device of `None` means that the message was recorded at the FX input.
+    pub fn process_replayed_midi_from_audio_hook(
+        &mut self,
+        event: ControlEvent<MidiEvent<IncomingMidiMessage>>,
+        device: Option<MidiInputDeviceId>,
+    ) {
+        let midi_control_input = match device {
+            None => MidiControlInput::FxInput,
+            Some(dev_id) => MidiControlInput::Device(dev_id),
+        };
+        self.current_control_input = Some(ControlInput::Midi(midi_control_input));
+        self.process_incoming_midi(event, Caller::AudioHook);
+        self.current_control_input = None;
+    }
+
+    fn record_incoming_midi(
+        &self,
+        device: Option<MidiInputDeviceId>,
+        event: ControlEvent<MidiEvent<IncomingMidiMessage>>,
+    ) {
+        if !self.control_input_recording_enabled {
+            return;
+        }
+        // Allocates in case of sys-ex, but recording is a diagnostic feature, so that's okay.
+        let msg = permit_alloc(|| event.payload().payload().to_owned());
+        self.normal_main_task_sender
+            .send_complaining(NormalRealTimeToMainThreadTask::RecordMidi {
+                device,
+                event: event.with_payload(msg),
+            });
+    }
+
     fn request_full_sync_and_discard_tasks_if_successful(&mut self) {
         if self
             .normal_main_task_sender
@@ -211,6 +289,9 @@ impl RealTimeProcessor {
                 UpdateControlIsGloballyEnabled(is_enabled) => {
                     self.control_is_globally_enabled = is_enabled;
                 }
+                UpdateControlInputRecordingEnabled(is_enabled) => {
+                    self.control_input_recording_enabled = is_enabled;
+                }
                 UpdateFeedbackIsGloballyEnabled(is_enabled) => {
                     // Handle lifecycle MIDI
                     if self.settings.midi_destination().is_some()
@@ -333,6 +414,35 @@ impl RealTimeProcessor {
                         self.send_lifecycle_midi_for_all_mappings(LifecyclePhase::Activation);
                     }
                 }
+                UpdateMidiRouting(midi_routing) => {
+                    permit_alloc(|| {
+                        debug!("Updating MIDI routing...");
+                    });
+                    // The previous table is deallocated outside of the real-time thread.
+                    self.midi_routing = midi_routing;
+                }
+                UpdateAdditionalIo(additional_io) => {
+                    permit_alloc(|| {
+                        debug!("Updating additional inputs and outputs...");
+                    });
+                    // Handle deactivation of removed outputs
+                    if self.feedback_is_globally_enabled {
+                        self.send_lifecycle_midi_to_outputs_missing_in(
+                            &self.additional_io,
+                            &additional_io,
+                            LifecyclePhase::Deactivation,
+                        );
+                    }
+                    let previous_io = std::mem::replace(&mut self.additional_io, additional_io);
+                    // Handle activation of added outputs
+                    if self.feedback_is_globally_enabled {
+                        self.send_lifecycle_midi_to_outputs_missing_in(
+                            &self.additional_io,
+                            &previous_io,
+                            LifecyclePhase::Activation,
+                        );
+                    }
+                }
                 UpdateSampleRate(sample_rate) => {
                     permit_alloc(|| {
                         debug!("Updating sample rate");
@@ -417,7 +527,37 @@ impl RealTimeProcessor {
     }
 
     fn processor_feedback_is_effectively_on(&self) -> bool {
-        self.feedback_is_globally_enabled && self.settings.midi_destination().is_some()
+        self.feedback_is_globally_enabled && self.lifecycle_midi_destinations().next().is_some()
+    }
+
+    /// The main MIDI feedback destination and all additional MIDI feedback devices.
+    fn lifecycle_midi_destinations(&self) -> impl Iterator<Item = MidiDestination> + '_ {
+        self.additional_io
+            .midi_destinations(self.settings.feedback_output)
+    }
+
+    /// Sends lifecycle MIDI of all mappings to those MIDI destinations of `io` which are not
+    /// destinations of `other_io`.
+    fn send_lifecycle_midi_to_outputs_missing_in(
+        &self,
+        io: &AdditionalIo,
+        other_io: &AdditionalIo,
+        phase: LifecyclePhase,
+    ) {
+        let main_output = self.settings.feedback_output;
+        for destination in io.midi_destinations(main_output) {
+            if other_io
+                .midi_destinations(main_output)
+                .any(|d| d == destination)
+            {
+                continue;
+            }
+            for m in self.all_mappings() {
+                if m.feedback_is_effectively_on() {
+                    self.send_lifecycle_midi_to_destination_from_audio_hook(destination, m, phase);
+                }
+            }
+        }
     }
 
     fn send_lifecycle_midi_for_all_mappings(&self, phase: LifecyclePhase) {
@@ -449,10 +589,17 @@ impl RealTimeProcessor {
                     // Poll (N)RPN scanner
                     for ch in 0..16 {
                         if let Some(nrpn_msg) = self.nrpn_scanner.poll(Channel::new(ch)) {
-                            // TODO-medium We should memorize the offset of the latest short message
-                            //  making up the NRPN message instead!
-                            let midi_event = MidiEvent::without_offset(nrpn_msg);
-                            let control_event = ControlEvent::new(midi_event, timestamp);
+                            // Use the offset and timestamp of the latest short message making up
+                            // the (N)RPN message, not the ones of the current block.
+                            let control_event = match self.latest_nrpn_part_events[ch as usize] {
+                                Some(evt) => {
+                                    evt.with_payload(MidiEvent::new(evt.payload(), nrpn_msg))
+                                }
+                                None => ControlEvent::new(
+                                    MidiEvent::without_offset(nrpn_msg),
+                                    timestamp,
+                                ),
+                            };
                             self.process_incoming_midi_normal_nrpn(
                                 control_event,
                                 Caller::AudioHook,
@@ -494,8 +641,8 @@ impl RealTimeProcessor {
                         );
                     }
                 }
-                NonAllocatingFxOutputFeedback(evt) => {
-                    send_raw_midi_to_fx_output(evt.bytes(), SampleOffset::ZERO, caller);
+                NonAllocatingFxOutputFeedback(evt, offset) => {
+                    send_raw_midi_to_fx_output(evt.bytes(), offset, caller);
                 }
             }
         }
@@ -627,6 +774,12 @@ impl RealTimeProcessor {
                     let midi_event = event.payload();
                     let (nrpn_match_outcome, cc14_match_outcome) = match midi_event.payload() {
                         IncomingMidiMessage::Short(short_msg) => {
+                            if short_msg.r#type() == ShortMessageType::ControlChange {
+                                if let Some(ch) = short_msg.channel() {
+                                    self.latest_nrpn_part_events[ch.get() as usize] =
+                                        Some(event.with_payload(midi_event.offset()));
+                                }
+                            }
                             let mut nrpn_match_outcome = MatchOutcome::Unmatched;
                             for nrpn_msg in self.nrpn_scanner.feed(&short_msg).iter().flatten() {
                                 let nrpn_event = event
@@ -716,7 +869,7 @@ impl RealTimeProcessor {
                 .iter()
                 .flatten()
             {
-                self.send_short_midi_to_fx_output(
+                self.pass_short_midi_through_to_fx_output(
                     MidiEvent::new(event.payload().offset(), *m),
                     caller,
                 );
@@ -815,7 +968,7 @@ impl RealTimeProcessor {
                 .iter()
             {
                 let short_event = MidiEvent::new(midi_event.offset(), *m);
-                self.send_short_midi_to_fx_output(short_event, caller);
+                self.pass_short_midi_through_to_fx_output(short_event, caller);
             }
         }
         match_outcome
@@ -866,34 +1019,91 @@ impl RealTimeProcessor {
         caller: Caller,
     ) -> MatchOutcome {
         let is_rendering = is_rendering();
+        let offset_event = value_event.with_payload(value_event.payload().offset());
+        let control_input = self.current_control_input;
         // We do pattern matching in order to use Rust's borrow splitting.
         let controller_outcome = if let [ref mut controller_mappings, ref mut main_mappings] =
             self.mappings.as_mut_slice()
         {
-            control_controller_mappings_midi(
+            control_controller_mappings(
                 &self.control_main_task_sender,
                 &self.feedback_task_sender,
                 controller_mappings,
                 main_mappings,
-                value_event,
+                offset_event,
                 caller,
                 self.settings.midi_destination(),
                 LogOptions::from_basic_settings(&self.settings),
                 &self.instance,
                 is_rendering,
+                |m| {
+                    if !m
+                        .options()
+                        .io_selection
+                        .accepts_control_input(control_input)
+                    {
+                        return None;
+                    }
+                    m.control_midi_virtualizing(flatten_control_midi_event(value_event))
+                },
             )
         } else {
             unreachable!()
         };
-        let main_outcome = self.control_main_mappings_midi(value_event, caller, is_rendering);
+        let main_outcome = self.control_main_mappings(offset_event, caller, is_rendering, |m| {
+            if !m
+                .options()
+                .io_selection
+                .accepts_control_input(control_input)
+            {
+                return None;
+            }
+            m.control_midi_source(flatten_control_midi_event(value_event))
+        });
         controller_outcome.merge_with(main_outcome)
     }
 
-    fn control_main_mappings_midi(
+    /// Lets audio sources analyze the given block of audio input of the ReaLearn FX.
+    pub fn process_incoming_audio_from_vst<I: AudioInput + ?Sized>(
+        &mut self,
+        input: &I,
+        host: &HostCallback,
+    ) {
+        if self.control_mode != ControlMode::Controlling || !self.control_is_globally_enabled {
+            return;
+        }
+        let caller = Caller::Vst(host);
+        let is_rendering = is_rendering();
+        let sample_rate = self.sample_rate;
+        // Audio sources emit at most one value per block, at the beginning of the block.
+        let offset_event = ControlEvent::new(SampleOffset::ZERO, ControlEventTimestamp::now());
+        // We do pattern matching in order to use Rust's borrow splitting.
+        if let [ref mut controller_mappings, ref mut main_mappings] = self.mappings.as_mut_slice() {
+            control_controller_mappings(
+                &self.control_main_task_sender,
+                &self.feedback_task_sender,
+                controller_mappings,
+                main_mappings,
+                offset_event,
+                caller,
+                self.settings.midi_destination(),
+                LogOptions::from_basic_settings(&self.settings),
+                &self.instance,
+                is_rendering,
+                |m| m.control_audio_virtualizing(input, sample_rate, offset_event.timestamp()),
+            );
+        }
+        self.control_main_mappings(offset_event, caller, is_rendering, |m| {
+            m.control_audio_source(input, sample_rate)
+        });
+    }
+
+    fn control_main_mappings(
         &mut self,
-        source_value_event: ControlEvent<MidiEvent<&MidiSourceValue<RawShortMessage>>>,
+        offset_event: ControlEvent<SampleOffset>,
         caller: Caller,
         is_rendering: bool,
+        mut control_source: impl FnMut(&mut RealTimeMapping) -> Option<ControlValue>,
     ) -> MatchOutcome {
         let compartment = CompartmentKind::Main;
         let mut match_outcome = MatchOutcome::Unmatched;
@@ -903,31 +1113,29 @@ impl RealTimeProcessor {
             // doesn't. Check again that it's a REAPER target.
             .filter(|m| m.control_is_effectively_on() && m.has_reaper_target())
         {
-            if let CompoundMappingSource::Midi(s) = &m.source() {
-                let midi_event = source_value_event.payload();
-                if let Some(control_value) = s.control(midi_event.payload()) {
-                    let args = ProcessRtMappingArgs {
-                        main_task_sender: &self.control_main_task_sender,
-                        rt_feedback_sender: &self.feedback_task_sender,
-                        compartment,
-                        value_event: source_value_event
-                            .with_payload(MidiEvent::new(midi_event.offset(), control_value)),
-                        options: ControlOptions {
-                            enforce_send_feedback_after_control: false,
-                            mode_control_options: Default::default(),
-                            enforce_target_refresh: match_outcome.matched(),
-                            coming_from_real_time: true,
-                        },
-                        caller,
-                        midi_feedback_output: self.settings.midi_destination(),
-                        log_options: LogOptions::from_basic_settings(&self.settings),
-                        instance: &self.instance,
-                        is_rendering,
-                    };
-                    process_real_mapping(m, args);
-                    // It can't be consumed because we checked this before for all mappings.
-                    match_outcome = MatchOutcome::Matched;
-                }
+            if let Some(control_value) = control_source(m) {
+                let args = ProcessRtMappingArgs {
+                    main_task_sender: &self.control_main_task_sender,
+                    rt_feedback_sender: &self.feedback_task_sender,
+                    compartment,
+                    value_event: offset_event
+                        .with_payload(MidiEvent::new(offset_event.payload(), control_value)),
+                    options: ControlOptions {
+                        enforce_send_feedback_after_control: false,
+                        mode_control_options: Default::default(),
+                        enforce_target_refresh: match_outcome.matched(),
+                        coming_from_real_time: true,
+                    },
+                    caller,
+                    midi_feedback_output: self.settings.midi_destination(),
+                    log_options: LogOptions::from_basic_settings(&self.settings),
+                    instance: &self.instance,
+                    is_rendering,
+                };
+                process_real_mapping(m, args);
+                // It can't be consumed because we checked this before for all mappings (or
+                // because it's not a MIDI source at all).
+                match_outcome = MatchOutcome::Matched;
             }
         }
         match_outcome
@@ -940,7 +1148,7 @@ impl RealTimeProcessor {
         if !self.settings.let_matched_events_through {
             return;
         }
-        self.send_incoming_midi_to_fx_output(event, caller);
+        self.pass_midi_through_to_fx_output(event, caller);
     }
 
     fn process_unmatched(&self, event: MidiEvent<IncomingMidiMessage>, caller: Caller) {
@@ -950,7 +1158,7 @@ impl RealTimeProcessor {
         if !self.settings.let_unmatched_events_through {
             return;
         }
-        self.send_incoming_midi_to_fx_output(event, caller);
+        self.pass_midi_through_to_fx_output(event, caller);
     }
 
     fn is_consumed_by_at_least_one_source(&self, msg: IncomingMidiMessage) -> bool {
@@ -986,48 +1194,65 @@ impl RealTimeProcessor {
         m: &RealTimeMapping,
         phase: LifecyclePhase,
     ) {
-        if let Some(output) = self.settings.midi_destination() {
-            match output {
-                MidiDestination::FxOutput => {
-                    // We can't send it now because we don't have safe access to the host callback
-                    // because this method is being called from the audio hook.
-                    self.feedback_task_sender.send_if_space(
-                        FeedbackRealTimeTask::SendLifecycleMidi(m.compartment(), m.id(), phase),
-                    );
-                }
-                MidiDestination::Device(dev_id) => {
-                    MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
-                        if let Some(mo) = mo {
-                            for m in m.lifecycle_midi_messages(phase) {
-                                match m {
-                                    LifecycleMidiMessage::Short(msg) => {
-                                        if self.settings.real_output_logging_enabled {
-                                            self.log_lifecycle_output(MidiSourceValue::Plain(*msg));
-                                        }
-                                        mo.send(*msg, SendMidiTime::Instantly);
+        for destination in self.lifecycle_midi_destinations() {
+            self.send_lifecycle_midi_to_destination_from_audio_hook(destination, m, phase);
+        }
+    }
+
+    fn send_lifecycle_midi_to_destination_from_audio_hook(
+        &self,
+        destination: MidiDestination,
+        m: &RealTimeMapping,
+        phase: LifecyclePhase,
+    ) {
+        if let Some(selected_output) = m.options().io_selection.feedback_output {
+            if selected_output != FeedbackOutput::Midi(destination) {
+                return;
+            }
+        }
+        match destination {
+            MidiDestination::FxOutput => {
+                // We can't send it now because we don't have safe access to the host callback
+                // because this method is being called from the audio hook.
+                self.feedback_task_sender
+                    .send_if_space(FeedbackRealTimeTask::SendLifecycleMidi(
+                        m.compartment(),
+                        m.id(),
+                        phase,
+                    ));
+            }
+            MidiDestination::Device(dev_id) => {
+                MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
+                    if let Some(mo) = mo {
+                        for m in m.lifecycle_midi_messages(phase) {
+                            match m {
+                                LifecycleMidiMessage::Short(msg) => {
+                                    if self.settings.real_output_logging_enabled {
+                                        self.log_lifecycle_output(MidiSourceValue::Plain(*msg));
                                     }
-                                    LifecycleMidiMessage::Raw(data) => {
-                                        if self.settings.real_output_logging_enabled {
-                                            permit_alloc(|| {
-                                                // We don't use this as feedback value,
-                                                // at least not in the sense that it
-                                                // participates in feedback relay.
-                                                let feedback_address_info = None;
-                                                let value = MidiSourceValue::single_raw(
-                                                    feedback_address_info,
-                                                    *data.clone(),
-                                                );
-                                                self.log_lifecycle_output(value);
-                                            });
-                                        }
-                                        mo.send_msg(**data, SendMidiTime::Instantly);
+                                    mo.send(*msg, SendMidiTime::Instantly);
+                                }
+                                LifecycleMidiMessage::Raw(data) => {
+                                    if self.settings.real_output_logging_enabled {
+                                        permit_alloc(|| {
+                                            // We don't use this as feedback value,
+                                            // at least not in the sense that it
+                                            // participates in feedback relay.
+                                            let feedback_address_info = None;
+                                            let value = MidiSourceValue::single_raw(
+                                                feedback_address_info,
+                                                *data.clone(),
+                                            );
+                                            self.log_lifecycle_output(value);
+                                        });
                                     }
+                                    mo.send_msg(**data, SendMidiTime::Instantly);
                                 }
                             }
                         }
-                    });
-                }
-            };
+                    }
+                });
+            }
         }
     }
 
@@ -1058,16 +1283,34 @@ impl RealTimeProcessor {
         }
     }
 
-    fn send_incoming_midi_to_fx_output(
+    /// Sends events which are let through to the FX output, applying the unit's MIDI routing.
+    fn pass_midi_through_to_fx_output(
         &self,
         event: MidiEvent<IncomingMidiMessage>,
         caller: Caller,
     ) {
         match event.payload() {
             IncomingMidiMessage::Short(s) => {
-                self.send_short_midi_to_fx_output(MidiEvent::new(event.offset(), s), caller);
+                self.pass_short_midi_through_to_fx_output(
+                    MidiEvent::new(event.offset(), s),
+                    caller,
+                );
+            }
+            IncomingMidiMessage::SysEx(s) => {
+                if self.midi_routing.lets_sys_ex_through() {
+                    send_raw_midi_to_fx_output(s, event.offset(), caller)
+                }
             }
-            IncomingMidiMessage::SysEx(s) => send_raw_midi_to_fx_output(s, event.offset(), caller),
+        }
+    }
+
+    fn pass_short_midi_through_to_fx_output(
+        &self,
+        event: MidiEvent<RawShortMessage>,
+        caller: Caller,
+    ) {
+        if let Some(msg) = self.midi_routing.route(event.payload()) {
+            self.send_short_midi_to_fx_output(MidiEvent::new(event.offset(), msg), caller);
         }
     }
 
@@ -1154,6 +1397,9 @@ pub enum NormalRealTimeTask {
         state: PersistentMappingProcessingState,
     },
     UpdateSettings(BasicSettings),
+    /// Replaces the routing table applied to MIDI events that are let through.
+    UpdateMidiRouting(MidiRoutingTable),
+    UpdateAdditionalIo(AdditionalIo),
     /// This takes care of propagating target activation states and/or real-time target updates
     /// (for non-virtual mappings).
     UpdateTargetsPartially(CompartmentKind, Vec<RealTimeTargetUpdate>),
@@ -1172,6 +1418,8 @@ pub enum NormalRealTimeTask {
     ReturnToControlMode,
     UpdateControlIsGloballyEnabled(bool),
     UpdateFeedbackIsGloballyEnabled(bool),
+    /// Makes the real-time processor forward incoming MIDI to the main processor for recording.
+    UpdateControlInputRecordingEnabled(bool),
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -1236,7 +1484,8 @@ pub enum FeedbackRealTimeTask {
     /// from the audio hook, we must wait until the VST process method is invoked. In order to let
     /// the MIDI event survive, we need to copy it. But we are not allowed to allocate, so the
     /// usual MidiSourceValue Raw variant is not suited.
-    NonAllocatingFxOutputFeedback(RawMidiEvent),
+    /// The sample offset is the position of the triggering control event within the block.
+    NonAllocatingFxOutputFeedback(RawMidiEvent, SampleOffset),
     /// Used only if feedback output is <FX output>, otherwise done synchronously.
     SendLifecycleMidi(CompartmentKind, MappingId, LifecyclePhase),
 }
@@ -1268,19 +1517,20 @@ pub enum MidiDestination {
 }
 
 #[allow(clippy::too_many_arguments)]
-fn control_controller_mappings_midi(
+fn control_controller_mappings(
     main_task_sender: &SenderToNormalThread<ControlMainTask>,
     rt_feedback_sender: &SenderToRealTimeThread<FeedbackRealTimeTask>,
     // Mappings with virtual targets
     controller_mappings: &mut OrderedMappingMap<RealTimeMapping>,
     // Mappings with virtual sources
     main_mappings: &mut OrderedMappingMap<RealTimeMapping>,
-    value_event: ControlEvent<MidiEvent<&MidiSourceValue<RawShortMessage>>>,
+    offset_event: ControlEvent<SampleOffset>,
     caller: Caller,
     midi_feedback_output: Option<MidiDestination>,
     log_options: LogOptions,
     instance: &WeakRealTimeInstance,
     is_rendering: bool,
+    mut control_source: impl FnMut(&mut RealTimeMapping) -> Option<PartialControlMatch>,
 ) -> MatchOutcome {
     let mut match_outcome = MatchOutcome::Unmatched;
     let mut enforce_target_refresh = false;
@@ -1288,9 +1538,7 @@ fn control_controller_mappings_midi(
         .values_mut()
         .filter(|m| m.control_is_effectively_on())
     {
-        if let Some(control_match) =
-            m.control_midi_virtualizing(flatten_control_midi_event(value_event))
-        {
+        if let Some(control_match) = control_source(m) {
             use PartialControlMatch::*;
             let child_match_outcome = match control_match {
                 ProcessVirtual(virtual_source_value) => {
@@ -1298,8 +1546,8 @@ fn control_controller_mappings_midi(
                         main_task_sender,
                         rt_feedback_sender,
                         main_mappings,
-                        value_event.with_payload(MidiEvent::new(
-                            value_event.payload().offset(),
+                        offset_event.with_payload(MidiEvent::new(
+                            offset_event.payload(),
                             virtual_source_value,
                         )),
                         ControlOptions {
@@ -1326,7 +1574,7 @@ fn control_controller_mappings_midi(
                     if log_options.virtual_input_logging_enabled {
                         log_virtual_control_input(
                             main_task_sender,
-                            value_event.with_payload(virtual_source_value),
+                            offset_event.with_payload(virtual_source_value),
                             virtual_match_outcome,
                         );
                     }
@@ -1337,10 +1585,8 @@ fn control_controller_mappings_midi(
                         main_task_sender,
                         rt_feedback_sender,
                         compartment: CompartmentKind::Controller,
-                        value_event: value_event.with_payload(MidiEvent::new(
-                            value_event.payload().offset(),
-                            control_value,
-                        )),
+                        value_event: offset_event
+                            .with_payload(MidiEvent::new(offset_event.payload(), control_value)),
                         options: ControlOptions {
                             enforce_send_feedback_after_control: false,
                             mode_control_options: Default::default(),
@@ -1425,6 +1671,7 @@ fn process_real_mapping_in_real_time(
     // REAPER real-time target actually wants real-time control. Try to process directly here in real-time.
     let control_context = RealTimeControlContext {
         instance: args.instance,
+        sample_offset: pure_control_event.timestamp().sample_offset(),
         _p: &(),
     };
     let mode_control_result = mapping_core.mode.control_with_options(
@@ -1452,7 +1699,7 @@ fn process_real_mapping_in_real_time(
                     args.log_options,
                     args.main_task_sender,
                     args.rt_feedback_sender,
-                    args.value_event.payload(),
+                    args.value_event,
                 ),
                 RealTimeReaperTarget::PlaytimeSlotTransport(t) => {
                     let result = t.hit(control_value, control_context);
@@ -1513,7 +1760,7 @@ fn real_time_target_send_midi(
     log_options: LogOptions,
     main_task_sender: &SenderToNormalThread<ControlMainTask>,
     rt_feedback_sender: &SenderToRealTimeThread<FeedbackRealTimeTask>,
-    value_event: MidiEvent<ControlValue>,
+    value_event: ControlEvent<MidiEvent<ControlValue>>,
 ) -> Result<(), &'static str> {
     let v = control_value.to_absolute_value()?;
     // This is a type of mapping that we should process right here because we want to
@@ -1547,7 +1794,7 @@ fn real_time_target_send_midi(
                 Caller::Vst(_) => {
                     send_raw_midi_to_fx_output(
                         raw_midi_event.bytes(),
-                        value_event.offset(),
+                        value_event.payload().offset(),
                         caller,
                     );
                 }
@@ -1555,7 +1802,10 @@ fn real_time_target_send_midi(
                     // We can't send to FX output here directly. Need to wait until VST processing
                     // starts (same processing cycle).
                     rt_feedback_sender.send_complaining(
-                        FeedbackRealTimeTask::NonAllocatingFxOutputFeedback(raw_midi_event),
+                        FeedbackRealTimeTask::NonAllocatingFxOutputFeedback(
+                            raw_midi_event,
+                            value_event.payload().offset(),
+                        ),
                     );
                 }
             }
@@ -1564,7 +1814,11 @@ fn real_time_target_send_midi(
         Some(MidiDestination::Device(dev_id)) => {
             MidiOutputDevice::new(dev_id).with_midi_output(|mo| {
                 if let Some(mo) = mo {
-                    mo.send_msg(raw_midi_event, SendMidiTime::Instantly);
+                    // Send at the position within the block at which the control event arrived
+                    let frame_offset = convert_block_offset_to_midi_frame_offset(
+                        value_event.timestamp().block_offset(),
+                    );
+                    mo.send_msg(raw_midi_event, SendMidiTime::AtFrameOffset(frame_offset));
                     true
                 } else {
                     false
@@ -1617,29 +1871,30 @@ fn control_main_mappings_virtual(
         .values_mut()
         .filter(|m| m.control_is_effectively_on())
     {
-        if let CompoundMappingSource::Virtual(s) = &m.source() {
-            let midi_event = value_event.payload();
-            if let Some(control_value) = s.control(&midi_event.payload()) {
-                let args = ProcessRtMappingArgs {
-                    main_task_sender,
-                    rt_feedback_sender,
-                    compartment: CompartmentKind::Main,
-                    value_event: value_event
-                        .with_payload(MidiEvent::new(midi_event.offset(), control_value)),
-                    options: ControlOptions {
-                        enforce_target_refresh: match_outcome.matched(),
-                        ..options
-                    },
-                    caller,
-                    midi_feedback_output,
-                    log_options,
-                    instance,
-                    is_rendering,
-                };
-                process_real_mapping(m, args);
-                // If we find an associated main mapping, this is not just consumed, it's matched.
-                match_outcome = MatchOutcome::Matched;
-            }
+        let midi_event = value_event.payload();
+        let virtual_source_value = midi_event.payload();
+        if let Some(control_value) =
+            m.control_virtual_source(value_event.with_payload(&virtual_source_value))
+        {
+            let args = ProcessRtMappingArgs {
+                main_task_sender,
+                rt_feedback_sender,
+                compartment: CompartmentKind::Main,
+                value_event: value_event
+                    .with_payload(MidiEvent::new(midi_event.offset(), control_value)),
+                options: ControlOptions {
+                    enforce_target_refresh: match_outcome.matched(),
+                    ..options
+                },
+                caller,
+                midi_feedback_output,
+                log_options,
+                instance,
+                is_rendering,
+            };
+            process_real_mapping(m, args);
+            // If we find an associated main mapping, this is not just consumed, it's matched.
+            match_outcome = MatchOutcome::Matched;
         }
     }
     match_outcome
@@ -1793,11 +2048,8 @@ fn log_virtual_control_input(
     });
 }
 
+/// The sample offset doesn't get lost because it's also part of the control event timestamp.
 fn flatten_control_midi_event<T: Copy>(evt: ControlEvent<MidiEvent<T>>) -> ControlEvent<T> {
-    // TODO-medium We could have sample-accurate control event times by converting the MIDI event
-    //  sample offset to something like microseconds (according to the current sample rate or by
-    //  using REAPER's MidiFrameOffset type instead of SampleOffset in the first place) and using
-    //  this microsecond unit in ControlEvent time.
     evt.map_payload(|midi_evt| midi_evt.payload())
 }
 
@@ -9,9 +9,9 @@ use crate::domain::{
     FeedbackOutput, FeedbackRealTimeTask, GroupId, InstanceStateChanged, MainMapping,
     MappingControlResult, MappingId, OrderedMappingMap, OscFeedbackTask, PluginParamIndex,
     ProcessorContext, QualifiedMappingId, RealTimeReaperTarget, RealearnSourceContext,
-    ReaperTarget, SharedInstance, SharedUnit, Tag, TagScope, TargetCharacter, TrackExclusivity,
-    UnitEvent, UnitId, WeakRealTimeInstance, ACTION_TARGET, ALL_TRACK_FX_ENABLE_TARGET,
-    ANY_ON_TARGET, AUTOMATION_MODE_OVERRIDE_TARGET, BROWSE_FXS_TARGET,
+    ReaperTarget, SampleOffset, SharedInstance, SharedUnit, Tag, TagScope, TargetCharacter,
+    TrackExclusivity, UnitEvent, UnitId, WeakRealTimeInstance, ACTION_TARGET,
+    ALL_TRACK_FX_ENABLE_TARGET, ANY_ON_TARGET, AUTOMATION_MODE_OVERRIDE_TARGET, BROWSE_FXS_TARGET,
     BROWSE_GROUP_MAPPINGS_TARGET, BROWSE_POT_FILTER_ITEMS_TARGET, BROWSE_POT_PRESETS_TARGET,
     COMPARTMENT_PARAMETER_VALUE_TARGET, DUMMY_TARGET, ENABLE_INSTANCES_TARGET,
     ENABLE_MAPPINGS_TARGET, FX_ENABLE_TARGET, FX_ONLINE_TARGET, FX_OPEN_TARGET,
@@ -21,11 +21,11 @@ use crate::domain::{
     OSC_SEND_TARGET, PLAYRATE_TARGET, PREVIEW_POT_PRESET_TARGET, ROUTE_AUTOMATION_MODE_TARGET,
     ROUTE_MONO_TARGET, ROUTE_MUTE_TARGET, ROUTE_PAN_TARGET, ROUTE_PHASE_TARGET,
     ROUTE_TOUCH_STATE_TARGET, ROUTE_VOLUME_TARGET, SAVE_MAPPING_SNAPSHOT_TARGET, SEEK_TARGET,
-    SELECTED_TRACK_TARGET, TEMPO_TARGET, TRACK_ARM_TARGET, TRACK_AUTOMATION_MODE_TARGET,
-    TRACK_MONITORING_MODE_TARGET, TRACK_MUTE_TARGET, TRACK_PAN_TARGET, TRACK_PARENT_SEND_TARGET,
-    TRACK_PEAK_TARGET, TRACK_PHASE_TARGET, TRACK_SELECTION_TARGET, TRACK_SHOW_TARGET,
-    TRACK_SOLO_TARGET, TRACK_TOOL_TARGET, TRACK_TOUCH_STATE_TARGET, TRACK_VOLUME_TARGET,
-    TRACK_WIDTH_TARGET, TRANSPORT_TARGET,
+    SELECTED_TRACK_TARGET, SELECT_BANK_TARGET, TEMPO_TARGET, TRACK_ARM_TARGET,
+    TRACK_AUTOMATION_MODE_TARGET, TRACK_MONITORING_MODE_TARGET, TRACK_MUTE_TARGET,
+    TRACK_PAN_TARGET, TRACK_PARENT_SEND_TARGET, TRACK_PEAK_TARGET, TRACK_PHASE_TARGET,
+    TRACK_SELECTION_TARGET, TRACK_SHOW_TARGET, TRACK_SOLO_TARGET, TRACK_TOOL_TARGET,
+    TRACK_TOUCH_STATE_TARGET, TRACK_VOLUME_TARGET, TRACK_WIDTH_TARGET, TRANSPORT_TARGET,
 };
 use base::hash_util::NonCryptoHashSet;
 use base::{SenderToNormalThread, SenderToRealTimeThread};
@@ -444,6 +444,8 @@ pub struct ControlContext<'a> {
 #[derive(Copy, Clone, Debug)]
 pub struct RealTimeControlContext<'a> {
     pub instance: &'a WeakRealTimeInstance,
+    /// Position of the control event within the current audio block.
+    pub sample_offset: SampleOffset,
     pub _p: &'a (),
 }
 
@@ -724,6 +726,7 @@ pub enum ReaperTargetType {
     LoadMappingSnapshot = 35,
     TakeMappingSnapshot = 55,
     BrowseGroup = 37,
+    SelectBank = 66,
 }
 
 impl Display for ReaperTargetType {
@@ -850,6 +853,7 @@ impl ReaperTargetType {
             LoadMappingSnapshot => &LOAD_MAPPING_SNAPSHOT_TARGET,
             TakeMappingSnapshot => &SAVE_MAPPING_SNAPSHOT_TARGET,
             BrowseGroup => &BROWSE_GROUP_MAPPINGS_TARGET,
+            SelectBank => &SELECT_BANK_TARGET,
             BrowsePotFilterItems => &BROWSE_POT_FILTER_ITEMS_TARGET,
             BrowsePotPresets => &BROWSE_POT_PRESETS_TARGET,
             PreviewPotPreset => &PREVIEW_POT_PRESET_TARGET,
@@ -1,4 +1,7 @@
-use crate::domain::{CompartmentKind, CompartmentParamIndex, RawParamValue, ReaperSourceAddress};
+use crate::domain::{
+    CompartmentKind, CompartmentParamIndex, CompartmentParams, LfoSource, RawParamValue,
+    ReaperSourceAddress, SequencerSource,
+};
 use base::hash_util::NonCryptoHashSet;
 use core::fmt;
 use derive_more::Display;
@@ -12,13 +15,15 @@ use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::time::{Duration, Instant};
 
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum ReaperSource {
     MidiDeviceChanges,
     RealearnInstanceStart,
     Timer(TimerSource),
     RealearnParameter(RealearnParameterSource),
     Speech(SpeechSource),
+    Lfo(LfoSource),
+    Sequencer(SequencerSource),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
@@ -134,13 +139,18 @@ impl ReaperSource {
     pub fn on_deactivate(&mut self) {
         match self {
             ReaperSource::Timer(s) => s.on_deactivate(),
+            ReaperSource::Lfo(s) => s.on_deactivate(),
+            ReaperSource::Sequencer(s) => s.on_deactivate(),
             _ => {}
         }
     }
 
     /// If this returns `true`, the `poll` method should be called, on a regular basis.
     pub fn wants_to_be_polled(&self) -> bool {
-        matches!(self, ReaperSource::Timer(_))
+        matches!(
+            self,
+            ReaperSource::Timer(_) | ReaperSource::Lfo(_) | ReaperSource::Sequencer(_)
+        )
     }
 
     pub fn possible_detailed_characters(&self) -> Vec<DetailedSourceCharacter> {
@@ -156,6 +166,7 @@ impl ReaperSource {
                 DetailedSourceCharacter::Trigger,
             ],
             Speech(_) => vec![DetailedSourceCharacter::RangeControl],
+            Lfo(_) | Sequencer(_) => vec![DetailedSourceCharacter::RangeControl],
         }
     }
 
@@ -174,16 +185,18 @@ impl ReaperSource {
             MidiDeviceChanges | RealearnInstanceStart | Timer(_) => {
                 SourceCharacter::MomentaryButton
             }
-            RealearnParameter(_) => SourceCharacter::RangeElement,
-            Speech(_) => SourceCharacter::RangeElement,
+            RealearnParameter(_) | Speech(_) | Lfo(_) | Sequencer(_) => {
+                SourceCharacter::RangeElement
+            }
         }
     }
 
-    pub fn poll(&mut self) -> Option<ControlValue> {
-        if let ReaperSource::Timer(t) = self {
-            t.poll()
-        } else {
-            None
+    pub fn poll(&mut self, params: &CompartmentParams) -> Option<ControlValue> {
+        match self {
+            ReaperSource::Timer(t) => t.poll(),
+            ReaperSource::Lfo(l) => l.poll(),
+            ReaperSource::Sequencer(s) => s.poll(params),
+            _ => None,
         }
     }
 
@@ -214,6 +227,11 @@ impl ReaperSource {
                 {
                     ControlValue::AbsoluteContinuous(UnitValue::new_clamped(c.value as f64))
                 }
+                ReaperSource::Lfo(s) if c.compartment == compartment => {
+                    // Parameter changes don't produce values but might restart the cycle.
+                    s.process_parameter_change(c.parameter_index, c.value);
+                    return None;
+                }
                 _ => return None,
             },
         };
@@ -223,7 +241,12 @@ impl ReaperSource {
     pub fn feedback(&self, feedback_value: &FeedbackValue) -> Option<ReaperSourceFeedbackValue> {
         use ReaperSource::*;
         match self {
-            MidiDeviceChanges | RealearnInstanceStart | Timer(_) | RealearnParameter(_) => None,
+            MidiDeviceChanges
+            | RealearnInstanceStart
+            | Timer(_)
+            | RealearnParameter(_)
+            | Lfo(_)
+            | Sequencer(_) => None,
             Speech(s) => Some(ReaperSourceFeedbackValue::Speech(
                 s.feedback(feedback_value),
             )),
@@ -44,8 +44,8 @@ use crate::domain::{
 use crate::domain::{
     AnyOnTarget, BrowseGroupMappingsTarget, CompoundChangeEvent, EnableInstancesTarget,
     EnableMappingsTarget, HitResponse, LoadMappingSnapshotTarget, RealearnTarget, ReaperTargetType,
-    RouteAutomationModeTarget, RouteMonoTarget, RoutePhaseTarget, TrackPhaseTarget,
-    TrackToolTarget,
+    RouteAutomationModeTarget, RouteMonoTarget, RoutePhaseTarget, SelectBankTarget,
+    TrackPhaseTarget, TrackToolTarget,
 };
 use base::default_util::is_default;
 use base::Global;
@@ -155,6 +155,7 @@ pub enum ReaperTarget {
     ModifyMapping(ModifyMappingTarget),
     EnableInstances(EnableInstancesTarget),
     BrowseGroupMappings(BrowseGroupMappingsTarget),
+    SelectBank(SelectBankTarget),
     BrowsePotFilterItems(BrowsePotFilterItemsTarget),
     BrowsePotPresets(BrowsePotPresetsTarget),
     PreviewPotPreset(PreviewPotPresetTarget),
@@ -317,7 +318,12 @@ impl ReaperTarget {
                 )
             }
             CompoundChangeEvent::Unit(e) => {
-                matches!(e, UnitEvent::ControlUnitTopLeftCornerChanged(_))
+                matches!(
+                    e,
+                    UnitEvent::ControlUnitTopLeftCornerChanged(_)
+                        // Bank-relative descriptors should be re-resolved
+                        | UnitEvent::CurrentBankChanged { .. }
+                )
             }
             CompoundChangeEvent::Instance(_) => false,
             #[cfg(feature = "playtime")]
@@ -682,6 +688,7 @@ impl<'a> Target<'a> for ReaperTarget {
             ModifyMapping(t) => t.current_value(context),
             EnableInstances(t) => t.current_value(context),
             BrowseGroupMappings(t) => t.current_value(context),
+            SelectBank(t) => t.current_value(context),
             BrowsePotFilterItems(t) => t.current_value(context),
             BrowsePotPresets(t) => t.current_value(context),
             PreviewPotPreset(t) => t.current_value(context),
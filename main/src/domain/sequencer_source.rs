@@ -0,0 +1,244 @@
+use crate::domain::{current_beat_position, CompartmentParamIndex, CompartmentParams};
+use helgoboss_learn::{ControlValue, UnitValue};
+use helgobox_api::persistence::SequencerDirection;
+use reaper_high::Reaper;
+
+/// A source which steps through a list of values in sync with the beat position of the current
+/// project.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SequencerSource {
+    settings: SequencerSettings,
+    state: SequencerState,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SequencerSettings {
+    pub steps: Vec<SequencerStep>,
+    /// Length of one step in beats.
+    pub beats_per_step: f64,
+    /// Amount by which every second step is delayed (0.0 to 1.0). At 1.0, the delayed step
+    /// starts three quarters into the step pair.
+    pub swing: f64,
+    pub direction: SequencerDirection,
+    /// If set, the value of step n is taken from compartment parameter `index + n`, which makes
+    /// it possible to edit the step values from other mappings.
+    pub step_value_parameter_index: Option<CompartmentParamIndex>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SequencerStep {
+    pub value: UnitValue,
+    /// If `false`, the step is a rest and doesn't emit any value.
+    pub gate: bool,
+}
+
+impl Default for SequencerStep {
+    fn default() -> Self {
+        Self {
+            value: UnitValue::MAX,
+            gate: true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+struct SequencerState {
+    /// Number of steps elapsed since the project start (not wrapped).
+    last_step_count: Option<i64>,
+}
+
+impl SequencerSource {
+    pub fn new(settings: SequencerSettings) -> Self {
+        Self {
+            settings,
+            state: Default::default(),
+        }
+    }
+
+    pub fn settings(&self) -> &SequencerSettings {
+        &self.settings
+    }
+
+    pub fn on_deactivate(&mut self) {
+        self.state.last_step_count = None;
+    }
+
+    /// Emits the value of the current step whenever the playhead enters a new step.
+    pub fn poll(&mut self, params: &CompartmentParams) -> Option<ControlValue> {
+        let step_count = self.current_step_count()?;
+        if self.state.last_step_count == Some(step_count) {
+            return None;
+        }
+        self.state.last_step_count = Some(step_count);
+        let step_index = self.step_index(step_count)?;
+        let step = self.settings.steps.get(step_index)?;
+        if !step.gate {
+            return None;
+        }
+        let value = self
+            .settings
+            .step_value_parameter_index
+            .and_then(|i| i + step_index as u32)
+            .map(|i| UnitValue::new_clamped(params.at(i).raw_value() as f64))
+            .unwrap_or(step.value);
+        Some(ControlValue::AbsoluteContinuous(value))
+    }
+
+    /// Returns the number of steps elapsed since the project start, taking swing into account.
+    fn current_step_count(&self) -> Option<i64> {
+        self.step_count_at(current_beat_position(Reaper::get().current_project()))
+    }
+
+    /// Returns the number of steps elapsed at the given beat position.
+    fn step_count_at(&self, beats: f64) -> Option<i64> {
+        let beats_per_step = self.settings.beats_per_step;
+        if beats_per_step <= 0.0 || self.settings.steps.is_empty() {
+            return None;
+        }
+        let pairs = beats / (2.0 * beats_per_step);
+        let pair_count = pairs.floor();
+        let second_step_start = 0.5 + 0.25 * self.settings.swing.clamp(0.0, 1.0);
+        let step_within_pair = if pairs - pair_count < second_step_start {
+            0
+        } else {
+            1
+        };
+        Some(pair_count as i64 * 2 + step_within_pair)
+    }
+
+    fn step_index(&self, step_count: i64) -> Option<usize> {
+        let len = self.settings.steps.len() as i64;
+        if len == 0 {
+            return None;
+        }
+        use SequencerDirection::*;
+        let index = match self.settings.direction {
+            Forward => step_count.rem_euclid(len),
+            Backward => len - 1 - step_count.rem_euclid(len),
+            PingPong => {
+                if len == 1 {
+                    0
+                } else {
+                    let period = 2 * len - 2;
+                    let pos = step_count.rem_euclid(period);
+                    if pos < len {
+                        pos
+                    } else {
+                        period - pos
+                    }
+                }
+            }
+            // Derived from the step count so that the same position always yields the same step.
+            Random => (hash_step_count(step_count) % len as u64) as i64,
+        };
+        Some(index as usize)
+    }
+}
+
+fn hash_step_count(step_count: i64) -> u64 {
+    // SplitMix64 finalizer
+    let mut x = step_count as u64;
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_sequencer(step_count: usize, direction: SequencerDirection) -> SequencerSource {
+        SequencerSource::new(SequencerSettings {
+            steps: vec![SequencerStep::default(); step_count],
+            beats_per_step: 0.5,
+            swing: 0.0,
+            direction,
+            step_value_parameter_index: None,
+        })
+    }
+
+    fn step_indexes(
+        sequencer: &SequencerSource,
+        step_counts: impl Iterator<Item = i64>,
+    ) -> Vec<usize> {
+        step_counts
+            .map(|c| sequencer.step_index(c).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn forward() {
+        // Given
+        let sequencer = create_sequencer(3, SequencerDirection::Forward);
+        // When
+        let indexes = step_indexes(&sequencer, -1..7);
+        // Then
+        assert_eq!(indexes, vec![2, 0, 1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn backward() {
+        // Given
+        let sequencer = create_sequencer(3, SequencerDirection::Backward);
+        // When
+        let indexes = step_indexes(&sequencer, 0..7);
+        // Then
+        assert_eq!(indexes, vec![2, 1, 0, 2, 1, 0, 2]);
+    }
+
+    #[test]
+    fn ping_pong() {
+        // Given
+        let sequencer = create_sequencer(3, SequencerDirection::PingPong);
+        let single_step_sequencer = create_sequencer(1, SequencerDirection::PingPong);
+        // When
+        let indexes = step_indexes(&sequencer, 0..9);
+        let single_step_indexes = step_indexes(&single_step_sequencer, 0..3);
+        // Then
+        assert_eq!(indexes, vec![0, 1, 2, 1, 0, 1, 2, 1, 0]);
+        assert_eq!(single_step_indexes, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn random() {
+        // Given
+        let sequencer = create_sequencer(4, SequencerDirection::Random);
+        // When
+        let indexes = step_indexes(&sequencer, 0..100);
+        let repeated_indexes = step_indexes(&sequencer, 0..100);
+        // Then
+        assert!(indexes.iter().all(|i| *i < 4));
+        // Same position, same step
+        assert_eq!(indexes, repeated_indexes);
+        // Not stuck on one step
+        assert!((0..4).all(|i| indexes.contains(&i)));
+    }
+
+    #[test]
+    fn step_count_without_swing() {
+        // Given
+        let sequencer = create_sequencer(4, SequencerDirection::Forward);
+        // When
+        let counts: Vec<_> = [0.0, 0.25, 0.5, 0.99, 1.0, 1.5]
+            .into_iter()
+            .map(|beats| sequencer.step_count_at(beats).unwrap())
+            .collect();
+        // Then
+        assert_eq!(counts, vec![0, 0, 1, 1, 2, 3]);
+    }
+
+    #[test]
+    fn step_count_with_swing() {
+        // Given
+        let mut sequencer = create_sequencer(4, SequencerDirection::Forward);
+        sequencer.settings.swing = 1.0;
+        // When
+        let counts: Vec<_> = [0.0, 0.5, 0.7, 0.75, 1.0, 1.5, 1.75]
+            .into_iter()
+            .map(|beats| sequencer.step_count_at(beats).unwrap())
+            .collect();
+        // Then
+        // Every second step starts three quarters into the step pair
+        assert_eq!(counts, vec![0, 0, 0, 1, 2, 2, 3]);
+    }
+}
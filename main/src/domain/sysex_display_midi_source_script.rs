@@ -0,0 +1,419 @@
+use helgoboss_learn::{
+    FeedbackValue, MidiSourceAddress, MidiSourceScript, MidiSourceScriptOutcome, RawMidiEvent,
+};
+use helgobox_api::persistence::{SysExDisplayAlignment, SysExDisplayEncoding};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// Sends textual feedback to arbitrary hardware displays by filling in a user-defined SysEx
+/// template.
+///
+/// This is not a script in the sense of a programming language, but it plugs into the same
+/// extension point of the MIDI source as the EEL and Lua scripts.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SysExDisplayMidiSourceScript {
+    template: SysExDisplayTemplate,
+    settings: SysExDisplaySettings,
+    address: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SysExDisplaySettings {
+    /// Line or segment index, inserted at `{line}`.
+    pub line: u8,
+    /// If set, the text is padded or truncated to exactly this number of characters.
+    pub length: Option<u32>,
+    pub alignment: SysExDisplayAlignment,
+    pub padding: char,
+    pub encoding: SysExDisplayEncoding,
+    /// Custom character mappings which take precedence over the encoding.
+    pub character_map: BTreeMap<char, u8>,
+}
+
+/// A parsed SysEx template such as `F0 00 20 6B 7F 42 04 {line} {text} F7`.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SysExDisplayTemplate {
+    items: Vec<TemplateItem>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+enum TemplateItem {
+    Byte(u8),
+    /// `{line}`
+    Line,
+    /// `{text}`
+    Text,
+    /// `{len}`: Number of text bytes.
+    Length,
+    /// `{checksum_start}`: Marks the first byte covered by the checksum.
+    ChecksumStart,
+    /// `{checksum}`: Roland-style checksum (the 7-bit sum of all covered bytes plus the checksum
+    /// is zero).
+    Checksum,
+}
+
+impl FromStr for SysExDisplayTemplate {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let items: Result<Vec<_>, _> = text
+            .split_whitespace()
+            .map(|token| {
+                let item = match token {
+                    "{line}" => TemplateItem::Line,
+                    "{text}" => TemplateItem::Text,
+                    "{len}" => TemplateItem::Length,
+                    "{checksum_start}" => TemplateItem::ChecksumStart,
+                    "{checksum}" => TemplateItem::Checksum,
+                    _ => {
+                        let byte = u8::from_str_radix(token, 16)
+                            .map_err(|_| format!("invalid template token \"{token}\""))?;
+                        TemplateItem::Byte(byte)
+                    }
+                };
+                Ok(item)
+            })
+            .collect();
+        let items = items?;
+        if !items.contains(&TemplateItem::Text) {
+            return Err("template doesn't contain {text}".to_string());
+        }
+        Ok(Self { items })
+    }
+}
+
+/// Highest value of a SysEx data byte.
+const MAX_DATA_BYTE: u8 = 0x7f;
+
+/// Checks that all custom characters are mapped to SysEx data bytes (00 - 7F).
+pub fn validate_sysex_display_character_map(map: &BTreeMap<char, u8>) -> Result<(), String> {
+    for (c, byte) in map {
+        if *byte > MAX_DATA_BYTE {
+            return Err(format!(
+                "character '{c}' is mapped to {byte:02X} but only bytes up to 7F are allowed"
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl SysExDisplayMidiSourceScript {
+    /// Fails if a setting would produce bytes outside of the SysEx data byte range.
+    pub fn new(
+        template: SysExDisplayTemplate,
+        settings: SysExDisplaySettings,
+    ) -> Result<Self, String> {
+        if settings.line > MAX_DATA_BYTE {
+            return Err(format!(
+                "line {} is too high, the maximum is 127",
+                settings.line
+            ));
+        }
+        validate_sysex_display_character_map(&settings.character_map)?;
+        if let Some(length) = settings.length {
+            if template.contains_length() && length > MAX_DATA_BYTE as u32 {
+                return Err(format!(
+                    "length {length} can't be inserted at {{len}}, the maximum is 127"
+                ));
+            }
+        }
+        // Sources with the same template and line address the same display area, so feedback
+        // from one should replace feedback from the other.
+        let mut hasher = DefaultHasher::new();
+        template.hash_header(&mut hasher);
+        settings.line.hash(&mut hasher);
+        let script = Self {
+            template,
+            settings,
+            address: hasher.finish(),
+        };
+        Ok(script)
+    }
+
+    fn format_text(&self, text: &str) -> Vec<u8> {
+        let chars: Vec<char> = text.chars().collect();
+        let chars = match self.settings.length {
+            None => chars,
+            Some(length) => {
+                let length = length as usize;
+                let mut chars = chars;
+                chars.truncate(length);
+                let missing = length - chars.len();
+                let (left, right) = match self.settings.alignment {
+                    SysExDisplayAlignment::Left => (0, missing),
+                    SysExDisplayAlignment::Center => (missing / 2, missing - missing / 2),
+                    SysExDisplayAlignment::Right => (missing, 0),
+                };
+                let padding = self.settings.padding;
+                std::iter::repeat(padding)
+                    .take(left)
+                    .chain(chars)
+                    .chain(std::iter::repeat(padding).take(right))
+                    .collect()
+            }
+        };
+        chars.into_iter().map(|c| self.encode_char(c)).collect()
+    }
+
+    fn encode_char(&self, c: char) -> u8 {
+        if let Some(byte) = self.settings.character_map.get(&c) {
+            return *byte;
+        }
+        match self.settings.encoding {
+            SysExDisplayEncoding::Ascii => {
+                if c.is_ascii() && !c.is_ascii_control() {
+                    c as u8
+                } else {
+                    b'?'
+                }
+            }
+            SysExDisplayEncoding::SevenSegment => encode_seven_segment(c),
+        }
+    }
+
+    fn build_message(&self, text_bytes: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let mut bytes = Vec::with_capacity(self.template.items.len() + text_bytes.len());
+        let mut checksum_start = 1;
+        for item in &self.template.items {
+            match item {
+                TemplateItem::Byte(b) => bytes.push(*b),
+                TemplateItem::Line => bytes.push(self.settings.line),
+                TemplateItem::Text => bytes.extend_from_slice(text_bytes),
+                TemplateItem::Length => {
+                    let length = u8::try_from(text_bytes.len())
+                        .ok()
+                        .filter(|l| *l <= MAX_DATA_BYTE)
+                        .ok_or("text too long for {len}, the maximum is 127 characters")?;
+                    bytes.push(length);
+                }
+                TemplateItem::ChecksumStart => checksum_start = bytes.len(),
+                TemplateItem::Checksum => {
+                    let covered = bytes.get(checksum_start..).unwrap_or_default();
+                    let sum = covered.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) & 0x7f;
+                    bytes.push((0x80 - sum) & 0x7f);
+                }
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+impl SysExDisplayTemplate {
+    fn contains_length(&self) -> bool {
+        self.items.contains(&TemplateItem::Length)
+    }
+
+    /// Hashes the fixed bytes up to the first placeholder, which usually identify the device.
+    fn hash_header(&self, state: &mut impl Hasher) {
+        for item in &self.items {
+            match item {
+                TemplateItem::Byte(b) => b.hash(state),
+                _ => break,
+            }
+        }
+    }
+}
+
+impl MidiSourceScript<'_> for SysExDisplayMidiSourceScript {
+    type AdditionalInput = ();
+
+    fn execute(
+        &self,
+        input_value: FeedbackValue,
+        _additional_input: (),
+    ) -> Result<MidiSourceScriptOutcome, Cow<'static, str>> {
+        let text = input_value.to_textual().text;
+        let text_bytes = self.format_text(&text);
+        let message = self.build_message(&text_bytes)?;
+        let event =
+            RawMidiEvent::try_from_slice(0, &message).map_err(|_| "display message too long")?;
+        let outcome = MidiSourceScriptOutcome {
+            address: Some(MidiSourceAddress::Script {
+                bytes: self.address,
+            }),
+            events: vec![event],
+        };
+        Ok(outcome)
+    }
+}
+
+/// Encodes the given character for a 7-segment display, bits in order "gfedcba".
+fn encode_seven_segment(c: char) -> u8 {
+    match c.to_ascii_uppercase() {
+        '0' | 'O' => 0x3f,
+        '1' | 'I' => 0x06,
+        '2' | 'Z' => 0x5b,
+        '3' => 0x4f,
+        '4' => 0x66,
+        '5' | 'S' => 0x6d,
+        '6' => 0x7d,
+        '7' => 0x07,
+        '8' => 0x7f,
+        '9' => 0x6f,
+        'A' => 0x77,
+        'B' => 0x7c,
+        'C' => 0x39,
+        'D' => 0x5e,
+        'E' => 0x79,
+        'F' => 0x71,
+        'G' => 0x3d,
+        'H' => 0x76,
+        'J' => 0x1e,
+        'L' => 0x38,
+        'N' => 0x54,
+        'P' => 0x73,
+        'R' => 0x50,
+        'T' => 0x78,
+        'U' | 'V' => 0x3e,
+        'Y' => 0x6e,
+        '-' => 0x40,
+        '_' => 0x08,
+        _ => 0x00,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::{FeedbackStyle, TextualFeedbackValue};
+
+    fn create_script(
+        template: &str,
+        settings: SysExDisplaySettings,
+    ) -> SysExDisplayMidiSourceScript {
+        SysExDisplayMidiSourceScript::new(template.parse().unwrap(), settings).unwrap()
+    }
+
+    fn default_settings() -> SysExDisplaySettings {
+        SysExDisplaySettings {
+            line: 0,
+            length: None,
+            alignment: Default::default(),
+            padding: ' ',
+            encoding: Default::default(),
+            character_map: Default::default(),
+        }
+    }
+
+    fn execute(script: &SysExDisplayMidiSourceScript, text: &str) -> Vec<RawMidiEvent> {
+        let value = FeedbackValue::Textual(TextualFeedbackValue::new(
+            FeedbackStyle::default(),
+            text.to_string().into(),
+        ));
+        script.execute(value, ()).unwrap().events
+    }
+
+    fn event(bytes: &[u8]) -> Vec<RawMidiEvent> {
+        vec![RawMidiEvent::try_from_slice(0, bytes).unwrap()]
+    }
+
+    #[test]
+    fn line_and_text() {
+        // Given
+        let settings = SysExDisplaySettings {
+            line: 2,
+            ..default_settings()
+        };
+        let script = create_script("F0 47 7F {line} {len} {text} F7", settings);
+        // When
+        let bytes = execute(&script, "Hi");
+        // Then
+        assert_eq!(
+            bytes,
+            event(&[0xf0, 0x47, 0x7f, 0x02, 0x02, b'H', b'i', 0xf7])
+        );
+    }
+
+    #[test]
+    fn padding_and_alignment() {
+        // Given
+        let settings = SysExDisplaySettings {
+            length: Some(4),
+            alignment: SysExDisplayAlignment::Right,
+            padding: '_',
+            ..default_settings()
+        };
+        let script = create_script("F0 {text} F7", settings);
+        // When
+        let bytes = execute(&script, "ab");
+        // Then
+        assert_eq!(bytes, event(&[0xf0, b'_', b'_', b'a', b'b', 0xf7]));
+    }
+
+    #[test]
+    fn checksum() {
+        // Given
+        let script = create_script(
+            "F0 41 10 {checksum_start} 12 {text} {checksum} F7",
+            default_settings(),
+        );
+        // When
+        let bytes = execute(&script, "A");
+        // Then
+        // 0x12 + 0x41 = 0x53, 0x80 - 0x53 = 0x2d
+        assert_eq!(bytes, event(&[0xf0, 0x41, 0x10, 0x12, 0x41, 0x2d, 0xf7]));
+    }
+
+    #[test]
+    fn seven_segment_with_custom_characters() {
+        // Given
+        let settings = SysExDisplaySettings {
+            encoding: SysExDisplayEncoding::SevenSegment,
+            character_map: [('x', 0x49)].into_iter().collect(),
+            ..default_settings()
+        };
+        let script = create_script("{text}", settings);
+        // When
+        let bytes = execute(&script, "1x");
+        // Then
+        assert_eq!(bytes, event(&[0x06, 0x49]));
+    }
+
+    #[test]
+    fn reject_bytes_outside_of_data_byte_range() {
+        // Given
+        let template: SysExDisplayTemplate = "F0 {line} {len} {text} F7".parse().unwrap();
+        let invalid_line = SysExDisplaySettings {
+            line: 128,
+            ..default_settings()
+        };
+        let invalid_character_map = SysExDisplaySettings {
+            character_map: [('x', 0x80)].into_iter().collect(),
+            ..default_settings()
+        };
+        let invalid_length = SysExDisplaySettings {
+            length: Some(128),
+            ..default_settings()
+        };
+        // When
+        // Then
+        assert!(SysExDisplayMidiSourceScript::new(template.clone(), invalid_line).is_err());
+        assert!(
+            SysExDisplayMidiSourceScript::new(template.clone(), invalid_character_map).is_err()
+        );
+        assert!(SysExDisplayMidiSourceScript::new(template, invalid_length).is_err());
+    }
+
+    #[test]
+    fn reject_text_too_long_for_length() {
+        // Given
+        let script = create_script("F0 {len} {text} F7", default_settings());
+        let value = FeedbackValue::Textual(TextualFeedbackValue::new(
+            FeedbackStyle::default(),
+            "x".repeat(128).into(),
+        ));
+        // When
+        let result = script.execute(value, ());
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!("F0 {text} ZZ F7".parse::<SysExDisplayTemplate>().is_err());
+        assert!("F0 {line} F7".parse::<SysExDisplayTemplate>().is_err());
+    }
+}
@@ -79,27 +79,11 @@ impl RealearnTarget for CompartmentParameterValueTarget {
         context: MappingControlContext,
     ) -> Result<HitResponse, &'static str> {
         let unit_value = value.to_unit_value()?;
-        let plugin_param_index = self.plugin_param_index();
-        if context.control_context.unit.borrow().is_main_unit() {
-            // The main unit of an instance is special in that its compartment parameters are
-            // connected to the VST plug-in parameters. That's why we should change the VST plug-in
-            // parameter directly for reasons of unidirectional data flow.
-            context
-                .control_context
-                .processor_context
-                .containing_fx()
-                .parameter_by_index(plugin_param_index.get())
-                .set_reaper_normalized_value(ReaperNormalizedFxParamValue::new(unit_value.get()))?;
-        } else {
-            // Compartment parameters of additional units are purely internal, so we need to
-            // control them internally.
-            context
-                .control_context
-                .unit
-                .borrow()
-                .parameter_manager()
-                .set_single_parameter(plugin_param_index, unit_value.get() as _);
-        }
+        set_compartment_parameter_value(
+            context.control_context,
+            self.plugin_param_index(),
+            unit_value,
+        )?;
         Ok(HitResponse::processed_with_effect())
     }
 
@@ -191,3 +175,31 @@ pub const COMPARTMENT_PARAMETER_VALUE_TARGET: TargetTypeDef = TargetTypeDef {
     short_name: "Set compartment parameter value",
     ..DEFAULT_TARGET
 };
+
+/// Sets the value of the given compartment parameter in a way that respects the unidirectional
+/// data flow.
+pub fn set_compartment_parameter_value(
+    context: ControlContext,
+    plugin_param_index: PluginParamIndex,
+    value: UnitValue,
+) -> Result<(), &'static str> {
+    if context.unit.borrow().is_main_unit() {
+        // The main unit of an instance is special in that its compartment parameters are
+        // connected to the VST plug-in parameters. That's why we should change the VST plug-in
+        // parameter directly for reasons of unidirectional data flow.
+        context
+            .processor_context
+            .containing_fx()
+            .parameter_by_index(plugin_param_index.get())
+            .set_reaper_normalized_value(ReaperNormalizedFxParamValue::new(value.get()))?;
+    } else {
+        // Compartment parameters of additional units are purely internal, so we need to
+        // control them internally.
+        context
+            .unit
+            .borrow()
+            .parameter_manager()
+            .set_single_parameter(plugin_param_index, value.get() as _);
+    }
+    Ok(())
+}
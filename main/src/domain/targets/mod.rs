@@ -172,6 +172,9 @@ pub use enable_instances_target::*;
 mod browse_group_mappings_target;
 pub use browse_group_mappings_target::*;
 
+mod select_bank_target;
+pub use select_bank_target::*;
+
 mod any_on_target;
 pub use any_on_target::*;
 
@@ -453,6 +453,9 @@ mod playtime_impl {
             context: RealTimeControlContext,
         ) -> Result<bool, &'static str> {
             use PlaytimeSlotTransportAction::*;
+            // Unquantized starts and stops should happen at the position of the control event
+            // within the current block, not at the beginning of the block.
+            let frame_offset = context.sample_offset.get() as usize;
             let matrix = context.clip_matrix()?;
             let mut matrix = matrix.lock();
             match self.basics.action {
@@ -479,39 +482,47 @@ mod playtime_impl {
                         true
                     };
                     if execute_in_real_time {
-                        matrix.trigger_slot(self.basics.slot_address, value.to_unit_value()?)?;
+                        matrix.trigger_slot_at(
+                            self.basics.slot_address,
+                            value.to_unit_value()?,
+                            frame_offset,
+                        )?;
                     }
                     Ok(forward_to_main_thread)
                 }
                 PlayStop => {
                     if value.is_on() {
-                        matrix
-                            .play_slot(self.basics.slot_address, self.basics.play_options(value))?;
+                        matrix.play_slot(
+                            self.basics.slot_address,
+                            self.basics.play_options_at(value, frame_offset),
+                        )?;
                     } else {
-                        matrix.stop_slot(self.basics.slot_address)?;
+                        matrix.stop_slot_at(self.basics.slot_address, frame_offset)?;
                     }
                     Ok(false)
                 }
                 PlayPause => {
                     if value.is_on() {
-                        matrix
-                            .play_slot(self.basics.slot_address, self.basics.play_options(value))?;
+                        matrix.play_slot(
+                            self.basics.slot_address,
+                            self.basics.play_options_at(value, frame_offset),
+                        )?;
                     } else {
-                        matrix.pause_slot(self.basics.slot_address)?;
+                        matrix.pause_slot_at(self.basics.slot_address, frame_offset)?;
                     }
                     // Completely handled in real-time, no need to forward to main thread
                     Ok(false)
                 }
                 Stop => {
                     if value.is_on() {
-                        matrix.stop_slot(self.basics.slot_address)?;
+                        matrix.stop_slot_at(self.basics.slot_address, frame_offset)?;
                     }
                     // Completely handled in real-time, no need to forward to main thread
                     Ok(false)
                 }
                 Pause => {
                     if value.is_on() {
-                        matrix.pause_slot(self.basics.slot_address)?;
+                        matrix.pause_slot_at(self.basics.slot_address, frame_offset)?;
                     }
                     // Completely handled in real-time, no need to forward to main thread
                     Ok(false)
@@ -557,6 +568,20 @@ mod playtime_impl {
             ColumnPlaySlotOptions {
                 velocity: Some(control_value.to_unit_value().unwrap_or(UnitValue::MAX)),
                 stop_column_if_slot_empty: self.options.stop_column_if_slot_empty,
+                frame_offset: 0,
+            }
+        }
+
+        /// Like [`Self::play_options`] but makes the slot start at the given position within the
+        /// current block (if not quantized).
+        fn play_options_at(
+            &self,
+            control_value: ControlValue,
+            frame_offset: usize,
+        ) -> ColumnPlaySlotOptions {
+            ColumnPlaySlotOptions {
+                frame_offset,
+                ..self.play_options(control_value)
             }
         }
     }
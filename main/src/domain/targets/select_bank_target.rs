@@ -0,0 +1,230 @@
+use crate::domain::{
+    convert_count_to_step_size, convert_discrete_to_unit_value, convert_unit_to_discrete_value,
+    CompartmentKind, CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitResponse,
+    MappingControlContext, RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter,
+    TargetSection, TargetTypeDef, UnitEvent, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+};
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, NumericValue, Target, UnitValue,
+};
+use std::borrow::Cow;
+
+#[derive(Debug)]
+pub struct UnresolvedSelectBankTarget {
+    pub compartment: CompartmentKind,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedSelectBankTarget {
+    fn resolve(
+        &self,
+        _: ExtendedProcessorContext,
+        _: CompartmentKind,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        Ok(vec![ReaperTarget::SelectBank(SelectBankTarget {
+            compartment: self.compartment,
+        })])
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SelectBankTarget {
+    /// This must always correspond to the compartment of the containing mapping.
+    pub compartment: CompartmentKind,
+}
+
+impl SelectBankTarget {
+    fn count(&self, context: ControlContext) -> u32 {
+        context.unit.borrow().banks(self.compartment).count()
+    }
+
+    fn current_bank_position(&self, context: ControlContext) -> Option<Fraction> {
+        let unit = context.unit.borrow();
+        let count = unit.banks(self.compartment).count();
+        if count == 0 {
+            return None;
+        }
+        let index = unit.current_bank_index(self.compartment);
+        Some(Fraction::new(index, count - 1))
+    }
+}
+
+impl RealearnTarget for SelectBankTarget {
+    fn control_type_and_character(
+        &self,
+        context: ControlContext,
+    ) -> (ControlType, TargetCharacter) {
+        (
+            ControlType::AbsoluteDiscrete {
+                atomic_step_size: convert_count_to_step_size(self.count(context)),
+                is_retriggerable: false,
+            },
+            TargetCharacter::Discrete,
+        )
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitResponse, &'static str> {
+        let value = value.to_absolute_value()?;
+        let mut unit = context.control_context.unit.borrow_mut();
+        let count = unit.banks(self.compartment).count();
+        let desired_index = get_desired_bank_index(value, count)?;
+        unit.set_current_bank_index(self.compartment, desired_index);
+        Ok(HitResponse::processed_with_effect())
+    }
+
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        self.parse_value_from_discrete_value(text, context)
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        context: ControlContext,
+    ) -> Result<u32, &'static str> {
+        let count = self.count(context);
+        Ok(convert_unit_to_discrete_value(input, count))
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        let count = self.count(context);
+        Ok(convert_discrete_to_unit_value(value, count))
+    }
+
+    fn is_available(&self, context: ControlContext) -> bool {
+        !context.unit.borrow().banks(self.compartment).is_empty()
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::Unit(UnitEvent::CurrentBankChanged { compartment })
+                if *compartment == self.compartment =>
+            {
+                (true, None)
+            }
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<Cow<'static, str>> {
+        let unit = context.unit.borrow();
+        let index = unit.current_bank_index(self.compartment);
+        let name = unit.banks(self.compartment).name(index)?;
+        Some(name.to_string().into())
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        let fraction = self.current_bank_position(context)?;
+        Some(NumericValue::Discrete(fraction.actual() as i32 + 1))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::SelectBank)
+    }
+}
+
+impl<'a> Target<'a> for SelectBankTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: ControlContext) -> Option<AbsoluteValue> {
+        let fraction = self.current_bank_position(context).unwrap_or(Fraction::MIN);
+        Some(AbsoluteValue::Discrete(fraction))
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+fn get_desired_bank_index(value: AbsoluteValue, count: u32) -> Result<u32, &'static str> {
+    if count == 0 {
+        return Err("no banks defined");
+    }
+    let desired_index = match value {
+        AbsoluteValue::Continuous(v) => convert_unit_to_discrete_value(v, count),
+        AbsoluteValue::Discrete(f) => f.actual(),
+    };
+    if desired_index >= count {
+        return Err("bank index out of bounds");
+    }
+    Ok(desired_index)
+}
+
+pub const SELECT_BANK_TARGET: TargetTypeDef = TargetTypeDef {
+    section: TargetSection::ReaLearn,
+    name: "Select bank",
+    short_name: "Select bank",
+    ..DEFAULT_TARGET
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desired_bank_index_from_continuous_value() {
+        // Given
+        let count = 4;
+        // When
+        // Then
+        assert_eq!(
+            get_desired_bank_index(AbsoluteValue::Continuous(UnitValue::MIN), count),
+            Ok(0)
+        );
+        assert_eq!(
+            get_desired_bank_index(AbsoluteValue::Continuous(UnitValue::new(0.5)), count),
+            Ok(2)
+        );
+        assert_eq!(
+            get_desired_bank_index(AbsoluteValue::Continuous(UnitValue::MAX), count),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn desired_bank_index_from_discrete_value() {
+        // Given
+        let count = 4;
+        // When
+        // Then
+        assert_eq!(
+            get_desired_bank_index(AbsoluteValue::Discrete(Fraction::new(1, 3)), count),
+            Ok(1)
+        );
+        assert!(
+            get_desired_bank_index(AbsoluteValue::Discrete(Fraction::new(4, 4)), count).is_err()
+        );
+    }
+
+    #[test]
+    fn desired_bank_index_without_banks() {
+        // Given
+        let count = 0;
+        // When
+        let result = get_desired_bank_index(AbsoluteValue::Continuous(UnitValue::MAX), count);
+        // Then
+        assert!(result.is_err());
+    }
+}
@@ -8,9 +8,10 @@ use serde_json::json;
 
 use crate::base::Prop;
 use crate::domain::{
-    CompartmentKind, FxDescriptor, GlobalControlAndFeedbackState, GroupId, MappingId,
-    MappingSnapshotContainer, ParameterManager, QualifiedMappingId, SharedInstance, Tag, TagScope,
-    TrackDescriptor, UnitId, VirtualMappingSnapshotIdForLoad, WeakInstance,
+    CompartmentBanks, CompartmentKind, FxDescriptor, GlobalControlAndFeedbackState, GroupId,
+    MappingId, MappingSnapshotContainer, ParameterManager, QualifiedMappingId, SharedInstance, Tag,
+    TagScope, TrackDescriptor, UnitId, VirtualMappingSnapshotIdForLoad, WeakInstance,
+    WebSocketFeedbackAddress, WebSocketFeedbackValue,
 };
 use base::hash_util::{NonCryptoHashMap, NonCryptoHashSet};
 use base::{serde_json_util, NamedChannelSender, SenderToNormalThread};
@@ -105,6 +106,23 @@ pub struct Unit {
     mapping_which_learns_target: Prop<Option<QualifiedMappingId>>,
     parameter_manager: Arc<ParameterManager>,
     custom_compartment_data: EnumMap<CompartmentKind, NonCryptoHashMap<String, serde_json::Value>>,
+    /// Bank definitions.
+    ///
+    /// - Persistent as part of the compartment
+    /// - Used for bank-relative target descriptors and target "ReaLearn: Select bank"
+    banks: EnumMap<CompartmentKind, CompartmentBanks>,
+    /// Index of the current bank.
+    ///
+    /// - Persistent
+    /// - Set by target "ReaLearn: Select bank".
+    /// - Non-redundant state!
+    current_bank_index: EnumMap<CompartmentKind, u32>,
+    /// The feedback value last sent to WebSocket clients, per address.
+    ///
+    /// - Not persistent
+    /// - Sent to WebSocket clients when they start acting as virtual controller device
+    web_socket_feedback_snapshot:
+        NonCryptoHashMap<WebSocketFeedbackAddress, WebSocketFeedbackValue>,
     control_unit_top_left_corner: playtime_api::persistence::SlotAddress,
 }
 
@@ -140,6 +158,9 @@ impl Unit {
             mapping_which_learns_target: Default::default(),
             parameter_manager: Arc::new(parameter_manager),
             custom_compartment_data: Default::default(),
+            banks: Default::default(),
+            current_bank_index: Default::default(),
+            web_socket_feedback_snapshot: Default::default(),
             control_unit_top_left_corner: Default::default(),
         }
     }
@@ -170,6 +191,51 @@ impl Unit {
         &self.custom_compartment_data[compartment]
     }
 
+    pub fn banks(&self, compartment: CompartmentKind) -> &CompartmentBanks {
+        &self.banks[compartment]
+    }
+
+    pub fn set_banks(&mut self, compartment: CompartmentKind, banks: CompartmentBanks) {
+        self.banks[compartment] = banks;
+        self.current_bank_index[compartment] =
+            self.banks[compartment].clamp_index(self.current_bank_index[compartment]);
+        self.event_sender
+            .send_complaining(UnitEvent::CurrentBankChanged { compartment });
+    }
+
+    pub fn current_bank_index(&self, compartment: CompartmentKind) -> u32 {
+        self.current_bank_index[compartment]
+    }
+
+    /// Selects the bank with the given index (clamped to the existing banks).
+    pub fn set_current_bank_index(&mut self, compartment: CompartmentKind, index: u32) {
+        let index = self.banks[compartment].clamp_index(index);
+        if index == self.current_bank_index[compartment] {
+            return;
+        }
+        self.current_bank_index[compartment] = index;
+        self.event_sender
+            .send_complaining(UnitEvent::CurrentBankChanged { compartment });
+    }
+
+    /// Returns the index offset which bank-relative target descriptors should apply.
+    pub fn current_bank_offset(&self, compartment: CompartmentKind) -> u32 {
+        self.banks[compartment].offset(self.current_bank_index[compartment])
+    }
+
+    pub fn memorize_web_socket_feedback(&mut self, value: &WebSocketFeedbackValue) {
+        self.web_socket_feedback_snapshot
+            .insert(value.address(), value.clone());
+    }
+
+    pub fn clear_web_socket_feedback_snapshot(&mut self) {
+        self.web_socket_feedback_snapshot.clear();
+    }
+
+    pub fn web_socket_feedback_snapshot(&self) -> impl Iterator<Item = &WebSocketFeedbackValue> {
+        self.web_socket_feedback_snapshot.values()
+    }
+
     pub fn is_main_unit(&self) -> bool {
         self.is_main_unit
     }
@@ -613,6 +679,10 @@ pub enum UnitEvent {
         mapping_id: Option<QualifiedMappingId>,
     },
     ControlUnitTopLeftCornerChanged(playtime_api::persistence::SlotAddress),
+    /// For bank-relative target descriptors and the "ReaLearn: Select bank" target.
+    CurrentBankChanged {
+        compartment: CompartmentKind,
+    },
 }
 
 impl UnitEvent {
@@ -16,13 +16,14 @@ use crate::domain::{
     UnresolvedPlayrateTarget, UnresolvedPreviewPotPresetTarget,
     UnresolvedRouteAutomationModeTarget, UnresolvedRouteMonoTarget, UnresolvedRouteMuteTarget,
     UnresolvedRoutePanTarget, UnresolvedRoutePhaseTarget, UnresolvedRouteTouchStateTarget,
-    UnresolvedRouteVolumeTarget, UnresolvedSeekTarget, UnresolvedTakeMappingSnapshotTarget,
-    UnresolvedTempoTarget, UnresolvedTrackArmTarget, UnresolvedTrackAutomationModeTarget,
-    UnresolvedTrackMonitoringModeTarget, UnresolvedTrackMuteTarget, UnresolvedTrackPanTarget,
-    UnresolvedTrackParentSendTarget, UnresolvedTrackPeakTarget, UnresolvedTrackPhaseTarget,
-    UnresolvedTrackSelectionTarget, UnresolvedTrackShowTarget, UnresolvedTrackSoloTarget,
-    UnresolvedTrackToolTarget, UnresolvedTrackTouchStateTarget, UnresolvedTrackVolumeTarget,
-    UnresolvedTrackWidthTarget, UnresolvedTransportTarget,
+    UnresolvedRouteVolumeTarget, UnresolvedSeekTarget, UnresolvedSelectBankTarget,
+    UnresolvedTakeMappingSnapshotTarget, UnresolvedTempoTarget, UnresolvedTrackArmTarget,
+    UnresolvedTrackAutomationModeTarget, UnresolvedTrackMonitoringModeTarget,
+    UnresolvedTrackMuteTarget, UnresolvedTrackPanTarget, UnresolvedTrackParentSendTarget,
+    UnresolvedTrackPeakTarget, UnresolvedTrackPhaseTarget, UnresolvedTrackSelectionTarget,
+    UnresolvedTrackShowTarget, UnresolvedTrackSoloTarget, UnresolvedTrackToolTarget,
+    UnresolvedTrackTouchStateTarget, UnresolvedTrackVolumeTarget, UnresolvedTrackWidthTarget,
+    UnresolvedTransportTarget,
 };
 use derive_more::{Display, Error};
 use enum_dispatch::enum_dispatch;
@@ -110,6 +111,7 @@ pub enum UnresolvedReaperTarget {
     EnableMappings(UnresolvedEnableMappingsTarget),
     ModifyMapping(UnresolvedModifyMappingTarget),
     BrowseGroup(UnresolvedBrowseGroupTarget),
+    SelectBank(UnresolvedSelectBankTarget),
     EnableInstances(UnresolvedEnableInstancesTarget),
     AnyOn(UnresolvedAnyOnTarget),
     LastTouched(UnresolvedLastTouchedTarget),
@@ -319,10 +321,12 @@ impl TrackDescriptor {
                 index,
                 commons,
                 scope,
+                bank_relative,
             } => (
                 VirtualTrack::ByIndex {
                     index,
                     scope: scope.unwrap_or_default(),
+                    bank_relative: bank_relative.unwrap_or(false),
                 },
                 commons,
             ),
@@ -414,13 +418,17 @@ impl FxDescriptor {
                     commons,
                     chain: FxChainDescriptor::Track { track, chain },
                     index,
+                    bank_relative,
                 } => {
                     let chain = chain.unwrap_or_default();
                     (
                         TrackDescriptor::from_api(track.unwrap_or_default())?,
                         VirtualFx::ChainFx {
                             is_input_fx: chain.is_input_fx(),
-                            chain_fx: VirtualChainFx::ByIndex(index),
+                            chain_fx: VirtualChainFx::ByIndex {
+                                index,
+                                bank_relative: bank_relative.unwrap_or(false),
+                            },
                         },
                         commons,
                     )
@@ -513,7 +521,10 @@ impl FxDescriptor {
                         // for monitoring FX it could still be good (which we don't get notified
                         // about unfortunately).
                         if matches!(self.track_descriptor.track, VirtualTrack::Selected { .. }) {
-                            MaybeOwned::Owned(VirtualChainFx::ByIndex(*index))
+                            MaybeOwned::Owned(VirtualChainFx::ByIndex {
+                                index: *index,
+                                bank_relative: false,
+                            })
                         } else {
                             MaybeOwned::Borrowed(chain_fx)
                         }
@@ -973,7 +984,12 @@ pub enum VirtualTrack {
         allow_multiple: bool,
     },
     /// Particular.
-    ByIndex { index: u32, scope: TrackScope },
+    ByIndex {
+        index: u32,
+        scope: TrackScope,
+        /// Adds the offset of the current bank to the index.
+        bank_relative: bool,
+    },
     /// This is the old default for targeting a particular track and it exists solely for backward
     /// compatibility.
     ByIdOrName(Guid, WildMatch),
@@ -997,7 +1013,11 @@ pub enum VirtualFxParameter {
     Dynamic(Box<ExpressionEvaluator>),
     ByName(WildMatch),
     ById(u32),
-    ByIndex(u32),
+    ByIndex {
+        index: u32,
+        /// Adds the offset of the current bank to the index.
+        bank_relative: bool,
+    },
 }
 
 impl VirtualFxParameter {
@@ -1027,7 +1047,15 @@ impl VirtualFxParameter {
                     name: Some(name.clone()),
                     index: None,
                 }),
-            ByIndex(i) | ById(i) => resolve_parameter_by_index(fx, *i),
+            ById(i) => resolve_parameter_by_index(fx, *i),
+            ByIndex {
+                index,
+                bank_relative,
+            } => {
+                let i = apply_bank_offset(context, compartment, *index, *bank_relative)
+                    .ok_or(FxParameterResolveError::OutOfRange)?;
+                resolve_parameter_by_index(fx, i)
+            }
         }
     }
 
@@ -1099,7 +1127,7 @@ impl VirtualFxParameter {
     pub fn index(&self) -> Option<u32> {
         use VirtualFxParameter::*;
         match self {
-            ByIndex(i) | ById(i) => Some(*i),
+            ByIndex { index: i, .. } | ById(i) => Some(*i),
             _ => None,
         }
     }
@@ -1119,7 +1147,11 @@ impl fmt::Display for VirtualFxParameter {
         match self {
             Dynamic(_) => f.write_str("<Dynamic>"),
             ByName(name) => write!(f, "\"{name}\""),
-            ByIndex(i) | ById(i) => write!(f, "#{}", i + 1),
+            ById(i) => write!(f, "#{}", i + 1),
+            ByIndex {
+                index,
+                bank_relative,
+            } => write_index_with_bank_suffix(f, *index, *bank_relative, ""),
         }
     }
 }
@@ -1250,13 +1282,17 @@ impl fmt::Display for VirtualTrack {
                 wild_match,
                 if *allow_multiple { " (all)" } else { "" }
             ),
-            ByIndex { index, scope } => {
+            ByIndex {
+                index,
+                scope,
+                bank_relative,
+            } => {
                 let suffix = match scope {
                     TrackScope::AllTracks => "",
                     TrackScope::TracksVisibleInTcp => " in TCP",
                     TrackScope::TracksVisibleInMcp => " in MCP",
                 };
-                write!(f, "#{}{}", index + 1, suffix)
+                write_index_with_bank_suffix(f, *index, *bank_relative, suffix)
             }
             FromClipColumn { .. } => f.write_str("From a clip column"),
         }
@@ -1436,8 +1472,15 @@ impl VirtualTrack {
             } => find_tracks_by_name(project, wild_match)
                 .take(if *allow_multiple { MAX_MULTIPLE } else { 1 })
                 .collect(),
-            ByIndex { index, scope } => {
-                let single = resolve_track_by_index(project, *index as i32, *scope)?;
+            ByIndex {
+                index,
+                scope,
+                bank_relative,
+            } => {
+                let index = apply_bank_offset(context, compartment, *index, *bank_relative)
+                    .and_then(|i| i32::try_from(i).ok())
+                    .ok_or(TrackResolveError::OutOfRange)?;
+                let single = resolve_track_by_index(project, index, *scope)?;
                 vec![single]
             }
             FromClipColumn {
@@ -1645,7 +1688,11 @@ pub enum VirtualChainFx {
         wild_match: WildMatch,
         allow_multiple: bool,
     },
-    ByIndex(u32),
+    ByIndex {
+        index: u32,
+        /// Adds the offset of the current bank to the index.
+        bank_relative: bool,
+    },
     /// This is the old default.
     ///
     /// The index comes into play as fallback whenever track is "<Selected>" or the GUID can't be
@@ -1671,7 +1718,11 @@ impl fmt::Display for VirtualChainFx {
                 wild_match,
                 if *allow_multiple { " (all)" } else { "" }
             ),
-            ByIdOrIndex(None, i) | ByIndex(i) => write!(f, "#{}", i + 1),
+            ByIdOrIndex(None, i) => write!(f, "#{}", i + 1),
+            ByIndex {
+                index,
+                bank_relative,
+            } => write_index_with_bank_suffix(f, *index, *bank_relative, ""),
             ByIdOrIndex(Some(guid), i) => {
                 write!(f, "{} ({})", guid.to_string_without_braces(), i + 1)
             }
@@ -1679,6 +1730,46 @@ impl fmt::Display for VirtualChainFx {
     }
 }
 
+/// Adds the offset of the current bank if the index is bank-relative.
+///
+/// Returns `None` if the resulting index is out of range.
+fn apply_bank_offset(
+    context: ExtendedProcessorContext,
+    compartment: CompartmentKind,
+    index: u32,
+    bank_relative: bool,
+) -> Option<u32> {
+    if !bank_relative {
+        return Some(index);
+    }
+    let offset = context
+        .control_context
+        .unit
+        .borrow()
+        .current_bank_offset(compartment);
+    index.checked_add(offset)
+}
+
+fn write_index_with_bank_suffix(
+    f: &mut Formatter<'_>,
+    index: u32,
+    bank_relative: bool,
+    suffix: &str,
+) -> fmt::Result {
+    if bank_relative {
+        write!(f, "#{}{} in bank", index + 1, suffix)
+    } else {
+        write!(f, "#{}{}", index + 1, suffix)
+    }
+}
+
+fn resolve_index_based_fxs(fx_chains: &[FxChain], index: u32) -> Vec<Fx> {
+    fx_chains
+        .iter()
+        .flat_map(|fx_chain| get_index_based_fx_on_chain(fx_chain, index))
+        .collect()
+}
+
 fn find_track_by_name(project: Project, name: &WildMatch) -> Option<Track> {
     project.tracks().find(|t| match t.name() {
         None => false,
@@ -1783,18 +1874,15 @@ impl VirtualChainFx {
             } => find_fxs_by_name(fx_chains, wild_match)
                 .take(if *allow_multiple { MAX_MULTIPLE } else { 1 })
                 .collect(),
-            ByIndex(index) | ByIdOrIndex(None, index) => fx_chains
-                .iter()
-                .flat_map(|fx_chain| {
-                    get_index_based_fx_on_chain(fx_chain, *index).map_err(|_| {
-                        FxResolveError::FxNotFound {
-                            guid: None,
-                            name: None,
-                            index: Some(*index),
-                        }
-                    })
-                })
-                .collect(),
+            ByIndex {
+                index,
+                bank_relative,
+            } => {
+                let index = apply_bank_offset(context, compartment, *index, *bank_relative)
+                    .ok_or(FxResolveError::OutOfRange)?;
+                resolve_index_based_fxs(fx_chains, index)
+            }
+            ByIdOrIndex(None, index) => resolve_index_based_fxs(fx_chains, *index),
             ByIdOrIndex(Some(guid), index) => {
                 let fx_not_found_error = || FxResolveError::FxNotFound {
                     guid: Some(*guid),
@@ -1901,7 +1989,7 @@ impl VirtualChainFx {
     pub fn index(&self) -> Option<u32> {
         use VirtualChainFx::*;
         match self {
-            ByIndex(i) | ByIdOrIndex(_, i) => Some(*i),
+            ByIndex { index: i, .. } | ByIdOrIndex(_, i) => Some(*i),
             ById(_, index_hint) => *index_hint,
             _ => None,
         }
@@ -0,0 +1,56 @@
+use crate::domain::ui_util::format_osc_message;
+use crate::domain::{VirtualControlElement, VirtualFeedbackValue, VirtualSourceValue};
+use rosc::OscMessage;
+use std::fmt::{Display, Formatter};
+
+/// A control message sent by a WebSocket client which acts as virtual controller device.
+///
+/// Only processed by units whose control input is set to "WebSocket".
+#[derive(Clone, PartialEq, Debug)]
+pub enum WebSocketControlMessage {
+    /// Addressed to a virtual control element.
+    ///
+    /// Controls main mappings with a matching virtual source directly, without the need for a
+    /// controller preset.
+    Virtual(VirtualSourceValue),
+    /// Addressed to a raw source address.
+    ///
+    /// Raw source addresses are modeled as OSC messages, so they are processed by mappings with
+    /// OSC sources. This makes it easy to port surfaces which used to speak OSC.
+    Raw(OscMessage),
+}
+
+impl Display for WebSocketControlMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebSocketControlMessage::Virtual(v) => v.fmt(f),
+            WebSocketControlMessage::Raw(msg) => f.write_str(&format_osc_message(msg)),
+        }
+    }
+}
+
+/// Feedback which is sent back to WebSocket clients, addressing the same elements that they use
+/// for control.
+#[derive(Clone, PartialEq, Debug)]
+pub enum WebSocketFeedbackValue {
+    Virtual(VirtualFeedbackValue),
+    Raw(OscMessage),
+}
+
+impl WebSocketFeedbackValue {
+    pub fn address(&self) -> WebSocketFeedbackAddress {
+        match self {
+            WebSocketFeedbackValue::Virtual(v) => {
+                WebSocketFeedbackAddress::Virtual(v.control_element())
+            }
+            WebSocketFeedbackValue::Raw(msg) => WebSocketFeedbackAddress::Raw(msg.addr.clone()),
+        }
+    }
+}
+
+/// The element which a WebSocket feedback value is addressed to.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum WebSocketFeedbackAddress {
+    Virtual(VirtualControlElement),
+    Raw(String),
+}
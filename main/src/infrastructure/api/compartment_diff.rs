@@ -0,0 +1,509 @@
+//! Structural diff and three-way merge of compartments in their persistence API representation.
+//!
+//! Mappings and groups are identified by their ID, parameters by their index. Entries without ID
+//! are identified by their position within the list (which is only reliable as long as nothing
+//! is inserted in front of them). Everything else is compared field by field. Arrays are treated
+//! as atomic values.
+use helgobox_api::persistence::Compartment;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+const MAPPINGS: &str = "mappings";
+const GROUPS: &str = "groups";
+const PARAMETERS: &str = "parameters";
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize)]
+pub struct CompartmentDiff {
+    pub mappings: Vec<EntryDiff>,
+    pub groups: Vec<EntryDiff>,
+    pub parameters: Vec<EntryDiff>,
+    /// Changes of all other compartment properties, e.g. default group, common Lua code or notes.
+    pub properties: Vec<FieldChange>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum EntryDiff {
+    Added {
+        key: String,
+        value: Value,
+    },
+    Removed {
+        key: String,
+        value: Value,
+    },
+    Changed {
+        key: String,
+        changes: Vec<FieldChange>,
+    },
+}
+
+/// Change of a single field, identified by a JSON pointer relative to the containing entry.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct FieldChange {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+/// An update of a compartment which can be merged into a locally modified copy.
+///
+/// See [`merge_compartments`].
+#[derive(Deserialize)]
+pub struct CompartmentUpdate {
+    /// The version on which the local copy is based, e.g. the preset as it was originally shipped.
+    pub base: Compartment,
+    /// The updated version, e.g. a new release of the preset.
+    pub theirs: Compartment,
+}
+
+#[derive(Serialize)]
+pub struct CompartmentMerge {
+    pub compartment: Compartment,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// A field which has been changed differently in both compartments.
+///
+/// The merge result always contains "our" value.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct MergeConflict {
+    /// `mappings`, `groups`, `parameters` or `properties`.
+    pub section: &'static str,
+    /// Key of the mapping, group or parameter. `None` for compartment properties.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ours: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theirs: Option<Value>,
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.section)?;
+        if let Some(key) = &self.key {
+            write!(f, " \"{key}\"")?;
+        }
+        write!(f, ": {}", self.path)
+    }
+}
+
+/// Computes the differences between the old and the new compartment.
+pub fn diff_compartments(
+    old: &Compartment,
+    new: &Compartment,
+) -> serde_json::Result<CompartmentDiff> {
+    let mut old = Sections::from_compartment(old)?;
+    let mut new = Sections::from_compartment(new)?;
+    let diff = CompartmentDiff {
+        mappings: diff_entries(old.take(MAPPINGS), new.take(MAPPINGS)),
+        groups: diff_entries(old.take(GROUPS), new.take(GROUPS)),
+        parameters: diff_entries(old.take(PARAMETERS), new.take(PARAMETERS)),
+        properties: {
+            let mut changes = vec![];
+            diff_values(
+                Some(&old.properties),
+                Some(&new.properties),
+                "",
+                &mut changes,
+            );
+            changes
+        },
+    };
+    Ok(diff)
+}
+
+/// Applies the changes between `base` and `theirs` onto `ours`.
+///
+/// Typical use case: `base` is the preset as it was originally shipped, `theirs` is an updated
+/// version of that preset and `ours` is a locally customized copy of the original preset.
+/// Whenever both sides changed the same field in different ways, our value wins and the conflict
+/// is reported.
+pub fn merge_compartments(
+    base: &Compartment,
+    ours: &Compartment,
+    theirs: &Compartment,
+) -> serde_json::Result<CompartmentMerge> {
+    let mut base = Sections::from_compartment(base)?;
+    let mut ours = Sections::from_compartment(ours)?;
+    let mut theirs = Sections::from_compartment(theirs)?;
+    let mut conflicts = vec![];
+    let mut merged = Map::new();
+    for section in [PARAMETERS, GROUPS, MAPPINGS] {
+        let entries = merge_entries(
+            section,
+            base.take(section),
+            ours.take(section),
+            theirs.take(section),
+            &mut conflicts,
+        );
+        if !entries.is_empty() {
+            merged.insert(section.to_string(), Value::Array(entries));
+        }
+    }
+    let mut property_conflicts = vec![];
+    let properties = merge_values(
+        Some(&base.properties),
+        Some(&ours.properties),
+        Some(&theirs.properties),
+        "",
+        &mut property_conflicts,
+    );
+    conflicts.extend(
+        property_conflicts
+            .into_iter()
+            .map(|c| c.into_merge_conflict("properties", None)),
+    );
+    if let Some(Value::Object(properties)) = properties {
+        merged.extend(properties);
+    }
+    let merge = CompartmentMerge {
+        compartment: serde_json::from_value(Value::Object(merged))?,
+        conflicts,
+    };
+    Ok(merge)
+}
+
+/// A compartment split into its keyed lists and the remaining properties.
+struct Sections {
+    entries: Vec<(&'static str, Vec<(String, Value)>)>,
+    properties: Value,
+}
+
+impl Sections {
+    fn from_compartment(compartment: &Compartment) -> serde_json::Result<Self> {
+        let Value::Object(mut properties) = serde_json::to_value(compartment)? else {
+            return Ok(Self {
+                entries: vec![],
+                properties: Value::Object(Map::new()),
+            });
+        };
+        let entries = [(MAPPINGS, "id"), (GROUPS, "id"), (PARAMETERS, "index")]
+            .into_iter()
+            .map(|(section, key_prop)| {
+                let list = match properties.remove(section) {
+                    Some(Value::Array(list)) => list,
+                    _ => vec![],
+                };
+                (section, key_entries(list, key_prop))
+            })
+            .collect();
+        let sections = Self {
+            entries,
+            properties: Value::Object(properties),
+        };
+        Ok(sections)
+    }
+
+    fn take(&mut self, section: &str) -> Vec<(String, Value)> {
+        self.entries
+            .iter_mut()
+            .find(|(s, _)| *s == section)
+            .map(|(_, entries)| std::mem::take(entries))
+            .unwrap_or_default()
+    }
+}
+
+fn key_entries(list: Vec<Value>, key_prop: &str) -> Vec<(String, Value)> {
+    list.into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let key = match entry.get(key_prop) {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Number(n)) => n.to_string(),
+                _ => format!("#{i}"),
+            };
+            (key, entry)
+        })
+        .collect()
+}
+
+fn find_entry<'a>(entries: &'a [(String, Value)], key: &str) -> Option<&'a Value> {
+    entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+fn diff_entries(old: Vec<(String, Value)>, new: Vec<(String, Value)>) -> Vec<EntryDiff> {
+    let mut diffs = vec![];
+    for (key, old_value) in &old {
+        match find_entry(&new, key) {
+            None => diffs.push(EntryDiff::Removed {
+                key: key.clone(),
+                value: old_value.clone(),
+            }),
+            Some(new_value) => {
+                let mut changes = vec![];
+                diff_values(Some(old_value), Some(new_value), "", &mut changes);
+                if !changes.is_empty() {
+                    diffs.push(EntryDiff::Changed {
+                        key: key.clone(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+    for (key, new_value) in new {
+        if find_entry(&old, &key).is_none() {
+            diffs.push(EntryDiff::Added {
+                key,
+                value: new_value,
+            });
+        }
+    }
+    diffs
+}
+
+fn diff_values(
+    old: Option<&Value>,
+    new: Option<&Value>,
+    path: &str,
+    changes: &mut Vec<FieldChange>,
+) {
+    if old == new {
+        return;
+    }
+    if let (Some(Value::Object(old)), Some(Value::Object(new))) = (old, new) {
+        for key in union_of_keys(old, new) {
+            diff_values(old.get(key), new.get(key), &child_path(path, key), changes);
+        }
+        return;
+    }
+    changes.push(FieldChange {
+        path: path.to_string(),
+        old: old.cloned(),
+        new: new.cloned(),
+    });
+}
+
+fn merge_entries(
+    section: &'static str,
+    base: Vec<(String, Value)>,
+    ours: Vec<(String, Value)>,
+    theirs: Vec<(String, Value)>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<Value> {
+    // Our order wins, entries only present in their version are appended in their order
+    let keys: Vec<&String> = ours
+        .iter()
+        .map(|(k, _)| k)
+        .chain(
+            theirs
+                .iter()
+                .map(|(k, _)| k)
+                .filter(|k| find_entry(&ours, k).is_none()),
+        )
+        .collect();
+    let mut merged = vec![];
+    for key in keys {
+        let mut entry_conflicts = vec![];
+        let value = merge_values(
+            find_entry(&base, key),
+            find_entry(&ours, key),
+            find_entry(&theirs, key),
+            "",
+            &mut entry_conflicts,
+        );
+        conflicts.extend(
+            entry_conflicts
+                .into_iter()
+                .map(|c| c.into_merge_conflict(section, Some(key.clone()))),
+        );
+        merged.extend(value);
+    }
+    merged
+}
+
+struct ValueConflict {
+    path: String,
+    base: Option<Value>,
+    ours: Option<Value>,
+    theirs: Option<Value>,
+}
+
+impl ValueConflict {
+    fn into_merge_conflict(self, section: &'static str, key: Option<String>) -> MergeConflict {
+        MergeConflict {
+            section,
+            key,
+            path: self.path,
+            base: self.base,
+            ours: self.ours,
+            theirs: self.theirs,
+        }
+    }
+}
+
+fn merge_values(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    path: &str,
+    conflicts: &mut Vec<ValueConflict>,
+) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+    if let (Some(Value::Object(o)), Some(Value::Object(t))) = (ours, theirs) {
+        let b = match base {
+            Some(Value::Object(b)) => Some(b),
+            _ => None,
+        };
+        let mut merged = Map::new();
+        for key in union_of_keys(o, t) {
+            let value = merge_values(
+                b.and_then(|b| b.get(key)),
+                o.get(key),
+                t.get(key),
+                &child_path(path, key),
+                conflicts,
+            );
+            if let Some(v) = value {
+                merged.insert(key.clone(), v);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+    conflicts.push(ValueConflict {
+        path: path.to_string(),
+        base: base.cloned(),
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    ours.cloned()
+}
+
+fn union_of_keys<'a>(a: &'a Map<String, Value>, b: &'a Map<String, Value>) -> Vec<&'a String> {
+    a.keys()
+        .chain(b.keys().filter(|k| !a.contains_key(*k)))
+        .collect()
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    let escaped_key = key.replace('~', "~0").replace('/', "~1");
+    format!("{path}/{escaped_key}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn compartment(json: Value) -> Compartment {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn diff() {
+        // Given
+        let old = compartment(json!({
+            "mappings": [
+                { "id": "a", "name": "A", "glue": { "reverse": false } },
+                { "id": "b", "name": "B" }
+            ],
+            "notes": "Old"
+        }));
+        let new = compartment(json!({
+            "mappings": [
+                { "id": "a", "name": "A", "glue": { "reverse": true } },
+                { "id": "c", "name": "C" }
+            ],
+            "notes": "New"
+        }));
+        // When
+        let diff = diff_compartments(&old, &new).unwrap();
+        // Then
+        assert_eq!(
+            diff.mappings,
+            vec![
+                EntryDiff::Changed {
+                    key: "a".to_string(),
+                    changes: vec![FieldChange {
+                        path: "/glue/reverse".to_string(),
+                        old: Some(json!(false)),
+                        new: Some(json!(true)),
+                    }]
+                },
+                EntryDiff::Removed {
+                    key: "b".to_string(),
+                    value: json!({ "id": "b", "name": "B" })
+                },
+                EntryDiff::Added {
+                    key: "c".to_string(),
+                    value: json!({ "id": "c", "name": "C" })
+                },
+            ]
+        );
+        assert_eq!(
+            diff.properties,
+            vec![FieldChange {
+                path: "/notes".to_string(),
+                old: Some(json!("Old")),
+                new: Some(json!("New")),
+            }]
+        );
+        assert!(diff.groups.is_empty());
+        assert!(diff.parameters.is_empty());
+    }
+
+    #[test]
+    fn merge() {
+        // Given
+        let base = compartment(json!({
+            "mappings": [
+                { "id": "a", "name": "A", "glue": { "reverse": false, "step_factor_interval": [1, 5] } },
+                { "id": "b", "name": "B" },
+                { "id": "c", "name": "C" }
+            ]
+        }));
+        let ours = compartment(json!({
+            "mappings": [
+                { "id": "a", "name": "My A", "glue": { "reverse": false, "step_factor_interval": [1, 2] } },
+                { "id": "b", "name": "B" },
+                { "id": "c", "name": "C" },
+                { "id": "mine", "name": "Mine" }
+            ]
+        }));
+        let theirs = compartment(json!({
+            "mappings": [
+                { "id": "a", "name": "A", "glue": { "reverse": true, "step_factor_interval": [1, 10] } },
+                { "id": "c", "name": "New C" },
+                { "id": "theirs", "name": "Theirs" }
+            ]
+        }));
+        // When
+        let merge = merge_compartments(&base, &ours, &theirs).unwrap();
+        // Then
+        let merged_mappings = serde_json::to_value(merge.compartment.mappings).unwrap();
+        assert_eq!(
+            merged_mappings,
+            json!([
+                { "id": "a", "name": "My A", "glue": { "reverse": true, "step_factor_interval": [1, 2] } },
+                { "id": "c", "name": "New C" },
+                { "id": "mine", "name": "Mine" },
+                { "id": "theirs", "name": "Theirs" }
+            ])
+        );
+        assert_eq!(
+            merge.conflicts,
+            vec![MergeConflict {
+                section: "mappings",
+                key: Some("a".to_string()),
+                path: "/glue/step_factor_interval".to_string(),
+                base: Some(json!([1, 5])),
+                ours: Some(json!([1, 2])),
+                theirs: Some(json!([1, 10])),
+            }]
+        );
+    }
+}
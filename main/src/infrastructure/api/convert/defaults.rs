@@ -31,6 +31,7 @@ pub const TARGET_TRACK_MUST_BE_SELECTED: bool = false;
 pub const TARGET_FX_MUST_HAVE_FOCUS: bool = false;
 pub const TARGET_TRACK_SELECTED_ALLOW_MULTIPLE: bool = false;
 pub const TARGET_BY_NAME_ALLOW_MULTIPLE: bool = false;
+pub const TARGET_BANK_RELATIVE: bool = false;
 pub const TARGET_BOOKMARK_SET_TIME_SELECTION: bool = false;
 pub const TARGET_BOOKMARK_SET_LOOP_POINTS: bool = false;
 pub const TARGET_POLL_FOR_FEEDBACK: bool = true;
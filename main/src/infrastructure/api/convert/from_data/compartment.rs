@@ -34,9 +34,20 @@ pub fn convert_compartment(
             let v = convert_multiple(data.mappings, |m| convert_mapping(m, style))?;
             style.required_value(v)
         },
+        mapping_templates: style.required_value(data.mapping_templates),
         common_lua: style.required_value(data.common_lua),
         custom_data: style.required_value(data.custom_data.into_iter().collect()),
         notes: style.required_value(data.notes),
+        bank_size: style.required_value(data.banks.size),
+        banks: {
+            let banks: Vec<_> = data
+                .banks
+                .banks
+                .into_iter()
+                .map(|b| persistence::Bank { name: b.name })
+                .collect();
+            style.required_value(banks)
+        },
         unknown_props: Default::default(),
     };
     Ok(compartment)
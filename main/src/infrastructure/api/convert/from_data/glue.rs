@@ -58,19 +58,7 @@ pub fn convert_glue(
             };
             style.required_value(v)
         },
-        takeover_mode: {
-            use persistence::TakeoverMode as T;
-            use TakeoverMode::*;
-            let v = match data.takeover_mode {
-                Off => T::Off,
-                PickupTolerant => T::PickUpTolerant,
-                Pickup => T::PickUp,
-                LongTimeNoSee => T::LongTimeNoSee,
-                Parallel => T::Parallel,
-                CatchUp => T::CatchUp,
-            };
-            style.required_value(v)
-        },
+        takeover_mode: style.required_value(convert_takeover_mode(data.takeover_mode)),
         round_target_value: style.required_value_with_default(
             data.round_target_value,
             defaults::GLUE_ROUND_TARGET_VALUE,
@@ -209,11 +197,24 @@ fn convert_absolute_mode(
     style.required_value(mode)
 }
 
-fn convert_unit_interval(min: UnitValue, max: UnitValue) -> persistence::Interval<f64> {
+pub fn convert_takeover_mode(mode: TakeoverMode) -> persistence::TakeoverMode {
+    use persistence::TakeoverMode as T;
+    use TakeoverMode::*;
+    match mode {
+        Off => T::Off,
+        PickupTolerant => T::PickUpTolerant,
+        Pickup => T::PickUp,
+        LongTimeNoSee => T::LongTimeNoSee,
+        Parallel => T::Parallel,
+        CatchUp => T::CatchUp,
+    }
+}
+
+pub fn convert_unit_interval(min: UnitValue, max: UnitValue) -> persistence::Interval<f64> {
     persistence::Interval(min.get(), max.get())
 }
 
-fn convert_virtual_color(v: VirtualColor) -> persistence::VirtualColor {
+pub fn convert_virtual_color(v: VirtualColor) -> persistence::VirtualColor {
     use persistence::VirtualColor as T;
     use VirtualColor::*;
     match v {
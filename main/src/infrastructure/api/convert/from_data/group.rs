@@ -1,8 +1,9 @@
 use crate::infrastructure::api::convert::from_data::{
-    convert_activation_condition, convert_tags, ConversionStyle,
+    convert_activation_condition, convert_feedback_policy, convert_tags, convert_takeover_mode,
+    convert_track_descriptor, convert_unit_interval, convert_virtual_color, ConversionStyle,
 };
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
-use crate::infrastructure::data::GroupModelData;
+use crate::infrastructure::data::{GroupDefaultsData, GroupModelData};
 use helgobox_api::persistence;
 
 pub fn convert_group(
@@ -18,6 +19,13 @@ pub fn convert_group(
             }
         },
         name: style.required_value(data.name),
+        parent: {
+            if data.parent.is_empty() {
+                None
+            } else {
+                Some(data.parent.into())
+            }
+        },
         tags: convert_tags(&data.tags, style),
         control_enabled: style.required_value_with_default(
             data.enabled_data.control_is_enabled,
@@ -28,6 +36,39 @@ pub fn convert_group(
             defaults::GROUP_FEEDBACK_ENABLED,
         ),
         activation_condition: convert_activation_condition(data.activation_condition_data),
+        defaults: if data.defaults == GroupDefaultsData::default() {
+            None
+        } else {
+            Some(convert_group_defaults(data.defaults))
+        },
     };
     Ok(group)
 }
+
+fn convert_group_defaults(data: GroupDefaultsData) -> persistence::GroupDefaults {
+    persistence::GroupDefaults {
+        takeover_mode: data.takeover_mode.map(convert_takeover_mode),
+        step_size_interval: data
+            .min_step_size
+            .zip(data.max_step_size)
+            .map(|(min, max)| convert_unit_interval(min, max)),
+        step_factor_interval: data
+            .min_step_factor
+            .zip(data.max_step_factor)
+            .map(|(min, max)| persistence::Interval(min.get(), max.get())),
+        feedback_color: data.feedback_color.map(convert_virtual_color),
+        feedback_background_color: data.feedback_background_color.map(convert_virtual_color),
+        source_channel: data.source_channel.map(|ch| ch.get()),
+        target_track: data.target_track.and_then(|track_data| {
+            let clip_column = data.target_clip_column.unwrap_or_default();
+            // Always explicit because "<This>" would otherwise be omitted
+            convert_track_descriptor(
+                track_data,
+                false,
+                &clip_column,
+                ConversionStyle::IncludeDefaultValues,
+            )
+        }),
+        feedback_policy: data.feedback_policy.map(convert_feedback_policy),
+    }
+}
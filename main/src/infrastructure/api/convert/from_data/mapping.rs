@@ -1,12 +1,18 @@
 use crate::application::{
-    LifecycleMidiMessageModel, LifecycleModel, MappingExtensionModel, RawMidiMessage,
+    LifecycleMidiMessageModel, LifecycleModel, LifecycleOscMessageModel, MappingExtensionModel,
+    RawMidiMessage,
+};
+use crate::domain::{
+    ControlInput, FeedbackOutput, LifecycleOscArg, MidiControlInput, MidiDestination,
 };
 use crate::infrastructure::api::convert::from_data::{
     convert_activation_condition, convert_glue, convert_source, convert_tags, convert_target,
     ConversionStyle, NewSourceProps,
 };
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
-use crate::infrastructure::data::MappingModelData;
+use crate::infrastructure::data::{
+    ControlDeviceId, FeedbackDeviceId, FeedbackPolicyData, MappingModelData,
+};
 use helgobox_api::persistence;
 use helgobox_api::persistence::LifecycleHook;
 
@@ -46,6 +52,15 @@ pub fn convert_mapping(
         glue: style.required_value(convert_glue(data.mode, style)?),
         target: style.required_value(convert_target(data.target, style)?),
         success_audio_feedback: data.success_audio_feedback,
+        feedback_policy: style.required_value(convert_feedback_policy(data.feedback_policy)),
+        control_input: data
+            .control_device_id
+            .map(|id| convert_control_input(&id))
+            .transpose()?,
+        feedback_output: data
+            .feedback_device_id
+            .map(|id| convert_feedback_output(&id))
+            .transpose()?,
         unprocessed: style.optional_value(advanced.unprocessed),
     };
     Ok(mapping)
@@ -123,10 +138,56 @@ fn convert_lifecycle_model(
                 .collect();
             style.required_value(actions?)
         },
+        send_osc_feedback: style.required_value(
+            lifecycle_model
+                .send_osc_feedback
+                .into_iter()
+                .map(convert_lifecycle_osc_message_model)
+                .collect(),
+        ),
+        set_target_value: lifecycle_model.set_target_value,
+        set_parameters: style.required_value(
+            lifecycle_model
+                .set_parameters
+                .into_iter()
+                .map(|p| persistence::SetParameterAction {
+                    parameter: match p.key {
+                        None => persistence::ParamRef::Index(p.index.get()),
+                        Some(key) => persistence::ParamRef::Key(key),
+                    },
+                    value: p.value,
+                })
+                .collect(),
+        ),
+        lua: lifecycle_model.lua,
     };
     Ok(style.required_value(hook))
 }
 
+fn convert_lifecycle_osc_message_model(
+    model: LifecycleOscMessageModel,
+) -> persistence::SendOscFeedbackAction {
+    use persistence::OscArgValue as T;
+    persistence::SendOscFeedbackAction {
+        address: model.address,
+        arguments: if model.arguments.is_empty() {
+            None
+        } else {
+            let args = model
+                .arguments
+                .into_iter()
+                .map(|arg| match arg {
+                    LifecycleOscArg::Bool(v) => T::Bool(v),
+                    LifecycleOscArg::Int(v) => T::Int(v),
+                    LifecycleOscArg::Float(v) => T::Float(v),
+                    LifecycleOscArg::String(v) => T::String(v),
+                })
+                .collect();
+            Some(args)
+        },
+    }
+}
+
 fn convert_lifecycle_midi_message_model(
     model: LifecycleMidiMessageModel,
 ) -> ConversionResult<persistence::SendMidiFeedbackAction> {
@@ -147,3 +208,43 @@ fn convert_raw_midi_msg(msg: RawMidiMessage) -> ConversionResult<persistence::Ra
     };
     Ok(v)
 }
+
+pub fn convert_feedback_policy(data: FeedbackPolicyData) -> persistence::FeedbackPolicy {
+    persistence::FeedbackPolicy {
+        max_rate: data.max_rate,
+        min_delta: data.min_delta,
+        debounce: data.debounce_millis,
+        settle_delay: data.settle_delay_millis,
+    }
+}
+
+fn convert_control_input(
+    id: &ControlDeviceId,
+) -> ConversionResult<persistence::MappingControlInput> {
+    use persistence::MappingControlInput as T;
+    let input = match id.to_control_input().map_err(anyhow::Error::msg)? {
+        ControlInput::Midi(MidiControlInput::FxInput) => T::FxInput,
+        ControlInput::Midi(MidiControlInput::Device(dev_id)) => T::MidiDevice { id: dev_id.get() },
+        ControlInput::Osc(dev_id) => T::OscDevice {
+            id: dev_id.to_string(),
+        },
+        ControlInput::Keyboard => T::Keyboard,
+        ControlInput::WebSocket => T::WebSocket,
+    };
+    Ok(input)
+}
+
+fn convert_feedback_output(
+    id: &FeedbackDeviceId,
+) -> ConversionResult<persistence::MappingFeedbackOutput> {
+    use persistence::MappingFeedbackOutput as T;
+    let output = match id.to_feedback_output().map_err(anyhow::Error::msg)? {
+        FeedbackOutput::Midi(MidiDestination::FxOutput) => T::FxOutput,
+        FeedbackOutput::Midi(MidiDestination::Device(dev_id)) => T::MidiDevice { id: dev_id.get() },
+        FeedbackOutput::Osc(dev_id) => T::OscDevice {
+            id: dev_id.to_string(),
+        },
+        FeedbackOutput::WebSocket => T::WebSocket,
+    };
+    Ok(output)
+}
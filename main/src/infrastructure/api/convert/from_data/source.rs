@@ -1,4 +1,8 @@
-use crate::application::{MidiSourceType, ReaperSourceType, SourceCategory};
+use crate::application::{
+    MidiSourceType, ReaperSourceType, SourceCategory, DEFAULT_AUDIO_ATTACK_MILLIS,
+    DEFAULT_AUDIO_CHANNEL_COUNT, DEFAULT_AUDIO_RELEASE_MILLIS, DEFAULT_LFO_FREQUENCY,
+    DEFAULT_SEQUENCER_BEATS_PER_STEP,
+};
 use crate::infrastructure::api::convert::from_data::{
     convert_control_element_id, convert_keystroke, convert_osc_argument, ConversionStyle,
 };
@@ -210,6 +214,18 @@ pub fn convert_source(
                         }
                     }
                 }
+                SysExDisplay => {
+                    let s = persistence::SysExDisplaySource {
+                        template: data.sysex_display_template,
+                        line: data.line,
+                        length: data.sysex_display_length,
+                        alignment: style.required_value(data.sysex_display_alignment),
+                        padding: data.sysex_display_padding,
+                        encoding: style.required_value(data.sysex_display_encoding),
+                        character_map: style.required_value(data.sysex_display_character_map),
+                    };
+                    persistence::Source::SysExDisplay(s)
+                }
             }
         }
         Osc => {
@@ -244,6 +260,37 @@ pub fn convert_source(
                     })
                 }
                 Speech => persistence::Source::Speech,
+                Lfo => persistence::Source::Lfo(persistence::LfoSource {
+                    waveform: style.required_value(data.lfo_waveform),
+                    frequency: style.required_value_with_default(
+                        data.lfo_frequency.unwrap_or(DEFAULT_LFO_FREQUENCY),
+                        DEFAULT_LFO_FREQUENCY,
+                    ),
+                    beats_per_cycle: data.lfo_beats_per_cycle,
+                    phase_offset: style.required_value(data.lfo_phase_offset),
+                    retrigger_parameter_index: data.lfo_retrigger_parameter_index.map(|i| i.get()),
+                }),
+                Sequencer => persistence::Source::Sequencer(persistence::SequencerSource {
+                    steps: data.sequencer_steps.as_ref().map(|steps| {
+                        steps
+                            .iter()
+                            .map(|s| persistence::SequencerStep {
+                                value: s.value.get(),
+                                gate: style.required_value_with_default(s.gate, true),
+                            })
+                            .collect()
+                    }),
+                    beats_per_step: style.required_value_with_default(
+                        data.sequencer_beats_per_step
+                            .unwrap_or(DEFAULT_SEQUENCER_BEATS_PER_STEP),
+                        DEFAULT_SEQUENCER_BEATS_PER_STEP,
+                    ),
+                    swing: style.required_value(data.sequencer_swing),
+                    direction: style.required_value(data.sequencer_direction),
+                    step_value_parameter_index: data
+                        .sequencer_step_value_parameter_index
+                        .map(|i| i.get()),
+                }),
             }
         }
         Virtual => {
@@ -259,6 +306,46 @@ pub fn convert_source(
             };
             persistence::Source::Key(s)
         }
+        Compound => {
+            let s = persistence::CompoundSource {
+                compound_kind: style.required_value(data.compound_kind),
+                members: data
+                    .compound_members
+                    .into_iter()
+                    .map(|member| {
+                        // Feedback behavior is a matter of the compound source, not its members
+                        let props = NewSourceProps {
+                            prevent_echo_feedback: false,
+                            send_feedback_after_control: false,
+                        };
+                        convert_source(member, props, style)
+                    })
+                    .collect::<ConversionResult<_>>()?,
+                timeout: data.compound_timeout_millis,
+            };
+            persistence::Source::Compound(s)
+        }
+        Audio => {
+            let s = persistence::AudioSource {
+                channel: style.required_value(data.audio_channel),
+                channel_count: style.optional_value_with_default(
+                    data.audio_channel_count,
+                    DEFAULT_AUDIO_CHANNEL_COUNT,
+                ),
+                detector: style.required_value(data.audio_detector),
+                attack: style.optional_value_with_default(
+                    data.audio_attack_millis,
+                    DEFAULT_AUDIO_ATTACK_MILLIS,
+                ),
+                release: style.optional_value_with_default(
+                    data.audio_release_millis,
+                    DEFAULT_AUDIO_RELEASE_MILLIS,
+                ),
+                threshold: data.audio_threshold_db,
+                analysis: style.required_value(data.audio_analysis),
+            };
+            persistence::Source::Audio(s)
+        }
     };
     Ok(source)
 }
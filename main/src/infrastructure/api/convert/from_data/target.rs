@@ -30,13 +30,13 @@ use helgobox_api::persistence::{
     LoadFxSnapshotTarget, LoadMappingSnapshotTarget, LoadPotPresetTarget, MappingModification,
     ModifyMappingTarget, MouseTarget, PlayRateTarget, PreviewPotPresetTarget, ReaperActionTarget,
     RouteAutomationModeTarget, RouteMonoStateTarget, RouteMuteStateTarget, RoutePanTarget,
-    RoutePhaseTarget, RouteTouchStateTarget, RouteVolumeTarget, SeekTarget, SendMidiTarget,
-    SendOscTarget, SetTargetToLastTouchedMappingModification, TakeMappingSnapshotTarget,
-    TempoTarget, TrackArmStateTarget, TrackAutomationModeTarget, TrackAutomationTouchStateTarget,
-    TrackMonitoringModeTarget, TrackMuteStateTarget, TrackPanTarget, TrackParentSendStateTarget,
-    TrackPeakTarget, TrackPhaseTarget, TrackSelectionStateTarget, TrackSoloStateTarget,
-    TrackToolTarget, TrackVisibilityTarget, TrackVolumeTarget, TrackWidthTarget,
-    TransportActionTarget,
+    RoutePhaseTarget, RouteTouchStateTarget, RouteVolumeTarget, SeekTarget, SelectBankTarget,
+    SendMidiTarget, SendOscTarget, SetTargetToLastTouchedMappingModification,
+    TakeMappingSnapshotTarget, TempoTarget, TrackArmStateTarget, TrackAutomationModeTarget,
+    TrackAutomationTouchStateTarget, TrackMonitoringModeTarget, TrackMuteStateTarget,
+    TrackPanTarget, TrackParentSendStateTarget, TrackPeakTarget, TrackPhaseTarget,
+    TrackSelectionStateTarget, TrackSoloStateTarget, TrackToolTarget, TrackVisibilityTarget,
+    TrackVolumeTarget, TrackWidthTarget, TransportActionTarget,
 };
 
 pub fn convert_target(
@@ -747,6 +747,7 @@ fn convert_real_target(
             },
             group: style.required_value(data.group_id.into()),
         }),
+        SelectBank => T::SelectBank(SelectBankTarget { commons }),
         BrowsePotFilterItems => T::BrowsePotFilterItems(BrowsePotFilterItemsTarget {
             commons,
             item_kind: style.required_value(data.pot_filter_item_kind),
@@ -864,7 +865,7 @@ fn convert_virtual_target(data: TargetModelData, style: ConversionStyle) -> pers
     })
 }
 
-fn convert_track_descriptor(
+pub fn convert_track_descriptor(
     data: TrackData,
     only_if_track_selected: bool,
     clip_column: &helgobox_api::persistence::PlaytimeColumnDescriptor,
@@ -910,11 +911,17 @@ fn convert_track_descriptor(
                 defaults::TARGET_BY_NAME_ALLOW_MULTIPLE,
             ),
         },
-        ByIndex | ByIndexTcp | ByIndexMcp => T::ByIndex {
-            commons,
-            index: props.index,
-            scope: style.optional_value(props.r#type.virtual_track_scope()),
-        },
+        ByIndex | ByIndexTcp | ByIndexMcp | ByIndexInBank | ByIndexInBankTcp | ByIndexInBankMcp => {
+            T::ByIndex {
+                commons,
+                index: props.index,
+                scope: style.optional_value(props.r#type.virtual_track_scope()),
+                bank_relative: style.required_value_with_default(
+                    props.r#type.is_bank_relative(),
+                    defaults::TARGET_BANK_RELATIVE,
+                ),
+            }
+        }
         FromClipColumn => T::FromClipColumn {
             commons,
             column: props.clip_column,
@@ -971,9 +978,13 @@ fn convert_fx_parameter_descriptor(
             index: props.index,
             fx: convert_fx_descriptor(data, style),
         },
-        ByIndex => T::ByIndex {
+        ByIndex | ByIndexInBank => T::ByIndex {
             index: props.index,
             fx: convert_fx_descriptor(data, style),
+            bank_relative: style.required_value_with_default(
+                props.r#type == ByIndexInBank,
+                defaults::TARGET_BANK_RELATIVE,
+            ),
         },
     }
 }
@@ -1059,10 +1070,14 @@ fn convert_fx_descriptor(
             ),
             chain: convert_fx_chain_descriptor(data, style),
         },
-        ByIndex => T::ByIndex {
+        ByIndex | ByIndexInBank => T::ByIndex {
             commons,
             index: props.index,
             chain: convert_fx_chain_descriptor(data, style),
+            bank_relative: style.required_value_with_default(
+                props.r#type == ByIndexInBank,
+                defaults::TARGET_BANK_RELATIVE,
+            ),
         },
     };
     style.required_value(v)
@@ -1,6 +1,8 @@
 use base::hash_util::{convert_into_other_hash_map, NonCryptoHashMap};
 
-use crate::domain::{CompartmentKind, CompartmentParamIndex, ParamSetting};
+use crate::domain::{
+    BankDefinition, CompartmentBanks, CompartmentKind, CompartmentParamIndex, ParamSetting,
+};
 use crate::infrastructure::api::convert::to_data::group::convert_group;
 use crate::infrastructure::api::convert::to_data::parameter::convert_parameter;
 use crate::infrastructure::api::convert::to_data::{convert_mapping, ApiToDataConversionContext};
@@ -66,6 +68,7 @@ pub fn convert_compartment(
         mappings: convert_multiple(compartment_content.mappings.unwrap_or_default(), |m| {
             convert_mapping(m, &context)
         })?,
+        mapping_templates: compartment_content.mapping_templates.unwrap_or_default(),
         parameters: context
             .parameters
             .iter()
@@ -78,6 +81,15 @@ pub fn convert_compartment(
             .unwrap_or_default(),
         common_lua: compartment_content.common_lua.unwrap_or_default(),
         notes: compartment_content.notes.unwrap_or_default(),
+        banks: CompartmentBanks {
+            size: compartment_content.bank_size.unwrap_or_default(),
+            banks: compartment_content
+                .banks
+                .unwrap_or_default()
+                .into_iter()
+                .map(|b| BankDefinition { name: b.name })
+                .collect(),
+        },
     };
     Ok(data)
 }
@@ -150,18 +150,7 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
             .round_target_value
             .unwrap_or(defaults::GLUE_ROUND_TARGET_VALUE),
         scale_mode_enabled: false,
-        takeover_mode: {
-            use helgoboss_learn::TakeoverMode as T;
-            use TakeoverMode::*;
-            match g.takeover_mode.unwrap_or_default() {
-                Off => T::Off,
-                PickUpTolerant => T::PickupTolerant,
-                PickUp => T::Pickup,
-                LongTimeNoSee => T::LongTimeNoSee,
-                Parallel => T::Parallel,
-                CatchUp => T::CatchUp,
-            }
-        },
+        takeover_mode: convert_takeover_mode(g.takeover_mode.unwrap_or_default()),
         button_usage: {
             use helgoboss_learn::ButtonUsage as T;
             if let Some(f) = g.button_filter {
@@ -215,7 +204,20 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
     Ok(data)
 }
 
-fn convert_step_factor_interval(
+pub fn convert_takeover_mode(mode: TakeoverMode) -> helgoboss_learn::TakeoverMode {
+    use helgoboss_learn::TakeoverMode as T;
+    use TakeoverMode::*;
+    match mode {
+        Off => T::Off,
+        PickUpTolerant => T::PickupTolerant,
+        PickUp => T::Pickup,
+        LongTimeNoSee => T::LongTimeNoSee,
+        Parallel => T::Parallel,
+        CatchUp => T::CatchUp,
+    }
+}
+
+pub fn convert_step_factor_interval(
     i: Interval<i32>,
 ) -> ConversionResult<helgoboss_learn::Interval<DiscreteIncrement>> {
     let result = helgoboss_learn::Interval::try_new(
@@ -238,7 +240,7 @@ fn convert_step_size_interval(
     Ok(result)
 }
 
-fn convert_unit_value_interval(
+pub fn convert_unit_value_interval(
     interval: Interval<f64>,
 ) -> ConversionResult<helgoboss_learn::Interval<UnitValue>> {
     let result = helgoboss_learn::Interval::try_new(
@@ -249,7 +251,7 @@ fn convert_unit_value_interval(
     Ok(result)
 }
 
-fn convert_virtual_color(color: VirtualColor) -> helgoboss_learn::VirtualColor {
+pub fn convert_virtual_color(color: VirtualColor) -> helgoboss_learn::VirtualColor {
     use helgoboss_learn::VirtualColor as T;
     use VirtualColor::*;
     match color {
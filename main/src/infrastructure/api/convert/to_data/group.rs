@@ -1,7 +1,15 @@
 use crate::domain::{CompartmentParamIndex, GroupKey};
-use crate::infrastructure::api::convert::to_data::{convert_activation, convert_tags};
+use crate::infrastructure::api::convert::to_data::glue::{
+    convert_step_factor_interval, convert_takeover_mode, convert_unit_value_interval,
+    convert_virtual_color,
+};
+use crate::infrastructure::api::convert::to_data::target::convert_track;
+use crate::infrastructure::api::convert::to_data::{
+    convert_activation, convert_feedback_policy, convert_tags,
+};
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
-use crate::infrastructure::data::{EnabledData, GroupModelData};
+use crate::infrastructure::data::{EnabledData, GroupDefaultsData, GroupModelData};
+use helgoboss_midi::Channel;
 use helgobox_api::persistence::*;
 
 pub fn convert_group(
@@ -16,6 +24,11 @@ pub fn convert_group(
             g.id.map(|id| id.into()).unwrap_or_default()
         },
         name: g.name.unwrap_or_default(),
+        parent: if is_default_group {
+            GroupKey::default()
+        } else {
+            g.parent.map(|id| id.into()).unwrap_or_default()
+        },
         tags: convert_tags(g.tags.unwrap_or_default())?,
         enabled_data: {
             EnabledData {
@@ -30,6 +43,43 @@ pub fn convert_group(
         } else {
             Default::default()
         },
+        defaults: if let Some(d) = g.defaults {
+            convert_group_defaults(d)?
+        } else {
+            Default::default()
+        },
+    };
+    Ok(data)
+}
+
+fn convert_group_defaults(d: GroupDefaults) -> ConversionResult<GroupDefaultsData> {
+    let step_size_interval = d
+        .step_size_interval
+        .map(convert_unit_value_interval)
+        .transpose()?;
+    let step_factor_interval = d
+        .step_factor_interval
+        .map(convert_step_factor_interval)
+        .transpose()?;
+    let (target_track, target_clip_column) = match d.target_track {
+        None => (None, None),
+        Some(t) => {
+            let (track_data, clip_column) = convert_track(t)?;
+            (Some(track_data), clip_column)
+        }
+    };
+    let data = GroupDefaultsData {
+        takeover_mode: d.takeover_mode.map(convert_takeover_mode),
+        min_step_size: step_size_interval.map(|i| i.min_val()),
+        max_step_size: step_size_interval.map(|i| i.max_val()),
+        min_step_factor: step_factor_interval.map(|i| i.min_val()),
+        max_step_factor: step_factor_interval.map(|i| i.max_val()),
+        feedback_color: d.feedback_color.map(convert_virtual_color),
+        feedback_background_color: d.feedback_background_color.map(convert_virtual_color),
+        source_channel: d.source_channel.map(Channel::try_from).transpose()?,
+        target_track,
+        target_clip_column,
+        feedback_policy: d.feedback_policy.map(convert_feedback_policy).transpose()?,
     };
     Ok(data)
 }
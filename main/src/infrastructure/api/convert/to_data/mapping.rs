@@ -1,17 +1,22 @@
 use super::convert_source;
 use crate::application;
 use crate::application::{
-    LifecycleMidiMessageModel, LifecycleModel, MappingExtensionModel, RawByteArrayMidiMessage,
+    LifecycleMidiMessageModel, LifecycleModel, LifecycleOscMessageModel,
+    LifecycleParameterValueModel, MappingExtensionModel, RawByteArrayMidiMessage,
 };
-use crate::domain::Tag;
+use crate::domain;
+use crate::domain::{ControlInput, FeedbackOutput, LifecycleOscArg, MidiControlInput, Tag};
 use crate::infrastructure::api::convert::to_data::glue::convert_glue;
 use crate::infrastructure::api::convert::to_data::target::convert_target;
 use crate::infrastructure::api::convert::to_data::{
-    convert_activation, ApiToDataConversionContext,
+    convert_activation, resolve_parameter_ref, ApiToDataConversionContext,
+};
+use crate::infrastructure::api::convert::{convert_multiple, defaults, ConversionResult};
+use crate::infrastructure::data::{
+    ControlDeviceId, EnabledData, FeedbackDeviceId, FeedbackPolicyData, MappingModelData,
 };
-use crate::infrastructure::api::convert::{defaults, ConversionResult};
-use crate::infrastructure::data::{EnabledData, MappingModelData};
 use helgobox_api::persistence::*;
+use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 use std::convert::TryInto;
 use std::str::FromStr;
 
@@ -68,11 +73,31 @@ pub fn convert_mapping(
         },
         prevent_echo_feedback,
         send_feedback_after_control,
-        advanced: convert_advanced(m.on_activate, m.on_deactivate, m.unprocessed)?,
+        advanced: convert_advanced(
+            m.on_activate,
+            m.on_deactivate,
+            m.unprocessed,
+            conversion_context,
+        )?,
         visible_in_projection: m
             .visible_in_projection
             .unwrap_or(defaults::MAPPING_VISIBLE_IN_PROJECTION),
         success_audio_feedback: m.success_audio_feedback,
+        feedback_policy: m
+            .feedback_policy
+            .map(convert_feedback_policy)
+            .transpose()?
+            .unwrap_or_default(),
+        control_device_id: m
+            .control_input
+            .map(convert_control_input)
+            .transpose()?
+            .map(ControlDeviceId::from_control_input),
+        feedback_device_id: m
+            .feedback_output
+            .map(convert_feedback_output)
+            .transpose()?
+            .map(FeedbackDeviceId::from_feedback_output),
     };
     Ok(v)
 }
@@ -90,6 +115,7 @@ fn convert_advanced(
     on_activate: Option<LifecycleHook>,
     on_deactivate: Option<LifecycleHook>,
     unprocessed: Option<serde_json::Map<String, serde_json::Value>>,
+    conversion_context: &impl ApiToDataConversionContext,
 ) -> ConversionResult<Option<serde_yaml::mapping::Mapping>> {
     fn into_yaml_mapping(value: serde_yaml::Value) -> serde_yaml::mapping::Mapping {
         if let serde_yaml::Value::Mapping(m) = value {
@@ -102,8 +128,8 @@ fn convert_advanced(
         return Ok(None);
     }
     let extension_model = MappingExtensionModel {
-        on_activate: convert_lifecycle_hook(on_activate)?,
-        on_deactivate: convert_lifecycle_hook(on_deactivate)?,
+        on_activate: convert_lifecycle_hook(on_activate, conversion_context)?,
+        on_deactivate: convert_lifecycle_hook(on_deactivate, conversion_context)?,
     };
     let value = serde_yaml::to_value(extension_model)?;
     let mut mapping = into_yaml_mapping(value);
@@ -117,11 +143,14 @@ fn convert_advanced(
     Ok(Some(mapping))
 }
 
-fn convert_lifecycle_hook(hook: Option<LifecycleHook>) -> ConversionResult<LifecycleModel> {
+fn convert_lifecycle_hook(
+    hook: Option<LifecycleHook>,
+    conversion_context: &impl ApiToDataConversionContext,
+) -> ConversionResult<LifecycleModel> {
+    let hook = hook.unwrap_or_default();
     let v = LifecycleModel {
         send_midi_feedback: {
             let actions: Result<Vec<_>, _> = hook
-                .unwrap_or_default()
                 .send_midi_feedback
                 .unwrap_or_default()
                 .into_iter()
@@ -129,10 +158,52 @@ fn convert_lifecycle_hook(hook: Option<LifecycleHook>) -> ConversionResult<Lifec
                 .collect();
             actions?
         },
+        send_osc_feedback: hook
+            .send_osc_feedback
+            .unwrap_or_default()
+            .into_iter()
+            .map(convert_send_osc_feedback_action)
+            .collect(),
+        set_target_value: hook.set_target_value,
+        set_parameters: convert_multiple(hook.set_parameters.unwrap_or_default(), |a| {
+            let model = LifecycleParameterValueModel {
+                index: resolve_parameter_ref(&a.parameter, &|key| {
+                    conversion_context.param_index_by_key(key)
+                })?,
+                key: match a.parameter {
+                    ParamRef::Key(key) => Some(key),
+                    ParamRef::Index(_) => None,
+                },
+                value: a.value,
+            };
+            Ok(model)
+        })?,
+        lua: hook.lua,
     };
     Ok(v)
 }
 
+fn convert_send_osc_feedback_action(action: SendOscFeedbackAction) -> LifecycleOscMessageModel {
+    LifecycleOscMessageModel {
+        address: action.address,
+        arguments: action
+            .arguments
+            .unwrap_or_default()
+            .into_iter()
+            .map(convert_osc_arg_value)
+            .collect(),
+    }
+}
+
+fn convert_osc_arg_value(value: OscArgValue) -> LifecycleOscArg {
+    match value {
+        OscArgValue::Bool(v) => LifecycleOscArg::Bool(v),
+        OscArgValue::Int(v) => LifecycleOscArg::Int(v),
+        OscArgValue::Float(v) => LifecycleOscArg::Float(v),
+        OscArgValue::String(v) => LifecycleOscArg::String(v),
+    }
+}
+
 fn convert_send_midi_feedback_action(
     action: SendMidiFeedbackAction,
 ) -> ConversionResult<LifecycleMidiMessageModel> {
@@ -152,3 +223,39 @@ fn convert_raw_midi_message(msg: RawMidiMessage) -> ConversionResult<application
     };
     Ok(v)
 }
+
+pub fn convert_feedback_policy(p: FeedbackPolicy) -> ConversionResult<FeedbackPolicyData> {
+    let data = FeedbackPolicyData {
+        max_rate: p.max_rate,
+        min_delta: p.min_delta,
+        debounce_millis: p.debounce,
+        settle_delay_millis: p.settle_delay,
+    };
+    data.to_model().validate().map_err(anyhow::Error::msg)?;
+    Ok(data)
+}
+
+fn convert_control_input(input: MappingControlInput) -> ConversionResult<ControlInput> {
+    let input = match input {
+        MappingControlInput::FxInput => ControlInput::Midi(MidiControlInput::FxInput),
+        MappingControlInput::MidiDevice { id } => {
+            ControlInput::Midi(MidiControlInput::Device(MidiInputDeviceId::new(id)))
+        }
+        MappingControlInput::OscDevice { id } => ControlInput::Osc(id.parse()?),
+        MappingControlInput::Keyboard => ControlInput::Keyboard,
+        MappingControlInput::WebSocket => ControlInput::WebSocket,
+    };
+    Ok(input)
+}
+
+fn convert_feedback_output(output: MappingFeedbackOutput) -> ConversionResult<FeedbackOutput> {
+    let output = match output {
+        MappingFeedbackOutput::FxOutput => FeedbackOutput::Midi(domain::MidiDestination::FxOutput),
+        MappingFeedbackOutput::MidiDevice { id } => {
+            FeedbackOutput::Midi(domain::MidiDestination::Device(MidiOutputDeviceId::new(id)))
+        }
+        MappingFeedbackOutput::OscDevice { id } => FeedbackOutput::Osc(id.parse()?),
+        MappingFeedbackOutput::WebSocket => FeedbackOutput::WebSocket,
+    };
+    Ok(output)
+}
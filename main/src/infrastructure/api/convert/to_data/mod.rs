@@ -7,6 +7,7 @@ use crate::{application, domain};
 use anyhow::anyhow;
 pub use compartment::*;
 use enumflags2::BitFlags;
+pub use group::*;
 use helgobox_api::persistence::{
     ActivationCondition, Interval, Keystroke, ModifierState, OscArgKind, ParamRef,
     VirtualControlElementId,
@@ -1,11 +1,12 @@
 use crate::application::{MidiSourceType, ReaperSourceType, SourceCategory};
+use crate::domain::{validate_sysex_display_character_map, SequencerStep};
 use crate::infrastructure::api::convert::to_data::{
     convert_control_element_id, convert_keystroke, convert_osc_arg_type, convert_osc_value_range,
 };
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
 use crate::infrastructure::data::SourceModelData;
 use anyhow::bail;
-use helgoboss_learn::DisplayType;
+use helgoboss_learn::{DisplayType, UnitValue};
 use helgoboss_midi::{Channel, U14};
 use helgobox_api::persistence::*;
 use std::convert::TryInto;
@@ -92,8 +93,39 @@ pub fn convert_source(s: Source) -> ConversionResult<SourceModelData> {
             Source::XTouchMackieLcd(s) => s.line,
             Source::MackieSevenSegmentDisplay(_) => None,
             Source::SiniConE24Display(s) => s.item_index,
+            Source::SysExDisplay(s) => s.line,
             _ => None,
         },
+        sysex_display_template: match &s {
+            Source::SysExDisplay(s) => s.template.clone(),
+            _ => Default::default(),
+        },
+        sysex_display_length: match &s {
+            Source::SysExDisplay(s) => s.length,
+            _ => None,
+        },
+        sysex_display_alignment: match &s {
+            Source::SysExDisplay(s) => s.alignment.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        sysex_display_padding: match &s {
+            Source::SysExDisplay(s) => s.padding,
+            _ => None,
+        },
+        sysex_display_encoding: match &s {
+            Source::SysExDisplay(s) => s.encoding.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        sysex_display_character_map: match &s {
+            Source::SysExDisplay(s) => {
+                let map = s.character_map.clone().unwrap_or_default();
+                if let Err(e) = validate_sysex_display_character_map(&map) {
+                    bail!(e);
+                }
+                map
+            }
+            _ => Default::default(),
+        },
         osc_address_pattern: match &s {
             Source::Osc(s) => s.address.as_ref().cloned().unwrap_or_default(),
             _ => Default::default(),
@@ -138,6 +170,8 @@ pub fn convert_source(s: Source) -> ConversionResult<SourceModelData> {
             Source::RealearnInstanceStart => ReaperSourceType::RealearnUnitStart,
             Source::Timer(_) => ReaperSourceType::Timer,
             Source::RealearnParameter(_) => ReaperSourceType::RealearnParameter,
+            Source::Lfo(_) => ReaperSourceType::Lfo,
+            Source::Sequencer(_) => ReaperSourceType::Sequencer,
             _ => Default::default(),
         },
         timer_millis: match &s {
@@ -150,6 +184,104 @@ pub fn convert_source(s: Source) -> ConversionResult<SourceModelData> {
             }
             _ => Default::default(),
         },
+        lfo_waveform: match &s {
+            Source::Lfo(s) => s.waveform.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        lfo_frequency: match &s {
+            Source::Lfo(s) => s.frequency,
+            _ => None,
+        },
+        lfo_beats_per_cycle: match &s {
+            Source::Lfo(s) => s.beats_per_cycle,
+            _ => None,
+        },
+        lfo_phase_offset: match &s {
+            Source::Lfo(s) => s.phase_offset.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        lfo_retrigger_parameter_index: match &s {
+            Source::Lfo(s) => match s.retrigger_parameter_index {
+                None => None,
+                Some(i) => Some(i.try_into().map_err(anyhow::Error::msg)?),
+            },
+            _ => None,
+        },
+        sequencer_steps: match &s {
+            Source::Sequencer(s) => s.steps.as_ref().map(|steps| {
+                steps
+                    .iter()
+                    .map(|s| SequencerStep {
+                        value: UnitValue::new_clamped(s.value),
+                        gate: s.gate.unwrap_or(true),
+                    })
+                    .collect()
+            }),
+            _ => None,
+        },
+        sequencer_beats_per_step: match &s {
+            Source::Sequencer(s) => s.beats_per_step,
+            _ => None,
+        },
+        sequencer_swing: match &s {
+            Source::Sequencer(s) => s.swing.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        sequencer_direction: match &s {
+            Source::Sequencer(s) => s.direction.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        sequencer_step_value_parameter_index: match &s {
+            Source::Sequencer(s) => match s.step_value_parameter_index {
+                None => None,
+                Some(i) => Some(i.try_into().map_err(anyhow::Error::msg)?),
+            },
+            _ => None,
+        },
+        compound_kind: match &s {
+            Source::Compound(s) => s.compound_kind.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        compound_timeout_millis: match &s {
+            Source::Compound(s) => s.timeout,
+            _ => None,
+        },
+        audio_channel: match &s {
+            Source::Audio(s) => s.channel.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        audio_channel_count: match &s {
+            Source::Audio(s) => s.channel_count,
+            _ => None,
+        },
+        audio_detector: match &s {
+            Source::Audio(s) => s.detector.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        audio_attack_millis: match &s {
+            Source::Audio(s) => s.attack,
+            _ => None,
+        },
+        audio_release_millis: match &s {
+            Source::Audio(s) => s.release,
+            _ => None,
+        },
+        audio_threshold_db: match &s {
+            Source::Audio(s) => s.threshold,
+            _ => None,
+        },
+        audio_analysis: match &s {
+            Source::Audio(s) => s.analysis.unwrap_or_default(),
+            _ => Default::default(),
+        },
+        compound_members: match s {
+            Source::Compound(s) => s
+                .members
+                .into_iter()
+                .map(convert_source)
+                .collect::<ConversionResult<_>>()?,
+            _ => vec![],
+        },
     };
     Ok(data)
 }
@@ -158,9 +290,13 @@ fn convert_category(s: &Source) -> SourceCategory {
     use Source::*;
     match s {
         None => SourceCategory::Never,
-        MidiDeviceChanges | RealearnInstanceStart | Timer(_) | RealearnParameter(_) | Speech => {
-            SourceCategory::Reaper
-        }
+        MidiDeviceChanges
+        | RealearnInstanceStart
+        | Timer(_)
+        | RealearnParameter(_)
+        | Speech
+        | Lfo(_)
+        | Sequencer(_) => SourceCategory::Reaper,
         MidiNoteVelocity(_)
         | MidiNoteKeyNumber(_)
         | MidiPolyphonicKeyPressureAmount(_)
@@ -179,10 +315,13 @@ fn convert_category(s: &Source) -> SourceCategory {
         | MackieSevenSegmentDisplay(_)
         | SiniConE24Display(_)
         | SlKeyboardDisplay(_)
-        | LaunchpadProScrollingTextDisplay => SourceCategory::Midi,
+        | LaunchpadProScrollingTextDisplay
+        | SysExDisplay(_) => SourceCategory::Midi,
         Osc(_) => SourceCategory::Osc,
         Key(_) => SourceCategory::Keyboard,
         Virtual(_) => SourceCategory::Virtual,
+        Compound(_) => SourceCategory::Compound,
+        Audio(_) => SourceCategory::Audio,
     }
 }
 
@@ -205,6 +344,7 @@ fn convert_midi_source_type(s: &Source) -> MidiSourceType {
         MackieLcd(_) | XTouchMackieLcd(_) | MackieSevenSegmentDisplay(_) | SiniConE24Display(_) => {
             MidiSourceType::Display
         }
+        SysExDisplay(_) => MidiSourceType::SysExDisplay,
         _ => MidiSourceType::default(),
     }
 }
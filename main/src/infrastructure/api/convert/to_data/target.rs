@@ -942,6 +942,11 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             group_id: d.group.map(|g| g.into()).unwrap_or_default(),
             ..init(d.commons)
         },
+        Target::SelectBank(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::SelectBank,
+            ..init(d.commons)
+        },
         Target::BrowsePotFilterItems(d) => TargetModelData {
             category: TargetCategory::Reaper,
             r#type: ReaperTargetType::BrowsePotFilterItems,
@@ -1119,12 +1124,21 @@ fn convert_track_desc(t: TrackDescriptor) -> ConversionResult<TrackDesc> {
             commons,
             index,
             scope,
+            bank_relative,
         } => (
             TrackPropValues {
-                r#type: match scope.unwrap_or_default() {
-                    TrackScope::AllTracks => VirtualTrackType::ByIndex,
-                    TrackScope::TracksVisibleInTcp => VirtualTrackType::ByIndexTcp,
-                    TrackScope::TracksVisibleInMcp => VirtualTrackType::ByIndexMcp,
+                r#type: if bank_relative.unwrap_or(defaults::TARGET_BANK_RELATIVE) {
+                    match scope.unwrap_or_default() {
+                        TrackScope::AllTracks => VirtualTrackType::ByIndexInBank,
+                        TrackScope::TracksVisibleInTcp => VirtualTrackType::ByIndexInBankTcp,
+                        TrackScope::TracksVisibleInMcp => VirtualTrackType::ByIndexInBankMcp,
+                    }
+                } else {
+                    match scope.unwrap_or_default() {
+                        TrackScope::AllTracks => VirtualTrackType::ByIndex,
+                        TrackScope::TracksVisibleInTcp => VirtualTrackType::ByIndexTcp,
+                        TrackScope::TracksVisibleInMcp => VirtualTrackType::ByIndexMcp,
+                    }
                 },
                 index,
                 ..Default::default()
@@ -1176,6 +1190,16 @@ fn convert_track_desc(t: TrackDescriptor) -> ConversionResult<TrackDesc> {
     Ok(desc)
 }
 
+/// Converts the given track descriptor into track data and an optional Playtime column.
+///
+/// Whether the track must be selected is not taken into account.
+pub fn convert_track(
+    t: TrackDescriptor,
+) -> ConversionResult<(TrackData, Option<PlaytimeColumnDescriptor>)> {
+    let desc = convert_track_desc(t)?;
+    Ok((desc.track_data, desc.clip_column))
+}
+
 fn convert_chain_desc(t: FxChainDescriptor) -> ConversionResult<FxChainDesc> {
     use FxChainDescriptor::*;
     let desc = match t {
@@ -1320,10 +1344,15 @@ fn convert_fx_desc(t: FxDescriptor) -> ConversionResult<FxDesc> {
             commons,
             chain,
             index,
+            bank_relative,
         } => (
             convert_chain_desc(chain)?,
             FxPropValues {
-                r#type: VirtualFxType::ByIndex,
+                r#type: if bank_relative.unwrap_or(defaults::TARGET_BANK_RELATIVE) {
+                    VirtualFxType::ByIndexInBank
+                } else {
+                    VirtualFxType::ByIndex
+                },
                 index,
                 ..Default::default()
             },
@@ -1382,10 +1411,18 @@ fn convert_fx_parameter_desc(t: FxParameterDescriptor) -> ConversionResult<FxPar
                 ..Default::default()
             },
         ),
-        ByIndex { fx, index } => (
+        ByIndex {
+            fx,
+            index,
+            bank_relative,
+        } => (
             convert_fx_desc(fx.unwrap_or_default())?,
             FxParameterPropValues {
-                r#type: VirtualFxParameterType::ByIndex,
+                r#type: if bank_relative.unwrap_or(defaults::TARGET_BANK_RELATIVE) {
+                    VirtualFxParameterType::ByIndexInBank
+                } else {
+                    VirtualFxParameterType::ByIndex
+                },
                 index,
                 ..Default::default()
             },
@@ -1469,3 +1506,48 @@ fn convert_fx_display_kind(display_kind: FxDisplayKind) -> FxDisplayType {
         Chain => T::Chain,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::api::convert::from_data::{
+        convert_track_descriptor, ConversionStyle,
+    };
+
+    #[test]
+    fn track_by_index_round_trip() {
+        // Given
+        let descriptors = [
+            TrackDescriptor::ByIndex {
+                commons: Default::default(),
+                index: 3,
+                scope: None,
+                bank_relative: None,
+            },
+            TrackDescriptor::ByIndex {
+                commons: Default::default(),
+                index: 3,
+                scope: None,
+                bank_relative: Some(true),
+            },
+            TrackDescriptor::ByIndex {
+                commons: Default::default(),
+                index: 5,
+                scope: Some(TrackScope::TracksVisibleInMcp),
+                bank_relative: Some(true),
+            },
+        ];
+        for descriptor in descriptors {
+            // When
+            let desc = convert_track_desc(descriptor.clone()).unwrap();
+            let converted_back = convert_track_descriptor(
+                desc.track_data,
+                desc.track_must_be_selected,
+                &desc.clip_column.unwrap_or_default(),
+                ConversionStyle::Minimal,
+            );
+            // Then
+            assert_eq!(converted_back, Some(descriptor));
+        }
+    }
+}
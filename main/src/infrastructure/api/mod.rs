@@ -1 +1,2 @@
+pub mod compartment_diff;
 pub mod convert;
@@ -1,14 +1,16 @@
 use crate::application::{CompartmentInSession, CompartmentModel, GroupModel, UnitModel};
 use crate::domain::{
-    CompartmentKind, CompartmentParamIndex, GroupId, GroupKey, MappingId, MappingKey, ParamSetting,
+    CompartmentBanks, CompartmentKind, CompartmentParamIndex, GroupId, GroupKey, MappingId,
+    MappingKey, ParamSetting,
 };
 use crate::infrastructure::data::{
-    GroupModelData, MappingModelData, MigrationDescriptor, ModelToDataConversionContext,
-    SimpleDataToModelConversionContext,
+    instantiate_mapping_templates, GroupModelData, MappingModelData, MigrationDescriptor,
+    ModelToDataConversionContext, SimpleDataToModelConversionContext,
 };
 use base::default_util::{deserialize_null_default, is_default};
 use base::hash_util::NonCryptoHashMap;
 use base::validation_util::{ensure_no_duplicate, ValidationError};
+use helgobox_api::persistence::MappingTemplate;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
@@ -33,6 +35,12 @@ pub struct CompartmentModelData {
         skip_serializing_if = "is_default"
     )]
     pub mappings: Vec<MappingModelData>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub mapping_templates: Vec<MappingTemplate>,
     // String key workaround because otherwise deserialization doesn't work with flattening.
     // (https://github.com/serde-rs/serde/issues/1183)
     #[serde(
@@ -59,6 +67,12 @@ pub struct CompartmentModelData {
         skip_serializing_if = "is_default"
     )]
     pub notes: String,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub banks: CompartmentBanks,
 }
 
 impl ModelToDataConversionContext for CompartmentModel {
@@ -85,8 +99,11 @@ impl CompartmentModelData {
             mappings: model
                 .mappings
                 .iter()
+                // Template instances are materialized again when loading
+                .filter(|m| !m.is_template_instance())
                 .map(|m| MappingModelData::from_model(m, model))
                 .collect(),
+            mapping_templates: model.mapping_templates.clone(),
             parameters: model
                 .parameters
                 .iter()
@@ -95,6 +112,7 @@ impl CompartmentModelData {
             common_lua: model.common_lua.clone(),
             custom_data: model.custom_data.clone(),
             notes: model.notes.clone(),
+            banks: model.banks.clone(),
         }
     }
 
@@ -106,15 +124,30 @@ impl CompartmentModelData {
         compartment: CompartmentKind,
         session: Option<&UnitModel>,
     ) -> anyhow::Result<CompartmentModel> {
+        let template_instances =
+            instantiate_mapping_templates(&self.mapping_templates, compartment, |key| {
+                let (index, _) = self.parameters.iter().find(|(_, p)| p.key_matches(key))?;
+                index.parse::<u32>().ok()?.try_into().ok()
+            })?;
+        let (template_ids, template_mappings): (Vec<_>, Vec<_>) = template_instances
+            .into_iter()
+            .map(|instance| (instance.template_id, instance.mapping))
+            .unzip();
+        let all_mappings: Vec<_> = self
+            .mappings
+            .iter()
+            .chain(template_mappings.iter())
+            .cloned()
+            .collect();
         ensure_no_duplicate_compartment_data(
-            &self.mappings,
+            &all_mappings,
             &self.groups,
             self.parameters.values(),
         )?;
         let migration_descriptor = MigrationDescriptor::new(version);
         let conversion_context = SimpleDataToModelConversionContext::from_session_or_random(
             &self.groups,
-            &self.mappings,
+            &all_mappings,
             session.map(|s| CompartmentInSession::new(s, compartment)),
         );
         let final_default_group = self
@@ -127,8 +160,7 @@ impl CompartmentModelData {
             .iter()
             .map(|g| g.to_model(compartment, false, &conversion_context))
             .collect();
-        let mappings: anyhow::Result<Vec<_>> = self
-            .mappings
+        let mut mappings: Vec<_> = all_mappings
             .iter()
             .map(|m| {
                 m.to_model_for_preset(
@@ -139,10 +171,18 @@ impl CompartmentModelData {
                 )
                 .map_err(anyhow::Error::msg)
             })
-            .collect();
+            .collect::<anyhow::Result<_>>()?;
+        let first_instance_index = self.mappings.len();
+        for (m, template_id) in mappings[first_instance_index..]
+            .iter_mut()
+            .zip(template_ids)
+        {
+            m.set_template_id(Some(template_id));
+        }
         let model = CompartmentModel {
             default_group: final_default_group,
-            mappings: mappings?,
+            mappings,
+            mapping_templates: self.mapping_templates.clone(),
             parameters: self
                 .parameters
                 .iter()
@@ -155,6 +195,7 @@ impl CompartmentModelData {
             common_lua: self.common_lua.clone(),
             custom_data: self.custom_data.clone(),
             notes: self.notes.clone(),
+            banks: self.banks.clone(),
         };
         Ok(model)
     }
@@ -175,3 +216,53 @@ pub fn ensure_no_duplicate_compartment_data<'a>(
     ensure_no_duplicate("parameter IDs", parameters.filter_map(|p| p.key.as_ref()))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn mapping_template_round_trip() {
+        // Given
+        let json = json!(
+            {
+                "mappings": [
+                    { "id": "normal", "source": {}, "mode": {}, "target": {} }
+                ],
+                "mappingTemplates": [
+                    { "id": "fader", "to": 3, "mapping": { "name": "Fader $i" } }
+                ]
+            }
+        );
+        let data: CompartmentModelData = serde_json::from_value(json).unwrap();
+        // When
+        let model = data.to_model(None, CompartmentKind::Main, None).unwrap();
+        let saved_data = CompartmentModelData::from_model(&model);
+        let reloaded_model = saved_data
+            .to_model(None, CompartmentKind::Main, None)
+            .unwrap();
+        // Then
+        assert_eq!(saved_data.mapping_templates, data.mapping_templates);
+        let saved_ids: Vec<_> = saved_data
+            .mappings
+            .iter()
+            .map(|m| m.id.as_ref().map(|id| id.to_string()))
+            .collect();
+        assert_eq!(saved_ids, vec![Some("normal".to_string())]);
+        let reloaded_mappings: Vec<_> = reloaded_model
+            .mappings
+            .iter()
+            .map(|m| (m.key().to_string(), m.template_id()))
+            .collect();
+        assert_eq!(
+            reloaded_mappings,
+            vec![
+                ("normal".to_string(), None),
+                ("fader:1".to_string(), Some("fader")),
+                ("fader:2".to_string(), Some("fader")),
+                ("fader:3".to_string(), Some("fader")),
+            ]
+        );
+    }
+}
@@ -1,5 +1,4 @@
 use crate::application::CompartmentPresetModel;
-use crate::domain::CompartmentKind;
 use crate::infrastructure::data::CompartmentModelData;
 use base::default_util::{deserialize_null_default, is_default};
 
@@ -34,19 +33,8 @@ impl CompartmentPresetData {
         }
     }
 
-    pub fn to_model(
-        &self,
-        id: String,
-        compartment: CompartmentKind,
-    ) -> anyhow::Result<CompartmentPresetModel> {
-        let preset = CompartmentPresetModel::new(
-            id,
-            self.name.clone(),
-            compartment,
-            self.data
-                .to_model(self.version.as_ref(), compartment, None)?,
-        );
-        Ok(preset)
+    pub fn into_data(self) -> CompartmentModelData {
+        self.data
     }
 
     pub fn clear_id(&mut self) {
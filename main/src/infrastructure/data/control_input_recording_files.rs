@@ -0,0 +1,43 @@
+use crate::domain::ControlInputRecording;
+use crate::infrastructure::plugin::BackboneShell;
+use anyhow::Context;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::fs;
+
+/// Saves the given recording as JSON file in the recording directory and returns its path.
+pub fn save_control_input_recording(
+    unit_key: &str,
+    recording: &ControlInputRecording,
+) -> anyhow::Result<Utf8PathBuf> {
+    let dir_path = BackboneShell::realearn_control_input_recording_dir_path();
+    fs::create_dir_all(&dir_path).context("couldn't create recording directory")?;
+    let file_name = format!(
+        "{}_{unit_key}.json",
+        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let file_path = dir_path.join(file_name);
+    let json = serde_json::to_string_pretty(recording).context("couldn't serialize recording")?;
+    fs::write(&file_path, json).context("couldn't write recording file")?;
+    Ok(file_path)
+}
+
+pub fn load_control_input_recording(path: &Utf8Path) -> anyhow::Result<ControlInputRecording> {
+    let json = fs::read_to_string(path).context("couldn't read recording file")?;
+    serde_json::from_str(&json).context("recording file has invalid format")
+}
+
+/// Returns the paths of all saved recordings, most recent first.
+pub fn list_control_input_recordings() -> Vec<Utf8PathBuf> {
+    let dir_path = BackboneShell::realearn_control_input_recording_dir_path();
+    let Ok(entries) = dir_path.read_dir_utf8() else {
+        return vec![];
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension() == Some("json"))
+        .collect();
+    // File names start with the date, so this sorts chronologically.
+    paths.sort_unstable_by(|a, b| b.cmp(a));
+    paths
+}
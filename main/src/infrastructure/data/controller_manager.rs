@@ -1,4 +1,4 @@
-use crate::domain::{format_as_pretty_hex, RequestMidiDeviceIdentityReply};
+use crate::domain::{format_as_pretty_hex, MidiDeviceIdentification};
 use anyhow::Context;
 use camino::Utf8PathBuf;
 use helgobox_api::persistence::{
@@ -105,7 +105,7 @@ impl ControllerManager {
     pub fn update_controller_device_identity(
         &mut self,
         controller_id: &str,
-        reply: Option<RequestMidiDeviceIdentityReply>,
+        identification: Option<MidiDeviceIdentification>,
     ) -> anyhow::Result<()> {
         let controller = self.find_controller_by_id_mut(controller_id)?;
         let connection = controller
@@ -114,13 +114,18 @@ impl ControllerManager {
             .context("controller has no connection")?;
         match connection {
             ControllerConnection::Midi(c) => {
-                if let Some(reply) = reply {
+                if let Some(identification) = identification {
                     // Convenience feature: Set input port automatically
-                    c.input_port = Some(MidiInputPort::new(reply.input_device_id.get() as u32));
-                    c.identity_response =
-                        Some(format_as_pretty_hex(&reply.device_inquiry_reply.message));
+                    c.input_port = Some(MidiInputPort::new(
+                        identification.input_device_id.get() as u32
+                    ));
+                    c.identity_response = identification
+                        .device_inquiry_reply_bytes()
+                        .map(format_as_pretty_hex);
+                    c.device_identity = identification.to_persistent_identity();
                 } else {
                     c.identity_response = None;
+                    c.device_identity = None;
                 }
             }
             ControllerConnection::Osc(_) => {}
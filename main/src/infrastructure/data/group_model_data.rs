@@ -1,13 +1,17 @@
-use crate::application::{Change, GroupCommand, GroupModel};
+use crate::application::{Change, GroupCommand, GroupDefaults, GroupModel};
 use crate::domain::{CompartmentKind, GroupId, GroupKey, Tag};
 use crate::infrastructure::data::{
-    ActivationConditionData, DataToModelConversionContext, EnabledData,
-    ModelToDataConversionContext,
+    deserialize_track, serialize_track, ActivationConditionData, DataToModelConversionContext,
+    EnabledData, FeedbackPolicyData, ModelToDataConversionContext, TrackData,
+    TrackDeserializationInput,
 };
 use base::default_util::{deserialize_null_default, is_default};
+use helgoboss_learn::{DiscreteIncrement, Interval, TakeoverMode, UnitValue, VirtualColor};
+use helgoboss_midi::Channel;
+use helgobox_api::persistence::PlaytimeColumnDescriptor;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GroupModelData {
     /// Doesn't have to be a UUID since 2.11.0-pre.13 and corresponds to the model *key* instead!
@@ -28,6 +32,13 @@ pub struct GroupModelData {
         skip_serializing_if = "is_default"
     )]
     pub name: String,
+    /// Key of the parent group. Empty if this is a top-level group.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub parent: GroupKey,
     #[serde(
         default,
         deserialize_with = "deserialize_null_default",
@@ -38,6 +49,130 @@ pub struct GroupModelData {
     pub enabled_data: EnabledData,
     #[serde(flatten)]
     pub activation_condition_data: ActivationConditionData,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub defaults: GroupDefaultsData,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupDefaultsData {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub takeover_mode: Option<TakeoverMode>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub min_step_size: Option<UnitValue>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub max_step_size: Option<UnitValue>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub min_step_factor: Option<DiscreteIncrement>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub max_step_factor: Option<DiscreteIncrement>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub feedback_color: Option<VirtualColor>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub feedback_background_color: Option<VirtualColor>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub source_channel: Option<Channel>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub target_track: Option<TrackData>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub target_clip_column: Option<PlaytimeColumnDescriptor>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub feedback_policy: Option<FeedbackPolicyData>,
+}
+
+impl GroupDefaultsData {
+    pub fn from_model(model: &GroupDefaults) -> Self {
+        let track_output = model.target_track.clone().map(serialize_track);
+        Self {
+            takeover_mode: model.takeover_mode,
+            min_step_size: model.step_size_interval.map(|i| i.min_val()),
+            max_step_size: model.step_size_interval.map(|i| i.max_val()),
+            min_step_factor: model.step_factor_interval.map(|i| i.min_val()),
+            max_step_factor: model.step_factor_interval.map(|i| i.max_val()),
+            feedback_color: model.feedback_color.clone(),
+            feedback_background_color: model.feedback_background_color.clone(),
+            source_channel: model.source_channel,
+            target_clip_column: track_output.as_ref().and_then(|o| o.clip_column.clone()),
+            target_track: track_output.map(|o| o.track_data),
+            feedback_policy: model
+                .feedback_policy
+                .as_ref()
+                .map(FeedbackPolicyData::from_model),
+        }
+    }
+
+    pub fn to_model(&self) -> GroupDefaults {
+        let clip_column = self.target_clip_column.clone().unwrap_or_default();
+        GroupDefaults {
+            takeover_mode: self.takeover_mode,
+            step_size_interval: self
+                .min_step_size
+                .zip(self.max_step_size)
+                .map(|(min, max)| Interval::new_auto(min, max)),
+            step_factor_interval: self
+                .min_step_factor
+                .zip(self.max_step_factor)
+                .map(|(min, max)| Interval::new_auto(min, max)),
+            feedback_color: self.feedback_color.clone(),
+            feedback_background_color: self.feedback_background_color.clone(),
+            source_channel: self.source_channel,
+            target_track: self.target_track.as_ref().map(|track_data| {
+                deserialize_track(TrackDeserializationInput {
+                    track_data,
+                    clip_column: &clip_column,
+                })
+            }),
+            feedback_policy: self.feedback_policy.as_ref().map(|p| p.to_model()),
+        }
+    }
 }
 
 impl GroupModelData {
@@ -48,6 +183,10 @@ impl GroupModelData {
         GroupModelData {
             id: model.key().clone(),
             name: model.name().to_owned(),
+            parent: model
+                .parent_id()
+                .and_then(|id| conversion_context.group_key_by_id(id))
+                .unwrap_or_default(),
             tags: model.tags().to_owned(),
             enabled_data: EnabledData {
                 control_is_enabled: model.control_is_enabled(),
@@ -57,6 +196,7 @@ impl GroupModelData {
                 model.activation_condition_model(),
                 conversion_context,
             ),
+            defaults: GroupDefaultsData::from_model(model.defaults()),
         }
     }
 
@@ -91,6 +231,12 @@ impl GroupModelData {
         conversion_context: &impl DataToModelConversionContext,
     ) {
         model.change(GroupCommand::SetName(self.name.clone()));
+        let parent_id = if self.parent.is_empty() {
+            None
+        } else {
+            conversion_context.group_id_by_key(&self.parent)
+        };
+        model.change(GroupCommand::SetParentId(parent_id));
         model.change(GroupCommand::SetTags(self.tags.clone()));
         model.change(GroupCommand::SetControlIsEnabled(
             self.enabled_data.control_is_enabled,
@@ -100,5 +246,6 @@ impl GroupModelData {
         ));
         self.activation_condition_data
             .apply_to_model(&mut model.activation_condition_model, conversion_context);
+        model.change(GroupCommand::SetDefaults(self.defaults.to_model()));
     }
 }
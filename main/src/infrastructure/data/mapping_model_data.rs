@@ -1,16 +1,18 @@
 use crate::application::{Change, MappingCommand, MappingModel};
 use crate::domain::{
-    CompartmentKind, ExtendedProcessorContext, FeedbackSendBehavior, GroupId, GroupKey, MappingId,
-    MappingKey, Tag,
+    CompartmentKind, ExtendedProcessorContext, FeedbackPolicy, FeedbackSendBehavior, GroupId,
+    GroupKey, MappingId, MappingIoSelection, MappingKey, Tag,
 };
 use crate::infrastructure::data::{
-    ActivationConditionData, DataToModelConversionContext, EnabledData, MigrationDescriptor,
-    ModeModelData, ModelToDataConversionContext, SourceModelData, TargetModelData,
+    ActivationConditionData, ControlDeviceId, DataToModelConversionContext, EnabledData,
+    FeedbackDeviceId, MigrationDescriptor, ModeModelData, ModelToDataConversionContext,
+    SourceModelData, TargetModelData,
 };
 use base::default_util::{bool_true, deserialize_null_default, is_bool_true, is_default};
 use helgobox_api::persistence::SuccessAudioFeedback;
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -70,6 +72,26 @@ pub struct MappingModelData {
         deserialize_with = "deserialize_null_default",
         skip_serializing_if = "is_default"
     )]
+    pub feedback_policy: FeedbackPolicyData,
+    /// `None` means the mapping reacts to all control inputs of the unit.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub control_device_id: Option<ControlDeviceId>,
+    /// `None` means the mapping sends feedback to all feedback outputs of the unit.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub feedback_device_id: Option<FeedbackDeviceId>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
     pub advanced: Option<serde_yaml::mapping::Mapping>,
     #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
     pub visible_in_projection: bool,
@@ -107,6 +129,15 @@ impl MappingModelData {
                 == FeedbackSendBehavior::PreventEchoFeedback,
             send_feedback_after_control: model.feedback_send_behavior()
                 == FeedbackSendBehavior::SendFeedbackAfterControl,
+            feedback_policy: FeedbackPolicyData::from_model(&model.feedback_policy()),
+            control_device_id: model
+                .io_selection()
+                .control_input
+                .map(ControlDeviceId::from_control_input),
+            feedback_device_id: model
+                .io_selection()
+                .feedback_output
+                .map(FeedbackDeviceId::from_feedback_output),
             activation_condition_data: ActivationConditionData::from_model(
                 model.activation_condition_model(),
                 conversion_context,
@@ -252,9 +283,72 @@ impl MappingModelData {
             FeedbackSendBehavior::Normal
         };
         model.change(P::SetFeedbackSendBehavior(feedback_send_behavior));
+        model.change(P::SetFeedbackPolicy(self.feedback_policy.to_model()));
+        let io_selection = MappingIoSelection {
+            control_input: self
+                .control_device_id
+                .as_ref()
+                .map(|id| id.to_control_input())
+                .transpose()?,
+            feedback_output: self
+                .feedback_device_id
+                .as_ref()
+                .map(|id| id.to_feedback_output())
+                .transpose()?,
+        };
+        model.change(P::SetIoSelection(io_selection));
         let _ = model.set_advanced_settings(self.advanced.clone());
         model.change(P::SetVisibleInProjection(self.visible_in_projection));
         model.change(P::SetBeepOnSuccess(self.success_audio_feedback.is_some()));
         Ok(())
     }
 }
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackPolicyData {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub max_rate: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub min_delta: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub debounce_millis: Option<u64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub settle_delay_millis: Option<u64>,
+}
+
+impl FeedbackPolicyData {
+    pub fn from_model(model: &FeedbackPolicy) -> Self {
+        Self {
+            max_rate: model.max_rate,
+            min_delta: model.min_delta,
+            debounce_millis: model.debounce.map(|d| d.as_millis() as _),
+            settle_delay_millis: model.settle_delay.map(|d| d.as_millis() as _),
+        }
+    }
+
+    pub fn to_model(&self) -> FeedbackPolicy {
+        FeedbackPolicy {
+            max_rate: self.max_rate,
+            min_delta: self.min_delta,
+            debounce: self.debounce_millis.map(Duration::from_millis),
+            settle_delay: self.settle_delay_millis.map(Duration::from_millis),
+        }
+    }
+}
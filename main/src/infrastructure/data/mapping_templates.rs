@@ -0,0 +1,402 @@
+use crate::application::CompartmentModel;
+use crate::domain::{CompartmentKind, CompartmentParamIndex, ExpressionEvaluator};
+use crate::infrastructure::api::convert::to_data::{convert_mapping, ApiToDataConversionContext};
+use crate::infrastructure::data::{
+    ensure_no_duplicate_compartment_data, GroupModelData, MappingModelData,
+};
+use anyhow::{anyhow, bail, ensure, Context};
+use base::validation_util::{ensure_no_duplicate, ValidationError};
+use helgobox_api::persistence::{Mapping, MappingTemplate};
+use serde_json::Value;
+
+/// Name of the template variable if the template doesn't define one.
+pub const DEFAULT_MAPPING_TEMPLATE_VARIABLE: &str = "i";
+
+/// First value of the template variable if the template doesn't define one.
+pub const DEFAULT_MAPPING_TEMPLATE_FROM: i32 = 1;
+
+/// Protects against accidentally huge ranges (e.g. a typo in `to`).
+const MAX_INSTANCE_COUNT_PER_TEMPLATE: i64 = 1000;
+
+/// A mapping that has been materialized from a mapping template.
+pub struct MappingTemplateInstanceData {
+    pub template_id: String,
+    pub mapping: MappingModelData,
+}
+
+/// Materializes the given mapping templates into mapping data, in order.
+pub fn instantiate_mapping_templates(
+    templates: &[MappingTemplate],
+    compartment: CompartmentKind,
+    param_index_by_key: impl Fn(&str) -> Option<CompartmentParamIndex>,
+) -> anyhow::Result<Vec<MappingTemplateInstanceData>> {
+    struct ConversionContext<F> {
+        compartment: CompartmentKind,
+        param_index_by_key: F,
+    }
+    impl<F: Fn(&str) -> Option<CompartmentParamIndex>> ApiToDataConversionContext
+        for ConversionContext<F>
+    {
+        fn compartment(&self) -> CompartmentKind {
+            self.compartment
+        }
+
+        fn param_index_by_key(&self, key: &str) -> Option<CompartmentParamIndex> {
+            (self.param_index_by_key)(key)
+        }
+    }
+    ensure_no_duplicate("mapping template IDs", templates.iter().map(|t| &t.id))?;
+    let context = ConversionContext {
+        compartment,
+        param_index_by_key,
+    };
+    let mut instances = vec![];
+    for template in templates {
+        for mapping in instantiate_mapping_template(template)? {
+            let instance = MappingTemplateInstanceData {
+                template_id: template.id.clone(),
+                mapping: convert_mapping(mapping, &context)?,
+            };
+            instances.push(instance);
+        }
+    }
+    Ok(instances)
+}
+
+/// Checks that replacing the current instances of the given template with the given new instances
+/// doesn't lead to duplicate IDs within the compartment.
+///
+/// This is the same check which is done when loading a compartment.
+pub fn ensure_template_instances_fit_compartment(
+    compartment_model: &CompartmentModel,
+    template_id: &str,
+    new_instances: &[MappingTemplateInstanceData],
+) -> Result<(), ValidationError> {
+    let mappings: Vec<_> = compartment_model
+        .mappings
+        .iter()
+        .filter(|m| m.template_id() != Some(template_id))
+        .map(|m| MappingModelData::from_model(m, compartment_model))
+        .chain(new_instances.iter().map(|i| i.mapping.clone()))
+        .collect();
+    let groups: Vec<_> = compartment_model
+        .groups
+        .iter()
+        .map(|g| GroupModelData::from_model(g, compartment_model))
+        .collect();
+    ensure_no_duplicate_compartment_data(
+        &mappings,
+        &groups,
+        compartment_model.parameters.iter().map(|(_, p)| p),
+    )
+}
+
+/// Creates one mapping for each value of the template variable.
+pub fn instantiate_mapping_template(template: &MappingTemplate) -> anyhow::Result<Vec<Mapping>> {
+    if template.id.is_empty() {
+        bail!("Mapping template doesn't have an ID");
+    }
+    let variable = template
+        .variable
+        .as_deref()
+        .unwrap_or(DEFAULT_MAPPING_TEMPLATE_VARIABLE);
+    let from = template.from.unwrap_or(DEFAULT_MAPPING_TEMPLATE_FROM);
+    let count = template.to as i64 - from as i64 + 1;
+    if count > MAX_INSTANCE_COUNT_PER_TEMPLATE {
+        bail!(
+            "Mapping template \"{}\" would create {count} mappings, the maximum is {MAX_INSTANCE_COUNT_PER_TEMPLATE}",
+            template.id
+        );
+    }
+    (from..=template.to)
+        .map(|value| {
+            let json =
+                substitute_in_value(&template.mapping, variable, value).with_context(|| {
+                    format!(
+                        "Couldn't instantiate mapping template \"{}\" for {variable} = {value}",
+                        template.id
+                    )
+                })?;
+            let mut mapping: Mapping = serde_json::from_value(json).with_context(|| {
+                format!(
+                    "Mapping template \"{}\" doesn't yield a valid mapping for {variable} = {value}",
+                    template.id
+                )
+            })?;
+            mapping.id = Some(format!("{}:{value}", template.id));
+            Ok(mapping)
+        })
+        .collect()
+}
+
+fn substitute_in_value(
+    value: &Value,
+    variable: &str,
+    variable_value: i32,
+) -> anyhow::Result<Value> {
+    let result = match value {
+        Value::String(text) => substitute_in_text(text, variable, variable_value)?,
+        Value::Array(items) => {
+            let items: anyhow::Result<Vec<_>> = items
+                .iter()
+                .map(|item| substitute_in_value(item, variable, variable_value))
+                .collect();
+            Value::Array(items?)
+        }
+        Value::Object(props) => {
+            let props: anyhow::Result<serde_json::Map<_, _>> = props
+                .iter()
+                .map(|(key, v)| {
+                    Ok((
+                        key.clone(),
+                        substitute_in_value(v, variable, variable_value)?,
+                    ))
+                })
+                .collect();
+            Value::Object(props?)
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => value.clone(),
+    };
+    Ok(result)
+}
+
+/// Replaces `$i` and `${...}` references in the given text.
+///
+/// The result is always a text, except if the text has the form `#{...}`. Then the expression is
+/// evaluated and the result is a number.
+fn substitute_in_text(text: &str, variable: &str, variable_value: i32) -> anyhow::Result<Value> {
+    if let Some(expression) = text.strip_prefix("#{").and_then(|t| t.strip_suffix('}')) {
+        let number = evaluate_expression(expression, variable, variable_value)?;
+        return Ok(convert_number_to_json(number));
+    }
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('$') {
+        output.push_str(&rest[..pos]);
+        let after_dollar = &rest[pos + 1..];
+        let number = if let Some(after_brace) = after_dollar.strip_prefix('{') {
+            let end = after_brace
+                .find('}')
+                .with_context(|| format!("Missing \"}}\" in \"{text}\""))?;
+            rest = &after_brace[end + 1..];
+            evaluate_expression(&after_brace[..end], variable, variable_value)?
+        } else {
+            let name_len = after_dollar
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(after_dollar.len());
+            if &after_dollar[..name_len] != variable {
+                // Not our variable, leave untouched
+                output.push('$');
+                rest = after_dollar;
+                continue;
+            }
+            rest = &after_dollar[name_len..];
+            variable_value as f64
+        };
+        output.push_str(&format_number(number));
+    }
+    output.push_str(rest);
+    Ok(Value::String(output))
+}
+
+/// Evaluates the given expression, making sure that the result is a finite number.
+fn evaluate_expression(
+    expression: &str,
+    variable: &str,
+    variable_value: i32,
+) -> anyhow::Result<f64> {
+    let evaluator = ExpressionEvaluator::compile(expression)
+        .map_err(|e| anyhow!("Invalid expression \"{expression}\": {e}"))?;
+    let number = evaluator
+        .evaluate_with_additional_vars(|name, _| {
+            if name == variable {
+                Some(variable_value as f64)
+            } else {
+                None
+            }
+        })
+        .map_err(|e| anyhow!("Couldn't evaluate expression \"{expression}\": {e}"))?;
+    ensure!(
+        number.is_finite(),
+        "Expression \"{expression}\" doesn't yield a finite number"
+    );
+    Ok(number)
+}
+
+fn convert_number_to_json(number: f64) -> Value {
+    match as_integer(number) {
+        Some(i) => Value::from(i),
+        None => Value::from(number),
+    }
+}
+
+fn format_number(number: f64) -> String {
+    match as_integer(number) {
+        Some(i) => i.to_string(),
+        None => number.to_string(),
+    }
+}
+
+/// Returns the given finite number as integer if it doesn't have a fractional part and fits.
+fn as_integer(number: f64) -> Option<i64> {
+    let is_integer = number.fract() == 0.0 && number.abs() < i64::MAX as f64;
+    is_integer.then_some(number as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::data::CompartmentModelData;
+    use helgobox_api::persistence::Source;
+    use serde_json::json;
+
+    #[test]
+    fn substitute_text() {
+        // Given
+        // When
+        // Then
+        assert_eq!(substitute_in_text("$i", "i", 3).unwrap(), json!("3"));
+        assert_eq!(substitute_in_text("${i + 7}", "i", 3).unwrap(), json!("10"));
+        assert_eq!(
+            substitute_in_text("${i / 2}", "i", 3).unwrap(),
+            json!("1.5")
+        );
+        assert_eq!(
+            substitute_in_text("Fader $i", "i", 3).unwrap(),
+            json!("Fader 3")
+        );
+        assert_eq!(
+            substitute_in_text("Track $i/${i * 2}", "i", 3).unwrap(),
+            json!("Track 3/6")
+        );
+        assert_eq!(
+            substitute_in_text("$index", "i", 3).unwrap(),
+            json!("$index")
+        );
+        assert_eq!(substitute_in_text("5 $", "i", 3).unwrap(), json!("5 $"));
+        assert!(substitute_in_text("${i + 1", "i", 3).is_err());
+        assert!(substitute_in_text("${i / 0}", "i", 3).is_err());
+    }
+
+    #[test]
+    fn substitute_number() {
+        // Given
+        // When
+        // Then
+        assert_eq!(substitute_in_text("#{i}", "i", 3).unwrap(), json!(3));
+        assert_eq!(substitute_in_text("#{i + 7}", "i", 3).unwrap(), json!(10));
+        assert_eq!(substitute_in_text("#{i / 2}", "i", 3).unwrap(), json!(1.5));
+        assert_eq!(
+            substitute_in_text("Fader #{i}", "i", 3).unwrap(),
+            json!("Fader #{i}")
+        );
+        assert!(substitute_in_text("#{i / 0}", "i", 3).is_err());
+        assert!(substitute_in_text("#{-i / 0}", "i", 3).is_err());
+    }
+
+    #[test]
+    fn instantiate() {
+        // Given
+        let template = MappingTemplate {
+            id: "fader".to_string(),
+            variable: None,
+            from: Some(0),
+            to: 7,
+            mapping: json!({
+                "name": "Fader ${i + 1}",
+                "source": {
+                    "kind": "MidiControlChangeValue",
+                    "channel": "#{i}",
+                    "controller_number": 7,
+                },
+            }),
+        };
+        // When
+        let mappings = instantiate_mapping_template(&template).unwrap();
+        // Then
+        assert_eq!(mappings.len(), 8);
+        let last = &mappings[7];
+        assert_eq!(last.id.as_deref(), Some("fader:7"));
+        assert_eq!(last.name.as_deref(), Some("Fader 8"));
+        let Some(Source::MidiControlChangeValue(source)) = &last.source else {
+            panic!("unexpected source");
+        };
+        assert_eq!(source.channel, Some(7));
+        assert_eq!(source.controller_number, Some(7));
+    }
+
+    #[test]
+    fn reject_instances_colliding_with_existing_mappings() {
+        // Given
+        let data: CompartmentModelData = serde_json::from_value(json!(
+            {
+                "mappings": [
+                    { "id": "fader:2", "source": {}, "mode": {}, "target": {} }
+                ]
+            }
+        ))
+        .unwrap();
+        let model = data.to_model(None, CompartmentKind::Main, None).unwrap();
+        let template = MappingTemplate {
+            id: "fader".to_string(),
+            to: 3,
+            mapping: json!({ "name": "Fader $i" }),
+            ..Default::default()
+        };
+        // When
+        let instances = instantiate_mapping_templates(
+            std::slice::from_ref(&template),
+            CompartmentKind::Main,
+            |_| None,
+        )
+        .unwrap();
+        let result = ensure_template_instances_fit_compartment(&model, &template.id, &instances);
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn accept_instances_replacing_previous_instances() {
+        // Given
+        let data: CompartmentModelData = serde_json::from_value(json!(
+            {
+                "mappingTemplates": [
+                    { "id": "fader", "to": 3, "mapping": { "name": "Fader $i" } }
+                ]
+            }
+        ))
+        .unwrap();
+        let model = data.to_model(None, CompartmentKind::Main, None).unwrap();
+        let template = MappingTemplate {
+            id: "fader".to_string(),
+            to: 5,
+            mapping: json!({ "name": "Fader $i" }),
+            ..Default::default()
+        };
+        // When
+        let instances = instantiate_mapping_templates(
+            std::slice::from_ref(&template),
+            CompartmentKind::Main,
+            |_| None,
+        )
+        .unwrap();
+        let result = ensure_template_instances_fit_compartment(&model, &template.id, &instances);
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn empty_range() {
+        // Given
+        let template = MappingTemplate {
+            id: "fader".to_string(),
+            to: 0,
+            mapping: json!({}),
+            ..Default::default()
+        };
+        // When
+        let mappings = instantiate_mapping_template(&template).unwrap();
+        // Then
+        assert!(mappings.is_empty());
+    }
+}
@@ -7,6 +7,9 @@ pub use compartment_model_data::*;
 mod mapping_model_data;
 pub use mapping_model_data::*;
 
+mod mapping_templates;
+pub use mapping_templates::*;
+
 mod group_model_data;
 pub use group_model_data::*;
 
@@ -43,6 +46,9 @@ pub use compartment_preset_data::*;
 mod preset_link;
 pub use preset_link::*;
 
+mod preset_validation;
+pub use preset_validation::*;
+
 mod deserializers;
 use deserializers::*;
 
@@ -60,3 +66,6 @@ pub use license_management::*;
 
 mod common;
 pub use common::*;
+
+mod control_input_recording_files;
+pub use control_input_recording_files::*;
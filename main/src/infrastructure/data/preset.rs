@@ -1,20 +1,28 @@
-use crate::application::{CompartmentPresetManager, CompartmentPresetModel};
+use crate::application::{CompartmentModel, CompartmentPresetManager, CompartmentPresetModel};
 
 use crate::base::notification;
 use crate::base::notification::{warn_user_about_anyhow_error, warn_user_on_anyhow_error};
 use crate::domain::{
     CompartmentKind, FsDirLuaModuleFinder, IncludedDirLuaModuleFinder, LuaModuleContainer,
-    LuaModuleFinder, SafeLua,
+    LuaModuleFinder, MidiDeviceIdentification, SafeLua,
 };
+use crate::infrastructure::api::compartment_diff::{merge_compartments, MergeConflict};
+use crate::infrastructure::api::convert::from_data;
+use crate::infrastructure::api::convert::from_data::ConversionStyle;
 use crate::infrastructure::api::convert::to_data::convert_compartment;
-use crate::infrastructure::data::CompartmentPresetData;
-use crate::infrastructure::plugin::{midi_output_port_patterns_match, BackboneShell};
+use crate::infrastructure::data::{
+    validate_compartment_data, CompartmentModelData, CompartmentPresetData, PresetValidationIssue,
+};
+use crate::infrastructure::plugin::{
+    midi_device_identity_matches_pattern, midi_output_port_patterns_match, BackboneShell,
+};
 use anyhow::{anyhow, bail, Context};
 use base::byte_pattern::BytePattern;
 use base::file_util;
 use base::file_util::is_hidden;
 use helgobox_api::persistence::{
-    CommonPresetMetaData, ControllerPresetMetaData, MainPresetMetaData, VirtualControlSchemeId,
+    CommonPresetMetaData, Compartment, ControllerPresetMetaData, MainPresetMetaData,
+    VirtualControlSchemeId,
 };
 use include_dir::{include_dir, Dir};
 use itertools::Itertools;
@@ -55,20 +63,25 @@ pub struct FileBasedCompartmentPresetManager<M> {
 impl FileBasedCompartmentPresetManager<ControllerPresetMetaData> {
     pub fn find_controller_preset_compatible_with_device(
         &self,
-        midi_identity_reply: &[u8],
+        identification: &MidiDeviceIdentification,
         midi_output_port_name: &str,
     ) -> Option<&PresetInfo<ControllerPresetMetaData>> {
+        let device_identity = identification.to_persistent_identity();
+        let midi_identity_reply = identification.device_inquiry_reply_bytes();
         self.preset_infos.iter().find(|info| {
-            // Check device identity
-            let Some(midi_identity_pattern) =
-                info.specific_meta_data.midi_identity_pattern.as_ref()
-            else {
-                return false;
-            };
-            let Ok(byte_pattern) = BytePattern::from_str(midi_identity_pattern) else {
-                return false;
+            // Check device identity, preferring the structured one
+            let meta_data = &info.specific_meta_data;
+            let identity_matches = if let (Some(pattern), Some(identity)) =
+                (&meta_data.midi_device_identity, &device_identity)
+            {
+                midi_device_identity_matches_pattern(identity, pattern)
+            } else if let (Some(pattern), Some(reply)) =
+                (&meta_data.midi_identity_pattern, midi_identity_reply)
+            {
+                BytePattern::from_str(pattern).is_ok_and(|p| p.matches(reply))
+            } else {
+                false
             };
-            let identity_matches = byte_pattern.matches(midi_identity_reply);
             if !identity_matches {
                 return false;
             }
@@ -165,6 +178,25 @@ pub trait CommonCompartmentPresetManager {
         &mut self,
         include_factory_presets: bool,
     ) -> anyhow::Result<PresetWorkspaceDescriptor>;
+    /// Loads the given preset and checks it for problems without actually using it.
+    ///
+    /// When validating a main preset, pass the controller compartment it's supposed to be used
+    /// with in order to check that all virtual control elements are provided.
+    fn validate_preset(
+        &self,
+        id: &str,
+        controller: Option<&CompartmentModel>,
+    ) -> anyhow::Result<Vec<PresetValidationIssue>>;
+    /// Applies the changes between `base` and `theirs` onto the given user preset and saves it.
+    ///
+    /// Returns the fields which have been changed differently in the preset. For those, the
+    /// preset keeps its own value.
+    fn merge_into_preset(
+        &mut self,
+        id: &str,
+        base: &Compartment,
+        theirs: &Compartment,
+    ) -> anyhow::Result<Vec<MergeConflict>>;
 }
 
 pub struct PresetWorkspaceDescriptor {
@@ -448,6 +480,25 @@ impl<S: SpecificPresetMetaData> FileBasedCompartmentPresetManager<S> {
         &self,
         preset_info: &PresetInfo<S>,
     ) -> anyhow::Result<CompartmentPresetModel> {
+        let compartment_data = self.load_preset_data(preset_info)?;
+        let compartment_model = compartment_data.to_model(
+            preset_info.common.meta_data.realearn_version.as_ref(),
+            self.compartment,
+            None,
+        )?;
+        let preset_model = CompartmentPresetModel::new(
+            preset_info.common.id.to_string(),
+            preset_info.common.meta_data.name.to_string(),
+            self.compartment,
+            compartment_model,
+        );
+        Ok(preset_model)
+    }
+
+    fn load_preset_data(
+        &self,
+        preset_info: &PresetInfo<S>,
+    ) -> anyhow::Result<CompartmentModelData> {
         let file_content: Cow<str> = match &preset_info.common.origin {
             PresetOrigin::User { absolute_file_path } => fs::read_to_string(absolute_file_path)
                 .map_err(|_| {
@@ -472,7 +523,7 @@ impl<S: SpecificPresetMetaData> FileBasedCompartmentPresetManager<S> {
                             e
                         )
                     })?;
-                data.to_model(preset_info.common.id.clone(), self.compartment)
+                Ok(data.into_data())
             }
             PresetFileType::Lua => {
                 let lua = SafeLua::new()?;
@@ -516,19 +567,7 @@ impl<S: SpecificPresetMetaData> FileBasedCompartmentPresetManager<S> {
                 )?;
                 let compartment_content: helgobox_api::persistence::Compartment =
                     lua.as_ref().from_value(value)?;
-                let compartment_data = convert_compartment(self.compartment, compartment_content)?;
-                let compartment_model = compartment_data.to_model(
-                    preset_info.common.meta_data.realearn_version.as_ref(),
-                    self.compartment,
-                    None,
-                )?;
-                let preset_model = CompartmentPresetModel::new(
-                    preset_info.common.id.to_string(),
-                    preset_info.common.meta_data.name.to_string(),
-                    self.compartment,
-                    compartment_model,
-                );
-                Ok(preset_model)
+                convert_compartment(self.compartment, compartment_content)
             }
         }
     }
@@ -608,6 +647,52 @@ impl<M: SpecificPresetMetaData> CommonCompartmentPresetManager
         };
         Ok(desc)
     }
+
+    fn validate_preset(
+        &self,
+        id: &str,
+        controller: Option<&CompartmentModel>,
+    ) -> anyhow::Result<Vec<PresetValidationIssue>> {
+        let preset_info = self
+            .find_preset_info_by_id(id)
+            .context("preset to be validated not found")?;
+        let compartment_data = self.load_preset_data(preset_info)?;
+        validate_compartment_data(
+            self.compartment,
+            preset_info.common.meta_data.realearn_version.as_ref(),
+            &compartment_data,
+            controller,
+        )
+    }
+
+    fn merge_into_preset(
+        &mut self,
+        id: &str,
+        base: &Compartment,
+        theirs: &Compartment,
+    ) -> anyhow::Result<Vec<MergeConflict>> {
+        let preset_info = self
+            .find_preset_info_by_id(id)
+            .context("preset to be merged into not found")?;
+        if preset_info.common.origin.is_factory() {
+            bail!("can't modify factory presets");
+        }
+        if preset_info.common.file_type == PresetFileType::Lua {
+            bail!("can't modify Lua presets, only JSON presets are supported");
+        }
+        let name = preset_info.common.meta_data.name.clone();
+        let ours = from_data::convert_compartment(
+            self.load_preset_data(preset_info)?,
+            ConversionStyle::Minimal,
+        )?;
+        let merge = merge_compartments(base, &ours, theirs)?;
+        let merged_data = convert_compartment(self.compartment, merge.compartment)?;
+        let merged_model = merged_data.to_model(None, self.compartment, None)?;
+        let preset =
+            CompartmentPresetModel::new(id.to_string(), name, self.compartment, merged_model);
+        self.update_preset(preset)?;
+        Ok(merge.conflicts)
+    }
 }
 
 impl<M: SpecificPresetMetaData> CompartmentPresetManager for FileBasedCompartmentPresetManager<M> {
@@ -0,0 +1,625 @@
+//! Static validation of controller and main presets.
+//!
+//! Everything here works without REAPER running (as long as the preset doesn't contain targets that
+//! need REAPER for conversion), so it can be used both from the preset manager and the API.
+use crate::application::{
+    ActivationConditionModel, ActivationType, CompartmentModel, GroupModel, MappingModel,
+    MidiSourceType, SourceCategory, TargetCategory,
+};
+use crate::domain::{
+    extract_used_param_indexes, ActivationCondition, CompartmentKind, CompartmentParamIndex,
+    CompoundMappingSourceAddress, EelCondition, EelMidiSourceScript, EelTransformation,
+    ExpressionCondition, ExpressionEvaluator, GroupId, LuaFeedbackScript, LuaMidiSourceScript,
+    MappingKey, RealearnSourceContext, SafeLua, VirtualControlElement,
+};
+use crate::infrastructure::data::{CompartmentModelData, GroupModelData};
+use base::hash_util::NonCryptoHashSet;
+use derive_more::Display;
+use helgoboss_learn::FeedbackType;
+use helgobox_api::persistence::MidiScriptKind;
+use itertools::Itertools;
+use semver::Version;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Display, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PresetValidationSeverity {
+    /// The preset works but probably not as intended or not on other machines.
+    Warning,
+    /// Parts of the preset don't work at all.
+    Error,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetValidationIssue {
+    pub severity: PresetValidationSeverity,
+    /// Key of the mapping which this issue is about, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping_key: Option<MappingKey>,
+    pub message: String,
+}
+
+impl fmt::Display for PresetValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Validates the given compartment data as it would be loaded from a preset file.
+///
+/// If `controller` is given and the compartment is the main compartment, the virtual sources of
+/// the main mappings are checked against the virtual control elements provided by that controller
+/// compartment.
+pub fn validate_compartment_data(
+    compartment: CompartmentKind,
+    version: Option<&Version>,
+    data: &CompartmentModelData,
+    controller: Option<&CompartmentModel>,
+) -> anyhow::Result<Vec<PresetValidationIssue>> {
+    let mut issues = vec![];
+    check_duplicate_keys(data, &mut issues);
+    check_group_references(data, &mut issues);
+    match data.to_model(version, compartment, None) {
+        Ok(model) => {
+            let lua = SafeLua::new()?;
+            check_common_lua(&lua, &model, &mut issues);
+            check_scripts(&lua, &model, &mut issues);
+            check_parameter_references(&model, &mut issues);
+            check_conflicting_sources(&model, &mut issues);
+            check_project_references(&model, &mut issues);
+            if let (CompartmentKind::Main, Some(controller)) = (compartment, controller) {
+                check_virtual_control_elements(controller, &model, &mut issues);
+            }
+        }
+        Err(e) => {
+            issues.push(error(
+                None,
+                format!("Skipped in-depth checks because the preset couldn't be converted: {e:#}"),
+            ));
+        }
+    }
+    Ok(issues)
+}
+
+fn check_duplicate_keys(data: &CompartmentModelData, issues: &mut Vec<PresetValidationIssue>) {
+    for key in data
+        .mappings
+        .iter()
+        .filter_map(|m| m.id.as_ref())
+        .duplicates()
+    {
+        issues.push(error(
+            Some(key.clone()),
+            format!("Mapping key \"{key}\" is used by more than one mapping"),
+        ));
+    }
+    for key in data
+        .groups
+        .iter()
+        .map(|g| &g.id)
+        .filter(|key| !key.is_empty())
+        .duplicates()
+    {
+        issues.push(error(
+            None,
+            format!("Group key \"{key}\" is used by more than one group"),
+        ));
+    }
+    for key in data
+        .parameters
+        .values()
+        .filter_map(|p| p.key.as_ref())
+        .duplicates()
+    {
+        issues.push(error(
+            None,
+            format!("Parameter key \"{key}\" is used by more than one parameter"),
+        ));
+    }
+}
+
+fn check_group_references(data: &CompartmentModelData, issues: &mut Vec<PresetValidationIssue>) {
+    for m in &data.mappings {
+        if m.group_id.is_empty() || data.groups.iter().any(|g| g.id == m.group_id) {
+            continue;
+        }
+        issues.push(error(
+            m.id.clone(),
+            format!(
+                "Mapping \"{}\" refers to group \"{}\", which doesn't exist (it will end up in the default group)",
+                m.name, m.group_id
+            ),
+        ));
+    }
+    for g in &data.groups {
+        if g.parent.is_empty() {
+            continue;
+        }
+        if !data.groups.iter().any(|p| p.id == g.parent) {
+            issues.push(error(
+                None,
+                format!(
+                    "Group \"{}\" refers to parent group \"{}\", which doesn't exist",
+                    g.name, g.parent
+                ),
+            ));
+        } else if is_own_ancestor(data, g) {
+            issues.push(error(
+                None,
+                format!(
+                    "Group \"{}\" is its own ancestor (inheritance stops at the cycle)",
+                    g.name
+                ),
+            ));
+        }
+    }
+}
+
+fn is_own_ancestor(data: &CompartmentModelData, group: &GroupModelData) -> bool {
+    let mut visited_keys = vec![];
+    let mut parent_key = &group.parent;
+    while !parent_key.is_empty() {
+        if *parent_key == group.id {
+            return true;
+        }
+        if visited_keys.contains(&parent_key) {
+            // Cycle further up, reported for the groups which are part of it
+            return false;
+        }
+        visited_keys.push(parent_key);
+        let Some(parent) = data.groups.iter().find(|g| &g.id == parent_key) else {
+            return false;
+        };
+        parent_key = &parent.parent;
+    }
+    false
+}
+
+fn check_common_lua(
+    lua: &SafeLua,
+    model: &CompartmentModel,
+    issues: &mut Vec<PresetValidationIssue>,
+) {
+    if model.common_lua.trim().is_empty() {
+        return;
+    }
+    let result = lua
+        .create_fresh_environment(false)
+        .and_then(|env| lua.compile_as_function("Common Lua", &model.common_lua, env));
+    if let Err(e) = result {
+        issues.push(error(
+            None,
+            format!("Common Lua code doesn't compile: {e:#}"),
+        ));
+    }
+}
+
+fn check_scripts(lua: &SafeLua, model: &CompartmentModel, issues: &mut Vec<PresetValidationIssue>) {
+    for m in &model.mappings {
+        let mut report = |what: &str, msg: String| {
+            issues.push(error(
+                Some(m.key().clone()),
+                format!(
+                    "{} has {what} that doesn't compile: {msg}",
+                    mapping_label(m)
+                ),
+            ));
+        };
+        let source = &m.source_model;
+        if source.category() == SourceCategory::Midi
+            && source.midi_source_type() == MidiSourceType::Script
+        {
+            let result = match source.midi_script_kind() {
+                MidiScriptKind::Eel => EelMidiSourceScript::compile(source.midi_script()).err(),
+                MidiScriptKind::Lua => LuaMidiSourceScript::compile(lua, source.midi_script())
+                    .err()
+                    .map(|e| format!("{e:#}")),
+            };
+            if let Some(msg) = result {
+                report("a MIDI source script", msg);
+            }
+        }
+        let mode = &m.mode_model;
+        if !mode.eel_control_transformation().trim().is_empty() {
+            if let Err(msg) =
+                EelTransformation::compile_for_control(mode.eel_control_transformation())
+            {
+                report("a control transformation", msg);
+            }
+        }
+        if !mode.eel_feedback_transformation().trim().is_empty() {
+            if let Err(msg) =
+                EelTransformation::compile_for_feedback(mode.eel_feedback_transformation())
+            {
+                report("a feedback transformation", msg);
+            }
+        }
+        if mode.feedback_type() == FeedbackType::Dynamic {
+            if let Err(e) = LuaFeedbackScript::compile(lua, mode.textual_feedback_expression()) {
+                report("a dynamic feedback script", format!("{e:#}"));
+            }
+        }
+        if let Some(msg) = check_activation_condition_script(m.activation_condition_model()) {
+            report("an activation condition", msg);
+        }
+    }
+    for g in groups(model) {
+        if let Some(msg) = check_activation_condition_script(g.activation_condition_model()) {
+            issues.push(error(
+                None,
+                format!(
+                    "{} has an activation condition that doesn't compile: {msg}",
+                    group_label(g)
+                ),
+            ));
+        }
+    }
+}
+
+fn check_activation_condition_script(condition: &ActivationConditionModel) -> Option<String> {
+    match condition.activation_type() {
+        ActivationType::Eel => EelCondition::compile(condition.script()).err(),
+        ActivationType::Expression => ExpressionCondition::compile(condition.script())
+            .err()
+            .map(|e| e.to_string()),
+        ActivationType::TargetValue => ExpressionEvaluator::compile(condition.script())
+            .err()
+            .map(|e| e.to_string()),
+        ActivationType::Always | ActivationType::Modifiers | ActivationType::Bank => None,
+    }
+}
+
+fn check_parameter_references(model: &CompartmentModel, issues: &mut Vec<PresetValidationIssue>) {
+    let defined_params: NonCryptoHashSet<_> = model.parameters.iter().map(|(i, _)| *i).collect();
+    let undefined_params = |condition: &ActivationConditionModel| -> Vec<CompartmentParamIndex> {
+        referenced_params(condition)
+            .into_iter()
+            .filter(|i| !defined_params.contains(i))
+            .sorted_by_key(|i| i.get())
+            .dedup()
+            .collect()
+    };
+    for m in &model.mappings {
+        for i in undefined_params(m.activation_condition_model()) {
+            issues.push(warning(
+                Some(m.key().clone()),
+                format!(
+                    "{} refers to parameter {}, which the preset doesn't define",
+                    mapping_label(m),
+                    i.get() + 1
+                ),
+            ));
+        }
+    }
+    for g in groups(model) {
+        for i in undefined_params(g.activation_condition_model()) {
+            issues.push(warning(
+                None,
+                format!(
+                    "{} refers to parameter {}, which the preset doesn't define",
+                    group_label(g),
+                    i.get() + 1
+                ),
+            ));
+        }
+    }
+}
+
+fn referenced_params(condition: &ActivationConditionModel) -> Vec<CompartmentParamIndex> {
+    match condition.activation_type() {
+        ActivationType::Modifiers => [
+            condition.modifier_condition_1().param_index(),
+            condition.modifier_condition_2().param_index(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        ActivationType::Bank => vec![condition.bank_condition().param_index()],
+        ActivationType::Eel => extract_used_param_indexes(condition.script())
+            .into_iter()
+            .filter_map(|i| CompartmentParamIndex::try_from(i).ok())
+            .collect(),
+        ActivationType::Always | ActivationType::Expression | ActivationType::TargetValue => {
+            vec![]
+        }
+    }
+}
+
+/// Reports mappings which react to the same source without any activation condition that could
+/// tell them apart.
+fn check_conflicting_sources(model: &CompartmentModel, issues: &mut Vec<PresetValidationIssue>) {
+    let mut mappings_by_address: Vec<(CompoundMappingSourceAddress, &MappingModel)> = vec![];
+    for m in model
+        .mappings
+        .iter()
+        .filter(|m| is_unconditionally_active(model, m))
+    {
+        let Some(address) = m
+            .source_model
+            .create_source()
+            .extract_feedback_address(source_context())
+        else {
+            continue;
+        };
+        if let Some((_, first)) = mappings_by_address.iter().find(|(a, _)| *a == address) {
+            issues.push(warning(
+                Some(m.key().clone()),
+                format!(
+                    "{} uses the same source as {}, and both are always active",
+                    mapping_label(m),
+                    mapping_label(first)
+                ),
+            ));
+        } else {
+            mappings_by_address.push((address, m));
+        }
+    }
+}
+
+fn is_unconditionally_active(model: &CompartmentModel, mapping: &MappingModel) -> bool {
+    if !mapping.is_enabled()
+        || !mapping.control_is_enabled()
+        || mapping.activation_condition_model().activation_type() != ActivationType::Always
+    {
+        return false;
+    }
+    let find_group = |id: GroupId| model.groups.iter().find(|g| g.id() == id);
+    let group_data = find_group(mapping.group_id())
+        .unwrap_or(&model.default_group)
+        .create_effective_data(find_group);
+    group_data.control_is_enabled
+        && matches!(group_data.activation_condition, ActivationCondition::Always)
+}
+
+fn source_context() -> RealearnSourceContext<'static> {
+    RealearnSourceContext {
+        additional_script_input: Default::default(),
+    }
+}
+
+fn check_project_references(model: &CompartmentModel, issues: &mut Vec<PresetValidationIssue>) {
+    for m in model.mappings.iter().filter(|m| m.has_project_references()) {
+        issues.push(warning(
+            Some(m.key().clone()),
+            format!(
+                "{} refers to a track or FX by ID, so it will only work in the project it was created in",
+                mapping_label(m)
+            ),
+        ));
+    }
+}
+
+fn check_virtual_control_elements(
+    controller: &CompartmentModel,
+    main: &CompartmentModel,
+    issues: &mut Vec<PresetValidationIssue>,
+) {
+    let provided_elements: NonCryptoHashSet<VirtualControlElement> = controller
+        .mappings
+        .iter()
+        .filter(|m| m.target_model.category() == TargetCategory::Virtual)
+        .map(|m| m.target_model.create_control_element())
+        .collect();
+    for m in &main.mappings {
+        // Members of compound sources can be virtual as well
+        let virtual_source_models = std::iter::once(&m.source_model)
+            .chain(m.source_model.compound_members())
+            .filter(|s| s.category() == SourceCategory::Virtual);
+        for source_model in virtual_source_models {
+            let element = source_model.create_control_element();
+            if provided_elements.contains(&element) {
+                continue;
+            }
+            let message = format!(
+                "{} uses virtual control element \"{element}\", which the controller preset \
+                doesn't provide",
+                mapping_label(m)
+            );
+            issues.push(warning(Some(m.key().clone()), message));
+        }
+    }
+}
+
+fn groups(model: &CompartmentModel) -> impl Iterator<Item = &GroupModel> {
+    std::iter::once(&model.default_group).chain(&model.groups)
+}
+
+fn mapping_label(mapping: &MappingModel) -> String {
+    if mapping.name().is_empty() {
+        format!("Mapping with key \"{}\"", mapping.key())
+    } else {
+        format!("Mapping \"{}\"", mapping.name())
+    }
+}
+
+fn group_label(group: &GroupModel) -> String {
+    if group.is_default_group() {
+        "Default group".to_string()
+    } else {
+        format!("Group \"{}\"", group.name())
+    }
+}
+
+fn error(mapping_key: Option<MappingKey>, message: String) -> PresetValidationIssue {
+    PresetValidationIssue {
+        severity: PresetValidationSeverity::Error,
+        mapping_key,
+        message,
+    }
+}
+
+fn warning(mapping_key: Option<MappingKey>, message: String) -> PresetValidationIssue {
+    PresetValidationIssue {
+        severity: PresetValidationSeverity::Warning,
+        mapping_key,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn report_broken_references_scripts_and_conflicts() {
+        // Given
+        let json = json!(
+            {
+                "mappings": [
+                    {
+                        "id": "a",
+                        "name": "A",
+                        "groupId": "missing",
+                        "source": { "channel": 0, "number": 1 },
+                        "mode": { "eelControlTransformation": "y = (" },
+                        "target": { "type": 2 }
+                    },
+                    {
+                        "id": "b",
+                        "name": "B",
+                        "source": { "channel": 0, "number": 1 },
+                        "mode": {},
+                        "target": { "type": 2 },
+                        "activationType": "modifiers",
+                        "modifierCondition1": { "paramIndex": 4, "isOn": true }
+                    },
+                    {
+                        "id": "c",
+                        "name": "C",
+                        "source": { "channel": 0, "number": 1 },
+                        "mode": {},
+                        "target": { "type": 2 }
+                    }
+                ]
+            }
+        );
+        let data: CompartmentModelData = serde_json::from_value(json).unwrap();
+        // When
+        let issues = validate_compartment_data(CompartmentKind::Main, None, &data, None).unwrap();
+        // Then
+        assert_eq!(
+            summarize(&issues),
+            vec![
+                (PresetValidationSeverity::Error, Some("a")),
+                (PresetValidationSeverity::Error, Some("a")),
+                (PresetValidationSeverity::Warning, Some("b")),
+                (PresetValidationSeverity::Warning, Some("c")),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_duplicate_mapping_keys() {
+        // Given
+        let json = json!(
+            {
+                "mappings": [
+                    { "id": "a", "source": {}, "mode": {}, "target": {} },
+                    { "id": "a", "source": {}, "mode": {}, "target": {} }
+                ]
+            }
+        );
+        let data: CompartmentModelData = serde_json::from_value(json).unwrap();
+        // When
+        let issues = validate_compartment_data(CompartmentKind::Main, None, &data, None).unwrap();
+        // Then
+        assert_eq!(
+            summarize(&issues),
+            vec![
+                (PresetValidationSeverity::Error, Some("a")),
+                (PresetValidationSeverity::Error, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_virtual_control_elements_not_provided_by_controller() {
+        // Given
+        let controller_json = json!(
+            {
+                "mappings": [
+                    {
+                        "id": "fader-1",
+                        "source": { "channel": 0, "number": 1 },
+                        "mode": {},
+                        "target": { "category": "virtual", "controlElementIndex": 0 }
+                    }
+                ]
+            }
+        );
+        let main_json = json!(
+            {
+                "mappings": [
+                    {
+                        "id": "provided",
+                        "source": { "category": "virtual", "controlElementIndex": 0 },
+                        "mode": {},
+                        "target": { "type": 2 }
+                    },
+                    {
+                        "id": "not-provided",
+                        "source": { "category": "virtual", "controlElementIndex": 1 },
+                        "mode": {},
+                        "target": { "type": 2 }
+                    }
+                ]
+            }
+        );
+        let controller_data: CompartmentModelData =
+            serde_json::from_value(controller_json).unwrap();
+        let controller = controller_data
+            .to_model(None, CompartmentKind::Controller, None)
+            .unwrap();
+        let main_data: CompartmentModelData = serde_json::from_value(main_json).unwrap();
+        // When
+        let issues =
+            validate_compartment_data(CompartmentKind::Main, None, &main_data, Some(&controller))
+                .unwrap();
+        // Then
+        assert_eq!(
+            summarize(&issues),
+            vec![(PresetValidationSeverity::Warning, Some("not-provided"))]
+        );
+    }
+
+    #[test]
+    fn report_broken_group_hierarchy() {
+        // Given
+        let json = json!(
+            {
+                "groups": [
+                    { "id": "a", "name": "A", "parent": "b" },
+                    { "id": "b", "name": "B", "parent": "a" },
+                    { "id": "c", "name": "C", "parent": "a" },
+                    { "id": "d", "name": "D", "parent": "missing" }
+                ],
+                "mappings": []
+            }
+        );
+        let data: CompartmentModelData = serde_json::from_value(json).unwrap();
+        // When
+        let issues = validate_compartment_data(CompartmentKind::Main, None, &data, None).unwrap();
+        // Then
+        let messages: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Group \"A\" is its own ancestor (inheritance stops at the cycle)",
+                "Group \"B\" is its own ancestor (inheritance stops at the cycle)",
+                "Group \"D\" refers to parent group \"missing\", which doesn't exist",
+            ]
+        );
+    }
+
+    fn summarize(
+        issues: &[PresetValidationIssue],
+    ) -> Vec<(PresetValidationSeverity, Option<&str>)> {
+        issues
+            .iter()
+            .map(|i| (i.severity, i.mapping_key.as_ref().map(|k| k.as_ref())))
+            .collect()
+    }
+}
@@ -1,17 +1,25 @@
 use super::none_if_minus_one;
 use crate::application::{
-    Change, MidiSourceType, ReaperSourceType, SourceCategory, SourceCommand, SourceModel,
+    default_sequencer_steps, Change, MidiSourceType, ReaperSourceType, SourceCategory,
+    SourceCommand, SourceModel, DEFAULT_AUDIO_ATTACK_MILLIS, DEFAULT_AUDIO_CHANNEL_COUNT,
+    DEFAULT_AUDIO_RELEASE_MILLIS, DEFAULT_LFO_FREQUENCY, DEFAULT_SEQUENCER_BEATS_PER_STEP,
+    DEFAULT_SYSEX_DISPLAY_PADDING,
 };
 use crate::base::notification;
-use crate::domain::{CompartmentKind, CompartmentParamIndex, Keystroke};
+use crate::domain::{CompartmentKind, CompartmentParamIndex, Keystroke, SequencerStep};
 use crate::infrastructure::data::common::OscValueRange;
 use crate::infrastructure::data::VirtualControlElementIdData;
 use base::default_util::{deserialize_null_default, is_default};
 use helgoboss_learn::{DisplayType, MidiClockTransportMessage, OscTypeTag, SourceCharacter};
 use helgoboss_midi::{Channel, U14, U7};
-use helgobox_api::persistence::{MidiScriptKind, VirtualControlElementCharacter};
+use helgobox_api::persistence::{
+    AudioAnalysis, AudioLevelDetector, CompoundSourceKind, LfoWaveform, MidiScriptKind,
+    SequencerDirection, SysExDisplayAlignment, SysExDisplayEncoding,
+    VirtualControlElementCharacter,
+};
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 
 /// This is the structure in which source settings are loaded and saved. It's optimized for being
@@ -105,6 +113,43 @@ pub struct SourceModelData {
         skip_serializing_if = "is_default"
     )]
     pub line: Option<u8>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sysex_display_template: String,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sysex_display_length: Option<u32>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sysex_display_alignment: SysExDisplayAlignment,
+    /// `None` means the default padding character.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sysex_display_padding: Option<char>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sysex_display_encoding: SysExDisplayEncoding,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sysex_display_character_map: BTreeMap<char, u8>,
     // OSC
     #[serde(
         default,
@@ -181,6 +226,134 @@ pub struct SourceModelData {
         skip_serializing_if = "is_default"
     )]
     pub parameter_index: CompartmentParamIndex,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub lfo_waveform: LfoWaveform,
+    /// `None` means the default frequency.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub lfo_frequency: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub lfo_beats_per_cycle: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub lfo_phase_offset: f64,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub lfo_retrigger_parameter_index: Option<CompartmentParamIndex>,
+    /// `None` means the default steps.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sequencer_steps: Option<Vec<SequencerStep>>,
+    /// `None` means the default step length.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sequencer_beats_per_step: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sequencer_swing: f64,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sequencer_direction: SequencerDirection,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub sequencer_step_value_parameter_index: Option<CompartmentParamIndex>,
+    // Compound
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub compound_kind: CompoundSourceKind,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub compound_timeout_millis: Option<u64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub compound_members: Vec<SourceModelData>,
+    // Audio
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub audio_channel: u32,
+    /// `None` means the default channel count.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub audio_channel_count: Option<u32>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub audio_detector: AudioLevelDetector,
+    /// `None` means the default attack time.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub audio_attack_millis: Option<u64>,
+    /// `None` means the default release time.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub audio_release_millis: Option<u64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub audio_threshold_db: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    pub audio_analysis: AudioAnalysis,
 }
 
 impl SourceModelData {
@@ -204,6 +377,13 @@ impl SourceModelData {
             display_type: model.display_type(),
             display_id: model.display_id(),
             line: model.line(),
+            sysex_display_template: model.sysex_display_template().to_owned(),
+            sysex_display_length: model.sysex_display_length(),
+            sysex_display_alignment: model.sysex_display_alignment(),
+            sysex_display_padding: Some(model.sysex_display_padding())
+                .filter(|c| *c != DEFAULT_SYSEX_DISPLAY_PADDING),
+            sysex_display_encoding: model.sysex_display_encoding(),
+            sysex_display_character_map: model.sysex_display_character_map().clone(),
             osc_address_pattern: model.osc_address_pattern().to_owned(),
             osc_arg_index: model.osc_arg_index(),
             osc_arg_type: model.osc_arg_type_tag(),
@@ -218,6 +398,35 @@ impl SourceModelData {
             reaper_source_type: model.reaper_source_type(),
             timer_millis: model.timer_millis(),
             parameter_index: model.parameter_index(),
+            lfo_waveform: model.lfo_waveform(),
+            lfo_frequency: Some(model.lfo_frequency()).filter(|f| *f != DEFAULT_LFO_FREQUENCY),
+            lfo_beats_per_cycle: model.lfo_beats_per_cycle(),
+            lfo_phase_offset: model.lfo_phase_offset(),
+            lfo_retrigger_parameter_index: model.lfo_retrigger_parameter_index(),
+            sequencer_steps: Some(model.sequencer_steps().to_vec())
+                .filter(|steps| *steps != default_sequencer_steps()),
+            sequencer_beats_per_step: Some(model.sequencer_beats_per_step())
+                .filter(|b| *b != DEFAULT_SEQUENCER_BEATS_PER_STEP),
+            sequencer_swing: model.sequencer_swing(),
+            sequencer_direction: model.sequencer_direction(),
+            sequencer_step_value_parameter_index: model.sequencer_step_value_parameter_index(),
+            compound_kind: model.compound_kind(),
+            compound_timeout_millis: model.compound_timeout_millis(),
+            compound_members: model
+                .compound_members()
+                .iter()
+                .map(SourceModelData::from_model)
+                .collect(),
+            audio_channel: model.audio_channel(),
+            audio_channel_count: Some(model.audio_channel_count())
+                .filter(|c| *c != DEFAULT_AUDIO_CHANNEL_COUNT),
+            audio_detector: model.audio_detector(),
+            audio_attack_millis: Some(model.audio_attack_millis())
+                .filter(|m| *m != DEFAULT_AUDIO_ATTACK_MILLIS),
+            audio_release_millis: Some(model.audio_release_millis())
+                .filter(|m| *m != DEFAULT_AUDIO_RELEASE_MILLIS),
+            audio_threshold_db: model.audio_threshold_db(),
+            audio_analysis: model.audio_analysis(),
         }
     }
 
@@ -283,6 +492,19 @@ impl SourceModelData {
         model.change(P::SetDisplayType(self.display_type));
         model.change(P::SetDisplayId(self.display_id));
         model.change(P::SetLine(self.line));
+        model.change(P::SetSysExDisplayTemplate(
+            self.sysex_display_template.clone(),
+        ));
+        model.change(P::SetSysExDisplayLength(self.sysex_display_length));
+        model.change(P::SetSysExDisplayAlignment(self.sysex_display_alignment));
+        model.change(P::SetSysExDisplayPadding(
+            self.sysex_display_padding
+                .unwrap_or(DEFAULT_SYSEX_DISPLAY_PADDING),
+        ));
+        model.change(P::SetSysExDisplayEncoding(self.sysex_display_encoding));
+        model.change(P::SetSysExDisplayCharacterMap(
+            self.sysex_display_character_map.clone(),
+        ));
         model.change(P::SetOscAddressPattern(self.osc_address_pattern.clone()));
         model.change(P::SetOscArgIndex(self.osc_arg_index));
         model.change(P::SetOscArgTypeTag(self.osc_arg_type));
@@ -298,7 +520,58 @@ impl SourceModelData {
         model.change(P::SetReaperSourceType(self.reaper_source_type));
         model.change(P::SetTimerMillis(self.timer_millis));
         model.change(P::SetParameterIndex(self.parameter_index));
+        model.change(P::SetLfoWaveform(self.lfo_waveform));
+        model.change(P::SetLfoFrequency(
+            self.lfo_frequency.unwrap_or(DEFAULT_LFO_FREQUENCY),
+        ));
+        model.change(P::SetLfoBeatsPerCycle(self.lfo_beats_per_cycle));
+        model.change(P::SetLfoPhaseOffset(self.lfo_phase_offset));
+        model.change(P::SetLfoRetriggerParameterIndex(
+            self.lfo_retrigger_parameter_index,
+        ));
+        model.change(P::SetSequencerSteps(
+            self.sequencer_steps
+                .clone()
+                .unwrap_or_else(default_sequencer_steps),
+        ));
+        model.change(P::SetSequencerBeatsPerStep(
+            self.sequencer_beats_per_step
+                .unwrap_or(DEFAULT_SEQUENCER_BEATS_PER_STEP),
+        ));
+        model.change(P::SetSequencerSwing(self.sequencer_swing));
+        model.change(P::SetSequencerDirection(self.sequencer_direction));
+        model.change(P::SetSequencerStepValueParameterIndex(
+            self.sequencer_step_value_parameter_index,
+        ));
         model.change(P::SetKeystroke(self.keystroke));
+        model.change(P::SetCompoundKind(self.compound_kind));
+        model.change(P::SetCompoundTimeoutMillis(self.compound_timeout_millis));
+        let members = self
+            .compound_members
+            .iter()
+            .map(|data| {
+                let mut member = SourceModel::new();
+                data.apply_to_model_flexible(&mut member, compartment, preset_version);
+                member
+            })
+            .collect();
+        model.change(P::SetCompoundMembers(members));
+        model.change(P::SetAudioChannel(self.audio_channel));
+        model.change(P::SetAudioChannelCount(
+            self.audio_channel_count
+                .unwrap_or(DEFAULT_AUDIO_CHANNEL_COUNT),
+        ));
+        model.change(P::SetAudioDetector(self.audio_detector));
+        model.change(P::SetAudioAttackMillis(
+            self.audio_attack_millis
+                .unwrap_or(DEFAULT_AUDIO_ATTACK_MILLIS),
+        ));
+        model.change(P::SetAudioReleaseMillis(
+            self.audio_release_millis
+                .unwrap_or(DEFAULT_AUDIO_RELEASE_MILLIS),
+        ));
+        model.change(P::SetAudioThresholdDb(self.audio_threshold_db));
+        model.change(P::SetAudioAnalysis(self.audio_analysis));
     }
 }
 
@@ -1069,6 +1069,21 @@ pub fn serialize_track(track: TrackPropValues) -> TrackSerializationOutput {
             index: Some(track.index),
             ..Default::default()
         },
+        ByIndexInBank => TrackData {
+            guid: Some("index_in_bank".to_string()),
+            index: Some(track.index),
+            ..Default::default()
+        },
+        ByIndexInBankTcp => TrackData {
+            guid: Some("index_in_bank_tcp".to_string()),
+            index: Some(track.index),
+            ..Default::default()
+        },
+        ByIndexInBankMcp => TrackData {
+            guid: Some("index_in_bank_mcp".to_string()),
+            index: Some(track.index),
+            ..Default::default()
+        },
         Dynamic => TrackData {
             expression: Some(track.expression),
             ..Default::default()
@@ -1174,6 +1189,14 @@ pub fn serialize_fx(fx: FxPropValues) -> FxData {
             is_input_fx: fx.is_input_fx,
             expression: None,
         },
+        ByIndexInBank => FxData {
+            anchor: Some(VirtualFxType::ByIndexInBank),
+            index: Some(fx.index),
+            guid: None,
+            name: None,
+            is_input_fx: fx.is_input_fx,
+            expression: None,
+        },
     }
 }
 
@@ -1200,7 +1223,7 @@ pub fn serialize_fx_parameter(param: FxParameterPropValues) -> FxParameterData {
             name: None,
             expression: None,
         },
-        ByIndex => FxParameterData {
+        ByIndex | ByIndexInBank => FxParameterData {
             // Before 2.8.0 we didn't have a type and this was the default ... let's leave it
             // at that.
             r#type: Some(param.r#type),
@@ -1467,6 +1490,7 @@ pub fn deserialize_track(input: TrackDeserializationInput) -> TrackPropValues {
         } if g == "index_tcp" => TrackPropValues::from_virtual_track(VirtualTrack::ByIndex {
             index: *i,
             scope: TrackScope::TracksVisibleInTcp,
+            bank_relative: false,
         }),
         TrackData {
             guid: Some(g),
@@ -1475,7 +1499,39 @@ pub fn deserialize_track(input: TrackDeserializationInput) -> TrackPropValues {
         } if g == "index_mcp" => TrackPropValues::from_virtual_track(VirtualTrack::ByIndex {
             index: *i,
             scope: TrackScope::TracksVisibleInMcp,
+            bank_relative: false,
+        }),
+        TrackData {
+            guid: Some(g),
+            index: Some(i),
+            ..
+        } if g == "index_in_bank" => TrackPropValues::from_virtual_track(VirtualTrack::ByIndex {
+            index: *i,
+            scope: TrackScope::AllTracks,
+            bank_relative: true,
         }),
+        TrackData {
+            guid: Some(g),
+            index: Some(i),
+            ..
+        } if g == "index_in_bank_tcp" => {
+            TrackPropValues::from_virtual_track(VirtualTrack::ByIndex {
+                index: *i,
+                scope: TrackScope::TracksVisibleInTcp,
+                bank_relative: true,
+            })
+        }
+        TrackData {
+            guid: Some(g),
+            index: Some(i),
+            ..
+        } if g == "index_in_bank_mcp" => {
+            TrackPropValues::from_virtual_track(VirtualTrack::ByIndex {
+                index: *i,
+                scope: TrackScope::TracksVisibleInMcp,
+                bank_relative: true,
+            })
+        }
         TrackData {
             guid: Some(g),
             expression: Some(e),
@@ -1748,11 +1804,12 @@ pub fn deserialize_fx_parameter(param_data: &FxParameterData) -> FxParameterProp
             ..Default::default()
         },
         FxParameterData {
-            r#type: Some(VirtualFxParameterType::ByIndex),
+            r#type:
+                Some(t @ (VirtualFxParameterType::ByIndex | VirtualFxParameterType::ByIndexInBank)),
             index: i,
             ..
         } => FxParameterPropValues {
-            r#type: VirtualFxParameterType::ByIndex,
+            r#type: *t,
             index: *i,
             ..Default::default()
         },
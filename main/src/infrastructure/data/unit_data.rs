@@ -4,15 +4,17 @@ use crate::application::{
     WeakUnitModel,
 };
 use crate::domain::{
-    compartment_param_index_iter, CompartmentKind, CompartmentParamIndex, CompartmentParams,
-    ControlInput, FeedbackOutput, GroupId, GroupKey, MappingId, MappingKey,
-    MappingSnapshotContainer, MappingSnapshotId, MidiControlInput, MidiDestination, OscDeviceId,
-    Param, PluginParams, StayActiveWhenProjectInBackground, Tag, Unit,
+    compartment_param_index_iter, AdditionalIo, CompartmentBanks, CompartmentKind,
+    CompartmentParamIndex, CompartmentParams, ControlInput, FeedbackOutput, GroupId, GroupKey,
+    MappingId, MappingKey, MappingSnapshotContainer, MappingSnapshotId, MidiControlInput,
+    MidiDestination, MidiRoutingTable, OscDeviceId, Param, PluginParams,
+    StayActiveWhenProjectInBackground, Tag, Unit,
 };
 use crate::infrastructure::data::{
     convert_target_value_to_api, convert_target_value_to_model,
-    ensure_no_duplicate_compartment_data, CompartmentModelData, GroupModelData, MappingModelData,
-    MigrationDescriptor, ParameterData,
+    ensure_no_duplicate_compartment_data, instantiate_mapping_templates, CompartmentModelData,
+    GroupModelData, MappingModelData, MappingTemplateInstanceData, MigrationDescriptor,
+    ParameterData,
 };
 use crate::infrastructure::plugin::{update_auto_units_async, BackboneShell};
 use base::default_util::{bool_true, deserialize_null_default, is_bool_true, is_default};
@@ -21,7 +23,7 @@ use crate::base::notification;
 use crate::infrastructure::api::convert::to_data::ApiToDataConversionContext;
 use base::hash_util::{NonCryptoHashMap, NonCryptoHashSet};
 use helgobox_api::persistence::{
-    FxDescriptor, MappingInSnapshot, MappingSnapshot, TrackDescriptor,
+    FxDescriptor, MappingInSnapshot, MappingSnapshot, MappingTemplate, TrackDescriptor,
 };
 use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 use semver::Version;
@@ -68,6 +70,12 @@ pub struct UnitData {
     let_matched_events_through: bool,
     #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
     let_unmatched_events_through: bool,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    midi_routing: MidiRoutingTable,
     /// Introduced with ReaLearn 2.14.0-pre.1. Before that "Always".
     #[serde(
         default,
@@ -109,6 +117,20 @@ pub struct UnitData {
         skip_serializing_if = "is_default"
     )]
     feedback_device_id: Option<FeedbackDeviceId>,
+    /// Only MIDI and OSC devices.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    additional_control_device_ids: Vec<ControlDeviceId>,
+    /// Only MIDI and OSC devices.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    additional_feedback_device_ids: Vec<FeedbackDeviceId>,
     // Not set before 1.12.0-pre9
     #[serde(
         default,
@@ -151,6 +173,18 @@ pub struct UnitData {
         deserialize_with = "deserialize_null_default",
         skip_serializing_if = "is_default"
     )]
+    main_mapping_templates: Vec<MappingTemplate>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    controller_mapping_templates: Vec<MappingTemplate>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
     controller_custom_data: NonCryptoHashMap<String, serde_json::Value>,
     #[serde(
         default,
@@ -187,6 +221,18 @@ pub struct UnitData {
         deserialize_with = "deserialize_null_default",
         skip_serializing_if = "is_default"
     )]
+    controller_banks: CompartmentBanks,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    main_banks: CompartmentBanks,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
     active_controller_id: Option<String>,
     #[serde(
         default,
@@ -349,6 +395,12 @@ struct CompartmentState {
         skip_serializing_if = "is_default"
     )]
     active_mapping_snapshots: NonCryptoHashMap<Tag, MappingSnapshotId>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_default",
+        skip_serializing_if = "is_default"
+    )]
+    current_bank_index: u32,
 }
 
 impl CompartmentState {
@@ -360,31 +412,110 @@ impl CompartmentState {
                 .mapping_snapshot_container(compartment)
                 .active_snapshot_id_by_tag()
                 .clone(),
+            current_bank_index: instance_state.current_bank_index(compartment),
         }
     }
 }
 
+/// `Midi("fx-input")` means "\<FX input>".
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
-enum ControlDeviceId {
+pub enum ControlDeviceId {
     Keyboard(KeyboardDevice),
+    WebSocket(WebSocketDevice),
     Osc(OscDeviceId),
     Midi(String),
 }
 
+impl ControlDeviceId {
+    pub fn from_control_input(input: ControlInput) -> Self {
+        match input {
+            ControlInput::Midi(MidiControlInput::FxInput) => {
+                ControlDeviceId::Midi("fx-input".to_owned())
+            }
+            ControlInput::Midi(MidiControlInput::Device(dev_id)) => {
+                ControlDeviceId::Midi(dev_id.to_string())
+            }
+            ControlInput::Osc(dev_id) => ControlDeviceId::Osc(dev_id),
+            ControlInput::Keyboard => ControlDeviceId::Keyboard(KeyboardDevice::TheKeyboard),
+            ControlInput::WebSocket => ControlDeviceId::WebSocket(WebSocketDevice::TheWebSocket),
+        }
+    }
+
+    pub fn to_control_input(&self) -> Result<ControlInput, &'static str> {
+        use ControlDeviceId::*;
+        let input = match self {
+            Keyboard(_) => ControlInput::Keyboard,
+            WebSocket(_) => ControlInput::WebSocket,
+            Midi(s) if s == "fx-input" => ControlInput::Midi(MidiControlInput::FxInput),
+            Midi(midi_dev_id_string) => {
+                let raw_midi_dev_id = midi_dev_id_string
+                    .parse::<u8>()
+                    .map_err(|_| "invalid MIDI input device ID")?;
+                let midi_dev_id = MidiInputDeviceId::new(raw_midi_dev_id);
+                ControlInput::Midi(MidiControlInput::Device(midi_dev_id))
+            }
+            Osc(osc_dev_id) => ControlInput::Osc(*osc_dev_id),
+        };
+        Ok(input)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-enum KeyboardDevice {
+pub enum KeyboardDevice {
     #[serde(rename = "keyboard")]
     TheKeyboard,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WebSocketDevice {
+    #[serde(rename = "web-socket")]
+    TheWebSocket,
+}
+
+/// `MidiOrFxOutput("fx-output")` means "\<FX output>".
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
-enum FeedbackDeviceId {
+pub enum FeedbackDeviceId {
+    WebSocket(WebSocketDevice),
     Osc(OscDeviceId),
     MidiOrFxOutput(String),
 }
 
+impl FeedbackDeviceId {
+    pub fn from_feedback_output(output: FeedbackOutput) -> Self {
+        match output {
+            FeedbackOutput::Midi(MidiDestination::FxOutput) => {
+                FeedbackDeviceId::MidiOrFxOutput("fx-output".to_owned())
+            }
+            FeedbackOutput::Midi(MidiDestination::Device(dev_id)) => {
+                FeedbackDeviceId::MidiOrFxOutput(dev_id.to_string())
+            }
+            FeedbackOutput::Osc(dev_id) => FeedbackDeviceId::Osc(dev_id),
+            FeedbackOutput::WebSocket => FeedbackDeviceId::WebSocket(WebSocketDevice::TheWebSocket),
+        }
+    }
+
+    pub fn to_feedback_output(&self) -> Result<FeedbackOutput, &'static str> {
+        use FeedbackDeviceId::*;
+        let output = match self {
+            WebSocket(_) => FeedbackOutput::WebSocket,
+            MidiOrFxOutput(s) if s == "fx-output" => {
+                FeedbackOutput::Midi(MidiDestination::FxOutput)
+            }
+            MidiOrFxOutput(midi_dev_id_string) => {
+                let midi_dev_id = midi_dev_id_string
+                    .parse::<u8>()
+                    .map(MidiOutputDeviceId::new)
+                    .map_err(|_| "invalid MIDI output device ID")?;
+                FeedbackOutput::Midi(MidiDestination::Device(midi_dev_id))
+            }
+            Osc(osc_dev_id) => FeedbackOutput::Osc(*osc_dev_id),
+        };
+        Ok(output)
+    }
+}
+
 impl Default for UnitData {
     #[allow(deprecated)]
     fn default() -> Self {
@@ -395,6 +526,7 @@ impl Default for UnitData {
             name: None,
             let_matched_events_through: session_defaults::LET_MATCHED_EVENTS_THROUGH,
             let_unmatched_events_through: session_defaults::LET_UNMATCHED_EVENTS_THROUGH,
+            midi_routing: Default::default(),
             stay_active_when_project_in_background: Some(
                 session_defaults::STAY_ACTIVE_WHEN_PROJECT_IN_BACKGROUND,
             ),
@@ -405,18 +537,24 @@ impl Default for UnitData {
                 session_defaults::RESET_FEEDBACK_WHEN_RELEASING_SOURCE,
             control_device_id: None,
             feedback_device_id: None,
+            additional_control_device_ids: vec![],
+            additional_feedback_device_ids: vec![],
             default_group: None,
             default_controller_group: None,
             groups: vec![],
             controller_groups: vec![],
             mappings: vec![],
             controller_mappings: vec![],
+            main_mapping_templates: vec![],
+            controller_mapping_templates: vec![],
             controller_custom_data: Default::default(),
             main_custom_data: Default::default(),
             controller_common_lua: Default::default(),
             main_common_lua: Default::default(),
             controller_notes: Default::default(),
             main_notes: Default::default(),
+            controller_banks: Default::default(),
+            main_banks: Default::default(),
             active_controller_id: None,
             active_main_preset_id: None,
             main_preset_auto_load_mode: session_defaults::MAIN_PRESET_AUTO_LOAD_MODE,
@@ -447,6 +585,8 @@ impl UnitData {
             let compartment_in_session = CompartmentInSession::new(session, compartment);
             session
                 .mappings(compartment)
+                // Template instances are materialized again when loading
+                .filter(|m| !m.borrow().is_template_instance())
                 .map(|m| MappingModelData::from_model(m.borrow().deref(), &compartment_in_session))
                 .collect()
         };
@@ -476,6 +616,7 @@ impl UnitData {
             name: session.name.clone(),
             let_matched_events_through: session.let_matched_events_through.get(),
             let_unmatched_events_through: session.let_unmatched_events_through.get(),
+            midi_routing: session.midi_routing.get_ref().clone(),
             stay_active_when_project_in_background: Some(
                 session.stay_active_when_project_in_background.get(),
             ),
@@ -488,25 +629,33 @@ impl UnitData {
             control_device_id: {
                 match session.control_input() {
                     ControlInput::Midi(MidiControlInput::FxInput) => None,
-                    ControlInput::Midi(MidiControlInput::Device(dev_id)) => {
-                        Some(ControlDeviceId::Midi(dev_id.to_string()))
-                    }
-                    ControlInput::Osc(dev_id) => Some(ControlDeviceId::Osc(dev_id)),
-                    ControlInput::Keyboard => {
-                        Some(ControlDeviceId::Keyboard(KeyboardDevice::TheKeyboard))
-                    }
+                    input => Some(ControlDeviceId::from_control_input(input)),
                 }
             },
-            feedback_device_id: {
-                session.feedback_output().map(|output| match output {
-                    FeedbackOutput::Midi(MidiDestination::FxOutput) => {
-                        FeedbackDeviceId::MidiOrFxOutput("fx-output".to_owned())
-                    }
-                    FeedbackOutput::Midi(MidiDestination::Device(dev_id)) => {
-                        FeedbackDeviceId::MidiOrFxOutput(dev_id.to_string())
-                    }
-                    FeedbackOutput::Osc(dev_id) => FeedbackDeviceId::Osc(dev_id),
-                })
+            feedback_device_id: session
+                .feedback_output()
+                .map(FeedbackDeviceId::from_feedback_output),
+            additional_control_device_ids: {
+                let additional_io = session.additional_io.get_ref();
+                additional_io
+                    .control_inputs
+                    .iter()
+                    .map(|i| {
+                        ControlDeviceId::from_control_input(ControlInput::from_device_input(*i))
+                    })
+                    .collect()
+            },
+            additional_feedback_device_ids: {
+                let additional_io = session.additional_io.get_ref();
+                additional_io
+                    .feedback_outputs
+                    .iter()
+                    .map(|o| {
+                        FeedbackDeviceId::from_feedback_output(FeedbackOutput::from_device_output(
+                            *o,
+                        ))
+                    })
+                    .collect()
             },
             default_group: from_group(CompartmentKind::Main),
             default_controller_group: from_group(CompartmentKind::Controller),
@@ -514,6 +663,10 @@ impl UnitData {
             controller_groups: from_groups(CompartmentKind::Controller),
             mappings: from_mappings(CompartmentKind::Main),
             controller_mappings: from_mappings(CompartmentKind::Controller),
+            main_mapping_templates: session.mapping_templates(CompartmentKind::Main).to_vec(),
+            controller_mapping_templates: session
+                .mapping_templates(CompartmentKind::Controller)
+                .to_vec(),
             controller_custom_data: unit
                 .custom_compartment_data(CompartmentKind::Controller)
                 .clone(),
@@ -528,6 +681,8 @@ impl UnitData {
                 .compartment_notes(CompartmentKind::Controller)
                 .to_owned(),
             main_notes: session.compartment_notes(CompartmentKind::Main).to_owned(),
+            controller_banks: unit.banks(CompartmentKind::Controller).clone(),
+            main_banks: unit.banks(CompartmentKind::Main).clone(),
             active_controller_id: session
                 .active_preset_id(CompartmentKind::Controller)
                 .map(|id| id.to_string()),
@@ -607,57 +762,59 @@ impl UnitData {
     ) -> Result<(), Box<dyn Error>> {
         // Validation
         let params = self.create_params();
+        let main_template_instances = self.instantiate_mapping_templates(
+            CompartmentKind::Main,
+            &self.main_mapping_templates,
+            &params,
+        )?;
+        let controller_template_instances = self.instantiate_mapping_templates(
+            CompartmentKind::Controller,
+            &self.controller_mapping_templates,
+            &params,
+        )?;
+        let main_mappings =
+            combine_mappings_with_template_instances(&self.mappings, &main_template_instances);
+        let controller_mappings = combine_mappings_with_template_instances(
+            &self.controller_mappings,
+            &controller_template_instances,
+        );
         let main_conversion_context = SimpleDataToModelConversionContext::from_session_or_random(
             &self.groups,
-            &self.mappings,
+            &main_mappings,
             Some(CompartmentInSession::new(session, CompartmentKind::Main)),
         );
         ensure_no_duplicate_compartment_data(
-            &self.mappings,
+            &main_mappings,
             &self.groups,
             self.parameters.values().map(|p| &p.setting),
         )?;
         ensure_no_duplicate_compartment_data(
-            &self.controller_mappings,
+            &controller_mappings,
             &self.controller_groups,
             self.controller_parameters.values().map(|p| &p.setting),
         )?;
         let control_input = match self.control_device_id.as_ref() {
             None => ControlInput::Midi(MidiControlInput::FxInput),
-            Some(dev_id) => {
-                use ControlDeviceId::*;
-                match dev_id {
-                    Keyboard(_) => ControlInput::Keyboard,
-                    Midi(midi_dev_id_string) => {
-                        let raw_midi_dev_id = midi_dev_id_string
-                            .parse::<u8>()
-                            .map_err(|_| "invalid MIDI input device ID")?;
-                        let midi_dev_id = MidiInputDeviceId::new(raw_midi_dev_id);
-                        ControlInput::Midi(MidiControlInput::Device(midi_dev_id))
-                    }
-                    Osc(osc_dev_id) => ControlInput::Osc(*osc_dev_id),
-                }
-            }
+            Some(dev_id) => dev_id.to_control_input()?,
         };
-        let feedback_output = match self.feedback_device_id.as_ref() {
-            None => None,
-            Some(dev_id) => {
-                use FeedbackDeviceId::*;
-                let output = match dev_id {
-                    MidiOrFxOutput(s) if s == "fx-output" => {
-                        FeedbackOutput::Midi(MidiDestination::FxOutput)
-                    }
-                    MidiOrFxOutput(midi_dev_id_string) => {
-                        let midi_dev_id = midi_dev_id_string
-                            .parse::<u8>()
-                            .map(MidiOutputDeviceId::new)
-                            .map_err(|_| "invalid MIDI output device ID")?;
-                        FeedbackOutput::Midi(MidiDestination::Device(midi_dev_id))
-                    }
-                    Osc(osc_dev_id) => FeedbackOutput::Osc(*osc_dev_id),
-                };
-                Some(output)
-            }
+        let feedback_output = self
+            .feedback_device_id
+            .as_ref()
+            .map(|dev_id| dev_id.to_feedback_output())
+            .transpose()?;
+        let additional_io = AdditionalIo {
+            control_inputs: self
+                .additional_control_device_ids
+                .iter()
+                .map(|dev_id| Ok(dev_id.to_control_input()?.device_input()))
+                .filter_map(Result::transpose)
+                .collect::<Result<_, &'static str>>()?,
+            feedback_outputs: self
+                .additional_feedback_device_ids
+                .iter()
+                .map(|dev_id| Ok(dev_id.to_feedback_output()?.device_output()))
+                .filter_map(Result::transpose)
+                .collect::<Result<_, &'static str>>()?,
         };
         let main_mapping_snapshot_container = convert_mapping_snapshots_to_model(
             &self.mapping_snapshots,
@@ -690,6 +847,9 @@ impl UnitData {
         session
             .feedback_output
             .set_without_notification(feedback_output);
+        session
+            .additional_io
+            .set_without_notification(additional_io);
         // Let events through or not
         {
             let is_old_preset = self
@@ -718,6 +878,9 @@ impl UnitData {
             session
                 .let_unmatched_events_through
                 .set_without_notification(unmatched);
+            session
+                .midi_routing
+                .set_without_notification(self.midi_routing.clone());
             let stay_active_when_project_in_background = self
                 .stay_active_when_project_in_background
                 .unwrap_or(StayActiveWhenProjectInBackground::Always);
@@ -729,7 +892,7 @@ impl UnitData {
         let controller_conversion_context =
             SimpleDataToModelConversionContext::from_session_or_random(
                 &self.controller_groups,
-                &self.controller_mappings,
+                &controller_mappings,
                 Some(CompartmentInSession::new(
                     session,
                     CompartmentKind::Controller,
@@ -783,25 +946,47 @@ impl UnitData {
         session.set_groups_without_notification(CompartmentKind::Controller, controller_groups);
         // Mappings
 
-        let mut apply_mappings =
-            |compartment, mappings: &Vec<MappingModelData>| -> Result<(), &'static str> {
-                let mappings: Result<Vec<_>, _> = mappings
-                    .iter()
-                    .map(|m| {
-                        m.to_model_flexible(
-                            compartment,
-                            &migration_descriptor,
-                            self.version.as_ref(),
-                            conversion_context(compartment),
-                            Some(session.extended_context_with_params(&params)),
-                        )
-                    })
-                    .collect();
-                session.set_mappings_without_notification(compartment, mappings?);
-                Ok(())
-            };
-        apply_mappings(CompartmentKind::Main, &self.mappings)?;
-        apply_mappings(CompartmentKind::Controller, &self.controller_mappings)?;
+        let mut apply_mappings = |compartment,
+                                  mappings: &Vec<MappingModelData>,
+                                  template_instances: &[MappingTemplateInstanceData],
+                                  templates: &Vec<MappingTemplate>|
+         -> Result<(), &'static str> {
+            let mut mappings: Vec<_> = mappings
+                .iter()
+                .map(|m| {
+                    m.to_model_flexible(
+                        compartment,
+                        &migration_descriptor,
+                        self.version.as_ref(),
+                        conversion_context(compartment),
+                        Some(session.extended_context_with_params(&params)),
+                    )
+                })
+                .collect::<Result<_, _>>()?;
+            // Template instances come last
+            let first_instance_index = mappings.len() - template_instances.len();
+            for (m, instance) in mappings[first_instance_index..]
+                .iter_mut()
+                .zip(template_instances)
+            {
+                m.set_template_id(Some(instance.template_id.clone()));
+            }
+            session.set_mappings_without_notification(compartment, mappings);
+            session.set_mapping_templates_without_notification(compartment, templates.clone());
+            Ok(())
+        };
+        apply_mappings(
+            CompartmentKind::Main,
+            &main_mappings,
+            &main_template_instances,
+            &self.main_mapping_templates,
+        )?;
+        apply_mappings(
+            CompartmentKind::Controller,
+            &controller_mappings,
+            &controller_template_instances,
+            &self.controller_mapping_templates,
+        )?;
         let _ = session.change(SessionCommand::ChangeCompartment(
             CompartmentKind::Controller,
             CompartmentCommand::SetCommonLua(self.controller_common_lua.clone()),
@@ -852,6 +1037,14 @@ impl UnitData {
                 self.controller_custom_data.clone(),
             );
             unit.set_custom_compartment_data(CompartmentKind::Main, self.main_custom_data.clone());
+            // Banks (definitions before current bank because the latter is clamped)
+            unit.set_banks(CompartmentKind::Controller, self.controller_banks.clone());
+            unit.set_banks(CompartmentKind::Main, self.main_banks.clone());
+            unit.set_current_bank_index(
+                CompartmentKind::Controller,
+                self.controller.current_bank_index,
+            );
+            unit.set_current_bank_index(CompartmentKind::Main, self.main.current_bank_index);
             unit.parameter_manager().set_all_parameters(params);
             unit.set_active_instance_tags_without_notification(self.active_instance_tags.clone());
             // Compartment-specific
@@ -887,6 +1080,19 @@ impl UnitData {
         Ok(())
     }
 
+    fn instantiate_mapping_templates(
+        &self,
+        compartment: CompartmentKind,
+        templates: &[MappingTemplate],
+        params: &PluginParams,
+    ) -> anyhow::Result<Vec<MappingTemplateInstanceData>> {
+        let compartment_params = params.compartment_params(compartment);
+        instantiate_mapping_templates(templates, compartment, |key| {
+            let (index, _) = compartment_params.find_setting_by_key(key)?;
+            Some(index)
+        })
+    }
+
     fn create_params(&self) -> PluginParams {
         let mut params = PluginParams::default();
         fill_compartment_params(
@@ -1130,3 +1336,65 @@ fn convert_mapping_snapshots_to_model(
         active_snapshot_id_by_tag.clone(),
     ))
 }
+
+/// Appends the mappings materialized from templates to the saved mappings.
+fn combine_mappings_with_template_instances(
+    mappings: &[MappingModelData],
+    template_instances: &[MappingTemplateInstanceData],
+) -> Vec<MappingModelData> {
+    mappings
+        .iter()
+        .chain(template_instances.iter().map(|i| &i.mapping))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_device_id_round_trip() {
+        // Given
+        let inputs = [
+            ControlInput::Midi(MidiControlInput::FxInput),
+            ControlInput::Midi(MidiControlInput::Device(MidiInputDeviceId::new(3))),
+            ControlInput::Osc(OscDeviceId::random()),
+            ControlInput::Keyboard,
+            ControlInput::WebSocket,
+        ];
+        for input in inputs {
+            // When
+            let device_id = ControlDeviceId::from_control_input(input);
+            // Then
+            assert_eq!(device_id.to_control_input(), Ok(input));
+        }
+    }
+
+    #[test]
+    fn control_device_id_rejects_invalid_midi_device() {
+        // Given
+        let device_id = ControlDeviceId::Midi("abc".to_owned());
+        // When
+        let result = device_id.to_control_input();
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn feedback_device_id_round_trip() {
+        // Given
+        let outputs = [
+            FeedbackOutput::Midi(MidiDestination::FxOutput),
+            FeedbackOutput::Midi(MidiDestination::Device(MidiOutputDeviceId::new(7))),
+            FeedbackOutput::Osc(OscDeviceId::random()),
+            FeedbackOutput::WebSocket,
+        ];
+        for output in outputs {
+            // When
+            let device_id = FeedbackDeviceId::from_feedback_output(output);
+            // Then
+            assert_eq!(device_id.to_feedback_output(), Ok(output));
+        }
+    }
+}
@@ -2,7 +2,10 @@ use crate::application::{
     AutoUnitData, ControllerPresetUsage, ControllerSuitability, MainPresetSuitability,
 };
 use crate::base::notification::notify_user_on_anyhow_error;
-use crate::domain::{parse_hex_string, DeviceControlInput, DeviceFeedbackOutput, OscDeviceId};
+use crate::domain::{
+    parse_hex_string, parse_midi_manufacturer_id, DeviceControlInput, DeviceFeedbackOutput,
+    OscDeviceId,
+};
 use crate::infrastructure::data::PresetInfo;
 use crate::infrastructure::plugin::{BackboneShell, InstanceShellInfo};
 use anyhow::Context;
@@ -11,7 +14,7 @@ use base::hash_util::NonCryptoHashMap;
 use base::Global;
 use helgobox_api::persistence::{
     Controller, ControllerConnection, ControllerPresetMetaData, MainPresetMetaData,
-    MidiControllerConnection,
+    MidiControllerConnection, MidiDeviceIdentity, MidiDeviceIdentityPattern,
 };
 use reaper_high::{MidiInputDevice, MidiOutputDevice, Reaper};
 use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
@@ -212,13 +215,22 @@ fn get_suitability_of_controller_preset_for_controller(
     match connection {
         ControllerConnection::Midi(con) => {
             // This is a MIDI controller
-            let identity_pattern_suitability =
-                if let Some(p) = &controller_preset_meta_data.midi_identity_pattern {
-                    get_suitability_of_midi_identity_pattern_for_controller(p, con)
+            let identity_pattern_suitability = if let (Some(p), Some(identity)) = (
+                &controller_preset_meta_data.midi_device_identity,
+                &con.device_identity,
+            ) {
+                // Structured identity is more reliable than the raw identity response
+                if midi_device_identity_matches_pattern(identity, p) {
+                    ControllerSuitability::Suitable
                 } else {
-                    // Controller preset doesn't define any identity pattern, which could just be laziness
-                    ControllerSuitability::MaybeSuitable
-                };
+                    ControllerSuitability::NotSuitable
+                }
+            } else if let Some(p) = &controller_preset_meta_data.midi_identity_pattern {
+                get_suitability_of_midi_identity_pattern_for_controller(p, con)
+            } else {
+                // Controller preset doesn't define any identity pattern, which could just be laziness
+                ControllerSuitability::MaybeSuitable
+            };
             if identity_pattern_suitability.is_not_suitable() {
                 // Identity doesn't match, this is a no-go
                 return ControllerSuitability::NotSuitable;
@@ -249,6 +261,7 @@ fn get_suitability_of_controller_preset_for_controller(
         ControllerConnection::Osc(_) => {
             // This is an OSC controller
             if controller_preset_meta_data.midi_identity_pattern.is_some()
+                || controller_preset_meta_data.midi_device_identity.is_some()
                 || !controller_preset_meta_data
                     .midi_output_port_patterns
                     .is_empty()
@@ -412,6 +425,38 @@ fn instance_comparator(a: &InstanceShellInfo, b: &InstanceShellInfo) -> Ordering
     }
 }
 
+pub fn midi_device_identity_matches_pattern(
+    identity: &MidiDeviceIdentity,
+    pattern: &MidiDeviceIdentityPattern,
+) -> bool {
+    let Some(expected_manufacturer_id) = parse_midi_manufacturer_id(&pattern.manufacturer_id)
+    else {
+        tracing::warn!(
+            "Invalid manufacturer ID in controller preset: {}",
+            pattern.manufacturer_id
+        );
+        return false;
+    };
+    if parse_midi_manufacturer_id(&identity.manufacturer_id) != Some(expected_manufacturer_id) {
+        return false;
+    }
+    if pattern.family.is_some_and(|f| f != identity.family) {
+        return false;
+    }
+    if pattern.model.is_some_and(|m| m != identity.model) {
+        return false;
+    }
+    let supported_profiles: Vec<_> = identity
+        .profiles
+        .iter()
+        .filter_map(|p| parse_hex_string(p).ok())
+        .collect();
+    pattern
+        .profiles
+        .iter()
+        .all(|p| parse_hex_string(p).is_ok_and(|expected| supported_profiles.contains(&expected)))
+}
+
 pub fn midi_output_port_patterns_match(patterns: &[String], out_port_name: &str) -> bool {
     if patterns.is_empty() {
         return true;
@@ -422,3 +467,113 @@ pub fn midi_output_port_patterns_match(patterns: &[String], out_port_name: &str)
         wild_match.matches(&lower_case_out_port_name)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launchpad_identity() -> MidiDeviceIdentity {
+        MidiDeviceIdentity {
+            manufacturer_id: "00 20 29".to_string(),
+            family: 0x0113,
+            model: 0x0001,
+            version: "00 00 00 01".to_string(),
+            profiles: vec!["7E 00 01 01 01".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn identity_matches_exact_pattern() {
+        // Given
+        let pattern = MidiDeviceIdentityPattern {
+            manufacturer_id: "002029".to_string(),
+            family: Some(0x0113),
+            model: Some(0x0001),
+            profiles: vec!["7E 00 01 01 01".to_string()],
+        };
+        // When
+        // Then
+        assert!(midi_device_identity_matches_pattern(
+            &launchpad_identity(),
+            &pattern
+        ));
+    }
+
+    #[test]
+    fn identity_matches_wildcard_pattern() {
+        // Given
+        let pattern = MidiDeviceIdentityPattern {
+            manufacturer_id: "00 20 29".to_string(),
+            family: None,
+            model: None,
+            profiles: vec![],
+        };
+        // When
+        // Then
+        assert!(midi_device_identity_matches_pattern(
+            &launchpad_identity(),
+            &pattern
+        ));
+    }
+
+    #[test]
+    fn identity_doesnt_match_pattern() {
+        // Given
+        let pattern = MidiDeviceIdentityPattern {
+            manufacturer_id: "00 20 29".to_string(),
+            ..Default::default()
+        };
+        let other_manufacturer = MidiDeviceIdentityPattern {
+            manufacturer_id: "47".to_string(),
+            ..pattern.clone()
+        };
+        let other_model = MidiDeviceIdentityPattern {
+            model: Some(0x0002),
+            ..pattern.clone()
+        };
+        let unsupported_profile = MidiDeviceIdentityPattern {
+            profiles: vec!["7E 00 02 01 01".to_string()],
+            ..pattern.clone()
+        };
+        let invalid_manufacturer = MidiDeviceIdentityPattern {
+            manufacturer_id: "00 20".to_string(),
+            ..pattern
+        };
+        // When
+        // Then
+        let identity = launchpad_identity();
+        assert!(!midi_device_identity_matches_pattern(
+            &identity,
+            &other_manufacturer
+        ));
+        assert!(!midi_device_identity_matches_pattern(
+            &identity,
+            &other_model
+        ));
+        assert!(!midi_device_identity_matches_pattern(
+            &identity,
+            &unsupported_profile
+        ));
+        assert!(!midi_device_identity_matches_pattern(
+            &identity,
+            &invalid_manufacturer
+        ));
+    }
+
+    #[test]
+    fn output_port_patterns() {
+        // Given
+        let patterns = vec!["Launchpad*".to_string(), "*MIDI ?".to_string()];
+        // When
+        // Then
+        assert!(midi_output_port_patterns_match(&[], "Anything"));
+        assert!(midi_output_port_patterns_match(
+            &patterns,
+            "launchpad X LPX MIDI In"
+        ));
+        assert!(midi_output_port_patterns_match(&patterns, "Some MIDI 2"));
+        assert!(!midi_output_port_patterns_match(&patterns, "Some MIDI 12"));
+        assert!(!midi_output_port_patterns_match(&patterns, "APC40"));
+    }
+}
@@ -4,12 +4,17 @@ use crate::application::{
 };
 use crate::base::notification;
 use crate::domain::{
+    build_midi_ci_discovery, build_midi_ci_profile_inquiry,
+    build_midi_ci_property_exchange_capabilities_inquiry, build_midi_ci_property_get_inquiry,
     format_as_pretty_hex, ActionInvokedEvent, AdditionalFeedbackEvent, Backbone,
     ChangeInstanceFxArgs, ChangeInstanceTrackArgs, CompartmentKind, ControlSurfaceEventHandler,
     DeviceDiff, EnableInstancesArgs, Exclusivity, FeedbackAudioHookTask, GroupId,
     HelgoboxWindowSnitch, InputDescriptor, InstanceContainerCommonArgs, InstanceFxChangeRequest,
     InstanceId, InstanceTrackChangeRequest, LastTouchedTargetFilter, MainProcessor,
-    MessageCaptureEvent, MessageCaptureResult, MidiInDevsConfig, MidiOutDevsConfig, MidiScanResult,
+    MessageCaptureEvent, MessageCaptureResult, MidiCiDevice, MidiCiDeviceInfo,
+    MidiCiDiscoveryReply, MidiCiMessageType, MidiCiMuid, MidiCiProfileInquiryReply,
+    MidiCiPropertyChunk, MidiCiTransactionCommand, MidiCiTransactionReply,
+    MidiDeviceIdentification, MidiInDevsConfig, MidiOutDevsConfig, MidiScanResult,
     NormalAudioHookTask, OscDeviceId, OscFeedbackProcessor, OscFeedbackTask, OscScanResult,
     ProcessorContext, QualifiedInstanceEvent, QualifiedMappingId, RealearnAccelerator,
     RealearnAudioHook, RealearnControlSurfaceMainTask, RealearnControlSurfaceMiddleware,
@@ -27,6 +32,7 @@ use crate::infrastructure::data::{
     SharedOscDeviceManager, SharedPresetLinkManager,
 };
 use crate::infrastructure::server;
+use crate::infrastructure::server::auth::ServerAccessSettings;
 use crate::infrastructure::server::{
     MetricsReporter, RealearnServer, SharedRealearnServer, COMPANION_WEB_APP_URL,
 };
@@ -58,6 +64,7 @@ use anyhow::{bail, Context};
 use base::hash_util::NonCryptoHashSet;
 use base::metrics_util::MetricsHook;
 use camino::{Utf8Path, Utf8PathBuf};
+use helgoboss_learn::RawMidiEvent;
 use helgobox_allocator::{start_async_deallocation_thread, AsyncDeallocatorCommandReceiver};
 use helgobox_api::persistence::{
     CompartmentPresetId, Controller, ControllerConnection, Envelope, FxChainDescriptor,
@@ -86,6 +93,7 @@ use std::cell::{Ref, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr};
 use std::rc::{Rc, Weak};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -386,10 +394,12 @@ impl BackboneShell {
             config.main.server_http_port,
             config.main.server_https_port,
             config.main.server_grpc_port,
+            config.main.server_bind_address(),
             BackboneShell::server_resource_dir_path()
                 .join("certificates")
                 .into(),
             MetricsReporter::new(),
+            ServerAccessSettings::from_app_settings(BackboneShell::read_app_settings().as_deref()),
         );
         // OSC devices are reconnected only if device list changes (= while instance active)
         let osc_feedback_processor = OscFeedbackProcessor::new(osc_feedback_task_receiver);
@@ -1090,7 +1100,13 @@ impl BackboneShell {
                 .parent()
                 .context("app settings file should have parent")?,
         )?;
-        fs::write(file_path, settings)?;
+        fs::write(file_path, &settings)?;
+        // Access tokens are part of the app settings, so revoked tokens must stop working now
+        let access_settings = ServerAccessSettings::from_app_settings(Some(&settings));
+        BackboneShell::get()
+            .server()
+            .borrow()
+            .update_access_settings(access_settings);
         Ok(())
     }
 
@@ -1153,6 +1169,10 @@ impl BackboneShell {
         Self::realearn_preset_dir_path().join(sub_dir)
     }
 
+    pub fn realearn_control_input_recording_dir_path() -> Utf8PathBuf {
+        Self::realearn_data_dir_path().join("control-input-recordings")
+    }
+
     pub fn realearn_auto_load_configs_dir_path() -> Utf8PathBuf {
         Self::realearn_data_dir_path().join("auto-load-configs")
     }
@@ -1861,6 +1881,155 @@ impl BackboneShell {
         receiver
     }
 
+    /// Identifies the device connected to the given MIDI output by sending a classic device
+    /// inquiry and a MIDI-CI discovery at the same time.
+    pub async fn identify_midi_device(
+        &self,
+        output_device_id: MidiOutputDeviceId,
+        input_device_id: Option<MidiInputDeviceId>,
+    ) -> Result<MidiDeviceIdentification, &'static str> {
+        let (inquiry_result, midi_ci_result) = futures::join!(
+            self.request_midi_device_identity(output_device_id, input_device_id),
+            self.discover_midi_ci_device(output_device_id, input_device_id)
+        );
+        let inquiry_reply = inquiry_result.ok();
+        let midi_ci_result = midi_ci_result.ok();
+        let input_device_id = inquiry_reply
+            .as_ref()
+            .map(|r| r.input_device_id)
+            .or_else(|| midi_ci_result.as_ref().map(|(id, _)| *id))
+            .ok_or("no device reply received")?;
+        let identification = MidiDeviceIdentification {
+            input_device_id,
+            device_inquiry_reply: inquiry_reply.map(|r| r.device_inquiry_reply),
+            midi_ci_device: midi_ci_result.map(|(_, d)| d),
+        };
+        Ok(identification)
+    }
+
+    /// Runs MIDI-CI discovery and queries profiles and device info if the device supports it.
+    async fn discover_midi_ci_device(
+        &self,
+        output_device_id: MidiOutputDeviceId,
+        input_device_id: Option<MidiInputDeviceId>,
+    ) -> Result<(MidiInputDeviceId, MidiCiDevice), &'static str> {
+        let muid = MidiCiMuid::random();
+        let reply = self
+            .send_midi_ci_message(
+                output_device_id,
+                input_device_id,
+                muid,
+                &build_midi_ci_discovery(muid),
+                MidiCiMessageType::DiscoveryReply,
+            )?
+            .recv()
+            .await
+            .map_err(|_| "no MIDI-CI discovery reply received")?;
+        let discovery_reply =
+            MidiCiDiscoveryReply::parse(&reply.message).ok_or("invalid MIDI-CI discovery reply")?;
+        tracing::debug!(msg = "Received MIDI-CI discovery reply", ?discovery_reply);
+        // From now on, we know where the device replies
+        let input_device_id = reply.input_device_id;
+        let device_muid = discovery_reply.muid;
+        let profiles = if discovery_reply.supports_profile_configuration() {
+            let profile_reply = self
+                .send_midi_ci_message(
+                    output_device_id,
+                    Some(input_device_id),
+                    muid,
+                    &build_midi_ci_profile_inquiry(muid, device_muid),
+                    MidiCiMessageType::ProfileInquiryReply,
+                )?
+                .recv()
+                .await
+                .ok()
+                .and_then(|r| MidiCiProfileInquiryReply::parse(&r.message))
+                .unwrap_or_default();
+            profile_reply
+                .enabled
+                .into_iter()
+                .chain(profile_reply.disabled)
+                .collect()
+        } else {
+            vec![]
+        };
+        let device_info = if discovery_reply.supports_property_exchange() {
+            self.request_midi_ci_device_info(output_device_id, input_device_id, muid, device_muid)
+                .await
+                .ok()
+        } else {
+            None
+        };
+        let device = MidiCiDevice {
+            muid: device_muid,
+            identity: discovery_reply.identity,
+            profiles,
+            device_info,
+        };
+        Ok((input_device_id, device))
+    }
+
+    /// Gets the "DeviceInfo" resource via MIDI-CI property exchange.
+    async fn request_midi_ci_device_info(
+        &self,
+        output_device_id: MidiOutputDeviceId,
+        input_device_id: MidiInputDeviceId,
+        muid: MidiCiMuid,
+        device_muid: MidiCiMuid,
+    ) -> Result<MidiCiDeviceInfo, &'static str> {
+        self.send_midi_ci_message(
+            output_device_id,
+            Some(input_device_id),
+            muid,
+            &build_midi_ci_property_exchange_capabilities_inquiry(muid, device_muid),
+            MidiCiMessageType::PropertyExchangeCapabilitiesReply,
+        )?
+        .recv()
+        .await
+        .map_err(|_| "no MIDI-CI property exchange capabilities reply received")?;
+        let receiver = self.send_midi_ci_message(
+            output_device_id,
+            Some(input_device_id),
+            muid,
+            &build_midi_ci_property_get_inquiry(muid, device_muid, 0, "DeviceInfo"),
+            MidiCiMessageType::PropertyGetReply,
+        )?;
+        let mut chunks = vec![];
+        while let Ok(reply) = receiver.recv().await {
+            let chunk = MidiCiPropertyChunk::parse(&reply.message)
+                .ok_or("invalid MIDI-CI property exchange reply")?;
+            let is_last = chunk.is_last();
+            chunks.push(chunk);
+            if is_last {
+                break;
+            }
+        }
+        MidiCiDeviceInfo::from_chunks(&chunks).ok_or("couldn't get device info")
+    }
+
+    fn send_midi_ci_message(
+        &self,
+        output_device_id: MidiOutputDeviceId,
+        input_device_id: Option<MidiInputDeviceId>,
+        muid: MidiCiMuid,
+        message: &[u8],
+        reply_type: MidiCiMessageType,
+    ) -> Result<async_channel::Receiver<MidiCiTransactionReply>, &'static str> {
+        let request = RawMidiEvent::try_from_slice(0, message)?;
+        let (sender, receiver) = async_channel::bounded(10);
+        let command = MidiCiTransactionCommand {
+            output_device_id,
+            input_device_id,
+            request,
+            muid,
+            reply_type,
+            sender,
+        };
+        self.audio_hook_task_sender
+            .send_complaining(NormalAudioHookTask::MidiCiTransaction(command));
+        Ok(receiver)
+    }
+
     async fn prompt_for_next_reaper_target(&self, msg: &str) -> Result<ReaperTarget, &'static str> {
         self.show_message_panel("ReaLearn", msg, || {
             BackboneShell::get()
@@ -2145,6 +2314,13 @@ struct MainConfig {
         skip_serializing_if = "is_default_server_grpc_port"
     )]
     server_grpc_port: u16,
+    /// IP address on which the servers listen, e.g. "127.0.0.1" for making them only reachable from
+    /// this computer.
+    #[serde(
+        default = "default_server_bind_address",
+        skip_serializing_if = "is_default_server_bind_address"
+    )]
+    server_bind_address: String,
     #[serde(
         default = "default_companion_web_app_url",
         skip_serializing_if = "is_default_companion_web_app_url"
@@ -2156,6 +2332,7 @@ struct MainConfig {
 const DEFAULT_SERVER_HTTP_PORT: u16 = 39080;
 const DEFAULT_SERVER_HTTPS_PORT: u16 = 39443;
 const DEFAULT_SERVER_GRPC_PORT: u16 = 39051;
+const DEFAULT_SERVER_BIND_ADDRESS: &str = "0.0.0.0";
 
 fn default_server_http_port() -> u16 {
     DEFAULT_SERVER_HTTP_PORT
@@ -2181,6 +2358,14 @@ fn is_default_server_grpc_port(v: &u16) -> bool {
     *v == DEFAULT_SERVER_GRPC_PORT
 }
 
+fn default_server_bind_address() -> String {
+    DEFAULT_SERVER_BIND_ADDRESS.to_string()
+}
+
+fn is_default_server_bind_address(v: &str) -> bool {
+    v == DEFAULT_SERVER_BIND_ADDRESS
+}
+
 fn default_companion_web_app_url() -> String {
     COMPANION_WEB_APP_URL.to_string()
 }
@@ -2189,6 +2374,18 @@ fn is_default_companion_web_app_url(v: &str) -> bool {
     v == COMPANION_WEB_APP_URL
 }
 
+impl MainConfig {
+    fn server_bind_address(&self) -> IpAddr {
+        self.server_bind_address.parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                msg = "Invalid server bind address, listening on all interfaces",
+                address = %self.server_bind_address
+            );
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+        })
+    }
+}
+
 impl Default for MainConfig {
     fn default() -> Self {
         MainConfig {
@@ -2196,6 +2393,7 @@ impl Default for MainConfig {
             server_http_port: default_server_http_port(),
             server_https_port: default_server_https_port(),
             server_grpc_port: default_server_grpc_port(),
+            server_bind_address: default_server_bind_address(),
             companion_web_app_url: default_companion_web_app_url(),
             showed_welcome_screen: 0,
         }
@@ -2636,10 +2834,10 @@ async fn maybe_create_controller_for_device(
     tmp_midi_ins.apply_to_reaper();
     // Apply changes
     Reaper::get().medium_reaper().low().midi_init(-1, -1);
-    // Send device identity request to MIDI output device
+    // Send device identity request and MIDI-CI discovery to MIDI output device
     tracing::debug!(msg = "Sending device request to MIDI output device...", %out_dev_id);
-    let identity_reply_result = BackboneShell::get()
-        .request_midi_device_identity(out_dev_id, None)
+    let identification_result = BackboneShell::get()
+        .identify_midi_device(out_dev_id, None)
         .await;
     // As soon as possible, reset MIDI devices to old state (we don't want to leave traces)
     tracing::debug!(msg = "Resetting MIDI output and input devices to previous state...");
@@ -2647,13 +2845,14 @@ async fn maybe_create_controller_for_device(
     old_midi_ins.apply_to_reaper();
     Reaper::get().medium_reaper().low().midi_init(-1, -1);
     // Process identity reply
-    let identity_reply = identity_reply_result?;
-    let in_dev_id = identity_reply.input_device_id;
+    let identification = identification_result?;
+    let in_dev_id = identification.input_device_id;
     tracing::info!(
         msg = "Received identity reply from MIDI device",
         %out_dev_id,
         %in_dev_id,
-        reply = %identity_reply.device_inquiry_reply
+        reply = ?identification.device_inquiry_reply,
+        midi_ci_device = ?identification.midi_ci_device,
     );
     //  Check if input already used by existing controller
     tracing::debug!(msg = "Check if input used already used by existing controller...");
@@ -2676,10 +2875,7 @@ async fn maybe_create_controller_for_device(
     let out_port_name = out_port_name.to_string_lossy();
     tracing::info!(msg = "Input not yet used. Finding matching controller preset...", %out_port_name);
     let controller_preset = controller_preset_manager
-        .find_controller_preset_compatible_with_device(
-            &identity_reply.device_inquiry_reply.message,
-            &out_port_name,
-        )
+        .find_controller_preset_compatible_with_device(&identification, &out_port_name)
         .ok_or("no controller preset matching device")?;
     let device_name = controller_preset
         .specific_meta_data
@@ -2734,12 +2930,11 @@ async fn maybe_create_controller_for_device(
         enabled: true,
         palette_color: None,
         connection: Some(ControllerConnection::Midi(MidiControllerConnection {
-            identity_response: Some(format_as_pretty_hex(
-                &identity_reply.device_inquiry_reply.message,
-            )),
-            input_port: Some(MidiInputPort::new(
-                identity_reply.input_device_id.get() as u32
-            )),
+            identity_response: identification
+                .device_inquiry_reply_bytes()
+                .map(format_as_pretty_hex),
+            device_identity: identification.to_persistent_identity(),
+            input_port: Some(MidiInputPort::new(in_dev_id.get() as u32)),
             output_port: Some(MidiOutputPort::new(out_dev_id.get() as u32)),
         })),
         default_controller_preset: None,
@@ -6,7 +6,7 @@ use vst::plugin::{
 };
 
 use crate::domain::{
-    ControlEvent, ControlEventTimestamp, InstanceId, MidiEvent, ParameterManager, PluginParamIndex,
+    AudioBlockStart, ControlEvent, InstanceId, MidiEvent, ParameterManager, PluginParamIndex,
     ProcessorContext, RawParamValue, PLUGIN_PARAMETER_COUNT,
 };
 use crate::infrastructure::plugin::instance_parameter_container::InstanceParameterContainer;
@@ -30,7 +30,6 @@ use crate::infrastructure::plugin::helgobox_plugin_editor::HelgoboxPluginEditor;
 use crate::infrastructure::plugin::instance_shell::InstanceShell;
 use crate::infrastructure::ui::instance_panel::InstancePanel;
 use anyhow::{anyhow, Context};
-use helgoboss_learn::AbstractTimestamp;
 use std::convert::TryInto;
 use std::ptr::null_mut;
 use std::rc::Rc;
@@ -224,7 +223,7 @@ impl Plugin for HelgoboxPlugin {
     fn process_events(&mut self, events: &Events) {
         firewall(|| {
             assert_no_alloc(|| {
-                let timestamp = ControlEventTimestamp::now();
+                let block_start = AudioBlockStart::now();
                 let is_transport_start = !self.was_playing_in_last_cycle && self.is_now_playing();
                 for e in events.events() {
                     let our_event = match MidiEvent::from_vst(e) {
@@ -235,6 +234,8 @@ impl Plugin for HelgoboxPlugin {
                         }
                         Ok(e) => e,
                     };
+                    let timestamp =
+                        block_start.event_timestamp(our_event.offset(), self.sample_rate);
                     let our_event = ControlEvent::new(our_event, timestamp);
                     if let Some(lazy_data) = self.lazy_data.get() {
                         lazy_data.instance_shell.process_incoming_midi_from_plugin(
@@ -255,6 +256,11 @@ impl Plugin for HelgoboxPlugin {
                 // (TimeInfoFlags::TRANSPORT_CHANGED doesn't work the way we want it).
                 self.was_playing_in_last_cycle = self.is_now_playing();
                 if let Some(lazy_data) = self.lazy_data.get() {
+                    // Analyze the audio input before Playtime writes its output into the (possibly
+                    // shared) buffers.
+                    lazy_data
+                        .instance_shell
+                        .process_incoming_audio_from_plugin(buffer, self.host);
                     #[cfg(feature = "playtime")]
                     lazy_data.instance_shell.run_playtime_from_plugin(
                         buffer,
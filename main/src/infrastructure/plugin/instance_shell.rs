@@ -584,6 +584,24 @@ impl InstanceShell {
         }
     }
 
+    /// Lets the audio sources of each unit analyze the plug-in's audio input.
+    ///
+    /// To be called from real-time thread (in the plug-in's processing function).
+    pub fn process_incoming_audio_from_plugin(
+        &self,
+        buffer: &mut vst::buffer::AudioBuffer<f64>,
+        host: HostCallback,
+    ) {
+        let Some(unit_shells) = non_blocking_try_read_lock(&self.additional_unit_shells) else {
+            // Better miss one block than blocking the entire audio thread
+            return;
+        };
+        let (inputs, _) = buffer.split();
+        for unit_shell in once(&self.main_unit_shell).chain(&*unit_shells) {
+            unit_shell.process_incoming_audio_from_vst(&inputs, host);
+        }
+    }
+
     /// Invokes the processing function for each unit.
     ///
     /// To be called from real-time thread (in the plug-in's processing function).
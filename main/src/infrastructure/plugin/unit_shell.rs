@@ -168,6 +168,7 @@ impl UnitShell {
             BackboneShell::get()
                 .feedback_audio_hook_task_sender()
                 .clone(),
+            BackboneShell::get().normal_audio_hook_task_sender().clone(),
             BackboneShell::get().additional_feedback_event_sender(),
             BackboneShell::get().instance_orchestration_event_sender(),
             BackboneShell::get().osc_feedback_task_sender().clone(),
@@ -240,6 +241,16 @@ impl UnitShell {
             .process_incoming_midi_from_vst(event, is_transport_start, &host);
     }
 
+    pub fn process_incoming_audio_from_vst(
+        &self,
+        input: &vst::buffer::Inputs<f64>,
+        host: HostCallback,
+    ) {
+        self.real_time_processor
+            .lock_recover()
+            .process_incoming_audio_from_vst(input, &host);
+    }
+
     pub fn run_from_vst(&self, host: HostCallback) {
         self.real_time_processor.lock_recover().run_from_vst(&host);
     }
@@ -14,19 +14,19 @@ use crate::infrastructure::proto::{
     event_reply, occasional_global_update, occasional_instance_update,
     qualified_occasional_unit_update, ArrangementPlayState, AudioInputChannel, AudioInputChannels,
     CellAddress, Compartment, ContinuousColumnUpdate, ContinuousMatrixUpdate,
-    GetContinuousColumnUpdatesReply, GetContinuousMatrixUpdatesReply,
-    GetContinuousSlotUpdatesReply, GetOccasionalClipUpdatesReply, GetOccasionalColumnUpdatesReply,
-    GetOccasionalGlobalUpdatesReply, GetOccasionalInstanceUpdatesReply,
-    GetOccasionalMatrixUpdatesReply, GetOccasionalPlaytimeEngineUpdatesReply,
-    GetOccasionalRowUpdatesReply, GetOccasionalSlotUpdatesReply, GetOccasionalTrackUpdatesReply,
-    GetOccasionalUnitUpdatesReply, HostColorScheme, MidiDeviceStatus, MidiInputDevice,
-    MidiInputDevices, MidiOutputDevice, MidiOutputDevices, OccasionalGlobalUpdate,
-    OccasionalInstanceUpdate, OccasionalMatrixUpdate, OccasionalPlaytimeEngineUpdate,
-    PitchShiftMode, PitchShiftModes, PitchShiftSubMode, QualifiedContinuousSlotUpdate,
-    QualifiedOccasionalClipUpdate, QualifiedOccasionalColumnUpdate, QualifiedOccasionalRowUpdate,
-    QualifiedOccasionalSlotUpdate, QualifiedOccasionalTrackUpdate, QualifiedOccasionalUnitUpdate,
-    ResampleMode, ResampleModes, RgbColor, Scope, Severity, SlotAddress, Unit, Units, Warning,
-    Warnings,
+    GetContinuousColumnUpdatesReply, GetContinuousMappingUpdatesReply,
+    GetContinuousMatrixUpdatesReply, GetContinuousSlotUpdatesReply, GetOccasionalClipUpdatesReply,
+    GetOccasionalColumnUpdatesReply, GetOccasionalGlobalUpdatesReply,
+    GetOccasionalInstanceUpdatesReply, GetOccasionalMatrixUpdatesReply,
+    GetOccasionalPlaytimeEngineUpdatesReply, GetOccasionalRowUpdatesReply,
+    GetOccasionalSlotUpdatesReply, GetOccasionalTrackUpdatesReply, GetOccasionalUnitUpdatesReply,
+    HostColorScheme, MidiDeviceStatus, MidiInputDevice, MidiInputDevices, MidiOutputDevice,
+    MidiOutputDevices, OccasionalGlobalUpdate, OccasionalInstanceUpdate, OccasionalMatrixUpdate,
+    OccasionalPlaytimeEngineUpdate, PitchShiftMode, PitchShiftModes, PitchShiftSubMode,
+    QualifiedContinuousMappingUpdate, QualifiedContinuousSlotUpdate, QualifiedOccasionalClipUpdate,
+    QualifiedOccasionalColumnUpdate, QualifiedOccasionalRowUpdate, QualifiedOccasionalSlotUpdate,
+    QualifiedOccasionalTrackUpdate, QualifiedOccasionalUnitUpdate, ResampleMode, ResampleModes,
+    RgbColor, Scope, Severity, SlotAddress, Unit, Units, Warning, Warnings,
 };
 use crate::infrastructure::server::data::get_controller_routing;
 
@@ -45,17 +45,7 @@ impl occasional_instance_update::Update {
     }
 
     pub fn units(instance_shell: &InstanceShell) -> Self {
-        let units = instance_shell.all_unit_models().map(|unit_model| {
-            let unit_model = unit_model.borrow();
-            Unit {
-                id: unit_model.unit_id().into(),
-                key: unit_model.unit_key.get_ref().clone(),
-                name: unit_model.name().map(|n| n.to_string()),
-            }
-        });
-        Self::Units(Units {
-            units: units.collect(),
-        })
+        Self::Units(Units::from_engine(instance_shell))
     }
 
     pub fn warning(severity: Severity, scope: Option<Scope>, message: String) -> Warning {
@@ -236,6 +226,29 @@ impl Compartment {
             Compartment::Main => CompartmentKind::Main,
         }
     }
+
+    pub fn from_engine(compartment: CompartmentKind) -> Self {
+        match compartment {
+            CompartmentKind::Controller => Compartment::Controller,
+            CompartmentKind::Main => Compartment::Main,
+        }
+    }
+}
+
+impl Units {
+    pub fn from_engine(instance_shell: &InstanceShell) -> Self {
+        let units = instance_shell.all_unit_models().map(|unit_model| {
+            let unit_model = unit_model.borrow();
+            Unit {
+                id: unit_model.unit_id().into(),
+                key: unit_model.unit_key.get_ref().clone(),
+                name: unit_model.name().map(|n| n.to_string()),
+            }
+        });
+        Self {
+            units: units.collect(),
+        }
+    }
 }
 
 impl MidiInputDevice {
@@ -404,6 +417,14 @@ impl From<Vec<QualifiedOccasionalUnitUpdate>> for event_reply::Value {
     }
 }
 
+impl From<Vec<QualifiedContinuousMappingUpdate>> for event_reply::Value {
+    fn from(value: Vec<QualifiedContinuousMappingUpdate>) -> Self {
+        event_reply::Value::ContinuousMappingUpdatesReply(GetContinuousMappingUpdatesReply {
+            mapping_updates: value,
+        })
+    }
+}
+
 impl From<Vec<OccasionalMatrixUpdate>> for event_reply::Value {
     fn from(value: Vec<OccasionalMatrixUpdate>) -> Self {
         event_reply::Value::OccasionalMatrixUpdatesReply(GetOccasionalMatrixUpdatesReply {
@@ -47,7 +47,7 @@ pub mod reply {
 pub struct CommandRequest {
     #[prost(
         oneof = "command_request::Value",
-        tags = "1, 3, 4, 5, 52, 6, 7, 8, 9, 10, 11, 50, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 37, 53, 40, 25, 26, 27, 34, 28, 29, 31, 32, 33, 35, 36, 38, 39, 41, 42, 43, 44, 51, 45, 46, 47, 48, 49"
+        tags = "1, 3, 4, 5, 52, 6, 7, 8, 9, 10, 11, 50, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 37, 53, 40, 25, 26, 27, 34, 28, 29, 31, 32, 33, 35, 36, 38, 39, 41, 42, 43, 44, 51, 45, 46, 47, 48, 49, 54, 55, 56, 57, 58, 59"
     )]
     pub value: ::core::option::Option<command_request::Value>,
 }
@@ -161,6 +161,19 @@ pub mod command_request {
         TriggerInstance(super::TriggerInstanceRequest),
         #[prost(message, tag = "49")]
         SetPlaytimeEngineSettings(super::SetPlaytimeEngineSettingsRequest),
+        /// ReaLearn mapping commands
+        #[prost(message, tag = "54")]
+        SaveMapping(super::SaveMappingRequest),
+        #[prost(message, tag = "55")]
+        DeleteMapping(super::DeleteMappingRequest),
+        #[prost(message, tag = "56")]
+        SaveGroup(super::SaveGroupRequest),
+        #[prost(message, tag = "57")]
+        DeleteGroup(super::DeleteGroupRequest),
+        #[prost(message, tag = "58")]
+        SetMappingEnabled(super::SetMappingEnabledRequest),
+        #[prost(message, tag = "59")]
+        HitMappingTarget(super::HitMappingTargetRequest),
     }
 }
 /// Envelope for queries.
@@ -183,7 +196,7 @@ pub struct QueryRequest {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Query {
-    #[prost(oneof = "query::Value", tags = "1, 2, 3, 4, 5, 6, 7, 8")]
+    #[prost(oneof = "query::Value", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10")]
     pub value: ::core::option::Option<query::Value>,
 }
 /// Nested message and enum types in `Query`.
@@ -207,6 +220,10 @@ pub mod query {
         GetCompartmentData(super::GetCompartmentDataRequest),
         #[prost(message, tag = "8")]
         GetCustomInstanceData(super::GetCustomInstanceDataRequest),
+        #[prost(message, tag = "9")]
+        GetUnits(super::GetUnitsRequest),
+        #[prost(message, tag = "10")]
+        GetMappings(super::GetMappingsRequest),
     }
 }
 /// Envelope for query results.
@@ -227,7 +244,10 @@ pub struct QueryReply {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct QueryResult {
-    #[prost(oneof = "query_result::Value", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9")]
+    #[prost(
+        oneof = "query_result::Value",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11"
+    )]
     pub value: ::core::option::Option<query_result::Value>,
 }
 /// Nested message and enum types in `QueryResult`.
@@ -253,6 +273,10 @@ pub mod query_result {
         GetCompartmentDataReply(super::GetCompartmentDataReply),
         #[prost(message, tag = "9")]
         GetCustomInstanceDataReply(super::GetCustomInstanceDataReply),
+        #[prost(message, tag = "10")]
+        GetUnitsReply(super::GetUnitsReply),
+        #[prost(message, tag = "11")]
+        GetMappingsReply(super::GetMappingsReply),
     }
 }
 /// Should contain all possible *event* replies from above service.
@@ -263,7 +287,7 @@ pub mod query_result {
 pub struct EventReply {
     #[prost(
         oneof = "event_reply::Value",
-        tags = "1, 15, 13, 14, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12"
+        tags = "1, 15, 13, 14, 16, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12"
     )]
     pub value: ::core::option::Option<event_reply::Value>,
 }
@@ -281,6 +305,8 @@ pub mod event_reply {
         OccasionalInstanceUpdatesReply(super::GetOccasionalInstanceUpdatesReply),
         #[prost(message, tag = "14")]
         OccasionalUnitUpdatesReply(super::GetOccasionalUnitUpdatesReply),
+        #[prost(message, tag = "16")]
+        ContinuousMappingUpdatesReply(super::GetContinuousMappingUpdatesReply),
         #[prost(message, tag = "2")]
         OccasionalMatrixUpdatesReply(super::GetOccasionalMatrixUpdatesReply),
         #[prost(message, tag = "3")]
@@ -872,6 +898,107 @@ pub struct GetCompartmentDataReply {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetUnitsRequest {
+    #[prost(uint32, tag = "1")]
+    pub instance_id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetUnitsReply {
+    #[prost(message, repeated, tag = "1")]
+    pub units: ::prost::alloc::vec::Vec<Unit>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMappingsRequest {
+    #[prost(message, optional, tag = "1")]
+    pub compartment_id: ::core::option::Option<FullCompartmentId>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMappingsReply {
+    /// Mappings as JSON array
+    #[prost(string, tag = "1")]
+    pub mappings: ::prost::alloc::string::String,
+    /// Groups (without default group) as JSON array
+    #[prost(string, tag = "2")]
+    pub groups: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SaveMappingRequest {
+    #[prost(message, optional, tag = "1")]
+    pub compartment_id: ::core::option::Option<FullCompartmentId>,
+    /// Mapping as JSON. If a mapping with the same key (property "id") exists already, it will be
+    /// replaced, otherwise it will be added.
+    #[prost(string, tag = "2")]
+    pub data: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteMappingRequest {
+    #[prost(message, optional, tag = "1")]
+    pub compartment_id: ::core::option::Option<FullCompartmentId>,
+    #[prost(string, tag = "2")]
+    pub mapping_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SaveGroupRequest {
+    #[prost(message, optional, tag = "1")]
+    pub compartment_id: ::core::option::Option<FullCompartmentId>,
+    /// Group as JSON. If a group with the same key (property "id") exists already, it will be
+    /// replaced, otherwise it will be added.
+    #[prost(string, tag = "2")]
+    pub data: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteGroupRequest {
+    #[prost(message, optional, tag = "1")]
+    pub compartment_id: ::core::option::Option<FullCompartmentId>,
+    #[prost(string, tag = "2")]
+    pub group_id: ::prost::alloc::string::String,
+    /// If false, the mappings of this group will be moved to the default group.
+    #[prost(bool, tag = "3")]
+    pub delete_mappings: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetMappingEnabledRequest {
+    #[prost(message, optional, tag = "1")]
+    pub compartment_id: ::core::option::Option<FullCompartmentId>,
+    #[prost(string, tag = "2")]
+    pub mapping_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub enabled: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HitMappingTargetRequest {
+    #[prost(message, optional, tag = "1")]
+    pub compartment_id: ::core::option::Option<FullCompartmentId>,
+    #[prost(string, tag = "2")]
+    pub mapping_id: ::prost::alloc::string::String,
+    #[prost(oneof = "hit_mapping_target_request::Value", tags = "3, 4")]
+    pub value: ::core::option::Option<hit_mapping_target_request::Value>,
+}
+/// Nested message and enum types in `HitMappingTargetRequest`.
+pub mod hit_mapping_target_request {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Value {
+        /// Normalized absolute value between 0.0 and 1.0
+        #[prost(double, tag = "3")]
+        Absolute(f64),
+        /// Relative increment, e.g. -1 or 1 for one encoder step. Only has an effect if the target
+        /// can be controlled relatively.
+        #[prost(sint32, tag = "4")]
+        Relative(i32),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetClipDetailReply {
     #[prost(bytes = "vec", optional, tag = "1")]
     pub rea_peaks: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
@@ -896,6 +1023,12 @@ pub struct GetOccasionalUnitUpdatesRequest {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContinuousMappingUpdatesRequest {
+    #[prost(uint32, tag = "1")]
+    pub instance_id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetOccasionalMatrixUpdatesRequest {
     #[prost(uint32, tag = "1")]
     pub matrix_id: u32,
@@ -1042,6 +1175,26 @@ pub mod qualified_occasional_unit_update {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContinuousMappingUpdatesReply {
+    /// For each mapping whose target value changed
+    #[prost(message, repeated, tag = "1")]
+    pub mapping_updates: ::prost::alloc::vec::Vec<QualifiedContinuousMappingUpdate>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QualifiedContinuousMappingUpdate {
+    #[prost(uint32, tag = "1")]
+    pub unit_id: u32,
+    #[prost(enumeration = "Compartment", tag = "2")]
+    pub compartment: i32,
+    #[prost(string, tag = "3")]
+    pub mapping_id: ::prost::alloc::string::String,
+    /// Normalized target value between 0.0 and 1.0
+    #[prost(double, tag = "4")]
+    pub target_value: f64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Units {
     #[prost(message, repeated, tag = "1")]
     pub units: ::prost::alloc::vec::Vec<Unit>,
@@ -2816,6 +2969,15 @@ pub mod helgobox_service_server {
             &self,
             request: tonic::Request<super::GetCompartmentDataRequest>,
         ) -> std::result::Result<tonic::Response<super::GetCompartmentDataReply>, tonic::Status>;
+        /// ReaLearn unit and mapping queries
+        async fn get_units(
+            &self,
+            request: tonic::Request<super::GetUnitsRequest>,
+        ) -> std::result::Result<tonic::Response<super::GetUnitsReply>, tonic::Status>;
+        async fn get_mappings(
+            &self,
+            request: tonic::Request<super::GetMappingsRequest>,
+        ) -> std::result::Result<tonic::Response<super::GetMappingsReply>, tonic::Status>;
         /// General global commands
         async fn trigger_global(
             &self,
@@ -2995,6 +3157,31 @@ pub mod helgobox_service_server {
             &self,
             request: tonic::Request<super::SaveCustomCompartmentDataRequest>,
         ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
+        /// ReaLearn mapping commands
+        async fn save_mapping(
+            &self,
+            request: tonic::Request<super::SaveMappingRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
+        async fn delete_mapping(
+            &self,
+            request: tonic::Request<super::DeleteMappingRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
+        async fn save_group(
+            &self,
+            request: tonic::Request<super::SaveGroupRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
+        async fn delete_group(
+            &self,
+            request: tonic::Request<super::DeleteGroupRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
+        async fn set_mapping_enabled(
+            &self,
+            request: tonic::Request<super::SetMappingEnabledRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
+        async fn hit_mapping_target(
+            &self,
+            request: tonic::Request<super::HitMappingTargetRequest>,
+        ) -> std::result::Result<tonic::Response<super::Empty>, tonic::Status>;
         /// Server streaming response type for the GetOccasionalGlobalUpdates method.
         type GetOccasionalGlobalUpdatesStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::GetOccasionalGlobalUpdatesReply, tonic::Status>,
@@ -3046,6 +3233,19 @@ pub mod helgobox_service_server {
             &self,
             request: tonic::Request<super::GetOccasionalUnitUpdatesRequest>,
         ) -> std::result::Result<tonic::Response<Self::GetOccasionalUnitUpdatesStream>, tonic::Status>;
+        /// Server streaming response type for the GetContinuousMappingUpdates method.
+        type GetContinuousMappingUpdatesStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::GetContinuousMappingUpdatesReply, tonic::Status>,
+            > + Send
+            + 'static;
+        /// ReaLearn mapping events
+        async fn get_continuous_mapping_updates(
+            &self,
+            request: tonic::Request<super::GetContinuousMappingUpdatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::GetContinuousMappingUpdatesStream>,
+            tonic::Status,
+        >;
         /// Server streaming response type for the GetOccasionalMatrixUpdates method.
         type GetOccasionalMatrixUpdatesStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::GetOccasionalMatrixUpdatesReply, tonic::Status>,
@@ -3569,6 +3769,88 @@ pub mod helgobox_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/generated.HelgoboxService/GetUnits" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetUnitsSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService> tonic::server::UnaryService<super::GetUnitsRequest> for GetUnitsSvc<T> {
+                        type Response = super::GetUnitsReply;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetUnitsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::get_units(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetUnitsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/generated.HelgoboxService/GetMappings" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetMappingsSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService> tonic::server::UnaryService<super::GetMappingsRequest>
+                        for GetMappingsSvc<T>
+                    {
+                        type Response = super::GetMappingsReply;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetMappingsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::get_mappings(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetMappingsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/generated.HelgoboxService/TriggerGlobal" => {
                     #[allow(non_camel_case_types)]
                     struct TriggerGlobalSvc<T: HelgoboxService>(pub Arc<T>);
@@ -5361,6 +5643,259 @@ pub mod helgobox_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/generated.HelgoboxService/SaveMapping" => {
+                    #[allow(non_camel_case_types)]
+                    struct SaveMappingSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService> tonic::server::UnaryService<super::SaveMappingRequest>
+                        for SaveMappingSvc<T>
+                    {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SaveMappingRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::save_mapping(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SaveMappingSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/generated.HelgoboxService/DeleteMapping" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteMappingSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService>
+                        tonic::server::UnaryService<super::DeleteMappingRequest>
+                        for DeleteMappingSvc<T>
+                    {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteMappingRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::delete_mapping(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteMappingSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/generated.HelgoboxService/SaveGroup" => {
+                    #[allow(non_camel_case_types)]
+                    struct SaveGroupSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService> tonic::server::UnaryService<super::SaveGroupRequest> for SaveGroupSvc<T> {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SaveGroupRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::save_group(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SaveGroupSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/generated.HelgoboxService/DeleteGroup" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteGroupSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService> tonic::server::UnaryService<super::DeleteGroupRequest>
+                        for DeleteGroupSvc<T>
+                    {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteGroupRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::delete_group(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteGroupSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/generated.HelgoboxService/SetMappingEnabled" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetMappingEnabledSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService>
+                        tonic::server::UnaryService<super::SetMappingEnabledRequest>
+                        for SetMappingEnabledSvc<T>
+                    {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetMappingEnabledRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::set_mapping_enabled(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetMappingEnabledSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/generated.HelgoboxService/HitMappingTarget" => {
+                    #[allow(non_camel_case_types)]
+                    struct HitMappingTargetSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService>
+                        tonic::server::UnaryService<super::HitMappingTargetRequest>
+                        for HitMappingTargetSvc<T>
+                    {
+                        type Response = super::Empty;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HitMappingTargetRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::hit_mapping_target(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = HitMappingTargetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/generated.HelgoboxService/GetOccasionalGlobalUpdates" => {
                     #[allow(non_camel_case_types)]
                     struct GetOccasionalGlobalUpdatesSvc<T: HelgoboxService>(pub Arc<T>);
@@ -5557,6 +6092,55 @@ pub mod helgobox_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/generated.HelgoboxService/GetContinuousMappingUpdates" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetContinuousMappingUpdatesSvc<T: HelgoboxService>(pub Arc<T>);
+                    impl<T: HelgoboxService>
+                        tonic::server::ServerStreamingService<
+                            super::GetContinuousMappingUpdatesRequest,
+                        > for GetContinuousMappingUpdatesSvc<T>
+                    {
+                        type Response = super::GetContinuousMappingUpdatesReply;
+                        type ResponseStream = T::GetContinuousMappingUpdatesStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetContinuousMappingUpdatesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as HelgoboxService>::get_continuous_mapping_updates(
+                                    &inner, request,
+                                )
+                                .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetContinuousMappingUpdatesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/generated.HelgoboxService/GetOccasionalMatrixUpdates" => {
                     #[allow(non_camel_case_types)]
                     struct GetOccasionalMatrixUpdatesSvc<T: HelgoboxService>(pub Arc<T>);
@@ -3,7 +3,7 @@ use reaper_high::ChangeEvent;
 use helgobox_api::runtime::{GlobalInfoEvent, InstanceInfoEvent};
 
 use crate::application::UnitModel;
-use crate::domain::{InstanceId, UnitId};
+use crate::domain::{InstanceId, TargetValueChangedEvent, UnitId};
 use crate::infrastructure::data::{
     ControllerManager, FileBasedControllerPresetManager, FileBasedMainPresetManager, LicenseManager,
 };
@@ -11,8 +11,9 @@ use crate::infrastructure::plugin::InstanceShell;
 use crate::infrastructure::proto::helgobox_service_server::HelgoboxServiceServer;
 use crate::infrastructure::proto::{
     occasional_global_update, occasional_instance_update, qualified_occasional_unit_update,
-    HelgoboxServiceImpl, OccasionalGlobalUpdate, OccasionalInstanceUpdate,
-    OccasionalInstanceUpdateBatch, OccasionalUnitUpdateBatch, ProtoRequestHandler, ProtoSenders,
+    Compartment, ContinuousMappingUpdateBatch, HelgoboxServiceImpl, OccasionalGlobalUpdate,
+    OccasionalInstanceUpdate, OccasionalInstanceUpdateBatch, OccasionalUnitUpdateBatch,
+    ProtoRequestHandler, ProtoSenders, QualifiedContinuousMappingUpdate,
     QualifiedOccasionalUnitUpdate,
 };
 
@@ -205,6 +206,35 @@ impl ProtoHub {
         });
     }
 
+    pub fn notify_target_value_changed(
+        &self,
+        unit_model: &UnitModel,
+        event: &TargetValueChangedEvent,
+    ) {
+        let sender = &self.senders.continuous_mapping_update_sender;
+        if sender.receiver_count() == 0 {
+            return;
+        }
+        let Some(mapping) = unit_model.find_mapping_by_id(event.compartment, event.mapping_id)
+        else {
+            return;
+        };
+        let Ok(mapping) = mapping.try_borrow() else {
+            return;
+        };
+        let update = QualifiedContinuousMappingUpdate {
+            unit_id: unit_model.unit_id().into(),
+            compartment: Compartment::from_engine(event.compartment).into(),
+            mapping_id: mapping.key().to_string(),
+            target_value: event.new_value.to_unit_value().get(),
+        };
+        let batch_event = ContinuousMappingUpdateBatch {
+            instance_id: unit_model.instance_id(),
+            value: vec![update],
+        };
+        let _ = sender.send(batch_event);
+    }
+
     pub fn notify_everything_in_unit_has_changed(&self, instance_id: InstanceId, unit_id: UnitId) {
         self.send_occasional_unit_updates(instance_id, || {
             [QualifiedOccasionalUnitUpdate {
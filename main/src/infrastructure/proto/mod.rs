@@ -1,3 +1,5 @@
+/// Generated from `helgobox.proto` by tonic-build (server only) and formatted with rustfmt. Don't
+/// edit manually.
 #[allow(clippy::enum_variant_names)]
 mod generated;
 pub use generated::*;
@@ -52,6 +52,7 @@ impl PlaytimeProtoRequestHandler {
                     ColumnPlaySlotOptions {
                         velocity: Some(UnitValue::MAX),
                         stop_column_if_slot_empty: false,
+                        frame_offset: 0,
                     },
                 );
                 Ok(())
@@ -1,38 +1,45 @@
 use anyhow::Context;
+use helgoboss_learn::{ControlValue, DiscreteIncrement, UnitValue};
 use helgoboss_license_api::persistence::LicenseKey;
 use reaper_high::{OrCurrentProject, Reaper};
 use reaper_medium::CommandId;
 use tonic::{Response, Status};
 
 use base::spawn_in_main_thread;
+use helgobox_api::persistence;
 use helgobox_api::runtime::{GlobalInfoEvent, InstanceInfoEvent};
+use std::rc::Rc;
 use swell_ui::Window;
 
-use crate::domain::{CompartmentKind, UnitId};
-use crate::infrastructure::api::convert::from_data;
+use crate::application::{CompartmentCommand, MappingCommand, SessionCommand, UnitModel};
+use crate::domain::{CompartmentKind, GroupId, GroupKey, MappingId, QualifiedMappingId, UnitId};
 use crate::infrastructure::api::convert::from_data::ConversionStyle;
+use crate::infrastructure::api::convert::to_data::ApiToDataConversionContext;
+use crate::infrastructure::api::convert::{from_data, to_data};
 use crate::infrastructure::data::CompartmentModelData;
 use crate::infrastructure::plugin::{BackboneShell, InstanceShell, UnitShell};
 #[cfg(feature = "playtime")]
 use crate::infrastructure::proto::PlaytimeProtoRequestHandler;
 use crate::infrastructure::proto::{
-    AddLicenseRequest, Compartment, DeleteControllerRequest, DragClipRequest, DragColumnRequest,
-    DragRowRequest, DragSlotRequest, Empty, FullCompartmentId, GetAppSettingsReply,
-    GetAppSettingsRequest, GetArrangementInfoReply, GetArrangementInfoRequest, GetClipDetailReply,
-    GetClipDetailRequest, GetCompartmentDataReply, GetCompartmentDataRequest,
-    GetCustomInstanceDataReply, GetCustomInstanceDataRequest, GetHostInfoReply, GetHostInfoRequest,
-    GetProjectDirReply, GetProjectDirRequest, ImportFilesRequest, InsertColumnsRequest,
-    OpenTrackFxRequest, ProveAuthenticityReply, ProveAuthenticityRequest, SaveControllerRequest,
-    SaveCustomCompartmentDataRequest, SetAppSettingsRequest, SetClipDataRequest,
-    SetClipNameRequest, SetColumnSettingsRequest, SetColumnTrackRequest,
-    SetCustomInstanceDataRequest, SetInstanceSettingsRequest, SetMatrixPanRequest,
-    SetMatrixPlayRateRequest, SetMatrixSettingsRequest, SetMatrixTempoRequest,
+    hit_mapping_target_request, AddLicenseRequest, Compartment, DeleteControllerRequest,
+    DeleteGroupRequest, DeleteMappingRequest, DragClipRequest, DragColumnRequest, DragRowRequest,
+    DragSlotRequest, Empty, FullCompartmentId, GetAppSettingsReply, GetAppSettingsRequest,
+    GetArrangementInfoReply, GetArrangementInfoRequest, GetClipDetailReply, GetClipDetailRequest,
+    GetCompartmentDataReply, GetCompartmentDataRequest, GetCustomInstanceDataReply,
+    GetCustomInstanceDataRequest, GetHostInfoReply, GetHostInfoRequest, GetMappingsReply,
+    GetMappingsRequest, GetProjectDirReply, GetProjectDirRequest, GetUnitsReply, GetUnitsRequest,
+    HitMappingTargetRequest, ImportFilesRequest, InsertColumnsRequest, OpenTrackFxRequest,
+    ProveAuthenticityReply, ProveAuthenticityRequest, SaveControllerRequest,
+    SaveCustomCompartmentDataRequest, SaveGroupRequest, SaveMappingRequest, SetAppSettingsRequest,
+    SetClipDataRequest, SetClipNameRequest, SetColumnSettingsRequest, SetColumnTrackRequest,
+    SetCustomInstanceDataRequest, SetInstanceSettingsRequest, SetMappingEnabledRequest,
+    SetMatrixPanRequest, SetMatrixPlayRateRequest, SetMatrixSettingsRequest, SetMatrixTempoRequest,
     SetMatrixTimeSignatureRequest, SetMatrixVolumeRequest, SetPlaytimeEngineSettingsRequest,
     SetRowDataRequest, SetSequenceInfoRequest, SetTrackColorRequest,
     SetTrackInputMonitoringRequest, SetTrackInputRequest, SetTrackNameRequest, SetTrackPanRequest,
     SetTrackVolumeRequest, TriggerClipRequest, TriggerColumnRequest, TriggerGlobalAction,
     TriggerGlobalRequest, TriggerInstanceAction, TriggerInstanceRequest, TriggerMatrixRequest,
-    TriggerRowRequest, TriggerSequenceRequest, TriggerSlotRequest, TriggerTrackRequest,
+    TriggerRowRequest, TriggerSequenceRequest, TriggerSlotRequest, TriggerTrackRequest, Units,
     HOST_API_VERSION,
 };
 
@@ -220,13 +227,13 @@ impl ProtoRequestHandler {
         if outcome.connection_changed {
             if let Some(dev_id) = outcome.new_midi_output_device_id {
                 spawn_in_main_thread(async move {
-                    let reply = BackboneShell::get()
-                        .request_midi_device_identity(dev_id, None)
+                    let identification = BackboneShell::get()
+                        .identify_midi_device(dev_id, None)
                         .await;
                     let _ = BackboneShell::get()
                         .controller_manager()
                         .borrow_mut()
-                        .update_controller_device_identity(&outcome.id, reply.ok());
+                        .update_controller_device_identity(&outcome.id, identification.ok());
                     Ok(())
                 })
             }
@@ -677,6 +684,152 @@ impl ProtoRequestHandler {
         )
     }
 
+    pub fn get_units(&self, request: GetUnitsRequest) -> Result<Response<GetUnitsReply>, Status> {
+        self.handle_instance_command_internal(request.instance_id, |instance_shell| {
+            let reply = GetUnitsReply {
+                units: Units::from_engine(instance_shell).units,
+            };
+            Ok(Response::new(reply))
+        })
+    }
+
+    pub fn get_mappings(
+        &self,
+        request: GetMappingsRequest,
+    ) -> Result<Response<GetMappingsReply>, Status> {
+        self.handle_compartment_command_internal(
+            &request.compartment_id,
+            |unit_shell, compartment| {
+                let unit_model = unit_shell.model().borrow();
+                let compartment_model = unit_model.extract_compartment_model(compartment);
+                let compartment_model_data = CompartmentModelData::from_model(&compartment_model);
+                let compartment_api = from_data::convert_compartment(
+                    compartment_model_data,
+                    ConversionStyle::Minimal,
+                )?;
+                let reply = GetMappingsReply {
+                    mappings: serde_json::to_string(&compartment_api.mappings.unwrap_or_default())?,
+                    groups: serde_json::to_string(&compartment_api.groups.unwrap_or_default())?,
+                };
+                Ok(Response::new(reply))
+            },
+        )
+    }
+
+    pub fn save_mapping(&self, request: SaveMappingRequest) -> Result<Response<Empty>, Status> {
+        let api_mapping: persistence::Mapping = serde_json::from_str(&request.data)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        self.handle_compartment_command(&request.compartment_id, |unit_shell, compartment| {
+            let mut unit_model = unit_shell.model().borrow_mut();
+            let mapping_model = {
+                let conversion_context = unit_model.compartment_in_session(compartment);
+                let mapping_data = to_data::convert_mapping(api_mapping, &conversion_context)?;
+                mapping_data
+                    .to_model(
+                        compartment,
+                        &conversion_context,
+                        Some(unit_model.extended_context()),
+                        None,
+                    )
+                    .map_err(anyhow::Error::msg)?
+            };
+            unit_model.upsert_mapping(compartment, mapping_model);
+            Ok(())
+        })
+    }
+
+    pub fn delete_mapping(&self, request: DeleteMappingRequest) -> Result<Response<Empty>, Status> {
+        self.handle_compartment_command(&request.compartment_id, |unit_shell, compartment| {
+            let mut unit_model = unit_shell.model().borrow_mut();
+            let mapping_id = find_mapping_id(&unit_model, compartment, request.mapping_id)?;
+            unit_model.remove_mapping(QualifiedMappingId::new(compartment, mapping_id));
+            Ok(())
+        })
+    }
+
+    pub fn save_group(&self, request: SaveGroupRequest) -> Result<Response<Empty>, Status> {
+        let mut api_group: persistence::Group = serde_json::from_str(&request.data)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        if api_group.id.is_none() {
+            // Without key, the group would be interpreted as the default group
+            api_group.id = Some(GroupKey::random().to_string());
+        }
+        self.handle_compartment_command(&request.compartment_id, |unit_shell, compartment| {
+            let mut unit_model = unit_shell.model().borrow_mut();
+            let group_model = {
+                let conversion_context = unit_model.compartment_in_session(compartment);
+                let group_data = to_data::convert_group(api_group, false, |key| {
+                    conversion_context.param_index_by_key(key)
+                })?;
+                group_data.to_model(compartment, false, &conversion_context)
+            };
+            unit_model.upsert_group(compartment, group_model);
+            Ok(())
+        })
+    }
+
+    pub fn delete_group(&self, request: DeleteGroupRequest) -> Result<Response<Empty>, Status> {
+        self.handle_compartment_command(&request.compartment_id, |unit_shell, compartment| {
+            let mut unit_model = unit_shell.model().borrow_mut();
+            let group_id = find_group_id(&unit_model, compartment, request.group_id)?;
+            unit_model.remove_group(compartment, group_id, request.delete_mappings);
+            Ok(())
+        })
+    }
+
+    pub fn set_mapping_enabled(
+        &self,
+        request: SetMappingEnabledRequest,
+    ) -> Result<Response<Empty>, Status> {
+        self.handle_compartment_command(&request.compartment_id, |unit_shell, compartment| {
+            let shared_unit_model = unit_shell.model();
+            let mut unit_model = shared_unit_model.borrow_mut();
+            let mapping_id = find_mapping_id(&unit_model, compartment, request.mapping_id)?;
+            unit_model.change_with_notification(
+                SessionCommand::ChangeCompartment(
+                    compartment,
+                    CompartmentCommand::ChangeMapping(
+                        mapping_id,
+                        Box::new(MappingCommand::SetIsEnabled(request.enabled)),
+                    ),
+                ),
+                None,
+                Rc::downgrade(shared_unit_model),
+            );
+            Ok(())
+        })
+    }
+
+    pub fn hit_mapping_target(
+        &self,
+        request: HitMappingTargetRequest,
+    ) -> Result<Response<Empty>, Status> {
+        use hit_mapping_target_request::Value;
+        let value = match request.value {
+            None => return Err(Status::invalid_argument("value missing")),
+            Some(Value::Absolute(v)) => {
+                if !(0.0..=1.0).contains(&v) {
+                    return Err(Status::invalid_argument(
+                        "absolute value must be between 0.0 and 1.0",
+                    ));
+                }
+                ControlValue::AbsoluteContinuous(UnitValue::new(v))
+            }
+            Some(Value::Relative(increment)) => {
+                if increment == 0 {
+                    return Err(Status::invalid_argument("increment must not be zero"));
+                }
+                ControlValue::RelativeDiscrete(DiscreteIncrement::new(increment))
+            }
+        };
+        self.handle_compartment_command(&request.compartment_id, |unit_shell, compartment| {
+            let unit_model = unit_shell.model().borrow();
+            let mapping_id = find_mapping_id(&unit_model, compartment, request.mapping_id)?;
+            unit_model.hit_target(QualifiedMappingId::new(compartment, mapping_id), value);
+            Ok(())
+        })
+    }
+
     pub fn get_custom_instance_data(
         &self,
         request: GetCustomInstanceDataRequest,
@@ -772,6 +925,15 @@ impl ProtoRequestHandler {
         Ok(Response::new(Empty {}))
     }
 
+    fn handle_compartment_command(
+        &self,
+        full_compartment_id: &Option<FullCompartmentId>,
+        handler: impl FnOnce(&UnitShell, CompartmentKind) -> anyhow::Result<()>,
+    ) -> Result<Response<Empty>, Status> {
+        self.handle_compartment_command_internal(full_compartment_id, handler)?;
+        Ok(Response::new(Empty {}))
+    }
+
     fn handle_compartment_command_internal<R>(
         &self,
         full_compartment_id: &Option<FullCompartmentId>,
@@ -819,6 +981,28 @@ impl ProtoRequestHandler {
     }
 }
 
+fn find_mapping_id(
+    unit_model: &UnitModel,
+    compartment: CompartmentKind,
+    mapping_key: String,
+) -> anyhow::Result<MappingId> {
+    unit_model
+        .find_mapping_id_by_key(compartment, &mapping_key.into())
+        .context("Mapping not found")
+}
+
+fn find_group_id(
+    unit_model: &UnitModel,
+    compartment: CompartmentKind,
+    group_key: String,
+) -> anyhow::Result<GroupId> {
+    let group = unit_model
+        .find_group_by_key(compartment, &group_key.into())
+        .context("Group not found")?;
+    let id = group.borrow().id();
+    Ok(id)
+}
+
 #[cfg(not(feature = "playtime"))]
 pub fn playtime_not_available() -> Result<Response<Empty>, Status> {
     Err(playtime_not_available_status())
@@ -2,9 +2,10 @@ use crate::domain::InstanceId;
 use crate::infrastructure::proto::{
     event_reply, ContinuousColumnUpdate, ContinuousMatrixUpdate, EventReply,
     OccasionalGlobalUpdate, OccasionalInstanceUpdate, OccasionalMatrixUpdate,
-    OccasionalPlaytimeEngineUpdate, QualifiedContinuousSlotUpdate, QualifiedOccasionalClipUpdate,
-    QualifiedOccasionalColumnUpdate, QualifiedOccasionalRowUpdate, QualifiedOccasionalSlotUpdate,
-    QualifiedOccasionalTrackUpdate, QualifiedOccasionalUnitUpdate,
+    OccasionalPlaytimeEngineUpdate, QualifiedContinuousMappingUpdate,
+    QualifiedContinuousSlotUpdate, QualifiedOccasionalClipUpdate, QualifiedOccasionalColumnUpdate,
+    QualifiedOccasionalRowUpdate, QualifiedOccasionalSlotUpdate, QualifiedOccasionalTrackUpdate,
+    QualifiedOccasionalUnitUpdate,
 };
 use futures::future;
 use tokio::sync::broadcast::{Receiver, Sender};
@@ -26,6 +27,7 @@ pub struct ProtoSenders {
     pub continuous_matrix_update_sender: Sender<ContinuousMatrixUpdateBatch>,
     pub continuous_column_update_sender: Sender<ContinuousColumnUpdateBatch>,
     pub continuous_slot_update_sender: Sender<ContinuousSlotUpdateBatch>,
+    pub continuous_mapping_update_sender: Sender<ContinuousMappingUpdateBatch>,
 }
 
 #[derive(Debug)]
@@ -43,6 +45,7 @@ pub struct ProtoReceivers {
     pub continuous_matrix_update_receiver: Receiver<ContinuousMatrixUpdateBatch>,
     pub continuous_column_update_receiver: Receiver<ContinuousColumnUpdateBatch>,
     pub continuous_slot_update_receiver: Receiver<ContinuousSlotUpdateBatch>,
+    pub continuous_mapping_update_receiver: Receiver<ContinuousMappingUpdateBatch>,
 }
 
 impl ProtoReceivers {
@@ -107,10 +110,17 @@ impl ProtoReceivers {
                 process,
                 &mut self.occasional_clip_update_receiver,
             ),
-            keep_processing_session_filtered_updates(
-                instance_id,
-                process,
-                &mut self.occasional_unit_update_receiver,
+            future::join(
+                keep_processing_session_filtered_updates(
+                    instance_id,
+                    process,
+                    &mut self.occasional_unit_update_receiver,
+                ),
+                keep_processing_session_filtered_updates(
+                    instance_id,
+                    process,
+                    &mut self.continuous_mapping_update_receiver,
+                ),
             ),
             keep_processing_updates(
                 process,
@@ -177,6 +187,7 @@ impl ProtoSenders {
             continuous_slot_update_sender: tokio::sync::broadcast::channel(1000).0,
             continuous_column_update_sender: tokio::sync::broadcast::channel(500).0,
             continuous_matrix_update_sender: tokio::sync::broadcast::channel(500).0,
+            continuous_mapping_update_sender: tokio::sync::broadcast::channel(1000).0,
         }
     }
 
@@ -197,6 +208,7 @@ impl ProtoSenders {
             continuous_matrix_update_receiver: self.continuous_matrix_update_sender.subscribe(),
             continuous_column_update_receiver: self.continuous_column_update_sender.subscribe(),
             continuous_slot_update_receiver: self.continuous_slot_update_sender.subscribe(),
+            continuous_mapping_update_receiver: self.continuous_mapping_update_sender.subscribe(),
         }
     }
 }
@@ -220,3 +232,4 @@ pub type OccasionalClipUpdateBatch = WithInstanceId<Vec<QualifiedOccasionalClipU
 pub type ContinuousMatrixUpdateBatch = WithInstanceId<ContinuousMatrixUpdate>;
 pub type ContinuousColumnUpdateBatch = WithInstanceId<Vec<ContinuousColumnUpdate>>;
 pub type ContinuousSlotUpdateBatch = WithInstanceId<Vec<QualifiedContinuousSlotUpdate>>;
+pub type ContinuousMappingUpdateBatch = WithInstanceId<Vec<QualifiedContinuousMappingUpdate>>;
@@ -4,14 +4,16 @@ use crate::infrastructure::proto::playtime_not_available_status;
 use crate::infrastructure::proto::senders::{ProtoSenders, WithInstanceId};
 use crate::infrastructure::proto::{
     create_initial_global_updates, create_initial_instance_updates, create_initial_unit_updates,
-    helgobox_service_server, AddLicenseRequest, DeleteControllerRequest, DragClipRequest,
-    DragColumnRequest, DragRowRequest, DragSlotRequest, Empty, GetAppSettingsReply,
-    GetAppSettingsRequest, GetArrangementInfoReply, GetArrangementInfoRequest, GetClipDetailReply,
-    GetClipDetailRequest, GetCompartmentDataReply, GetCompartmentDataRequest,
-    GetContinuousColumnUpdatesReply, GetContinuousColumnUpdatesRequest,
+    helgobox_service_server, AddLicenseRequest, DeleteControllerRequest, DeleteGroupRequest,
+    DeleteMappingRequest, DragClipRequest, DragColumnRequest, DragRowRequest, DragSlotRequest,
+    Empty, GetAppSettingsReply, GetAppSettingsRequest, GetArrangementInfoReply,
+    GetArrangementInfoRequest, GetClipDetailReply, GetClipDetailRequest, GetCompartmentDataReply,
+    GetCompartmentDataRequest, GetContinuousColumnUpdatesReply, GetContinuousColumnUpdatesRequest,
+    GetContinuousMappingUpdatesReply, GetContinuousMappingUpdatesRequest,
     GetContinuousMatrixUpdatesReply, GetContinuousMatrixUpdatesRequest,
     GetContinuousSlotUpdatesReply, GetContinuousSlotUpdatesRequest, GetCustomInstanceDataReply,
-    GetCustomInstanceDataRequest, GetOccasionalClipUpdatesReply, GetOccasionalClipUpdatesRequest,
+    GetCustomInstanceDataRequest, GetMappingsReply, GetMappingsRequest,
+    GetOccasionalClipUpdatesReply, GetOccasionalClipUpdatesRequest,
     GetOccasionalColumnUpdatesReply, GetOccasionalColumnUpdatesRequest,
     GetOccasionalGlobalUpdatesReply, GetOccasionalGlobalUpdatesRequest,
     GetOccasionalInstanceUpdatesReply, GetOccasionalInstanceUpdatesRequest,
@@ -20,14 +22,16 @@ use crate::infrastructure::proto::{
     GetOccasionalRowUpdatesReply, GetOccasionalRowUpdatesRequest, GetOccasionalSlotUpdatesReply,
     GetOccasionalSlotUpdatesRequest, GetOccasionalTrackUpdatesReply,
     GetOccasionalTrackUpdatesRequest, GetOccasionalUnitUpdatesReply,
-    GetOccasionalUnitUpdatesRequest, GetProjectDirReply, GetProjectDirRequest, ImportFilesRequest,
-    InsertColumnsRequest, OpenTrackFxRequest, ProtoRequestHandler, ProveAuthenticityReply,
-    ProveAuthenticityRequest, SaveControllerRequest, SaveCustomCompartmentDataRequest,
+    GetOccasionalUnitUpdatesRequest, GetProjectDirReply, GetProjectDirRequest, GetUnitsReply,
+    GetUnitsRequest, HitMappingTargetRequest, ImportFilesRequest, InsertColumnsRequest,
+    OpenTrackFxRequest, ProtoRequestHandler, ProveAuthenticityReply, ProveAuthenticityRequest,
+    SaveControllerRequest, SaveCustomCompartmentDataRequest, SaveGroupRequest, SaveMappingRequest,
     SetAppSettingsRequest, SetClipDataRequest, SetClipNameRequest, SetColumnSettingsRequest,
     SetColumnTrackRequest, SetCustomInstanceDataRequest, SetInstanceSettingsRequest,
-    SetMatrixPanRequest, SetMatrixPlayRateRequest, SetMatrixSettingsRequest, SetMatrixTempoRequest,
-    SetMatrixTimeSignatureRequest, SetMatrixVolumeRequest, SetPlaytimeEngineSettingsRequest,
-    SetRowDataRequest, SetTrackColorRequest, SetTrackInputMonitoringRequest, SetTrackInputRequest,
+    SetMappingEnabledRequest, SetMatrixPanRequest, SetMatrixPlayRateRequest,
+    SetMatrixSettingsRequest, SetMatrixTempoRequest, SetMatrixTimeSignatureRequest,
+    SetMatrixVolumeRequest, SetPlaytimeEngineSettingsRequest, SetRowDataRequest,
+    SetTrackColorRequest, SetTrackInputMonitoringRequest, SetTrackInputRequest,
     SetTrackNameRequest, SetTrackPanRequest, SetTrackVolumeRequest, TriggerClipRequest,
     TriggerColumnRequest, TriggerGlobalRequest, TriggerInstanceRequest, TriggerMatrixRequest,
     TriggerRowRequest, TriggerSlotRequest, TriggerTrackRequest,
@@ -100,6 +104,22 @@ impl helgobox_service_server::HelgoboxService for HelgoboxServiceImpl {
         )
     }
 
+    type GetContinuousMappingUpdatesStream =
+        SyncBoxStream<'static, Result<GetContinuousMappingUpdatesReply, Status>>;
+
+    async fn get_continuous_mapping_updates(
+        &self,
+        request: Request<GetContinuousMappingUpdatesRequest>,
+    ) -> Result<Response<Self::GetContinuousMappingUpdatesStream>, Status> {
+        let receiver = self.senders.continuous_mapping_update_sender.subscribe();
+        stream_by_session_id(
+            request.into_inner().instance_id,
+            receiver,
+            |mapping_updates| GetContinuousMappingUpdatesReply { mapping_updates },
+            iter::empty(),
+        )
+    }
+
     type GetOccasionalColumnUpdatesStream =
         SyncBoxStream<'static, Result<GetOccasionalColumnUpdatesReply, Status>>;
 
@@ -737,6 +757,64 @@ impl helgobox_service_server::HelgoboxService for HelgoboxServiceImpl {
             .save_custom_compartment_data(request.into_inner())
     }
 
+    async fn get_units(
+        &self,
+        request: Request<GetUnitsRequest>,
+    ) -> Result<Response<GetUnitsReply>, Status> {
+        self.command_handler.get_units(request.into_inner())
+    }
+
+    async fn get_mappings(
+        &self,
+        request: Request<GetMappingsRequest>,
+    ) -> Result<Response<GetMappingsReply>, Status> {
+        self.command_handler.get_mappings(request.into_inner())
+    }
+
+    async fn save_mapping(
+        &self,
+        request: Request<SaveMappingRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        self.command_handler.save_mapping(request.into_inner())
+    }
+
+    async fn delete_mapping(
+        &self,
+        request: Request<DeleteMappingRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        self.command_handler.delete_mapping(request.into_inner())
+    }
+
+    async fn save_group(
+        &self,
+        request: Request<SaveGroupRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        self.command_handler.save_group(request.into_inner())
+    }
+
+    async fn delete_group(
+        &self,
+        request: Request<DeleteGroupRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        self.command_handler.delete_group(request.into_inner())
+    }
+
+    async fn set_mapping_enabled(
+        &self,
+        request: Request<SetMappingEnabledRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        self.command_handler
+            .set_mapping_enabled(request.into_inner())
+    }
+
+    async fn hit_mapping_target(
+        &self,
+        request: Request<HitMappingTargetRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        self.command_handler
+            .hit_mapping_target(request.into_inner())
+    }
+
     async fn get_custom_instance_data(
         &self,
         request: Request<GetCustomInstanceDataRequest>,
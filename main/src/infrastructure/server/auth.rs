@@ -0,0 +1,277 @@
+//! Contains token-based access control for the HTTP, WebSocket and gRPC servers.
+//!
+//! Access tokens are managed in the app settings (the app generates and revokes them), so the
+//! server just reads them from there. As long as authentication is not enabled in the app
+//! settings, all clients are allowed to do everything (as in older versions).
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+pub type SharedServerAccessControl = Arc<ServerAccessControl>;
+
+/// Something that a client is allowed to do with the server.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, derive_more::Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccessScope {
+    /// Reading session, controller and routing data (read-only projection).
+    #[display(fmt = "projection")]
+    Projection,
+    /// Modifying controllers, compartments and mappings.
+    #[display(fmt = "controller-editing")]
+    ControllerEditing,
+    /// Remote-controlling Playtime.
+    #[display(fmt = "playtime-control")]
+    PlaytimeControl,
+    /// Sending control messages via WebSocket, acting as virtual controller device.
+    #[display(fmt = "control")]
+    Control,
+    /// Reading the Prometheus metrics.
+    #[display(fmt = "metrics")]
+    Metrics,
+    /// Reading and writing the app settings, which contain the access tokens themselves.
+    #[display(fmt = "admin")]
+    Admin,
+    /// A scope unknown to this version (e.g. written by a newer app). Never required, so it
+    /// doesn't grant anything.
+    #[serde(other)]
+    #[display(fmt = "unknown")]
+    Unknown,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerAccessToken {
+    /// Stable ID of the token, e.g. for revoking it.
+    pub id: String,
+    /// Human-readable description, e.g. the name of the device using the token.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// The actual secret which the client needs to send as bearer token.
+    pub token: String,
+    #[serde(default)]
+    pub scopes: Vec<AccessScope>,
+}
+
+/// The part of the app settings which is relevant for server access control.
+///
+/// All other app settings properties are ignored.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServerAccessSettings {
+    pub server_auth_enabled: bool,
+    pub server_access_tokens: Vec<ServerAccessToken>,
+}
+
+impl ServerAccessSettings {
+    /// Extracts the server access settings from the app settings JSON.
+    ///
+    /// Falls back to defaults if the app settings are not available. If they can't be parsed,
+    /// all access is denied because we can't know whether authentication was supposed to be
+    /// enabled.
+    pub fn from_app_settings(app_settings: Option<&str>) -> Self {
+        let Some(json) = app_settings else {
+            return Self::default();
+        };
+        serde_json::from_str(json).unwrap_or_else(|e| {
+            tracing::error!(
+                msg = "Couldn't parse server access settings from app settings, denying all server access",
+                %e
+            );
+            Self::deny_all()
+        })
+    }
+
+    /// Settings which enable authentication without accepting any token.
+    pub fn deny_all() -> Self {
+        Self {
+            server_auth_enabled: true,
+            server_access_tokens: vec![],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, derive_more::Display)]
+pub enum AuthRejection {
+    #[display(fmt = "missing access token")]
+    MissingToken,
+    #[display(fmt = "unknown access token")]
+    UnknownToken,
+    #[display(fmt = "access token lacks required scope")]
+    InsufficientScope,
+}
+
+impl AuthRejection {
+    /// ID for recording this rejection as metric.
+    pub fn metric_id(&self) -> &'static str {
+        match self {
+            AuthRejection::MissingToken => "server.auth.rejected.missing_token",
+            AuthRejection::UnknownToken => "server.auth.rejected.unknown_token",
+            AuthRejection::InsufficientScope => "server.auth.rejected.insufficient_scope",
+        }
+    }
+}
+
+/// Decides whether requests are allowed.
+///
+/// Shared between the server threads, updated from the main thread whenever the app settings
+/// change.
+#[derive(Debug, Default)]
+pub struct ServerAccessControl {
+    settings: RwLock<ServerAccessSettings>,
+}
+
+impl ServerAccessControl {
+    pub fn new(settings: ServerAccessSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+        }
+    }
+
+    pub fn update_settings(&self, settings: ServerAccessSettings) {
+        *self.settings.write().unwrap() = settings;
+    }
+
+    /// Checks whether the given bearer token (if any) grants the given scope.
+    pub fn authorize(&self, token: Option<&str>, scope: AccessScope) -> Result<(), AuthRejection> {
+        let settings = self.settings.read().unwrap();
+        if !settings.server_auth_enabled {
+            return Ok(());
+        }
+        let token = token.ok_or(AuthRejection::MissingToken)?;
+        let access_token = settings
+            .server_access_tokens
+            .iter()
+            .find(|t| constant_time_eq(t.token.as_bytes(), token.as_bytes()))
+            .ok_or(AuthRejection::UnknownToken)?;
+        if !access_token.scopes.contains(&scope) {
+            return Err(AuthRejection::InsufficientScope);
+        }
+        Ok(())
+    }
+}
+
+/// Compares without returning early, so that the comparison doesn't leak timing information.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access_control() -> ServerAccessControl {
+        let json = r#"{
+            "theme": "dark",
+            "serverAuthEnabled": true,
+            "serverAccessTokens": [
+                {
+                    "id": "stage",
+                    "token": "abc",
+                    "scopes": ["projection"]
+                },
+                {
+                    "id": "admin",
+                    "label": "Studio computer",
+                    "token": "xyz",
+                    "scopes": ["projection", "controller-editing", "metrics"]
+                }
+            ]
+        }"#;
+        ServerAccessControl::new(ServerAccessSettings::from_app_settings(Some(json)))
+    }
+
+    #[test]
+    fn allow_everything_if_auth_disabled() {
+        let access_control = ServerAccessControl::default();
+        assert_eq!(
+            access_control.authorize(None, AccessScope::ControllerEditing),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reject_missing_or_unknown_token() {
+        let access_control = access_control();
+        assert_eq!(
+            access_control.authorize(None, AccessScope::Projection),
+            Err(AuthRejection::MissingToken)
+        );
+        assert_eq!(
+            access_control.authorize(Some("ab"), AccessScope::Projection),
+            Err(AuthRejection::UnknownToken)
+        );
+    }
+
+    #[test]
+    fn check_scopes() {
+        let access_control = access_control();
+        assert_eq!(
+            access_control.authorize(Some("abc"), AccessScope::Projection),
+            Ok(())
+        );
+        assert_eq!(
+            access_control.authorize(Some("abc"), AccessScope::ControllerEditing),
+            Err(AuthRejection::InsufficientScope)
+        );
+        assert_eq!(
+            access_control.authorize(Some("xyz"), AccessScope::ControllerEditing),
+            Ok(())
+        );
+        assert_eq!(
+            access_control.authorize(Some("xyz"), AccessScope::PlaytimeControl),
+            Err(AuthRejection::InsufficientScope)
+        );
+    }
+
+    #[test]
+    fn deny_everything_if_settings_invalid() {
+        // Given
+        let json = r#"{ "serverAuthEnabled": "maybe" }"#;
+        // When
+        let settings = ServerAccessSettings::from_app_settings(Some(json));
+        // Then
+        let access_control = ServerAccessControl::new(settings);
+        assert_eq!(
+            access_control.authorize(None, AccessScope::Projection),
+            Err(AuthRejection::MissingToken)
+        );
+        assert_eq!(
+            access_control.authorize(Some("abc"), AccessScope::Projection),
+            Err(AuthRejection::UnknownToken)
+        );
+    }
+
+    #[test]
+    fn tolerate_unknown_scopes() {
+        // Given
+        let json = r#"{
+            "serverAuthEnabled": true,
+            "serverAccessTokens": [
+                {
+                    "id": "stage",
+                    "token": "abc",
+                    "scopes": ["projection", "teleportation"]
+                }
+            ]
+        }"#;
+        // When
+        let settings = ServerAccessSettings::from_app_settings(Some(json));
+        // Then
+        assert_eq!(
+            settings.server_access_tokens[0].scopes,
+            vec![AccessScope::Projection, AccessScope::Unknown]
+        );
+        let access_control = ServerAccessControl::new(settings);
+        assert_eq!(
+            access_control.authorize(Some("abc"), AccessScope::Projection),
+            Ok(())
+        );
+        assert_eq!(
+            access_control.authorize(Some("abc"), AccessScope::Admin),
+            Err(AuthRejection::InsufficientScope)
+        );
+    }
+}
@@ -1,14 +1,31 @@
 //! Contains the actual application interface and implementation without any HTTP-specific stuff.
 
 use crate::application::{
-    CompartmentPresetManager, CompartmentPresetModel, SourceCategory, TargetCategory, UnitModel,
+    CompartmentPresetManager, CompartmentPresetModel, SharedUnitModel, SourceCategory,
+    TargetCategory, UnitModel,
+};
+use crate::domain::{
+    compartment_param_index_iter, CompartmentKind, MappingKey, ProjectionFeedbackValue,
+    QualifiedMappingId, RealearnTarget, SourceFeedbackEvent, TargetControlEvent,
+    TargetValueChangedEvent, VirtualControlElement, VirtualControlElementId, VirtualSourceValue,
+    WebSocketControlMessage, WebSocketFeedbackValue,
+};
+use crate::infrastructure::api::compartment_diff;
+use crate::infrastructure::api::compartment_diff::{CompartmentDiff, CompartmentMerge};
+use crate::infrastructure::api::convert::from_data::ConversionStyle;
+use crate::infrastructure::api::convert::{from_data, to_data};
+use crate::infrastructure::data::{
+    CommonCompartmentPresetManager, CompartmentModelData, CompartmentPresetData,
+    PresetValidationIssue, PresetValidationSeverity,
 };
-use crate::domain::{CompartmentKind, MappingKey, ProjectionFeedbackValue};
-use crate::infrastructure::data::CompartmentPresetData;
 use crate::infrastructure::plugin::BackboneShell;
 use base::hash_util::{NonCryptoHashMap, NonCryptoHashSet};
-use helgoboss_learn::UnitValue;
+use helgoboss_learn::{ControlValue, DiscreteIncrement, FeedbackValue, Target, UnitValue};
+use helgobox_api::persistence;
+use helgobox_api::persistence::VirtualControlElementCharacter;
+use rosc::{OscMessage, OscType};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::rc::Rc;
@@ -25,12 +42,25 @@ pub enum DataError {
     OnlyPatchReplaceIsSupported,
     OnlyCustomDataKeyIsSupportedAsPatchPath,
     ControllerUpdateFailed,
+    InvalidCompartment,
+    MappingNotFound,
+    PresetNotFound,
+    /// Contains the details why the compartment data is invalid.
+    InvalidCompartmentData(String),
+    /// Contains the details why the patched mapping is invalid.
+    InvalidMappingData(String),
+    InvalidJsonPatch,
+    JsonPatchTestFailed,
+    MappingIdChangeNotSupported,
+    /// Contains the details why the conversion failed.
+    CompartmentConversionFailed(String),
 }
 
 pub enum DataErrorCategory {
     NotFound,
     BadRequest,
     MethodNotAllowed,
+    Conflict,
     InternalServerError,
 }
 
@@ -46,18 +76,50 @@ impl DataError {
                 "only '/customData/{key}' is supported as path"
             }
             ControllerUpdateFailed => "couldn't update controller",
+            InvalidCompartment => "compartment must be 'main' or 'controller'",
+            MappingNotFound => "mapping not found",
+            PresetNotFound => "preset not found",
+            InvalidCompartmentData(_) => "invalid compartment data",
+            InvalidMappingData(_) => "patched mapping is not valid",
+            InvalidJsonPatch => "couldn't apply JSON patch",
+            JsonPatchTestFailed => "JSON patch 'test' operation failed",
+            MappingIdChangeNotSupported => "changing the mapping ID is not supported",
+            CompartmentConversionFailed(_) => "couldn't convert compartment",
         }
     }
 
-    pub fn category(&self) -> DataErrorCategory {
+    /// Like [`Self::description`] but including details about the cause, if available.
+    pub fn message(&self) -> String {
         use DataError::*;
         match self {
-            SessionNotFound | SessionHasNoActiveController | ControllerNotFound => {
-                DataErrorCategory::NotFound
+            InvalidCompartmentData(details)
+            | InvalidMappingData(details)
+            | CompartmentConversionFailed(details) => {
+                format!("{}: {details}", self.description())
             }
+            _ => self.description().to_string(),
+        }
+    }
+
+    pub fn category(&self) -> DataErrorCategory {
+        use DataError::*;
+        match self {
+            SessionNotFound
+            | SessionHasNoActiveController
+            | ControllerNotFound
+            | MappingNotFound
+            | PresetNotFound => DataErrorCategory::NotFound,
             OnlyPatchReplaceIsSupported => DataErrorCategory::MethodNotAllowed,
-            OnlyCustomDataKeyIsSupportedAsPatchPath => DataErrorCategory::BadRequest,
-            ControllerUpdateFailed => DataErrorCategory::InternalServerError,
+            OnlyCustomDataKeyIsSupportedAsPatchPath
+            | InvalidCompartment
+            | InvalidCompartmentData(_)
+            | InvalidMappingData(_)
+            | InvalidJsonPatch
+            | MappingIdChangeNotSupported => DataErrorCategory::BadRequest,
+            JsonPatchTestFailed => DataErrorCategory::Conflict,
+            ControllerUpdateFailed | CompartmentConversionFailed(_) => {
+                DataErrorCategory::InternalServerError
+            }
         }
     }
 }
@@ -217,6 +279,455 @@ pub fn patch_controller(controller_id: String, req: PatchRequest) -> Result<(),
     Ok(())
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceData {
+    id: u32,
+    units: Vec<UnitData>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitData {
+    /// Unit key, usable as session ID in the other endpoints.
+    key: String,
+    name: Option<String>,
+    active_controller_preset_id: Option<String>,
+    active_main_preset_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetData {
+    id: String,
+    name: String,
+    factory: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterData {
+    /// Zero-based index within the compartment.
+    index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    name: String,
+    /// Normalized value between 0.0 and 1.0.
+    value: f64,
+    /// Value as displayed to the user, taking value count and labels into account.
+    formatted_value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadPresetRequest {
+    /// Preset to load. `None` means loading the <None> preset, which clears the compartment.
+    id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatePresetQuery {
+    /// Preset to validate.
+    id: String,
+    /// Controller preset against which a main preset should be validated.
+    #[serde(default)]
+    controller_preset_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CompartmentDiffRequest {
+    old: persistence::Compartment,
+    new: persistence::Compartment,
+}
+
+#[derive(Deserialize)]
+pub struct CompartmentMergeRequest {
+    /// Common ancestor, e.g. the preset as it was originally shipped.
+    base: persistence::Compartment,
+    /// Locally modified version. Wins in case of conflicts.
+    ours: persistence::Compartment,
+    /// Updated version, e.g. a new release of the preset.
+    theirs: persistence::Compartment,
+}
+
+/// A single operation of a JSON Patch document as defined in RFC 6902.
+///
+/// `move` and `copy` are not supported.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum JsonPatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Test { path: String, value: Value },
+}
+
+pub fn parse_compartment(compartment: &str) -> Result<CompartmentKind, DataError> {
+    match compartment {
+        "main" => Ok(CompartmentKind::Main),
+        "controller" => Ok(CompartmentKind::Controller),
+        _ => Err(DataError::InvalidCompartment),
+    }
+}
+
+pub fn get_instances() -> Vec<InstanceData> {
+    BackboneShell::get().with_instance_shell_infos(|infos| {
+        infos
+            .iter()
+            .filter_map(|info| {
+                let instance_shell = info.instance_shell.upgrade()?;
+                let units = instance_shell
+                    .all_unit_models()
+                    .map(|unit_model| {
+                        let unit_model = unit_model.borrow();
+                        UnitData {
+                            key: unit_model.unit_key().to_string(),
+                            name: unit_model.name().map(|n| n.to_string()),
+                            active_controller_preset_id: unit_model
+                                .active_preset_id(CompartmentKind::Controller)
+                                .map(|id| id.to_string()),
+                            active_main_preset_id: unit_model
+                                .active_preset_id(CompartmentKind::Main)
+                                .map(|id| id.to_string()),
+                        }
+                    })
+                    .collect();
+                let data = InstanceData {
+                    id: info.instance_id.into(),
+                    units,
+                };
+                Some(data)
+            })
+            .collect()
+    })
+}
+
+pub fn get_compartment(
+    session_id: &str,
+    compartment: &str,
+) -> Result<persistence::Compartment, DataError> {
+    let compartment = parse_compartment(compartment)?;
+    let session = find_session(session_id)?;
+    let session = session.borrow();
+    extract_api_compartment(&session, compartment)
+}
+
+pub fn put_compartment(
+    session_id: &str,
+    compartment: &str,
+    api_compartment: persistence::Compartment,
+) -> Result<(), DataError> {
+    let compartment = parse_compartment(compartment)?;
+    let session = find_session(session_id)?;
+    let mut session = session.borrow_mut();
+    let compartment_data = to_data::convert_compartment(compartment, api_compartment)
+        .map_err(|e| DataError::InvalidCompartmentData(format!("{e:#}")))?;
+    let compartment_model = compartment_data
+        .to_model(None, compartment, Some(&*session))
+        .map_err(|e| DataError::InvalidCompartmentData(format!("{e:#}")))?;
+    session.import_compartment(compartment, Some(compartment_model));
+    Ok(())
+}
+
+/// Applies the given JSON Patch to the mapping with the given ID and returns the patched mapping.
+///
+/// The patch is applied to the mapping in its persistence API representation, so paths look like
+/// `/source/channel` or `/glue/source_interval`.
+pub fn patch_mapping(
+    session_id: &str,
+    compartment: &str,
+    mapping_id: &str,
+    operations: Vec<JsonPatchOperation>,
+) -> Result<Value, DataError> {
+    let compartment = parse_compartment(compartment)?;
+    let session = find_session(session_id)?;
+    let mut session = session.borrow_mut();
+    let api_mapping = extract_api_compartment(&session, compartment)?
+        .mappings
+        .unwrap_or_default()
+        .into_iter()
+        .find(|m| m.id.as_deref() == Some(mapping_id))
+        .ok_or(DataError::MappingNotFound)?;
+    let mut json = serde_json::to_value(api_mapping)
+        .map_err(|e| DataError::CompartmentConversionFailed(e.to_string()))?;
+    apply_json_patch(&mut json, operations)?;
+    let patched_api_mapping: persistence::Mapping = serde_json::from_value(json.clone())
+        .map_err(|e| DataError::InvalidMappingData(e.to_string()))?;
+    if patched_api_mapping.id.as_deref() != Some(mapping_id) {
+        return Err(DataError::MappingIdChangeNotSupported);
+    }
+    let mapping_model = {
+        let conversion_context = session.compartment_in_session(compartment);
+        let mapping_data = to_data::convert_mapping(patched_api_mapping, &conversion_context)
+            .map_err(|e| DataError::InvalidMappingData(format!("{e:#}")))?;
+        mapping_data
+            .to_model(
+                compartment,
+                &conversion_context,
+                Some(session.extended_context()),
+                None,
+            )
+            .map_err(|e| DataError::InvalidMappingData(e.to_string()))?
+    };
+    session.upsert_mapping(compartment, mapping_model);
+    Ok(json)
+}
+
+pub fn get_presets(compartment: &str) -> Result<Vec<PresetData>, DataError> {
+    fn collect(manager: &dyn CommonCompartmentPresetManager) -> Vec<PresetData> {
+        manager
+            .common_preset_infos()
+            .map(|info| PresetData {
+                id: info.id.clone(),
+                name: info.meta_data.name.clone(),
+                factory: info.origin.is_factory(),
+            })
+            .collect()
+    }
+    let backbone = BackboneShell::get();
+    let presets = match parse_compartment(compartment)? {
+        CompartmentKind::Controller => collect(&*backbone.controller_preset_manager().borrow()),
+        CompartmentKind::Main => collect(&*backbone.main_preset_manager().borrow()),
+    };
+    Ok(presets)
+}
+
+pub fn load_preset(
+    session_id: &str,
+    compartment: &str,
+    req: LoadPresetRequest,
+) -> Result<(), DataError> {
+    let compartment = parse_compartment(compartment)?;
+    let session = find_session(session_id)?;
+    if let Some(id) = &req.id {
+        let backbone = BackboneShell::get();
+        let exists = match compartment {
+            CompartmentKind::Controller => backbone
+                .controller_preset_manager()
+                .borrow()
+                .common_preset_info_by_id(id)
+                .is_some(),
+            CompartmentKind::Main => backbone
+                .main_preset_manager()
+                .borrow()
+                .common_preset_info_by_id(id)
+                .is_some(),
+        };
+        if !exists {
+            return Err(DataError::PresetNotFound);
+        }
+    }
+    session.borrow_mut().activate_preset(compartment, req.id);
+    Ok(())
+}
+
+/// Validates the given preset without loading it into any unit.
+///
+/// A preset that can't be loaded at all is not treated as request error but reported as issue.
+pub fn validate_preset(
+    compartment: &str,
+    query: ValidatePresetQuery,
+) -> Result<Vec<PresetValidationIssue>, DataError> {
+    let compartment = parse_compartment(compartment)?;
+    let backbone = BackboneShell::get();
+    let controller = match &query.controller_preset_id {
+        None => None,
+        Some(id) => {
+            let preset = backbone
+                .controller_preset_manager()
+                .borrow()
+                .find_by_id(id)
+                .ok_or(DataError::PresetNotFound)?;
+            Some(preset.model().clone())
+        }
+    };
+    let preset_manager = backbone.compartment_preset_manager(compartment);
+    let preset_manager = preset_manager.borrow();
+    if preset_manager.common_preset_info_by_id(&query.id).is_none() {
+        return Err(DataError::PresetNotFound);
+    }
+    let issues = preset_manager
+        .validate_preset(&query.id, controller.as_ref())
+        .unwrap_or_else(|e| {
+            vec![PresetValidationIssue {
+                severity: PresetValidationSeverity::Error,
+                mapping_key: None,
+                message: format!("Couldn't load preset: {e:#}"),
+            }]
+        });
+    Ok(issues)
+}
+
+pub fn diff_compartments(req: CompartmentDiffRequest) -> Result<CompartmentDiff, DataError> {
+    compartment_diff::diff_compartments(&req.old, &req.new)
+        .map_err(|e| DataError::InvalidCompartmentData(e.to_string()))
+}
+
+pub fn merge_compartments(req: CompartmentMergeRequest) -> Result<CompartmentMerge, DataError> {
+    compartment_diff::merge_compartments(&req.base, &req.ours, &req.theirs)
+        .map_err(|e| DataError::InvalidCompartmentData(e.to_string()))
+}
+
+pub fn get_parameters(
+    session_id: &str,
+    compartment: &str,
+) -> Result<Vec<ParameterData>, DataError> {
+    let compartment = parse_compartment(compartment)?;
+    let session = find_session(session_id)?;
+    let parameters = collect_parameters(&session.borrow(), compartment);
+    Ok(parameters)
+}
+
+fn collect_parameters(session: &UnitModel, compartment: CompartmentKind) -> Vec<ParameterData> {
+    let compartment_params = session.params().compartment_params(compartment);
+    compartment_param_index_iter()
+        .map(|i| {
+            let param = compartment_params.at(i);
+            ParameterData {
+                index: i.get(),
+                key: param.setting().key.clone(),
+                name: compartment_params.get_parameter_name(i).to_string(),
+                value: param.raw_value() as f64,
+                formatted_value: param.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the current values of all targets in the given compartment which can report one.
+///
+/// Only considers mappings which are on. The key is the mapping key.
+fn collect_target_values(
+    session: &UnitModel,
+    compartment: CompartmentKind,
+) -> NonCryptoHashMap<String, f64> {
+    let on_mappings: Vec<_> = {
+        let instance_state = session.unit().borrow();
+        session
+            .mappings(compartment)
+            .filter(|m| instance_state.mapping_is_on(m.borrow().qualified_id()))
+            .cloned()
+            .collect()
+    };
+    let control_context = session.control_context();
+    on_mappings
+        .into_iter()
+        .filter_map(|m| {
+            let m = m.borrow();
+            let target = m
+                .target_model
+                .with_context(session.extended_context(), compartment)
+                .resolve_first()
+                .ok()?;
+            if target.is_virtual() || !target.can_report_current_value() {
+                return None;
+            }
+            let value = target.current_value(control_context)?;
+            Some((m.key().to_string(), value.to_unit_value().get()))
+        })
+        .collect()
+}
+
+fn collect_mapping_on_states(
+    session: &UnitModel,
+    compartment: CompartmentKind,
+) -> NonCryptoHashMap<String, bool> {
+    let instance_state = session.unit().borrow();
+    session
+        .mappings(compartment)
+        .map(|m| {
+            let m = m.borrow();
+            let is_on = instance_state.mapping_is_on(m.qualified_id());
+            (m.key().to_string(), is_on)
+        })
+        .collect()
+}
+
+fn find_session(session_id: &str) -> Result<SharedUnitModel, DataError> {
+    BackboneShell::get()
+        .find_unit_model_by_key(session_id)
+        .ok_or(DataError::SessionNotFound)
+}
+
+fn extract_api_compartment(
+    session: &UnitModel,
+    compartment: CompartmentKind,
+) -> Result<persistence::Compartment, DataError> {
+    let compartment_model = session.extract_compartment_model(compartment);
+    let compartment_data = CompartmentModelData::from_model(&compartment_model);
+    from_data::convert_compartment(compartment_data, ConversionStyle::Minimal)
+        .map_err(|e| DataError::CompartmentConversionFailed(format!("{e:#}")))
+}
+
+fn apply_json_patch(doc: &mut Value, operations: Vec<JsonPatchOperation>) -> Result<(), DataError> {
+    for op in operations {
+        match op {
+            JsonPatchOperation::Add { path, value } if path.is_empty() => {
+                // Adding to the root replaces the whole document (RFC 6902, section 4.1)
+                *doc = value;
+            }
+            JsonPatchOperation::Add { path, value } => {
+                let (parent, last) = split_json_pointer(&path)?;
+                match doc.pointer_mut(parent).ok_or(DataError::InvalidJsonPatch)? {
+                    Value::Object(map) => {
+                        map.insert(last, value);
+                    }
+                    Value::Array(array) if last == "-" => array.push(value),
+                    Value::Array(array) => {
+                        let index = parse_array_index(&last, array.len() + 1)?;
+                        array.insert(index, value);
+                    }
+                    _ => return Err(DataError::InvalidJsonPatch),
+                }
+            }
+            JsonPatchOperation::Remove { path } => {
+                // The root can't be removed because then there wouldn't be a document anymore.
+                // That's covered by split_json_pointer() because the empty pointer has no parent.
+                let (parent, last) = split_json_pointer(&path)?;
+                match doc.pointer_mut(parent).ok_or(DataError::InvalidJsonPatch)? {
+                    Value::Object(map) => {
+                        map.remove(&last).ok_or(DataError::InvalidJsonPatch)?;
+                    }
+                    Value::Array(array) => {
+                        let index = parse_array_index(&last, array.len())?;
+                        array.remove(index);
+                    }
+                    _ => return Err(DataError::InvalidJsonPatch),
+                }
+            }
+            JsonPatchOperation::Replace { path, value } => {
+                // The empty pointer refers to the root, so this replaces the whole document
+                let target = doc.pointer_mut(&path).ok_or(DataError::InvalidJsonPatch)?;
+                *target = value;
+            }
+            JsonPatchOperation::Test { path, value } => {
+                if doc.pointer(&path) != Some(&value) {
+                    return Err(DataError::JsonPatchTestFailed);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits a JSON pointer into the pointer to the parent and the unescaped last reference token.
+fn split_json_pointer(pointer: &str) -> Result<(&str, String), DataError> {
+    let (parent, last) = pointer
+        .rsplit_once('/')
+        .ok_or(DataError::InvalidJsonPatch)?;
+    let last = last.replace("~1", "/").replace("~0", "~");
+    Ok((parent, last))
+}
+
+fn parse_array_index(token: &str, len: usize) -> Result<usize, DataError> {
+    let index: usize = token.parse().map_err(|_| DataError::InvalidJsonPatch)?;
+    if index >= len {
+        return Err(DataError::InvalidJsonPatch);
+    }
+    Ok(index)
+}
+
 #[derive(Deserialize)]
 pub struct WebSocketRequest {
     pub topics: String,
@@ -230,12 +741,56 @@ impl WebSocketRequest {
 
 pub type Topics = NonCryptoHashSet<Topic>;
 
+/// Returns the ID of the session which a client acting as virtual controller device controls.
+///
+/// That's the session whose WebSocket feedback topic the client subscribed to. If the client
+/// subscribed to the WebSocket feedback of multiple sessions, it's ambiguous, so `None` is returned.
+pub fn get_web_socket_control_session_id(topics: &Topics) -> Option<String> {
+    let mut session_ids = topics.iter().filter_map(|t| match t {
+        Topic::WebSocketFeedback { session_id } => Some(session_id),
+        _ => None,
+    });
+    let session_id = session_ids.next()?;
+    if session_ids.next().is_some() {
+        return None;
+    }
+    Some(session_id.clone())
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Topic {
-    Session { session_id: String },
-    ActiveController { session_id: String },
-    ControllerRouting { session_id: String },
-    Feedback { session_id: String },
+    Session {
+        session_id: String,
+    },
+    ActiveController {
+        session_id: String,
+    },
+    ControllerRouting {
+        session_id: String,
+    },
+    Feedback {
+        session_id: String,
+    },
+    TargetValues {
+        session_id: String,
+        compartment: CompartmentKind,
+    },
+    Parameters {
+        session_id: String,
+        compartment: CompartmentKind,
+    },
+    MappingOnStates {
+        session_id: String,
+        compartment: CompartmentKind,
+    },
+    /// Control and feedback log entries of all mappings.
+    Log {
+        session_id: String,
+    },
+    /// Feedback for clients acting as virtual controller device (feedback output "WebSocket").
+    WebSocketFeedback {
+        session_id: String,
+    },
 }
 
 impl TryFrom<&str> for Topic {
@@ -253,15 +808,82 @@ impl TryFrom<&str> for Topic {
             ["realearn", "session", id, "feedback"] => Topic::Feedback {
                 session_id: id.to_string(),
             },
+            ["realearn", "session", id, "log"] => Topic::Log {
+                session_id: id.to_string(),
+            },
+            ["realearn", "session", id, "web-socket-feedback"] => Topic::WebSocketFeedback {
+                session_id: id.to_string(),
+            },
             ["realearn", "session", id] => Topic::Session {
                 session_id: id.to_string(),
             },
+            ["realearn", "session", id, "compartment", compartment, "target-values"] => {
+                Topic::TargetValues {
+                    session_id: id.to_string(),
+                    compartment: parse_topic_compartment(compartment)?,
+                }
+            }
+            ["realearn", "session", id, "compartment", compartment, "parameters"] => {
+                Topic::Parameters {
+                    session_id: id.to_string(),
+                    compartment: parse_topic_compartment(compartment)?,
+                }
+            }
+            ["realearn", "session", id, "compartment", compartment, "mapping-on-states"] => {
+                Topic::MappingOnStates {
+                    session_id: id.to_string(),
+                    compartment: parse_topic_compartment(compartment)?,
+                }
+            }
             _ => return Err("invalid topic expression"),
         };
         Ok(topic)
     }
 }
 
+fn parse_topic_compartment(compartment: &str) -> Result<CompartmentKind, &'static str> {
+    parse_compartment(compartment).map_err(|_| "invalid compartment in topic expression")
+}
+
+fn format_compartment(compartment: CompartmentKind) -> &'static str {
+    match compartment {
+        CompartmentKind::Controller => "controller",
+        CompartmentKind::Main => "main",
+    }
+}
+
+fn compartment_topic_path(session_id: &str, compartment: CompartmentKind, suffix: &str) -> String {
+    format!(
+        "/realearn/session/{session_id}/compartment/{}/{suffix}",
+        format_compartment(compartment)
+    )
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LogEntryData {
+    #[serde(rename_all = "camelCase")]
+    Control {
+        compartment: &'static str,
+        mapping_key: String,
+        mapping_name: String,
+        context: String,
+        kind: String,
+        control_value: Option<String>,
+        target_index: usize,
+        invocation_count: u32,
+        #[serde(skip_serializing_if = "str::is_empty")]
+        error: &'static str,
+    },
+    #[serde(rename_all = "camelCase")]
+    Feedback {
+        compartment: &'static str,
+        mapping_key: String,
+        mapping_name: String,
+        feedback_value: String,
+    },
+}
+
 pub fn send_initial_feedback(session_id: &str) {
     if let Some(session) = BackboneShell::get().find_unit_model_by_key(session_id) {
         session.borrow_mut().send_all_feedback();
@@ -287,6 +909,261 @@ pub fn get_projection_feedback_event(
     Event::patch(format!("/realearn/session/{session_id}/feedback"), map)
 }
 
+pub fn get_target_values_updated_event(
+    session_id: &str,
+    compartment: CompartmentKind,
+    session: Option<&UnitModel>,
+) -> Event<Option<NonCryptoHashMap<String, f64>>> {
+    Event::put(
+        compartment_topic_path(session_id, compartment, "target-values"),
+        session.map(|s| collect_target_values(s, compartment)),
+    )
+}
+
+pub fn get_target_value_changed_event(
+    session: &UnitModel,
+    event: &TargetValueChangedEvent,
+) -> Option<Event<NonCryptoHashMap<String, f64>>> {
+    let mapping = session.find_mapping_by_id(event.compartment, event.mapping_id)?;
+    let mapping = mapping.try_borrow().ok()?;
+    let mut map = HashMap::default();
+    map.insert(
+        mapping.key().to_string(),
+        event.new_value.to_unit_value().get(),
+    );
+    let path = compartment_topic_path(session.unit_key(), event.compartment, "target-values");
+    Some(Event::patch(path, map))
+}
+
+pub fn get_parameters_updated_event(
+    session_id: &str,
+    compartment: CompartmentKind,
+    session: Option<&UnitModel>,
+) -> Event<Option<Vec<ParameterData>>> {
+    Event::put(
+        compartment_topic_path(session_id, compartment, "parameters"),
+        session.map(|s| collect_parameters(s, compartment)),
+    )
+}
+
+pub fn get_mapping_on_states_updated_event(
+    session_id: &str,
+    compartment: CompartmentKind,
+    session: Option<&UnitModel>,
+) -> Event<Option<NonCryptoHashMap<String, bool>>> {
+    Event::put(
+        compartment_topic_path(session_id, compartment, "mapping-on-states"),
+        session.map(|s| collect_mapping_on_states(s, compartment)),
+    )
+}
+
+/// The log doesn't have any history, so the initial event just contains an empty list (or `null` if
+/// the session doesn't exist).
+pub fn get_initial_log_event(
+    session_id: &str,
+    session: Option<&UnitModel>,
+) -> Event<Option<Vec<LogEntryData>>> {
+    Event::put(
+        format!("/realearn/session/{session_id}/log"),
+        session.map(|_| vec![]),
+    )
+}
+
+pub fn get_control_log_event(
+    session: &UnitModel,
+    event: &TargetControlEvent,
+) -> Option<Event<LogEntryData>> {
+    let (mapping_key, mapping_name) = find_mapping_key_and_name(session, event.id)?;
+    let entry = &event.log_entry;
+    let data = LogEntryData::Control {
+        compartment: format_compartment(event.id.compartment),
+        mapping_key,
+        mapping_name,
+        context: event.log_context.to_string(),
+        kind: entry.kind.to_string(),
+        control_value: entry.control_value.map(|v| v.to_string()),
+        target_index: entry.target_index,
+        invocation_count: entry.invocation_count,
+        error: entry.error,
+    };
+    Some(get_log_entry_event(session.unit_key(), data))
+}
+
+pub fn get_feedback_log_event(
+    session: &UnitModel,
+    event: &SourceFeedbackEvent,
+) -> Option<Event<LogEntryData>> {
+    let (mapping_key, mapping_name) = find_mapping_key_and_name(session, event.id)?;
+    let data = LogEntryData::Feedback {
+        compartment: format_compartment(event.id.compartment),
+        mapping_key,
+        mapping_name,
+        feedback_value: event.log_entry.to_string(),
+    };
+    Some(get_log_entry_event(session.unit_key(), data))
+}
+
+fn get_log_entry_event(session_id: &str, data: LogEntryData) -> Event<LogEntryData> {
+    Event::post(format!("/realearn/session/{session_id}/log"), data)
+}
+
+/// Control message which a WebSocket client acting as virtual controller device sends to the
+/// server.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WebSocketControlMessageData {
+    /// Addressed to a virtual control element.
+    Virtual {
+        id: VirtualControlElementIdData,
+        #[serde(default)]
+        character: VirtualControlElementCharacter,
+        #[serde(flatten)]
+        value: ControlValueData,
+    },
+    /// Addressed to a raw source address (processed by mappings with OSC sources).
+    Raw { address: String, value: f64 },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum VirtualControlElementIdData {
+    /// 1-based position of an indexed control element.
+    Position(u32),
+    /// Name of a named control element or 1-based position as string.
+    Name(String),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ControlValueData {
+    /// Absolute value between 0.0 and 1.0.
+    Absolute { value: f64 },
+    /// Relative increment, e.g. -1 or 1 for one encoder step.
+    Relative { increment: i32 },
+}
+
+impl WebSocketControlMessageData {
+    pub fn to_domain(&self) -> Result<WebSocketControlMessage, &'static str> {
+        let msg = match self {
+            WebSocketControlMessageData::Virtual {
+                id,
+                character,
+                value,
+            } => {
+                let id = match id {
+                    VirtualControlElementIdData::Position(pos) => {
+                        let index = pos.checked_sub(1).ok_or("position must be at least 1")?;
+                        VirtualControlElementId::Indexed(index)
+                    }
+                    VirtualControlElementIdData::Name(name) => {
+                        if name.parse::<i32>().is_ok_and(|pos| pos < 1) {
+                            return Err("position must be at least 1");
+                        }
+                        name.parse()?
+                    }
+                };
+                let control_value = match value {
+                    ControlValueData::Absolute { value } => {
+                        ControlValue::AbsoluteContinuous(UnitValue::new_clamped(*value))
+                    }
+                    ControlValueData::Relative { increment } => {
+                        if *increment == 0 {
+                            return Err("increment must not be zero");
+                        }
+                        ControlValue::RelativeDiscrete(DiscreteIncrement::new(*increment))
+                    }
+                };
+                let control_element = VirtualControlElement::new(id, *character);
+                WebSocketControlMessage::Virtual(VirtualSourceValue::new(
+                    control_element,
+                    control_value,
+                ))
+            }
+            WebSocketControlMessageData::Raw { address, value } => {
+                WebSocketControlMessage::Raw(OscMessage {
+                    addr: address.clone(),
+                    args: vec![OscType::Float(*value as f32)],
+                })
+            }
+        };
+        Ok(msg)
+    }
+}
+
+/// Feedback which is sent to WebSocket clients acting as virtual controller device.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WebSocketFeedbackData {
+    Virtual {
+        id: String,
+        character: VirtualControlElementCharacter,
+        value: Value,
+    },
+    Raw {
+        address: String,
+        value: Value,
+    },
+}
+
+impl WebSocketFeedbackData {
+    fn from_domain(value: WebSocketFeedbackValue) -> Self {
+        match value {
+            WebSocketFeedbackValue::Virtual(v) => {
+                let control_element = v.control_element();
+                WebSocketFeedbackData::Virtual {
+                    id: control_element.id().to_string(),
+                    character: control_element.character(),
+                    value: convert_feedback_value(v.feedback_value()),
+                }
+            }
+            WebSocketFeedbackValue::Raw(msg) => WebSocketFeedbackData::Raw {
+                value: msg.args.first().map(convert_osc_arg).unwrap_or_default(),
+                address: msg.addr,
+            },
+        }
+    }
+}
+
+fn convert_feedback_value(value: &FeedbackValue) -> Value {
+    match value {
+        FeedbackValue::Off => Value::Null,
+        FeedbackValue::Numeric(v) => v.value.to_unit_value().get().into(),
+        FeedbackValue::Textual(v) => v.text.to_string().into(),
+        FeedbackValue::Complex(v) => serde_json::to_value(&v.value).unwrap_or_default(),
+    }
+}
+
+fn convert_osc_arg(arg: &OscType) -> Value {
+    match arg {
+        OscType::Float(v) => (*v as f64).into(),
+        OscType::Double(v) => (*v).into(),
+        OscType::Int(v) => (*v).into(),
+        OscType::Long(v) => (*v).into(),
+        OscType::Bool(v) => (*v).into(),
+        OscType::String(v) => v.clone().into(),
+        _ => Value::Null,
+    }
+}
+
+pub fn get_web_socket_feedback_event(
+    session_id: &str,
+    value: WebSocketFeedbackValue,
+) -> Event<WebSocketFeedbackData> {
+    Event::post(
+        format!("/realearn/session/{session_id}/web-socket-feedback"),
+        WebSocketFeedbackData::from_domain(value),
+    )
+}
+
+fn find_mapping_key_and_name(
+    session: &UnitModel,
+    id: QualifiedMappingId,
+) -> Option<(String, String)> {
+    let mapping = session.find_mapping_by_id(id.compartment, id.id)?;
+    let mapping = mapping.try_borrow().ok()?;
+    Some((mapping.key().to_string(), mapping.effective_name()))
+}
+
 pub fn get_session_updated_event(
     session_id: &str,
     session_data: Option<SessionResponseData>,
@@ -334,6 +1211,14 @@ impl<T> Event<T> {
             body,
         }
     }
+
+    pub fn post(path: String, body: T) -> Event<T> {
+        Event {
+            r#type: EventType::Post,
+            path,
+            body,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
@@ -341,6 +1226,8 @@ impl<T> Event<T> {
 enum EventType {
     Put,
     Patch,
+    /// Appends the body to a collection resource, e.g. a log entry.
+    Post,
 }
 
 fn get_controller(session: &UnitModel) -> Option<CompartmentPresetData> {
@@ -371,3 +1258,209 @@ fn get_controller_preset_data_internal(
     );
     Ok(CompartmentPresetData::from_model(&preset))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(mut doc: Value, operations: Value) -> Result<Value, DataError> {
+        let operations = serde_json::from_value(operations).unwrap();
+        apply_json_patch(&mut doc, operations)?;
+        Ok(doc)
+    }
+
+    fn parse_control_message(json: &str) -> Result<WebSocketControlMessage, &'static str> {
+        serde_json::from_str::<WebSocketControlMessageData>(json)
+            .unwrap()
+            .to_domain()
+    }
+
+    #[test]
+    fn virtual_control_element_positions_are_one_based() {
+        // Given
+        // When
+        let msg = parse_control_message(r#"{ "type": "virtual", "id": 5, "value": 1 }"#);
+        // Then
+        let Ok(WebSocketControlMessage::Virtual(value)) = msg else {
+            panic!("unexpected message");
+        };
+        assert_eq!(
+            value.control_element().id(),
+            VirtualControlElementId::Indexed(4)
+        );
+        assert!(parse_control_message(r#"{ "type": "virtual", "id": 0, "value": 1 }"#).is_err());
+        assert!(parse_control_message(r#"{ "type": "virtual", "id": "0", "value": 1 }"#).is_err());
+    }
+
+    #[test]
+    fn web_socket_control_session_must_be_unambiguous() {
+        // Given
+        let topics = |list: &str| {
+            WebSocketRequest {
+                topics: list.to_string(),
+            }
+            .parse_topics()
+        };
+        // When
+        // Then
+        assert_eq!(
+            get_web_socket_control_session_id(&topics(
+                "/realearn/session/a/feedback,/realearn/session/a/web-socket-feedback"
+            )),
+            Some("a".to_string())
+        );
+        assert_eq!(
+            get_web_socket_control_session_id(&topics("/realearn/session/a/feedback")),
+            None
+        );
+        assert_eq!(
+            get_web_socket_control_session_id(&topics(
+                "/realearn/session/a/web-socket-feedback,/realearn/session/b/web-socket-feedback"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn json_patch_add() {
+        // Given
+        let doc = json!({ "name": "a", "tags": ["x", "z"] });
+        // When
+        let result = patch(
+            doc,
+            json!([
+                { "op": "add", "path": "/group", "value": "g" },
+                { "op": "add", "path": "/tags/1", "value": "y" },
+                { "op": "add", "path": "/tags/-", "value": "end" },
+                { "op": "add", "path": "/a~1b", "value": 1 },
+            ]),
+        );
+        // Then
+        assert_eq!(
+            result.ok(),
+            Some(json!({
+                "name": "a",
+                "group": "g",
+                "tags": ["x", "y", "z", "end"],
+                "a/b": 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn json_patch_add_to_root_replaces_document() {
+        // Given
+        let doc = json!({ "name": "a" });
+        // When
+        let result = patch(
+            doc,
+            json!([{ "op": "add", "path": "", "value": { "name": "b" } }]),
+        );
+        // Then
+        assert_eq!(result.ok(), Some(json!({ "name": "b" })));
+    }
+
+    #[test]
+    fn json_patch_replace() {
+        // Given
+        let doc = json!({ "name": "a", "tags": ["x", "y"] });
+        // When
+        let result = patch(
+            doc,
+            json!([
+                { "op": "replace", "path": "/name", "value": "b" },
+                { "op": "replace", "path": "/tags/0", "value": "w" },
+            ]),
+        );
+        // Then
+        assert_eq!(
+            result.ok(),
+            Some(json!({ "name": "b", "tags": ["w", "y"] }))
+        );
+    }
+
+    #[test]
+    fn json_patch_replace_root_replaces_document() {
+        // Given
+        let doc = json!({ "name": "a" });
+        // When
+        let result = patch(
+            doc,
+            json!([{ "op": "replace", "path": "", "value": { "name": "b" } }]),
+        );
+        // Then
+        assert_eq!(result.ok(), Some(json!({ "name": "b" })));
+    }
+
+    #[test]
+    fn json_patch_remove() {
+        // Given
+        let doc = json!({ "name": "a", "group": "g", "tags": ["x", "y"] });
+        // When
+        let result = patch(
+            doc,
+            json!([
+                { "op": "remove", "path": "/group" },
+                { "op": "remove", "path": "/tags/0" },
+            ]),
+        );
+        // Then
+        assert_eq!(result.ok(), Some(json!({ "name": "a", "tags": ["y"] })));
+    }
+
+    #[test]
+    fn json_patch_test() {
+        // Given
+        let doc = json!({ "name": "a" });
+        // When
+        let matching = patch(
+            doc.clone(),
+            json!([
+                { "op": "test", "path": "/name", "value": "a" },
+                { "op": "test", "path": "", "value": { "name": "a" } },
+            ]),
+        );
+        let failing = patch(
+            doc,
+            json!([{ "op": "test", "path": "/name", "value": "b" }]),
+        );
+        // Then
+        assert_eq!(matching.ok(), Some(json!({ "name": "a" })));
+        assert!(matches!(failing, Err(DataError::JsonPatchTestFailed)));
+    }
+
+    #[test]
+    fn json_patch_errors() {
+        // Given
+        let doc = json!({ "name": "a", "tags": ["x"] });
+        let invalid = |operation: Value| {
+            matches!(
+                patch(doc.clone(), json!([operation])),
+                Err(DataError::InvalidJsonPatch)
+            )
+        };
+        // When
+        // Then
+        assert!(invalid(
+            json!({ "op": "add", "path": "/missing/name", "value": 1 })
+        ));
+        assert!(invalid(
+            json!({ "op": "add", "path": "/tags/2", "value": "y" })
+        ));
+        assert!(invalid(
+            json!({ "op": "add", "path": "/name/x", "value": 1 })
+        ));
+        assert!(invalid(json!({ "op": "add", "path": "name", "value": 1 })));
+        assert!(invalid(json!({ "op": "remove", "path": "" })));
+        assert!(invalid(json!({ "op": "remove", "path": "/missing" })));
+        assert!(invalid(json!({ "op": "remove", "path": "/tags/1" })));
+        assert!(invalid(json!({ "op": "remove", "path": "/tags/-" })));
+        assert!(invalid(
+            json!({ "op": "replace", "path": "/missing", "value": 1 })
+        ));
+        assert!(invalid(
+            json!({ "op": "replace", "path": "/tags/1", "value": 1 })
+        ));
+    }
+}
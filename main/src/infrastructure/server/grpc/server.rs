@@ -1,12 +1,17 @@
+use crate::infrastructure::server::auth::SharedServerAccessControl;
+use crate::infrastructure::server::layers::{AuthLayer, MainThreadLayer};
 use crate::infrastructure::server::services::Services;
 use std::net::SocketAddr;
 
 pub async fn start_grpc_server(
     address: SocketAddr,
     services: Services,
+    access_control: SharedServerAccessControl,
 ) -> Result<(), tonic::transport::Error> {
     tonic::transport::Server::builder()
-        .layer(crate::infrastructure::server::layers::MainThreadLayer)
+        // Reject unauthorized requests before bothering the main thread
+        .layer(AuthLayer::grpc(access_control))
+        .layer(MainThreadLayer)
         .add_service(services.helgobox_service)
         .serve(address)
         .await
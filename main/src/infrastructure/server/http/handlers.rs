@@ -1,22 +1,31 @@
-use crate::infrastructure::data::CompartmentPresetData;
+use crate::domain::RealearnControlSurfaceMainTask;
+use crate::infrastructure::api::compartment_diff::{CompartmentDiff, CompartmentMerge};
+use crate::infrastructure::data::{CompartmentPresetData, PresetValidationIssue};
+use crate::infrastructure::plugin::BackboneShell;
 use crate::infrastructure::server::data::{
-    get_controller_preset_data, get_controller_routing_by_session_id, patch_controller,
-    ControllerRouting, DataError, DataErrorCategory, PatchRequest, SessionResponseData, Topics,
+    diff_compartments, get_compartment, get_controller_preset_data,
+    get_controller_routing_by_session_id, get_instances, get_parameters, get_presets,
+    get_web_socket_control_session_id, load_preset, merge_compartments, patch_controller,
+    patch_mapping, put_compartment, validate_preset, CompartmentDiffRequest,
+    CompartmentMergeRequest, ControllerRouting, DataError, DataErrorCategory, InstanceData,
+    JsonPatchOperation, LoadPresetRequest, ParameterData, PatchRequest, PresetData,
+    SessionResponseData, Topics, ValidatePresetQuery, WebSocketControlMessageData,
 };
 use crate::infrastructure::server::http::{send_initial_events, ServerClients, WebSocketClient};
 use crate::infrastructure::server::MetricsReporter;
 use axum::body::Body;
 use axum::extract::ws::{Message, WebSocket};
-use axum::extract::Path;
+use axum::extract::{Path, Query};
 use axum::http::{Response, StatusCode};
 use axum::response::Html;
 use axum::Json;
-use base::Global;
+use base::{Global, NamedChannelSender};
+use helgobox_api::persistence;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
-type SimpleResponse = (StatusCode, &'static str);
+type SimpleResponse = (StatusCode, String);
 
 pub async fn welcome_handler() -> Html<&'static str> {
     Html(include_str!("../http/welcome_page.html"))
@@ -57,6 +66,86 @@ pub async fn patch_controller_handler(
     Ok(StatusCode::OK)
 }
 
+/// Needs to be executed in the main thread!
+pub async fn instances_handler() -> Json<Vec<InstanceData>> {
+    Json(get_instances())
+}
+
+/// Needs to be executed in the main thread!
+pub async fn get_compartment_handler(
+    Path((session_id, compartment)): Path<(String, String)>,
+) -> Result<Json<persistence::Compartment>, SimpleResponse> {
+    let compartment = get_compartment(&session_id, &compartment).map_err(translate_data_error)?;
+    Ok(Json(compartment))
+}
+
+/// Needs to be executed in the main thread!
+pub async fn put_compartment_handler(
+    Path((session_id, compartment)): Path<(String, String)>,
+    Json(api_compartment): Json<persistence::Compartment>,
+) -> Result<StatusCode, SimpleResponse> {
+    put_compartment(&session_id, &compartment, api_compartment).map_err(translate_data_error)?;
+    Ok(StatusCode::OK)
+}
+
+/// Needs to be executed in the main thread!
+pub async fn patch_mapping_handler(
+    Path((session_id, compartment, mapping_id)): Path<(String, String, String)>,
+    Json(operations): Json<Vec<JsonPatchOperation>>,
+) -> Result<Json<serde_json::Value>, SimpleResponse> {
+    let mapping = patch_mapping(&session_id, &compartment, &mapping_id, operations)
+        .map_err(translate_data_error)?;
+    Ok(Json(mapping))
+}
+
+/// Needs to be executed in the main thread!
+pub async fn put_preset_handler(
+    Path((session_id, compartment)): Path<(String, String)>,
+    Json(req): Json<LoadPresetRequest>,
+) -> Result<StatusCode, SimpleResponse> {
+    load_preset(&session_id, &compartment, req).map_err(translate_data_error)?;
+    Ok(StatusCode::OK)
+}
+
+/// Needs to be executed in the main thread!
+pub async fn parameters_handler(
+    Path((session_id, compartment)): Path<(String, String)>,
+) -> Result<Json<Vec<ParameterData>>, SimpleResponse> {
+    let parameters = get_parameters(&session_id, &compartment).map_err(translate_data_error)?;
+    Ok(Json(parameters))
+}
+
+/// Needs to be executed in the main thread!
+pub async fn presets_handler(
+    Path(compartment): Path<String>,
+) -> Result<Json<Vec<PresetData>>, SimpleResponse> {
+    let presets = get_presets(&compartment).map_err(translate_data_error)?;
+    Ok(Json(presets))
+}
+
+/// Needs to be executed in the main thread!
+pub async fn preset_validation_handler(
+    Path(compartment): Path<String>,
+    Query(query): Query<ValidatePresetQuery>,
+) -> Result<Json<Vec<PresetValidationIssue>>, SimpleResponse> {
+    let issues = validate_preset(&compartment, query).map_err(translate_data_error)?;
+    Ok(Json(issues))
+}
+
+pub async fn compartment_diff_handler(
+    Json(req): Json<CompartmentDiffRequest>,
+) -> Result<Json<CompartmentDiff>, SimpleResponse> {
+    let diff = diff_compartments(req).map_err(translate_data_error)?;
+    Ok(Json(diff))
+}
+
+pub async fn compartment_merge_handler(
+    Json(req): Json<CompartmentMergeRequest>,
+) -> Result<Json<CompartmentMerge>, SimpleResponse> {
+    let merge = merge_compartments(req).map_err(translate_data_error)?;
+    Ok(Json(merge))
+}
+
 pub fn create_cert_response(cert: String, cert_file_name: &str) -> Response<Body> {
     Response::builder()
         .status(StatusCode::OK)
@@ -80,8 +169,16 @@ pub async fn create_metrics_response(metrics_reporter: MetricsReporter) -> Respo
         .unwrap()
 }
 
-pub async fn handle_websocket_upgrade(socket: WebSocket, topics: Topics, clients: ServerClients) {
+/// If `control_allowed` is `true`, the client may act as virtual controller device by sending
+/// control messages.
+pub async fn handle_websocket_upgrade(
+    socket: WebSocket,
+    topics: Topics,
+    clients: ServerClients,
+    control_allowed: bool,
+) {
     use futures::{FutureExt, StreamExt};
+    let control_session_id = get_web_socket_control_session_id(&topics);
     let (ws_sender_sink, mut ws_receiver_stream) = socket.split();
     let (client_sender, client_receiver) = mpsc::unbounded_channel();
     let client_receiver_stream = UnboundedReceiverStream::new(client_receiver);
@@ -113,26 +210,74 @@ pub async fn handle_websocket_upgrade(socket: WebSocket, topics: Topics, clients
         .unwrap();
     // Keep receiving websocket receiver stream messages
     while let Some(result) = ws_receiver_stream.next().await {
-        // We will need this as soon as we are interested in what the client says
-        let _msg = match result {
+        let msg = match result {
             Ok(msg) => msg,
             Err(e) => {
                 eprintln!("websocket error: {e}");
                 break;
             }
         };
+        if let Message::Text(text) = msg {
+            if !control_allowed {
+                tracing::debug!(msg = "Ignored WebSocket control message of unauthorized client");
+                continue;
+            }
+            let Some(session_id) = &control_session_id else {
+                tracing::debug!(
+                    msg = "Ignored WebSocket control message because client didn't subscribe to the WebSocket feedback of exactly one session"
+                );
+                continue;
+            };
+            process_web_socket_control_message(session_id, &text);
+        }
     }
     // Stream closed up, so remove from the client list
     clients.write().unwrap().remove(&client_id);
 }
 
+/// Forwards the control message of a client acting as virtual controller device to the unit with
+/// the given key (only processed if its control input is "WebSocket").
+fn process_web_socket_control_message(session_id: &str, text: &str) {
+    let msg = serde_json::from_str::<WebSocketControlMessageData>(text)
+        .map_err(|_| "invalid JSON")
+        .and_then(|data| data.to_domain());
+    let msg = match msg {
+        Ok(msg) => msg,
+        Err(e) => {
+            tracing::debug!(msg = "Ignored invalid WebSocket control message", %e, text);
+            return;
+        }
+    };
+    let session_id = session_id.to_string();
+    Global::task_support()
+        .do_later_in_main_thread_asap(move || {
+            let backbone_shell = BackboneShell::get();
+            let Some(session) = backbone_shell.find_unit_model_by_key(&session_id) else {
+                tracing::debug!(
+                    msg = "Ignored WebSocket control message for unknown session",
+                    %session_id
+                );
+                return;
+            };
+            let unit_id = session.borrow().unit_id();
+            backbone_shell
+                .control_surface_main_task_sender()
+                .0
+                .send_complaining(
+                    RealearnControlSurfaceMainTask::ProcessWebSocketControlMessage(unit_id, msg),
+                );
+        })
+        .unwrap();
+}
+
 fn translate_data_error(e: DataError) -> SimpleResponse {
     use DataErrorCategory::*;
     let status_code = match e.category() {
         NotFound => StatusCode::NOT_FOUND,
         BadRequest => StatusCode::BAD_REQUEST,
         MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+        Conflict => StatusCode::CONFLICT,
         InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
     };
-    (status_code, e.description())
+    (status_code, e.message())
 }
@@ -1,11 +1,17 @@
 //! Contains functions for sending data to WebSocket clients.
 use crate::application::{SharedUnitModel, UnitModel};
 use crate::base::when;
-use crate::domain::ProjectionFeedbackValue;
+use crate::domain::{
+    CompartmentKind, ProjectionFeedbackValue, SourceFeedbackEvent, TargetControlEvent,
+    TargetValueChangedEvent, WebSocketFeedbackValue,
+};
 use crate::infrastructure::plugin::BackboneShell;
 use crate::infrastructure::server::data::{
-    get_active_controller_updated_event, get_controller_routing_updated_event,
-    get_projection_feedback_event, get_session_updated_event, send_initial_feedback,
+    get_active_controller_updated_event, get_control_log_event,
+    get_controller_routing_updated_event, get_feedback_log_event, get_initial_log_event,
+    get_mapping_on_states_updated_event, get_parameters_updated_event,
+    get_projection_feedback_event, get_session_updated_event, get_target_value_changed_event,
+    get_target_values_updated_event, get_web_socket_feedback_event, send_initial_feedback,
     SessionResponseData, Topic,
 };
 use crate::infrastructure::server::http::client::WebSocketClient;
@@ -33,6 +39,48 @@ fn send_initial_events_for_topic(
             send_initial_feedback(session_id);
             Ok(())
         }
+        WebSocketFeedback { session_id } => send_initial_web_socket_feedback(client, session_id),
+        TargetValues {
+            session_id,
+            compartment,
+        } => {
+            let session = BackboneShell::get().find_unit_model_by_key(session_id);
+            let session = session.as_ref().map(|s| s.borrow());
+            client.send(get_target_values_updated_event(
+                session_id,
+                *compartment,
+                session.as_deref(),
+            ))
+        }
+        Parameters {
+            session_id,
+            compartment,
+        } => {
+            let session = BackboneShell::get().find_unit_model_by_key(session_id);
+            let session = session.as_ref().map(|s| s.borrow());
+            client.send(get_parameters_updated_event(
+                session_id,
+                *compartment,
+                session.as_deref(),
+            ))
+        }
+        MappingOnStates {
+            session_id,
+            compartment,
+        } => {
+            let session = BackboneShell::get().find_unit_model_by_key(session_id);
+            let session = session.as_ref().map(|s| s.borrow());
+            client.send(get_mapping_on_states_updated_event(
+                session_id,
+                *compartment,
+                session.as_deref(),
+            ))
+        }
+        Log { session_id } => {
+            let session = BackboneShell::get().find_unit_model_by_key(session_id);
+            let session = session.as_ref().map(|s| s.borrow());
+            client.send(get_initial_log_event(session_id, session.as_deref()))
+        }
     }
 }
 
@@ -115,6 +163,132 @@ pub fn send_projection_feedback_to_subscribed_clients(
     )
 }
 
+/// Sends the last feedback values to the given client only.
+///
+/// In contrast to the projection feedback, we can't just resend all feedback because that would
+/// also hit the unit's other feedback outputs.
+fn send_initial_web_socket_feedback(
+    client: &WebSocketClient,
+    session_id: &str,
+) -> Result<(), &'static str> {
+    let session = BackboneShell::get()
+        .find_unit_model_by_key(session_id)
+        .ok_or("session not found")?;
+    let session = session.borrow();
+    let unit = session.unit().borrow();
+    for value in unit.web_socket_feedback_snapshot() {
+        client.send(get_web_socket_feedback_event(session_id, value.clone()))?;
+    }
+    Ok(())
+}
+
+pub fn send_web_socket_feedback_to_subscribed_clients(
+    session_id: &str,
+    value: WebSocketFeedbackValue,
+) -> Result<(), &'static str> {
+    send_to_clients_subscribed_to(
+        &Topic::WebSocketFeedback {
+            session_id: session_id.to_string(),
+        },
+        || Some(get_web_socket_feedback_event(session_id, value)),
+    )
+}
+
+pub fn send_target_value_to_subscribed_clients(
+    session: &UnitModel,
+    event: &TargetValueChangedEvent,
+) -> Result<(), &'static str> {
+    send_to_clients_subscribed_to(
+        &Topic::TargetValues {
+            session_id: session.unit_key().to_string(),
+            compartment: event.compartment,
+        },
+        || get_target_value_changed_event(session, event),
+    )
+}
+
+pub fn send_updated_parameters(session: &UnitModel) -> Result<(), &'static str> {
+    for compartment in CompartmentKind::enum_iter() {
+        send_to_clients_subscribed_to(
+            &Topic::Parameters {
+                session_id: session.unit_key().to_string(),
+                compartment,
+            },
+            || {
+                Some(get_parameters_updated_event(
+                    session.unit_key(),
+                    compartment,
+                    Some(session),
+                ))
+            },
+        )?;
+    }
+    Ok(())
+}
+
+pub fn send_updated_mapping_on_states(session: &UnitModel) -> Result<(), &'static str> {
+    for compartment in CompartmentKind::enum_iter() {
+        send_to_clients_subscribed_to(
+            &Topic::MappingOnStates {
+                session_id: session.unit_key().to_string(),
+                compartment,
+            },
+            || {
+                Some(get_mapping_on_states_updated_event(
+                    session.unit_key(),
+                    compartment,
+                    Some(session),
+                ))
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Sends a snapshot of all target values, e.g. after the mapping list changed.
+pub fn send_updated_target_values(session: &UnitModel) -> Result<(), &'static str> {
+    for compartment in CompartmentKind::enum_iter() {
+        send_to_clients_subscribed_to(
+            &Topic::TargetValues {
+                session_id: session.unit_key().to_string(),
+                compartment,
+            },
+            || {
+                Some(get_target_values_updated_event(
+                    session.unit_key(),
+                    compartment,
+                    Some(session),
+                ))
+            },
+        )?;
+    }
+    Ok(())
+}
+
+pub fn send_control_log_entry_to_subscribed_clients(
+    session: &UnitModel,
+    event: &TargetControlEvent,
+) -> Result<(), &'static str> {
+    send_to_clients_subscribed_to(
+        &Topic::Log {
+            session_id: session.unit_key().to_string(),
+        },
+        || get_control_log_event(session, event),
+    )
+}
+
+pub fn send_feedback_log_entry_to_subscribed_clients(
+    session: &UnitModel,
+    event: &SourceFeedbackEvent,
+) -> Result<(), &'static str> {
+    send_to_clients_subscribed_to(
+        &Topic::Log {
+            session_id: session.unit_key().to_string(),
+        },
+        || get_feedback_log_event(session, event),
+    )
+}
+
 fn send_to_clients_subscribed_to<T: Serialize>(
     topic: &Topic,
     create_message: impl FnOnce() -> Option<T>,
@@ -189,7 +363,9 @@ pub fn keep_informing_clients_about_session_events(shared_session: &SharedUnitMo
     )
     .with(Rc::downgrade(shared_session))
     .do_async(|session, _| {
-        let _ = send_updated_controller_routing(&session.borrow());
+        let session = session.borrow();
+        let _ = send_updated_controller_routing(&session);
+        let _ = send_updated_mapping_on_states(&session);
     });
     when(
         BackboneShell::get()
@@ -212,5 +388,8 @@ pub fn keep_informing_clients_about_session_events(shared_session: &SharedUnitMo
         let session = session.borrow();
         let _ = send_updated_active_controller(&session);
         let _ = send_updated_controller_routing(&session);
+        let _ = send_updated_mapping_on_states(&session);
+        let _ = send_updated_parameters(&session);
+        let _ = send_updated_target_values(&session);
     });
 }
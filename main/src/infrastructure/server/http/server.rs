@@ -2,37 +2,42 @@ use crate::infrastructure::plugin::BackboneShell;
 use crate::infrastructure::server::http::ServerClients;
 use axum::extract::{Query, WebSocketUpgrade};
 use axum::handler::Handler;
-use axum::http::header::CONTENT_TYPE;
-use axum::http::Method;
-use axum::routing::{get, patch};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{HeaderMap, Method, Uri};
+use axum::routing::{get, patch, post, put};
 use axum::Router;
 use std::io;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use tower_http::cors::{Any, CorsLayer};
 
+use crate::infrastructure::server::auth::{AccessScope, SharedServerAccessControl};
 use crate::infrastructure::server::data::WebSocketRequest;
 pub use crate::infrastructure::server::http::handlers::*;
-use crate::infrastructure::server::layers::MainThreadLayer;
+use crate::infrastructure::server::layers::{
+    extract_access_token_or_query_param, AuthLayer, MainThreadLayer,
+};
 use crate::infrastructure::server::MetricsReporter;
 use base::Global;
 
 #[allow(clippy::too_many_arguments)]
 pub async fn start_http_server(
+    bind_address: IpAddr,
     http_port: u16,
     https_port: u16,
     clients: ServerClients,
     (key, cert): (String, String),
     metrics_reporter: MetricsReporter,
+    access_control: SharedServerAccessControl,
 ) -> Result<(), io::Error> {
     // Router
-    let router = create_router(cert.clone(), clients, metrics_reporter);
+    let router = create_router(cert.clone(), clients, metrics_reporter, access_control);
     // Binding
     let http_future = {
-        let addr = SocketAddr::from(([0, 0, 0, 0], http_port));
+        let addr = SocketAddr::new(bind_address, http_port);
         axum_server::bind(addr).serve(router.clone().into_make_service())
     };
     let https_future = {
-        let addr = SocketAddr::from(([0, 0, 0, 0], https_port));
+        let addr = SocketAddr::new(bind_address, https_port);
         let rustls_config =
             axum_server::tls_rustls::RustlsConfig::from_pem(cert.into(), key.into())
                 .await
@@ -56,7 +61,10 @@ fn create_router(
     cert: String,
     clients: ServerClients,
     metrics_reporter: MetricsReporter,
+    access_control: SharedServerAccessControl,
 ) -> Router {
+    let auth = |scope| AuthLayer::new(access_control.clone(), scope);
+    let ws_access_control = access_control.clone();
     let router = Router::new()
         .route("/", get(welcome_handler))
         .route(
@@ -65,23 +73,73 @@ fn create_router(
         )
         .route(
             "/realearn/session/:id",
-            get(session_handler.layer(MainThreadLayer)),
+            get(session_handler.layer(MainThreadLayer)).route_layer(auth(AccessScope::Projection)),
         )
         .route(
             "/realearn/session/:id/controller",
-            get(session_controller_handler.layer(MainThreadLayer)),
+            get(session_controller_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::Projection)),
         )
         .route(
             "/realearn/session/:id/controller-routing",
-            get(controller_routing_handler.layer(MainThreadLayer)),
+            get(controller_routing_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::Projection)),
+        )
+        .route(
+            "/realearn/instances",
+            get(instances_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::Projection)),
+        )
+        .route(
+            "/realearn/session/:id/compartment/:compartment",
+            get(get_compartment_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::Projection))
+                .merge(
+                    put(put_compartment_handler.layer(MainThreadLayer))
+                        .route_layer(auth(AccessScope::ControllerEditing)),
+                ),
+        )
+        .route(
+            "/realearn/session/:id/compartment/:compartment/mapping/:mapping_id",
+            patch(patch_mapping_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::ControllerEditing)),
+        )
+        .route(
+            "/realearn/session/:id/compartment/:compartment/preset",
+            put(put_preset_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::ControllerEditing)),
+        )
+        .route(
+            "/realearn/session/:id/compartment/:compartment/parameters",
+            get(parameters_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::Projection)),
+        )
+        .route(
+            "/realearn/presets/:compartment",
+            get(presets_handler.layer(MainThreadLayer)).route_layer(auth(AccessScope::Projection)),
+        )
+        .route(
+            "/realearn/presets/:compartment/validation",
+            get(preset_validation_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::Projection)),
+        )
+        .route(
+            "/realearn/compartment/diff",
+            post(compartment_diff_handler).route_layer(auth(AccessScope::Projection)),
+        )
+        .route(
+            "/realearn/compartment/merge",
+            post(compartment_merge_handler).route_layer(auth(AccessScope::Projection)),
         )
         .route(
             "/realearn/controller/:id",
-            patch(patch_controller_handler.layer(MainThreadLayer)),
+            patch(patch_controller_handler.layer(MainThreadLayer))
+                .route_layer(auth(AccessScope::ControllerEditing)),
         )
         .route(
             "/realearn/metrics",
-            get(move || async move { create_metrics_response(metrics_reporter).await }),
+            get(move || async move { create_metrics_response(metrics_reporter).await })
+                .route_layer(auth(AccessScope::Metrics)),
         );
     router
         .layer(
@@ -94,15 +152,29 @@ fn create_router(
                     Method::DELETE,
                     Method::PATCH,
                 ])
-                .allow_headers(vec![CONTENT_TYPE]),
+                .allow_headers(vec![CONTENT_TYPE, AUTHORIZATION]),
         )
         .route(
             "/ws",
             get(
-                |ws: WebSocketUpgrade, Query(req): Query<WebSocketRequest>| async move {
+                |ws: WebSocketUpgrade,
+                 Query(req): Query<WebSocketRequest>,
+                 headers: HeaderMap,
+                 uri: Uri| async move {
                     let topics = req.parse_topics();
-                    ws.on_upgrade(|socket| handle_websocket_upgrade(socket, topics, clients))
+                    // Connecting just needs the projection scope. Acting as virtual controller
+                    // device needs more.
+                    let token = extract_access_token_or_query_param(&headers, &uri);
+                    let control_allowed = ws_access_control
+                        .authorize(token.as_deref(), AccessScope::Control)
+                        .is_ok();
+                    ws.on_upgrade(move |socket| {
+                        handle_websocket_upgrade(socket, topics, clients, control_allowed)
+                    })
                 },
-            ),
+            )
+            // Browsers can't send headers when opening a WebSocket, so the token is usually
+            // passed as query parameter.
+            .route_layer(auth(AccessScope::Projection).accepting_query_token()),
         )
 }
@@ -0,0 +1,354 @@
+use crate::infrastructure::server::auth::{AccessScope, AuthRejection, SharedServerAccessControl};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use axum::http::{HeaderMap, HeaderValue, Request, Response, StatusCode, Uri};
+use base::metrics_util::record_occurrence;
+use futures::future::BoxFuture;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// Name of the query parameter which can be used as alternative to the `Authorization` header.
+///
+/// Necessary for WebSocket connections because browsers don't allow setting headers for them. Only
+/// accepted by layers which explicitly allow it (see [`AuthLayer::accepting_query_token`]) because
+/// URLs tend to end up in logs and browser histories.
+const ACCESS_TOKEN_QUERY_PARAM: &str = "access_token";
+
+/// Defines which scope a request needs.
+#[derive(Copy, Clone, Debug)]
+pub enum ScopeRequirement {
+    /// Each request needs the given scope.
+    Fixed(AccessScope),
+    /// The needed scope depends on the called gRPC method. Rejections are reported as gRPC status.
+    GrpcMethod,
+}
+
+/// A Tower layer that rejects requests which don't carry an access token with the required scope.
+#[derive(Clone)]
+pub struct AuthLayer {
+    access_control: SharedServerAccessControl,
+    requirement: ScopeRequirement,
+    accept_query_token: bool,
+}
+
+impl AuthLayer {
+    pub fn new(access_control: SharedServerAccessControl, scope: AccessScope) -> Self {
+        Self {
+            access_control,
+            requirement: ScopeRequirement::Fixed(scope),
+            accept_query_token: false,
+        }
+    }
+
+    pub fn grpc(access_control: SharedServerAccessControl) -> Self {
+        Self {
+            access_control,
+            requirement: ScopeRequirement::GrpcMethod,
+            accept_query_token: false,
+        }
+    }
+
+    /// Makes the layer accept the access token as `access_token` query parameter in addition to
+    /// the `Authorization` header.
+    pub fn accepting_query_token(mut self) -> Self {
+        self.accept_query_token = true;
+        self
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            access_control: self.access_control.clone(),
+            requirement: self.requirement,
+            accept_query_token: self.accept_query_token,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AuthService<S> {
+    inner: S,
+    access_control: SharedServerAccessControl,
+    requirement: ScopeRequirement,
+    accept_query_token: bool,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for AuthService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response<ResBody>, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let scope = match self.requirement {
+            ScopeRequirement::Fixed(scope) => scope,
+            ScopeRequirement::GrpcMethod => match grpc_method_access(request.uri().path()) {
+                GrpcMethodAccess::Public => return Box::pin(self.inner.call(request)),
+                GrpcMethodAccess::Restricted(scope) => scope,
+                GrpcMethodAccess::Unknown => {
+                    record_occurrence("server.auth.rejected.unknown_method");
+                    tracing::debug!(
+                        msg = "Rejected call of unknown gRPC method",
+                        path = request.uri().path(),
+                    );
+                    let response = create_grpc_unknown_method_response();
+                    return Box::pin(async move { Ok(response) });
+                }
+            },
+        };
+        let token = if self.accept_query_token {
+            extract_access_token_or_query_param(request.headers(), request.uri())
+        } else {
+            extract_access_token(request.headers())
+        };
+        match self.access_control.authorize(token.as_deref(), scope) {
+            Ok(_) => Box::pin(self.inner.call(request)),
+            Err(rejection) => {
+                record_occurrence(rejection.metric_id());
+                tracing::debug!(
+                    msg = "Rejected server request",
+                    path = request.uri().path(),
+                    %scope,
+                    %rejection,
+                );
+                let response = match self.requirement {
+                    ScopeRequirement::Fixed(_) => create_http_rejection_response(rejection),
+                    ScopeRequirement::GrpcMethod => create_grpc_rejection_response(rejection),
+                };
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
+
+/// Extracts the access token from the `Authorization` header.
+pub fn extract_access_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.trim().to_string())
+}
+
+/// Extracts the access token from the `Authorization` header or, if not available, from the
+/// `access_token` query parameter.
+pub fn extract_access_token_or_query_param(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    if let Some(token) = extract_access_token(headers) {
+        return Some(token);
+    }
+    let query = uri.query()?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == ACCESS_TOKEN_QUERY_PARAM)
+        .map(|(_, value)| value.into_owned())
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum GrpcMethodAccess {
+    /// Method can be called without access token.
+    Public,
+    /// Method needs an access token with the given scope.
+    Restricted(AccessScope),
+    /// Method is not known to the access control, so it's not allowed to be called at all.
+    Unknown,
+}
+
+/// Returns which access is required for calling the gRPC method with the given path.
+///
+/// Path has the form `/{package}.{service}/{method}`. Each method must be listed explicitly.
+/// Methods which are not listed are denied, so that newly added methods don't accidentally become
+/// reachable with an unrelated scope.
+fn grpc_method_access(path: &str) -> GrpcMethodAccess {
+    use GrpcMethodAccess::*;
+    let method = path.rsplit('/').next().unwrap_or_default();
+    match method {
+        // Needed by clients to find out whom they are talking to before authenticating
+        "GetHostInfo" | "ProveAuthenticity" => Public,
+        "GetUnits"
+        | "GetMappings"
+        | "GetCompartmentData"
+        | "GetContinuousMappingUpdates"
+        | "GetOccasionalUnitUpdates"
+        | "GetOccasionalGlobalUpdates"
+        | "GetOccasionalInstanceUpdates"
+        | "GetCustomInstanceData"
+        | "GetProjectDir" => Restricted(AccessScope::Projection),
+        "AddLicense"
+        | "SaveMapping"
+        | "DeleteMapping"
+        | "SaveGroup"
+        | "DeleteGroup"
+        | "SetMappingEnabled"
+        | "HitMappingTarget"
+        | "SaveCustomCompartmentData"
+        | "SaveController"
+        | "DeleteController"
+        | "SetInstanceSettings"
+        | "SetCustomInstanceData"
+        | "TriggerInstance" => Restricted(AccessScope::ControllerEditing),
+        // The app settings contain the access tokens and the authentication switch
+        "GetAppSettings" | "SetAppSettings" => Restricted(AccessScope::Admin),
+        "GetArrangementInfo"
+        | "GetClipDetail"
+        | "GetContinuousMatrixUpdates"
+        | "GetContinuousColumnUpdates"
+        | "GetContinuousSlotUpdates"
+        | "GetOccasionalMatrixUpdates"
+        | "GetOccasionalTrackUpdates"
+        | "GetOccasionalColumnUpdates"
+        | "GetOccasionalRowUpdates"
+        | "GetOccasionalSlotUpdates"
+        | "GetOccasionalClipUpdates"
+        | "GetOccasionalPlaytimeEngineUpdates"
+        | "TriggerMatrix"
+        | "TriggerGlobal"
+        | "TriggerColumn"
+        | "TriggerRow"
+        | "TriggerSlot"
+        | "TriggerClip"
+        | "TriggerSequence"
+        | "TriggerTrack"
+        | "SetMatrixSettings"
+        | "SetMatrixTempo"
+        | "SetMatrixTimeSignature"
+        | "SetMatrixVolume"
+        | "SetMatrixPan"
+        | "SetMatrixPlayRate"
+        | "SetColumnSettings"
+        | "SetColumnTrack"
+        | "SetRowData"
+        | "SetClipName"
+        | "SetClipData"
+        | "SetSequenceInfo"
+        | "SetTrackName"
+        | "SetTrackColor"
+        | "SetTrackInput"
+        | "SetTrackInputMonitoring"
+        | "SetTrackVolume"
+        | "SetTrackPan"
+        | "SetPlaytimeEngineSettings"
+        | "DragSlot"
+        | "DragClip"
+        | "DragRow"
+        | "DragColumn"
+        | "InsertColumns"
+        | "ImportFiles"
+        | "OpenTrackFx" => Restricted(AccessScope::PlaytimeControl),
+        _ => Unknown,
+    }
+}
+
+fn create_http_rejection_response<B: Default>(rejection: AuthRejection) -> Response<B> {
+    let status = match rejection {
+        AuthRejection::MissingToken | AuthRejection::UnknownToken => StatusCode::UNAUTHORIZED,
+        AuthRejection::InsufficientScope => StatusCode::FORBIDDEN,
+    };
+    Response::builder()
+        .status(status)
+        .header(WWW_AUTHENTICATE, "Bearer")
+        .body(B::default())
+        .unwrap()
+}
+
+/// gRPC clients expect errors to be reported as status in the headers of a successful HTTP
+/// response ("trailers-only" response).
+fn create_grpc_rejection_response<B: Default>(rejection: AuthRejection) -> Response<B> {
+    let code = match rejection {
+        AuthRejection::MissingToken | AuthRejection::UnknownToken => tonic::Code::Unauthenticated,
+        AuthRejection::InsufficientScope => tonic::Code::PermissionDenied,
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/grpc")
+        .header("grpc-status", HeaderValue::from(code as i32))
+        .header("grpc-message", rejection.to_string())
+        .body(B::default())
+        .unwrap()
+}
+
+fn create_grpc_unknown_method_response<B: Default>() -> Response<B> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/grpc")
+        .header(
+            "grpc-status",
+            HeaderValue::from(tonic::Code::Unimplemented as i32),
+        )
+        .header("grpc-message", "unknown method")
+        .body(B::default())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grpc_method_access_is_explicit() {
+        // Given
+        let path = |method: &str| format!("/generated.HelgoboxService/{method}");
+        // When
+        // Then
+        assert_eq!(
+            grpc_method_access(&path("GetHostInfo")),
+            GrpcMethodAccess::Public
+        );
+        assert_eq!(
+            grpc_method_access(&path("GetAppSettings")),
+            GrpcMethodAccess::Restricted(AccessScope::Admin)
+        );
+        assert_eq!(
+            grpc_method_access(&path("SetAppSettings")),
+            GrpcMethodAccess::Restricted(AccessScope::Admin)
+        );
+        assert_eq!(
+            grpc_method_access(&path("GetOccasionalGlobalUpdates")),
+            GrpcMethodAccess::Restricted(AccessScope::Projection)
+        );
+        assert_eq!(
+            grpc_method_access(&path("TriggerInstance")),
+            GrpcMethodAccess::Restricted(AccessScope::ControllerEditing)
+        );
+        assert_eq!(
+            grpc_method_access(&path("TriggerSlot")),
+            GrpcMethodAccess::Restricted(AccessScope::PlaytimeControl)
+        );
+        assert_eq!(
+            grpc_method_access(&path("DoSomethingNew")),
+            GrpcMethodAccess::Unknown
+        );
+    }
+
+    #[test]
+    fn query_token_only_if_requested() {
+        // Given
+        let uri: Uri = "/ws?topics=foo&access_token=abc".parse().unwrap();
+        let mut headers_with_token = HeaderMap::new();
+        headers_with_token.insert(AUTHORIZATION, HeaderValue::from_static("Bearer xyz"));
+        // When
+        // Then
+        assert_eq!(extract_access_token(&HeaderMap::new()), None);
+        assert_eq!(
+            extract_access_token(&headers_with_token),
+            Some("xyz".to_string())
+        );
+        assert_eq!(
+            extract_access_token_or_query_param(&HeaderMap::new(), &uri),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            extract_access_token_or_query_param(&headers_with_token, &uri),
+            Some("xyz".to_string())
+        );
+    }
+}
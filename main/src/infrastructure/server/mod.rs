@@ -12,9 +12,13 @@ use std::fs;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
 
 use url::Url;
 
+use crate::infrastructure::server::auth::{
+    ServerAccessControl, ServerAccessSettings, SharedServerAccessControl,
+};
 use crate::infrastructure::server::grpc::start_grpc_server;
 use crate::infrastructure::server::http::start_http_server;
 use crate::infrastructure::server::http::ServerClients;
@@ -24,6 +28,7 @@ use tokio::runtime::Runtime;
 
 pub type SharedRealearnServer = Rc<RefCell<RealearnServer>>;
 
+pub mod auth;
 pub mod data;
 mod grpc;
 pub mod http;
@@ -35,11 +40,13 @@ pub struct RealearnServer {
     http_port: u16,
     https_port: u16,
     grpc_port: u16,
+    bind_address: IpAddr,
     state: ServerState,
     certs_dir_path: PathBuf,
     changed_subject: LocalSubject<'static, (), ()>,
     local_ip: Option<IpAddr>,
     metrics_reporter: MetricsReporter,
+    access_control: SharedServerAccessControl,
 }
 
 /// Responsible for reporting application metrics.
@@ -116,43 +123,56 @@ impl RealearnServer {
         http_port: u16,
         https_port: u16,
         grpc_port: u16,
+        bind_address: IpAddr,
         certs_dir_path: PathBuf,
         metrics_reporter: MetricsReporter,
+        access_settings: ServerAccessSettings,
     ) -> RealearnServer {
         RealearnServer {
             http_port,
             https_port,
             grpc_port,
+            bind_address,
             state: ServerState::Stopped,
             certs_dir_path,
             changed_subject: Default::default(),
             local_ip: get_local_ip(),
             metrics_reporter,
+            access_control: Arc::new(ServerAccessControl::new(access_settings)),
         }
     }
 
+    /// Applies changed access tokens. Takes effect immediately, also if the server is running.
+    pub fn update_access_settings(&self, access_settings: ServerAccessSettings) {
+        self.access_control.update_settings(access_settings);
+    }
+
     /// Idempotent
     pub fn start(&mut self, runtime: &Runtime, services: Services) -> Result<(), String> {
         if self.state.is_starting_or_running() {
             return Ok(());
         }
-        check_port(PortType::Http, self.http_port)?;
-        check_port(PortType::Https, self.https_port)?;
-        check_port(PortType::Grpc, self.grpc_port)?;
+        check_port(PortType::Http, self.bind_address, self.http_port)?;
+        check_port(PortType::Https, self.bind_address, self.https_port)?;
+        check_port(PortType::Grpc, self.bind_address, self.grpc_port)?;
         let clients: ServerClients = Default::default();
         let clients_clone = clients.clone();
         let http_port = self.http_port;
         let https_port = self.https_port;
         let grpc_port = self.grpc_port;
+        let bind_address = self.bind_address;
         let key_and_cert = self.key_and_cert();
         let metrics_reporter = self.metrics_reporter.clone();
+        let access_control = self.access_control.clone();
         let server_join_handle = runtime.spawn(start_servers(
+            bind_address,
             http_port,
             https_port,
             grpc_port,
             clients_clone,
             key_and_cert,
             metrics_reporter,
+            access_control,
             services,
         ));
         let runtime_data = ServerRuntimeData {
@@ -164,7 +184,14 @@ impl RealearnServer {
         Ok(())
     }
 
+    /// The IP address for which the server certificate is issued.
+    ///
+    /// If the server is bound to a specific address, that's the one. Otherwise, it's the IP
+    /// address under which this computer is reachable in the local network.
     fn effective_ip(&self) -> IpAddr {
+        if !self.bind_address.is_unspecified() {
+            return self.bind_address;
+        }
         self.local_ip().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
     }
 
@@ -262,6 +289,10 @@ impl RealearnServer {
         self.grpc_port
     }
 
+    pub fn bind_address(&self) -> IpAddr {
+        self.bind_address
+    }
+
     pub fn log_debug_info(&self, session_id: &str) {
         let msg = format!(
             "\n\
@@ -288,23 +319,30 @@ impl RealearnServer {
 // TODO-medium-playtime Use https://github.com/faern/triggered instead of channel-based shutdown
 #[allow(clippy::too_many_arguments)]
 async fn start_servers(
+    bind_address: IpAddr,
     http_port: u16,
     https_port: u16,
     grpc_port: u16,
     clients: ServerClients,
     (key, cert): (String, String),
     metrics_reporter: MetricsReporter,
+    access_control: SharedServerAccessControl,
     services: Services,
 ) {
     let http_server_future = start_http_server(
+        bind_address,
         http_port,
         https_port,
         clients,
         (key, cert),
         metrics_reporter,
+        access_control.clone(),
+    );
+    let grpc_server_future = start_grpc_server(
+        SocketAddr::new(bind_address, grpc_port),
+        services,
+        access_control,
     );
-    let grpc_server_future =
-        start_grpc_server(SocketAddr::from(([0, 0, 0, 0], grpc_port)), services);
     let (http_result, grpc_result) =
         futures::future::join(http_server_future, grpc_server_future).await;
     http_result.expect("HTTP server error");
@@ -405,8 +443,8 @@ impl PortType {
     }
 }
 
-fn check_port(port_type: PortType, port: u16) -> Result<(), String> {
-    if !local_port_available(port) {
+fn check_port(port_type: PortType, bind_address: IpAddr, port: u16) -> Result<(), String> {
+    if !local_port_available(bind_address, port) {
         let msg = format!(
             r#"{port_type_display_label} port {port} is not available. Possible causes and solutions:
 
@@ -437,6 +475,6 @@ Set another {port_type_display_label} port in "realearn.ini", for example:
     Ok(())
 }
 
-fn local_port_available(port: u16) -> bool {
-    std::net::TcpListener::bind(("0.0.0.0", port)).is_ok()
+fn local_port_available(bind_address: IpAddr, port: u16) -> bool {
+    std::net::TcpListener::bind((bind_address, port)).is_ok()
 }
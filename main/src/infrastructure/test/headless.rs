@@ -0,0 +1,374 @@
+//! Runs scenarios against controller and main presets without REAPER.
+//!
+//! A scenario is a JSON file which refers to a controller and/or main preset (JSON or Lua, path
+//! relative to the scenario file) and describes a sequence of steps. Each step sends a message
+//! or changes a target value or parameter and optionally states which target values and which
+//! MIDI feedback are expected afterwards.
+//!
+//! All scenarios in `scenarios/` are verified by `cargo test`. Presets referred to by scenarios
+//! may live anywhere, e.g. in `resources/controller-presets`.
+//!
+//! Scenarios run in a simulation ([`HeadlessUnit`]), not in the real processors. See its
+//! documentation for what is and isn't covered.
+use crate::application::{CompartmentModel, GroupData};
+use crate::domain::{
+    CompartmentKind, CompartmentParamIndex, FinalSourceFeedbackValue, FsDirLuaModuleFinder,
+    GroupId, HeadlessUnit, LuaModuleContainer, MappingKey, MockTarget, PluginParams, RawParamValue,
+    SafeLua,
+};
+use crate::infrastructure::api::convert::to_data::convert_compartment;
+use crate::infrastructure::data::{parse_lua_frontmatter, CompartmentPresetData};
+use anyhow::{anyhow, bail, ensure, Context};
+use camino::Utf8Path;
+use helgoboss_learn::{AbsoluteValue, UnitValue};
+use helgoboss_midi::{DataEntryByteOrder, RawShortMessage, ShortMessage, ShortMessageFactory, U7};
+use helgobox_api::persistence::CommonPresetMetaData;
+use mlua::LuaSerdeExt;
+use serde::Deserialize;
+use std::fs;
+
+/// Maximum deviation between expected and actual target value.
+const TARGET_VALUE_TOLERANCE: f64 = 0.001;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HeadlessScenario {
+    #[serde(default)]
+    pub controller_preset: Option<String>,
+    #[serde(default)]
+    pub main_preset: Option<String>,
+    /// Mock targets which should behave differently than a continuous target at 0%.
+    #[serde(default)]
+    pub targets: Vec<MockTargetSetup>,
+    /// Feedback expected right after loading the presets.
+    #[serde(default)]
+    pub expect_initial_feedback: Option<Vec<Vec<u8>>>,
+    pub steps: Vec<ScenarioStep>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockTargetSetup {
+    #[serde(flatten)]
+    pub mapping: MappingRef,
+    #[serde(flatten)]
+    pub kind: MockTargetKind,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum MockTargetKind {
+    Continuous {
+        #[serde(default)]
+        value: f64,
+    },
+    Retriggerable {
+        #[serde(default)]
+        value: f64,
+    },
+    Discrete {
+        count: u32,
+        #[serde(default)]
+        index: u32,
+    },
+    Relative {
+        #[serde(default)]
+        value: f64,
+    },
+}
+
+/// Refers to a mapping by key or - if no mapping has that key - by name.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingRef {
+    #[serde(default = "default_compartment")]
+    pub compartment: CompartmentKind,
+    pub mapping: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioStep {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub action: ScenarioAction,
+    /// Normalized target values expected after this step.
+    #[serde(default)]
+    pub expect_targets: Vec<ExpectedTargetValue>,
+    /// MIDI feedback expected as a result of this step. `None` means it's not checked.
+    #[serde(default)]
+    pub expect_feedback: Option<Vec<Vec<u8>>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScenarioAction {
+    /// Incoming short MIDI message as 3 bytes.
+    Midi(Vec<u8>),
+    /// Incoming MIDI system-exclusive message.
+    SysEx(Vec<u8>),
+    /// Target value change not caused by the unit itself.
+    TargetValue {
+        #[serde(flatten)]
+        mapping: MappingRef,
+        value: f64,
+    },
+    /// Compartment parameter change.
+    Parameter {
+        #[serde(default = "default_compartment")]
+        compartment: CompartmentKind,
+        index: u32,
+        value: RawParamValue,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedTargetValue {
+    #[serde(flatten)]
+    pub mapping: MappingRef,
+    pub value: f64,
+}
+
+fn default_compartment() -> CompartmentKind {
+    CompartmentKind::Main
+}
+
+/// Loads the scenario from the given file and runs it.
+///
+/// Returns an error describing the first step that didn't meet its expectations.
+pub fn run_scenario_file(path: &Utf8Path) -> anyhow::Result<()> {
+    let json = fs::read_to_string(path).with_context(|| format!("couldn't read {path}"))?;
+    let scenario: HeadlessScenario =
+        serde_json::from_str(&json).with_context(|| format!("couldn't parse {path}"))?;
+    let base_dir = path.parent().unwrap_or(Utf8Path::new("."));
+    run_scenario(&scenario, base_dir)
+}
+
+/// Runs the given scenario. Preset paths are resolved relative to the given directory.
+pub fn run_scenario(scenario: &HeadlessScenario, base_dir: &Utf8Path) -> anyhow::Result<()> {
+    let presets: Vec<_> = [
+        (CompartmentKind::Controller, &scenario.controller_preset),
+        (CompartmentKind::Main, &scenario.main_preset),
+    ]
+    .into_iter()
+    .filter_map(|(compartment, path)| Some((compartment, base_dir.join(path.as_ref()?))))
+    .map(|(compartment, path)| {
+        let model = load_compartment_preset(&path, compartment)
+            .with_context(|| format!("couldn't load {compartment} preset {path}"))?;
+        Ok((compartment, model))
+    })
+    .collect::<anyhow::Result<_>>()?;
+    ensure!(!presets.is_empty(), "scenario doesn't refer to any preset");
+    let mut unit = create_headless_unit(&presets);
+    let resolve = |mapping: &MappingRef| resolve_mapping_ref(&presets, mapping);
+    for setup in &scenario.targets {
+        unit.set_target(
+            setup.mapping.compartment,
+            &resolve(&setup.mapping)?,
+            setup.kind.create_mock_target(),
+        )?;
+    }
+    let initial_feedback = unit.feedback_all();
+    if let Some(expected_feedback) = &scenario.expect_initial_feedback {
+        check_feedback(expected_feedback, &initial_feedback).context("initial feedback")?;
+    }
+    for (i, step) in scenario.steps.iter().enumerate() {
+        let label = match &step.description {
+            None => format!("step {}", i + 1),
+            Some(d) => format!("step {} ({d})", i + 1),
+        };
+        let feedback = match &step.action {
+            ScenarioAction::Midi(bytes) => unit.send_midi(parse_short_message(bytes)?),
+            ScenarioAction::SysEx(bytes) => unit.send_sys_ex(bytes),
+            ScenarioAction::TargetValue { mapping, value } => unit.change_target_value(
+                mapping.compartment,
+                &resolve(mapping)?,
+                AbsoluteValue::Continuous(UnitValue::new_clamped(*value)),
+            )?,
+            ScenarioAction::Parameter {
+                compartment,
+                index,
+                value,
+            } => {
+                let index = CompartmentParamIndex::try_from(*index)
+                    .map_err(|_| anyhow!("invalid parameter index {index}"))?;
+                unit.set_parameter(*compartment, index, *value)
+            }
+        };
+        for expected in &step.expect_targets {
+            let key = resolve(&expected.mapping)?;
+            let actual = unit
+                .target(expected.mapping.compartment, &key)
+                .with_context(|| format!("{label}: mapping {key} doesn't have a REAPER target"))?
+                .value()
+                .to_unit_value()
+                .get();
+            ensure!(
+                (actual - expected.value).abs() <= TARGET_VALUE_TOLERANCE,
+                "{label}: expected target value of mapping \"{}\" to be {} but was {actual}",
+                expected.mapping.mapping,
+                expected.value
+            );
+        }
+        if let Some(expected_feedback) = &step.expect_feedback {
+            check_feedback(expected_feedback, &feedback).context(label)?;
+        }
+    }
+    Ok(())
+}
+
+/// Loads a controller or main preset in JSON or Lua format.
+pub fn load_compartment_preset(
+    path: &Utf8Path,
+    compartment: CompartmentKind,
+) -> anyhow::Result<CompartmentModel> {
+    let content = fs::read_to_string(path)?;
+    if path.extension() == Some("lua") {
+        let lua = SafeLua::new()?;
+        let module_root = path.parent().unwrap_or(Utf8Path::new(".")).to_path_buf();
+        let module_container = LuaModuleContainer::new(Ok(FsDirLuaModuleFinder::new(module_root)));
+        let value =
+            module_container.execute_as_module(lua.as_ref(), None, path.to_string(), &content)?;
+        let compartment_content: helgobox_api::persistence::Compartment =
+            lua.as_ref().from_value(value)?;
+        let compartment_data = convert_compartment(compartment, compartment_content)?;
+        let version = parse_lua_frontmatter::<CommonPresetMetaData>(&content)
+            .ok()
+            .and_then(|meta_data| meta_data.realearn_version);
+        compartment_data.to_model(version.as_ref(), compartment, None)
+    } else {
+        let data: CompartmentPresetData = serde_json::from_str(&content)?;
+        let meta_data: CommonPresetMetaData = serde_json::from_str(&content)?;
+        data.into_data()
+            .to_model(meta_data.realearn_version.as_ref(), compartment, None)
+    }
+}
+
+fn create_headless_unit(presets: &[(CompartmentKind, CompartmentModel)]) -> HeadlessUnit {
+    let mut params = PluginParams::default();
+    let mut mappings = vec![];
+    for (compartment, model) in presets {
+        params
+            .compartment_params_mut(*compartment)
+            .apply_given_settings(model.parameters.clone());
+        for mapping in &model.mappings {
+            let group_data = find_group_data(model, mapping.group_id());
+            let main_mapping = mapping.create_main_mapping(group_data);
+            mappings.push((mapping.key().clone(), main_mapping));
+        }
+    }
+    HeadlessUnit::new(mappings, params)
+}
+
+fn find_group_data(model: &CompartmentModel, group_id: GroupId) -> GroupData {
+    if group_id.is_default() {
+        return model.default_group.create_data();
+    }
+    let find_group = |id: GroupId| model.groups.iter().find(|g| g.id() == id);
+    find_group(group_id)
+        .map(|g| g.create_effective_data(find_group))
+        .unwrap_or_default()
+}
+
+fn resolve_mapping_ref(
+    presets: &[(CompartmentKind, CompartmentModel)],
+    mapping_ref: &MappingRef,
+) -> anyhow::Result<MappingKey> {
+    let (_, model) = presets
+        .iter()
+        .find(|(c, _)| *c == mapping_ref.compartment)
+        .with_context(|| format!("no preset loaded in {}", mapping_ref.compartment))?;
+    let mapping = model
+        .mappings
+        .iter()
+        .find(|m| m.key().as_ref() == mapping_ref.mapping)
+        .or_else(|| {
+            model
+                .mappings
+                .iter()
+                .find(|m| m.name() == mapping_ref.mapping)
+        })
+        .with_context(|| format!("mapping \"{}\" not found", mapping_ref.mapping))?;
+    Ok(mapping.key().clone())
+}
+
+fn parse_short_message(bytes: &[u8]) -> anyhow::Result<RawShortMessage> {
+    let [status_byte, data_byte_1, data_byte_2] = bytes else {
+        bail!("short MIDI message must consist of exactly 3 bytes but was {bytes:?}");
+    };
+    RawShortMessage::from_bytes((
+        *status_byte,
+        U7::try_from(*data_byte_1).map_err(|_| anyhow!("invalid first data byte"))?,
+        U7::try_from(*data_byte_2).map_err(|_| anyhow!("invalid second data byte"))?,
+    ))
+    .map_err(|_| anyhow!("invalid status byte"))
+}
+
+fn check_feedback(expected: &[Vec<u8>], actual: &[FinalSourceFeedbackValue]) -> anyhow::Result<()> {
+    let actual: Vec<Vec<u8>> = actual.iter().flat_map(feedback_to_midi_bytes).collect();
+    ensure!(
+        actual == expected,
+        "expected MIDI feedback {expected:?} but got {actual:?}"
+    );
+    Ok(())
+}
+
+/// Non-MIDI feedback is ignored.
+fn feedback_to_midi_bytes(value: &FinalSourceFeedbackValue) -> Vec<Vec<u8>> {
+    let FinalSourceFeedbackValue::Midi(value) = value else {
+        return vec![];
+    };
+    if let Some(events) = value.to_raw() {
+        return events.into_iter().map(|e| e.bytes().to_vec()).collect();
+    }
+    value
+        .to_short_messages::<RawShortMessage>(DataEntryByteOrder::MsbFirst)
+        .into_iter()
+        .flatten()
+        .map(|msg| {
+            let (status_byte, data_byte_1, data_byte_2) = msg.to_bytes();
+            vec![status_byte, data_byte_1.get(), data_byte_2.get()]
+        })
+        .collect()
+}
+
+impl MockTargetKind {
+    fn create_mock_target(&self) -> MockTarget {
+        match *self {
+            MockTargetKind::Continuous { value } => {
+                MockTarget::continuous(UnitValue::new_clamped(value))
+            }
+            MockTargetKind::Retriggerable { value } => {
+                MockTarget::retriggerable(UnitValue::new_clamped(value))
+            }
+            MockTargetKind::Discrete { count, index } => MockTarget::discrete(count, index),
+            MockTargetKind::Relative { value } => {
+                MockTarget::relative(UnitValue::new_clamped(value))
+            }
+        }
+    }
+}
+
+#[test]
+fn scenarios() {
+    let scenario_dir =
+        Utf8Path::new(env!("CARGO_MANIFEST_DIR")).join("src/infrastructure/test/scenarios");
+    let mut scenario_files: Vec<_> = scenario_dir
+        .read_dir_utf8()
+        .unwrap()
+        .map(|entry| entry.unwrap().into_path())
+        .filter(|path| path.extension() == Some("json"))
+        .collect();
+    scenario_files.sort();
+    assert!(!scenario_files.is_empty());
+    let failures: Vec<_> = scenario_files
+        .iter()
+        .filter_map(|path| {
+            let error = run_scenario_file(path).err()?;
+            Some(format!("{path}: {error:#}"))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -9,11 +9,15 @@ use helgoboss_midi::test_util::*;
 use helgoboss_midi::{DataEntryByteOrder, ParameterNumberMessage, RawShortMessage, ShortMessage};
 use reaper_high::{Fx, FxParameter, Reaper, Track};
 use reaper_medium::{Db, ReaperPanValue, StuffMidiMessageTarget};
+use rosc::{OscMessage, OscType};
 use std::ffi::CString;
 use std::future::Future;
-use FinalSourceFeedbackValue::Midi;
+use FinalSourceFeedbackValue::{Midi, Osc};
 use MidiSourceValue::{ParameterNumber, Plain};
 
+#[cfg(test)]
+mod headless;
+
 pub fn run_test() {
     Global::future_support().spawn_in_main_thread_from_main_thread(async {
         Test::new().test().await;
@@ -89,6 +93,8 @@ impl Test {
         self.step("Conditional activation - EEL", conditional_activation_eel())
             .await;
         self.step("Virtual", virtual_mapping()).await;
+        self.step("Lifecycle actions - Order", lifecycle_actions_order())
+            .await;
         log("\nTests executed successfully!")
     }
 
@@ -1420,6 +1426,51 @@ async fn conditional_activation_eel() {
     );
 }
 
+async fn lifecycle_actions_order() {
+    // Given
+    let realearn = setup().await;
+    // When
+    load_realearn_preset(&realearn, include_str!("presets/lifecycle-order.json"));
+    moment().await;
+    // Then
+    assert_eq!(
+        realearn.pop_feedback(),
+        vec![
+            Osc(lifecycle_osc_message("/activate", "2")),
+            Midi(Plain(note_on(0, 65, 91)))
+        ],
+        "lifecycle actions should be executed before normal feedback is sent"
+    );
+    // When
+    realearn
+        .parameter_by_index(82)
+        .set_reaper_normalized_value(1.0)
+        .unwrap();
+    moment().await;
+    // Then
+    let feedback = realearn.pop_feedback();
+    assert_eq!(
+        feedback[..2],
+        [
+            Osc(lifecycle_osc_message("/deactivate", "2")),
+            Osc(lifecycle_osc_message("/activate", "1")),
+        ],
+        "deactivation actions should be executed before activation actions and both before \
+        normal feedback is sent"
+    );
+    assert!(
+        feedback[2..].contains(&Midi(Plain(note_on(0, 64, 91)))),
+        "feedback of activated mapping should be sent"
+    );
+}
+
+fn lifecycle_osc_message(address: &str, argument: &str) -> OscMessage {
+    OscMessage {
+        addr: address.to_string(),
+        args: vec![OscType::String(argument.to_string())],
+    }
+}
+
 fn load_realearn_preset(realearn: &RealearnTestInstance, json: &str) {
     let preset_c_string = CString::new(json).expect("couldn't convert preset into c string");
     unsafe {
@@ -0,0 +1,40 @@
+--- realearn_version: 2.16.0
+--- name: Compound sources
+
+local function note(key_number)
+    return {
+        kind = "MidiNoteVelocity",
+        channel = 0,
+        key_number = key_number,
+    }
+end
+
+return {
+    mappings = {
+        {
+            id = "chord",
+            name = "Chord",
+            source = {
+                kind = "Compound",
+                compound_kind = "Combination",
+                members = { note(60), note(64) },
+            },
+            target = {
+                kind = "TrackMuteState",
+            },
+        },
+        {
+            id = "sequence",
+            name = "Sequence",
+            source = {
+                kind = "Compound",
+                compound_kind = "Sequence",
+                members = { note(70), note(71), note(72) },
+                timeout = 60000,
+            },
+            target = {
+                kind = "TrackArmState",
+            },
+        },
+    },
+}
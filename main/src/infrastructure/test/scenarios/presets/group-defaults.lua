@@ -0,0 +1,47 @@
+--- realearn_version: 2.16.0
+--- name: Group defaults
+
+return {
+    groups = {
+        {
+            id = "bank",
+            name = "Bank",
+            defaults = {
+                source_channel = 1,
+                step_size_interval = { 0.1, 0.1 },
+            },
+        },
+        {
+            id = "faders",
+            name = "Faders",
+            parent = "bank",
+        },
+    },
+    mappings = {
+        {
+            id = "volume",
+            name = "Volume",
+            group = "faders",
+            source = {
+                kind = "MidiControlChangeValue",
+                controller_number = 7,
+            },
+            target = {
+                kind = "TrackVolume",
+            },
+        },
+        {
+            id = "pan",
+            name = "Pan",
+            group = "faders",
+            source = {
+                kind = "MidiControlChangeValue",
+                controller_number = 8,
+                character = "Relative1",
+            },
+            target = {
+                kind = "TrackPan",
+            },
+        },
+    },
+}
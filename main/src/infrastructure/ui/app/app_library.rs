@@ -450,6 +450,18 @@ fn process_query_request(
                 Ok(query_result::Value::GetCustomInstanceDataReply(value))
             });
         }
+        GetUnits(req) => {
+            send_query_reply_to_app(instance_id, req_id, async move {
+                let value = handler.get_units(req)?.into_inner();
+                Ok(query_result::Value::GetUnitsReply(value))
+            });
+        }
+        GetMappings(req) => {
+            send_query_reply_to_app(instance_id, req_id, async move {
+                let value = handler.get_mappings(req)?.into_inner();
+                Ok(query_result::Value::GetMappingsReply(value))
+            });
+        }
     }
     Ok(())
 }
@@ -702,6 +714,24 @@ fn process_command(
         SetCustomInstanceData(req) => {
             handler.set_custom_instance_data(req)?;
         }
+        SaveMapping(req) => {
+            handler.save_mapping(req)?;
+        }
+        DeleteMapping(req) => {
+            handler.delete_mapping(req)?;
+        }
+        SaveGroup(req) => {
+            handler.save_group(req)?;
+        }
+        DeleteGroup(req) => {
+            handler.delete_group(req)?;
+        }
+        SetMappingEnabled(req) => {
+            handler.set_mapping_enabled(req)?;
+        }
+        HitMappingTarget(req) => {
+            handler.hit_mapping_target(req)?;
+        }
     }
     Ok(())
 }
@@ -61,6 +61,8 @@ impl GroupPanel {
                                 initiator,
                             );
                         }
+                        // Not editable in the user interface
+                        P::ParentId | P::Defaults => {}
                         P::InActivationCondition(p) => match p {
                             Multiple => {
                                 self.mapping_header_panel.invalidate_controls();
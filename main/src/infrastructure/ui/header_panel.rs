@@ -18,14 +18,15 @@ use crate::application::{
 use crate::base::when;
 use crate::domain::{
     convert_compartment_param_index_range_to_iter, Backbone, CompartmentKind,
-    CompartmentParamIndex, ControlInput, FeedbackOutput, GroupId, MessageCaptureEvent, OscDeviceId,
-    ParamSetting, ReaperTarget, StayActiveWhenProjectInBackground, COMPARTMENT_PARAMETER_COUNT,
+    CompartmentParamIndex, ControlInput, DeviceControlInput, DeviceFeedbackOutput, GroupId,
+    MessageCaptureEvent, OscDeviceId, ParamSetting, ReaperTarget,
+    StayActiveWhenProjectInBackground, COMPARTMENT_PARAMETER_COUNT,
 };
-use crate::domain::{MidiControlInput, MidiDestination};
+use crate::domain::{MidiControlInput, MidiRoutingTable};
 use crate::infrastructure::data::{
-    CommonCompartmentPresetManager, CommonPresetInfo, CompartmentModelData,
-    FileBasedMainPresetManager, InstanceOrUnitData, MappingModelData, OscDevice, PresetFileType,
-    PresetOrigin, UnitData,
+    list_control_input_recordings, load_control_input_recording, CommonCompartmentPresetManager,
+    CommonPresetInfo, CompartmentModelData, FileBasedMainPresetManager, InstanceOrUnitData,
+    MappingModelData, OscDevice, PresetFileType, PresetOrigin, UnitData,
 };
 use crate::infrastructure::plugin::{
     update_auto_units_async, warn_about_failed_server_start, BackboneShell,
@@ -34,16 +35,16 @@ use crate::infrastructure::plugin::{
 use crate::infrastructure::ui::bindings::root;
 
 use crate::base::notification::{notify_processing_result, notify_user_about_anyhow_error};
+use crate::infrastructure::api::compartment_diff::CompartmentUpdate;
 use crate::infrastructure::api::convert::from_data::ConversionStyle;
 use crate::infrastructure::ui::color_panel::{ColorPanel, ColorPanelDesc};
 use crate::infrastructure::ui::dialog_util::add_group_via_dialog;
 use crate::infrastructure::ui::instance_panel::InstancePanel;
 use crate::infrastructure::ui::menus::{
-    build_compartment_preset_menu_entries, get_midi_input_device_list_label,
-    get_midi_output_device_list_label, get_osc_device_list_label,
+    additional_control_input_menu_entries, additional_feedback_output_menu_entries,
+    build_compartment_preset_menu_entries, get_control_input_label, get_feedback_output_label,
     menu_containing_compartment_presets, ControlInputMenuAction, FeedbackOutputMenuAction,
-    OscDeviceManagementAction, CONTROL_INPUT_KEYBOARD_LABEL, CONTROL_INPUT_MIDI_FX_INPUT_LABEL,
-    FEEDBACK_OUTPUT_MIDI_FX_OUTPUT, FEEDBACK_OUTPUT_NONE_LABEL,
+    OscDeviceManagementAction,
 };
 use crate::infrastructure::ui::util::{
     close_child_panel_if_open, colors, open_child_panel, open_child_panel_dyn, open_in_browser,
@@ -60,12 +61,14 @@ use crate::infrastructure::ui::{
 };
 use crate::infrastructure::ui::{dialog_util, CompanionAppPresenter};
 use anyhow::{bail, Context};
+use camino::{Utf8Path, Utf8PathBuf};
 use helgobox_api::persistence::{Envelope, VirtualControlElementCharacter};
 use itertools::Itertools;
 use reaper_medium::Hbrush;
 use semver::Version;
 use std::cell::{Cell, RefCell};
 use std::error::Error;
+use std::fs;
 use std::net::Ipv4Addr;
 use std::ops::{DerefMut, RangeInclusive};
 use strum::IntoEnumIterator;
@@ -634,6 +637,62 @@ impl HeaderPanel {
                         ),
                     ],
                 ),
+                menu(
+                    "Additional control inputs",
+                    additional_control_input_menu_entries(
+                        session.additional_io.get_ref(),
+                        MainMenuAction::ToggleAdditionalControlInput,
+                    ),
+                ),
+                menu(
+                    "Additional feedback outputs",
+                    additional_feedback_output_menu_entries(
+                        session.additional_io.get_ref(),
+                        MainMenuAction::ToggleAdditionalFeedbackOutput,
+                    ),
+                ),
+                menu(
+                    "MIDI routing",
+                    vec![
+                        disabled_item(format!(
+                            "{} rule(s)",
+                            session.midi_routing.get_ref().rules.len()
+                        )),
+                        item("Copy as JSON", MainMenuAction::CopyMidiRoutingAsJson),
+                        item(
+                            "Paste from JSON (replace)",
+                            MainMenuAction::PasteMidiRoutingFromJson,
+                        ),
+                        item("Clear", MainMenuAction::ClearMidiRouting),
+                    ],
+                ),
+                menu(
+                    "Control input recording",
+                    vec![
+                        if session.control_input_recording_is_active() {
+                            item(
+                                "Stop recording and save",
+                                MainMenuAction::StopRecordingControlInput,
+                            )
+                        } else {
+                            item(
+                                "Start recording",
+                                MainMenuAction::StartRecordingControlInput,
+                            )
+                        },
+                        menu(
+                            "Replay",
+                            generate_control_input_replay_menu_entries(
+                                &list_control_input_recordings(),
+                            ),
+                        ),
+                        item("Stop replaying", MainMenuAction::StopReplayingControlInput),
+                        item(
+                            "Open recording folder",
+                            MainMenuAction::OpenControlInputRecordingFolder,
+                        ),
+                    ],
+                ),
                 item("Send feedback now", MainMenuAction::SendFeedbackNow),
                 separator(),
                 // Instance scope
@@ -693,6 +752,22 @@ impl HeaderPanel {
                             "Reload all presets from disk",
                             MainMenuAction::ReloadAllPresets,
                         ),
+                        item_with_opts(
+                            "Validate active preset",
+                            ItemOpts {
+                                enabled: session.active_preset_id(compartment).is_some(),
+                                checked: false,
+                            },
+                            MainMenuAction::ValidateActivePreset,
+                        ),
+                        item_with_opts(
+                            "Merge preset update from clipboard",
+                            ItemOpts {
+                                enabled: session.active_preset_id(compartment).is_some(),
+                                checked: false,
+                            },
+                            MainMenuAction::MergePresetUpdateFromClipboard,
+                        ),
                     ],
                 ),
                 menu(
@@ -809,6 +884,11 @@ impl HeaderPanel {
                 self.create_compartment_preset_workspace(true)
             }
             MainMenuAction::ReloadAllPresets => self.reload_all_presets(),
+            MainMenuAction::ValidateActivePreset => self.validate_active_preset(),
+            MainMenuAction::MergePresetUpdateFromClipboard => {
+                let result = self.merge_preset_update_from_clipboard();
+                self.notify_user_on_anyhow_error(result);
+            }
             MainMenuAction::OpenPotBrowser => {
                 self.show_pot_browser();
             }
@@ -821,6 +901,47 @@ impl HeaderPanel {
             MainMenuAction::OpenPresetFolder => self.open_preset_folder(),
             MainMenuAction::SendFeedbackNow => self.session().borrow().send_all_feedback(),
             MainMenuAction::LogDebugInfo => self.log_debug_info(),
+            MainMenuAction::ToggleAdditionalControlInput(input) => {
+                self.session()
+                    .borrow_mut()
+                    .additional_io
+                    .mut_in_place(|io| io.toggle_control_input(input));
+            }
+            MainMenuAction::ToggleAdditionalFeedbackOutput(output) => {
+                self.session()
+                    .borrow_mut()
+                    .additional_io
+                    .mut_in_place(|io| io.toggle_feedback_output(output));
+            }
+            MainMenuAction::CopyMidiRoutingAsJson => {
+                let result = self.copy_midi_routing_as_json();
+                self.notify_user_on_anyhow_error(result);
+            }
+            MainMenuAction::PasteMidiRoutingFromJson => {
+                let result = self.paste_midi_routing_from_json();
+                self.notify_user_on_anyhow_error(result);
+            }
+            MainMenuAction::ClearMidiRouting => {
+                self.session()
+                    .borrow_mut()
+                    .midi_routing
+                    .set(Default::default());
+            }
+            MainMenuAction::StartRecordingControlInput => {
+                self.session().borrow_mut().start_recording_control_input()
+            }
+            MainMenuAction::StopRecordingControlInput => {
+                self.session().borrow_mut().stop_recording_control_input()
+            }
+            MainMenuAction::ReplayControlInput(path, speed) => {
+                self.replay_control_input(&path, speed)
+            }
+            MainMenuAction::StopReplayingControlInput => {
+                self.session().borrow().stop_replaying_control_input()
+            }
+            MainMenuAction::OpenControlInputRecordingFolder => {
+                self.open_control_input_recording_folder()
+            }
             MainMenuAction::EditPresetLinkFxId(scope, fx_id) => {
                 with_scoped_preset_link_mutator(scope, &self.session, |m| {
                     edit_preset_link_fx_id(m, fx_id);
@@ -1396,7 +1517,7 @@ impl HeaderPanel {
                 true,
                 input == MidiControlInput::FxInput || reaper_supports_global_midi_filter(),
             ),
-            ControlInput::Osc(_) => (false, false),
+            ControlInput::Osc(_) | ControlInput::WebSocket => (false, false),
             ControlInput::Keyboard => (true, true),
         };
         for c in controls {
@@ -1598,36 +1719,14 @@ impl HeaderPanel {
     }
 
     fn invalidate_control_input_button(&self) {
-        let text = match self.session().borrow().control_input() {
-            ControlInput::Midi(midi_control_input) => match midi_control_input {
-                MidiControlInput::FxInput => CONTROL_INPUT_MIDI_FX_INPUT_LABEL.to_string(),
-                MidiControlInput::Device(dev_id) => {
-                    let dev = Reaper::get().midi_input_device_by_id(dev_id);
-                    get_midi_input_device_list_label(dev)
-                }
-            },
-            ControlInput::Osc(osc_device_id) => get_osc_dev_list_label(&osc_device_id, false),
-            ControlInput::Keyboard => CONTROL_INPUT_KEYBOARD_LABEL.to_string(),
-        };
+        let text = get_control_input_label(self.session().borrow().control_input());
         self.view
             .require_control(root::ID_CONTROL_INPUT_BUTTON)
             .set_text(text);
     }
 
     fn invalidate_feedback_output_button(&self) {
-        let text = match self.session().borrow().feedback_output() {
-            None => FEEDBACK_OUTPUT_NONE_LABEL.to_string(),
-            Some(FeedbackOutput::Midi(midi_dest)) => match midi_dest {
-                MidiDestination::FxOutput => FEEDBACK_OUTPUT_MIDI_FX_OUTPUT.to_string(),
-                MidiDestination::Device(dev_id) => {
-                    let dev = Reaper::get().midi_output_device_by_id(dev_id);
-                    get_midi_output_device_list_label(dev)
-                }
-            },
-            Some(FeedbackOutput::Osc(osc_device_id)) => {
-                get_osc_dev_list_label(&osc_device_id, true)
-            }
-        };
+        let text = get_feedback_output_label(self.session().borrow().feedback_output());
         self.view
             .require_control(root::ID_FEEDBACK_OUTPUT_BUTTON)
             .set_text(text);
@@ -2204,6 +2303,80 @@ impl HeaderPanel {
             .load_presets_from_disk();
     }
 
+    fn validate_active_preset(&self) {
+        let compartment = self.active_compartment();
+        let session = self.session();
+        let session = session.borrow();
+        let Some(preset_id) = session.active_preset_id(compartment) else {
+            return;
+        };
+        // Main presets are validated against the controller compartment they are currently used with
+        let controller = match compartment {
+            CompartmentKind::Controller => None,
+            CompartmentKind::Main => {
+                Some(session.extract_compartment_model(CompartmentKind::Controller))
+            }
+        };
+        let result = BackboneShell::get()
+            .compartment_preset_manager(compartment)
+            .borrow()
+            .validate_preset(preset_id, controller.as_ref());
+        let text = match result {
+            Ok(issues) if issues.is_empty() => {
+                format!("Preset \"{preset_id}\" looks good. No issues found.")
+            }
+            Ok(issues) => format!(
+                "Preset \"{preset_id}\" has the following issues:\n\n{}",
+                issues.iter().join("\n\n")
+            ),
+            Err(e) => format!("Couldn't validate preset \"{preset_id}\":\n\n{e:#}"),
+        };
+        self.view.require_window().alert("ReaLearn", text);
+    }
+
+    /// Merges the preset update in the clipboard into the active preset and reloads it.
+    ///
+    /// The clipboard must contain an object with the properties `base` (the version on which the
+    /// active preset is based) and `theirs` (the updated version).
+    fn merge_preset_update_from_clipboard(&self) -> anyhow::Result<()> {
+        let compartment = self.active_compartment();
+        let session = self.session();
+        let preset_id = session
+            .borrow()
+            .active_preset_id(compartment)
+            .context("no active preset")?
+            .to_string();
+        if session.borrow().compartment_or_preset_is_dirty(compartment)
+            && !self
+                .view
+                .require_window()
+                .confirm("ReaLearn", COMPARTMENT_CHANGES_WARNING_TEXT)
+        {
+            return Ok(());
+        }
+        let text = get_text_from_clipboard().context("Couldn't read from clipboard.")?;
+        let update: CompartmentUpdate = serde_json::from_str(&text).context(
+            "Clipboard doesn't contain a valid preset update (object with properties \"base\" and \"theirs\").",
+        )?;
+        let conflicts = BackboneShell::get()
+            .compartment_preset_manager(compartment)
+            .borrow_mut()
+            .merge_into_preset(&preset_id, &update.base, &update.theirs)?;
+        session
+            .borrow_mut()
+            .activate_preset(compartment, Some(preset_id.clone()));
+        let text = if conflicts.is_empty() {
+            format!("Merged update into preset \"{preset_id}\" without conflicts.")
+        } else {
+            format!(
+                "Merged update into preset \"{preset_id}\". The following fields have been changed in both versions, so the preset kept its own value:\n\n{}",
+                conflicts.iter().join("\n")
+            )
+        };
+        self.view.require_window().alert("ReaLearn", text);
+        Ok(())
+    }
+
     pub fn show_pot_browser(&self) {
         #[cfg(not(feature = "egui"))]
         {
@@ -2265,6 +2438,45 @@ impl HeaderPanel {
         self.notify_user_on_error(result);
     }
 
+    fn copy_midi_routing_as_json(&self) -> anyhow::Result<()> {
+        let session = self.session();
+        let session = session.borrow();
+        let json = serde_json::to_string_pretty(session.midi_routing.get_ref())?;
+        copy_text_to_clipboard(json);
+        Ok(())
+    }
+
+    fn paste_midi_routing_from_json(&self) -> anyhow::Result<()> {
+        let text = get_text_from_clipboard().context("Couldn't read from clipboard.")?;
+        let midi_routing: MidiRoutingTable = serde_json::from_str(&text)
+            .context("Clipboard doesn't contain a valid MIDI routing table.")?;
+        midi_routing.validate()?;
+        self.session().borrow_mut().midi_routing.set(midi_routing);
+        Ok(())
+    }
+
+    fn open_control_input_recording_folder(&self) {
+        let path = BackboneShell::realearn_control_input_recording_dir_path();
+        let _ = fs::create_dir_all(&path);
+        let result = open_in_file_manager(path.as_std_path()).map_err(|e| e.into());
+        self.notify_user_on_error(result);
+    }
+
+    fn replay_control_input(&self, path: &Utf8Path, speed: f64) {
+        let recording = match load_control_input_recording(path) {
+            Ok(r) => r,
+            Err(e) => {
+                self.view
+                    .require_window()
+                    .alert("ReaLearn", format!("{e:#}"));
+                return;
+            }
+        };
+        self.session()
+            .borrow()
+            .replay_control_input(recording, speed);
+    }
+
     fn make_mappings_project_independent_if_desired(&self) {
         let session = self.session();
         let compartment = self.active_compartment();
@@ -3002,6 +3214,8 @@ enum MainMenuAction {
     RemovePresetLink(PresetLinkScope, FxId),
     LinkToPreset(PresetLinkScope, FxId, String),
     ReloadAllPresets,
+    ValidateActivePreset,
+    MergePresetUpdateFromClipboard,
     OpenPotBrowser,
     ShowApp,
     CloseApp,
@@ -3009,6 +3223,16 @@ enum MainMenuAction {
     EditCompartmentParameter(CompartmentKind, RangeInclusive<CompartmentParamIndex>),
     SendFeedbackNow,
     LogDebugInfo,
+    ToggleAdditionalControlInput(DeviceControlInput),
+    ToggleAdditionalFeedbackOutput(DeviceFeedbackOutput),
+    CopyMidiRoutingAsJson,
+    PasteMidiRoutingFromJson,
+    ClearMidiRouting,
+    StartRecordingControlInput,
+    StopRecordingControlInput,
+    ReplayControlInput(Utf8PathBuf, f64),
+    StopReplayingControlInput,
+    OpenControlInputRecordingFolder,
     CreateCompartmentPresetWorkspace,
     CreateCompartmentPresetWorkspaceIncludingFactoryPresets,
 }
@@ -3035,6 +3259,35 @@ enum PresetLinkScope {
     Instance,
 }
 
+/// Replay speed factors offered in the menu.
+const CONTROL_INPUT_REPLAY_SPEEDS: [f64; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+fn generate_control_input_replay_menu_entries(
+    recordings: &[Utf8PathBuf],
+) -> Vec<swell_ui::menu_tree::Entry<MainMenuAction>> {
+    use swell_ui::menu_tree::*;
+    if recordings.is_empty() {
+        return vec![disabled_item("<No recordings>")];
+    }
+    recordings
+        .iter()
+        .map(|path| {
+            menu(
+                path.file_stem().unwrap_or_default(),
+                CONTROL_INPUT_REPLAY_SPEEDS
+                    .into_iter()
+                    .map(|speed| {
+                        item(
+                            format!("At {}% speed", speed * 100.0),
+                            MainMenuAction::ReplayControlInput(path.clone(), speed),
+                        )
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
 fn generate_fx_to_preset_links_menu_entries(
     last_focused_fx_id: Option<&FxId>,
     main_preset_manager: &FileBasedMainPresetManager,
@@ -3119,16 +3372,6 @@ fn with_scoped_preset_link_mutator(
     }
 }
 
-fn get_osc_dev_list_label(osc_device_id: &OscDeviceId, is_output: bool) -> String {
-    let dev_manager = BackboneShell::get().osc_device_manager();
-    let dev_manager = dev_manager.borrow();
-    if let Some(dev) = dev_manager.find_device_by_id(osc_device_id) {
-        get_osc_device_list_label(dev, is_output)
-    } else {
-        format!("OSC: <Not present> ({osc_device_id})")
-    }
-}
-
 const PRESET_RELATED_MENU_LABEL: &str = "Preset-related";
 
 fn build_show_color_panel_desc() -> ColorPanelDesc {
@@ -328,6 +328,21 @@ pub fn deserialize_api_object_from_lua(
     SafeLua::from_value(value)
 }
 
+pub fn serialize_mapping_template_to_lua(
+    template: &persistence::MappingTemplate,
+) -> anyhow::Result<String> {
+    Ok(lua_serializer::to_string(template)?)
+}
+
+pub fn deserialize_mapping_template_from_lua(
+    text: &str,
+    active_compartment: CompartmentKind,
+) -> anyhow::Result<persistence::MappingTemplate> {
+    let lua = SafeLua::new()?;
+    let value = execute_lua_import_script(&lua, text, active_compartment, true)?;
+    SafeLua::from_value(value)
+}
+
 fn verify_no_recursive_tables(value: &Value) -> Result<(), LuaError> {
     verify_no_recursive_tables_internal(value, &mut Default::default(), &mut Default::default())
 }
@@ -3,6 +3,7 @@ use std::cell::OnceCell;
 use tracing::debug;
 
 use crate::application::{Affected, SessionProp, SharedMapping, UnitModel, WeakUnitModel};
+use crate::base::notification;
 use crate::domain::{
     CompartmentKind, MappingId, MappingMatchedEvent, SourceFeedbackEvent, TargetControlEvent,
     TargetValueChangedEvent,
@@ -108,6 +109,15 @@ impl IndependentPanelManager {
     ///
     /// If the window is already open, it will be closed and reopened.
     pub fn edit_mapping(&mut self, mapping: &SharedMapping) {
+        if let Some(template_id) = mapping.borrow().template_id() {
+            // Template instances are materialized again when loading, so edits would be lost
+            notification::alert(format!(
+                "This mapping was created from the mapping template \"{template_id}\" and can't \
+                be edited directly. Use \"Advanced → Copy mapping template as Lua\" in the \
+                context menu of the mapping row, change the template and paste it again."
+            ));
+            return;
+        }
         let existing_panel = self
             .mapping_panels
             .iter()
@@ -28,12 +28,13 @@ use helgoboss_learn::{
     TakeoverMode, Target, UnitValue, ValueSequence, VirtualColor, DEFAULT_OSC_ARG_VALUE_RANGE,
 };
 use helgobox_api::persistence::{
-    Axis, BrowseTracksMode, FxDescriptor, FxToolAction, LearnableTargetKind, MidiScriptKind,
-    MonitoringMode, MouseButton, PlaytimeColumnAction, PlaytimeColumnDescriptor,
-    PlaytimeColumnDescriptorKind, PlaytimeMatrixAction, PlaytimeRowAction, PlaytimeRowDescriptor,
-    PlaytimeRowDescriptorKind, PlaytimeSlotDescriptor, PlaytimeSlotDescriptorKind,
-    PlaytimeSlotManagementAction, PlaytimeSlotTransportAction, PotFilterKind, SeekBehavior,
-    TrackToolAction, VirtualControlElementCharacter,
+    AudioAnalysis, Axis, BrowseTracksMode, CompoundSourceKind, FeedbackPolicy, FxDescriptor,
+    FxToolAction, LearnableTargetKind, LfoWaveform, MidiScriptKind, MonitoringMode, MouseButton,
+    PlaytimeColumnAction, PlaytimeColumnDescriptor, PlaytimeColumnDescriptorKind,
+    PlaytimeMatrixAction, PlaytimeRowAction, PlaytimeRowDescriptor, PlaytimeRowDescriptorKind,
+    PlaytimeSlotDescriptor, PlaytimeSlotDescriptorKind, PlaytimeSlotManagementAction,
+    PlaytimeSlotTransportAction, PotFilterKind, SeekBehavior, SequencerDirection,
+    SysExDisplayEncoding, TrackToolAction, VirtualControlElementCharacter,
 };
 use swell_ui::{
     DeviceContext, DialogUnits, Point, SharedView, SwellStringArg, View, ViewContext, WeakView,
@@ -42,26 +43,28 @@ use swell_ui::{
 
 use crate::application::{
     format_osc_feedback_args, get_bookmark_label_by_id, get_fx_label, get_fx_param_label,
-    get_non_present_bookmark_label, get_optional_fx_label, get_route_label,
-    parse_osc_feedback_args, Affected, AutomationModeOverrideType, BookmarkAnchorType, Change,
-    CompartmentProp, ConcreteFxInstruction, ConcreteTrackInstruction, MappingChangeContext,
-    MappingCommand, MappingModel, MappingModificationKind, MappingProp, MappingRefModel,
-    MappingSnapshotTypeForLoad, MappingSnapshotTypeForTake, MidiSourceType, ModeCommand, ModeModel,
-    ModeProp, RealearnAutomationMode, RealearnTrackArea, ReaperSourceType, SessionProp,
-    SharedMapping, SharedUnitModel, SourceCategory, SourceCommand, SourceModel, SourceProp,
-    TargetCategory, TargetCommand, TargetModel, TargetModelFormatVeryShort, TargetModelWithContext,
-    TargetProp, TargetUnit, TrackRouteSelectorType, UnitModel, VirtualFxParameterType,
-    VirtualFxType, VirtualTrackType, WeakUnitModel, KEY_UNDEFINED_LABEL,
+    get_non_present_bookmark_label, get_optional_fx_label, get_route_label, parse_lfo_rate,
+    parse_osc_feedback_args, parse_sequencer_steps, Affected, AutomationModeOverrideType,
+    BookmarkAnchorType, Change, CompartmentProp, ConcreteFxInstruction, ConcreteTrackInstruction,
+    LfoRate, MappingChangeContext, MappingCommand, MappingModel, MappingModificationKind,
+    MappingProp, MappingRefModel, MappingSnapshotTypeForLoad, MappingSnapshotTypeForTake,
+    MidiSourceType, ModeCommand, ModeModel, ModeProp, RealearnAutomationMode, RealearnTrackArea,
+    ReaperSourceType, SessionProp, SharedMapping, SharedUnitModel, SourceCategory, SourceCommand,
+    SourceModel, SourceProp, TargetCategory, TargetCommand, TargetModel,
+    TargetModelFormatVeryShort, TargetModelWithContext, TargetProp, TargetUnit,
+    TrackRouteSelectorType, UnitModel, VirtualFxParameterType, VirtualFxType, VirtualTrackType,
+    WeakUnitModel, KEY_UNDEFINED_LABEL,
 };
 use crate::base::{notification, when, Prop};
 use crate::domain::ui_util::{
     format_as_percentage_without_unit, format_tags_as_csv, parse_unit_value_from_percentage,
 };
 use crate::domain::{
-    control_element_domains, AnyOnParameter, Backbone, ControlContext, Exclusivity,
-    FeedbackSendBehavior, KeyStrokePortability, MouseActionType, PortabilityIssue, ReaperTarget,
-    ReaperTargetType, SendMidiDestination, SimpleExclusivity, SourceFeedbackEvent,
-    TargetControlEvent, TouchedRouteParameterType, TrackGangBehavior, WithControlContext,
+    control_element_domains, AnyOnParameter, Backbone, ControlContext, ControlInput, Exclusivity,
+    FeedbackOutput, FeedbackSendBehavior, KeyStrokePortability, MappingIoSelection,
+    MouseActionType, PortabilityIssue, ReaperTarget, ReaperTargetType, SendMidiDestination,
+    SimpleExclusivity, SourceFeedbackEvent, TargetControlEvent, TouchedRouteParameterType,
+    TrackGangBehavior, WithControlContext,
 };
 use crate::domain::{
     get_non_present_virtual_route_label, get_non_present_virtual_track_label,
@@ -70,6 +73,8 @@ use crate::domain::{
     RealearnTarget, SoloBehavior, TargetCharacter, TouchedTrackParameterType, TrackExclusivity,
     TrackRouteType, TransportAction, VirtualControlElement, VirtualControlElementId, VirtualFx,
 };
+use crate::infrastructure::api::convert::{from_data, to_data};
+use crate::infrastructure::data::FeedbackPolicyData;
 use crate::infrastructure::plugin::BackboneShell;
 use crate::infrastructure::ui::bindings::root;
 use crate::infrastructure::ui::color_panel::{ColorPanel, ColorPanelDesc};
@@ -246,7 +251,10 @@ impl MappingPanel {
                                 P::FeedbackSendBehavior => {
                                     view.invalidate_mapping_feedback_send_behavior_combo_box();
                                 }
-                                P::GroupId => {}
+                                P::FeedbackPolicy => {
+                                    view.invalidate_mapping_advanced_settings_button();
+                                }
+                                P::GroupId | P::IoSelection => {}
                                 P::InActivationCondition(p) => match p {
                                     Multiple => {
                                         view.panel.mapping_header_panel.invalidate_controls();
@@ -314,17 +322,42 @@ impl MappingPanel {
                                                 view.invalidate_source_line_4_combo_box_2();
                                             }
                                             P::Line => {
+                                                view.invalidate_source_line_4_edit_control(initiator);
                                                 view.invalidate_source_line_5_combo_box();
                                             }
-                                            P::OscAddressPattern | P::TimerMillis => {
+                                            P::SysExDisplayEncoding => {
+                                                view.invalidate_source_line_3_combo_box_1();
+                                            }
+                                            P::SysExDisplayLength => {
+                                                view.invalidate_source_line_5_edit_control(initiator);
+                                            }
+                                            P::SysExDisplayTemplate => {
+                                                view.invalidate_source_line_7_edit_control(initiator);
+                                            }
+                                            P::SysExDisplayAlignment | P::SysExDisplayPadding | P::SysExDisplayCharacterMap => {}
+                                            P::OscAddressPattern | P::TimerMillis | P::CompoundTimeoutMillis | P::AudioThresholdDb => {
                                                 view.invalidate_source_line_3_edit_control(initiator);
                                             }
+                                            P::CompoundKind | P::CompoundMembers | P::AudioAnalysis => {
+                                                view.invalidate_source_line_2_combo_box();
+                                                view.invalidate_source_line_7_edit_control(initiator);
+                                            }
+                                            P::AudioChannel | P::AudioChannelCount | P::AudioDetector | P::AudioAttackMillis | P::AudioReleaseMillis => {
+                                                view.invalidate_source_line_7_edit_control(initiator);
+                                            }
                                             P::RawMidiPattern  => {
                                                 view.invalidate_source_line_7_edit_control(initiator);
                                             }
-                                            P::ParameterIndex => {
+                                            P::ParameterIndex | P::LfoWaveform | P::SequencerDirection => {
                                                 view.invalidate_source_line_3_combo_box_1()
                                             }
+                                            P::LfoFrequency | P::LfoBeatsPerCycle | P::SequencerSteps => {
+                                                view.invalidate_source_line_4_edit_control(initiator);
+                                            }
+                                            P::LfoPhaseOffset | P::SequencerBeatsPerStep => {
+                                                view.invalidate_source_line_5_edit_control(initiator);
+                                            }
+                                            P::LfoRetriggerParameterIndex | P::SequencerSwing | P::SequencerStepValueParameterIndex => {}
                                             P::MidiScriptKind => {
                                                 view.invalidate_source_line_3(initiator);
                                             }
@@ -1355,6 +1388,110 @@ impl MappingPanel {
         );
     }
 
+    fn edit_feedback_policy(&self) {
+        let session = self.session.clone();
+        self.edit_yaml(
+            |m| {
+                let data = FeedbackPolicyData::from_model(&m.feedback_policy());
+                let api_policy = from_data::convert_feedback_policy(data);
+                match serde_yaml::to_value(api_policy).ok()? {
+                    serde_yaml::Value::Mapping(m) if !m.is_empty() => Some(m),
+                    _ => None,
+                }
+            },
+            move |m, yaml| {
+                let api_policy: FeedbackPolicy = match yaml {
+                    None => Default::default(),
+                    Some(yaml) => serde_yaml::from_value(serde_yaml::Value::Mapping(yaml))
+                        .map_err(|e| e.to_string())?,
+                };
+                let policy = to_data::convert_feedback_policy(api_policy)
+                    .map_err(|e| e.to_string())?
+                    .to_model();
+                let session = session.upgrade().expect("session gone");
+                let result = session.borrow_mut().change_mapping_with_closure(
+                    m,
+                    None,
+                    Rc::downgrade(&session),
+                    |ctx| {
+                        Ok(ctx
+                            .mapping
+                            .change(MappingCommand::SetFeedbackPolicy(policy)))
+                    },
+                );
+                result
+            },
+        );
+    }
+
+    fn open_advanced_menu(&self) {
+        enum MenuAction {
+            EditAdvancedSettings,
+            EditFeedbackPolicy,
+            SelectControlInput(Option<ControlInput>),
+            SelectFeedbackOutput(Option<FeedbackOutput>),
+        }
+        let mapping = self.mapping();
+        let io_selection = mapping.borrow().io_selection();
+        let menu = {
+            use swell_ui::menu_tree::*;
+            let session = self.session();
+            let session = session.borrow();
+            anonymous_menu(vec![
+                item(
+                    "Edit advanced settings...",
+                    MenuAction::EditAdvancedSettings,
+                ),
+                item("Edit feedback policy...", MenuAction::EditFeedbackPolicy),
+                separator(),
+                menu(
+                    "Control input",
+                    menus::mapping_control_input_menu_entries(
+                        &session,
+                        io_selection.control_input,
+                        MenuAction::SelectControlInput,
+                    ),
+                ),
+                menu(
+                    "Feedback output",
+                    menus::mapping_feedback_output_menu_entries(
+                        &session,
+                        io_selection.feedback_output,
+                        MenuAction::SelectFeedbackOutput,
+                    ),
+                ),
+            ])
+        };
+        let menu_action = self
+            .view
+            .require_window()
+            .open_popup_menu(menu, Window::cursor_pos());
+        let new_io_selection = match menu_action {
+            None => return,
+            Some(MenuAction::EditAdvancedSettings) => return self.edit_advanced_settings(),
+            Some(MenuAction::EditFeedbackPolicy) => return self.edit_feedback_policy(),
+            Some(MenuAction::SelectControlInput(control_input)) => MappingIoSelection {
+                control_input,
+                ..io_selection
+            },
+            Some(MenuAction::SelectFeedbackOutput(feedback_output)) => MappingIoSelection {
+                feedback_output,
+                ..io_selection
+            },
+        };
+        let session = self.session();
+        let _ = session.borrow_mut().change_mapping_with_closure(
+            &mut mapping.borrow_mut(),
+            None,
+            Rc::downgrade(&session),
+            |ctx| {
+                Ok(ctx
+                    .mapping
+                    .change(MappingCommand::SetIoSelection(new_io_selection)))
+            },
+        );
+    }
+
     pub fn handle_matched_mapping(self: SharedView<Self>) {
         self.source_match_indicator_control().enable();
         self.view
@@ -1952,7 +2089,7 @@ impl<'a> MutableMappingPanel<'a> {
                     SourceCommand::SetOscArgIsRelative(checked),
                 ));
             }
-            Reaper | Virtual | Never | Keyboard => {}
+            Reaper | Virtual | Never | Keyboard | Compound | Audio => {}
         };
     }
 
@@ -1968,6 +2105,13 @@ impl<'a> MutableMappingPanel<'a> {
                         SourceCommand::SetMidiScriptKind(kind),
                     ));
                 }
+                MidiSourceType::SysExDisplay => {
+                    let i = b.selected_combo_box_item_index();
+                    let encoding = i.try_into().expect("invalid SysEx display encoding");
+                    self.change_mapping(MappingCommand::ChangeSource(
+                        SourceCommand::SetSysExDisplayEncoding(encoding),
+                    ));
+                }
                 t if t.supports_channel() => {
                     let value = match b.selected_combo_box_item_data() {
                         -1 => None,
@@ -1988,6 +2132,20 @@ impl<'a> MutableMappingPanel<'a> {
                         ),
                     ));
                 }
+                ReaperSourceType::Lfo => {
+                    let i = b.selected_combo_box_item_index();
+                    let waveform = i.try_into().expect("invalid LFO waveform");
+                    self.change_mapping(MappingCommand::ChangeSource(
+                        SourceCommand::SetLfoWaveform(waveform),
+                    ));
+                }
+                ReaperSourceType::Sequencer => {
+                    let i = b.selected_combo_box_item_index();
+                    let direction = i.try_into().expect("invalid sequencer direction");
+                    self.change_mapping(MappingCommand::ChangeSource(
+                        SourceCommand::SetSequencerDirection(direction),
+                    ));
+                }
                 _ => b.hide(),
             },
             _ => {}
@@ -2126,6 +2284,18 @@ impl<'a> MutableMappingPanel<'a> {
                     SourceCommand::SetControlElementCharacter(element_type),
                 ));
             }
+            Compound => {
+                let kind = i.try_into().expect("invalid compound source kind");
+                self.change_mapping(MappingCommand::ChangeSource(
+                    SourceCommand::SetCompoundKind(kind),
+                ));
+            }
+            Audio => {
+                let analysis = i.try_into().expect("invalid audio analysis");
+                self.change_mapping(MappingCommand::ChangeSource(
+                    SourceCommand::SetAudioAnalysis(analysis),
+                ));
+            }
             _ => {}
         };
     }
@@ -2163,15 +2333,24 @@ impl<'a> MutableMappingPanel<'a> {
         let text = c.text().unwrap_or_default();
         use SourceCategory::*;
         match self.mapping.source_model.category() {
-            Midi => {
-                let value = text.parse().ok();
-                self.change_mapping_with_initiator(
-                    MappingCommand::ChangeSource(SourceCommand::SetParameterNumberMessageNumber(
-                        value,
-                    )),
-                    Some(edit_control_id),
-                );
-            }
+            Midi => match self.mapping.source_model.midi_source_type() {
+                MidiSourceType::SysExDisplay => {
+                    let value = text.parse().ok();
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetLine(value)),
+                        Some(edit_control_id),
+                    );
+                }
+                _ => {
+                    let value = text.parse().ok();
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(
+                            SourceCommand::SetParameterNumberMessageNumber(value),
+                        ),
+                        Some(edit_control_id),
+                    );
+                }
+            },
             Virtual => {
                 let value = text.parse().unwrap_or_default();
                 self.change_mapping_with_initiator(
@@ -2179,7 +2358,40 @@ impl<'a> MutableMappingPanel<'a> {
                     Some(edit_control_id),
                 );
             }
-            Reaper | Never | Keyboard | Osc => {}
+            Reaper => match self.mapping.source_model.reaper_source_type() {
+                ReaperSourceType::Lfo => {
+                    let Some(rate) = parse_lfo_rate(&text) else {
+                        return;
+                    };
+                    let (frequency, beats_per_cycle) = match rate {
+                        LfoRate::Frequency(f) => (Some(f), None),
+                        LfoRate::BeatsPerCycle(b) => (None, Some(b)),
+                    };
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetLfoBeatsPerCycle(
+                            beats_per_cycle,
+                        )),
+                        Some(edit_control_id),
+                    );
+                    if let Some(f) = frequency {
+                        self.change_mapping_with_initiator(
+                            MappingCommand::ChangeSource(SourceCommand::SetLfoFrequency(f)),
+                            Some(edit_control_id),
+                        );
+                    }
+                }
+                ReaperSourceType::Sequencer => {
+                    let Some(steps) = parse_sequencer_steps(&text) else {
+                        return;
+                    };
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetSequencerSteps(steps)),
+                        Some(edit_control_id),
+                    );
+                }
+                _ => {}
+            },
+            Never | Keyboard | Osc | Compound | Audio => {}
         };
     }
 
@@ -2189,6 +2401,15 @@ impl<'a> MutableMappingPanel<'a> {
         let text = c.text().unwrap_or_default();
         use SourceCategory::*;
         match self.mapping.source_model.category() {
+            Midi if self.mapping.source_model.midi_source_type()
+                == MidiSourceType::SysExDisplay =>
+            {
+                let value = text.trim().parse().ok();
+                self.change_mapping_with_initiator(
+                    MappingCommand::ChangeSource(SourceCommand::SetSysExDisplayLength(value)),
+                    Some(edit_control_id),
+                );
+            }
             Osc => {
                 let v = parse_osc_arg_value_range(&text);
                 self.change_mapping_with_initiator(
@@ -2196,6 +2417,30 @@ impl<'a> MutableMappingPanel<'a> {
                     Some(edit_control_id),
                 );
             }
+            Reaper => match self.mapping.source_model.reaper_source_type() {
+                ReaperSourceType::Lfo => {
+                    let v = parse_unit_value_from_percentage(&text).unwrap_or_default();
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetLfoPhaseOffset(v.get())),
+                        Some(edit_control_id),
+                    );
+                }
+                ReaperSourceType::Sequencer => {
+                    let Ok(beats) = text.trim().parse::<f64>() else {
+                        return;
+                    };
+                    if beats <= 0.0 {
+                        return;
+                    }
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetSequencerBeatsPerStep(
+                            beats,
+                        )),
+                        Some(edit_control_id),
+                    );
+                }
+                _ => {}
+            },
             _ => {}
         };
     }
@@ -2222,6 +2467,32 @@ impl<'a> MutableMappingPanel<'a> {
                     }
                     _ => {}
                 },
+                Compound => {
+                    // Empty means no timeout
+                    let value = value.trim().parse().ok();
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetCompoundTimeoutMillis(
+                            value,
+                        )),
+                        Some(edit_control_id),
+                    )
+                }
+                Audio => {
+                    // Empty means no threshold. Invalid input keeps the current threshold.
+                    let value = value.trim();
+                    let value = if value.is_empty() {
+                        None
+                    } else {
+                        match value.parse::<f64>() {
+                            Ok(v) if v.is_finite() => Some(v),
+                            _ => return,
+                        }
+                    };
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetAudioThresholdDb(value)),
+                        Some(edit_control_id),
+                    )
+                }
                 Midi | Virtual | Never | Keyboard => {}
             }
         }
@@ -2246,6 +2517,12 @@ impl<'a> MutableMappingPanel<'a> {
                         Some(edit_control_id),
                     );
                 }
+                MidiSourceType::SysExDisplay => {
+                    self.change_mapping_with_initiator(
+                        MappingCommand::ChangeSource(SourceCommand::SetSysExDisplayTemplate(value)),
+                        Some(edit_control_id),
+                    );
+                }
                 _ => {}
             },
             Osc => {
@@ -3689,7 +3966,7 @@ impl<'a> MutableMappingPanel<'a> {
                             Some(edit_control_id),
                         );
                     }
-                    VirtualFxType::ByIndex => {
+                    VirtualFxType::ByIndex | VirtualFxType::ByIndexInBank => {
                         let index = parse_position_as_index(control);
                         self.change_mapping_with_initiator(
                             MappingCommand::ChangeTarget(TargetCommand::SetFxIndex(index)),
@@ -3726,7 +4003,7 @@ impl<'a> MutableMappingPanel<'a> {
                             Some(edit_control_id),
                         );
                     }
-                    VirtualFxParameterType::ByIndex => {
+                    VirtualFxParameterType::ByIndex | VirtualFxParameterType::ByIndexInBank => {
                         let index = parse_position_as_index(control);
                         self.change_mapping_with_initiator(
                             MappingCommand::ChangeTarget(TargetCommand::SetParamIndex(index)),
@@ -4069,8 +4346,24 @@ impl<'a> ImmutableMappingPanel<'a> {
 
     fn invalidate_mapping_advanced_settings_button(&self) {
         let cb = self.view.require_control(root::ID_MAPPING_ADVANCED_BUTTON);
-        let suffix = if let Some(m) = self.mapping.advanced_settings() {
-            format!(" ({})", m.len())
+        let policy = self.mapping.feedback_policy();
+        let policy_count = [
+            policy.max_rate.is_some(),
+            policy.min_delta.is_some(),
+            policy.debounce.is_some(),
+            policy.settle_delay.is_some(),
+        ]
+        .into_iter()
+        .filter(|is_set| *is_set)
+        .count();
+        let count = self
+            .mapping
+            .advanced_settings()
+            .map(|m| m.len())
+            .unwrap_or(0)
+            + policy_count;
+        let suffix = if count > 0 {
+            format!(" ({count})")
         } else {
             "".to_owned()
         };
@@ -4147,6 +4440,8 @@ impl<'a> ImmutableMappingPanel<'a> {
             Midi => self.source.midi_source_type().into(),
             Reaper => self.source.reaper_source_type().into(),
             Virtual => self.source.control_element_character().into(),
+            Compound => self.source.compound_kind().into(),
+            Audio => self.source.audio_analysis().into(),
             _ => return,
         };
         let b = self.view.require_control(root::ID_SOURCE_TYPE_COMBO_BOX);
@@ -4187,6 +4482,7 @@ impl<'a> ImmutableMappingPanel<'a> {
         let text = match self.source.category() {
             Midi => match self.source.midi_source_type() {
                 MidiSourceType::Script => Some("Kind"),
+                MidiSourceType::SysExDisplay => Some("Encoding"),
                 t if t.supports_channel() => Some("Channel"),
                 _ => None,
             },
@@ -4194,9 +4490,13 @@ impl<'a> ImmutableMappingPanel<'a> {
             Reaper => match self.source.reaper_source_type() {
                 ReaperSourceType::Timer => Some("Millis"),
                 ReaperSourceType::RealearnParameter => Some("Param"),
+                ReaperSourceType::Lfo => Some("Wave"),
+                ReaperSourceType::Sequencer => Some("Direction"),
                 _ => None,
             },
             Keyboard => Some("Key"),
+            Compound => Some("Timeout"),
+            Audio => Some("Threshold"),
             _ => None,
         };
         self.view
@@ -4229,6 +4529,11 @@ impl<'a> ImmutableMappingPanel<'a> {
                     b.show();
                     b.select_combo_box_item_by_index(self.source.midi_script_kind().into());
                 }
+                MidiSourceType::SysExDisplay => {
+                    b.fill_combo_box_indexed(SysExDisplayEncoding::iter());
+                    b.show();
+                    b.select_combo_box_item_by_index(self.source.sysex_display_encoding().into());
+                }
                 t if t.supports_channel() => {
                     b.fill_combo_box_with_data_small(
                         iter::once((-1isize, "<Any> (no feedback)".to_string()))
@@ -4256,6 +4561,16 @@ impl<'a> ImmutableMappingPanel<'a> {
                     b.show();
                     b.select_combo_box_item_by_index(self.source.parameter_index().get() as usize);
                 }
+                ReaperSourceType::Lfo => {
+                    b.fill_combo_box_indexed(LfoWaveform::iter());
+                    b.show();
+                    b.select_combo_box_item_by_index(self.source.lfo_waveform().into());
+                }
+                ReaperSourceType::Sequencer => {
+                    b.fill_combo_box_indexed(SequencerDirection::iter());
+                    b.show();
+                    b.select_combo_box_item_by_index(self.source.sequencer_direction().into());
+                }
                 _ => b.hide(),
             },
             _ => {
@@ -4341,6 +4656,7 @@ impl<'a> ImmutableMappingPanel<'a> {
                             Some("Display")
                         }
                     }
+                    SysExDisplay => Some("Line"),
                     t if t.supports_midi_message_number()
                         || t.supports_parameter_number_message_number() =>
                     {
@@ -4351,6 +4667,11 @@ impl<'a> ImmutableMappingPanel<'a> {
             }
             Virtual => Some("ID"),
             Osc => Some("Argument"),
+            Reaper => match self.source.reaper_source_type() {
+                ReaperSourceType::Lfo => Some("Rate"),
+                ReaperSourceType::Sequencer => Some("Steps"),
+                _ => None,
+            },
             _ => None,
         };
         self.view
@@ -4451,9 +4772,17 @@ impl<'a> ImmutableMappingPanel<'a> {
                         Some(n) => Some(n.to_string()),
                     }
                 }
+                MidiSourceType::SysExDisplay => {
+                    Some(self.source.line().unwrap_or_default().to_string())
+                }
                 _ => None,
             },
             Virtual => Some(self.source.control_element_id().to_string()),
+            Reaper => match self.source.reaper_source_type() {
+                ReaperSourceType::Lfo => Some(self.source.format_lfo_rate()),
+                ReaperSourceType::Sequencer => Some(self.source.format_sequencer_steps()),
+                _ => None,
+            },
             _ => None,
         };
         self.view
@@ -4491,9 +4820,12 @@ impl<'a> ImmutableMappingPanel<'a> {
             Midi => match self.source.midi_source_type() {
                 MidiSourceType::Raw => Some("Pattern"),
                 MidiSourceType::Script => Some("Script"),
+                MidiSourceType::SysExDisplay => Some("Template"),
                 _ => None,
             },
             Osc => Some("Feedback arguments"),
+            Compound => Some("Members"),
+            Audio => Some("Settings"),
             _ => None,
         };
         self.view
@@ -4514,6 +4846,22 @@ impl<'a> ImmutableMappingPanel<'a> {
                 ReaperSourceType::Timer => Some((self.source.timer_millis().to_string(), true)),
                 _ => None,
             },
+            Compound => {
+                let text = self
+                    .source
+                    .compound_timeout_millis()
+                    .map(|millis| millis.to_string())
+                    .unwrap_or_default();
+                Some((text, true))
+            }
+            Audio => {
+                let text = self
+                    .source
+                    .audio_threshold_db()
+                    .map(|db| db.to_string())
+                    .unwrap_or_default();
+                Some((text, true))
+            }
             Keyboard => {
                 let text = self
                     .source
@@ -4569,12 +4917,19 @@ impl<'a> ImmutableMappingPanel<'a> {
                         has_multiple_lines(text),
                     )
                 }
+                MidiSourceType::SysExDisplay => {
+                    (Some(self.source.sysex_display_template().to_owned()), false)
+                }
                 _ => (None, false),
             },
             Osc => {
                 let text = format_osc_feedback_args(self.source.osc_feedback_args());
                 (Some(text), false)
             }
+            // Members can only be defined via import (Lua or JSON) for now
+            Compound => (Some(self.source.format_compound_members()), true),
+            // Detailed settings can only be defined via import (Lua or JSON) for now
+            Audio => (Some(self.source.format_audio_settings()), true),
             _ => (None, false),
         };
         c.set_text_or_hide(value_text);
@@ -4600,11 +4955,17 @@ impl<'a> ImmutableMappingPanel<'a> {
                             None
                         }
                     }
+                    SysExDisplay => Some("Length"),
                     t if t.supports_custom_character() => Some("Character"),
                     _ => None,
                 }
             }
             Osc if self.source.supports_osc_arg_value_range() => Some("Range"),
+            Reaper => match self.source.reaper_source_type() {
+                ReaperSourceType::Lfo => Some("Phase"),
+                ReaperSourceType::Sequencer => Some("Beats"),
+                _ => None,
+            },
             _ => None,
         };
         self.view
@@ -4663,6 +5024,12 @@ impl<'a> ImmutableMappingPanel<'a> {
         }
         use SourceCategory::*;
         let text = match self.source.category() {
+            Midi if self.source.midi_source_type() == MidiSourceType::SysExDisplay => Some(
+                self.source
+                    .sysex_display_length()
+                    .map(|l| l.to_string())
+                    .unwrap_or_default(),
+            ),
             Osc if self.source.supports_osc_arg_value_range() => {
                 let text = format_osc_arg_value_range(
                     self.source.osc_arg_value_range(),
@@ -4670,6 +5037,16 @@ impl<'a> ImmutableMappingPanel<'a> {
                 );
                 Some(text)
             }
+            Reaper => match self.source.reaper_source_type() {
+                ReaperSourceType::Lfo => {
+                    let phase_offset = UnitValue::new_clamped(self.source.lfo_phase_offset());
+                    Some(format_as_percentage_without_unit(phase_offset))
+                }
+                ReaperSourceType::Sequencer => {
+                    Some(self.source.sequencer_beats_per_step().to_string())
+                }
+                _ => None,
+            },
             _ => None,
         };
         self.view
@@ -5302,7 +5679,7 @@ impl<'a> ImmutableMappingPanel<'a> {
                         Some(self.target.param_expression().to_owned())
                     }
                     VirtualFxParameterType::ByName => Some(self.target.param_name().to_owned()),
-                    VirtualFxParameterType::ByIndex => {
+                    VirtualFxParameterType::ByIndex | VirtualFxParameterType::ByIndexInBank => {
                         let index = self.target.param_index();
                         Some((index + 1).to_string())
                     }
@@ -5394,7 +5771,7 @@ impl<'a> ImmutableMappingPanel<'a> {
                 t if t.supports_fx() => {
                     let text = match self.target.fx_type() {
                         VirtualFxType::Dynamic => Some(self.target.fx_expression().to_owned()),
-                        VirtualFxType::ByIndex => {
+                        VirtualFxType::ByIndex | VirtualFxType::ByIndexInBank => {
                             let index = self.target.fx_index();
                             Some((index + 1).to_string())
                         }
@@ -6950,6 +7327,8 @@ impl<'a> ImmutableMappingPanel<'a> {
             Midi => b.fill_combo_box_indexed(MidiSourceType::iter()),
             Reaper => b.fill_combo_box_indexed(ReaperSourceType::iter()),
             Virtual => b.fill_combo_box_indexed(VirtualControlElementCharacter::iter()),
+            Compound => b.fill_combo_box_indexed(CompoundSourceKind::iter()),
+            Audio => b.fill_combo_box_indexed(AudioAnalysis::iter()),
             Osc | Never | Keyboard => {}
         };
     }
@@ -7100,7 +7479,7 @@ impl View for MappingPanel {
                 self.write(|p| p.update_mapping_is_visible_in_projection());
             }
             root::ID_MAPPING_ADVANCED_BUTTON => {
-                self.edit_advanced_settings();
+                self.open_advanced_menu();
             }
             root::ID_MAPPING_FIND_IN_LIST_BUTTON => {
                 self.force_scroll_to_mapping_in_main_panel();
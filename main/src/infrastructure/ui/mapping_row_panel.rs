@@ -8,7 +8,9 @@ use crate::domain::{CompartmentKind, GroupId, GroupKey, MappingId, QualifiedMapp
 
 use crate::domain::ui_util::format_tags_as_csv;
 use crate::infrastructure::api::convert::from_data::ConversionStyle;
+use crate::infrastructure::api::convert::to_data::ApiToDataConversionContext;
 use crate::infrastructure::data::{
+    ensure_template_instances_fit_compartment, instantiate_mapping_templates,
     ActivationConditionData, MappingModelData, ModeModelData, SourceModelData, TargetModelData,
 };
 use crate::infrastructure::plugin::BackboneShell;
@@ -24,11 +26,12 @@ use crate::infrastructure::ui::util::{
 };
 use crate::infrastructure::ui::{
     copy_text_to_clipboard, deserialize_api_object_from_lua, deserialize_data_object_from_json,
-    get_text_from_clipboard, serialize_data_object, DataObject, IndependentPanelManager,
-    SerializationFormat, SharedMainState,
+    deserialize_mapping_template_from_lua, get_text_from_clipboard, serialize_data_object,
+    serialize_mapping_template_to_lua, DataObject, IndependentPanelManager, SerializationFormat,
+    SharedMainState,
 };
 use core::iter;
-use helgobox_api::persistence::{ApiObject, Envelope};
+use helgobox_api::persistence::{ApiObject, Envelope, MappingTemplate};
 use reaper_medium::Hbrush;
 use rxrust::prelude::*;
 use std::cell::{Ref, RefCell};
@@ -223,6 +226,12 @@ impl MappingRowPanel {
             }
             right_label += &group_label;
         };
+        if let Some(template_id) = mapping.template_id() {
+            if !right_label.is_empty() {
+                right_label += " | ";
+            }
+            right_label += &format!("Template {template_id}");
+        }
         self.view
             .require_window()
             .require_control(root::ID_MAPPING_ROW_GROUP_LABEL)
@@ -395,20 +404,30 @@ impl MappingRowPanel {
 
     fn invalidate_button_enabled_states(&self) {
         let enabled = !self.mappings_are_read_only();
+        // Template instances are materialized from their template when loading, so changing them
+        // directly would have no lasting effect. Duplicating them is fine, the duplicate is a
+        // normal mapping.
+        let is_template_instance = self
+            .optional_mapping()
+            .is_some_and(|m| m.borrow().is_template_instance());
         let buttons = [
-            root::ID_UP_BUTTON,
-            root::ID_DOWN_BUTTON,
-            root::ID_MAPPING_ROW_CONTROL_CHECK_BOX,
-            root::ID_MAPPING_ROW_FEEDBACK_CHECK_BOX,
-            root::ID_MAPPING_ROW_EDIT_BUTTON,
-            root::ID_MAPPING_ROW_DUPLICATE_BUTTON,
-            root::ID_MAPPING_ROW_REMOVE_BUTTON,
-            root::ID_MAPPING_ROW_LEARN_SOURCE_BUTTON,
-            root::ID_MAPPING_ROW_LEARN_TARGET_BUTTON,
+            (root::ID_UP_BUTTON, true),
+            (root::ID_DOWN_BUTTON, true),
+            (root::ID_MAPPING_ROW_CONTROL_CHECK_BOX, true),
+            (root::ID_MAPPING_ROW_FEEDBACK_CHECK_BOX, true),
+            (root::ID_MAPPING_ROW_EDIT_BUTTON, true),
+            (root::ID_MAPPING_ROW_DUPLICATE_BUTTON, false),
+            (root::ID_MAPPING_ROW_REMOVE_BUTTON, true),
+            (root::ID_MAPPING_ROW_LEARN_SOURCE_BUTTON, true),
+            (root::ID_MAPPING_ROW_LEARN_TARGET_BUTTON, true),
         ];
-        for b in buttons.iter() {
-            self.view.require_control(*b).set_enabled(enabled);
+        for (b, modifies_mapping) in buttons {
+            let enabled = enabled && !(modifies_mapping && is_template_instance);
+            self.view.require_control(b).set_enabled(enabled);
         }
+        self.view
+            .require_control(root::IDC_MAPPING_ROW_ENABLED_CHECK_BOX)
+            .set_enabled(!is_template_instance);
     }
 
     fn register_listeners(self: &SharedView<Self>) {
@@ -588,6 +607,30 @@ impl MappingRowPanel {
         Ok(())
     }
 
+    fn copy_mapping_template_as_lua(&self) -> Result<(), Box<dyn Error>> {
+        let text = {
+            let mapping = self.require_mapping();
+            let mapping = mapping.borrow();
+            let template_id = mapping
+                .template_id()
+                .ok_or("This mapping was not created from a template.")?;
+            let session = self.session();
+            let session = session.borrow();
+            let template = session
+                .find_mapping_template(mapping.compartment(), template_id)
+                .ok_or("mapping template not found")?;
+            serialize_mapping_template_to_lua(template)?
+        };
+        copy_text_to_clipboard(text);
+        Ok(())
+    }
+
+    fn paste_mapping_template_from_lua(&self, text: &str) -> Result<(), Box<dyn Error>> {
+        let active_compartment = self.active_compartment();
+        let template = deserialize_mapping_template_from_lua(text, active_compartment)?;
+        upsert_mapping_template(template, self.session(), active_compartment)
+    }
+
     fn paste_from_lua_insert_below(&self, text: &str) -> Result<(), Box<dyn Error>> {
         let active_compartment = self.active_compartment();
         let api_object = deserialize_api_object_from_lua(text, active_compartment)?;
@@ -632,6 +675,8 @@ impl MappingRowPanel {
             CopyMappingAsLua(ConversionStyle),
             PasteFromLuaReplace(String),
             PasteFromLuaInsertBelow(String),
+            CopyMappingTemplateAsLua,
+            PasteMappingTemplateFromLua(String),
             LogDebugInfo,
         }
         impl Default for MenuAction {
@@ -654,6 +699,8 @@ impl MappingRowPanel {
             let clipboard_could_contain_lua =
                 text_from_clipboard.is_some() && data_object_from_clipboard.is_none();
             let text_from_clipboard_clone = text_from_clipboard.clone();
+            let text_from_clipboard_for_template = text_from_clipboard.clone();
+            let is_template_instance = mapping.is_template_instance();
             let data_object_from_clipboard_clone = data_object_from_clipboard.clone();
             let group_id = mapping.group_id();
             let entries = vec![
@@ -684,10 +731,11 @@ impl MappingRowPanel {
                         }
                         _ => None,
                     };
-                    if let Some((label, obj)) = desc {
-                        item(label, MenuAction::PasteObjectInPlace(obj))
-                    } else {
-                        disabled_item("Paste (replace)")
+                    match desc {
+                        Some((label, obj)) if !is_template_instance => {
+                            item(label, MenuAction::PasteObjectInPlace(obj))
+                        }
+                        _ => disabled_item("Paste (replace)"),
                     }
                 },
                 {
@@ -732,7 +780,7 @@ impl MappingRowPanel {
                             item_with_opts(
                                 g.to_string(),
                                 ItemOpts {
-                                    enabled: group_id != g_id,
+                                    enabled: group_id != g_id && !is_template_instance,
                                     checked: false,
                                 },
                                 MenuAction::MoveMappingToGroup(Some(g_id)),
@@ -754,7 +802,7 @@ impl MappingRowPanel {
                         item_with_opts(
                             "Paste from Lua (replace)",
                             ItemOpts {
-                                enabled: clipboard_could_contain_lua,
+                                enabled: clipboard_could_contain_lua && !is_template_instance,
                                 checked: false,
                             },
                             MenuAction::PasteFromLuaReplace(
@@ -771,6 +819,24 @@ impl MappingRowPanel {
                                 text_from_clipboard_clone.unwrap_or_default(),
                             ),
                         ),
+                        item_with_opts(
+                            "Copy mapping template as Lua",
+                            ItemOpts {
+                                enabled: is_template_instance,
+                                checked: false,
+                            },
+                            MenuAction::CopyMappingTemplateAsLua,
+                        ),
+                        item_with_opts(
+                            "Paste mapping template from Lua",
+                            ItemOpts {
+                                enabled: clipboard_could_contain_lua,
+                                checked: false,
+                            },
+                            MenuAction::PasteMappingTemplateFromLua(
+                                text_from_clipboard_for_template.unwrap_or_default(),
+                            ),
+                        ),
                         item("Log debug info (now)", MenuAction::LogDebugInfo),
                     ],
                 ),
@@ -814,6 +880,12 @@ impl MappingRowPanel {
                 )
                 .unwrap();
             }
+            MenuAction::CopyMappingTemplateAsLua => {
+                self.notify_user_on_error(self.copy_mapping_template_as_lua());
+            }
+            MenuAction::PasteMappingTemplateFromLua(text) => {
+                self.notify_user_on_error(self.paste_mapping_template_from_lua(&text));
+            }
             MenuAction::CopyMappingAsLua(style) => {
                 copy_mapping_object(
                     self.session(),
@@ -1140,6 +1212,40 @@ pub fn paste_mappings(
     Ok(())
 }
 
+/// Replaces the mapping template with the same ID (or adds it) and materializes it again.
+pub fn upsert_mapping_template(
+    template: MappingTemplate,
+    session: SharedUnitModel,
+    compartment: CompartmentKind,
+) -> Result<(), Box<dyn Error>> {
+    let mut session = session.borrow_mut();
+    let instances: Vec<_> = {
+        let compartment_in_session = session.compartment_in_session(compartment);
+        let instance_datas =
+            instantiate_mapping_templates(std::slice::from_ref(&template), compartment, |key| {
+                compartment_in_session.param_index_by_key(key)
+            })?;
+        ensure_template_instances_fit_compartment(
+            &session.extract_compartment_model(compartment),
+            &template.id,
+            &instance_datas,
+        )?;
+        instance_datas
+            .into_iter()
+            .map(|instance| {
+                instance.mapping.to_model(
+                    compartment,
+                    &compartment_in_session,
+                    Some(session.extended_context()),
+                    None,
+                )
+            })
+            .collect::<Result<_, _>>()?
+    };
+    session.upsert_mapping_template(compartment, template, instances.into_iter());
+    Ok(())
+}
+
 const SOURCE_MATCH_INDICATOR_TIMER_ID: usize = 571;
 
 struct MappingTriple {
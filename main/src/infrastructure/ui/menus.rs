@@ -1,8 +1,8 @@
 use crate::application::{UnitModel, WeakUnitModel};
 use crate::domain::{
-    compartment_param_index_iter, CompartmentKind, CompartmentParamIndex, CompartmentParams,
-    ControlInput, FeedbackOutput, MappingId, MidiControlInput, MidiDestination, OscDeviceId,
-    ReaperTargetType, TargetSection,
+    compartment_param_index_iter, AdditionalIo, CompartmentKind, CompartmentParamIndex,
+    CompartmentParams, ControlInput, DeviceControlInput, DeviceFeedbackOutput, FeedbackOutput,
+    MappingId, MidiControlInput, MidiDestination, OscDeviceId, ReaperTargetType, TargetSection,
 };
 use crate::infrastructure::data::{CommonPresetInfo, OscDevice};
 use crate::infrastructure::plugin::{ActionSection, BackboneShell, ACTION_DEFS};
@@ -84,6 +84,14 @@ pub fn control_input_menu(current_value: ControlInput) -> Menu<ControlInputMenuA
             checked: current_value == ControlInput::Keyboard,
         },
         ControlInputMenuAction::SelectControlInput(ControlInput::Keyboard),
+    )))
+    .chain(iter::once(item_with_opts(
+        CONTROL_INPUT_WEB_SOCKET_LABEL,
+        ItemOpts {
+            enabled: true,
+            checked: current_value == ControlInput::WebSocket,
+        },
+        ControlInputMenuAction::SelectControlInput(ControlInput::WebSocket),
     )));
     anonymous_menu(entries.collect())
 }
@@ -154,13 +162,155 @@ pub fn feedback_output_menu(
         "Manage OSC devices",
         osc_device_management_menu_entries(FeedbackOutputMenuAction::ManageOsc),
     )))
-    .chain(iter::once(separator()));
+    .chain(iter::once(separator()))
+    .chain(iter::once(item_with_opts(
+        FEEDBACK_OUTPUT_WEB_SOCKET_LABEL,
+        ItemOpts {
+            enabled: true,
+            checked: current_value == Some(FeedbackOutput::WebSocket),
+        },
+        FeedbackOutputMenuAction::SelectFeedbackOutput(Some(FeedbackOutput::WebSocket)),
+    )));
     anonymous_menu(entries.collect())
 }
 
+/// Menu entries for toggling MIDI and OSC devices as additional control inputs.
+pub fn additional_control_input_menu_entries<T>(
+    additional_io: &AdditionalIo,
+    create_action: impl Fn(DeviceControlInput) -> T,
+) -> Vec<Entry<T>> {
+    let build_item = |label: String, input: DeviceControlInput| {
+        item_with_opts(
+            label,
+            ItemOpts {
+                enabled: true,
+                checked: additional_io.control_inputs.contains(&input),
+            },
+            create_action(input),
+        )
+    };
+    let osc_device_manager = BackboneShell::get().osc_device_manager();
+    let osc_device_manager = osc_device_manager.borrow();
+    Reaper::get()
+        .midi_input_devices()
+        .filter(|d| d.is_available())
+        .map(|dev| {
+            build_item(
+                get_midi_input_device_list_label(dev),
+                DeviceControlInput::Midi(dev.id()),
+            )
+        })
+        .chain(osc_device_manager.devices().map(|dev| {
+            build_item(
+                get_osc_device_list_label(dev, false),
+                DeviceControlInput::Osc(*dev.id()),
+            )
+        }))
+        .collect()
+}
+
+/// Menu entries for toggling MIDI and OSC devices as additional feedback outputs.
+pub fn additional_feedback_output_menu_entries<T>(
+    additional_io: &AdditionalIo,
+    create_action: impl Fn(DeviceFeedbackOutput) -> T,
+) -> Vec<Entry<T>> {
+    let build_item = |label: String, output: DeviceFeedbackOutput| {
+        item_with_opts(
+            label,
+            ItemOpts {
+                enabled: true,
+                checked: additional_io.feedback_outputs.contains(&output),
+            },
+            create_action(output),
+        )
+    };
+    let osc_device_manager = BackboneShell::get().osc_device_manager();
+    let osc_device_manager = osc_device_manager.borrow();
+    Reaper::get()
+        .midi_output_devices()
+        .filter(|d| d.is_available())
+        .map(|dev| {
+            build_item(
+                get_midi_output_device_list_label(dev),
+                DeviceFeedbackOutput::Midi(dev.id()),
+            )
+        })
+        .chain(osc_device_manager.devices().map(|dev| {
+            build_item(
+                get_osc_device_list_label(dev, true),
+                DeviceFeedbackOutput::Osc(*dev.id()),
+            )
+        }))
+        .collect()
+}
+
+/// Menu entries for restricting a mapping to one of the control inputs of its unit.
+pub fn mapping_control_input_menu_entries<T>(
+    unit: &UnitModel,
+    current_value: Option<ControlInput>,
+    create_action: impl Fn(Option<ControlInput>) -> T,
+) -> Vec<Entry<T>> {
+    let build_item = |label: String, input: Option<ControlInput>| {
+        item_with_opts(
+            label,
+            ItemOpts {
+                enabled: true,
+                checked: current_value == input,
+            },
+            create_action(input),
+        )
+    };
+    iter::once(build_item(
+        MAPPING_CONTROL_INPUT_ALL_LABEL.to_string(),
+        None,
+    ))
+    .chain(iter::once(separator()))
+    .chain(
+        unit.additional_io
+            .get_ref()
+            .control_inputs(unit.control_input())
+            .map(|input| build_item(get_control_input_label(input), Some(input))),
+    )
+    .collect()
+}
+
+/// Menu entries for restricting a mapping to one of the feedback outputs of its unit.
+pub fn mapping_feedback_output_menu_entries<T>(
+    unit: &UnitModel,
+    current_value: Option<FeedbackOutput>,
+    create_action: impl Fn(Option<FeedbackOutput>) -> T,
+) -> Vec<Entry<T>> {
+    let build_item = |label: String, output: Option<FeedbackOutput>| {
+        item_with_opts(
+            label,
+            ItemOpts {
+                enabled: true,
+                checked: current_value == output,
+            },
+            create_action(output),
+        )
+    };
+    iter::once(build_item(
+        MAPPING_FEEDBACK_OUTPUT_ALL_LABEL.to_string(),
+        None,
+    ))
+    .chain(iter::once(separator()))
+    .chain(
+        unit.additional_io
+            .get_ref()
+            .feedback_outputs(unit.feedback_output())
+            .map(|output| build_item(get_feedback_output_label(Some(output)), Some(output))),
+    )
+    .collect()
+}
+
+pub const MAPPING_CONTROL_INPUT_ALL_LABEL: &str = "<All control inputs of the unit>";
+pub const MAPPING_FEEDBACK_OUTPUT_ALL_LABEL: &str = "<All feedback outputs of the unit>";
 pub const CONTROL_INPUT_MIDI_FX_INPUT_LABEL: &str = "MIDI: <FX input>";
 pub const CONTROL_INPUT_KEYBOARD_LABEL: &str = "Computer keyboard";
+pub const CONTROL_INPUT_WEB_SOCKET_LABEL: &str = "WebSocket clients";
 pub const FEEDBACK_OUTPUT_MIDI_FX_OUTPUT: &str = "MIDI: <FX output>";
+pub const FEEDBACK_OUTPUT_WEB_SOCKET_LABEL: &str = "WebSocket clients";
 pub const FEEDBACK_OUTPUT_NONE_LABEL: &str = "<None>";
 
 fn build_midi_input_dev_menu_item(
@@ -223,6 +373,46 @@ fn build_osc_output_dev_menu_item(
     )
 }
 
+pub fn get_control_input_label(input: ControlInput) -> String {
+    match input {
+        ControlInput::Midi(midi_control_input) => match midi_control_input {
+            MidiControlInput::FxInput => CONTROL_INPUT_MIDI_FX_INPUT_LABEL.to_string(),
+            MidiControlInput::Device(dev_id) => {
+                let dev = Reaper::get().midi_input_device_by_id(dev_id);
+                get_midi_input_device_list_label(dev)
+            }
+        },
+        ControlInput::Osc(osc_device_id) => get_osc_dev_list_label(&osc_device_id, false),
+        ControlInput::Keyboard => CONTROL_INPUT_KEYBOARD_LABEL.to_string(),
+        ControlInput::WebSocket => CONTROL_INPUT_WEB_SOCKET_LABEL.to_string(),
+    }
+}
+
+pub fn get_feedback_output_label(output: Option<FeedbackOutput>) -> String {
+    match output {
+        None => FEEDBACK_OUTPUT_NONE_LABEL.to_string(),
+        Some(FeedbackOutput::Midi(midi_dest)) => match midi_dest {
+            MidiDestination::FxOutput => FEEDBACK_OUTPUT_MIDI_FX_OUTPUT.to_string(),
+            MidiDestination::Device(dev_id) => {
+                let dev = Reaper::get().midi_output_device_by_id(dev_id);
+                get_midi_output_device_list_label(dev)
+            }
+        },
+        Some(FeedbackOutput::Osc(osc_device_id)) => get_osc_dev_list_label(&osc_device_id, true),
+        Some(FeedbackOutput::WebSocket) => FEEDBACK_OUTPUT_WEB_SOCKET_LABEL.to_string(),
+    }
+}
+
+fn get_osc_dev_list_label(osc_device_id: &OscDeviceId, is_output: bool) -> String {
+    let dev_manager = BackboneShell::get().osc_device_manager();
+    let dev_manager = dev_manager.borrow();
+    if let Some(dev) = dev_manager.find_device_by_id(osc_device_id) {
+        get_osc_device_list_label(dev, is_output)
+    } else {
+        format!("OSC: <Not present> ({osc_device_id})")
+    }
+}
+
 pub fn get_osc_device_list_label(dev: &OscDevice, is_output: bool) -> String {
     format!("OSC: {}", dev.get_list_label(is_output))
 }
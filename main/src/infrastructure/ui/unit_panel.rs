@@ -12,16 +12,20 @@ use crate::application::{
     get_virtual_fx_label, get_virtual_track_label, Affected, CompartmentProp, SessionCommand,
     SessionProp, SessionUi, UnitModel, VirtualFxType, WeakUnitModel,
 };
-use crate::base::when;
+use crate::base::{notification, when};
 use crate::domain::ui_util::format_tags_as_csv;
 use crate::domain::{
-    CompartmentKind, InstanceId, InternalInfoEvent, MappingId, MappingMatchedEvent,
-    ProjectionFeedbackValue, QualifiedMappingId, SourceFeedbackEvent, TargetControlEvent,
-    TargetValueChangedEvent,
+    CompartmentKind, ControlInputRecording, InstanceId, InternalInfoEvent, MappingId,
+    MappingMatchedEvent, ProjectionFeedbackValue, QualifiedMappingId, SourceFeedbackEvent,
+    TargetControlEvent, TargetValueChangedEvent, WebSocketFeedbackValue,
 };
+use crate::infrastructure::data::save_control_input_recording;
 use crate::infrastructure::plugin::{update_auto_units_async, BackboneShell};
 use crate::infrastructure::server::http::{
-    send_projection_feedback_to_subscribed_clients, send_updated_controller_routing,
+    send_control_log_entry_to_subscribed_clients, send_feedback_log_entry_to_subscribed_clients,
+    send_projection_feedback_to_subscribed_clients, send_target_value_to_subscribed_clients,
+    send_updated_controller_routing, send_updated_parameters,
+    send_web_socket_feedback_to_subscribed_clients,
 };
 use crate::infrastructure::ui::instance_panel::InstancePanel;
 use crate::infrastructure::ui::util::{header_panel_height, parse_tags_from_csv};
@@ -276,6 +280,14 @@ impl UnitPanel {
     }
 
     fn handle_changed_target_value(&self, event: TargetValueChangedEvent) {
+        if let Some(session) = self.session.upgrade() {
+            if let Ok(session) = session.try_borrow() {
+                BackboneShell::get()
+                    .proto_hub()
+                    .notify_target_value_changed(&session, &event);
+                let _ = send_target_value_to_subscribed_clients(&session, &event);
+            }
+        }
         self.panel_manager
             .borrow()
             .handle_changed_target_value(event);
@@ -289,12 +301,22 @@ impl UnitPanel {
     }
 
     fn handle_target_control_event(&self, event: TargetControlEvent) {
+        if let Some(session) = self.session.upgrade() {
+            if let Ok(session) = session.try_borrow() {
+                let _ = send_control_log_entry_to_subscribed_clients(&session, &event);
+            }
+        }
         self.panel_manager
             .borrow()
             .handle_target_control_event(event);
     }
 
     fn handle_source_feedback_event(&self, event: SourceFeedbackEvent) {
+        if let Some(session) = self.session.upgrade() {
+            if let Ok(session) = session.try_borrow() {
+                let _ = send_feedback_log_entry_to_subscribed_clients(&session, &event);
+            }
+        }
         self.panel_manager
             .borrow()
             .handle_source_feedback_event(event);
@@ -367,6 +389,7 @@ impl UnitPanel {
     }
 
     fn handle_changed_parameters(&self, session: &UnitModel) {
+        let _ = send_updated_parameters(session);
         self.panel_manager
             .borrow()
             .handle_changed_parameters(session);
@@ -542,6 +565,10 @@ impl SessionUi for Weak<UnitPanel> {
         let _ = send_projection_feedback_to_subscribed_clients(session.unit_key(), value);
     }
 
+    fn send_web_socket_feedback(&self, session: &UnitModel, value: WebSocketFeedbackValue) {
+        let _ = send_web_socket_feedback_to_subscribed_clients(session.unit_key(), value);
+    }
+
     fn mapping_matched(&self, event: MappingMatchedEvent) {
         upgrade_panel(self).handle_matched_mapping(event);
     }
@@ -595,6 +622,17 @@ impl SessionUi for Weak<UnitPanel> {
     fn handle_global_control_and_feedback_state_changed(&self) {
         update_auto_units_async();
     }
+
+    fn control_input_recorded(&self, session: &UnitModel, recording: ControlInputRecording) {
+        let event_count = recording.events.len();
+        match save_control_input_recording(session.unit_key(), &recording) {
+            Ok(path) => notification::notify_processing_result(
+                "Control input recording",
+                vec![format!("Saved {event_count} recorded events to {path}")],
+            ),
+            Err(e) => notification::warn_user_about_anyhow_error(e),
+        }
+    }
 }
 
 fn upgrade_panel(panel: &Weak<UnitPanel>) -> Rc<UnitPanel> {